@@ -0,0 +1,76 @@
+use blockchain::{fixtures, Chain, WalletSort};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Build a 200 blocks blockchain with 50 payments per block, and report its index memory.
+fn setup() -> Chain {
+    let chain = fixtures::build_chain(42, 200, 100, 50);
+    let usage = chain.memory_usage();
+    let transactions: usize = chain
+        .get_all_blocks()
+        .iter()
+        .map(|block| block.transactions.len())
+        .sum();
+    let representation = match cfg!(feature = "compact-index") {
+        true => "compact",
+        false => "string",
+    };
+
+    println!(
+        "{} transaction index: {} bytes for {} transactions ({:.1} bytes each), \
+         wallet histories: {} bytes",
+        representation,
+        usage.transaction_index_bytes,
+        transactions,
+        usage.transaction_index_bytes as f64 / transactions as f64,
+        usage.index_bytes,
+    );
+
+    chain
+}
+
+/// Measure the lookup of mined transactions through the transaction index.
+fn lookup(c: &mut Criterion) {
+    let chain = setup();
+    let hashes: Vec<String> = chain
+        .get_all_blocks()
+        .iter()
+        .step_by(20)
+        .flat_map(|block| block.transactions.iter().map(|tx| tx.hash.to_owned()))
+        .collect();
+    let mut group = c.benchmark_group("transaction index");
+
+    group.bench_function("locate", |b| {
+        b.iter(|| {
+            hashes
+                .iter()
+                .filter_map(|hash| chain.locate_transaction(hash))
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+/// Measure the pagination of the longest wallet history, on its first and last pages.
+fn pagination(c: &mut Criterion) {
+    let chain = setup();
+    let wallet = chain
+        .list_wallets(1, 100, WalletSort::Address)
+        .into_iter()
+        .max_by_key(|wallet| wallet.transactions.len())
+        .unwrap();
+    let last = wallet.transactions.len().div_ceil(50);
+    let mut group = c.benchmark_group("wallet history page of 50");
+
+    group.bench_function("first", |b| {
+        b.iter(|| chain.get_wallet_transactions(wallet.address.to_owned(), 1, 50))
+    });
+    group.bench_function("last", |b| {
+        b.iter(|| chain.get_wallet_transactions(wallet.address.to_owned(), last, 50))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, lookup, pagination);
+criterion_main!(benches);
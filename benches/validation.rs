@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use blockchain::{fixtures, Address, Block, Keypair, Transaction};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Build a block with 1000 transactions.
+fn setup() -> Block {
+    let mut block = Block::new("0".to_string(), 1.0);
+
+    for i in 0..1000 {
+        block.transactions.push(
+            Transaction::builder()
+                .from(format!("sender-{}", i))
+                .to(format!("receiver-{}", i))
+                .amount(i as f64 + 1.0)
+                .fee(0.1)
+                .build(),
+        );
+    }
+
+    block.count = block.transactions.len();
+
+    block
+}
+
+/// Build a block with a coinbase and 1000 transactions signed by distinct senders.
+///
+/// # Returns
+///
+/// The block, and the public keys of the senders by address.
+fn setup_signed() -> (Block, HashMap<Address, String>) {
+    let mut block = Block::new("0".to_string(), 1.0);
+    let mut keys = HashMap::new();
+
+    block.transactions.push(
+        Transaction::builder()
+            .from("Root")
+            .to("miner")
+            .amount(100.0)
+            .fee(0.0)
+            .build(),
+    );
+
+    for i in 0..1000 {
+        let keypair = Keypair::generate();
+
+        block.transactions.push(
+            Transaction::builder()
+                .from(keypair.address())
+                .to(format!("receiver-{}", i))
+                .amount(i as f64 + 1.0)
+                .fee(0.1)
+                .build()
+                .sign(&keypair),
+        );
+        keys.insert(keypair.address(), keypair.public_key());
+    }
+
+    block.count = block.transactions.len();
+
+    (block, keys)
+}
+
+/// Compare serial and parallel validation of a 1000 transactions block.
+fn validation(c: &mut Criterion) {
+    let block = setup();
+    let mut group = c.benchmark_group("validate 1k transactions");
+
+    group.bench_function("serial", |b| b.iter(|| block.verify_transactions()));
+    group.bench_function("parallel", |b| {
+        b.iter(|| block.verify_transactions_parallel())
+    });
+
+    group.finish();
+}
+
+/// Compare serial and parallel verification of the signatures of a 1000 transactions block.
+fn signature_validation(c: &mut Criterion) {
+    let (block, keys) = setup_signed();
+    let mut group = c.benchmark_group("verify 1k signatures");
+
+    group.bench_function("serial", |b| {
+        b.iter_batched_ref(
+            || keys.clone(),
+            |keys| block.verify_signatures(keys),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("parallel", |b| {
+        b.iter_batched_ref(
+            || keys.clone(),
+            |keys| block.verify_signatures_parallel(keys),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+/// Measure the verification of a 100 blocks blockchain with 20 payments per block.
+fn chain_validation(c: &mut Criterion) {
+    let chain = fixtures::build_chain(42, 100, 50, 20);
+
+    c.bench_function("validate 100 blocks", |b| {
+        b.iter(|| chain.validate_range(0, 100))
+    });
+}
+
+criterion_group!(benches, validation, signature_validation, chain_validation);
+criterion_main!(benches);
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use blockchain::Keypair;
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::{errors::ApiError, handlers::AppState};
+
+/// The length of the session tokens.
+const TOKEN_LENGTH: usize = 48;
+
+/// The wallet a bearer token is bound to.
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The chain identifier of the wallet.
+    pub chain_id: String,
+
+    /// The wallet address.
+    pub address: String,
+
+    /// The keypair owning the wallet, held by the server on behalf of the token holder.
+    pub keypair: Keypair,
+}
+
+impl Session {
+    /// Check whether the session allows spending from a wallet.
+    ///
+    /// # Arguments
+    ///
+    /// - `chain_id` - The chain identifier.
+    /// - `address` - The address of the sender.
+    ///
+    /// # Returns
+    ///
+    /// A `403 Forbidden` error if the token is bound to another wallet.
+    pub fn authorize(&self, chain_id: &str, address: &str) -> Result<(), ApiError> {
+        match self.chain_id == chain_id && self.address == address {
+            true => Ok(()),
+            false => Err(ApiError::forbidden_sender()),
+        }
+    }
+}
+
+/// The bearer tokens issued to wallets, kept in memory until the server stops.
+#[derive(Debug, Default)]
+pub struct Sessions {
+    /// A map to associate the tokens with their wallets.
+    tokens: HashMap<String, Session>,
+}
+
+impl Sessions {
+    /// Issue a new token bound to a wallet.
+    ///
+    /// # Arguments
+    ///
+    /// - `chain_id` - The chain identifier of the wallet.
+    /// - `address` - The wallet address.
+    /// - `keypair` - The keypair owning the wallet.
+    ///
+    /// # Returns
+    ///
+    /// The bearer token.
+    pub fn issue(&mut self, chain_id: &str, address: &str, keypair: Keypair) -> String {
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+
+        self.tokens.insert(
+            token.to_owned(),
+            Session {
+                chain_id: chain_id.to_string(),
+                address: address.to_string(),
+                keypair,
+            },
+        );
+
+        token
+    }
+
+    /// Get the wallet a token is bound to.
+    ///
+    /// # Arguments
+    ///
+    /// - `token` - The bearer token.
+    ///
+    /// # Returns
+    ///
+    /// The session, or `None` if the token was not issued.
+    pub fn get(&self, token: &str) -> Option<&Session> {
+        self.tokens.get(token)
+    }
+
+    /// Revoke the tokens of the wallets of a blockchain.
+    ///
+    /// # Arguments
+    ///
+    /// - `chain_id` - The chain identifier.
+    pub fn revoke_chain(&mut self, chain_id: &str) {
+        self.tokens
+            .retain(|_, session| session.chain_id != chain_id);
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Session {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(ApiError::unauthorized)?;
+
+        state
+            .sessions
+            .lock()
+            .unwrap()
+            .get(token.trim())
+            .cloned()
+            .ok_or_else(ApiError::unauthorized)
+    }
+}
+
+/// An operator authenticated with the admin token of the settings.
+#[derive(Clone, Copy, Debug)]
+pub struct Admin;
+
+#[async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let expected = state
+            .admin_token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(ApiError::admin_disabled)?;
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(ApiError::unauthorized)?;
+
+        match constant_time_eq(token.trim().as_bytes(), expected.as_bytes()) {
+            true => Ok(Admin),
+            false => Err(ApiError::unauthorized()),
+        }
+    }
+}
+
+/// Compare two secrets in a time independent of the position of their first difference.
+///
+/// # Arguments
+///
+/// - `a` - The first secret.
+/// - `b` - The second secret.
+///
+/// # Returns
+///
+/// `true` if the secrets are equal.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,151 @@
+use std::{fs, io, net::SocketAddr};
+
+use blockchain::EmailPrivacy;
+use serde::Deserialize;
+
+/// The path of the configuration file, unless overridden by the `CONFIG_PATH` variable.
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// The server settings.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// The host the server listens on.
+    pub host: String,
+
+    /// The port the server listens on.
+    pub port: u16,
+
+    /// The directory the blockchains are persisted to.
+    pub data_dir: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: "0.0.0.0".to_string(),
+            port: 7878,
+            data_dir: "data".to_string(),
+        }
+    }
+}
+
+/// The CORS settings.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// The origins allowed to call the API, `*` allows any origin.
+    pub allowed_origins: Vec<String>,
+
+    /// How long browsers may cache preflight responses, in seconds.
+    pub max_age: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: vec!["*".to_string()],
+            max_age: 3600,
+        }
+    }
+}
+
+/// The response compression settings.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Whether responses are compressed with gzip.
+    pub enabled: bool,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        CompressionConfig { enabled: true }
+    }
+}
+
+/// The request tracing settings.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TracingConfig {
+    /// The log filter.
+    pub filter: String,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        TracingConfig {
+            filter: "api_axum=info,tower_http=info".to_string(),
+        }
+    }
+}
+
+/// The privacy settings.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// How the blockchains created by the server store the wallet emails. Unless they are stored
+    /// as given, the wallet responses only expose the address and the balance.
+    pub email: EmailPrivacy,
+}
+
+/// The administration settings.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// The bearer token of the administration routes, which are disabled without a token.
+    pub token: Option<String>,
+}
+
+/// The application settings.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// The server settings.
+    pub server: ServerConfig,
+
+    /// The CORS settings.
+    pub cors: CorsConfig,
+
+    /// The response compression settings.
+    pub compression: CompressionConfig,
+
+    /// The request tracing settings.
+    pub tracing: TracingConfig,
+
+    /// The privacy settings.
+    pub privacy: PrivacyConfig,
+
+    /// The administration settings.
+    pub admin: AdminConfig,
+}
+
+impl AppConfig {
+    /// Load the settings from the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// The settings from the file, or the default settings if the file does not exist.
+    pub fn load() -> io::Result<Self> {
+        let path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| CONFIG_PATH.to_string());
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(AppConfig::default()),
+            Err(err) => return Err(err),
+        };
+
+        toml::from_str(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Get the address the server listens on.
+    ///
+    /// # Returns
+    ///
+    /// The socket address built from the host and the port.
+    pub fn address(&self) -> io::Result<SocketAddr> {
+        format!("{}:{}", self.server.host, self.server.port)
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+    }
+}
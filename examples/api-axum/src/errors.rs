@@ -0,0 +1,369 @@
+use std::time::Duration;
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain::ChainError;
+use serde_json::{json, Value};
+
+/// An error returned by the API.
+#[derive(Debug)]
+pub struct ApiError {
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+
+    /// The machine-readable error code.
+    pub code: &'static str,
+
+    /// The human-readable error message.
+    pub message: String,
+
+    /// Additional information about the error.
+    pub details: Value,
+
+    /// The delay after which the request can be retried, sent in the `Retry-After` header.
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    /// Create a new API error.
+    ///
+    /// # Arguments
+    ///
+    /// - `status` - The HTTP status of the response.
+    /// - `code` - The machine-readable error code.
+    /// - `message` - The human-readable error message.
+    ///
+    /// # Returns
+    ///
+    /// A new API error without details.
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+            details: Value::Null,
+            retry_after: None,
+        }
+    }
+
+    /// Attach additional information to the error.
+    ///
+    /// # Arguments
+    ///
+    /// - `details` - The additional information.
+    ///
+    /// # Returns
+    ///
+    /// The API error with details.
+    pub fn with_details(self, details: Value) -> Self {
+        ApiError { details, ..self }
+    }
+
+    /// Advise the client to retry the request after a delay.
+    ///
+    /// # Arguments
+    ///
+    /// - `delay` - The delay, rounded up to whole seconds.
+    ///
+    /// # Returns
+    ///
+    /// The API error with a `Retry-After` header.
+    pub fn with_retry_after(self, delay: Duration) -> Self {
+        ApiError {
+            retry_after: Some(delay),
+            ..self
+        }
+    }
+
+    /// The blockchain is not registered.
+    pub fn chain_not_found() -> Self {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            "chain_not_found",
+            "Chain is not found",
+        )
+    }
+
+    /// The wallet does not exist.
+    pub fn wallet_not_found() -> Self {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            "wallet_not_found",
+            "Wallet is not found",
+        )
+    }
+
+    /// The transaction does not exist.
+    pub fn transaction_not_found() -> Self {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            "transaction_not_found",
+            "Transaction is not found",
+        )
+    }
+
+    /// The bearer token is missing or was not issued.
+    pub fn unauthorized() -> Self {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "Bearer token is missing or invalid",
+        )
+    }
+
+    /// The administration routes are disabled, no admin token being configured.
+    pub fn admin_disabled() -> Self {
+        ApiError::new(
+            StatusCode::FORBIDDEN,
+            "admin_disabled",
+            "Administration routes are disabled",
+        )
+    }
+
+    /// The bearer token is bound to another wallet than the sender.
+    pub fn forbidden_sender() -> Self {
+        ApiError::new(
+            StatusCode::FORBIDDEN,
+            "forbidden_sender",
+            "Token does not allow spending from this wallet",
+        )
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "code": self.code,
+            "message": self.message,
+            "details": self.details,
+        });
+
+        let mut response = (self.status, Json(body)).into_response();
+
+        if let Some(delay) = self.retry_after {
+            let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, seconds.into());
+        }
+
+        response
+    }
+}
+
+impl From<ChainError> for ApiError {
+    fn from(err: ChainError) -> Self {
+        let message = err.to_string();
+
+        match err {
+            ChainError::InvalidEmail => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_email", message)
+            }
+            ChainError::DuplicateEmail(email) => {
+                ApiError::new(StatusCode::CONFLICT, "duplicate_email", message)
+                    .with_details(json!({ "email": email }))
+            }
+            ChainError::AddressTaken(address) => {
+                ApiError::new(StatusCode::CONFLICT, "address_taken", message)
+                    .with_details(json!({ "address": address }))
+            }
+            ChainError::InvalidPublicKey => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_public_key",
+                message,
+            ),
+            ChainError::InvalidMnemonic(reason) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_mnemonic",
+                message,
+            )
+            .with_details(json!({ "reason": reason })),
+            ChainError::InvalidKeyRotation(reason) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_key_rotation",
+                message,
+            )
+            .with_details(json!({ "reason": reason })),
+            ChainError::InvalidMultiSig(reason) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_multisig",
+                message,
+            )
+            .with_details(json!({ "reason": reason })),
+            ChainError::UnknownSender(address) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "unknown_sender", message)
+                    .with_details(json!({ "address": address }))
+            }
+            ChainError::UnknownReceiver(address) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unknown_receiver",
+                message,
+            )
+            .with_details(json!({ "address": address })),
+            ChainError::UnknownFeePayer(address) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unknown_fee_payer",
+                message,
+            )
+            .with_details(json!({ "address": address })),
+            ChainError::SelfTransfer => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "self_transfer", message)
+            }
+            ChainError::InvalidAmount => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_amount", message)
+            }
+            ChainError::InsufficientBalance => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "insufficient_balance",
+                message,
+            ),
+            ChainError::FeeTooLow => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "fee_too_low", message)
+            }
+            ChainError::RelayFeeTooLow => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "relay_fee_too_low",
+                message,
+            ),
+            ChainError::InvalidHash => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_hash", message)
+            }
+            ChainError::SignatureRequired => {
+                ApiError::new(StatusCode::UNAUTHORIZED, "signature_required", message)
+            }
+            ChainError::InvalidSignature => {
+                ApiError::new(StatusCode::UNAUTHORIZED, "invalid_signature", message)
+            }
+            ChainError::UnregisteredKey(_) => {
+                ApiError::new(StatusCode::UNAUTHORIZED, "unregistered_key", message)
+            }
+            ChainError::InsufficientApprovals { required, actual } => {
+                ApiError::new(StatusCode::UNAUTHORIZED, "insufficient_approvals", message)
+                    .with_details(json!({ "required": required, "actual": actual }))
+            }
+            ChainError::UnknownProposal(hash) => {
+                ApiError::new(StatusCode::NOT_FOUND, "unknown_proposal", message)
+                    .with_details(json!({ "hash": hash }))
+            }
+            ChainError::PolicyViolation(reason) => {
+                ApiError::new(StatusCode::FORBIDDEN, "policy_violation", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::TransactionRejected(reason) => {
+                ApiError::new(StatusCode::FORBIDDEN, "transaction_rejected", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::DuplicateTransaction => {
+                ApiError::new(StatusCode::CONFLICT, "duplicate_transaction", message)
+            }
+            ChainError::MemoTooLong { max, actual } => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "memo_too_long", message)
+                    .with_details(json!({ "max": max, "actual": actual }))
+            }
+            ChainError::InvalidNonce { expected, actual } => {
+                ApiError::new(StatusCode::CONFLICT, "invalid_nonce", message)
+                    .with_details(json!({ "expected": expected, "actual": actual }))
+            }
+            ChainError::DoubleSpend(input) => {
+                ApiError::new(StatusCode::CONFLICT, "double_spend", message)
+                    .with_details(json!({ "input": input }))
+            }
+            ChainError::InvalidInputs(reason) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_inputs", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::MempoolFull { retry_after_hint } => {
+                ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "mempool_full", message)
+                    .with_details(json!({ "retry_after": retry_after_hint.as_secs() }))
+                    .with_retry_after(retry_after_hint)
+            }
+            ChainError::InvalidDifficulty => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_difficulty",
+                message,
+            ),
+            ChainError::InvalidReward => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_reward", message)
+            }
+            ChainError::InvalidFee => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_fee", message)
+            }
+            ChainError::InvalidMilestone(name) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_milestone",
+                message,
+            )
+            .with_details(json!({ "name": name })),
+            ChainError::UnknownParent => {
+                ApiError::new(StatusCode::CONFLICT, "unknown_parent", message)
+            }
+            ChainError::MiningCancelled => {
+                ApiError::new(StatusCode::CONFLICT, "mining_cancelled", message)
+            }
+            ChainError::ChainPaused(reason) => {
+                ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "chain_paused", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::InvalidScript(reason) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_script", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::InvalidStake(reason) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_stake", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::UnknownContract(address) => {
+                ApiError::new(StatusCode::NOT_FOUND, "unknown_contract", message)
+                    .with_details(json!({ "address": address }))
+            }
+            ChainError::InvalidContract(reason) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_contract",
+                message,
+            )
+            .with_details(json!({ "reason": reason })),
+            ChainError::ContractReverted(reason) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "contract_reverted",
+                message,
+            )
+            .with_details(json!({ "reason": reason })),
+            ChainError::OutOfGas { limit } => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "out_of_gas", message)
+                    .with_details(json!({ "limit": limit }))
+            }
+            ChainError::InvalidGas(reason) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_gas", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::UnknownAsset(asset_id) => {
+                ApiError::new(StatusCode::NOT_FOUND, "unknown_asset", message)
+                    .with_details(json!({ "asset_id": asset_id }))
+            }
+            ChainError::InvalidAsset(reason) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_asset", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::NotValidator => {
+                ApiError::new(StatusCode::FORBIDDEN, "not_validator", message)
+            }
+            ChainError::InvalidBlock(reason) => {
+                ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_block", message)
+                    .with_details(json!({ "reason": reason }))
+            }
+            ChainError::InsufficientWork => {
+                ApiError::new(StatusCode::CONFLICT, "insufficient_work", message)
+            }
+            ChainError::InvariantViolation(reason) => ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "invariant_violation",
+                message,
+            )
+            .with_details(json!({ "reason": reason })),
+        }
+    }
+}
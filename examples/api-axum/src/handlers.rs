@@ -1,20 +1,70 @@
 use std::sync::{Arc, Mutex};
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
     Json,
 };
-use blockchain::Chain;
+use blockchain::{
+    CancellationToken, Chain, ChainConfig, ChainError, ChainEvent, ChainRegistry, EmailPrivacy,
+    Keypair, Miner, SpendingPolicy, Transaction, Wallet, WalletSort, MAX_MEMO_LENGTH,
+};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use crate::{
+    auth::{Admin, Session, Sessions},
+    errors::ApiError,
+    validation::{
+        max_length, minute_of_day, non_empty, non_negative, page_size, positive_amount, FieldError,
+        Validate, ValidatedJson, ValidatedQuery,
+    },
+};
+
+/// The maximum length of the reason of a paused transaction intake.
+const MAX_PAUSE_REASON_LENGTH: usize = 256;
+
+/// The result of a request handler.
+pub type ApiResult = Result<(StatusCode, Json<Value>), ApiError>;
 
 /// The application state.
 #[derive(Clone)]
 pub struct AppState {
-    /// The blockchain.
-    pub chain: Arc<Mutex<Chain>>,
+    /// The registry of blockchains.
+    pub registry: Arc<Mutex<ChainRegistry>>,
+
+    /// The directory the blockchains are persisted to.
+    pub data_dir: String,
+
+    /// The bearer tokens issued to the created wallets.
+    pub sessions: Arc<Mutex<Sessions>>,
+
+    /// How the blockchains created by the server store the wallet emails.
+    pub email_privacy: EmailPrivacy,
+
+    /// The bearer token of the administration routes, `None` to disable them.
+    pub admin_token: Option<String>,
+}
+
+/// Create a new blockchain.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateChainInput {
+    /// The chain identifier.
+    pub id: String,
+
+    /// The initial mining difficulty level of the network.
+    pub difficulty: f64,
+
+    /// The initial block reward for miners.
+    pub reward: f64,
+
+    /// The transaction fee.
+    pub fee: f64,
 }
 
 /// Create a new wallet.
@@ -22,6 +72,10 @@ pub struct AppState {
 pub struct CreateWalletInput {
     /// The wallet email.
     pub email: String,
+
+    /// Return the existing wallet of the email instead of creating a new one.
+    #[serde(default)]
+    pub idempotent: bool,
 }
 
 /// Add a new transaction.
@@ -35,6 +89,25 @@ pub struct AddTransactionInput {
 
     /// The transaction amount.
     pub amount: f64,
+
+    /// The payment reference attached to the transaction.
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+/// Submit a transaction built and signed by the client.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitTransactionInput {
+    /// The transaction.
+    #[serde(flatten)]
+    pub transaction: Transaction,
+}
+
+/// Pause the transaction intake of a blockchain.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PauseIntakeInput {
+    /// The reason given to the rejected submitters.
+    pub reason: String,
 }
 
 /// Get the balance of a wallet.
@@ -57,6 +130,45 @@ pub struct GetWalletTransactionInput {
     pub size: usize,
 }
 
+/// Set the spending policy of a wallet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetSpendingPolicyInput {
+    /// The wallet address.
+    pub address: String,
+
+    /// The spending policy.
+    #[serde(flatten)]
+    pub policy: SpendingPolicy,
+}
+
+/// Get or remove the spending policy of a wallet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpendingPolicyInput {
+    /// The wallet address.
+    pub address: String,
+}
+
+/// Get a list of wallets.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetWalletsInput {
+    /// The page number.
+    pub page: usize,
+
+    /// The page size.
+    pub size: usize,
+
+    /// The order of the wallets.
+    #[serde(default)]
+    pub sort: WalletSort,
+}
+
+/// Search wallets.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchWalletsInput {
+    /// The email or address prefix.
+    pub query: String,
+}
+
 /// Get a list of transactions of a wallet.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetTransactionsInput {
@@ -67,24 +179,399 @@ pub struct GetTransactionsInput {
     pub size: usize,
 }
 
+/// Get a summary of a blockchain.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetOverviewInput {
+    /// The amount of latest blocks and transactions.
+    pub size: usize,
+}
+
+impl Validate for CreateChainInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "id", &self.id);
+        non_negative(&mut errors, "difficulty", self.difficulty);
+        non_negative(&mut errors, "reward", self.reward);
+        non_negative(&mut errors, "fee", self.fee);
+
+        errors
+    }
+}
+
+impl Validate for CreateWalletInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "email", &self.email);
+
+        errors
+    }
+}
+
+impl Validate for AddTransactionInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "from", &self.from);
+        non_empty(&mut errors, "to", &self.to);
+        positive_amount(&mut errors, "amount", self.amount);
+
+        if let Some(memo) = &self.memo {
+            max_length(&mut errors, "memo", memo, MAX_MEMO_LENGTH);
+        }
+
+        errors
+    }
+}
+
+impl Validate for SubmitTransactionInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "from", &self.transaction.from);
+        non_empty(&mut errors, "to", &self.transaction.to);
+        positive_amount(&mut errors, "amount", self.transaction.amount);
+
+        errors
+    }
+}
+
+impl Validate for PauseIntakeInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "reason", &self.reason);
+        max_length(&mut errors, "reason", &self.reason, MAX_PAUSE_REASON_LENGTH);
+
+        errors
+    }
+}
+
+impl Validate for GetWalletBalanceInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "address", &self.address);
+
+        errors
+    }
+}
+
+impl Validate for GetWalletTransactionInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "address", &self.address);
+        page_size(&mut errors, self.size);
+
+        errors
+    }
+}
+
+impl Validate for SetSpendingPolicyInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "address", &self.address);
+
+        for window in &self.policy.time_windows {
+            minute_of_day(&mut errors, "time_windows.start", window.start);
+            minute_of_day(&mut errors, "time_windows.end", window.end);
+        }
+
+        if let Some(max_amount) = self.policy.max_amount {
+            positive_amount(&mut errors, "max_amount", max_amount);
+        }
+
+        errors
+    }
+}
+
+impl Validate for SpendingPolicyInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "address", &self.address);
+
+        errors
+    }
+}
+
+impl Validate for GetWalletsInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        page_size(&mut errors, self.size);
+
+        errors
+    }
+}
+
+impl Validate for SearchWalletsInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        non_empty(&mut errors, "query", &self.query);
+
+        errors
+    }
+}
+
+impl Validate for GetOverviewInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        page_size(&mut errors, self.size);
+
+        errors
+    }
+}
+
+impl Validate for GetTransactionsInput {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        page_size(&mut errors, self.size);
+
+        errors
+    }
+}
+
+/// Get the identifiers of all blockchains.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+///
+/// # Returns
+///
+/// The list of chain identifiers.
+pub async fn get_chains(State(state): State<AppState>) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": registry.get_chain_ids() })),
+    ))
+}
+
+/// Create a new blockchain.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `body` - The request body.
+///
+/// # Returns
+///
+/// The new chain identifier.
+pub async fn create_chain(
+    State(state): State<AppState>,
+    ValidatedJson(body): ValidatedJson<CreateChainInput>,
+) -> ApiResult {
+    let mut registry = state.registry.lock().unwrap();
+
+    let config = ChainConfig {
+        email_privacy: state.email_privacy,
+        ..ChainConfig::default()
+    };
+
+    if !registry.create_chain_with_config(
+        body.id.to_owned(),
+        body.difficulty,
+        body.reward,
+        body.fee,
+        config,
+    ) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "chain_conflict",
+            "Chain already exists or has an invalid identifier",
+        )
+        .with_details(json!({ "id": body.id })));
+    }
+
+    Ok((StatusCode::CREATED, Json(json!({ "data": body.id }))))
+}
+
+/// Remove a blockchain.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+///
+/// # Returns
+///
+/// The removed chain identifier.
+pub async fn remove_chain(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+) -> ApiResult {
+    let mut registry = state.registry.lock().unwrap();
+
+    registry
+        .remove_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    state.sessions.lock().unwrap().revoke_chain(&chain_id);
+
+    Ok((StatusCode::OK, Json(json!({ "data": chain_id }))))
+}
+
+/// Persist all blockchains to the data directory.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+///
+/// # Returns
+///
+/// The list of persisted chain identifiers.
+pub async fn persist_chains(State(state): State<AppState>) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+
+    registry.persist(&state.data_dir).map_err(|err| {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "persistence_failed",
+            err.to_string(),
+        )
+    })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": registry.get_chain_ids() })),
+    ))
+}
+
 /// Create a new wallet.
 ///
 /// # Arguments
 ///
 /// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
 /// - `body` - The request body.
 ///
 /// # Returns
 ///
-/// A new wallet address.
+/// A new wallet address, and the bearer token allowing to spend from the wallet with the keypair
+/// generated for it. The existing wallet of an idempotent request comes without a token, so
+/// knowing an email does not grant access to its wallet.
 pub async fn create_wallet(
     State(state): State<AppState>,
-    Json(body): Json<CreateWalletInput>,
-) -> impl IntoResponse {
-    let mut chain = state.chain.lock().unwrap();
-    let address = chain.create_wallet(body.email);
+    Path(chain_id): Path<String>,
+    ValidatedJson(body): ValidatedJson<CreateWalletInput>,
+) -> ApiResult {
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let existing = chain
+        .get_wallet_by_email(&body.email)
+        .map(|wallet| wallet.address.to_owned());
+    let keypair = Keypair::generate();
+    let address = match (body.idempotent, &existing) {
+        (true, Some(address)) => address.to_owned(),
+        _ => chain.create_wallet_with_key(body.email, &keypair.public_key())?,
+    };
+
+    let token = match existing {
+        Some(_) => None,
+        None => Some(
+            state
+                .sessions
+                .lock()
+                .unwrap()
+                .issue(&chain_id, &address, keypair),
+        ),
+    };
 
-    (StatusCode::OK, Json(json!({ "data": address })))
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": address, "token": token })),
+    ))
+}
+
+/// Get a list of wallets.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `params` - The request query parameters.
+///
+/// # Returns
+///
+/// The list of wallets.
+pub async fn get_wallets(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    ValidatedQuery(params): ValidatedQuery<GetWalletsInput>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let wallets = chain.list_wallets(params.page, params.size, params.sort);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": wallet_views(chain, wallets) })),
+    ))
+}
+
+/// Render wallets for a response, exposing only their address and balance unless the
+/// blockchain stores the emails as given.
+///
+/// # Arguments
+///
+/// - `chain` - The blockchain of the wallets.
+/// - `wallets` - The wallets.
+///
+/// # Returns
+///
+/// The wallets.
+fn wallet_views(chain: &Chain, wallets: Vec<Wallet>) -> Value {
+    match chain.get_config().email_privacy {
+        EmailPrivacy::Plain => json!(wallets),
+        _ => wallets
+            .into_iter()
+            .map(|wallet| json!({ "address": wallet.address, "balance": wallet.balance }))
+            .collect(),
+    }
+}
+
+/// Search wallets by the prefix of their email or address.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `params` - The request query parameters.
+///
+/// # Returns
+///
+/// The matching wallets.
+pub async fn search_wallets(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    ValidatedQuery(params): ValidatedQuery<SearchWalletsInput>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let wallets = chain.search_wallets(&params.query);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": wallet_views(chain, wallets) })),
+    ))
 }
 
 /// Get the balance of a wallet.
@@ -92,6 +579,7 @@ pub async fn create_wallet(
 /// # Arguments
 ///
 /// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
 /// - `params` - The request query parameters.
 ///
 /// # Returns
@@ -99,18 +587,19 @@ pub async fn create_wallet(
 /// The balance of the wallet.
 pub async fn get_wallet_balance(
     State(state): State<AppState>,
-    Query(params): Query<GetWalletBalanceInput>,
-) -> impl IntoResponse {
-    let chain = state.chain.lock().unwrap();
-    let balance = chain.get_wallet_balance(params.address);
+    Path(chain_id): Path<String>,
+    ValidatedQuery(params): ValidatedQuery<GetWalletBalanceInput>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
 
-    match balance {
-        Some(balance) => (StatusCode::OK, Json(json!({ "data": balance }))),
-        None => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "message": "Wallet is not found" })),
-        ),
-    }
+    let balance = chain
+        .get_wallet_balance(params.address)
+        .ok_or_else(ApiError::wallet_not_found)?;
+
+    Ok((StatusCode::OK, Json(json!({ "data": balance }))))
 }
 
 /// Get a list of transactions of a wallet.
@@ -118,6 +607,7 @@ pub async fn get_wallet_balance(
 /// # Arguments
 ///
 /// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
 /// - `params` - The request query parameters.
 ///
 /// # Returns
@@ -125,18 +615,19 @@ pub async fn get_wallet_balance(
 /// The list of transactions of the wallet.
 pub async fn get_wallet_transactions(
     State(state): State<AppState>,
-    Query(params): Query<GetWalletTransactionInput>,
-) -> impl IntoResponse {
-    let chain = state.chain.lock().unwrap();
-    let transaction = chain.get_wallet_transactions(params.address, params.page, params.size);
+    Path(chain_id): Path<String>,
+    ValidatedQuery(params): ValidatedQuery<GetWalletTransactionInput>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
 
-    match transaction {
-        Some(transaction) => (StatusCode::OK, Json(json!({ "data": transaction }))),
-        None => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "message": "Wallet is not found" })),
-        ),
-    }
+    let transactions = chain
+        .get_wallet_transactions(params.address, params.page, params.size)
+        .ok_or_else(ApiError::wallet_not_found)?;
+
+    Ok((StatusCode::OK, Json(json!({ "data": transactions }))))
 }
 
 /// Get all transactions.
@@ -144,6 +635,7 @@ pub async fn get_wallet_transactions(
 /// # Arguments
 ///
 /// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
 /// - `params` - The request query parameters.
 ///
 /// # Returns
@@ -151,12 +643,17 @@ pub async fn get_wallet_transactions(
 /// All transactions.
 pub async fn get_transactions(
     State(state): State<AppState>,
-    Query(params): Query<GetTransactionsInput>,
-) -> impl IntoResponse {
-    let chain = state.chain.lock().unwrap();
+    Path(chain_id): Path<String>,
+    ValidatedQuery(params): ValidatedQuery<GetTransactionsInput>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
     let transactions = chain.get_transactions(params.page, params.size);
 
-    (StatusCode::OK, Json(json!({ "data": transactions })))
+    Ok((StatusCode::OK, Json(json!({ "data": transactions }))))
 }
 
 /// Get a transaction.
@@ -164,6 +661,7 @@ pub async fn get_transactions(
 /// # Arguments
 ///
 /// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
 /// - `hash` - The transaction hash.
 ///
 /// # Returns
@@ -171,18 +669,24 @@ pub async fn get_transactions(
 /// The transaction.
 pub async fn get_transaction(
     State(state): State<AppState>,
-    Path(hash): Path<String>,
-) -> impl IntoResponse {
-    let chain = state.chain.lock().unwrap();
-    let transaction = chain.get_transaction(hash);
+    Path((chain_id, hash)): Path<(String, String)>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
 
-    match transaction {
-        Some(transaction) => (StatusCode::OK, Json(json!({ "data": transaction }))),
-        None => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "message": "Transaction is not found" })),
-        ),
-    }
+    // Search the pending transactions, then the mined ones
+    let transaction = chain
+        .get_transaction(hash.to_owned())
+        .or_else(|| {
+            chain
+                .find_transaction_in_chain(&hash)
+                .map(|(_, transaction)| transaction)
+        })
+        .ok_or_else(ApiError::transaction_not_found)?;
+
+    Ok((StatusCode::OK, Json(json!({ "data": transaction }))))
 }
 
 /// Add a new transaction.
@@ -190,18 +694,412 @@ pub async fn get_transaction(
 /// # Arguments
 ///
 /// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `session` - The wallet bound to the bearer token, which must be the sender.
 /// - `body` - The request body.
 ///
 /// # Returns
 ///
-/// The new transaction.
+/// The hash of the new transaction, with a `201 Created` status.
 pub async fn add_transaction(
     State(state): State<AppState>,
-    Json(body): Json<AddTransactionInput>,
-) -> impl IntoResponse {
-    let mut chain = state.chain.lock().unwrap();
+    Path(chain_id): Path<String>,
+    session: Session,
+    ValidatedJson(body): ValidatedJson<AddTransactionInput>,
+) -> ApiResult {
+    session.authorize(&chain_id, &body.from)?;
+
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let hash = chain.add_transaction_with_memo(
+        body.from.to_owned(),
+        body.to.to_owned(),
+        body.amount,
+        body.memo,
+        &session.keypair,
+    )?;
+
+    Ok((StatusCode::CREATED, Json(json!({ "data": hash }))))
+}
+
+/// Submit a transaction built and signed by the client, safe to retry.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `session` - The wallet bound to the bearer token, which must be the sender.
+/// - `body` - The request body.
+///
+/// # Returns
+///
+/// The hash of the transaction, with a `201 Created` status, or a `200 OK` status if the
+/// identical transaction was already submitted.
+pub async fn submit_transaction(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    session: Session,
+    ValidatedJson(body): ValidatedJson<SubmitTransactionInput>,
+) -> ApiResult {
+    session.authorize(&chain_id, &body.transaction.from)?;
+
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let submitted = chain.locate_transaction(&body.transaction.hash).is_some();
+    let hash = chain.submit_transaction_idempotent(body.transaction)?;
+    let status = match submitted {
+        true => StatusCode::OK,
+        false => StatusCode::CREATED,
+    };
+
+    Ok((status, Json(json!({ "data": hash }))))
+}
+
+/// Pause the transaction intake of a blockchain, e.g. during a maintenance.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `body` - The request body.
+///
+/// # Returns
+///
+/// The reason of the pause. Submitted transactions are rejected with a `503 Service Unavailable`
+/// status until the intake is resumed, while mining and queries continue.
+pub async fn pause_intake(
+    _: Admin,
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    ValidatedJson(body): ValidatedJson<PauseIntakeInput>,
+) -> ApiResult {
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    chain.pause_intake(&body.reason);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": { "paused": true, "reason": body.reason } })),
+    ))
+}
+
+/// Resume the transaction intake of a blockchain.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+///
+/// # Returns
+///
+/// The reason of the lifted pause, `null` if the intake was not paused.
+pub async fn resume_intake(
+    _: Admin,
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+) -> ApiResult {
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let reason = chain.resume_intake();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": { "paused": false, "reason": reason } })),
+    ))
+}
+
+/// Get the spending policy and the policy audit log of the wallet of the session.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `session` - The session of the wallet owner.
+/// - `params` - The request query parameters.
+///
+/// # Returns
+///
+/// The spending policy, `null` if the wallet has none, and the changes to it, oldest first.
+pub async fn get_spending_policy(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    session: Session,
+    ValidatedQuery(params): ValidatedQuery<SpendingPolicyInput>,
+) -> ApiResult {
+    session.authorize(&chain_id, &params.address)?;
+
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let audit: Vec<_> = chain
+        .get_policy_audit_log()
+        .iter()
+        .filter(|entry| entry.address == params.address)
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "data": chain.get_spending_policy(&params.address),
+            "audit": audit,
+        })),
+    ))
+}
+
+/// Set the spending policy of the wallet of the session.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `session` - The session of the wallet owner.
+/// - `body` - The request body.
+///
+/// # Returns
+///
+/// The spending policy.
+pub async fn set_spending_policy(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    session: Session,
+    ValidatedJson(body): ValidatedJson<SetSpendingPolicyInput>,
+) -> ApiResult {
+    session.authorize(&chain_id, &body.address)?;
+
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    chain.set_spending_policy(&body.address, body.policy.to_owned())?;
+
+    Ok((StatusCode::OK, Json(json!({ "data": body.policy }))))
+}
+
+/// Remove the spending policy of the wallet of the session.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `session` - The session of the wallet owner.
+/// - `params` - The request query parameters.
+///
+/// # Returns
+///
+/// `true` if the wallet had a spending policy, `false` otherwise.
+pub async fn remove_spending_policy(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    session: Session,
+    ValidatedQuery(params): ValidatedQuery<SpendingPolicyInput>,
+) -> ApiResult {
+    session.authorize(&chain_id, &params.address)?;
+
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    let removed = chain.remove_spending_policy(&params.address).is_some();
+
+    Ok((StatusCode::OK, Json(json!({ "data": removed }))))
+}
+
+/// Get the storage and memory usage of a blockchain.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+///
+/// # Returns
+///
+/// The storage stats, including the approximate memory usage and evictions.
+pub async fn get_stats(State(state): State<AppState>, Path(chain_id): Path<String>) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": chain.storage_stats() })),
+    ))
+}
+
+/// Get the named heights of a blockchain.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+///
+/// # Returns
+///
+/// The milestones sorted by height, with the hash of the block at each reached height.
+pub async fn get_milestones(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    Ok((StatusCode::OK, Json(json!({ "data": chain.milestones() }))))
+}
+
+/// Get a summary of a blockchain for the home page of an explorer.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `params` - The request query parameters.
+///
+/// # Returns
+///
+/// The latest blocks and transactions, the mempool size, the difficulty, the hashrate estimate, and the supply.
+pub async fn get_overview(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    ValidatedQuery(params): ValidatedQuery<GetOverviewInput>,
+) -> ApiResult {
+    let registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "data": chain.overview(params.size) })),
+    ))
+}
+
+/// Mine the next block of a blockchain without blocking the executor.
+///
+/// The registry is only locked to build the block and to append it, so other requests are
+/// served while the proof of work runs on the blocking thread pool. The work is abandoned
+/// once a competing block is appended, or the client disconnects.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+///
+/// # Returns
+///
+/// The height, the hash, and the subsidy and fees breakdown of the mined block.
+pub async fn mine_block(State(state): State<AppState>, Path(chain_id): Path<String>) -> ApiResult {
+    let (mut block, events) = {
+        let mut registry = state.registry.lock().unwrap();
+        let chain = registry
+            .get_chain_mut(&chain_id)
+            .ok_or_else(ApiError::chain_not_found)?;
+        let address = chain.get_address().to_owned();
+
+        (chain.block_template(&address)?, chain.subscribe())
+    };
+
+    // Cancel the work once another block is appended to the blockchain
+    let token = CancellationToken::new();
+    let competing = token.clone();
+
+    tokio::task::spawn_blocking(move || {
+        for event in events {
+            if let ChainEvent::BlockMined { .. } = event {
+                competing.cancel();
+                break;
+            }
+        }
+    });
+
+    block.header = Miner::new(0)
+        .mine_async(block.header.clone(), token)
+        .await
+        .ok_or(ChainError::MiningCancelled)?;
+    let hash = block.compute_hash();
+    let payout = block.payout();
+
+    let mut registry = state.registry.lock().unwrap();
+    let chain = registry
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?;
+
+    chain.import_block(block)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "data": {
+                "height": chain.get_all_blocks().len() - 1,
+                "hash": hash,
+                "payout": payout,
+            }
+        })),
+    ))
+}
+
+/// Stream the events of a blockchain over a WebSocket, so explorers can update in real time.
+///
+/// # Arguments
+///
+/// - `state` - The application state.
+/// - `chain_id` - The chain identifier.
+/// - `ws` - The WebSocket upgrade request.
+///
+/// # Returns
+///
+/// The upgrade response, after which every event is sent as a JSON text message.
+pub async fn subscribe_events(
+    State(state): State<AppState>,
+    Path(chain_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let events = state
+        .registry
+        .lock()
+        .unwrap()
+        .get_chain_mut(&chain_id)
+        .ok_or_else(ApiError::chain_not_found)?
+        .subscribe();
+
+    // Forward the events of the blocking receiver to the socket task
+    let (sender, mut receiver) = mpsc::unbounded_channel::<ChainEvent>();
+
+    tokio::task::spawn_blocking(move || {
+        while let Ok(event) = events.recv() {
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    });
 
-    let result = chain.add_transaction(body.from, body.to, body.amount);
+    Ok(ws.on_upgrade(|mut socket: WebSocket| async move {
+        while let Some(event) = receiver.recv().await {
+            let Ok(text) = serde_json::to_string(&event) else {
+                continue;
+            };
 
-    (StatusCode::OK, Json(json!({ "data": result })))
+            // Stop streaming once the client disconnects
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    }))
 }
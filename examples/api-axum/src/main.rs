@@ -1,41 +1,163 @@
 use std::{
-    net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use axum::{
-    routing::{get, post},
+    body::Body,
+    http::{HeaderValue, Request},
+    routing::{delete, get, post},
     Router,
 };
-use blockchain::Chain;
+use blockchain::{ChainConfig, ChainRegistry};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, Any, CorsLayer},
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing_subscriber::EnvFilter;
 
-use crate::handlers::AppState;
+use crate::{
+    auth::Sessions,
+    config::{AppConfig, CorsConfig},
+    handlers::AppState,
+};
 
+mod auth;
+mod config;
+mod errors;
 mod handlers;
+mod validation;
+
+/// Build the CORS layer from the settings.
+///
+/// # Arguments
+///
+/// - `config` - The CORS settings.
+///
+/// # Returns
+///
+/// A CORS layer allowing the configured origins.
+fn cors(config: &CorsConfig) -> CorsLayer {
+    let origins = match config.allowed_origins.iter().any(|origin| origin == "*") {
+        true => AllowOrigin::any(),
+        false => AllowOrigin::list(
+            config
+                .allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        ),
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .max_age(Duration::from_secs(config.max_age))
+}
 
 #[tokio::main]
 async fn main() {
-    let chain = Chain::new(2.0, 100.0, 0.01);
+    let config = AppConfig::load().expect("Unable to load the configuration");
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&config.tracing.filter))
+        .init();
+
+    // Restore the persisted blockchains or start with a default one
+    let registry = ChainRegistry::load(&config.server.data_dir).unwrap_or_else(|_| {
+        let mut registry = ChainRegistry::new();
+        let chain_config = ChainConfig {
+            email_privacy: config.privacy.email,
+            ..ChainConfig::default()
+        };
+
+        registry.create_chain_with_config("default".to_string(), 2.0, 100.0, 0.01, chain_config);
+
+        registry
+    });
 
     let state = AppState {
-        chain: Arc::new(Mutex::new(chain)),
+        registry: Arc::new(Mutex::new(registry)),
+        data_dir: config.server.data_dir.to_owned(),
+        sessions: Arc::new(Mutex::new(Sessions::default())),
+        email_privacy: config.privacy.email,
+        admin_token: config.admin.token.to_owned(),
     };
 
-    let app = Router::new()
-        .route("/transactions/:hash", get(handlers::get_transaction))
-        .route("/transactions", get(handlers::get_transactions))
-        .route("/transactions", post(handlers::add_transaction))
-        .route("/wallet/balance", get(handlers::get_wallet_balance))
+    let mut app = Router::new()
+        .route("/chains", get(handlers::get_chains))
+        .route("/chains", post(handlers::create_chain))
+        .route("/chains/persist", post(handlers::persist_chains))
+        .route("/chains/:chain_id", delete(handlers::remove_chain))
+        .route("/:chain_id/stats", get(handlers::get_stats))
+        .route("/:chain_id/overview", get(handlers::get_overview))
+        .route("/:chain_id/milestones", get(handlers::get_milestones))
+        .route("/:chain_id/admin/pause", post(handlers::pause_intake))
+        .route("/:chain_id/admin/resume", post(handlers::resume_intake))
+        .route("/:chain_id/events", get(handlers::subscribe_events))
+        .route("/:chain_id/blocks/mine", post(handlers::mine_block))
+        .route(
+            "/:chain_id/transactions/:hash",
+            get(handlers::get_transaction),
+        )
+        .route("/:chain_id/transactions", get(handlers::get_transactions))
+        .route("/:chain_id/transactions", post(handlers::add_transaction))
+        .route(
+            "/:chain_id/transactions/signed",
+            post(handlers::submit_transaction),
+        )
         .route(
-            "/wallet/transactions",
+            "/:chain_id/wallet/balance",
+            get(handlers::get_wallet_balance),
+        )
+        .route(
+            "/:chain_id/wallet/transactions",
             get(handlers::get_wallet_transactions),
         )
-        .route("/wallet/create", post(handlers::create_wallet))
+        .route(
+            "/:chain_id/wallet/policy",
+            get(handlers::get_spending_policy)
+                .put(handlers::set_spending_policy)
+                .delete(handlers::remove_spending_policy),
+        )
+        .route("/:chain_id/wallet/create", post(handlers::create_wallet))
+        .route("/:chain_id/wallets", get(handlers::get_wallets))
+        .route("/:chain_id/wallets/search", get(handlers::search_wallets))
         .with_state(state);
 
-    let address = SocketAddr::from(([0, 0, 0, 0], 7878));
+    if config.compression.enabled {
+        app = app.layer(CompressionLayer::new());
+    }
+
+    // Layers wrap each other from bottom to top, so the request id is set before the request is traced
+    let app = app
+        .layer(cors(&config.cors))
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+                let request_id = request
+                    .headers()
+                    .get("x-request-id")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+
+                tracing::info_span!(
+                    "request",
+                    method = %request.method(),
+                    uri = %request.uri(),
+                    request_id,
+                )
+            }),
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+
+    let address = config
+        .address()
+        .expect("Unable to parse the server address");
 
-    println!("Server is running on {}", address);
+    tracing::info!("Server is running on {}", address);
 
     axum::Server::bind(&address)
         .serve(app.into_make_service())
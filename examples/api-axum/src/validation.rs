@@ -0,0 +1,214 @@
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::{FromRequest, FromRequestParts, Query},
+    http::{request::Parts, Request, StatusCode},
+    BoxError, Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+
+use crate::errors::ApiError;
+
+/// The maximum number of items per page.
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// A field that failed validation.
+#[derive(Debug, Serialize)]
+pub struct FieldError {
+    /// The name of the field.
+    pub field: &'static str,
+
+    /// The reason of the failure.
+    pub message: &'static str,
+}
+
+/// A request input that can be validated.
+pub trait Validate {
+    /// Validate the input.
+    ///
+    /// # Returns
+    ///
+    /// The list of fields that failed validation, empty if the input is valid.
+    fn validate(&self) -> Vec<FieldError>;
+}
+
+/// Validate that a string is not blank.
+///
+/// # Arguments
+///
+/// - `errors` - The list of failed fields.
+/// - `field` - The name of the field.
+/// - `value` - The value of the field.
+pub fn non_empty(errors: &mut Vec<FieldError>, field: &'static str, value: &str) {
+    if value.trim().is_empty() {
+        errors.push(FieldError {
+            field,
+            message: "must not be empty",
+        });
+    }
+}
+
+/// Validate that an amount is a positive finite number.
+///
+/// # Arguments
+///
+/// - `errors` - The list of failed fields.
+/// - `field` - The name of the field.
+/// - `value` - The value of the field.
+pub fn positive_amount(errors: &mut Vec<FieldError>, field: &'static str, value: f64) {
+    if !value.is_finite() || value <= 0.0 {
+        errors.push(FieldError {
+            field,
+            message: "must be a positive finite number",
+        });
+    }
+}
+
+/// Validate that a string is not longer than a maximum length.
+///
+/// # Arguments
+///
+/// - `errors` - The list of failed fields.
+/// - `field` - The name of the field.
+/// - `value` - The value of the field.
+/// - `max` - The maximum length in bytes.
+pub fn max_length(errors: &mut Vec<FieldError>, field: &'static str, value: &str, max: usize) {
+    if value.len() > max {
+        errors.push(FieldError {
+            field,
+            message: "is too long",
+        });
+    }
+}
+
+/// Validate that a number is finite and non-negative.
+///
+/// # Arguments
+///
+/// - `errors` - The list of failed fields.
+/// - `field` - The name of the field.
+/// - `value` - The value of the field.
+pub fn non_negative(errors: &mut Vec<FieldError>, field: &'static str, value: f64) {
+    if !value.is_finite() || value < 0.0 {
+        errors.push(FieldError {
+            field,
+            message: "must be a non-negative finite number",
+        });
+    }
+}
+
+/// Validate that a number is a minute of the day.
+///
+/// # Arguments
+///
+/// - `errors` - The list of failed fields.
+/// - `field` - The name of the field.
+/// - `value` - The value of the field.
+pub fn minute_of_day(errors: &mut Vec<FieldError>, field: &'static str, value: u32) {
+    if value >= 24 * 60 {
+        errors.push(FieldError {
+            field,
+            message: "must be a minute of the day, below 1440",
+        });
+    }
+}
+
+/// Validate the page size of a paginated request.
+///
+/// # Arguments
+///
+/// - `errors` - The list of failed fields.
+/// - `size` - The page size.
+pub fn page_size(errors: &mut Vec<FieldError>, size: usize) {
+    if size == 0 || size > MAX_PAGE_SIZE {
+        errors.push(FieldError {
+            field: "size",
+            message: "must be between 1 and 100",
+        });
+    }
+}
+
+/// Turn validation failures into an API error.
+///
+/// # Arguments
+///
+/// - `input` - The request input.
+///
+/// # Returns
+///
+/// A `422 Unprocessable Entity` error listing the failed fields.
+fn check<T: Validate>(input: &T) -> Result<(), ApiError> {
+    let errors = input.validate();
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    Err(ApiError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+        "Request is invalid",
+    )
+    .with_details(json!({ "fields": errors })))
+}
+
+/// A JSON request body that is validated before reaching the handler.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(input) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|rejection| {
+                ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "invalid_body",
+                    rejection.body_text(),
+                )
+            })?;
+
+        check(&input)?;
+
+        Ok(ValidatedJson(input))
+    }
+}
+
+/// Query parameters that are validated before reaching the handler.
+pub struct ValidatedQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(input) =
+            Query::<T>::from_request_parts(parts, state)
+                .await
+                .map_err(|rejection| {
+                    ApiError::new(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        "invalid_query",
+                        rejection.body_text(),
+                    )
+                })?;
+
+        check(&input)?;
+
+        Ok(ValidatedQuery(input))
+    }
+}
@@ -0,0 +1,10 @@
+/// Compile the protocol buffers into the gRPC server, with the vendored `protoc`.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/blockchain.proto"], &["proto"])?;
+
+    Ok(())
+}
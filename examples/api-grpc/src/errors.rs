@@ -0,0 +1,92 @@
+use blockchain::ChainError;
+use tonic::{Code, Status};
+
+/// Convert a blockchain error into a gRPC status.
+///
+/// # Arguments
+///
+/// - `err` - The blockchain error.
+///
+/// # Returns
+///
+/// The status with the code matching the error and its message.
+pub fn status(err: ChainError) -> Status {
+    let code = match err {
+        ChainError::InvalidEmail
+        | ChainError::InvalidPublicKey
+        | ChainError::InvalidMnemonic(_)
+        | ChainError::InvalidKeyRotation(_)
+        | ChainError::InvalidMultiSig(_)
+        | ChainError::UnknownSender(_)
+        | ChainError::UnknownReceiver(_)
+        | ChainError::UnknownFeePayer(_)
+        | ChainError::SelfTransfer
+        | ChainError::InvalidAmount
+        | ChainError::FeeTooLow
+        | ChainError::RelayFeeTooLow
+        | ChainError::InvalidHash
+        | ChainError::MemoTooLong { .. }
+        | ChainError::InvalidInputs(_)
+        | ChainError::InvalidDifficulty
+        | ChainError::InvalidReward
+        | ChainError::InvalidFee
+        | ChainError::InvalidMilestone(_)
+        | ChainError::InvalidContract(_)
+        | ChainError::InvalidGas(_)
+        | ChainError::InvalidAsset(_)
+        | ChainError::InvalidBlock(_) => Code::InvalidArgument,
+        ChainError::DuplicateEmail(_)
+        | ChainError::AddressTaken(_)
+        | ChainError::DuplicateTransaction => Code::AlreadyExists,
+        ChainError::InsufficientBalance
+        | ChainError::InvalidNonce { .. }
+        | ChainError::DoubleSpend(_)
+        | ChainError::InvalidScript(_)
+        | ChainError::InvalidStake(_)
+        | ChainError::ContractReverted(_)
+        | ChainError::UnknownParent
+        | ChainError::InsufficientWork => Code::FailedPrecondition,
+        ChainError::SignatureRequired
+        | ChainError::InvalidSignature
+        | ChainError::UnregisteredKey(_)
+        | ChainError::InsufficientApprovals { .. } => Code::Unauthenticated,
+        ChainError::UnknownProposal(_)
+        | ChainError::UnknownContract(_)
+        | ChainError::UnknownAsset(_) => Code::NotFound,
+        ChainError::PolicyViolation(_) | ChainError::TransactionRejected(_) => {
+            Code::PermissionDenied
+        }
+        ChainError::MempoolFull { .. } | ChainError::OutOfGas { .. } => Code::ResourceExhausted,
+        ChainError::MiningCancelled => Code::Aborted,
+        ChainError::NotValidator => Code::PermissionDenied,
+        ChainError::ChainPaused(_) => Code::Unavailable,
+        ChainError::InvariantViolation(_) => Code::Internal,
+    };
+
+    Status::new(code, err.to_string())
+}
+
+/// The wallet does not exist.
+pub fn wallet_not_found() -> Status {
+    Status::not_found("Wallet is not found")
+}
+
+/// The transaction does not exist.
+pub fn transaction_not_found() -> Status {
+    Status::not_found("Transaction is not found")
+}
+
+/// The block does not exist.
+pub fn block_not_found() -> Status {
+    Status::not_found("Block is not found")
+}
+
+/// The secret key signing a transaction is not 32 hex encoded bytes.
+pub fn invalid_secret() -> Status {
+    Status::invalid_argument("Secret key is invalid")
+}
+
+/// The lock of the blockchain was poisoned by a panicking request.
+pub fn unavailable() -> Status {
+    Status::unavailable("Blockchain is unavailable")
+}
@@ -0,0 +1,46 @@
+// Every gRPC handler returns a `tonic::Status` error, which cannot be boxed
+#![allow(clippy::result_large_err)]
+
+use std::env;
+
+use blockchain::Chain;
+use tonic::transport::Server;
+
+use crate::{
+    proto::{
+        blocks_server::BlocksServer, transactions_server::TransactionsServer,
+        wallets_server::WalletsServer,
+    },
+    service::Node,
+};
+
+mod errors;
+mod service;
+
+/// The messages and services generated from the protocol buffers.
+pub mod proto {
+    tonic::include_proto!("blockchain");
+}
+
+/// The address the server listens on when `GRPC_ADDRESS` is not set.
+const DEFAULT_ADDRESS: &str = "0.0.0.0:50051";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let address = env::var("GRPC_ADDRESS")
+        .unwrap_or_else(|_| DEFAULT_ADDRESS.to_string())
+        .parse()?;
+
+    let node = Node::new(Chain::new(2.0, 100.0, 0.01));
+
+    println!("Listening on {}", address);
+
+    Server::builder()
+        .add_service(WalletsServer::new(node.clone()))
+        .add_service(TransactionsServer::new(node.clone()))
+        .add_service(BlocksServer::new(node))
+        .serve(address)
+        .await?;
+
+    Ok(())
+}
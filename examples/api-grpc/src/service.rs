@@ -0,0 +1,304 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use blockchain::{Chain, Keypair};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    errors,
+    proto::{
+        blocks_server, transactions_server, wallets_server, AddTransactionRequest,
+        AddTransactionResponse, Block, BlockPayout, CreateWalletRequest, CreateWalletResponse,
+        GetBalanceRequest, GetBalanceResponse, GetBlockRequest, GetPendingTransactionsRequest,
+        GetTransactionRequest, GetWalletTransactionsRequest, MineBlockRequest,
+        SubscribeBlocksRequest, Transaction, TransactionList,
+    },
+};
+
+/// The number of mined blocks buffered for the slowest subscriber.
+const BLOCKS_CAPACITY: usize = 64;
+
+/// The state shared by the gRPC services.
+#[derive(Clone)]
+pub struct Node {
+    /// The blockchain.
+    chain: Arc<Mutex<Chain>>,
+
+    /// The sender of the newly mined blocks.
+    blocks: broadcast::Sender<Block>,
+}
+
+impl Node {
+    /// Create a new node serving a blockchain.
+    ///
+    /// # Arguments
+    ///
+    /// - `chain` - The blockchain.
+    ///
+    /// # Returns
+    ///
+    /// A new node without block subscribers.
+    pub fn new(chain: Chain) -> Self {
+        let (blocks, _) = broadcast::channel(BLOCKS_CAPACITY);
+
+        Node {
+            chain: Arc::new(Mutex::new(chain)),
+            blocks,
+        }
+    }
+
+    /// Lock the blockchain.
+    ///
+    /// # Returns
+    ///
+    /// The guard of the blockchain, or an `unavailable` status if the lock is poisoned.
+    fn chain(&self) -> Result<MutexGuard<'_, Chain>, Status> {
+        self.chain.lock().map_err(|_| errors::unavailable())
+    }
+}
+
+impl From<&blockchain::Transaction> for Transaction {
+    fn from(transaction: &blockchain::Transaction) -> Self {
+        Transaction {
+            hash: transaction.hash.to_owned(),
+            from: transaction.from.to_owned(),
+            to: transaction.to.to_owned(),
+            amount: transaction.amount,
+            fee: transaction.fee,
+            timestamp: transaction.timestamp,
+        }
+    }
+}
+
+impl From<blockchain::BlockPayout> for BlockPayout {
+    fn from(payout: blockchain::BlockPayout) -> Self {
+        BlockPayout {
+            subsidy: payout.subsidy,
+            fees: payout.fees,
+            miner_payout: payout.miner_payout,
+            burned: payout.burned,
+        }
+    }
+}
+
+impl Block {
+    /// Convert a block of the blockchain into its message.
+    ///
+    /// # Arguments
+    ///
+    /// - `height` - The height of the block.
+    /// - `block` - The block.
+    ///
+    /// # Returns
+    ///
+    /// The block message.
+    fn new(height: usize, block: &blockchain::Block) -> Self {
+        Block {
+            height: height as u64,
+            hash: block.hash(),
+            previous_hash: block.header.previous_hash.to_owned(),
+            merkle: block.header.merkle.to_owned(),
+            timestamp: block.header.timestamp,
+            nonce: block.header.nonce,
+            difficulty: block.header.difficulty,
+            transactions: block.transactions.iter().map(Transaction::from).collect(),
+            payout: block.payout().map(BlockPayout::from),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl wallets_server::Wallets for Node {
+    async fn create_wallet(
+        &self,
+        request: Request<CreateWalletRequest>,
+    ) -> Result<Response<CreateWalletResponse>, Status> {
+        let request = request.into_inner();
+        let email = request.email;
+        let mut chain = self.chain()?;
+
+        // Reject empty emails before creating the wallet
+        if email.trim().is_empty() {
+            return Err(errors::status(blockchain::ChainError::InvalidEmail));
+        }
+
+        let address = match request.public_key.is_empty() {
+            true => chain
+                .create_wallet_idempotent(email)
+                .map_err(errors::status)?,
+            false => chain
+                .create_wallet_with_key(email, &request.public_key)
+                .map_err(errors::status)?,
+        };
+
+        Ok(Response::new(CreateWalletResponse { address }))
+    }
+
+    async fn get_balance(
+        &self,
+        request: Request<GetBalanceRequest>,
+    ) -> Result<Response<GetBalanceResponse>, Status> {
+        let address = request.into_inner().address;
+        let balance = self
+            .chain()?
+            .get_wallet_balance(address)
+            .ok_or_else(errors::wallet_not_found)?;
+
+        Ok(Response::new(GetBalanceResponse { balance }))
+    }
+
+    async fn get_wallet_transactions(
+        &self,
+        request: Request<GetWalletTransactionsRequest>,
+    ) -> Result<Response<TransactionList>, Status> {
+        let request = request.into_inner();
+        let transactions = self
+            .chain()?
+            .get_wallet_transactions(
+                request.address,
+                request.page as usize,
+                request.size as usize,
+            )
+            .ok_or_else(errors::wallet_not_found)?;
+
+        Ok(Response::new(TransactionList {
+            transactions: transactions.iter().map(Transaction::from).collect(),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl transactions_server::Transactions for Node {
+    async fn add_transaction(
+        &self,
+        request: Request<AddTransactionRequest>,
+    ) -> Result<Response<AddTransactionResponse>, Status> {
+        let request = request.into_inner();
+        let keypair = Keypair::from_secret(&request.secret).ok_or_else(errors::invalid_secret)?;
+        let hash = self
+            .chain()?
+            .add_transaction(request.from, request.to, request.amount, &keypair)
+            .map_err(errors::status)?;
+
+        Ok(Response::new(AddTransactionResponse { hash }))
+    }
+
+    async fn get_transaction(
+        &self,
+        request: Request<GetTransactionRequest>,
+    ) -> Result<Response<Transaction>, Status> {
+        let hash = request.into_inner().hash;
+        let chain = self.chain()?;
+
+        // Look for the transaction in the pending transactions, then in the mined blocks
+        let transaction = chain
+            .get_transaction(hash.to_owned())
+            .or_else(|| {
+                chain
+                    .find_transaction_in_chain(&hash)
+                    .map(|(_, transaction)| transaction)
+            })
+            .ok_or_else(errors::transaction_not_found)?;
+
+        Ok(Response::new(Transaction::from(transaction)))
+    }
+
+    async fn get_pending_transactions(
+        &self,
+        _request: Request<GetPendingTransactionsRequest>,
+    ) -> Result<Response<TransactionList>, Status> {
+        let transactions = self
+            .chain()?
+            .get_pending_transactions()
+            .iter()
+            .map(Transaction::from)
+            .collect();
+
+        Ok(Response::new(TransactionList { transactions }))
+    }
+}
+
+/// The stream of blocks sent to a subscriber.
+type BlockStream = Pin<Box<dyn Stream<Item = Result<Block, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl blocks_server::Blocks for Node {
+    async fn mine_block(
+        &self,
+        request: Request<MineBlockRequest>,
+    ) -> Result<Response<Block>, Status> {
+        let miner = request.into_inner().miner;
+        let node = self.clone();
+
+        // Mine outside of the async runtime, the proof of work being CPU-bound
+        let block = tokio::task::spawn_blocking(move || {
+            let mut chain = node.chain()?;
+
+            match miner.is_empty() {
+                true => chain.generate_new_block(),
+                false => chain.generate_new_block_for(&miner),
+            }
+            .map_err(errors::status)?;
+
+            let height = chain.get_all_blocks().len() - 1;
+            let block = Block::new(height, &chain.get_all_blocks()[height]);
+
+            // Notify the subscribers while holding the lock, so they receive the blocks in order
+            let _ = node.blocks.send(block.to_owned());
+
+            Ok::<_, Status>(block)
+        })
+        .await
+        .map_err(|err| Status::internal(err.to_string()))??;
+
+        Ok(Response::new(block))
+    }
+
+    async fn get_block(
+        &self,
+        request: Request<GetBlockRequest>,
+    ) -> Result<Response<Block>, Status> {
+        let height = request.into_inner().height as usize;
+        let chain = self.chain()?;
+        let block = chain
+            .get_block(height)
+            .ok_or_else(errors::block_not_found)?;
+
+        Ok(Response::new(Block::new(height, block)))
+    }
+
+    type SubscribeBlocksStream = BlockStream;
+
+    async fn subscribe_blocks(
+        &self,
+        request: Request<SubscribeBlocksRequest>,
+    ) -> Result<Response<Self::SubscribeBlocksStream>, Status> {
+        let from_height = request.into_inner().from_height;
+        let chain = self.chain()?;
+
+        // Subscribe while holding the lock, so no block is mined between the replay and the feed
+        let receiver = self.blocks.subscribe();
+        let blocks = chain.get_all_blocks();
+        let start = from_height.map_or(blocks.len(), |height| height as usize);
+        let replay: Vec<Result<Block, Status>> = blocks
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(height, block)| Ok(Block::new(height, block)))
+            .collect();
+
+        drop(chain);
+
+        // Fail the stream of a subscriber too slow to keep up with the mined blocks
+        let feed = BroadcastStream::new(receiver)
+            .map(|block| block.map_err(|err| Status::data_loss(err.to_string())));
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::iter(replay).chain(feed),
+        )))
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use blockchain::{ChainRegistry, Keypair, TransactionProof};
+
+/// A one-way link between two chains: funds locked on the source chain are released on the target chain.
+pub struct Route {
+    /// The identifier of the chain funds are locked on.
+    pub source: String,
+
+    /// The wallet holding the locked funds on the source chain.
+    pub vault: String,
+
+    /// The identifier of the chain funds are released on.
+    pub target: String,
+
+    /// The wallet paying out the released funds on the target chain.
+    pub treasury: String,
+
+    /// The keypair owning the treasury wallet, signing the payouts.
+    pub keypair: Keypair,
+}
+
+/// A relayer moving funds between two chains of a registry.
+///
+/// Funds are locked by paying the escrow wallet of chain A, which is proven to the bridge with a
+/// Merkle proof and the header of the block. Wrapped funds are then paid out on chain B from a
+/// reserve wallet funded when the bridge is created. Burning wrapped funds back to the reserve
+/// releases the escrowed funds on chain A the same way.
+pub struct Bridge {
+    /// The route from chain A to chain B.
+    lock: Route,
+
+    /// The route from chain B back to chain A.
+    release: Route,
+
+    /// The hashes of the transactions already relayed, to prevent replays.
+    relayed: HashSet<String>,
+}
+
+impl Bridge {
+    /// Create a bridge between two chains of a registry.
+    ///
+    /// # Arguments
+    ///
+    /// - `registry` - The registry holding both chains.
+    /// - `a` - The identifier of the chain holding the original funds.
+    /// - `b` - The identifier of the chain holding the wrapped funds.
+    /// - `supply` - The amount of wrapped funds the bridge can pay out.
+    ///
+    /// # Returns
+    ///
+    /// A new bridge, or `None` if a chain is missing.
+    pub fn new(registry: &mut ChainRegistry, a: &str, b: &str, supply: f64) -> Option<Self> {
+        let (escrow_keypair, reserve_keypair) = (Keypair::generate(), Keypair::generate());
+        let escrow = registry
+            .get_chain_mut(a)?
+            .create_wallet_with_key("escrow@bridge".to_string(), &escrow_keypair.public_key())
+            .ok()?;
+
+        let chain = registry.get_chain_mut(b)?;
+        let reserve = chain
+            .create_wallet_with_key("reserve@bridge".to_string(), &reserve_keypair.public_key())
+            .ok()?;
+
+        // Back the wrapped funds, there is no token issuance on the chain
+        chain.credit_wallet(&reserve, supply);
+        chain.generate_new_block().ok()?;
+
+        Some(Bridge {
+            lock: Route {
+                source: a.to_string(),
+                vault: escrow.to_owned(),
+                target: b.to_string(),
+                treasury: reserve.to_owned(),
+                keypair: reserve_keypair,
+            },
+            release: Route {
+                source: b.to_string(),
+                vault: reserve,
+                target: a.to_string(),
+                treasury: escrow,
+                keypair: escrow_keypair,
+            },
+            relayed: HashSet::new(),
+        })
+    }
+
+    /// Get the escrow wallet on chain A, paying it locks funds.
+    ///
+    /// # Returns
+    ///
+    /// The escrow wallet address.
+    pub fn escrow(&self) -> &str {
+        &self.lock.vault
+    }
+
+    /// Get the reserve wallet on chain B, paying it burns wrapped funds.
+    ///
+    /// # Returns
+    ///
+    /// The reserve wallet address.
+    pub fn reserve(&self) -> &str {
+        &self.release.vault
+    }
+
+    /// Pay out wrapped funds on chain B for funds locked on chain A.
+    ///
+    /// # Arguments
+    ///
+    /// - `registry` - The registry holding both chains.
+    /// - `proof` - The proof of the payment to the escrow wallet.
+    /// - `recipient` - The wallet receiving the wrapped funds on chain B.
+    ///
+    /// # Returns
+    ///
+    /// The reason of the failure if the proof is refused.
+    pub fn mint(
+        &mut self,
+        registry: &mut ChainRegistry,
+        proof: &TransactionProof,
+        recipient: &str,
+    ) -> Result<(), String> {
+        Bridge::relay(&self.lock, &mut self.relayed, registry, proof, recipient)
+    }
+
+    /// Release funds on chain A for wrapped funds burned on chain B.
+    ///
+    /// # Arguments
+    ///
+    /// - `registry` - The registry holding both chains.
+    /// - `proof` - The proof of the payment to the reserve wallet.
+    /// - `recipient` - The wallet receiving the released funds on chain A.
+    ///
+    /// # Returns
+    ///
+    /// The reason of the failure if the proof is refused.
+    pub fn release(
+        &mut self,
+        registry: &mut ChainRegistry,
+        proof: &TransactionProof,
+        recipient: &str,
+    ) -> Result<(), String> {
+        Bridge::relay(&self.release, &mut self.relayed, registry, proof, recipient)
+    }
+
+    /// Verify a proof of a payment on the source chain and pay the amount on the target chain.
+    ///
+    /// # Arguments
+    ///
+    /// - `route` - The route of the funds.
+    /// - `relayed` - The hashes of the transactions already relayed.
+    /// - `registry` - The registry holding both chains.
+    /// - `proof` - The proof of the payment on the source chain.
+    /// - `recipient` - The wallet receiving the funds on the target chain.
+    ///
+    /// # Returns
+    ///
+    /// The reason of the failure if the proof is refused.
+    fn relay(
+        route: &Route,
+        relayed: &mut HashSet<String>,
+        registry: &mut ChainRegistry,
+        proof: &TransactionProof,
+        recipient: &str,
+    ) -> Result<(), String> {
+        let source = registry
+            .get_chain(&route.source)
+            .ok_or("Source chain not found")?;
+
+        // Validate if the header is part of the source chain
+        let block = source
+            .get_all_blocks()
+            .get(proof.height)
+            .ok_or("Block not found on the source chain")?;
+
+        if block.header.pow_hash() != proof.header.pow_hash() {
+            return Err("Header does not belong to the source chain".to_string());
+        }
+
+        // Validate if the transaction is included in the block
+        if !proof.verify() {
+            return Err("Invalid Merkle proof".to_string());
+        }
+
+        if proof.transaction.to != route.vault {
+            return Err("Transaction does not pay the bridge".to_string());
+        }
+
+        if !relayed.insert(proof.transaction.hash.to_owned()) {
+            return Err("Transaction was already relayed".to_string());
+        }
+
+        let target = registry
+            .get_chain_mut(&route.target)
+            .ok_or("Target chain not found")?;
+
+        if let Err(err) = target.add_transaction(
+            route.treasury.to_owned(),
+            recipient.to_string(),
+            proof.transaction.amount,
+            &route.keypair,
+        ) {
+            relayed.remove(&proof.transaction.hash);
+
+            return Err(format!("Payout was rejected by the target chain: {}", err));
+        }
+
+        target.generate_new_block().map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+}
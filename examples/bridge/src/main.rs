@@ -0,0 +1,116 @@
+use blockchain::{Chain, ChainRegistry, Keypair};
+
+use crate::bridge::Bridge;
+
+mod bridge;
+
+/// Print the balances of a wallet on both chains.
+///
+/// # Arguments
+///
+/// - `registry` - The registry holding both chains.
+/// - `label` - The label of the step.
+/// - `a` - The wallet address on chain A.
+/// - `b` - The wallet address on chain B.
+fn print_balances(registry: &ChainRegistry, label: &str, a: &str, b: &str) {
+    let balance = |id: &str, address: &str| {
+        registry
+            .get_chain(id)
+            .and_then(|chain| chain.get_wallet_balance(address.to_string()))
+            .unwrap_or_default()
+    };
+
+    println!(
+        "{:<10} chain A: {:>6.2}  chain B (wrapped): {:>6.2}",
+        label,
+        balance("a", a),
+        balance("b", b)
+    );
+}
+
+/// Pay a wallet and mine the transaction.
+///
+/// # Arguments
+///
+/// - `chain` - The blockchain.
+/// - `from` - The sender address.
+/// - `to` - The receiver address.
+/// - `amount` - The amount of the payment.
+/// - `keypair` - The keypair owning the sender wallet.
+///
+/// # Returns
+///
+/// The hash of the mined transaction.
+fn pay(chain: &mut Chain, from: &str, to: &str, amount: f64, keypair: &Keypair) -> String {
+    let hash = chain
+        .add_transaction(from.to_string(), to.to_string(), amount, keypair)
+        .unwrap();
+
+    chain.generate_new_block().unwrap();
+
+    hash
+}
+
+/// The main function.
+fn main() {
+    // Fees are disabled so the bridge wallets can pay out exactly what they received
+    let mut registry = ChainRegistry::new();
+    registry.create_chain("a".to_string(), 1.0, 100.0, 0.0);
+    registry.create_chain("b".to_string(), 1.0, 100.0, 0.0);
+
+    let mut bridge = Bridge::new(&mut registry, "a", "b", 1_000.0).unwrap();
+
+    // Alice owns a wallet on both chains with the same keypair
+    let alice = Keypair::generate();
+    let chain = registry.get_chain_mut("a").unwrap();
+    let alice_a = chain
+        .create_wallet_with_key("alice@mail.com".to_string(), &alice.public_key())
+        .unwrap();
+    chain.credit_wallet(&alice_a, 50.0);
+    chain.generate_new_block().unwrap();
+
+    let alice_b = registry
+        .get_chain_mut("b")
+        .unwrap()
+        .create_wallet_with_key("alice@mail.com".to_string(), &alice.public_key())
+        .unwrap();
+
+    print_balances(&registry, "start", &alice_a, &alice_b);
+
+    // Lock 20 on chain A and prove it to the bridge
+    let chain = registry.get_chain_mut("a").unwrap();
+    let hash = pay(chain, &alice_a, bridge.escrow(), 20.0, &alice);
+    let proof = chain.get_transaction_proof(&hash).unwrap();
+
+    bridge.mint(&mut registry, &proof, &alice_b).unwrap();
+    print_balances(&registry, "locked", &alice_a, &alice_b);
+
+    // The same proof cannot be relayed twice
+    match bridge.mint(&mut registry, &proof, &alice_b) {
+        Ok(()) => println!("replayed proof was accepted"),
+        Err(err) => println!("replayed proof was refused: {}", err),
+    }
+
+    // A proof of a tampered transaction is refused
+    let mut forged = proof.clone();
+    forged.transaction.amount = 500.0;
+
+    match bridge.mint(&mut registry, &forged, &alice_b) {
+        Ok(()) => println!("forged proof was accepted"),
+        Err(err) => println!("forged proof was refused: {}", err),
+    }
+
+    // Burn 15 wrapped funds on chain B to release them on chain A
+    let chain = registry.get_chain_mut("b").unwrap();
+    let hash = pay(chain, &alice_b, bridge.reserve(), 15.0, &alice);
+    let proof = chain.get_transaction_proof(&hash).unwrap();
+
+    bridge.release(&mut registry, &proof, &alice_a).unwrap();
+    print_balances(&registry, "released", &alice_a, &alice_b);
+
+    for id in ["a", "b"] {
+        let chain = registry.get_chain(id).unwrap();
+
+        assert_eq!(chain.check_invariants(), Ok(()));
+    }
+}
@@ -1,4 +1,4 @@
-use blockchain::Chain;
+use blockchain::{Chain, Keypair, MAX_MEMO_LENGTH};
 use cliclack::spinner;
 
 /// The main function.
@@ -45,7 +45,7 @@ fn main() -> std::io::Result<()> {
 
     spinner.stop(format!(
         "✅ Blockchain was created successfully: {}",
-        chain.address
+        chain.get_address()
     ));
 
     loop {
@@ -83,9 +83,16 @@ fn main() -> std::io::Result<()> {
                 let confirm = cliclack::confirm("Confirm creating a wallet").interact()?;
 
                 if confirm {
-                    let address = chain.create_wallet(email);
-
-                    println!("✅ Wallet was created successfully: {}", address)
+                    let keypair = Keypair::generate();
+
+                    match chain.create_wallet_with_key(email, &keypair.public_key()) {
+                        Ok(address) => println!(
+                            "✅ Wallet was created successfully: {}, secret key: {}",
+                            address,
+                            keypair.secret()
+                        ),
+                        Err(err) => println!("❌ Cannot create a wallet: {}", err),
+                    }
                 }
             }
             "get_wallet_balance" => {
@@ -135,6 +142,14 @@ fn main() -> std::io::Result<()> {
                     })
                     .interact()?;
 
+                let keypair: Keypair = cliclack::input("Sender secret key")
+                    .validate(|input: &String| match Keypair::from_secret(input.trim()) {
+                        Some(_) => Ok(()),
+                        None => Err("Please enter a valid secret key"),
+                    })
+                    .interact::<String>()
+                    .map(|input| Keypair::from_secret(input.trim()).unwrap())?;
+
                 let receiver: String = cliclack::input("Receiver")
                     .validate(|input: &String| {
                         if input.is_empty() {
@@ -155,18 +170,36 @@ fn main() -> std::io::Result<()> {
                     })
                     .interact()?;
 
+                let memo = if cliclack::confirm("Attach a memo").interact()? {
+                    let memo: String = cliclack::input("Memo")
+                        .validate(|input: &String| {
+                            if input.len() > MAX_MEMO_LENGTH {
+                                Err("Please enter a shorter memo")
+                            } else {
+                                Ok(())
+                            }
+                        })
+                        .interact()?;
+
+                    Some(memo)
+                } else {
+                    None
+                };
+
                 let confirm = cliclack::confirm("Confirm adding a transaction").interact()?;
 
                 if confirm {
-                    let res = chain.add_transaction(
+                    let res = chain.add_transaction_with_memo(
                         sender.trim().to_string(),
                         receiver.trim().to_string(),
                         amount,
+                        memo,
+                        &keypair,
                     );
 
                     match res {
-                        true => println!("✅ Transaction was added successfully"),
-                        false => println!("❌ Cannot add a transaction"),
+                        Ok(hash) => println!("✅ Transaction {} was added successfully", hash),
+                        Err(err) => println!("❌ Cannot add a transaction: {}", err),
                     }
                 }
             }
@@ -194,8 +227,8 @@ fn main() -> std::io::Result<()> {
             "generate_block" => {
                 let res = chain.generate_new_block();
                 match res {
-                    true => println!("✅ Block was generated successfully"),
-                    false => println!("❌ Cannot generate a block"),
+                    Ok(()) => println!("✅ Block was generated successfully"),
+                    Err(err) => println!("❌ Cannot generate a block: {}", err),
                 }
             }
             "change_reward" => {
@@ -215,8 +248,8 @@ fn main() -> std::io::Result<()> {
                     let res = chain.update_reward(new_reward.trim().parse().unwrap());
 
                     match res {
-                        true => println!("✅ Reward was changed successfully"),
-                        false => println!("❌ Cannot change a reward"),
+                        Ok(()) => println!("✅ Reward was changed successfully"),
+                        Err(err) => println!("❌ Cannot change a reward: {}", err),
                     }
                 }
             }
@@ -237,8 +270,8 @@ fn main() -> std::io::Result<()> {
                     let res = chain.update_difficulty(new_difficulty);
 
                     match res {
-                        true => println!("✅ Difficulty was changed successfully"),
-                        false => println!("❌ Cannot change a difficulty"),
+                        Ok(()) => println!("✅ Difficulty was changed successfully"),
+                        Err(err) => println!("❌ Cannot change a difficulty: {}", err),
                     }
                 }
             }
@@ -259,8 +292,8 @@ fn main() -> std::io::Result<()> {
                     let res = chain.update_fee(new_fee.trim().parse().unwrap());
 
                     match res {
-                        true => println!("✅ Transaction fee was changed successfully"),
-                        false => println!("❌ Cannot change a transaction fee"),
+                        Ok(()) => println!("✅ Transaction fee was changed successfully"),
+                        Err(err) => println!("❌ Cannot change a transaction fee: {}", err),
                     }
                 }
             }
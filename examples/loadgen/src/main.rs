@@ -0,0 +1,201 @@
+use std::{
+    env, process, thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    report::Report,
+    scenario::{Generator, Operation, Scenario},
+    target::{InProcess, Outcome, Rest, Target},
+};
+
+mod report;
+mod scenario;
+mod target;
+
+/// The usage of the load generator.
+const USAGE: &str = "Usage: loadgen [--target in-process|rest] [--url URL] [--chain ID]
+               [--wallets N] [--operations N] [--balance AMOUNT] [--zipf S]
+               [--churn P] [--reads P] [--rate OPS] [--burst-every N]
+               [--burst-size N] [--mine-every N] [--mempool-bytes N] [--seed N]";
+
+/// The settings of a run.
+struct Args {
+    /// The kind of target: `in-process` or `rest`.
+    target: String,
+
+    /// The base URL of the REST API.
+    url: String,
+
+    /// The chain identifier on the REST API.
+    chain: String,
+
+    /// The amount of pending transactions that triggers a new block on an in-process chain.
+    mine_every: usize,
+
+    /// The cap on the memory of the pending transactions of an in-process chain, `0` for no cap.
+    mempool_bytes: usize,
+
+    /// The settings of the scenario.
+    scenario: Scenario,
+}
+
+/// Parse a flag value.
+///
+/// # Arguments
+///
+/// - `name` - The flag name.
+/// - `value` - The flag value.
+///
+/// # Returns
+///
+/// The parsed value, exiting with the usage when it is missing or invalid.
+fn parse<T: std::str::FromStr>(name: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("Invalid value for {}\n{}", name, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+/// Parse the command line arguments.
+///
+/// # Returns
+///
+/// The settings of the run, exiting with the usage when they are invalid.
+fn parse_args() -> Args {
+    let mut args = Args {
+        target: "in-process".to_string(),
+        url: "http://localhost:7878".to_string(),
+        chain: "default".to_string(),
+        mine_every: 1000,
+        mempool_bytes: 0,
+        scenario: Scenario::default(),
+    };
+
+    let mut input = env::args().skip(1);
+
+    while let Some(name) = input.next() {
+        let value = input.next();
+
+        match name.as_str() {
+            "--target" => args.target = parse(&name, value),
+            "--url" => args.url = parse(&name, value),
+            "--chain" => args.chain = parse(&name, value),
+            "--mine-every" => args.mine_every = parse(&name, value),
+            "--mempool-bytes" => args.mempool_bytes = parse(&name, value),
+            "--wallets" => args.scenario.wallets = parse(&name, value),
+            "--operations" => args.scenario.operations = parse(&name, value),
+            "--balance" => args.scenario.balance = parse(&name, value),
+            "--zipf" => args.scenario.zipf = parse(&name, value),
+            "--churn" => args.scenario.churn = parse(&name, value),
+            "--reads" => args.scenario.reads = parse(&name, value),
+            "--rate" => args.scenario.rate = parse(&name, value),
+            "--burst-every" => args.scenario.burst_every = parse(&name, value),
+            "--burst-size" => args.scenario.burst_size = parse(&name, value),
+            "--seed" => args.scenario.seed = parse(&name, value),
+            _ => {
+                eprintln!("Unknown argument {}\n{}", name, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    // Payments need two distinct wallets
+    if args.scenario.wallets < 2 {
+        eprintln!("At least 2 wallets are required\n{}", USAGE);
+        process::exit(2);
+    }
+
+    args
+}
+
+/// The main function.
+fn main() {
+    let args = parse_args();
+
+    let mut target: Box<dyn Target> = match args.target.as_str() {
+        "in-process" => Box::new(InProcess::new(args.mine_every, args.mempool_bytes)),
+        "rest" => Box::new(Rest::new(&args.url, &args.chain)),
+        _ => {
+            eprintln!("Unknown target {}\n{}", args.target, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let scenario = args.scenario.clone();
+    let mut generator = Generator::new(args.scenario);
+
+    // Create the initial wallets, outside of the measured traffic
+    let mut wallets = Vec::with_capacity(scenario.wallets);
+
+    for _ in 0..scenario.wallets {
+        let email = generator.next_email();
+
+        match target.create_wallet(&email, scenario.balance) {
+            Ok(address) => wallets.push(address),
+            Err(outcome) => {
+                eprintln!("Unable to create the initial wallets: {:?}", outcome);
+                process::exit(1);
+            }
+        }
+    }
+
+    println!(
+        "Running {} operations against {} wallets ({})",
+        scenario.operations, scenario.wallets, args.target
+    );
+
+    let mut report = Report::default();
+    let interval = match scenario.rate {
+        0 => Duration::ZERO,
+        rate => Duration::from_secs_f64(1.0 / rate as f64),
+    };
+    let start = Instant::now();
+    let mut next = start;
+
+    for index in 0..scenario.operations {
+        let operation = generator.next_operation();
+
+        // Pace the steady traffic, latencies include the time spent behind the schedule
+        let scheduled = match interval.is_zero() || generator.in_burst(index) {
+            true => {
+                next = Instant::now();
+                next
+            }
+            false => {
+                next += interval;
+
+                if let Some(wait) = next.checked_duration_since(Instant::now()) {
+                    thread::sleep(wait);
+                }
+
+                next
+            }
+        };
+
+        let outcome = match &operation {
+            Operation::CreateWallet { slot, email } => {
+                match target.create_wallet(email, scenario.balance) {
+                    Ok(address) => {
+                        wallets[*slot] = address;
+                        Outcome::Accepted
+                    }
+                    Err(outcome) => outcome,
+                }
+            }
+            Operation::Payment { from, to, amount } => {
+                target.payment(&wallets[*from], &wallets[*to], *amount)
+            }
+            Operation::ReadBalance { slot } => target.read_balance(&wallets[*slot]),
+        };
+
+        report.record(operation.kind(), outcome, scheduled.elapsed());
+
+        target.tick();
+    }
+
+    report.print(start.elapsed());
+}
@@ -0,0 +1,126 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::target::Outcome;
+
+/// The latencies and outcomes of one kind of operation.
+#[derive(Default)]
+struct Series {
+    /// The latency of every operation.
+    latencies: Vec<Duration>,
+
+    /// The amount of accepted operations.
+    accepted: usize,
+
+    /// The amount of rejected operations.
+    rejected: usize,
+
+    /// The amount of throttled operations.
+    throttled: usize,
+
+    /// The amount of failed operations.
+    failed: usize,
+}
+
+/// Collect the results of a load test.
+#[derive(Default)]
+pub struct Report {
+    /// The results grouped by operation kind.
+    series: BTreeMap<&'static str, Series>,
+}
+
+impl Report {
+    /// Record the result of an operation.
+    ///
+    /// # Arguments
+    ///
+    /// - `kind` - The operation kind.
+    /// - `outcome` - The outcome of the operation.
+    /// - `latency` - The time between the scheduled start and the end of the operation.
+    pub fn record(&mut self, kind: &'static str, outcome: Outcome, latency: Duration) {
+        let series = self.series.entry(kind).or_default();
+
+        series.latencies.push(latency);
+
+        match outcome {
+            Outcome::Accepted => series.accepted += 1,
+            Outcome::Rejected => series.rejected += 1,
+            Outcome::Throttled => series.throttled += 1,
+            Outcome::Failed => series.failed += 1,
+        }
+    }
+
+    /// Print the throughput and latency percentiles of every operation kind.
+    ///
+    /// # Arguments
+    ///
+    /// - `elapsed` - The duration of the load test.
+    pub fn print(&mut self, elapsed: Duration) {
+        let total: usize = self
+            .series
+            .values()
+            .map(|series| series.latencies.len())
+            .sum();
+
+        println!(
+            "{} operations in {:.2?} ({:.0} ops/s)",
+            total,
+            elapsed,
+            total as f64 / elapsed.as_secs_f64()
+        );
+        println!(
+            "{:<14} {:>9} {:>9} {:>9} {:>9} {:>7} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "operation",
+            "ops/s",
+            "accepted",
+            "rejected",
+            "throttled",
+            "failed",
+            "p50",
+            "p90",
+            "p99",
+            "p99.9",
+            "max"
+        );
+
+        for (kind, series) in self.series.iter_mut() {
+            series.latencies.sort_unstable();
+
+            let latencies = &series.latencies;
+
+            println!(
+                "{:<14} {:>9.0} {:>9} {:>9} {:>9} {:>7} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                kind,
+                latencies.len() as f64 / elapsed.as_secs_f64(),
+                series.accepted,
+                series.rejected,
+                series.throttled,
+                series.failed,
+                percentile(latencies, 50.0),
+                percentile(latencies, 90.0),
+                percentile(latencies, 99.0),
+                percentile(latencies, 99.9),
+                latencies.last().copied().unwrap_or_default(),
+            );
+        }
+    }
+}
+
+/// Get a percentile of sorted latencies, using the nearest rank.
+///
+/// # Arguments
+///
+/// - `latencies` - The latencies, sorted in ascending order.
+/// - `percentile` - The percentile, between `0` and `100`.
+///
+/// # Returns
+///
+/// The latency at the percentile, or zero if there are no latencies.
+fn percentile(latencies: &[Duration], percentile: f64) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = (percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+
+    latencies[rank.clamp(1, latencies.len()) - 1]
+}
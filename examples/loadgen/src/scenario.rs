@@ -0,0 +1,234 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The settings of a load-test scenario.
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    /// The amount of wallets created before the traffic starts.
+    pub wallets: usize,
+
+    /// The amount of operations to generate.
+    pub operations: usize,
+
+    /// The initial balance of every wallet.
+    pub balance: f64,
+
+    /// The exponent of the Zipf distribution used to pick payment pairs.
+    pub zipf: f64,
+
+    /// The probability of an operation to replace an active wallet with a new one.
+    pub churn: f64,
+
+    /// The probability of an operation to be a read.
+    pub reads: f64,
+
+    /// The steady rate of operations per second, `0` for no pacing.
+    pub rate: u64,
+
+    /// The amount of operations between the start of two bursts, `0` to disable bursts.
+    pub burst_every: usize,
+
+    /// The amount of operations sent back to back in a burst.
+    pub burst_size: usize,
+
+    /// The seed of the random number generator.
+    pub seed: u64,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Scenario {
+            wallets: 1000,
+            operations: 100_000,
+            balance: 1_000_000.0,
+            zipf: 1.1,
+            churn: 0.01,
+            reads: 0.2,
+            rate: 0,
+            burst_every: 10_000,
+            burst_size: 2_000,
+            seed: 42,
+        }
+    }
+}
+
+/// An operation sent to the chain under test.
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// Create a wallet replacing the active wallet at the given slot.
+    CreateWallet { slot: usize, email: String },
+
+    /// Send a payment between the wallets at the given slots.
+    Payment { from: usize, to: usize, amount: f64 },
+
+    /// Read the balance of the wallet at the given slot.
+    ReadBalance { slot: usize },
+}
+
+impl Operation {
+    /// Get the name of the operation kind, used to group the latencies.
+    ///
+    /// # Returns
+    ///
+    /// The name of the operation kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Operation::CreateWallet { .. } => "create_wallet",
+            Operation::Payment { .. } => "payment",
+            Operation::ReadBalance { .. } => "read_balance",
+        }
+    }
+}
+
+/// Sample ranks following a Zipf distribution.
+///
+/// Rank `k` is picked with a probability proportional to `1 / (k + 1)^s`, so a few wallets
+/// send and receive most of the payments, as on real payment networks.
+pub struct Zipf {
+    /// The cumulative distribution of the ranks.
+    cdf: Vec<f64>,
+}
+
+impl Zipf {
+    /// Create a new Zipf distribution.
+    ///
+    /// # Arguments
+    ///
+    /// - `n` - The amount of ranks.
+    /// - `s` - The exponent, `0` for a uniform distribution.
+    ///
+    /// # Returns
+    ///
+    /// A new Zipf distribution.
+    pub fn new(n: usize, s: f64) -> Self {
+        let mut total = 0.0;
+        let mut cdf: Vec<f64> = (0..n)
+            .map(|rank| {
+                total += 1.0 / ((rank + 1) as f64).powf(s);
+                total
+            })
+            .collect();
+
+        for value in cdf.iter_mut() {
+            *value /= total;
+        }
+
+        Zipf { cdf }
+    }
+
+    /// Sample a rank.
+    ///
+    /// # Arguments
+    ///
+    /// - `rng` - The random number generator.
+    ///
+    /// # Returns
+    ///
+    /// A rank between `0` and `n - 1`.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let value: f64 = rng.gen();
+
+        self.cdf
+            .partition_point(|&probability| probability < value)
+            .min(self.cdf.len() - 1)
+    }
+}
+
+/// Generate the operations of a scenario.
+pub struct Generator {
+    /// The settings of the scenario.
+    scenario: Scenario,
+
+    /// The distribution of the wallet slots.
+    zipf: Zipf,
+
+    /// The random number generator.
+    rng: StdRng,
+
+    /// The amount of wallets created so far, used to build unique emails.
+    created: usize,
+}
+
+impl Generator {
+    /// Create a new generator.
+    ///
+    /// # Arguments
+    ///
+    /// - `scenario` - The settings of the scenario.
+    ///
+    /// # Returns
+    ///
+    /// A new generator.
+    pub fn new(scenario: Scenario) -> Self {
+        Generator {
+            zipf: Zipf::new(scenario.wallets, scenario.zipf),
+            rng: StdRng::seed_from_u64(scenario.seed),
+            created: 0,
+            scenario,
+        }
+    }
+
+    /// Build the email of the next wallet.
+    ///
+    /// # Returns
+    ///
+    /// A unique email for this run.
+    pub fn next_email(&mut self) -> String {
+        self.created += 1;
+
+        format!(
+            "loadgen-{}-{}@example.com",
+            self.scenario.seed, self.created
+        )
+    }
+
+    /// Check whether an operation is part of a burst.
+    ///
+    /// # Arguments
+    ///
+    /// - `index` - The index of the operation.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the operation is sent without pacing.
+    pub fn in_burst(&self, index: usize) -> bool {
+        self.scenario.burst_every > 0
+            && index % self.scenario.burst_every < self.scenario.burst_size
+    }
+
+    /// Generate the next operation.
+    ///
+    /// # Returns
+    ///
+    /// A wallet creation, a payment or a read, following the scenario mix.
+    pub fn next_operation(&mut self) -> Operation {
+        let roll: f64 = self.rng.gen();
+
+        // Replace a random active wallet, so new wallets also join the popular ranks
+        if roll < self.scenario.churn {
+            let slot = self.rng.gen_range(0..self.scenario.wallets);
+            let email = self.next_email();
+
+            return Operation::CreateWallet { slot, email };
+        }
+
+        if roll < self.scenario.churn + self.scenario.reads {
+            return Operation::ReadBalance {
+                slot: self.zipf.sample(&mut self.rng),
+            };
+        }
+
+        let from = self.zipf.sample(&mut self.rng);
+        let to = loop {
+            let to = self.zipf.sample(&mut self.rng);
+
+            if to != from {
+                break to;
+            }
+        };
+
+        // Most payments are small, a few are large
+        let amount = (self.rng.gen::<f64>().powi(4) * 100.0).max(0.01);
+
+        Operation::Payment { from, to, amount }
+    }
+}
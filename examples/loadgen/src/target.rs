@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use blockchain::{Chain, ChainConfig, ChainError, Keypair, MemoryLimits};
+use serde_json::{json, Value};
+
+/// The result of an operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The chain applied the operation.
+    Accepted,
+
+    /// The chain refused the operation, e.g. a payment above the sender balance.
+    Rejected,
+
+    /// The chain asked to retry the operation later, its mempool being full.
+    Throttled,
+
+    /// The operation could not be delivered.
+    Failed,
+}
+
+/// A chain receiving the generated traffic.
+pub trait Target {
+    /// Create a wallet and fund it.
+    ///
+    /// # Arguments
+    ///
+    /// - `email` - The unique wallet email.
+    /// - `balance` - The initial balance, ignored when the target cannot issue funds.
+    ///
+    /// # Returns
+    ///
+    /// The wallet address, or the outcome of the failed request.
+    fn create_wallet(&mut self, email: &str, balance: f64) -> Result<String, Outcome>;
+
+    /// Send a payment.
+    ///
+    /// # Arguments
+    ///
+    /// - `from` - The sender address.
+    /// - `to` - The receiver address.
+    /// - `amount` - The payment amount.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the payment.
+    fn payment(&mut self, from: &str, to: &str, amount: f64) -> Outcome;
+
+    /// Read the balance of a wallet.
+    ///
+    /// # Arguments
+    ///
+    /// - `address` - The wallet address.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the read.
+    fn read_balance(&mut self, address: &str) -> Outcome;
+
+    /// Give the target a chance to drain its pending transactions.
+    fn tick(&mut self) {}
+}
+
+/// A chain living in the load generator process.
+pub struct InProcess {
+    /// The chain under test.
+    chain: Chain,
+
+    /// The amount of pending transactions that triggers a new block, `0` to never mine.
+    mine_every: usize,
+
+    /// Whether a payment was throttled since the last block, triggering a new block.
+    throttled: bool,
+
+    /// The keypairs owning the created wallets, by address.
+    keypairs: HashMap<String, Keypair>,
+}
+
+impl InProcess {
+    /// Create a new in-process target.
+    ///
+    /// # Arguments
+    ///
+    /// - `mine_every` - The amount of pending transactions that triggers a new block.
+    /// - `mempool_bytes` - The cap on the memory of the pending transactions, `0` for no cap.
+    ///
+    /// # Returns
+    ///
+    /// A new in-process target with a fresh chain.
+    pub fn new(mine_every: usize, mempool_bytes: usize) -> Self {
+        let config = ChainConfig {
+            memory: MemoryLimits {
+                mempool_bytes: (mempool_bytes > 0).then_some(mempool_bytes),
+                index_bytes: None,
+            },
+            ..ChainConfig::default()
+        };
+
+        // Keep the proof of work cheap, the traffic is what is measured
+        InProcess {
+            chain: Chain::with_config(1.0, 100.0, 0.01, config),
+            mine_every,
+            throttled: false,
+            keypairs: HashMap::new(),
+        }
+    }
+}
+
+impl Target for InProcess {
+    fn create_wallet(&mut self, email: &str, balance: f64) -> Result<String, Outcome> {
+        let keypair = Keypair::generate();
+        let address = self
+            .chain
+            .create_wallet_with_key(email.to_string(), &keypair.public_key())
+            .map_err(|_| Outcome::Rejected)?;
+
+        if balance > 0.0 && !self.chain.credit_wallet(&address, balance) {
+            return Err(Outcome::Rejected);
+        }
+
+        self.keypairs.insert(address.to_owned(), keypair);
+
+        Ok(address)
+    }
+
+    fn payment(&mut self, from: &str, to: &str, amount: f64) -> Outcome {
+        let Some(keypair) = self.keypairs.get(from) else {
+            return Outcome::Rejected;
+        };
+
+        match self
+            .chain
+            .try_add_transaction(from.to_string(), to.to_string(), amount, keypair)
+        {
+            Ok(_) => Outcome::Accepted,
+            Err(ChainError::MempoolFull { .. }) => {
+                self.throttled = true;
+                Outcome::Throttled
+            }
+            Err(_) => Outcome::Rejected,
+        }
+    }
+
+    fn read_balance(&mut self, address: &str) -> Outcome {
+        match self.chain.get_wallet_balance(address.to_string()) {
+            Some(_) => Outcome::Accepted,
+            None => Outcome::Rejected,
+        }
+    }
+
+    fn tick(&mut self) {
+        let pending = self.chain.get_pending_transactions().len();
+
+        // A full mempool is drained right away, as a miner would be prompted by the fees
+        if self.throttled || (self.mine_every > 0 && pending >= self.mine_every) {
+            self.throttled = false;
+
+            let _ = self.chain.generate_new_block();
+        }
+    }
+}
+
+/// A chain served by the REST API example.
+///
+/// The API cannot issue funds, so wallets start empty and payments are expected to be
+/// rejected unless the chain was funded beforehand.
+pub struct Rest {
+    /// The HTTP agent, reusing connections between requests.
+    agent: ureq::Agent,
+
+    /// The base URL of the chain, e.g. `http://localhost:7878/default`.
+    base: String,
+
+    /// The bearer tokens of the created wallets, by address.
+    tokens: HashMap<String, String>,
+}
+
+impl Rest {
+    /// Create a new REST target.
+    ///
+    /// # Arguments
+    ///
+    /// - `url` - The base URL of the API.
+    /// - `chain_id` - The chain identifier.
+    ///
+    /// # Returns
+    ///
+    /// A new REST target.
+    pub fn new(url: &str, chain_id: &str) -> Self {
+        Rest {
+            agent: ureq::Agent::new(),
+            base: format!("{}/{}", url.trim_end_matches('/'), chain_id),
+            tokens: HashMap::new(),
+        }
+    }
+
+    /// Map the result of a request to an outcome.
+    ///
+    /// # Arguments
+    ///
+    /// - `result` - The result of the request.
+    ///
+    /// # Returns
+    ///
+    /// The outcome and the response when the request succeeded.
+    fn outcome(result: Result<ureq::Response, ureq::Error>) -> Result<ureq::Response, Outcome> {
+        match result {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, _)) if status < 500 => Err(Outcome::Rejected),
+            Err(ureq::Error::Status(503, response)) if response.header("Retry-After").is_some() => {
+                Err(Outcome::Throttled)
+            }
+            Err(_) => Err(Outcome::Failed),
+        }
+    }
+}
+
+impl Target for Rest {
+    fn create_wallet(&mut self, email: &str, _balance: f64) -> Result<String, Outcome> {
+        let response = Rest::outcome(
+            self.agent
+                .post(&format!("{}/wallet/create", self.base))
+                .send_json(json!({ "email": email })),
+        )?;
+
+        let body: Value = response.into_json().map_err(|_| Outcome::Failed)?;
+
+        let address = body["data"].as_str().ok_or(Outcome::Failed)?.to_string();
+
+        // Payments from the wallet require its bearer token
+        if let Some(token) = body["token"].as_str() {
+            self.tokens.insert(address.to_owned(), token.to_string());
+        }
+
+        Ok(address)
+    }
+
+    fn payment(&mut self, from: &str, to: &str, amount: f64) -> Outcome {
+        let token = self
+            .tokens
+            .get(from)
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        match Rest::outcome(
+            self.agent
+                .post(&format!("{}/transactions", self.base))
+                .set("Authorization", &format!("Bearer {}", token))
+                .send_json(json!({ "from": from, "to": to, "amount": amount })),
+        ) {
+            Ok(_) => Outcome::Accepted,
+            Err(outcome) => outcome,
+        }
+    }
+
+    fn read_balance(&mut self, address: &str) -> Outcome {
+        match Rest::outcome(
+            self.agent
+                .get(&format!("{}/wallet/balance", self.base))
+                .query("address", address)
+                .call(),
+        ) {
+            Ok(_) => Outcome::Accepted,
+            Err(outcome) => outcome,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{env, process, thread};
+
+use blockchain::{Chain, Network, Node};
+
+use crate::miner::Miner;
+
+mod miner;
+
+/// The usage of the demo.
+const USAGE: &str = "Usage: node [--nodes N] [--blocks N] [--difficulty D]";
+
+/// The settings of a run.
+struct Args {
+    /// The amount of nodes mining against each other.
+    nodes: usize,
+
+    /// The height of the last block to mine.
+    blocks: usize,
+
+    /// The mining difficulty of the blockchain.
+    difficulty: f64,
+}
+
+/// Parse a flag value.
+///
+/// # Arguments
+///
+/// - `name` - The flag name.
+/// - `value` - The flag value.
+///
+/// # Returns
+///
+/// The parsed value, exiting with the usage when it is missing or invalid.
+fn parse<T: std::str::FromStr>(name: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("Invalid value for {}\n{}", name, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+/// Parse the command line arguments.
+///
+/// # Returns
+///
+/// The settings of the run, exiting with the usage when they are invalid.
+fn parse_args() -> Args {
+    let mut args = Args {
+        nodes: 2,
+        blocks: 20,
+        difficulty: 4.0,
+    };
+
+    let mut input = env::args().skip(1);
+
+    while let Some(name) = input.next() {
+        let value = input.next();
+
+        match name.as_str() {
+            "--nodes" => args.nodes = parse(&name, value),
+            "--blocks" => args.blocks = parse(&name, value),
+            "--difficulty" => args.difficulty = parse(&name, value),
+            _ => {
+                eprintln!("Unknown argument {}\n{}", name, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    args
+}
+
+fn main() {
+    let args = parse_args();
+
+    // Every node starts from the same genesis block
+    let genesis = Chain::new(args.difficulty, 100.0, 0.01);
+    let mut networks: Vec<Network> = (0..args.nodes)
+        .map(|_| Network::new(Node::new(genesis.clone())))
+        .collect();
+
+    let addresses: Vec<String> = networks
+        .iter()
+        .map(|network| {
+            network
+                .listen("127.0.0.1:0")
+                .expect("Unable to listen")
+                .to_string()
+        })
+        .collect();
+
+    // Connect every node to every other node
+    for (index, network) in networks.iter_mut().enumerate() {
+        for (peer, address) in addresses.iter().enumerate() {
+            if peer != index {
+                network.connect(address).expect("Unable to connect");
+            }
+        }
+    }
+
+    let miners: Vec<_> = networks
+        .into_iter()
+        .enumerate()
+        .map(|(index, network)| {
+            let miner = Miner::new(format!("node-{}", index), network);
+            let blocks = args.blocks;
+
+            thread::spawn(move || miner.run(blocks))
+        })
+        .collect();
+
+    let mut results: Vec<_> = miners
+        .into_iter()
+        .map(|miner| miner.join().expect("The miner panicked"))
+        .collect();
+
+    println!();
+
+    for (index, (stats, network)) in results.iter_mut().enumerate() {
+        // Converge on the branch with the most work before comparing the tips
+        network.sync();
+
+        let node = network.node();
+
+        println!(
+            "node-{}: height {}, tip {}, mined {}, abandoned {}, stale {}",
+            index,
+            node.chain().get_all_blocks().len() - 1,
+            node.chain().get_last_hash(),
+            stats.mined,
+            stats.abandoned,
+            stats.stale,
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use blockchain::{Block, ChainError, ChainEvent, Network};
+
+/// The outcome of the work of a miner.
+#[derive(Debug, Default)]
+pub struct MinerStats {
+    /// The amount of blocks mined and appended to the blockchain, including the ones later
+    /// replaced by a branch of a peer carrying more work.
+    pub mined: usize,
+
+    /// The amount of templates abandoned because a peer extended the blockchain first.
+    pub abandoned: usize,
+
+    /// The amount of mined blocks rejected because the blockchain moved on meanwhile.
+    pub stale: usize,
+}
+
+/// A node mining continuously on top of its blockchain.
+pub struct Miner {
+    /// The name of the node in the logs.
+    name: String,
+
+    /// The networked node.
+    network: Network,
+
+    /// The address receiving the block rewards.
+    address: String,
+
+    /// The flag interrupting the proof of work once the blockchain changes.
+    cancel: Arc<AtomicBool>,
+}
+
+impl Miner {
+    /// Create a new miner, interrupted by every block appended to its blockchain.
+    ///
+    /// # Arguments
+    ///
+    /// - `name` - The name of the node in the logs.
+    /// - `network` - The networked node.
+    ///
+    /// # Returns
+    ///
+    /// A new miner rewarding a new wallet of the node.
+    pub fn new(name: String, network: Network) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (address, events) = {
+            let mut node = network.node();
+            let address = node
+                .chain_mut()
+                .create_wallet(format!("{}@mail.com", name))
+                .expect("Unable to create the miner wallet");
+
+            (address, node.chain_mut().subscribe())
+        };
+
+        // Interrupt the proof of work whenever a block is appended, by this node or a peer
+        let flag = cancel.clone();
+
+        thread::spawn(move || {
+            for event in events {
+                if let ChainEvent::BlockMined { .. } = event {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+
+        Miner {
+            name,
+            network,
+            address,
+            cancel,
+        }
+    }
+
+    /// Mine blocks until the blockchain reaches a height.
+    ///
+    /// # Arguments
+    ///
+    /// - `height` - The height of the last block to mine.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the work, and the networked node.
+    pub fn run(mut self, height: usize) -> (MinerStats, Network) {
+        let mut stats = MinerStats::default();
+
+        loop {
+            // Catch up with the peers, switching to their branch if it carries more work
+            self.network.sync();
+
+            // Reset the flag before building the template, so no later block goes unnoticed
+            self.cancel.store(false, Ordering::Relaxed);
+
+            let template = {
+                let node = self.network.node();
+
+                let next = node.chain().get_all_blocks().len();
+
+                match next > height {
+                    true => None,
+                    false => Some(
+                        node.chain()
+                            .block_template(&self.address)
+                            .map(|block| (next, block)),
+                    ),
+                }
+            };
+
+            match template {
+                Some(Ok((next, block))) => self.mine(next, block, &mut stats),
+                Some(Err(err)) => {
+                    eprintln!("[{}] unable to build a template: {}", self.name, err);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        (stats, self.network)
+    }
+
+    /// Mine a template, and abandon it as soon as another block extends the blockchain.
+    ///
+    /// # Arguments
+    ///
+    /// - `height` - The height of the template.
+    /// - `block` - The template.
+    /// - `stats` - The outcome of the work.
+    fn mine(&mut self, height: usize, mut block: Block, stats: &mut MinerStats) {
+        loop {
+            if Block::proof_of_work_cancellable(&mut block.header, &self.cancel) {
+                let nonce = block.header.nonce;
+
+                match self.network.submit_block(block) {
+                    Ok(()) => {
+                        stats.mined += 1;
+                        println!("[{}] mined block {} (nonce {})", self.name, height, nonce);
+                    }
+                    Err(ChainError::UnknownParent) => {
+                        stats.stale += 1;
+                        println!("[{}] block {} arrived too late", self.name, height);
+                    }
+                    Err(err) => eprintln!("[{}] block {} rejected: {}", self.name, height, err),
+                }
+
+                return;
+            }
+
+            // The flag is also raised by the own blocks of the node, so check the tip moved
+            self.cancel.store(false, Ordering::Relaxed);
+
+            if self.network.node().chain().get_last_hash() != block.header.previous_hash {
+                stats.abandoned += 1;
+                println!(
+                    "[{}] abandoned template {}: a better tip arrived from a peer",
+                    self.name, height
+                );
+
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::Chain;
+
+/// A scheme assigning addresses to new wallets.
+pub trait AddressGenerator: Debug + Send + Sync {
+    /// Generate an address for a new wallet.
+    ///
+    /// # Arguments
+    /// - `chain_id`: The identifier of the blockchain the wallet is created on.
+    /// - `email`: The wallet email.
+    ///
+    /// # Returns
+    /// The address of the new wallet.
+    fn generate(&self, chain_id: &str, email: &str) -> String;
+}
+
+/// Random alphanumeric addresses.
+#[derive(Clone, Debug)]
+pub struct RandomAddressGenerator {
+    /// The length of the generated addresses.
+    pub length: usize,
+}
+
+impl Default for RandomAddressGenerator {
+    fn default() -> Self {
+        RandomAddressGenerator { length: 42 }
+    }
+}
+
+impl AddressGenerator for RandomAddressGenerator {
+    fn generate(&self, _chain_id: &str, _email: &str) -> String {
+        Chain::generate_address(self.length)
+    }
+}
+
+/// Addresses derived from a salted hash of the wallet email and the chain identifier.
+#[derive(Clone, Debug)]
+pub struct DeterministicAddressGenerator {
+    /// Salt mixed into the address derivation.
+    pub salt: String,
+
+    /// The length of the generated addresses.
+    pub length: usize,
+}
+
+impl DeterministicAddressGenerator {
+    /// Create a new deterministic address generator.
+    ///
+    /// # Arguments
+    /// - `salt`: The salt mixed into the address derivation.
+    ///
+    /// # Returns
+    /// A new generator producing 42 characters long addresses.
+    pub fn new(salt: String) -> Self {
+        DeterministicAddressGenerator { salt, length: 42 }
+    }
+}
+
+impl AddressGenerator for DeterministicAddressGenerator {
+    fn generate(&self, chain_id: &str, email: &str) -> String {
+        Chain::derive_address(&self.salt, chain_id, email, self.length)
+    }
+}
+
+/// Sequential addresses made of a prefix and a counter, handy for tests.
+#[derive(Debug)]
+pub struct SequentialAddressGenerator {
+    /// The prefix of the generated addresses.
+    pub prefix: String,
+
+    /// The counter of the next address.
+    next: AtomicUsize,
+}
+
+impl SequentialAddressGenerator {
+    /// Create a new sequential address generator.
+    ///
+    /// # Arguments
+    /// - `prefix`: The prefix of the generated addresses.
+    ///
+    /// # Returns
+    /// A new generator starting at zero.
+    pub fn new(prefix: String) -> Self {
+        SequentialAddressGenerator {
+            prefix,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl AddressGenerator for SequentialAddressGenerator {
+    fn generate(&self, _chain_id: &str, _email: &str) -> String {
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+
+        format!("{}{}", self.prefix, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_address_generator() {
+        let generator = RandomAddressGenerator::default();
+
+        let result = generator.generate("chain", "s@mail.com");
+
+        assert_eq!(result.len(), 42);
+        assert_ne!(result, generator.generate("chain", "s@mail.com"));
+    }
+
+    #[test]
+    fn test_deterministic_address_generator() {
+        let generator = DeterministicAddressGenerator::new("salt".to_string());
+
+        let result = generator.generate("chain", "s@mail.com");
+
+        assert_eq!(result.len(), 42);
+        assert_eq!(result, generator.generate("chain", "s@mail.com"));
+    }
+
+    #[test]
+    fn test_sequential_address_generator() {
+        let generator = SequentialAddressGenerator::new("wallet-".to_string());
+
+        assert_eq!(generator.generate("chain", "a@mail.com"), "wallet-0");
+        assert_eq!(generator.generate("chain", "b@mail.com"), "wallet-1");
+    }
+}
@@ -0,0 +1,198 @@
+use std::{fs::File, path::Path, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use parquet::{arrow::ArrowWriter, errors::ParquetError};
+
+use crate::{Block, Chain, Transaction};
+
+/// Name of the file holding the blocks in a Parquet export.
+pub const BLOCKS_FILE: &str = "blocks.parquet";
+
+/// Name of the file holding the transactions in a Parquet export.
+pub const TRANSACTIONS_FILE: &str = "transactions.parquet";
+
+impl Chain {
+    /// Get the headers of the blocks as an Arrow record batch, one row per block.
+    ///
+    /// # Returns
+    /// The record batch with the `height`, `hash`, `previous_hash`, `merkle`, `timestamp`,
+    /// `nonce`, `difficulty`, `transaction_count` and `pruned` columns.
+    pub fn blocks_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let blocks = &self.chain;
+
+        let schema = Schema::new(vec![
+            Field::new("height", DataType::UInt64, false),
+            Field::new("hash", DataType::Utf8, false),
+            Field::new("previous_hash", DataType::Utf8, false),
+            Field::new("merkle", DataType::Utf8, false),
+            Field::new("timestamp", DataType::Int64, false),
+            Field::new("nonce", DataType::UInt32, false),
+            Field::new("difficulty", DataType::Float64, false),
+            Field::new("transaction_count", DataType::UInt64, false),
+            Field::new("pruned", DataType::Boolean, false),
+        ]);
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(0..blocks.len() as u64)),
+            Arc::new(StringArray::from_iter_values(
+                blocks.iter().map(Block::hash),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                blocks.iter().map(|block| &block.header.previous_hash),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                blocks.iter().map(|block| &block.header.merkle),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                blocks.iter().map(|block| block.header.timestamp),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                blocks.iter().map(|block| block.header.nonce),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                blocks.iter().map(|block| block.header.difficulty),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                blocks.iter().map(|block| block.count as u64),
+            )),
+            Arc::new(BooleanArray::from_iter(
+                blocks.iter().map(|block| Some(block.is_pruned())),
+            )),
+        ];
+
+        RecordBatch::try_new(Arc::new(schema), columns)
+    }
+
+    /// Get the stored transactions as an Arrow record batch, one row per transaction.
+    ///
+    /// The transactions of the blocks come first, followed by the pending transactions with a
+    /// null `block_height`. The transactions of pruned blocks are not stored, so they are missing.
+    ///
+    /// # Returns
+    /// The record batch with the `block_height`, `hash`, `from`, `to`, `amount`, `fee`,
+    /// `fee_payer`, `timestamp`, `sequence` and `signed` columns.
+    pub fn transactions_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let transactions: Vec<(Option<u64>, &Transaction)> = self
+            .chain
+            .iter()
+            .enumerate()
+            .flat_map(|(height, block)| {
+                block
+                    .transactions
+                    .iter()
+                    .map(move |transaction| (Some(height as u64), transaction))
+            })
+            .chain(
+                self.current_transactions
+                    .iter()
+                    .map(|transaction| (None, transaction)),
+            )
+            .collect();
+
+        let schema = Schema::new(vec![
+            Field::new("block_height", DataType::UInt64, true),
+            Field::new("hash", DataType::Utf8, false),
+            Field::new("from", DataType::Utf8, false),
+            Field::new("to", DataType::Utf8, false),
+            Field::new("amount", DataType::Float64, false),
+            Field::new("fee", DataType::Float64, false),
+            Field::new("fee_payer", DataType::Utf8, true),
+            Field::new("timestamp", DataType::Int64, false),
+            Field::new("sequence", DataType::UInt64, true),
+            Field::new("signed", DataType::Boolean, false),
+        ]);
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter(
+                transactions.iter().map(|(height, _)| *height),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| &transaction.hash),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| &transaction.from),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                transactions.iter().map(|(_, transaction)| &transaction.to),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| transaction.amount),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| transaction.charged_fee()),
+            )),
+            Arc::new(StringArray::from_iter(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| transaction.fee_payer.as_deref()),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| transaction.timestamp),
+            )),
+            Arc::new(UInt64Array::from_iter(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| transaction.sequence),
+            )),
+            Arc::new(BooleanArray::from_iter(
+                transactions
+                    .iter()
+                    .map(|(_, transaction)| Some(transaction.signature.is_some())),
+            )),
+        ];
+
+        RecordBatch::try_new(Arc::new(schema), columns)
+    }
+
+    /// Export the blocks and the transactions to Parquet files.
+    ///
+    /// The directory receives a `blocks.parquet` and a `transactions.parquet` file, replacing
+    /// the files of a previous export.
+    ///
+    /// # Arguments
+    /// - `directory`: The existing directory receiving the files.
+    ///
+    /// # Returns
+    /// An error if a file cannot be written.
+    pub fn export_parquet<P: AsRef<Path>>(&self, directory: P) -> Result<(), ParquetError> {
+        let directory = directory.as_ref();
+
+        write_parquet(&directory.join(BLOCKS_FILE), &self.blocks_record_batch()?)?;
+        write_parquet(
+            &directory.join(TRANSACTIONS_FILE),
+            &self.transactions_record_batch()?,
+        )
+    }
+}
+
+/// Write a record batch to a Parquet file.
+///
+/// # Arguments
+/// - `path`: The path of the file.
+/// - `batch`: The record batch.
+///
+/// # Returns
+/// An error if the file cannot be written.
+fn write_parquet(path: &Path, batch: &RecordBatch) -> Result<(), ParquetError> {
+    let file = File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+
+    writer.write(batch)?;
+    writer.close()?;
+
+    Ok(())
+}
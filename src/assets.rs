@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{encoding, Address, Chain, ChainError, Keypair, Transaction};
+
+/// Domain of the hashes identifying the assets.
+const ASSET_DOMAIN: &str = "blockchain/asset/v1";
+
+/// Maximum length of an asset name, in bytes.
+pub const MAX_ASSET_NAME_LENGTH: usize = 32;
+
+/// A token issued by a wallet with a fixed supply, held and transferred besides the coins of the
+/// blockchain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    /// The hash identifying the asset, derived from the chain identifier, the issuer and the name.
+    pub id: String,
+
+    /// The name of the asset, unique among the assets of its issuer.
+    pub name: String,
+
+    /// The address of the issuing wallet.
+    pub issuer: Address,
+
+    /// The fixed amount of the asset, credited to the issuer.
+    pub supply: f64,
+
+    /// The time the asset was issued, from the timestamp of its issuance transaction, in seconds
+    /// since the Unix epoch.
+    pub issued_at: i64,
+}
+
+impl Transaction {
+    /// Get the amount of coins moved by the transaction.
+    ///
+    /// # Returns
+    /// The amount, or 0 if the transaction transfers an asset.
+    pub fn native_amount(&self) -> f64 {
+        match self.asset_id {
+            Some(_) => 0.0,
+            None => self.amount,
+        }
+    }
+}
+
+impl Chain {
+    /// Issue a named asset with a fixed supply, credited to the issuing wallet.
+    ///
+    /// The issuance is recorded by a transaction from the issuer to itself with no fee, mined
+    /// and imported like the other transactions, and signed if the issuer has a public key.
+    /// Assets are only held by the wallets of the account ledger model, and are transferred
+    /// with `transfer_asset` or with transactions built with `TransactionBuilder::asset`.
+    ///
+    /// # Arguments
+    /// - `issuer`: The address of the issuing wallet.
+    /// - `name`: The name of the asset, at most `MAX_ASSET_NAME_LENGTH` bytes long.
+    /// - `supply`: The amount of the asset.
+    /// - `keypair`: The keypair owning the issuing wallet, required if the wallet has a public
+    ///   key.
+    ///
+    /// # Returns
+    /// The identifier of the asset, or the reason it cannot be issued.
+    pub fn issue_asset(
+        &mut self,
+        issuer: &str,
+        name: &str,
+        supply: f64,
+        keypair: Option<&Keypair>,
+    ) -> Result<String, ChainError> {
+        let Some(nonce) = self.get_nonce(issuer) else {
+            return Err(ChainError::UnknownSender(issuer.to_string()));
+        };
+
+        let id = encoding::hash(ASSET_DOMAIN, &[&self.config.chain_id, &issuer, &name]);
+        let mut transaction = Transaction::builder()
+            .from(issuer)
+            .to(issuer)
+            .amount(supply)
+            .fee(0.0)
+            .asset_issuance(id.to_owned(), name)
+            .nonce(nonce)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build();
+
+        if let Some(keypair) = keypair {
+            transaction = transaction.sign(keypair);
+        }
+
+        self.submit_transaction(transaction)?;
+
+        Ok(id)
+    }
+
+    /// Apply the issuance of an asset whose signature was verified and add it to the current
+    /// transactions.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction issuing the asset.
+    ///
+    /// # Returns
+    /// An error describing why the asset cannot be issued, if it cannot.
+    pub(crate) fn accept_asset_issuance(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<(), ChainError> {
+        let invalid = |reason: String| Err(ChainError::InvalidAsset(reason));
+
+        let (Some(id), Some(name)) = (&transaction.asset_id, &transaction.asset_name) else {
+            return invalid("the transaction issues no asset".to_string());
+        };
+
+        if transaction.from != transaction.to
+            || transaction.fee != 0.0
+            || transaction.gas.is_some()
+            || transaction.fee_payer.is_some()
+            || !transaction.inputs.is_empty()
+        {
+            return invalid("the transaction moves funds".to_string());
+        }
+
+        if !self.wallets.contains_key(&transaction.from) {
+            return Err(ChainError::UnknownSender(transaction.from));
+        }
+
+        if !self.ledger().model().is_account() {
+            return invalid("assets require the account ledger model".to_string());
+        }
+
+        if name.trim().is_empty() || name.len() > MAX_ASSET_NAME_LENGTH {
+            return invalid(format!(
+                "the name must be between 1 and {} bytes long",
+                MAX_ASSET_NAME_LENGTH
+            ));
+        }
+
+        let supply = transaction.amount;
+
+        if !supply.is_finite() || supply <= 0.0 {
+            return invalid("the supply must be positive".to_string());
+        }
+
+        if *id
+            != encoding::hash(
+                ASSET_DOMAIN,
+                &[&self.config.chain_id, &transaction.from, name],
+            )
+        {
+            return invalid("the identifier does not match the issuer and the name".to_string());
+        }
+
+        if self.assets.contains_key(id) {
+            return invalid(format!("{} already issued {}", transaction.from, name));
+        }
+
+        if !self.reserve_mempool(&transaction) {
+            return Err(self.mempool_full());
+        }
+
+        if let Some(wallet) = self.wallets.get_mut(&transaction.from) {
+            wallet.assets.insert(id.to_owned(), supply);
+            wallet.nonce += 1;
+            wallet.transactions.push(transaction.hash.to_owned());
+        }
+
+        // Millisecond timestamps carry a tie-breaker counter
+        let issued_at = match transaction.sequence {
+            Some(_) => transaction.timestamp.div_euclid(1000),
+            None => transaction.timestamp,
+        };
+
+        self.assets.insert(
+            id.to_owned(),
+            Asset {
+                id: id.to_owned(),
+                name: name.to_owned(),
+                issuer: transaction.from.to_owned(),
+                supply,
+                issued_at,
+            },
+        );
+        self.current_transactions.push(transaction);
+
+        self.assert_invariants();
+
+        Ok(())
+    }
+
+    /// Add a new transaction transferring an asset from a wallet without public key.
+    ///
+    /// The sender pays the fee of the blockchain in coins, as a flat fee since the amount is not
+    /// counted in coins.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `asset_id`: The identifier of the asset.
+    /// - `amount`: The amount of the asset.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, or the reason it is invalid.
+    pub fn transfer_asset(
+        &mut self,
+        from: String,
+        to: String,
+        asset_id: &str,
+        amount: f64,
+    ) -> Result<String, ChainError> {
+        self.check_intake()?;
+
+        // Validate the transaction before hashing it
+        self.check_spending_policy(&from, &to, 0.0)?;
+        self.check_asset_transfer(&from, &to, &from, asset_id, amount, self.fee)?;
+
+        // Wallets with a public key only spend through signed transactions
+        if self
+            .wallets
+            .get(&from)
+            .is_some_and(|wallet| wallet.public_key.is_some())
+        {
+            return Err(ChainError::SignatureRequired);
+        }
+
+        let nonce = self.get_nonce(&from).unwrap_or_default();
+        let transaction = Transaction::builder()
+            .from(from)
+            .to(to)
+            .amount(amount)
+            .fee(self.fee)
+            .asset(asset_id)
+            .nonce(nonce)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build();
+        let hash = transaction.hash.to_owned();
+
+        self.submit_transaction(transaction)?;
+
+        Ok(hash)
+    }
+
+    /// Get an issued asset.
+    ///
+    /// # Arguments
+    /// - `asset_id`: The identifier of the asset.
+    ///
+    /// # Returns
+    /// The asset, or `None` if no asset has the identifier.
+    pub fn get_asset(&self, asset_id: &str) -> Option<&Asset> {
+        self.assets.get(asset_id)
+    }
+
+    /// Get the issued assets.
+    ///
+    /// # Returns
+    /// The assets, ordered by identifier.
+    pub fn get_assets(&self) -> Vec<&Asset> {
+        self.assets.values().collect()
+    }
+
+    /// Get the balance of an asset held by a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `asset_id`: The identifier of the asset.
+    ///
+    /// # Returns
+    /// The amount of the asset held by the wallet, 0 if it holds none, or `None` if the wallet
+    /// or the asset is not found.
+    pub fn get_asset_balance(&self, address: &str, asset_id: &str) -> Option<f64> {
+        if !self.assets.contains_key(asset_id) {
+            return None;
+        }
+
+        self.wallets
+            .get(address)
+            .map(|wallet| wallet.assets.get(asset_id).copied().unwrap_or_default())
+    }
+
+    /// Validate a transfer of an asset whose fee is paid by a given wallet, possibly the sender.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `payer`: The fee payer's address.
+    /// - `asset_id`: The identifier of the asset.
+    /// - `amount`: The amount of the asset.
+    /// - `fee`: The fee the payer must hold.
+    ///
+    /// # Returns
+    /// An error describing the first rule the transfer breaks, if any.
+    pub(crate) fn check_asset_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        payer: &str,
+        asset_id: &str,
+        amount: f64,
+        fee: f64,
+    ) -> Result<(), ChainError> {
+        self.check_transfer_parties(from, to, payer, amount)?;
+
+        let Some(balance) = self.get_asset_balance(from, asset_id) else {
+            return Err(ChainError::UnknownAsset(asset_id.to_string()));
+        };
+
+        if balance < amount || !self.can_pay(from, payer, 0.0, fee) {
+            return Err(ChainError::InsufficientBalance);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_asset() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let issuer = chain.create_wallet("s@mail.com".to_string()).unwrap();
+        let id = chain.issue_asset(&issuer, "Gold", 1000.0, None).unwrap();
+        let asset = chain.get_asset(&id).unwrap();
+
+        assert_eq!(asset.name, "Gold");
+        assert_eq!(asset.issuer, issuer);
+        assert_eq!(asset.supply, 1000.0);
+        assert_eq!(chain.get_assets(), [asset]);
+        assert_eq!(chain.get_asset_balance(&issuer, &id), Some(1000.0));
+        assert_eq!(chain.get_asset_balance(&issuer, "unknown"), None);
+
+        // The name is unique among the assets of the issuer
+        assert!(matches!(
+            chain.issue_asset(&issuer, "Gold", 1.0, None),
+            Err(ChainError::InvalidAsset(_))
+        ));
+        assert!(matches!(
+            chain.issue_asset(&issuer, " ", 1.0, None),
+            Err(ChainError::InvalidAsset(_))
+        ));
+        assert!(matches!(
+            chain.issue_asset(&issuer, "Silver", 0.0, None),
+            Err(ChainError::InvalidAsset(_))
+        ));
+        assert_eq!(
+            chain.issue_asset("unknown", "Silver", 1.0, None),
+            Err(ChainError::UnknownSender("unknown".to_string()))
+        );
+    }
+}
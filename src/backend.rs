@@ -0,0 +1,463 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    io::{self, ErrorKind},
+    mem,
+    sync::{Arc, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, Wallet};
+
+/// A backend storing the blocks and the wallets of a blockchain, so they do not have to live
+/// entirely in memory.
+///
+/// Blocks are stored by height, and the methods take `&self` so a backend can be shared by
+/// several blockchains and threads.
+pub trait Storage: Debug + Send + Sync {
+    /// Store a block, replacing the block stored at its height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block, at most the amount of stored blocks.
+    /// - `block`: The block.
+    ///
+    /// # Returns
+    /// An error if the block cannot be written.
+    fn put_block(&self, height: usize, block: &Block) -> io::Result<()>;
+
+    /// Read a block.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The block, `None` if no block is stored at the height, or an error if it cannot be read.
+    fn get_block(&self, height: usize) -> io::Result<Option<Block>>;
+
+    /// Get the amount of stored blocks.
+    ///
+    /// # Returns
+    /// The amount of stored blocks, or an error if it cannot be read.
+    fn block_count(&self) -> io::Result<usize>;
+
+    /// Remove the blocks from a height, e.g. once they are reverted by a reorganization.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the first removed block.
+    ///
+    /// # Returns
+    /// An error if the blocks cannot be removed.
+    fn truncate_blocks(&self, height: usize) -> io::Result<()>;
+
+    /// Store a wallet, replacing the wallet stored at its address.
+    ///
+    /// # Arguments
+    /// - `wallet`: The wallet.
+    ///
+    /// # Returns
+    /// An error if the wallet cannot be written.
+    fn put_wallet(&self, wallet: &Wallet) -> io::Result<()>;
+
+    /// Read a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the wallet.
+    ///
+    /// # Returns
+    /// The wallet, `None` if no wallet is stored at the address, or an error if it cannot be read.
+    fn get_wallet(&self, address: &str) -> io::Result<Option<Wallet>>;
+
+    /// Read every wallet.
+    ///
+    /// # Returns
+    /// The stored wallets, or an error if they cannot be read.
+    fn get_wallets(&self) -> io::Result<Vec<Wallet>>;
+
+    /// Store the state of the blockchain besides its blocks and wallets.
+    ///
+    /// # Arguments
+    /// - `state`: The serialized state.
+    ///
+    /// # Returns
+    /// An error if the state cannot be written.
+    fn put_state(&self, state: &[u8]) -> io::Result<()>;
+
+    /// Read the state of the blockchain besides its blocks and wallets.
+    ///
+    /// # Returns
+    /// The serialized state, `None` if no state is stored, or an error if it cannot be read.
+    fn get_state(&self) -> io::Result<Option<Vec<u8>>>;
+
+    /// Make the writes durable. Does nothing by default.
+    ///
+    /// # Returns
+    /// An error if the writes cannot be made durable.
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Storage keeping everything in memory, the default when no backend is set.
+#[derive(Debug, Default)]
+pub struct InMemoryStorage {
+    /// The blocks, by height.
+    blocks: RwLock<Vec<Block>>,
+
+    /// The wallets, by address.
+    wallets: RwLock<BTreeMap<String, Wallet>>,
+
+    /// The serialized state.
+    state: RwLock<Option<Vec<u8>>>,
+}
+
+impl InMemoryStorage {
+    /// Create a new empty storage.
+    ///
+    /// # Returns
+    /// A new `InMemoryStorage` instance.
+    pub fn new() -> Self {
+        InMemoryStorage::default()
+    }
+}
+
+impl Storage for InMemoryStorage {
+    fn put_block(&self, height: usize, block: &Block) -> io::Result<()> {
+        let mut blocks = self.blocks.write().unwrap_or_else(|e| e.into_inner());
+
+        match height.cmp(&blocks.len()) {
+            std::cmp::Ordering::Less => blocks[height] = block.clone(),
+            std::cmp::Ordering::Equal => blocks.push(block.clone()),
+            std::cmp::Ordering::Greater => return Err(missing_parent(height)),
+        }
+
+        Ok(())
+    }
+
+    fn get_block(&self, height: usize) -> io::Result<Option<Block>> {
+        let blocks = self.blocks.read().unwrap_or_else(|e| e.into_inner());
+
+        Ok(blocks.get(height).cloned())
+    }
+
+    fn block_count(&self) -> io::Result<usize> {
+        Ok(self.blocks.read().unwrap_or_else(|e| e.into_inner()).len())
+    }
+
+    fn truncate_blocks(&self, height: usize) -> io::Result<()> {
+        self.blocks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .truncate(height);
+
+        Ok(())
+    }
+
+    fn put_wallet(&self, wallet: &Wallet) -> io::Result<()> {
+        self.wallets
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(wallet.address.to_owned(), wallet.clone());
+
+        Ok(())
+    }
+
+    fn get_wallet(&self, address: &str) -> io::Result<Option<Wallet>> {
+        let wallets = self.wallets.read().unwrap_or_else(|e| e.into_inner());
+
+        Ok(wallets.get(address).cloned())
+    }
+
+    fn get_wallets(&self) -> io::Result<Vec<Wallet>> {
+        let wallets = self.wallets.read().unwrap_or_else(|e| e.into_inner());
+
+        Ok(wallets.values().cloned().collect())
+    }
+
+    fn put_state(&self, state: &[u8]) -> io::Result<()> {
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = Some(state.to_vec());
+
+        Ok(())
+    }
+
+    fn get_state(&self) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.state.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+}
+
+/// The state of a blockchain written by `Chain::persist`.
+#[derive(Serialize, Deserialize)]
+struct PersistedState<C> {
+    /// The amount of stored blocks the state follows.
+    blocks: usize,
+
+    /// The amount of blocks whose transactions were pruned from memory.
+    pruned: usize,
+
+    /// The blockchain, without its blocks and wallets.
+    chain: C,
+}
+
+impl Chain {
+    /// Set the backend storing the blocks and the wallets, and write the blocks it misses.
+    ///
+    /// The blocks are then written as they are appended, and the pruning policy only drops the
+    /// transactions of the blocks already written, so they can still be read with
+    /// `get_stored_block`. The backend is shared by the clones of the blockchain and is not
+    /// persisted with it.
+    ///
+    /// # Arguments
+    /// - `storage`: The backend.
+    ///
+    /// # Returns
+    /// An error if the backend cannot be read or written.
+    pub fn set_storage(&mut self, storage: Arc<dyn Storage>) -> io::Result<()> {
+        // Keep the stored blocks shared with the blockchain, which may be pruned in memory
+        let mut height = storage.block_count()?.min(self.chain.len());
+
+        while height > 0
+            && storage.get_block(height - 1)?.map(|block| block.hash())
+                != Some(self.chain[height - 1].hash())
+        {
+            height -= 1;
+        }
+
+        self.storage = Some(storage);
+        self.stored_blocks = Some(height);
+
+        self.store_blocks()
+    }
+
+    /// Write the blockchain to its backend: the missing blocks, the wallets and the state.
+    ///
+    /// # Returns
+    /// A `NotFound` error if no backend is set, or an error if the backend cannot be written.
+    pub fn persist(&mut self) -> io::Result<()> {
+        let storage = self
+            .storage
+            .clone()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "No storage is set"))?;
+
+        self.store_blocks()?;
+
+        for wallet in self.wallets.values() {
+            storage.put_wallet(wallet)?;
+        }
+
+        // Serialize the state without cloning the blocks and the wallets
+        let blocks = mem::take(&mut self.chain);
+        let wallets = mem::take(&mut self.wallets);
+        let state = serde_json::to_vec(&PersistedState {
+            blocks: blocks.len(),
+            pruned: blocks.iter().take_while(|block| block.is_pruned()).count(),
+            chain: &*self,
+        });
+
+        self.chain = blocks;
+        self.wallets = wallets;
+
+        storage.put_state(&state?)?;
+        storage.flush()
+    }
+
+    /// Read a blockchain written to a backend with `persist`.
+    ///
+    /// The blocks appended after the last call to `persist` are imported again, until one is
+    /// rejected. The blocks pruned in memory when the blockchain was persisted are pruned as
+    /// they are read.
+    ///
+    /// # Arguments
+    /// - `storage`: The backend, set as the backend of the blockchain.
+    ///
+    /// # Returns
+    /// The blockchain, a `NotFound` error if no blockchain was persisted, or an error if the
+    /// backend cannot be read.
+    pub fn restore(storage: Arc<dyn Storage>) -> io::Result<Chain> {
+        let state = storage
+            .get_state()?
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "No blockchain is persisted"))?;
+        let state: PersistedState<Chain> = serde_json::from_slice(&state)?;
+        let mut chain = state.chain;
+
+        for height in 0..state.blocks {
+            let mut block = storage.get_block(height)?.ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Persisted block {} is missing", height),
+                )
+            })?;
+
+            if height < state.pruned {
+                block.prune();
+            }
+
+            chain.chain.push(block);
+        }
+
+        chain.wallets = storage
+            .get_wallets()?
+            .into_iter()
+            .map(|wallet| (wallet.address.to_owned(), wallet))
+            .collect();
+        chain.update_transaction_index();
+        chain.storage = Some(storage.clone());
+        chain.stored_blocks = Some(state.blocks);
+
+        // Read the later blocks first, as importing a block rewrites the blocks above it
+        let later = (state.blocks..storage.block_count()?)
+            .map(|height| storage.get_block(height))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        for block in later.into_iter().map_while(|block| block) {
+            if chain.import_block(block).is_err() {
+                break;
+            }
+        }
+
+        Ok(chain)
+    }
+
+    /// Get a block by its height, reading it from the backend if it was pruned in memory.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The block with its transactions if it is in memory or stored, `None` if the blockchain
+    /// is not that high, or an error if the backend cannot be read.
+    pub fn get_stored_block(&self, height: usize) -> io::Result<Option<Block>> {
+        match (self.chain.get(height), &self.storage) {
+            (Some(block), Some(storage)) if block.is_pruned() => storage.get_block(height),
+            (block, _) => Ok(block.cloned()),
+        }
+    }
+
+    /// Write the blocks missing from the backend, replacing the blocks reverted since.
+    ///
+    /// # Returns
+    /// An error if the backend cannot be written.
+    pub(crate) fn store_blocks(&mut self) -> io::Result<()> {
+        let (Some(storage), Some(stored)) = (self.storage.clone(), self.stored_blocks) else {
+            return Ok(());
+        };
+
+        storage.truncate_blocks(stored)?;
+
+        for height in stored..self.chain.len() {
+            storage.put_block(height, &self.chain[height])?;
+            self.stored_blocks = Some(height + 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Create the error of a block stored above the stored blocks.
+///
+/// # Arguments
+/// - `height`: The height of the block.
+///
+/// # Returns
+/// An `InvalidInput` error.
+pub(crate) fn missing_parent(height: usize) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidInput,
+        format!("Block {} is stored above the stored blocks", height),
+    )
+}
+
+/// The key layout shared by the key-value backends.
+#[cfg(any(feature = "sled", feature = "rocksdb"))]
+pub(crate) mod kv {
+    use std::io::{self, ErrorKind};
+
+    /// Key of the amount of stored blocks.
+    pub(crate) const BLOCK_COUNT_KEY: &[u8] = b"meta/blocks";
+
+    /// Key of the state of the blockchain.
+    pub(crate) const STATE_KEY: &[u8] = b"meta/state";
+
+    /// Prefix of the wallet keys.
+    pub(crate) const WALLET_PREFIX: &[u8] = b"wallet/";
+
+    /// Get the key of a block.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The key, ordered like the heights.
+    pub(crate) fn block_key(height: usize) -> Vec<u8> {
+        [b"block/".as_slice(), &(height as u64).to_be_bytes()].concat()
+    }
+
+    /// Get the key of a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the wallet.
+    ///
+    /// # Returns
+    /// The key.
+    pub(crate) fn wallet_key(address: &str) -> Vec<u8> {
+        [WALLET_PREFIX, address.as_bytes()].concat()
+    }
+
+    /// Decode the amount of stored blocks.
+    ///
+    /// # Arguments
+    /// - `value`: The stored value, `None` if no block was stored.
+    ///
+    /// # Returns
+    /// The amount of stored blocks, or an `InvalidData` error if the value is corrupted.
+    pub(crate) fn decode_block_count(value: Option<&[u8]>) -> io::Result<usize> {
+        match value {
+            None => Ok(0),
+            Some(bytes) => bytes
+                .try_into()
+                .map(|bytes| u64::from_be_bytes(bytes) as usize)
+                .map_err(|_| io::Error::new(ErrorKind::InvalidData, "Block count is corrupted")),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_block_key_order() {
+            assert!(block_key(255) < block_key(256));
+            assert_eq!(decode_block_count(None).unwrap(), 0);
+            assert_eq!(decode_block_count(Some(&7u64.to_be_bytes())).unwrap(), 7);
+            assert!(decode_block_count(Some(b"x")).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_storage() {
+        let storage = InMemoryStorage::new();
+        let block = Block::new("0".to_string(), 1.0);
+
+        storage.put_block(0, &block).unwrap();
+        storage.put_block(1, &block).unwrap();
+
+        assert_eq!(storage.block_count().unwrap(), 2);
+        assert!(storage.put_block(3, &block).is_err());
+
+        storage.truncate_blocks(1).unwrap();
+
+        assert_eq!(storage.block_count().unwrap(), 1);
+        assert!(storage.get_block(1).unwrap().is_none());
+
+        let wallet = Wallet::new("s@mail.com".to_string(), "address".to_string(), 1.0);
+        storage.put_wallet(&wallet).unwrap();
+
+        assert_eq!(storage.get_wallet("address").unwrap().unwrap().balance, 1.0);
+        assert_eq!(storage.get_wallets().unwrap().len(), 1);
+        assert!(storage.get_state().unwrap().is_none());
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Chain, ChainError, Keypair};
+
+/// Shortest delay advised before retrying a submission rejected by a full mempool.
+const MIN_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Amount of recent blocks averaged to estimate the time until the next block.
+const RETRY_AFTER_WINDOW: usize = 10;
+
+impl Chain {
+    /// Add a new transaction to the blockchain if the mempool has room for it.
+    ///
+    /// Unlike `add_transaction`, no pending transaction paying a lower fee is evicted to make
+    /// room, so producers see the mempool filling up instead of silently replacing transactions.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `keypair`: The keypair owning the sender wallet.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `MempoolFull` with the
+    /// advised delay before retrying if the mempool is at its cap, or the reason it is invalid.
+    pub fn try_add_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        keypair: &Keypair,
+    ) -> Result<String, ChainError> {
+        let transaction = self
+            .metered_transaction(from, to, amount, None, true)?
+            .build()
+            .sign(keypair);
+        let hash = transaction.hash.to_owned();
+
+        if !self.fits_mempool(&transaction) {
+            return Err(self.mempool_full());
+        }
+
+        self.submit_transaction(transaction)?;
+
+        Ok(hash)
+    }
+
+    /// Add a new transaction to a shared blockchain, waiting for room in the mempool.
+    ///
+    /// The lock is released while waiting, so the blocks draining the mempool can be mined.
+    ///
+    /// # Arguments
+    /// - `chain`: The shared blockchain.
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `keypair`: The keypair owning the sender wallet.
+    /// - `timeout`: The longest time to wait for room in the mempool.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `MempoolFull` if the
+    /// mempool is still at its cap after the timeout, or the reason it is invalid.
+    pub fn add_transaction_when_ready(
+        chain: &Mutex<Chain>,
+        from: &str,
+        to: &str,
+        amount: f64,
+        keypair: &Keypair,
+        timeout: Duration,
+    ) -> Result<String, ChainError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let result = chain
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_add_transaction(from.to_string(), to.to_string(), amount, keypair);
+
+            match result {
+                Err(ChainError::MempoolFull { retry_after_hint }) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+
+                    if remaining.is_zero() {
+                        return result;
+                    }
+
+                    thread::sleep(retry_after_hint.min(remaining));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Estimate the time until the next block drains the mempool.
+    ///
+    /// The estimate is the average interval of the last blocks, minus the time elapsed since the
+    /// last block.
+    ///
+    /// # Returns
+    /// The advised delay before retrying a submission rejected by a full mempool, at least a
+    /// second.
+    pub fn mempool_retry_after(&self) -> Duration {
+        let recent = &self.chain[self.chain.len().saturating_sub(RETRY_AFTER_WINDOW + 1)..];
+
+        let [first, .., last] = recent else {
+            return MIN_RETRY_AFTER;
+        };
+
+        let interval = (last.header.timestamp - first.header.timestamp).max(0) as u64
+            / (recent.len() - 1) as u64;
+        let elapsed = (self.clock().now_millis() / 1000 - last.header.timestamp).max(0) as u64;
+
+        Duration::from_secs(interval.saturating_sub(elapsed)).max(MIN_RETRY_AFTER)
+    }
+
+    /// Build the error rejecting a transaction that does not fit in the mempool.
+    ///
+    /// # Returns
+    /// `MempoolFull` with the advised delay before retrying.
+    pub(crate) fn mempool_full(&self) -> ChainError {
+        ChainError::MempoolFull {
+            retry_after_hint: self.mempool_retry_after(),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Chain {
+    /// Add a new transaction to a blockchain shared between tasks, waiting for room in the
+    /// mempool without blocking the async executor.
+    ///
+    /// # Arguments
+    /// - `chain`: The shared blockchain.
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `keypair`: The keypair owning the sender wallet.
+    /// - `timeout`: The longest time to wait for room in the mempool.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `MempoolFull` if the
+    /// mempool is still at its cap after the timeout, or the reason it is invalid.
+    pub async fn add_transaction_when_ready_async(
+        chain: &tokio::sync::Mutex<Chain>,
+        from: &str,
+        to: &str,
+        amount: f64,
+        keypair: &Keypair,
+        timeout: Duration,
+    ) -> Result<String, ChainError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let result = chain.lock().await.try_add_transaction(
+                from.to_string(),
+                to.to_string(),
+                amount,
+                keypair,
+            );
+
+            match result {
+                Err(ChainError::MempoolFull { retry_after_hint }) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+
+                    if remaining.is_zero() {
+                        return result;
+                    }
+
+                    tokio::time::sleep(retry_after_hint.min(remaining)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_add_transaction() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let from = chain
+            .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&from, 100.0);
+        chain.generate_new_block().unwrap();
+
+        assert!(chain
+            .try_add_transaction(from.clone(), to.clone(), 1.0, &keypair)
+            .is_ok());
+
+        // The mempool is at its cap
+        chain.config.memory.mempool_bytes = Some(chain.memory_usage().mempool_bytes);
+
+        assert_eq!(
+            chain.try_add_transaction(from.clone(), to.clone(), 1.0, &keypair),
+            Err(ChainError::MempoolFull {
+                retry_after_hint: MIN_RETRY_AFTER
+            })
+        );
+        assert_eq!(chain.get_pending_transactions().len(), 1);
+        assert_eq!(chain.get_wallet_balance(from.clone()), Some(98.9));
+
+        // A new block drains the mempool
+        chain.generate_new_block().unwrap();
+
+        assert!(chain
+            .try_add_transaction(from.clone(), to, 1.0, &keypair)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_add_transaction_when_ready() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let from = chain
+            .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&from, 100.0);
+        chain.config.memory.mempool_bytes = Some(0);
+
+        let chain = Mutex::new(chain);
+
+        assert!(matches!(
+            Chain::add_transaction_when_ready(&chain, &from, &to, 1.0, &keypair, Duration::ZERO),
+            Err(ChainError::MempoolFull { .. })
+        ));
+
+        // The submission waits for room in the mempool
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| {
+                let timeout = Duration::from_secs(30);
+
+                Chain::add_transaction_when_ready(&chain, &from, &to, 1.0, &keypair, timeout)
+            });
+
+            thread::sleep(Duration::from_millis(100));
+            chain.lock().unwrap().config.memory.mempool_bytes = None;
+
+            assert!(waiter.join().unwrap().is_ok());
+        });
+    }
+}
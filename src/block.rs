@@ -1,7 +1,19 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
+
+use crate::{
+    encoding::{self, BLOCK_HEADER_DOMAIN},
+    keys, Address, BlockSignature, Chain, ChainError, Transaction,
+};
 use chrono::Utc;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::{Chain, Transaction};
+/// A transaction with the public keys of its sender and its fee payer, if they have one.
+type Signer<'a> = (&'a Transaction, Option<String>, Option<String>);
 
 /// Identifier of a particular block on an entire blockchain.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -20,6 +32,91 @@ pub struct BlockHeader {
 
     /// Current difficulty level of the network.
     pub difficulty: f64,
+
+    /// Hash of the consensus rules of the network, set on the genesis block only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+
+    /// Signature of the validator sealing the block under proof of authority, not part of the
+    /// hash it signs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<BlockSignature>,
+}
+
+impl BlockHeader {
+    /// Get the proof-of-work hash of the header.
+    ///
+    /// # Returns
+    ///
+    /// The SHA-256 digest of the canonical encoding of the header, read as a big-endian 256-bit
+    /// integer.
+    pub fn pow_hash(&self) -> [u8; 32] {
+        encoding::digest(BLOCK_HEADER_DOMAIN, &[self])
+    }
+
+    /// Get the target the proof-of-work hash must not exceed.
+    ///
+    /// A difficulty `d` sets the target to 2^(256 - 4d). Every unit of difficulty divides the
+    /// target by 16, one more leading hexadecimal zero, and the fractional part `f` requires
+    /// the digits following the `floor(d)` leading zeros, read as a fraction, to be at most
+    /// 16^-f. For example 2.5 requires two leading zeros followed by a digit below 4, so the
+    /// expected work `16^d` grows smoothly between whole difficulties.
+    ///
+    /// # Returns
+    ///
+    /// The target, as a big-endian 256-bit integer.
+    pub fn target(&self) -> [u8; 32] {
+        difficulty_target(self.difficulty)
+    }
+
+    /// Check whether the proof-of-work hash of the header is at most its target.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the header satisfies its difficulty.
+    pub fn meets_difficulty(&self) -> bool {
+        self.pow_hash() <= self.target()
+    }
+
+    /// Get the hash identifying the header and its block.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded proof-of-work hash of the header.
+    pub fn hash(&self) -> String {
+        encoding::to_hex(&self.pow_hash())
+    }
+
+    /// Get the work needed to mine the header.
+    ///
+    /// # Returns
+    ///
+    /// The expected amount of hashes computed to satisfy the difficulty.
+    pub fn work(&self) -> f64 {
+        16f64.powf(self.difficulty)
+    }
+}
+
+impl AsRef<BlockHeader> for BlockHeader {
+    fn as_ref(&self) -> &BlockHeader {
+        self
+    }
+}
+
+/// Breakdown of the funds issued and paid by a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockPayout {
+    /// Amount of newly issued funds of the coinbase.
+    pub subsidy: f64,
+
+    /// Total amount of fees paid by the transactions after the coinbase.
+    pub fees: f64,
+
+    /// Amount credited to the miner.
+    pub miner_payout: f64,
+
+    /// Amount of fees paid by the senders that no wallet receives.
+    pub burned: f64,
 }
 
 /// Data storage in a blockchain.
@@ -33,6 +130,10 @@ pub struct Block {
 
     /// An amount of transactions.
     pub transactions: Vec<Transaction>,
+
+    /// Hash of the header, cached when the block is mined or accepted.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) hash: String,
 }
 
 impl Block {
@@ -54,6 +155,8 @@ impl Block {
             previous_hash,
             merkle: String::new(),
             timestamp: Utc::now().timestamp(),
+            rules: None,
+            signature: None,
         };
 
         // Create a new block
@@ -61,39 +164,433 @@ impl Block {
             header,
             count: 0,
             transactions: vec![],
+            hash: String::new(),
+        }
+    }
+
+    /// Get the hash identifying the block.
+    ///
+    /// # Returns
+    ///
+    /// The cached hash of the header, or the hash computed from the header if the block was not
+    /// sealed yet.
+    pub fn hash(&self) -> String {
+        match self.hash.is_empty() {
+            true => self.compute_hash(),
+            false => self.hash.to_owned(),
+        }
+    }
+
+    /// Compute the hash of the canonical encoding of the header.
+    ///
+    /// # Returns
+    ///
+    /// The hex encoded proof-of-work hash of the header.
+    pub fn compute_hash(&self) -> String {
+        self.header.hash()
+    }
+
+    /// Cache the hash of the header, once the header is final.
+    pub fn seal(&mut self) {
+        self.hash = self.compute_hash();
+    }
+
+    /// Check whether the cached hash matches the header.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the block is not sealed or its header did not change since.
+    pub fn has_valid_hash(&self) -> bool {
+        self.hash.is_empty() || self.hash == self.compute_hash()
+    }
+
+    /// Drop the transactions of the block while keeping its header and transaction count.
+    pub fn prune(&mut self) {
+        self.transactions.clear();
+        self.transactions.shrink_to_fit();
+    }
+
+    /// Check whether the transactions of the block were pruned.
+    ///
+    /// # Returns
+    /// `true` if the block had transactions that are no longer stored.
+    pub fn is_pruned(&self) -> bool {
+        self.count > 0 && self.transactions.is_empty()
+    }
+
+    /// Verify every transaction of the block one after another.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all transactions are valid, `false` on the first invalid one.
+    pub fn verify_transactions(&self) -> bool {
+        self.transactions.iter().all(Transaction::verify)
+    }
+
+    /// Check whether the transactions of the block match its header and their hashes.
+    ///
+    /// The signatures depend on the keys of the wallets, and are verified by
+    /// `verify_signatures`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the count and the Merkle root match the transactions, and every transaction is
+    /// valid.
+    pub fn has_valid_transactions(&self) -> bool {
+        self.count == self.transactions.len()
+            && self.header.merkle == Chain::get_merkle(self.transactions.clone())
+            && self.verify_transactions_parallel()
+    }
+
+    /// Verify every transaction of the block in parallel.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all transactions are valid, `false` as soon as an invalid one is found.
+    pub fn verify_transactions_parallel(&self) -> bool {
+        self.transactions.par_iter().all(Transaction::verify)
+    }
+
+    /// Verify the signatures of the senders and the fee payers of the transactions one after
+    /// another.
+    ///
+    /// # Arguments
+    ///
+    /// - `keys` - The public keys of the wallets as of the previous block, updated with the key
+    ///   rotations of the block.
+    ///
+    /// # Returns
+    ///
+    /// `SignatureRequired` or `InvalidSignature` for the first transaction of a wallet with a
+    /// public key that is not validly signed.
+    pub fn verify_signatures(&self, keys: &mut HashMap<Address, String>) -> Result<(), ChainError> {
+        match self.signers(keys).iter().find_map(check_signer) {
+            Some(err) => Err(err),
+            None => Ok(()),
         }
     }
 
+    /// Verify the signatures of the senders and the fee payers of the transactions in parallel.
+    ///
+    /// The keys are looked up one transaction after another, so that a transaction signed by a
+    /// key rotated earlier in the block is verified against the new key. Only the signatures are
+    /// verified in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// - `keys` - The public keys of the wallets as of the previous block, updated with the key
+    ///   rotations of the block.
+    ///
+    /// # Returns
+    ///
+    /// `SignatureRequired` or `InvalidSignature` for the first transaction of a wallet with a
+    /// public key that is not validly signed.
+    pub fn verify_signatures_parallel(
+        &self,
+        keys: &mut HashMap<Address, String>,
+    ) -> Result<(), ChainError> {
+        match self.signers(keys).par_iter().find_map_first(check_signer) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Look up the public keys of the sender and the fee payer of every transaction but the
+    /// coinbase, applying the key rotations of the block along the way.
+    ///
+    /// # Arguments
+    ///
+    /// - `keys` - The public keys of the wallets as of the previous block.
+    ///
+    /// # Returns
+    ///
+    /// The transactions with the public keys of their sender and fee payer, if any.
+    fn signers(&self, keys: &mut HashMap<Address, String>) -> Vec<Signer<'_>> {
+        let mut signers = Vec::with_capacity(self.transactions.len());
+
+        for transaction in self.transactions.iter().skip(1) {
+            let sender = keys.get(&transaction.from).cloned();
+            let fee_payer = transaction
+                .fee_payer
+                .as_ref()
+                .and_then(|fee_payer| keys.get(fee_payer).cloned());
+
+            if let Some(rotation) = &transaction.key_rotation {
+                keys.insert(transaction.from.to_owned(), rotation.public_key.to_owned());
+            }
+
+            signers.push((transaction, sender, fee_payer));
+        }
+
+        signers
+    }
+
+    /// Sort the transactions of the block in the canonical order.
+    ///
+    /// The first transaction is the coinbase and keeps its position. The others are sorted by
+    /// sender nonce, then by fee from the highest, then by hash, so that blocks built
+    /// independently from the same transactions hash identically. The sender nonce is the
+    /// position of a transaction among the transactions of the same sender, which keeps the
+    /// submission order of each sender.
+    pub fn order_transactions(&mut self) {
+        let Some((coinbase, transactions)) = self.transactions.split_first() else {
+            return;
+        };
+
+        let mut ordered: Vec<(usize, &Transaction)> = sender_nonces(transactions)
+            .into_iter()
+            .zip(transactions)
+            .collect();
+        ordered.sort_by(|a, b| compare_transactions(*a, *b));
+
+        let mut sorted = Vec::with_capacity(self.transactions.len());
+        sorted.push(coinbase.clone());
+        sorted.extend(ordered.into_iter().map(|(_, t)| t.clone()));
+
+        self.transactions = sorted;
+    }
+
+    /// Check whether the transactions of the block follow the canonical order.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the transactions after the coinbase are sorted by sender nonce, fee, and hash.
+    pub fn is_ordered(&self) -> bool {
+        let Some((_, transactions)) = self.transactions.split_first() else {
+            return true;
+        };
+
+        let keyed: Vec<(usize, &Transaction)> = sender_nonces(transactions)
+            .into_iter()
+            .zip(transactions)
+            .collect();
+
+        keyed
+            .windows(2)
+            .all(|pair| compare_transactions(pair[0], pair[1]) != Ordering::Greater)
+    }
+
     /// Perform the proof-of-work process to mine a block.
     ///
+    /// The nonce is increased until the hash of the header meets the target of its difficulty,
+    /// and the timestamp is moved forward whenever every nonce was tried.
+    ///
     /// # Arguments
     /// - `header`: A mutable reference to the block header to be mined.
     pub fn proof_of_work(header: &mut BlockHeader) {
-        loop {
-            let hash = Chain::hash(header);
-            let slice = &hash[..header.difficulty as usize];
-
-            match slice.parse::<u32>() {
-                Ok(val) => {
-                    if val != 0 {
-                        header.nonce += 1;
-                    } else {
-                        break;
-                    }
-                }
-                Err(_) => {
-                    header.nonce += 1;
-
-                    continue;
-                }
-            };
+        while !header.meets_difficulty() {
+            Block::next_nonce(header);
+        }
+    }
+
+    /// Perform the proof-of-work process until the block is mined or the work is cancelled.
+    ///
+    /// The cancellation flag is checked between hashes, and the header keeps the last nonce
+    /// tried, so calling it again resumes the work where it stopped.
+    ///
+    /// # Arguments
+    /// - `header`: A mutable reference to the block header to be mined.
+    /// - `cancel`: The flag set by another thread to stop the work.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the header satisfies its difficulty, `false` if the work was cancelled first.
+    pub fn proof_of_work_cancellable(header: &mut BlockHeader, cancel: &AtomicBool) -> bool {
+        while !header.meets_difficulty() {
+            if cancel.load(AtomicOrdering::Relaxed) {
+                return false;
+            }
+
+            Block::next_nonce(header);
+        }
+
+        true
+    }
+
+    /// Move the header to the next nonce, and forward the timestamp whenever every nonce was tried.
+    ///
+    /// # Arguments
+    /// - `header`: A mutable reference to the block header being mined.
+    fn next_nonce(header: &mut BlockHeader) {
+        header.nonce = header.nonce.wrapping_add(1);
+
+        if header.nonce == 0 {
+            header.timestamp += 1;
+        }
+    }
+
+    /// Check whether the hash of the block header satisfies its difficulty.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the proof-of-work hash of the header is at most its target.
+    pub fn meets_difficulty(&self) -> bool {
+        self.header.meets_difficulty()
+    }
+
+    /// Get the work needed to mine the block.
+    ///
+    /// # Returns
+    ///
+    /// The expected amount of hashes computed to satisfy the difficulty.
+    pub fn work(&self) -> f64 {
+        self.header.work()
+    }
+
+    /// Get the weight of the block, limited by the consensus rules.
+    ///
+    /// # Returns
+    ///
+    /// The total amount of bytes of the serialized transactions.
+    pub fn weight(&self) -> usize {
+        self.transactions.iter().map(Transaction::weight).sum()
+    }
+
+    /// Get the breakdown of the subsidy and the fees of the block.
+    ///
+    /// The miner receives the coinbase only, which pays the gas fees of the metered transactions
+    /// on top of the subsidy, and the other fees paid by the senders are burned.
+    ///
+    /// # Returns
+    ///
+    /// The subsidy, the total fees, the miner payout, and the burned amount, or `None` if the
+    /// transactions of the block were pruned.
+    pub fn payout(&self) -> Option<BlockPayout> {
+        if self.is_pruned() {
+            return None;
         }
+
+        let (payout, gas_fees, transactions) = match self.transactions.split_first() {
+            Some((coinbase, rest)) if coinbase.from == "Root" => {
+                (coinbase.amount, self.gas_fees(), rest)
+            }
+            _ => (0.0, 0.0, self.transactions.as_slice()),
+        };
+        let fees: f64 = transactions.iter().map(Transaction::charged_fee).sum();
+
+        Some(BlockPayout {
+            subsidy: payout - gas_fees,
+            fees,
+            miner_payout: payout,
+            burned: fees - gas_fees,
+        })
     }
 }
 
+impl AsRef<BlockHeader> for Block {
+    fn as_ref(&self) -> &BlockHeader {
+        &self.header
+    }
+}
+
+/// Compute the proof-of-work target of a difficulty: 2^(256 - 4 * difficulty), see
+/// `BlockHeader::target`.
+///
+/// # Arguments
+/// - `difficulty`: The difficulty level.
+///
+/// # Returns
+/// The target, as a big-endian 256-bit integer.
+fn difficulty_target(difficulty: f64) -> [u8; 32] {
+    let bits = 256.0 - 4.0 * difficulty;
+
+    if bits >= 256.0 || bits.is_nan() {
+        return [u8::MAX; 32];
+    }
+
+    if bits < 0.0 {
+        return [0; 32];
+    }
+
+    // The target is a 53-bit mantissa shifted to the exponent
+    let exponent = bits.floor() as u32;
+    let mantissa = (2f64.powf(bits - exponent as f64) * (1u64 << 52) as f64) as u64;
+    let mut target = [0u8; 32];
+
+    for bit in (0..=52).filter(|bit| (mantissa >> bit) & 1 == 1) {
+        let position = exponent as i64 - 52 + bit;
+
+        if (0..256).contains(&position) {
+            target[31 - position as usize / 8] |= 1 << (position % 8);
+        }
+    }
+
+    target
+}
+
+/// Compute the sender nonce of every transaction.
+///
+/// # Arguments
+/// - `transactions`: The transactions, in submission order.
+///
+/// # Returns
+/// The position of each transaction among the transactions of the same sender.
+fn sender_nonces(transactions: &[Transaction]) -> Vec<usize> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    transactions
+        .iter()
+        .map(|transaction| {
+            let count = counts.entry(transaction.from.as_str()).or_insert(0);
+            *count += 1;
+
+            *count - 1
+        })
+        .collect()
+}
+
+/// Verify the signatures of a transaction by its sender and its fee payer.
+///
+/// # Arguments
+/// - `signer`: The transaction with the public keys of its sender and its fee payer.
+///
+/// # Returns
+/// `SignatureRequired` or `InvalidSignature` if a wallet with a public key did not validly sign
+/// the transaction, `None` otherwise.
+fn check_signer((transaction, sender, fee_payer): &Signer<'_>) -> Option<ChainError> {
+    if let Some(public_key) = fee_payer {
+        if transaction.fee_payer_signature.is_none() {
+            return Some(ChainError::SignatureRequired);
+        }
+
+        if !keys::verify_fee_payer_signature(public_key, transaction) {
+            return Some(ChainError::InvalidSignature);
+        }
+    }
+
+    if let Some(public_key) = sender {
+        if transaction.signature.is_none() {
+            return Some(ChainError::SignatureRequired);
+        }
+
+        if !keys::verify_signature(public_key, transaction) {
+            return Some(ChainError::InvalidSignature);
+        }
+    }
+
+    None
+}
+
+/// Compare two transactions with their sender nonces in the canonical order.
+///
+/// # Arguments
+/// - `a`: The sender nonce and the first transaction.
+/// - `b`: The sender nonce and the second transaction.
+///
+/// # Returns
+/// The ordering by sender nonce, then by fee from the highest, then by hash.
+fn compare_transactions(a: (usize, &Transaction), b: (usize, &Transaction)) -> Ordering {
+    a.0.cmp(&b.0)
+        .then_with(|| b.1.fee.total_cmp(&a.1.fee))
+        .then_with(|| a.1.hash.cmp(&b.1.hash))
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::{Chain, Keypair};
 
     #[test]
     fn test_proof_of_work() {
@@ -104,6 +601,120 @@ mod tests {
         assert!(!block.header.previous_hash.is_empty());
     }
 
+    #[test]
+    fn test_proof_of_work_cancellable() {
+        let mut block = Block::new("0".to_string(), 32.0);
+        let cancel = AtomicBool::new(true);
+
+        assert!(!Block::proof_of_work_cancellable(
+            &mut block.header,
+            &cancel
+        ));
+        assert_eq!(block.header.nonce, 0);
+
+        block.header.difficulty = 1.0;
+        cancel.store(false, AtomicOrdering::Relaxed);
+
+        assert!(Block::proof_of_work_cancellable(&mut block.header, &cancel));
+        assert!(block.meets_difficulty());
+    }
+
+    #[test]
+    fn test_meets_difficulty() {
+        let mut block = Block::new("0".to_string(), 2.0);
+        Block::proof_of_work(&mut block.header);
+
+        assert!(block.meets_difficulty());
+
+        block.header.difficulty = 65.0;
+
+        assert!(!block.meets_difficulty());
+    }
+
+    #[test]
+    fn test_block_hash() {
+        let mut block = Block::new("0".to_string(), 1.0);
+
+        assert!(block.hash.is_empty());
+        assert_eq!(block.hash(), encoding::to_hex(&block.header.pow_hash()));
+
+        block.seal();
+
+        assert_eq!(block.hash, block.compute_hash());
+        assert!(block.has_valid_hash());
+
+        block.header.nonce += 1;
+
+        assert!(!block.has_valid_hash());
+        assert_ne!(block.hash(), block.compute_hash());
+    }
+
+    #[test]
+    fn test_difficulty_target() {
+        let mut one = [0u8; 32];
+        one[0] = 0x10;
+
+        assert_eq!(difficulty_target(0.0), [u8::MAX; 32]);
+        assert_eq!(difficulty_target(1.0), one);
+        assert_eq!(difficulty_target(64.0)[31], 1);
+        assert_eq!(difficulty_target(65.0), [0; 32]);
+
+        // Fractional difficulties fall between the whole ones
+        assert!(difficulty_target(1.5) < difficulty_target(1.0));
+        assert!(difficulty_target(1.5) > difficulty_target(2.0));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_mined_header_satisfies_difficulty(
+            difficulty in 1.0..3.0f64,
+            timestamp in 0..i64::MAX / 2,
+            previous_hash in "[0-9a-f]{64}",
+        ) {
+            let mut block = Block::new(previous_hash, difficulty);
+            block.header.timestamp = timestamp;
+            Block::proof_of_work(&mut block.header);
+
+            let hash = block.hash();
+            let zeros = difficulty.floor() as usize;
+            let fraction = u64::from_str_radix(&hash[zeros..zeros + 12], 16).unwrap() as f64
+                / 16f64.powi(12);
+
+            // The hash has the whole leading zeros, then a fraction within the fractional part
+            prop_assert!(block.meets_difficulty());
+            prop_assert!(hash[..zeros].bytes().all(|digit| digit == b'0'));
+            prop_assert!(fraction <= 16f64.powf(zeros as f64 - difficulty) + 1e-12);
+        }
+
+        #[test]
+        fn test_target_decreases_with_difficulty(a in 0.0..65.0f64, b in 0.0..65.0f64) {
+            let (easier, harder) = (a.min(b), a.max(b));
+
+            prop_assert!(difficulty_target(easier) >= difficulty_target(harder));
+        }
+    }
+
+    #[test]
+    fn test_fractional_difficulty_prefix() {
+        let header = |hash: &str| {
+            let mut bytes = [0u8; 32];
+
+            for (index, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hash[index * 2..index * 2 + 2], 16).unwrap();
+            }
+
+            bytes
+        };
+
+        // 2.5 is two leading zeros followed by a digit below 4
+        assert!(header(&format!("003f{}", "f".repeat(60))) < difficulty_target(2.5));
+        assert!(header(&format!("0040{}", "0".repeat(60))) == difficulty_target(2.5));
+        assert!(header(&format!("0040{}1", "0".repeat(59))) > difficulty_target(2.5));
+        assert!(header(&format!("01{}", "0".repeat(62))) > difficulty_target(2.5));
+    }
+
     #[test]
     fn test_new_block() {
         let block = Block::new("0".to_string(), 3.0);
@@ -111,4 +722,160 @@ mod tests {
         assert_eq!(block.count, 0);
         assert_eq!(block.transactions.len(), 0);
     }
+
+    #[test]
+    fn test_verify_transactions() {
+        let mut block = Block::new("0".to_string(), 1.0);
+
+        for amount in 1..10 {
+            block.transactions.push(
+                Transaction::builder()
+                    .from("from")
+                    .to("to")
+                    .amount(amount as f64)
+                    .fee(0.1)
+                    .build(),
+            );
+        }
+
+        assert!(block.verify_transactions());
+        assert!(block.verify_transactions_parallel());
+
+        block.transactions[5].amount = 1000.0;
+
+        assert!(!block.verify_transactions());
+        assert!(!block.verify_transactions_parallel());
+    }
+
+    #[test]
+    fn test_verify_signatures() {
+        let keypair = Keypair::generate();
+        let keys = HashMap::from([(keypair.address(), keypair.public_key())]);
+        let mut block = Block::new("0".to_string(), 1.0);
+
+        // The first transaction is the coinbase
+        for amount in 0..10 {
+            block.transactions.push(
+                Transaction::builder()
+                    .from(keypair.address())
+                    .to("to")
+                    .amount(amount as f64 + 1.0)
+                    .fee(0.1)
+                    .build()
+                    .sign(&keypair),
+            );
+        }
+
+        assert_eq!(block.verify_signatures(&mut keys.clone()), Ok(()));
+        assert_eq!(block.verify_signatures_parallel(&mut keys.clone()), Ok(()));
+
+        block.transactions[5].signature = None;
+        block.transactions[7].signature = block.transactions[8].signature.clone();
+
+        assert_eq!(
+            block.verify_signatures(&mut keys.clone()),
+            Err(ChainError::SignatureRequired)
+        );
+        assert_eq!(
+            block.verify_signatures_parallel(&mut keys.clone()),
+            Err(ChainError::SignatureRequired)
+        );
+    }
+
+    #[test]
+    fn test_order_transactions() {
+        let transaction = |from: &str, fee: f64| {
+            Transaction::builder()
+                .from(from)
+                .to("to")
+                .amount(1.0)
+                .fee(fee)
+                .build()
+        };
+
+        let mut block = Block::new("0".to_string(), 1.0);
+        block.transactions = vec![
+            transaction("Root", 0.0),
+            transaction("a", 0.1),
+            transaction("a", 0.5),
+            transaction("b", 0.2),
+            transaction("c", 0.2),
+        ];
+
+        assert!(!block.is_ordered());
+
+        // Same transactions received in another order, keeping the order of each sender
+        let mut shuffled = block.clone();
+        shuffled.transactions[1..].rotate_left(2);
+
+        block.order_transactions();
+        shuffled.order_transactions();
+
+        assert!(block.is_ordered());
+        assert_eq!(block.transactions[0].from, "Root");
+        assert_eq!(block.transactions[3].from, "a");
+        assert_eq!(block.transactions[3].fee, 0.1);
+        assert_eq!(block.transactions[4].fee, 0.5);
+        assert_eq!(
+            Chain::get_merkle(block.transactions.clone()),
+            Chain::get_merkle(shuffled.transactions.clone())
+        );
+    }
+
+    #[test]
+    fn test_prune_block() {
+        let mut block = Block::new("0".to_string(), 1.0);
+        block.transactions.push(
+            Transaction::builder()
+                .from("Root")
+                .to("address")
+                .amount(100.0)
+                .fee(0.0)
+                .build(),
+        );
+        block.count = block.transactions.len();
+
+        assert!(!block.is_pruned());
+
+        block.prune();
+
+        assert!(block.is_pruned());
+        assert_eq!(block.count, 1);
+    }
+
+    #[test]
+    fn test_block_payout() {
+        let transaction = |from: &str, amount: f64, fee: f64| {
+            Transaction::builder()
+                .from(from)
+                .to("address")
+                .amount(amount)
+                .fee(fee)
+                .build()
+        };
+        let mut block = Block::new("0".to_string(), 1.0);
+
+        assert_eq!(block.payout(), Some(BlockPayout::default()));
+
+        block.transactions = vec![
+            transaction("Root", 100.0, 0.1),
+            transaction("sender", 10.0, 1.0),
+            transaction("sender", 20.0, 2.0),
+        ];
+        block.count = block.transactions.len();
+
+        assert_eq!(
+            block.payout(),
+            Some(BlockPayout {
+                subsidy: 100.0,
+                fees: 3.0,
+                miner_payout: 100.0,
+                burned: 3.0,
+            })
+        );
+
+        block.prune();
+
+        assert_eq!(block.payout(), None);
+    }
 }
@@ -0,0 +1,260 @@
+use std::{
+    fs, io,
+    net::ToSocketAddrs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Node, Peer};
+
+/// Peers dialed by a node on startup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapConfig {
+    /// Static peer addresses, as `host:port`.
+    #[serde(default)]
+    pub peers: Vec<String>,
+
+    /// DNS seeds, as `host:port`, resolved to every address they point to.
+    #[serde(default)]
+    pub seeds: Vec<String>,
+
+    /// File persisting the known-good peers for the next start, if any.
+    #[serde(default)]
+    pub peers_file: Option<PathBuf>,
+
+    /// Amount of dial attempts per peer.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the second attempt, doubled after every failure, in milliseconds.
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+
+    /// Maximum delay between two attempts, in milliseconds.
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        BootstrapConfig {
+            peers: vec![],
+            seeds: vec![],
+            peers_file: None,
+            max_attempts: default_max_attempts(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+/// Default amount of dial attempts per peer.
+fn default_max_attempts() -> u32 {
+    3
+}
+
+/// Default delay before the second attempt.
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+/// Default maximum delay between two attempts.
+fn default_max_backoff_ms() -> u64 {
+    30_000
+}
+
+impl BootstrapConfig {
+    /// Load the bootstrap configuration from a JSON file.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the configuration file.
+    ///
+    /// # Returns
+    /// The configuration, or an error if the file cannot be read or parsed.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+
+        serde_json::from_str(&content).map_err(io::Error::from)
+    }
+
+    /// Get the delay before a dial attempt.
+    ///
+    /// # Arguments
+    /// - `attempt`: The zero-based attempt number.
+    ///
+    /// # Returns
+    /// No delay for the first attempt, then an exponentially growing delay up to the maximum.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        if attempt == 0 {
+            return Duration::ZERO;
+        }
+
+        let factor = 2u64.saturating_pow(attempt - 1);
+        let delay = self.initial_backoff_ms.saturating_mul(factor);
+
+        Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+
+    /// Get the addresses to dial, known-good peers first, without duplicates.
+    ///
+    /// Seeds that cannot be resolved are skipped.
+    ///
+    /// # Returns
+    /// The candidate peer addresses.
+    pub fn candidates(&self) -> Vec<String> {
+        let known = match &self.peers_file {
+            Some(path) => load_known_peers(path),
+            None => vec![],
+        };
+
+        let seeds = self
+            .seeds
+            .iter()
+            .filter_map(|seed| seed.to_socket_addrs().ok())
+            .flatten()
+            .map(|address| address.to_string());
+
+        let mut candidates: Vec<String> = Vec::new();
+
+        for address in known.into_iter().chain(self.peers.clone()).chain(seeds) {
+            if !candidates.contains(&address) {
+                candidates.push(address);
+            }
+        }
+
+        candidates
+    }
+}
+
+/// Opens connections to peers.
+pub trait Dialer {
+    /// The connection to a peer.
+    type Peer: Peer;
+
+    /// Connect to a peer.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the peer.
+    ///
+    /// # Returns
+    /// The connection, or `None` if the peer is unreachable.
+    fn dial(&mut self, address: &str) -> Option<Self::Peer>;
+}
+
+/// Load the known-good peers persisted by a previous start.
+///
+/// # Arguments
+/// - `path`: The path of the peers file.
+///
+/// # Returns
+/// The peer addresses, or none if the file is missing or invalid.
+pub fn load_known_peers(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the known-good peers for the next start.
+///
+/// # Arguments
+/// - `path`: The path of the peers file.
+/// - `peers`: The peer addresses.
+///
+/// # Returns
+/// An error if the file cannot be written.
+pub fn save_known_peers(path: &Path, peers: &[String]) -> io::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(peers)?)
+}
+
+impl Node {
+    /// Dial the bootstrap peers and synchronize the mempool with every reachable one.
+    ///
+    /// Every candidate is dialed up to `max_attempts` times with an exponential backoff between
+    /// attempts. The reachable peers replace the content of the peers file, if configured.
+    ///
+    /// # Arguments
+    /// - `config`: The bootstrap configuration.
+    /// - `dialer`: The dialer opening the connections.
+    ///
+    /// # Returns
+    /// The reachable peers with their addresses.
+    pub fn bootstrap<D: Dialer>(
+        &mut self,
+        config: &BootstrapConfig,
+        dialer: &mut D,
+    ) -> Vec<(String, D::Peer)> {
+        let mut connected = Vec::new();
+
+        for address in config.candidates() {
+            let peer = (0..config.max_attempts.max(1)).find_map(|attempt| {
+                thread::sleep(config.backoff(attempt));
+
+                dialer.dial(&address)
+            });
+
+            if let Some(mut peer) = peer {
+                self.sync_mempool(&mut peer);
+                connected.push((address, peer));
+            }
+        }
+
+        if let Some(path) = &config.peers_file {
+            let addresses: Vec<String> = connected
+                .iter()
+                .map(|(address, _)| address.to_owned())
+                .collect();
+
+            // Losing the peers file only slows down the next start
+            let _ = save_known_peers(path, &addresses);
+        }
+
+        connected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let config = BootstrapConfig {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1_000,
+            ..BootstrapConfig::default()
+        };
+
+        assert_eq!(config.backoff(0), Duration::ZERO);
+        assert_eq!(config.backoff(1), Duration::from_millis(100));
+        assert_eq!(config.backoff(3), Duration::from_millis(400));
+        assert_eq!(config.backoff(10), Duration::from_millis(1_000));
+        assert_eq!(config.backoff(u32::MAX), Duration::from_millis(1_000));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let config = BootstrapConfig {
+            peers: vec!["10.0.0.1:8000".to_string(), "10.0.0.1:8000".to_string()],
+            seeds: vec!["127.0.0.1:8001".to_string(), "invalid seed".to_string()],
+            ..BootstrapConfig::default()
+        };
+
+        assert_eq!(
+            config.candidates(),
+            vec!["10.0.0.1:8000".to_string(), "127.0.0.1:8001".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: BootstrapConfig = serde_json::from_str(r#"{"peers":["a:1"]}"#).unwrap();
+
+        assert_eq!(config.peers, vec!["a:1".to_string()]);
+        assert_eq!(config.max_attempts, 3);
+        assert_eq!(config.peers_file, None);
+    }
+}
@@ -1,34 +1,193 @@
-use std::{collections::HashMap, fmt::Write, iter};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    iter,
+    sync::{mpsc::Sender, Arc},
+};
 
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::{Block, Transaction, Wallet};
+#[cfg(feature = "contracts")]
+use crate::ContractState;
+use crate::{
+    encoding::{self, EMAIL_DOMAIN, MERKLE_LEAF_DOMAIN, MERKLE_NODE_DOMAIN},
+    keys, Address, AddressGenerator, AddressMode, Asset, Block, BlockUndo, ChainConfig, ChainError,
+    ChainEvent, Clock, Consensus, ConsensusRules, DeterministicAddressGenerator,
+    DifficultyAlgorithm, EmailPrivacy, Evictions, FailedNotification, Gas, HdWallet, KeyRotation,
+    Keypair, LedgerState, Memo, MultiSigWallet, NotificationId, PaymentWatch, PluginId,
+    PolicyAuditEntry, ProofOfWork, RandomAddressGenerator, Ready, RegisteredPlugin,
+    RegisteredValidator, RegisteredWebhook, SecurityEvent, SpendingPolicy, StakingState,
+    StateSnapshot, Storage, SystemClock, Transaction, TransactionBuilder, TransactionIndex,
+    ValidatorId, Wallet, WalletSort, WatchId, MAX_MEMO_LENGTH,
+};
+
+/// Amount of the latest blocks whose fee rates are considered by `Chain::estimate_fee`.
+const FEE_ESTIMATE_BLOCKS: usize = 10;
 
 /// A blockchain.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Chain {
     /// Chain of blocks.
-    pub chain: Vec<Block>,
+    pub(crate) chain: Vec<Block>,
 
     /// List of transactions.
-    pub current_transactions: Vec<Transaction>,
+    pub(crate) current_transactions: Vec<Transaction>,
 
     /// Current difficulty level of the network.
-    pub difficulty: f64,
+    pub(crate) difficulty: f64,
 
     /// Blockchain genesis address.
-    pub address: String,
+    pub(crate) address: String,
 
     /// Block reward.
-    pub reward: f64,
+    pub(crate) reward: f64,
 
     /// Transaction fee.
-    pub fee: f64,
+    pub(crate) fee: f64,
 
     /// A map to associate wallets with their corresponding addresses and balances.
-    pub wallets: HashMap<String, Wallet>,
+    pub(crate) wallets: HashMap<String, Wallet>,
+
+    /// Optional blockchain settings.
+    #[serde(default)]
+    pub(crate) config: ChainConfig,
+
+    /// State snapshots taken by the pruning policy.
+    #[serde(default)]
+    pub(crate) snapshots: Vec<StateSnapshot>,
+
+    /// Total amount of funds issued to wallets.
+    #[serde(default)]
+    pub(crate) issued_supply: f64,
+
+    /// Total amount of fees paid by wallets.
+    #[serde(default)]
+    pub(crate) collected_fees: f64,
+
+    /// Amount of entries evicted to respect the memory caps.
+    #[serde(default)]
+    pub(crate) evictions: Evictions,
+
+    /// Spending policies of the wallets, by wallet address.
+    #[serde(default)]
+    pub(crate) policies: HashMap<String, SpendingPolicy>,
+
+    /// Changes to the spending policies, oldest first.
+    #[serde(default)]
+    pub(crate) policy_audit_log: Vec<PolicyAuditEntry>,
+
+    /// Key rotations and recoveries of the wallets, oldest first.
+    #[serde(default)]
+    pub(crate) security_audit_log: Vec<SecurityEvent>,
+
+    /// Record of the funds moved by the transactions, following the ledger model.
+    #[serde(default)]
+    pub(crate) ledger: LedgerState,
+
+    /// Owners and thresholds of the multi-signature wallets, by wallet address.
+    #[serde(default)]
+    pub(crate) multisig_wallets: HashMap<String, MultiSigWallet>,
+
+    /// Transactions awaiting the approvals of the owners of a multi-signature wallet, by wallet
+    /// address.
+    #[serde(default)]
+    pub(crate) proposals: HashMap<String, Transaction>,
+
+    /// Heights of the blockchain tagged with a name, by name.
+    #[serde(default)]
+    pub(crate) milestones: BTreeMap<String, usize>,
+
+    /// Changes applied by the blocks whose bodies are kept, by block height.
+    #[serde(default)]
+    pub(crate) undo_log: BTreeMap<usize, BlockUndo>,
+
+    /// Reason the transaction intake was paused for, `None` while transactions are accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) paused: Option<String>,
+
+    /// Stakes, delegations and pending rewards of the staking module.
+    #[serde(default)]
+    pub(crate) staking: StakingState,
+
+    /// Assets issued by the wallets, by identifier.
+    #[serde(default)]
+    pub(crate) assets: BTreeMap<String, Asset>,
+
+    /// Deployed WASM contracts.
+    #[cfg(feature = "contracts")]
+    #[serde(default)]
+    pub(crate) contracts: ContractState,
+
+    /// Custom scheme assigning addresses to new wallets, overriding the address mode.
+    #[serde(skip)]
+    address_generator: Option<Arc<dyn AddressGenerator>>,
+
+    /// Clock of the transaction and block timestamps, the system clock if `None`.
+    #[serde(skip)]
+    pub(crate) clock: Option<Arc<dyn Clock>>,
+
+    /// Consensus engine sealing and verifying the blocks, proof of work if `None`.
+    #[serde(skip)]
+    consensus: Option<Arc<dyn Consensus>>,
+
+    /// Backend storing the blocks and the wallets, `None` to keep them in memory only.
+    #[serde(skip)]
+    pub(crate) storage: Option<Arc<dyn Storage>>,
+
+    /// Amount of blocks written to the backend, `None` without backend.
+    #[serde(skip)]
+    pub(crate) stored_blocks: Option<usize>,
+
+    /// Height of the block including every indexed mined transaction, by transaction hash.
+    #[serde(skip)]
+    pub(crate) transaction_index: TransactionIndex,
+
+    /// Amount of blocks whose transactions are in the transaction index.
+    #[serde(skip)]
+    pub(crate) indexed_blocks: usize,
+
+    /// Watches for incoming payments.
+    #[serde(skip)]
+    pub(crate) watches: Vec<PaymentWatch>,
+
+    /// The identifier of the next payment watch.
+    #[serde(skip)]
+    pub(crate) next_watch_id: WatchId,
+
+    /// Senders of the events to the subscribers.
+    #[serde(skip)]
+    pub(crate) subscribers: Vec<Sender<ChainEvent>>,
+
+    /// Validators run on the submitted transactions, in registration order.
+    #[serde(skip)]
+    pub(crate) validators: Vec<RegisteredValidator>,
+
+    /// The identifier of the next transaction validator.
+    #[serde(skip)]
+    pub(crate) next_validator_id: ValidatorId,
+
+    /// Plugins notified of the applied blocks, in registration order.
+    #[serde(skip)]
+    pub(crate) plugins: Vec<RegisteredPlugin>,
+
+    /// The identifier of the next plugin.
+    #[serde(skip)]
+    pub(crate) next_plugin_id: PluginId,
+
+    /// Webhooks receiving the events, in registration order.
+    #[serde(skip)]
+    pub(crate) webhooks: Vec<RegisteredWebhook>,
+
+    /// Events whose delivery to a webhook exhausted its attempts, oldest first, persisted to be
+    /// re-driven after a restart.
+    #[serde(default)]
+    pub(crate) failed_notifications: Vec<FailedNotification>,
+
+    /// The identifier of the next failed notification.
+    #[serde(default)]
+    pub(crate) next_notification_id: NotificationId,
 }
 
 impl Chain {
@@ -42,19 +201,226 @@ impl Chain {
     /// # Returns
     /// A new `Chain` instance with the given parameters and a genesis block.
     pub fn new(difficulty: f64, reward: f64, fee: f64) -> Self {
-        let mut chain = Chain {
+        Chain::with_config(difficulty, reward, fee, ChainConfig::default())
+    }
+
+    /// Initialize a new blockchain with the specified parameters and settings.
+    ///
+    /// # Arguments
+    /// - `difficulty`: The initial mining difficulty level of the network.
+    /// - `reward`: The initial block reward for miners.
+    /// - `fee`: The transaction fee.
+    /// - `config`: The optional blockchain settings.
+    ///
+    /// # Returns
+    /// A new `Chain` instance with the given parameters and a genesis block.
+    pub fn with_config(difficulty: f64, reward: f64, fee: f64, config: ChainConfig) -> Self {
+        let mut chain = Chain::empty(difficulty, reward, fee, config);
+
+        // An invalid difficulty leaves the blockchain without a genesis block until it is updated
+        let _ = chain.generate_new_block();
+
+        chain
+    }
+
+    /// Initialize a new blockchain without any block.
+    ///
+    /// # Arguments
+    /// - `difficulty`: The initial mining difficulty level of the network.
+    /// - `reward`: The initial block reward for miners.
+    /// - `fee`: The transaction fee.
+    /// - `config`: The optional blockchain settings.
+    ///
+    /// # Returns
+    /// A new `Chain` instance with a random genesis address and no genesis block.
+    pub(crate) fn empty(difficulty: f64, reward: f64, fee: f64, config: ChainConfig) -> Self {
+        Chain {
+            ledger: LedgerState::new(config.consensus.ledger),
             fee,
             reward,
             difficulty,
+            config,
+            address_generator: None,
+            clock: None,
+            consensus: None,
+            storage: None,
+            stored_blocks: None,
+            transaction_index: TransactionIndex::default(),
+            indexed_blocks: 0,
+            watches: Vec::new(),
+            next_watch_id: 0,
+            subscribers: Vec::new(),
+            validators: Vec::new(),
+            next_validator_id: 0,
+            plugins: Vec::new(),
+            next_plugin_id: 0,
+            webhooks: Vec::new(),
+            failed_notifications: Vec::new(),
+            next_notification_id: 0,
             chain: Vec::new(),
+            snapshots: Vec::new(),
+            issued_supply: 0.0,
+            collected_fees: 0.0,
+            evictions: Evictions::default(),
+            policies: HashMap::new(),
+            policy_audit_log: Vec::new(),
+            security_audit_log: Vec::new(),
+            multisig_wallets: HashMap::new(),
+            proposals: HashMap::new(),
+            milestones: BTreeMap::new(),
+            undo_log: BTreeMap::new(),
+            paused: None,
+            staking: StakingState::default(),
+            assets: BTreeMap::new(),
+            #[cfg(feature = "contracts")]
+            contracts: ContractState::default(),
             wallets: HashMap::new(),
             current_transactions: Vec::new(),
             address: Chain::generate_address(42),
+        }
+    }
+
+    /// Get the blocks of the blockchain.
+    ///
+    /// # Returns
+    /// The blocks ordered by height, starting with the genesis block.
+    pub fn get_all_blocks(&self) -> &[Block] {
+        &self.chain
+    }
+
+    /// Get a block by its height.
+    ///
+    /// # Arguments
+    /// - `index`: The height of the block, the genesis block being at height 0.
+    ///
+    /// # Returns
+    /// The block, or `None` if the blockchain is not that high.
+    pub fn get_block(&self, index: usize) -> Option<&Block> {
+        self.chain.get(index)
+    }
+
+    /// Get a block by the hash of its header.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the block header.
+    ///
+    /// # Returns
+    /// The block, or `None` if no block has the hash.
+    pub fn get_block_by_hash(&self, hash: &str) -> Option<&Block> {
+        self.chain.iter().find(|block| block.hash() == hash)
+    }
+
+    /// Get a list of blocks in the blockchain.
+    ///
+    /// # Arguments
+    /// - `page`: The page number.
+    /// - `size`: The number of blocks per page.
+    ///
+    /// # Returns
+    /// The blocks of the specified page, ordered by height.
+    pub fn get_blocks(&self, page: usize, size: usize) -> Vec<Block> {
+        // Return an empty vector if the page size is zero
+        if size == 0 {
+            return Vec::new();
+        }
+
+        // Return an empty vector if the page is past the last block
+        let start = match page.saturating_sub(1).checked_mul(size) {
+            Some(start) if start < self.chain.len() => start,
+            _ => return Vec::new(),
         };
 
-        chain.generate_new_block();
+        self.chain[start..start.saturating_add(size).min(self.chain.len())].to_vec()
+    }
 
-        chain
+    /// Get the transactions waiting to be mined.
+    ///
+    /// # Returns
+    /// The pending transactions in the order they were added.
+    pub fn get_pending_transactions(&self) -> &[Transaction] {
+        &self.current_transactions
+    }
+
+    /// Get the current mining difficulty level of the network.
+    ///
+    /// # Returns
+    /// The mining difficulty.
+    pub fn get_difficulty(&self) -> f64 {
+        self.difficulty
+    }
+
+    /// Get the blockchain genesis address.
+    ///
+    /// # Returns
+    /// The address receiving the block rewards.
+    pub fn get_address(&self) -> &str {
+        &self.address
+    }
+
+    /// Get the block reward.
+    ///
+    /// # Returns
+    /// The reward for mining a block.
+    pub fn get_reward(&self) -> f64 {
+        self.reward
+    }
+
+    /// Get the transaction fee.
+    ///
+    /// # Returns
+    /// The transaction fee.
+    pub fn get_fee(&self) -> f64 {
+        self.fee
+    }
+
+    /// Estimate the fee of a transaction from the fee rates paid recently.
+    ///
+    /// The estimate is the amount times the median fee rate of the pending transactions and of
+    /// the transactions of the latest blocks, never below the fee of the blockchain nor the
+    /// minimum relay fee of the settings.
+    ///
+    /// # Arguments
+    /// - `amount`: The amount of the transaction.
+    ///
+    /// # Returns
+    /// The suggested fee.
+    pub fn estimate_fee(&self, amount: f64) -> f64 {
+        let mut rates: Vec<f64> = self
+            .chain
+            .iter()
+            .rev()
+            .take(FEE_ESTIMATE_BLOCKS)
+            .flat_map(|block| &block.transactions)
+            .chain(&self.current_transactions)
+            .filter(|transaction| {
+                transaction.from != "Root"
+                    && transaction.asset_id.is_none()
+                    && transaction.amount > 0.0
+            })
+            .map(|transaction| transaction.fee / transaction.amount)
+            .collect();
+
+        rates.sort_by(f64::total_cmp);
+
+        let rate = rates.get(rates.len() / 2).copied().unwrap_or(self.fee);
+
+        (amount * rate.max(self.fee)).max(self.config.min_relay_fee)
+    }
+
+    /// Get the consensus rules of the blockchain.
+    ///
+    /// # Returns
+    /// The consensus rules, hashed into the genesis block.
+    pub fn get_consensus_rules(&self) -> &ConsensusRules {
+        &self.config.consensus
+    }
+
+    /// Get the optional blockchain settings.
+    ///
+    /// # Returns
+    /// The blockchain settings.
+    pub fn get_config(&self) -> &ChainConfig {
+        &self.config
     }
 
     /// Get a list of current transactions in the blockchain.
@@ -66,8 +432,13 @@ impl Chain {
     /// # Returns
     /// A reference to a vector containing the current transactions for the specified page.
     pub fn get_transactions(&self, page: usize, size: usize) -> Vec<Transaction> {
+        // Return an empty vector if the page size is zero
+        if size == 0 {
+            return Vec::new();
+        }
+
         // Calculate the total number of pages
-        let total_pages = (self.current_transactions.len() + size - 1) / size;
+        let total_pages = self.current_transactions.len().div_ceil(size);
 
         // Return an empty vector if the page is greater than the total number of pages
         if page > total_pages {
@@ -95,53 +466,164 @@ impl Chain {
             .find(|&trx| trx.hash == hash)
     }
 
-    /// Add a new transaction to the blockchain.
+    /// Find a mined transaction by its hash.
+    ///
+    /// The transactions of pruned blocks are not stored, so they are not found.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The height of the block including the transaction and the transaction, or `None` if no
+    /// stored block includes it.
+    pub fn find_transaction_in_chain(&self, hash: &str) -> Option<(usize, &Transaction)> {
+        let height = self.indexed_height(hash)?;
+
+        self.chain[height]
+            .transactions
+            .iter()
+            .find(|transaction| transaction.hash == hash)
+            .map(|transaction| (height, transaction))
+    }
+
+    /// Add a new transaction to the blockchain, signed by the owner of the sender wallet.
+    ///
+    /// Only wallets with a public key spend through this method, so that the key owner alone
+    /// can spend their funds.
     ///
     /// # Arguments
     /// - `from`: The sender's address.
     /// - `to`: The receiver's address.
     /// - `amount`: The amount of the transaction.
+    /// - `keypair`: The keypair owning the sender wallet.
     ///
     /// # Returns
-    /// `true` if the transaction is successfully added to the current transactions.
-    pub fn add_transaction(&mut self, from: String, to: String, amount: f64) -> bool {
-        let total = amount * self.fee;
+    /// The hash of the transaction added to the current transactions, `UnregisteredKey` if the
+    /// sender wallet has no public key, or the reason it is invalid.
+    pub fn add_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        keypair: &Keypair,
+    ) -> Result<String, ChainError> {
+        self.add_transaction_with_memo(from, to, amount, None, keypair)
+    }
 
-        // Validate the transaction and create a new transaction if it is valid
-        let transaction = match self.validate_transaction(&from, &to, total) {
-            true => Transaction::new(from.to_owned(), to.to_owned(), self.fee, total),
-            false => return false,
-        };
+    /// Add a new transaction to the blockchain, with a plain memo such as a payment reference.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `memo`: The text of the memo, at most `MAX_MEMO_LENGTH` bytes long.
+    /// - `keypair`: The keypair owning the sender wallet.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `UnregisteredKey` if the
+    /// sender wallet has no public key, or the reason it is invalid.
+    pub fn add_transaction_with_memo(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        memo: Option<String>,
+        keypair: &Keypair,
+    ) -> Result<String, ChainError> {
+        self.add_wallet_transaction(from, to, amount, memo, None, Some(keypair))
+    }
 
-        // Update sender's balance
-        match self.wallets.get_mut(&from) {
-            Some(wallet) => {
-                wallet.balance -= total;
+    /// Add a new transaction that cannot be included in a block before a time, such as a
+    /// scheduled payment.
+    ///
+    /// The transaction is applied to the wallets right away and stays pending until a block
+    /// timestamp reaches its locktime.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `unlock_at`: The earliest block timestamp including the transaction, in seconds since
+    ///   the Unix epoch.
+    /// - `keypair`: The keypair owning the sender wallet.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `UnregisteredKey` if the
+    /// sender wallet has no public key, or the reason it is invalid.
+    pub fn add_timelocked_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        unlock_at: i64,
+        keypair: &Keypair,
+    ) -> Result<String, ChainError> {
+        self.add_wallet_transaction(from, to, amount, None, Some(unlock_at), Some(keypair))
+    }
 
-                // Add the transaction to the sender's transaction history
-                wallet.transactions.push(transaction.hash.to_owned());
-            }
-            None => return false,
-        };
+    /// Add a new transaction from a wallet without public key, such as the transfer of a
+    /// contract or of a replayed workload.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, or the reason it is invalid.
+    pub(crate) fn add_unsigned_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+    ) -> Result<String, ChainError> {
+        self.add_wallet_transaction(from, to, amount, None, None, None)
+    }
 
-        // Update receiver's balance
-        match self.wallets.get_mut(&to) {
-            Some(wallet) => {
-                wallet.balance += amount;
+    /// Add a new transaction from a wallet, signed if the wallet has a public key.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `memo`: The text of a plain memo, if any.
+    /// - `locktime`: The earliest block timestamp including the transaction, if any.
+    /// - `keypair`: The keypair owning the sender wallet, `None` for a wallet without public key.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, or the reason it is invalid.
+    fn add_wallet_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        memo: Option<String>,
+        locktime: Option<i64>,
+        keypair: Option<&Keypair>,
+    ) -> Result<String, ChainError> {
+        let mut builder = self.metered_transaction(from, to, amount, None, keypair.is_some())?;
+
+        if let Some(memo) = memo {
+            builder = builder.memo(Memo::plain(memo));
+        }
 
-                // Add the transaction to the receiver's transaction history
-                wallet.transactions.push(transaction.hash.to_owned());
-            }
-            None => return false,
+        if let Some(locktime) = locktime {
+            builder = builder.locktime(locktime);
+        }
+
+        let transaction = match keypair {
+            Some(keypair) => builder.build().sign(keypair),
+            None => builder.build(),
         };
+        let hash = transaction.hash.to_owned();
 
-        // Add the transaction to the current transactions
-        self.current_transactions.push(transaction);
+        self.submit_transaction(transaction)?;
 
-        true
+        Ok(hash)
     }
 
-    /// Validate a transaction.
+    /// Start building a new transaction from a wallet without public key, spending its unspent
+    /// outputs.
     ///
     /// # Arguments
     /// - `from`: The sender's address.
@@ -149,118 +631,1224 @@ impl Chain {
     /// - `amount`: The amount of the transaction.
     ///
     /// # Returns
-    /// `true` if the transaction is valid, `false` otherwise.
-    pub fn validate_transaction(&self, from: &str, to: &str, amount: f64) -> bool {
-        // Validate if the sender is not the root
-        if from == "Root" {
-            return false;
+    /// The builder of the transaction to submit, or the reason it is invalid.
+    pub(crate) fn unsigned_transaction(
+        &self,
+        from: String,
+        to: String,
+        amount: f64,
+    ) -> Result<TransactionBuilder<Ready>, ChainError> {
+        self.metered_transaction(from, to, amount, None, false)
+    }
+
+    /// Start building a new transaction from a wallet, possibly metered, spending its unspent
+    /// outputs.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `gas`: The gas of a metered transaction, `None` to pay the fee of the blockchain.
+    /// - `signed`: Whether the transaction is signed by the owner of the sender wallet.
+    ///
+    /// # Returns
+    /// The builder of the transaction to submit, `SignatureRequired` or `UnregisteredKey` if the
+    /// sender wallet has a public key or none while the transaction is unsigned or signed, or
+    /// the reason it is invalid.
+    pub(crate) fn metered_transaction(
+        &self,
+        from: String,
+        to: String,
+        amount: f64,
+        gas: Option<Gas>,
+        signed: bool,
+    ) -> Result<TransactionBuilder<Ready>, ChainError> {
+        self.check_intake()?;
+
+        let fee = gas.map_or(amount * self.fee, |gas| gas.max_fee());
+
+        // Validate the transaction before hashing it
+        self.check_spending_policy(&from, &to, amount)?;
+        self.check_transfer(&from, &to, &from, amount, fee)?;
+
+        // Wallets with a public key only spend through signed transactions, and the signatures
+        // of the others cannot be verified, except the approvals of multi-signature wallets
+        let keyed = self
+            .wallets
+            .get(&from)
+            .is_some_and(|wallet| wallet.public_key.is_some());
+
+        match (keyed, signed) {
+            (true, false) => return Err(ChainError::SignatureRequired),
+            (false, true) if !self.multisig_wallets.contains_key(&from) => {
+                return Err(ChainError::UnregisteredKey(from))
+            }
+            _ => {}
         }
 
-        // Validate that sender and receiver addresses are different
-        if from == to {
-            return false;
+        let inputs = self.select_inputs(&from, amount + fee)?;
+        let nonce = self.get_nonce(&from).unwrap_or_default();
+        let builder = Transaction::builder().from(from).to(to).amount(amount);
+        let builder = match gas {
+            Some(gas) => builder.gas(gas),
+            None => builder.fee_rate(self.fee),
         }
+        .nonce(nonce)
+        .inputs(inputs)
+        .precision(self.config.timestamps)
+        .clock(self.clock());
 
-        // Validate if the amount is non-negative
-        if amount <= 0.0 {
-            return false;
+        Ok(builder)
+    }
+
+    /// Add a new transaction whose fee is paid by another wallet than the sender.
+    ///
+    /// The sender pays the amount only, and the fee payer pays the fee of `amount * fee`. The
+    /// transaction is signed by the sender and by the fee payer, agreeing to pay its fee.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `fee_payer`: The fee payer's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `keypair`: The keypair owning the sender wallet.
+    /// - `fee_payer_keypair`: The keypair owning the fee payer wallet.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `UnregisteredKey` if the
+    /// sender or the fee payer wallet has no public key, or the reason it is invalid.
+    pub fn add_sponsored_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        fee_payer: String,
+        amount: f64,
+        keypair: &Keypair,
+        fee_payer_keypair: &Keypair,
+    ) -> Result<String, ChainError> {
+        self.check_intake()?;
+
+        // Validate the transaction before hashing it
+        self.check_spending_policy(&from, &to, amount)?;
+        self.check_transfer(&from, &to, &fee_payer, amount, amount * self.fee)?;
+
+        // The signatures of wallets without public key cannot be verified, except the approvals
+        // of multi-signature senders
+        let keyed = |address: &String| {
+            self.wallets
+                .get(address)
+                .is_some_and(|wallet| wallet.public_key.is_some())
+        };
+
+        if !keyed(&from) && !self.multisig_wallets.contains_key(&from) {
+            return Err(ChainError::UnregisteredKey(from));
         }
 
-        // Validate if sender and receiver addresses are valid
-        let sender = match self.wallets.get(from) {
-            Some(wallet) => wallet,
-            None => return false,
+        if !keyed(&fee_payer) {
+            return Err(ChainError::UnregisteredKey(fee_payer));
+        }
+
+        let inputs = match fee_payer == from {
+            true => self.select_inputs(&from, amount + amount * self.fee)?,
+            false => [
+                self.select_inputs(&from, amount)?,
+                self.select_inputs(&fee_payer, amount * self.fee)?,
+            ]
+            .concat(),
         };
+        let nonce = self.get_nonce(&from).unwrap_or_default();
+        let transaction = Transaction::builder()
+            .from(from)
+            .to(to)
+            .amount(amount)
+            .fee_rate(self.fee)
+            .nonce(nonce)
+            .inputs(inputs)
+            .fee_payer(fee_payer)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build()
+            .sign(keypair)
+            .sponsor(fee_payer_keypair);
+        let hash = transaction.hash.to_owned();
+
+        self.submit_transaction(transaction)?;
+
+        Ok(hash)
+    }
 
-        // Validate if the receiver address is valid
-        if !self.wallets.contains_key(to) {
-            return false;
+    /// Submit a transaction built with `Transaction::builder()` to the blockchain.
+    ///
+    /// The fee of the transaction may exceed the fee of the blockchain, but not be lower. If the
+    /// sender wallet has a public key, the transaction must be signed with its keypair, and a
+    /// transaction already recorded in the history of the sender is rejected as a replay. A fee
+    /// below the minimum relay fee of the settings is rejected first, the spending policy of
+    /// the sender, if any, is evaluated before the signatures, and the registered validators
+    /// after the consensus validation. Every transaction is rejected while the intake is paused.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// An error describing why the transaction is invalid, if it is.
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<(), ChainError> {
+        self.check_intake()?;
+
+        // Contract wallets only send the transfers of their calls
+        #[cfg(feature = "contracts")]
+        self.check_contract_sender(&transaction.from)?;
+
+        // Key rotations, asset issuances and staking operations pay no fee to other wallets
+        let transfer = transaction.key_rotation.is_none()
+            && transaction.asset_name.is_none()
+            && transaction.staking.is_none();
+
+        // The relay fee floor is a policy of the mempool, not a consensus rule
+        if transfer && transaction.fee < self.config.min_relay_fee {
+            return Err(ChainError::RelayFeeTooLow);
+        }
+
+        // The spending policy of the sender is evaluated before its signature
+        if transfer {
+            self.check_spending_policy(
+                &transaction.from,
+                &transaction.to,
+                transaction.native_amount(),
+            )?;
+        }
+
+        let added = (!self.subscribers.is_empty() || !self.webhooks.is_empty())
+            .then(|| Box::new(transaction.to_owned()));
+
+        self.accept_transaction(transaction, true, false)?;
+
+        if let Some(transaction) = added {
+            self.emit(|_| ChainEvent::TransactionAdded { transaction });
+        }
+
+        Ok(())
+    }
+
+    /// Stop accepting new transactions, e.g. while the storage is migrated or an incident is
+    /// investigated.
+    ///
+    /// Submitted transactions are rejected with `ChainPaused` until the intake is resumed. The
+    /// pending transactions can still be mined, blocks from other nodes are still imported, and
+    /// queries are unaffected. The pause is kept when the blockchain is persisted.
+    ///
+    /// # Arguments
+    /// - `reason`: The reason given to the rejected submitters.
+    pub fn pause_intake(&mut self, reason: &str) {
+        self.paused = Some(reason.to_string());
+    }
+
+    /// Accept new transactions again after `pause_intake`.
+    ///
+    /// # Returns
+    /// The reason of the pause, or `None` if the intake was not paused.
+    pub fn resume_intake(&mut self) -> Option<String> {
+        self.paused.take()
+    }
+
+    /// Get the reason the transaction intake is paused for.
+    ///
+    /// # Returns
+    /// The reason, or `None` if transactions are accepted.
+    pub fn intake_paused(&self) -> Option<&str> {
+        self.paused.as_deref()
+    }
+
+    /// Check whether the blockchain accepts new transactions.
+    ///
+    /// # Returns
+    /// `ChainPaused` with the reason of the pause if the intake is paused.
+    pub(crate) fn check_intake(&self) -> Result<(), ChainError> {
+        match &self.paused {
+            Some(reason) => Err(ChainError::ChainPaused(reason.to_owned())),
+            None => Ok(()),
+        }
+    }
+
+    /// Submit a transaction, or get the hash of the identical transaction submitted before.
+    ///
+    /// The hash of a transaction is computed from its content, including the timestamp and the
+    /// nonce set when it is built, so a client retrying a submission whose response was lost
+    /// resubmits a transaction with the same hash. The retry is recognized instead of being
+    /// rejected as a replay, and spends nothing twice. A transaction whose block was pruned is
+    /// no longer recognized.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// The hash of the transaction, `DuplicateTransaction` if another transaction with the same
+    /// hash was submitted, e.g. with another fee or signature, or an error describing why the
+    /// transaction is invalid.
+    pub fn submit_transaction_idempotent(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<String, ChainError> {
+        if transaction.verify() {
+            if let Some(submitted) = self.lookup_transaction(&transaction.hash) {
+                let identical = submitted.signing_payload() == transaction.signing_payload()
+                    && submitted.signature == transaction.signature;
+
+                return match identical {
+                    true => Ok(transaction.hash),
+                    false => Err(ChainError::DuplicateTransaction),
+                };
+            }
         }
 
-        // Validate if sender can send the amount of the transaction
-        if sender.balance < amount {
-            return false;
-        }
+        let hash = transaction.hash.to_owned();
+
+        self.submit_transaction(transaction)?;
+
+        Ok(hash)
+    }
+
+    /// Apply a transaction to the wallets and add it to the current transactions.
+    ///
+    /// The spending policies and the validators are local rules, not consensus rules, so they
+    /// are not evaluated for the transactions of imported blocks.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    /// - `validate`: Whether to run the registered validators.
+    /// - `verified`: Whether the signatures of the sender and the fee payer were already verified,
+    ///   e.g. with the other transactions of an imported block.
+    ///
+    /// # Returns
+    /// An error describing why the transaction is invalid, if it is.
+    pub(crate) fn accept_transaction(
+        &mut self,
+        transaction: Transaction,
+        validate: bool,
+        verified: bool,
+    ) -> Result<(), ChainError> {
+        let (fee, amount) = (transaction.fee, transaction.amount);
+
+        // Validate the transaction hash
+        if !transaction.verify() {
+            return Err(ChainError::InvalidHash);
+        }
+
+        if let Some(memo) = &transaction.memo {
+            if memo.size() > MAX_MEMO_LENGTH {
+                return Err(ChainError::MemoTooLong {
+                    max: MAX_MEMO_LENGTH,
+                    actual: memo.size(),
+                });
+            }
+        }
+
+        // Validate the signature of the fee payer of a sponsored transaction
+        if let Some(fee_payer) = &transaction.fee_payer {
+            let public_key = match self.wallets.get(fee_payer) {
+                Some(wallet) => wallet.public_key.as_deref(),
+                None => return Err(ChainError::UnknownFeePayer(fee_payer.to_owned())),
+            };
+
+            // The owners of a multi-signature wallet only approve its own transactions
+            if fee_payer != &transaction.from && self.multisig_wallets.contains_key(fee_payer) {
+                return Err(ChainError::SignatureRequired);
+            }
+
+            if let Some(public_key) = public_key.filter(|_| !verified) {
+                if transaction.fee_payer_signature.is_none() {
+                    return Err(ChainError::SignatureRequired);
+                }
+
+                if !keys::verify_fee_payer_signature(public_key, &transaction) {
+                    return Err(ChainError::InvalidSignature);
+                }
+            }
+        }
+
+        // Validate the approvals of the owners of a multi-signature sender wallet
+        if let Some(multisig) = self.multisig_wallets.get(&transaction.from) {
+            multisig.check_approvals(&transaction)?;
+
+            if self
+                .wallets
+                .get(&transaction.from)
+                .is_some_and(|wallet| wallet.transactions.contains(&transaction.hash))
+            {
+                return Err(ChainError::DuplicateTransaction);
+            }
+        }
+
+        // Validate the signature and that the transaction is not replayed
+        if let Some(sender) = self.wallets.get(&transaction.from) {
+            if let Some(public_key) = &sender.public_key {
+                if !verified && transaction.signature.is_none() {
+                    return Err(ChainError::SignatureRequired);
+                }
+
+                if !verified && !keys::verify_signature(public_key, &transaction) {
+                    return Err(ChainError::InvalidSignature);
+                }
+
+                if sender.transactions.contains(&transaction.hash) {
+                    return Err(ChainError::DuplicateTransaction);
+                }
+            }
+
+            self.check_nonce(&transaction.from, transaction.nonce)?;
+        }
+
+        if transaction.key_rotation.is_some() {
+            return self.accept_key_rotation(transaction);
+        }
+
+        if transaction.asset_name.is_some() {
+            return self.accept_asset_issuance(transaction);
+        }
+
+        if transaction.staking.is_some() {
+            return self.accept_staking(transaction);
+        }
+
+        // Asset transfers move no coins and pay the fee of the blockchain as a flat fee
+        let native = transaction.native_amount();
+        let min_fee = match transaction.asset_id {
+            Some(_) => self.fee,
+            None => amount * self.fee,
+        };
+
+        // Validate the transaction, the minimum fee and that the sender and the fee payer can pay,
+        // the payer of a metered transaction holding its maximum fee
+        let required = transaction.gas.map_or(min_fee, |gas| gas.max_fee());
+
+        match &transaction.asset_id {
+            Some(asset_id) => self.check_asset_transfer(
+                &transaction.from,
+                &transaction.to,
+                transaction.payer(),
+                asset_id,
+                amount,
+                required,
+            )?,
+            None => self.check_transfer(
+                &transaction.from,
+                &transaction.to,
+                transaction.payer(),
+                amount,
+                required,
+            )?,
+        }
+
+        // Metered transactions pay for their gas instead of a share of the amount
+        match transaction.gas {
+            Some(_) => transaction.check_gas()?,
+            None if fee < min_fee => return Err(ChainError::FeeTooLow),
+            None => {}
+        }
+
+        if !self.can_pay(&transaction.from, transaction.payer(), native, fee) {
+            return Err(ChainError::InsufficientBalance);
+        }
+
+        // Validate that the inputs are unspent outputs covering the amount and the fee
+        self.ledger().check(&transaction)?;
+
+        // Run the rules of the embedder once the consensus rules are satisfied
+        if validate {
+            self.run_validators(&transaction)?;
+        }
+
+        // Make room for the transaction within the mempool cap
+        if !self.reserve_mempool(&transaction) {
+            return Err(self.mempool_full());
+        }
+
+        // Update sender's balance
+        match self.wallets.get_mut(&transaction.from) {
+            Some(wallet) => {
+                wallet.balance -= native;
+                wallet.nonce += 1;
+
+                if let Some(asset_id) = &transaction.asset_id {
+                    *wallet.assets.entry(asset_id.to_owned()).or_default() -= amount;
+                }
+
+                // Add the transaction to the sender's transaction history
+                wallet.transactions.push(transaction.hash.to_owned());
+            }
+            None => return Err(ChainError::UnknownSender(transaction.from)),
+        };
+
+        // Update fee payer's balance, the sender unless the transaction is sponsored, refunding
+        // the gas left by a metered transaction
+        let sponsor =
+            transaction.payer() != transaction.from && transaction.payer() != transaction.to;
+        let fee = transaction.charged_fee();
+
+        if let Some(wallet) = self.wallets.get_mut(transaction.payer()) {
+            wallet.balance -= fee;
+
+            // Add the transaction to the history of a fee payer taking no other part in it
+            if sponsor {
+                wallet.transactions.push(transaction.hash.to_owned());
+            }
+        }
+
+        // Update receiver's balance
+        match self.wallets.get_mut(&transaction.to) {
+            Some(wallet) => {
+                wallet.balance += native;
+
+                if let Some(asset_id) = &transaction.asset_id {
+                    *wallet.assets.entry(asset_id.to_owned()).or_default() += amount;
+                }
+
+                // Add the transaction to the receiver's transaction history
+                wallet.transactions.push(transaction.hash.to_owned());
+            }
+            None => return Err(ChainError::UnknownReceiver(transaction.to)),
+        };
+
+        // Add the transaction to the current transactions
+        self.ledger_mut().apply(&transaction);
+        self.current_transactions.push(transaction);
+        self.collected_fees += fee;
+
+        self.assert_invariants();
+
+        Ok(())
+    }
+
+    /// Apply a key rotation whose signature was verified and add it to the current transactions.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction recording the key rotation.
+    ///
+    /// # Returns
+    /// An error describing why the key rotation is invalid, if it is.
+    fn accept_key_rotation(&mut self, transaction: Transaction) -> Result<(), ChainError> {
+        let invalid = |reason: &str| Err(ChainError::InvalidKeyRotation(reason.to_string()));
+
+        let Some(rotation) = &transaction.key_rotation else {
+            return invalid("the transaction records no key rotation");
+        };
+
+        if transaction.from != transaction.to
+            || transaction.amount != 0.0
+            || transaction.fee != 0.0
+            || transaction.gas.is_some()
+            || transaction.asset_id.is_some()
+            || transaction.fee_payer.is_some()
+            || !transaction.inputs.is_empty()
+        {
+            return invalid("the transaction moves funds");
+        }
+
+        if !keys::is_valid_public_key(&rotation.public_key) {
+            return Err(ChainError::InvalidPublicKey);
+        }
+
+        if self.multisig_wallets.contains_key(&transaction.from) {
+            return invalid("the wallet is owned by several keys");
+        }
+
+        match self.wallets.get(&transaction.from) {
+            Some(wallet) if wallet.public_key != rotation.previous => {
+                return invalid("the previous key does not own the wallet");
+            }
+            Some(_) => {}
+            None => return Err(ChainError::UnknownSender(transaction.from)),
+        }
+
+        if !self.reserve_mempool(&transaction) {
+            return Err(self.mempool_full());
+        }
+
+        if let Some(wallet) = self.wallets.get_mut(&transaction.from) {
+            wallet.public_key = Some(rotation.public_key.to_owned());
+            wallet.nonce += 1;
+            wallet.transactions.push(transaction.hash.to_owned());
+        }
+
+        self.record_security_event(&transaction);
+        self.current_transactions.push(transaction);
+
+        self.assert_invariants();
+
+        Ok(())
+    }
+
+    /// Validate a transaction.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    ///
+    /// # Returns
+    /// `true` if the transaction is valid, `false` otherwise.
+    pub fn validate_transaction(&self, from: &str, to: &str, amount: f64) -> bool {
+        self.check_transaction(from, to, amount).is_ok()
+    }
+
+    /// Validate a transaction and tell why it is invalid.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    ///
+    /// # Returns
+    /// An error describing the first rule the transaction breaks, if any.
+    pub fn check_transaction(&self, from: &str, to: &str, amount: f64) -> Result<(), ChainError> {
+        self.check_spending_policy(from, to, amount)?;
+        self.check_transfer(from, to, from, amount, amount * self.fee)
+    }
+
+    /// Validate the nonce of a transaction, which must follow the last transaction of the sender.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `nonce`: The nonce of the transaction.
+    ///
+    /// # Returns
+    /// `InvalidNonce` if the nonce is already used or skips a transaction, or `UnknownSender` if
+    /// the sender is not a wallet.
+    pub fn check_nonce(&self, from: &str, nonce: u64) -> Result<(), ChainError> {
+        match self.get_nonce(from) {
+            Some(expected) if expected == nonce => Ok(()),
+            Some(expected) => Err(ChainError::InvalidNonce {
+                expected,
+                actual: nonce,
+            }),
+            None => Err(ChainError::UnknownSender(from.to_string())),
+        }
+    }
+
+    /// Validate a transaction whose fee is paid by a given wallet, possibly the sender.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `payer`: The fee payer's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `fee`: The fee the payer must hold.
+    ///
+    /// # Returns
+    /// An error describing the first rule the transaction breaks, if any.
+    fn check_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        payer: &str,
+        amount: f64,
+        fee: f64,
+    ) -> Result<(), ChainError> {
+        self.check_transfer_parties(from, to, payer, amount)?;
+
+        // Validate if sender can send the amount of the transaction and the payer can pay the fee
+        if !self.can_pay(from, payer, amount, fee) {
+            return Err(ChainError::InsufficientBalance);
+        }
+
+        Ok(())
+    }
+
+    /// Validate the wallets and the amount of a transaction, regardless of the balances.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `payer`: The fee payer's address.
+    /// - `amount`: The amount of the transaction.
+    ///
+    /// # Returns
+    /// An error describing the first rule the transaction breaks, if any.
+    pub(crate) fn check_transfer_parties(
+        &self,
+        from: &str,
+        to: &str,
+        payer: &str,
+        amount: f64,
+    ) -> Result<(), ChainError> {
+        // Validate if the sender is not the root
+        if from == "Root" {
+            return Err(ChainError::UnknownSender(from.to_string()));
+        }
+
+        // Validate that sender and receiver addresses are different
+        if from == to {
+            return Err(ChainError::SelfTransfer);
+        }
+
+        // Validate if the amount is positive
+        if amount.is_nan() || amount <= 0.0 {
+            return Err(ChainError::InvalidAmount);
+        }
+
+        // Validate if sender and receiver addresses are valid
+        if !self.wallets.contains_key(from) {
+            return Err(ChainError::UnknownSender(from.to_string()));
+        }
+
+        // Validate if the receiver address is valid
+        if !self.wallets.contains_key(to) {
+            return Err(ChainError::UnknownReceiver(to.to_string()));
+        }
+
+        // Validate if the fee payer address is valid
+        if payer == "Root" || !self.wallets.contains_key(payer) {
+            return Err(ChainError::UnknownFeePayer(payer.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the sender can pay the amount and the fee payer the fee of a transaction
+    /// from their spendable balances.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `payer`: The fee payer's address, possibly the sender.
+    /// - `amount`: The amount of the transaction.
+    /// - `fee`: The fee of the transaction.
+    ///
+    /// # Returns
+    /// `true` if both wallets exist and hold enough funds.
+    pub(crate) fn can_pay(&self, from: &str, payer: &str, amount: f64, fee: f64) -> bool {
+        let balance = |address: &str| self.get_spendable_balance(address);
+
+        match (balance(from), balance(payer)) {
+            (Some(sender), _) if from == payer => sender >= amount + fee,
+            (Some(sender), Some(payer)) => sender >= amount && payer >= fee,
+            _ => false,
+        }
+    }
+
+    /// Create a new wallet with a unique email and an initial balance.
+    ///
+    /// In the deterministic address mode, creating a wallet for an email that already has one
+    /// returns the existing address and keeps the wallet untouched.
+    ///
+    /// # Arguments
+    /// - `email`: The unique user email.
+    ///
+    /// # Returns
+    /// The newly created wallet address, or `AddressTaken` if the address belongs to the wallet
+    /// of another email or was generated by a custom address generator.
+    pub fn create_wallet(&mut self, email: String) -> Result<Address, ChainError> {
+        let address = match &self.address_generator {
+            Some(generator) => generator.generate(&self.config.chain_id, &email),
+            None => match &self.config.address_mode {
+                AddressMode::Random => {
+                    RandomAddressGenerator::default().generate(&self.config.chain_id, &email)
+                }
+                AddressMode::Deterministic { salt } => {
+                    DeterministicAddressGenerator::new(salt.to_owned())
+                        .generate(&self.config.chain_id, &email)
+                }
+            },
+        };
+
+        // Keep the existing wallet only if it was derived from the same email
+        if let Some(existing) = self.wallets.get(&address) {
+            let deterministic = self.address_generator.is_none()
+                && matches!(self.config.address_mode, AddressMode::Deterministic { .. });
+
+            if deterministic
+                && self
+                    .stored_email(&email)
+                    .is_none_or(|stored| stored == existing.email)
+            {
+                return Ok(address);
+            }
+
+            return Err(ChainError::AddressTaken(address));
+        }
+
+        let wallet = Wallet::new(
+            self.stored_email(&email).unwrap_or_default(),
+            address.to_owned(),
+            0.0,
+        );
+
+        self.wallets.insert(address.to_string(), wallet);
+
+        self.assert_invariants();
+
+        Ok(address)
+    }
+
+    /// Create a new wallet owned by a keypair.
+    ///
+    /// The address is derived from the public key, and outgoing transactions must be signed
+    /// with the matching keypair, by `add_transaction` or before `submit_transaction`.
+    ///
+    /// # Arguments
+    /// - `email`: The unique user email.
+    /// - `public_key`: The hex encoded ed25519 public key of the owner.
+    ///
+    /// # Returns
+    /// The address of the wallet, or `InvalidPublicKey` if the public key is invalid.
+    pub fn create_wallet_with_key(
+        &mut self,
+        email: String,
+        public_key: &str,
+    ) -> Result<Address, ChainError> {
+        if !keys::is_valid_public_key(public_key) {
+            return Err(ChainError::InvalidPublicKey);
+        }
+
+        let address = keys::address_from_public_key(public_key);
+
+        // Keep the existing wallet if the key is already registered
+        if self.wallets.contains_key(&address) {
+            return Ok(address);
+        }
+
+        let mut wallet = Wallet::new(
+            self.stored_email(&email).unwrap_or_default(),
+            address.to_owned(),
+            0.0,
+        );
+        wallet.public_key = Some(public_key.to_string());
+
+        self.wallets.insert(address.to_owned(), wallet);
+
+        self.assert_invariants();
+
+        Ok(address)
+    }
+
+    /// Create a new wallet owned by the first keypair of a new HD wallet.
+    ///
+    /// The mnemonic of the HD wallet is the only backup of the keypair, and derives the
+    /// keypairs of more wallets with `HdWallet::derive_child`.
+    ///
+    /// # Arguments
+    /// - `email`: The unique user email.
+    ///
+    /// # Returns
+    /// The address of the wallet, owned by the child 0 of the HD wallet, and the HD wallet.
+    pub fn create_wallet_with_mnemonic(
+        &mut self,
+        email: String,
+    ) -> Result<(Address, HdWallet), ChainError> {
+        let hd_wallet = HdWallet::generate();
+        let address =
+            self.create_wallet_with_key(email, &hd_wallet.derive_child(0).public_key())?;
+
+        Ok((address, hd_wallet))
+    }
+
+    /// Bind a new keypair to a wallet, revoking its current public key.
+    ///
+    /// The wallet keeps its address and balance. The rotation is recorded on-chain by a pending
+    /// transaction from the wallet to itself, signed with the current keypair when the wallet has
+    /// a public key. Transactions signed with the revoked key are rejected from then on, while
+    /// the ones already pending stay valid.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `current`: The keypair owning the wallet, required if the wallet has a public key.
+    ///
+    /// # Returns
+    /// The new keypair, or an error describing why the rotation is invalid.
+    pub fn rotate_keys(
+        &mut self,
+        address: &str,
+        current: Option<&Keypair>,
+    ) -> Result<Keypair, ChainError> {
+        let keypair = Keypair::generate();
+
+        self.submit_key_rotation(address, current, &keypair, false)?;
+
+        Ok(keypair)
+    }
+
+    /// Submit the transaction binding a new keypair to a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `current`: The keypair owning the wallet, required if the wallet has a public key.
+    /// - `keypair`: The new keypair.
+    /// - `recovery`: Whether the current keypair was restored from a backup.
+    ///
+    /// # Returns
+    /// An error describing why the rotation is invalid, if it is.
+    pub(crate) fn submit_key_rotation(
+        &mut self,
+        address: &str,
+        current: Option<&Keypair>,
+        keypair: &Keypair,
+        recovery: bool,
+    ) -> Result<(), ChainError> {
+        let (previous, nonce) = match self.wallets.get(address) {
+            Some(wallet) => (wallet.public_key.to_owned(), wallet.nonce),
+            None => return Err(ChainError::UnknownSender(address.to_string())),
+        };
+
+        let mut transaction = Transaction::builder()
+            .from(address)
+            .to(address)
+            .amount(0.0)
+            .fee(0.0)
+            .key_rotation(KeyRotation {
+                previous,
+                public_key: keypair.public_key(),
+                recovery,
+            })
+            .nonce(nonce)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build();
+
+        if let Some(current) = current {
+            transaction = transaction.sign(current);
+        }
+
+        self.submit_transaction(transaction)
+    }
+
+    /// Create a new wallet, or get the existing wallet of the email.
+    ///
+    /// Submitting the same email twice, e.g. when a client retries a request, returns the address
+    /// of the first wallet instead of creating an unrelated one, unless the emails are omitted by
+    /// the email privacy of the settings.
+    ///
+    /// # Arguments
+    /// - `email`: The unique user email.
+    ///
+    /// # Returns
+    /// The address of the existing or newly created wallet, or `AddressTaken` if the address
+    /// of the new wallet is already taken.
+    pub fn create_wallet_idempotent(&mut self, email: String) -> Result<Address, ChainError> {
+        match self.get_wallet_by_email(&email) {
+            Some(wallet) => Ok(wallet.address.to_owned()),
+            None => self.create_wallet(email),
+        }
+    }
+
+    /// Create many wallets at once.
+    ///
+    /// Uniqueness of every email is validated up front, against the existing wallets as well
+    /// as the rest of the batch, so one invalid email does not prevent the others from being created.
+    ///
+    /// # Arguments
+    /// - `emails`: The unique user emails.
+    ///
+    /// # Returns
+    /// The newly created wallet address or the reason of the failure for each email, in order.
+    pub fn create_wallets(&mut self, emails: &[String]) -> Vec<Result<Address, ChainError>> {
+        // Collect the emails that are already taken
+        let mut taken: HashSet<String> = self
+            .wallets
+            .values()
+            .map(|wallet| wallet.email.to_owned())
+            .collect();
+
+        self.wallets.reserve(emails.len());
+
+        emails
+            .iter()
+            .map(|email| {
+                // Validate if the email is not empty
+                if email.trim().is_empty() {
+                    return Err(ChainError::InvalidEmail);
+                }
+
+                // Validate if the email is unique, unless the emails are not stored
+                if let Some(stored) = self.stored_email(email) {
+                    if !taken.insert(stored) {
+                        return Err(ChainError::DuplicateEmail(email.to_owned()));
+                    }
+                }
+
+                self.create_wallet(email.to_owned())
+            })
+            .collect()
+    }
+
+    /// Get a wallet based on its address.
+    ///
+    /// # Arguments
+    /// - `address`: The unique wallet address.
+    ///
+    /// # Returns
+    /// An option containing a reference to the wallet if found, or `None` if not found.
+    pub fn get_wallet(&self, address: &str) -> Option<&Wallet> {
+        self.wallets.get(address)
+    }
+
+    /// Get a wallet based on its email.
+    ///
+    /// # Arguments
+    /// - `email`: The wallet email.
+    ///
+    /// # Returns
+    /// An option containing a reference to the wallet if found, the one with the lowest address if
+    /// several wallets share the email, or `None` if not found.
+    pub fn get_wallet_by_email(&self, email: &str) -> Option<&Wallet> {
+        let stored = self.stored_email(email)?;
+
+        self.wallets
+            .values()
+            .filter(|wallet| wallet.email == stored)
+            .min_by(|a, b| a.address.cmp(&b.address))
+    }
+
+    /// Get the email stored by the wallets for an email, following the email privacy of the
+    /// settings.
+    ///
+    /// # Arguments
+    /// - `email`: The wallet email.
+    ///
+    /// # Returns
+    /// The email or its salted hash, or `None` if the emails are omitted.
+    pub(crate) fn stored_email(&self, email: &str) -> Option<String> {
+        match self.config.email_privacy {
+            EmailPrivacy::Plain => Some(email.to_string()),
+            EmailPrivacy::Hashed => Some(encoding::hash(
+                EMAIL_DOMAIN,
+                &[&self.config.chain_id, &email],
+            )),
+            EmailPrivacy::Omitted => None,
+        }
+    }
+
+    /// Get the amount of wallets.
+    ///
+    /// # Returns
+    /// The amount of wallets in the blockchain.
+    pub fn get_wallets_count(&self) -> usize {
+        self.wallets.len()
+    }
+
+    /// Credit a wallet with newly issued funds, for tests only.
+    ///
+    /// The credit is recorded as a transaction from the root address, so it is part of the
+    /// wallet history and gets mined into the next block like any other transaction.
+    ///
+    /// # Arguments
+    /// - `address`: The unique wallet address.
+    /// - `amount`: The amount to add to the wallet balance.
+    ///
+    /// # Returns
+    /// `true` if the wallet is found and the amount is positive.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn credit_wallet(&mut self, address: &str, amount: f64) -> bool {
+        // Validate if the amount is positive
+        if amount <= 0.0 {
+            return false;
+        }
+
+        let transaction = Transaction::builder()
+            .from("Root")
+            .to(address)
+            .amount(amount)
+            .fee(0.0)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build();
+
+        // Update receiver's balance
+        match self.wallets.get_mut(address) {
+            Some(wallet) => {
+                wallet.balance += amount;
+
+                // Add the transaction to the receiver's transaction history
+                wallet.transactions.push(transaction.hash.to_owned());
+            }
+            None => return false,
+        };
+
+        // Add the transaction to the current transactions
+        self.ledger_mut().apply(&transaction);
+        self.current_transactions.push(transaction);
+        self.issued_supply += amount;
+
+        self.assert_invariants();
+
+        true
+    }
+
+    /// Set a custom scheme assigning addresses to new wallets.
+    ///
+    /// The generator takes precedence over the address mode of the settings.
+    ///
+    /// # Arguments
+    /// - `generator`: The address generator.
+    pub fn set_address_generator<G: AddressGenerator + 'static>(&mut self, generator: G) {
+        self.address_generator = Some(Arc::new(generator));
+    }
+
+    /// Set the clock of the transaction and block timestamps, the system clock by default.
+    ///
+    /// # Arguments
+    /// - `clock`: The clock.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// Get the clock of the transaction and block timestamps.
+    ///
+    /// # Returns
+    /// The clock set with `set_clock`, or the system clock.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone().unwrap_or_else(|| Arc::new(SystemClock))
+    }
+
+    /// Set the consensus engine sealing and verifying the blocks, proof of work by default.
+    ///
+    /// The engine applies to the blocks appended afterwards, the existing blocks are kept.
+    ///
+    /// # Arguments
+    /// - `consensus`: The consensus engine.
+    pub fn set_consensus(&mut self, consensus: Arc<dyn Consensus>) {
+        self.consensus = Some(consensus);
+    }
+
+    /// Get the consensus engine sealing and verifying the blocks.
+    ///
+    /// # Returns
+    /// The engine set with `set_consensus`, or proof of work.
+    pub fn consensus(&self) -> Arc<dyn Consensus> {
+        self.consensus
+            .clone()
+            .unwrap_or_else(|| Arc::new(ProofOfWork))
+    }
 
-        true
+    /// Get a wallet's balance based on its address.
+    ///
+    /// # Arguments
+    /// - `address`: The unique wallet address.
+    ///
+    /// # Returns
+    /// The wallet balance.
+    pub fn get_wallet_balance(&self, address: String) -> Option<f64> {
+        self.wallets.get(&address).map(|wallet| wallet.balance)
     }
 
-    /// Create a new wallet with a unique email and an initial balance.
+    /// Get the nonce expected on the next transaction sent from a wallet.
     ///
     /// # Arguments
-    /// - `email`: The unique user email.
+    /// - `address`: The unique wallet address.
+    ///
+    /// # Returns
+    /// The amount of transactions sent from the wallet, including the pending ones, or `None`
+    /// if the wallet is not found.
+    pub fn get_nonce(&self, address: &str) -> Option<u64> {
+        self.wallets.get(address).map(|wallet| wallet.nonce)
+    }
+
+    /// Get a list of wallets using pagination details.
+    ///
+    /// # Arguments
+    /// - `page`: The page number.
+    /// - `size`: The number of wallets per page.
+    /// - `sort`: The order of the wallets.
     ///
     /// # Returns
-    /// The newly created wallet address.
-    pub fn create_wallet(&mut self, email: String) -> String {
-        let address = Chain::generate_address(42);
+    /// The wallets for the specified page.
+    pub fn list_wallets(&self, page: usize, size: usize, sort: WalletSort) -> Vec<Wallet> {
+        // Return an empty vector if the page size is zero
+        if size == 0 {
+            return Vec::new();
+        }
 
-        let wallet = Wallet::new(email, address.to_owned(), 0.0);
+        let mut wallets: Vec<&Wallet> = self.wallets.values().collect();
 
-        self.wallets.insert(address.to_string(), wallet);
+        match sort {
+            WalletSort::Address => wallets.sort_by(|a, b| a.address.cmp(&b.address)),
+            WalletSort::Email => wallets.sort_by(|a, b| a.email.cmp(&b.email)),
+            WalletSort::BalanceAsc => wallets.sort_by(|a, b| a.balance.total_cmp(&b.balance)),
+            WalletSort::BalanceDesc => wallets.sort_by(|a, b| b.balance.total_cmp(&a.balance)),
+        }
 
-        address
+        // Calculate the start index for the wallets of the current page, past every wallet if it
+        // overflows
+        let start = page.saturating_sub(1).saturating_mul(size);
+
+        wallets
+            .into_iter()
+            .skip(start)
+            .take(size)
+            .cloned()
+            .collect()
     }
 
-    /// Get a wallet's balance based on its address.
+    /// Search wallets by the prefix of their email, when stored as given, or of their address.
     ///
     /// # Arguments
-    /// - `address`: The unique wallet address.
+    /// - `query`: The email or address prefix.
     ///
     /// # Returns
-    /// The wallet balance.
-    pub fn get_wallet_balance(&self, address: String) -> Option<f64> {
-        self.wallets.get(&address).map(|wallet| wallet.balance)
+    /// The matching wallets ordered by address.
+    pub fn search_wallets(&self, query: &str) -> Vec<Wallet> {
+        // Return an empty vector if the query is empty
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut wallets: Vec<Wallet> = self
+            .wallets
+            .values()
+            .filter(|wallet| {
+                // Hashed emails do not match the prefix of an email
+                (self.config.email_privacy == EmailPrivacy::Plain
+                    && wallet.email.starts_with(query))
+                    || wallet.address.starts_with(query)
+            })
+            .cloned()
+            .collect();
+
+        wallets.sort_by(|a, b| a.address.cmp(&b.address));
+
+        wallets
     }
 
     /// Get a wallet's transaction history based on its address.
     ///
+    /// The history includes the pending and the mined transactions, resolved with the
+    /// transaction index. The transactions of pruned blocks are not stored, so they are left out
+    /// of their page.
+    ///
     /// # Arguments
     /// - `address`: The unique wallet address.
     /// - `page`: The page number.
     /// - `size`: The number of transactions per page.
     ///
     /// # Returns
-    /// The wallet transaction history for the specified page.
+    /// The wallet transaction history for the specified page, or `None` if the wallet is not found.
     pub fn get_wallet_transactions(
         &self,
         address: String,
         page: usize,
         size: usize,
     ) -> Option<Vec<Transaction>> {
-        match self
-            .wallets
-            .get(&address)
-            .map(|wallet| wallet.transactions.to_owned())
-        {
-            // Get the transaction history of the wallet
-            Some(txs) => {
-                let mut result = Vec::new();
+        // Get the transaction history of the wallet
+        let wallet = self.wallets.get(&address)?;
 
-                // Calculate the total number of pages
-                let total_pages = (self.current_transactions.len() + size - 1) / size;
-
-                // Return an empty vector if the page is greater than the total number of pages
-                if page > total_pages {
-                    return Some(result);
-                }
+        // Return an empty vector if the page size is zero
+        if size == 0 {
+            return Some(Vec::new());
+        }
 
-                // Calculate the start and end indices for the transactions of the current page
-                let start = page.saturating_sub(1) * size;
-                let end = start + size;
+        // Return an empty vector if the page starts past any history
+        let Some(start) = page.saturating_sub(1).checked_mul(size) else {
+            return Some(Vec::new());
+        };
 
-                for tx in txs[start..end.min(txs.len())].iter() {
-                    match self.get_transaction(tx.to_string()) {
-                        Some(transaction) => result.push(transaction.to_owned()),
-                        None => continue,
-                    }
-                }
+        // Resolve the hashes of the page against the pending transactions and the mined blocks
+        let transactions = wallet
+            .transactions
+            .iter()
+            .skip(start)
+            .take(size)
+            .filter_map(|hash| self.lookup_transaction(hash))
+            .cloned()
+            .collect();
 
-                Some(result)
-            }
-            // Return None if the wallet is not found
-            None => None,
-        }
+        Some(transactions)
     }
 
     /// Get the hash of the last block in the blockchain.
@@ -275,20 +1863,37 @@ impl Chain {
             }
         };
 
-        Chain::hash(&block.header)
+        block.hash()
+    }
+
+    /// Get the hash of a block by its height.
+    ///
+    /// # Arguments
+    /// - `index`: The height of the block.
+    ///
+    /// # Returns
+    /// The hash of the block, or `None` if the height is unknown.
+    pub fn get_block_hash(&self, index: usize) -> Option<String> {
+        self.chain.get(index).map(Block::hash)
     }
 
     /// Update the mining difficulty of the blockchain.
     ///
+    /// With a retargeting difficulty algorithm, the difficulty is recomputed after the next block.
+    ///
     /// # Arguments
-    /// - `difficulty`: The new mining difficulty level.
+    /// - `difficulty`: The new mining difficulty level, between 1 and 64.
     ///
     /// # Returns
-    /// `true` if the difficulty is successfully updated.
-    pub fn update_difficulty(&mut self, difficulty: f64) -> bool {
-        self.difficulty = difficulty;
+    /// `InvalidDifficulty` if the difficulty is out of range.
+    pub fn update_difficulty(&mut self, difficulty: f64) -> Result<(), ChainError> {
+        if !Chain::is_valid_difficulty(difficulty) {
+            return Err(ChainError::InvalidDifficulty);
+        }
 
-        true
+        self.set_difficulty(difficulty);
+
+        Ok(())
     }
 
     /// Update the block reward.
@@ -297,11 +1902,15 @@ impl Chain {
     /// - `reward`: The new block reward value.
     ///
     /// # Returns
-    /// `true` if the reward is successfully updated.
-    pub fn update_reward(&mut self, reward: f64) -> bool {
+    /// `InvalidReward` if the reward is negative or not a number.
+    pub fn update_reward(&mut self, reward: f64) -> Result<(), ChainError> {
+        if !reward.is_finite() || reward < 0.0 {
+            return Err(ChainError::InvalidReward);
+        }
+
         self.reward = reward;
 
-        true
+        Ok(())
     }
 
     /// Update the transaction fee.
@@ -310,44 +1919,516 @@ impl Chain {
     /// - `fee`: The new transaction fee value.
     ///
     /// # Returns
-    /// `true` if the transaction fee is successfully updated.
-    pub fn update_fee(&mut self, fee: f64) -> bool {
+    /// `InvalidFee` if the fee is negative or not a number.
+    pub fn update_fee(&mut self, fee: f64) -> Result<(), ChainError> {
+        if !fee.is_finite() || fee < 0.0 {
+            return Err(ChainError::InvalidFee);
+        }
+
         self.fee = fee;
 
-        true
+        Ok(())
     }
 
     /// Generate a new block and append it to the blockchain.
     ///
+    /// The block reward goes to the address of the blockchain.
+    ///
+    /// # Returns
+    /// `InvalidDifficulty` if the difficulty of the blockchain cannot be mined.
+    pub fn generate_new_block(&mut self) -> Result<(), ChainError> {
+        self.mine_block(self.address.to_owned())
+    }
+
+    /// Generate a new block and credit its reward to the wallet of the miner.
+    ///
+    /// The reward can only be spent once the coinbase maturity of the consensus rules is reached.
+    ///
+    /// # Arguments
+    /// - `miner_address`: The address of the miner wallet.
+    ///
+    /// # Returns
+    /// `UnknownReceiver` if the miner is not a wallet, or `InvalidDifficulty` if the difficulty
+    /// of the blockchain cannot be mined.
+    pub fn generate_new_block_for(&mut self, miner_address: &str) -> Result<(), ChainError> {
+        if !self.wallets.contains_key(miner_address) {
+            return Err(ChainError::UnknownReceiver(miner_address.to_string()));
+        }
+
+        self.mine_block(miner_address.to_string())
+    }
+
+    /// Get the part of a wallet balance that can be spent.
+    ///
+    /// # Arguments
+    /// - `address`: The unique wallet address.
+    ///
+    /// # Returns
+    /// The balance without the block rewards that have not reached the coinbase maturity, or
+    /// `None` if the wallet is not found.
+    pub fn get_spendable_balance(&self, address: &str) -> Option<f64> {
+        let wallet = self.wallets.get(address)?;
+        let maturity = self.config.consensus.coinbase_maturity;
+
+        // The rewards of the last blocks do not have enough blocks mined on top of them yet
+        let immature: f64 = self
+            .chain
+            .iter()
+            .rev()
+            .take(maturity)
+            .filter_map(|block| block.transactions.first())
+            .filter(|coinbase| coinbase.from == "Root" && coinbase.to == address)
+            .map(|coinbase| coinbase.amount)
+            .sum();
+
+        Some(wallet.balance - immature)
+    }
+
+    /// Build the next block to mine on top of the last block, without sealing it.
+    ///
+    /// The block holds the coinbase rewarding the miner and the oldest pending transactions
+    /// fitting in the block weight and transaction count, in the canonical order. Once its header
+    /// satisfies the difficulty, for example with `Block::proof_of_work_cancellable`, or is sealed
+    /// by the consensus engine, it is appended with `import_block`, which fails with
+    /// `UnknownParent` if another block was appended meanwhile.
+    ///
+    /// # Arguments
+    /// - `miner`: The address receiving the block reward.
+    ///
     /// # Returns
-    /// `true` if a new block is successfully generated and added to the blockchain.
-    pub fn generate_new_block(&mut self) -> bool {
+    /// The unsealed block, or an error of the consensus engine, e.g. `InvalidDifficulty` if the
+    /// difficulty of the blockchain cannot be mined.
+    pub fn block_template(&self, miner: &str) -> Result<Block, ChainError> {
         // Create a new block
         let mut block = Block::new(self.get_last_hash(), self.difficulty);
+        block.header.timestamp = self.clock().now_millis().div_euclid(1000);
+
+        self.consensus().prepare_block(self, &mut block)?;
+
+        // Create a reward transaction, paying the gas fees of the included transactions as well
+        let coinbase = |amount: f64| {
+            Transaction::builder()
+                .from("Root")
+                .to(miner)
+                .amount(amount)
+                .fee(self.fee)
+                .precision(self.config.timestamps)
+                .clock(self.clock())
+                .build()
+        };
+        let transaction = coinbase(self.reward);
 
-        // Create a reward transaction
-        let transaction = Transaction::new(
-            "Root".to_string(),
-            self.address.to_string(),
-            self.fee,
-            self.reward,
-        );
+        // The genesis block commits to the consensus rules of the network
+        if self.chain.is_empty() {
+            block.header.rules = Some(self.config.consensus.hash());
+        }
+
+        // Add the reward transaction and the pending transactions fitting in the block limits
+        let mut weight = transaction.weight();
 
-        // Add the reward transaction to the block
         block.transactions.push(transaction);
-        block.transactions.append(&mut self.current_transactions);
+
+        let rules = &self.config.consensus;
+        let max_weight = rules.max_block_weight.unwrap_or(usize::MAX);
+        let max_transactions = rules.max_transactions_per_block.unwrap_or(usize::MAX);
+        let (max_transfer_weight, max_transfers) = rules.transfer_limits();
+        let (mut transfer_weight, mut transfers) = (weight, 0);
+
+        // Wallets whose later transactions would depend on a transaction left pending
+        let mut deferred: HashSet<&str> = HashSet::new();
+        let mut congested = false;
+
+        // Transactions are taken in submission order, the rest stays pending for the next block.
+        // Once the transfers fill their part of the block, the system transactions still take the
+        // reserved part. Time-locked transactions wait for a block timestamp reaching their
+        // locktime without holding back the unrelated ones.
+        for transaction in &self.current_transactions {
+            if block.transactions.len() > max_transactions {
+                break;
+            }
+
+            let size = transaction.weight();
+            let blocked = !transaction.is_unlocked(block.header.timestamp)
+                || deferred.contains(transaction.from.as_str())
+                || deferred.contains(transaction.payer());
+            let fits = weight + size <= max_weight
+                && (transaction.is_system()
+                    || (!congested
+                        && transfer_weight + size <= max_transfer_weight
+                        && transfers < max_transfers));
+
+            if blocked || !fits {
+                deferred.extend([
+                    transaction.from.as_str(),
+                    transaction.payer(),
+                    transaction.to.as_str(),
+                ]);
+                congested |= !blocked;
+
+                continue;
+            }
+
+            if !transaction.is_system() {
+                transfer_weight += size;
+                transfers += 1;
+            }
+
+            weight += size;
+            block.transactions.push(transaction.to_owned());
+        }
+
+        block.order_transactions();
+
+        let gas_fees = block.gas_fees();
+
+        if gas_fees > 0.0 {
+            block.transactions[0] = coinbase(self.reward + gas_fees);
+        }
 
         // Update the block count and the Merkle root hash
         block.count = block.transactions.len();
         block.header.merkle = Chain::get_merkle(block.transactions.clone());
 
-        // Perform the proof-of-work process
-        Block::proof_of_work(&mut block.header);
+        Ok(block)
+    }
+
+    /// Mine a new block rewarding an address and append it to the blockchain.
+    ///
+    /// # Arguments
+    /// - `miner`: The address receiving the block reward.
+    ///
+    /// # Returns
+    /// An error of the consensus engine, e.g. `InvalidDifficulty` if the difficulty of the
+    /// blockchain cannot be mined.
+    pub(crate) fn mine_block(&mut self, miner: String) -> Result<(), ChainError> {
+        let consensus = self.consensus();
+
+        self.mine_block_with(miner, |block| consensus.seal_block(block))
+    }
+
+    /// Mine a new block rewarding an address with a sealing process and append it to the
+    /// blockchain.
+    ///
+    /// # Arguments
+    /// - `miner`: The address receiving the block reward.
+    /// - `seal`: The process sealing the block, e.g. updating the header until it satisfies its
+    ///   difficulty.
+    ///
+    /// # Returns
+    /// An error of the consensus engine, e.g. `InvalidDifficulty` if the difficulty of the
+    /// blockchain cannot be mined.
+    pub(crate) fn mine_block_with(
+        &mut self,
+        miner: String,
+        seal: impl FnOnce(&mut Block) -> Result<(), ChainError>,
+    ) -> Result<(), ChainError> {
+        let mut block = self.block_template(&miner)?;
+
+        // Seal the block before the mempool changes, so a failure leaves the blockchain unchanged
+        seal(&mut block)?;
+        self.consensus().verify_seal(&block.header)?;
+        block.seal();
+
+        // The block holds the oldest pending transactions after the coinbase, and the system
+        // transactions fitting in the reserved block space
+        let included: HashSet<&str> = block.transactions[1..]
+            .iter()
+            .map(|transaction| transaction.hash.as_str())
+            .collect();
+
+        self.current_transactions
+            .retain(|transaction| !included.contains(transaction.hash.as_str()));
+
+        self.apply_coinbase(&block.transactions[0]);
+        self.append_block(block);
+
+        Ok(())
+    }
+
+    /// Append a block mined by another node to the blockchain.
+    ///
+    /// The block must extend the last block, be sealed by the consensus engine, and hold valid
+    /// transactions in the canonical order, starting with the coinbase. Its transactions pending
+    /// locally are removed from the mempool, and the others are applied to the wallets like
+    /// submitted transactions. The blockchain is left unchanged if the block is rejected.
+    ///
+    /// # Arguments
+    /// - `block`: The block.
+    ///
+    /// # Returns
+    /// `UnknownParent` if the block does not extend the last block, or an error describing why it
+    /// is invalid.
+    pub fn import_block(&mut self, block: Block) -> Result<(), ChainError> {
+        self.import_block_with(block, false)
+    }
+
+    /// Append a block mined by another node to the blockchain, possibly verified beforehand.
+    ///
+    /// # Arguments
+    /// - `block`: The block.
+    /// - `verified`: Whether the seal, the Merkle root, and the transaction signatures of the
+    ///   block were already verified, e.g. by the stages of a sync pipeline.
+    ///
+    /// # Returns
+    /// `UnknownParent` if the block does not extend the last block, or an error describing why it
+    /// is invalid.
+    pub(crate) fn import_block_with(
+        &mut self,
+        mut block: Block,
+        verified: bool,
+    ) -> Result<(), ChainError> {
+        if block.header.previous_hash != self.get_last_hash() {
+            return Err(ChainError::UnknownParent);
+        }
+
+        let invalid = |reason: &str| Err(ChainError::InvalidBlock(reason.to_string()));
+
+        if !verified {
+            self.consensus().verify_seal(&block.header)?;
+        }
+
+        // A retargeted difficulty is part of the consensus
+        let retargeted = self.config.consensus.difficulty_algorithm != DifficultyAlgorithm::Fixed;
+
+        if retargeted && block.header.difficulty != self.difficulty {
+            return invalid("the difficulty does not match the retarget");
+        }
+
+        if !verified && !block.has_valid_transactions() {
+            return invalid("the transactions do not match the header");
+        }
+
+        // Verify the signatures in parallel before applying the transactions one after another
+        if !verified {
+            block.verify_signatures_parallel(&mut self.signing_keys())?;
+        }
+
+        if !block.is_ordered() {
+            return invalid("the transactions are not in the canonical order");
+        }
+
+        let rules = &self.config.consensus;
+
+        if rules
+            .max_block_weight
+            .is_some_and(|max| block.weight() > max)
+        {
+            return invalid("the block exceeds the maximum weight");
+        }
+
+        if rules
+            .max_transactions_per_block
+            .is_some_and(|max| block.transactions.len().saturating_sub(1) > max)
+        {
+            return invalid("the block exceeds the maximum transaction count");
+        }
+
+        let (max_transfer_weight, max_transfers) = rules.transfer_limits();
+        let (transfer_weight, transfers) = block
+            .transactions
+            .iter()
+            .skip(1)
+            .filter(|transaction| !transaction.is_system())
+            .fold(
+                (block.transactions.first().map_or(0, Transaction::weight), 0),
+                |(weight, count), transaction| (weight + transaction.weight(), count + 1),
+            );
+
+        if transfer_weight > max_transfer_weight || transfers > max_transfers {
+            return invalid("the transfers exceed the block space outside the system lane");
+        }
+
+        let now = self.clock().now_millis().div_euclid(1000);
+
+        if block.header.timestamp > now + rules.max_timestamp_drift {
+            return invalid("the timestamp is too far in the future");
+        }
+
+        if !block
+            .transactions
+            .iter()
+            .all(|transaction| transaction.is_unlocked(block.header.timestamp))
+        {
+            return invalid("a transaction is locked after the block timestamp");
+        }
+
+        match block.transactions.split_first() {
+            Some((coinbase, _)) if coinbase.from == "Root" => {
+                if coinbase.amount > self.reward + block.gas_fees() {
+                    return invalid("the coinbase exceeds the block reward and the gas fees");
+                }
+            }
+            _ => return invalid("the first transaction is not the coinbase"),
+        }
+
+        if !block.has_valid_hash() {
+            return invalid("the hash does not match the header");
+        }
+
+        block.seal();
+
+        // Restore the state if a transaction is rejected
+        let wallets = self.wallets.clone();
+        let pending = self.current_transactions.clone();
+        let (collected_fees, evictions) = (self.collected_fees, self.evictions.clone());
+        let (ledger, assets) = (self.ledger.clone(), self.assets.clone());
+        let staking = self.staking.clone();
+
+        for transaction in &block.transactions[1..] {
+            let result = match self
+                .current_transactions
+                .iter()
+                .position(|pending| pending.hash == transaction.hash)
+            {
+                Some(index) => {
+                    self.current_transactions.remove(index);
+                    Ok(())
+                }
+                None if transaction.from == "Root" => {
+                    Err(ChainError::UnknownSender(transaction.from.to_owned()))
+                }
+                None => self
+                    .accept_transaction(transaction.clone(), false, true)
+                    .map(|_| {
+                        self.current_transactions.pop();
+                    }),
+            };
+
+            if let Err(err) = result {
+                self.wallets = wallets;
+                self.current_transactions = pending;
+                self.collected_fees = collected_fees;
+                self.evictions = evictions;
+                self.ledger = ledger;
+                self.assets = assets;
+                self.staking = staking;
+
+                return Err(err);
+            }
+        }
+
+        self.apply_coinbase(&block.transactions[0]);
+        self.append_block(block);
+
+        Ok(())
+    }
+
+    /// Get the public keys of the wallets as of the last block, to verify the signatures of the
+    /// next block.
+    ///
+    /// # Returns
+    /// The public keys by wallet address, the pending key rotations being reverted.
+    pub(crate) fn signing_keys(&self) -> HashMap<Address, String> {
+        let mut keys: HashMap<Address, String> = self
+            .wallets
+            .iter()
+            .filter_map(|(address, wallet)| {
+                Some((address.to_owned(), wallet.public_key.to_owned()?))
+            })
+            .collect();
+
+        for transaction in self.current_transactions.iter().rev() {
+            let Some(rotation) = &transaction.key_rotation else {
+                continue;
+            };
+
+            match &rotation.previous {
+                Some(previous) => keys.insert(transaction.from.to_owned(), previous.to_owned()),
+                None => keys.remove(&transaction.from),
+            };
+        }
+
+        keys
+    }
+
+    /// Credit the reward of a block to the miner, if the miner is a wallet.
+    ///
+    /// # Arguments
+    /// - `coinbase`: The reward transaction of the block.
+    fn apply_coinbase(&mut self, coinbase: &Transaction) {
+        if let Some(wallet) = self.wallets.get_mut(&coinbase.to) {
+            wallet.balance += coinbase.amount;
+            wallet.transactions.push(coinbase.hash.to_owned());
+
+            self.issued_supply += coinbase.amount;
+            self.ledger_mut().apply(coinbase);
+
+            let height = self.chain.len();
+
+            self.emit(|_| ChainEvent::RewardPaid {
+                miner: coinbase.to.to_owned(),
+                amount: coinbase.amount,
+                height,
+            });
+        }
+    }
+
+    /// Add a mined block to the blockchain and apply the policies following a new block.
+    ///
+    /// # Arguments
+    /// - `block`: The mined block.
+    fn append_block(&mut self, block: Block) {
+        // Record the changes of the block, to undo them if the block is removed
+        let undo = BlockUndo::new(&block.transactions, &self.wallets);
+
+        self.undo_log.insert(self.chain.len(), undo);
 
         // Add the block to the blockchain
         self.chain.push(block);
+        self.update_transaction_index();
 
-        true
+        let height = self.chain.len() - 1;
+
+        self.emit(|chain| ChainEvent::BlockMined {
+            height,
+            block: chain.chain[height].to_owned(),
+        });
+
+        self.notify_block_applied(height);
+
+        // Retarget the difficulty of the next block
+        self.apply_difficulty_algorithm();
+
+        // Distribute the staking rewards of a completed epoch
+        self.distribute_staking_rewards();
+
+        // Notify the payment watches before the block bodies are pruned
+        self.notify_watches();
+
+        // Write the block to the backend, a failed write is retried with the next block and the
+        // blocks not written yet are not pruned
+        let _ = self.store_blocks();
+
+        // Apply the retention policy for block bodies
+        self.apply_pruning();
+
+        // Apply the memory cap of the wallet histories
+        self.apply_index_limit();
+
+        self.assert_invariants();
+    }
+
+    /// Set the difficulty of the next block computed by the difficulty algorithm, if it is not fixed.
+    pub(crate) fn apply_difficulty_algorithm(&mut self) {
+        if let Some(difficulty) = self
+            .config
+            .consensus
+            .difficulty_algorithm
+            .next_difficulty(&self.chain)
+        {
+            self.set_difficulty(difficulty);
+        }
+    }
+
+    /// Check whether a difficulty can be mined: the hash has 64 hexadecimal digits.
+    ///
+    /// # Arguments
+    /// - `difficulty`: The difficulty level.
+    ///
+    /// # Returns
+    /// `true` if the difficulty is between 1 and 64.
+    pub(crate) fn is_valid_difficulty(difficulty: f64) -> bool {
+        (1.0..=64.0).contains(&difficulty)
     }
 
     /// Calculate the Merkle root hash for a list of transactions.
@@ -361,7 +2442,7 @@ impl Chain {
         let mut merkle = Vec::new();
 
         for t in &transactions {
-            let hash = Chain::hash(t);
+            let hash = Chain::merkle_leaf(t);
             merkle.push(hash);
         }
 
@@ -371,18 +2452,39 @@ impl Chain {
         }
 
         while merkle.len() > 1 {
-            let mut h1 = merkle.remove(0);
+            let h1 = merkle.remove(0);
             let h2 = merkle.remove(0);
 
-            h1.push_str(&h2);
-
-            let nh = Chain::hash(&h1);
+            let nh = Chain::merkle_node(&h1, &h2);
             merkle.push(nh);
         }
 
         merkle.pop().unwrap()
     }
 
+    /// Calculate the Merkle tree leaf of a transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// The hash of the canonical encoding of the whole transaction.
+    pub(crate) fn merkle_leaf(transaction: &Transaction) -> String {
+        encoding::hash(MERKLE_LEAF_DOMAIN, &[transaction])
+    }
+
+    /// Calculate the Merkle tree node combining two hashes.
+    ///
+    /// # Arguments
+    /// - `left`: The hash of the left child.
+    /// - `right`: The hash of the right child.
+    ///
+    /// # Returns
+    /// The hash of the node.
+    pub(crate) fn merkle_node(left: &str, right: &str) -> String {
+        encoding::hash(MERKLE_NODE_DOMAIN, &[&left, &right])
+    }
+
     /// Calculate the SHA-256 hash of a serializable item.
     ///
     /// # Arguments
@@ -413,7 +2515,7 @@ impl Chain {
     ///
     /// # Returns
     /// A `String` containing the generated alphanumeric string.
-    fn generate_address(length: usize) -> String {
+    pub(crate) fn generate_address(length: usize) -> String {
         let mut rng = rand::thread_rng();
 
         let address: String = iter::repeat(())
@@ -423,6 +2525,22 @@ impl Chain {
 
         address
     }
+
+    /// Derives an address from a salted hash of an email and a chain identifier.
+    ///
+    /// # Arguments
+    /// - `salt`: The salt mixed into the hash.
+    /// - `chain_id`: The identifier of the blockchain.
+    /// - `email`: The wallet email.
+    /// - `length`: The length of the derived address.
+    ///
+    /// # Returns
+    /// A `String` containing the derived address.
+    pub(crate) fn derive_address(salt: &str, chain_id: &str, email: &str, length: usize) -> String {
+        let hash = Chain::hash(&(salt, chain_id, email));
+
+        hash.chars().take(length).collect()
+    }
 }
 
 #[cfg(test)]
@@ -435,4 +2553,19 @@ mod tests {
 
         assert_eq!(result.len(), 42);
     }
+
+    #[test]
+    fn test_derive_address() {
+        let result = Chain::derive_address("salt", "chain", "s@mail.com", 42);
+
+        assert_eq!(result.len(), 42);
+        assert_eq!(
+            result,
+            Chain::derive_address("salt", "chain", "s@mail.com", 42)
+        );
+        assert_ne!(
+            result,
+            Chain::derive_address("salt", "other", "s@mail.com", 42)
+        );
+    }
 }
@@ -0,0 +1,204 @@
+use std::{fmt::Debug, sync::Mutex, time::Duration};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// A source of time for the timestamps of transactions and blocks.
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current time.
+    ///
+    /// # Returns
+    /// The amount of milliseconds since the Unix epoch.
+    fn now_millis(&self) -> i64;
+
+    /// Get the current millisecond and a counter breaking the ties within that millisecond.
+    ///
+    /// The default implementation shares its counter with every clock of the process and never
+    /// goes backwards.
+    ///
+    /// # Returns
+    /// The millisecond timestamp and the tie-breaker counter.
+    fn tick(&self) -> (i64, u64) {
+        next_tick(&LAST_TICK, self.now_millis())
+    }
+}
+
+/// The system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        Utc::now().timestamp_millis()
+    }
+}
+
+/// A clock only moving when it is set or advanced, for simulations and replays.
+#[derive(Debug)]
+pub struct ManualClock {
+    /// The current millisecond.
+    now: Mutex<i64>,
+
+    /// Last issued millisecond timestamp and tie-breaker counter of this clock.
+    last_tick: Mutex<(i64, u64)>,
+}
+
+impl ManualClock {
+    /// Create a new clock stopped at a given time.
+    ///
+    /// # Arguments
+    /// - `millis`: The amount of milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// A new manual clock.
+    pub fn new(millis: i64) -> Self {
+        ManualClock {
+            now: Mutex::new(millis),
+            last_tick: Mutex::new((i64::MIN, 0)),
+        }
+    }
+
+    /// Move the clock to a given time, possibly backwards.
+    ///
+    /// # Arguments
+    /// - `millis`: The amount of milliseconds since the Unix epoch.
+    pub fn set(&self, millis: i64) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = millis;
+    }
+
+    /// Move the clock forward.
+    ///
+    /// # Arguments
+    /// - `duration`: The elapsed time.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration.as_millis() as i64;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_millis(&self) -> i64 {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn tick(&self) -> (i64, u64) {
+        let now = self.now_millis();
+        let mut last = self.last_tick.lock().unwrap_or_else(|e| e.into_inner());
+
+        // A clock moved backwards starts over, so replays keep their timestamps
+        *last = if now == last.0 {
+            (now, last.1 + 1)
+        } else {
+            (now, 0)
+        };
+
+        *last
+    }
+}
+
+/// Precision of the transaction timestamps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampPrecision {
+    /// Whole seconds, without a tie-breaker, as produced by earlier versions.
+    #[default]
+    Seconds,
+
+    /// Milliseconds, with a counter breaking the ties between transactions of the same millisecond.
+    Millis,
+}
+
+/// Last issued millisecond timestamp and tie-breaker counter, shared by every blockchain of the process.
+static LAST_TICK: Mutex<(i64, u64)> = Mutex::new((i64::MIN, 0));
+
+impl TimestampPrecision {
+    /// Get the timestamp of a new transaction.
+    ///
+    /// In milliseconds, the timestamps never go backwards even if the system clock does, and the
+    /// counter is increased for every transaction issued within the same millisecond.
+    ///
+    /// # Returns
+    /// The timestamp, and the tie-breaker counter for millisecond timestamps.
+    pub(crate) fn now(self) -> (i64, Option<u64>) {
+        self.now_on(&SystemClock)
+    }
+
+    /// Get the timestamp of a new transaction from a clock.
+    ///
+    /// # Arguments
+    /// - `clock`: The clock.
+    ///
+    /// # Returns
+    /// The timestamp, and the tie-breaker counter for millisecond timestamps.
+    pub(crate) fn now_on(self, clock: &dyn Clock) -> (i64, Option<u64>) {
+        match self {
+            TimestampPrecision::Seconds => (clock.now_millis().div_euclid(1000), None),
+            TimestampPrecision::Millis => {
+                let (millis, sequence) = clock.tick();
+
+                (millis, Some(sequence))
+            }
+        }
+    }
+}
+
+/// Issue the next millisecond timestamp and tie-breaker counter.
+///
+/// # Arguments
+/// - `last`: The last issued timestamp and counter.
+/// - `now`: The current millisecond.
+///
+/// # Returns
+/// The timestamp, never lower than the last one, and the counter.
+fn next_tick(last: &Mutex<(i64, u64)>, now: i64) -> (i64, u64) {
+    let mut last = last.lock().unwrap_or_else(|e| e.into_inner());
+
+    *last = if now > last.0 {
+        (now, 0)
+    } else {
+        (last.0, last.1 + 1)
+    };
+
+    *last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now_seconds() {
+        let (timestamp, sequence) = TimestampPrecision::Seconds.now();
+
+        assert!((timestamp - Utc::now().timestamp()).abs() <= 1);
+        assert_eq!(sequence, None);
+    }
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(5_000);
+
+        assert_eq!(TimestampPrecision::Seconds.now_on(&clock), (5, None));
+        assert_eq!(TimestampPrecision::Millis.now_on(&clock), (5_000, Some(0)));
+        assert_eq!(TimestampPrecision::Millis.now_on(&clock), (5_000, Some(1)));
+
+        clock.advance(Duration::from_millis(1));
+
+        assert_eq!(TimestampPrecision::Millis.now_on(&clock), (5_001, Some(0)));
+
+        // A clock moved backwards replays the same timestamps
+        clock.set(5_000);
+
+        assert_eq!(TimestampPrecision::Millis.now_on(&clock), (5_000, Some(0)));
+    }
+
+    #[test]
+    fn test_now_millis_monotonic() {
+        let ticks: Vec<(i64, Option<u64>)> = (0..1000)
+            .map(|_| TimestampPrecision::Millis.now())
+            .collect();
+
+        for pair in ticks.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, Block, Chain};
+
+/// Relative tolerance of the balance comparisons, absorbing the rounding of floating point sums.
+const EPSILON: f64 = 1e-6;
+
+/// Blocks at the same height of two blockchains with different hashes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockDifference {
+    /// The height of the blocks.
+    pub height: usize,
+
+    /// The hash of the block of the compared blockchain, `None` if it is shorter.
+    pub local: Option<String>,
+
+    /// The hash of the block of the other blockchain, `None` if it is shorter.
+    pub other: Option<String>,
+}
+
+/// Balances of the same wallet on two blockchains that disagree.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BalanceDifference {
+    /// The address of the wallet.
+    pub address: Address,
+
+    /// The balance on the compared blockchain, `None` if the wallet does not exist there.
+    pub local: Option<f64>,
+
+    /// The balance on the other blockchain, `None` if the wallet does not exist there.
+    pub other: Option<f64>,
+}
+
+/// Differences between two blockchains, e.g. two nodes or a backup and a live node.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainComparison {
+    /// The height of the last block of the compared blockchain, `None` without blocks.
+    pub local_height: Option<usize>,
+
+    /// The height of the last block of the other blockchain, `None` without blocks.
+    pub other_height: Option<usize>,
+
+    /// The lowest height at which the blocks differ, `None` if every block matches.
+    pub first_divergent_height: Option<usize>,
+
+    /// The blocks differing from the first divergent height up to the last block of the longer
+    /// blockchain, in height order.
+    pub blocks: Vec<BlockDifference>,
+
+    /// The wallets whose balances differ or which exist on one blockchain only, in address order.
+    pub balances: Vec<BalanceDifference>,
+}
+
+impl ChainComparison {
+    /// Check whether the blockchains agree.
+    ///
+    /// # Returns
+    /// `true` if the blockchains have the same blocks and the same balances.
+    pub fn is_identical(&self) -> bool {
+        self.first_divergent_height.is_none() && self.balances.is_empty()
+    }
+}
+
+impl Chain {
+    /// Compare the blockchain with another one, to debug why two nodes disagree.
+    ///
+    /// The blocks are compared by hash height by height, so once a block differs every later
+    /// block differs too. A blockchain extending the other one diverges at the first height the
+    /// other one lacks. The balances include the pending transactions of each blockchain.
+    ///
+    /// # Arguments
+    /// - `other`: The other blockchain.
+    ///
+    /// # Returns
+    /// The first divergent height, the differing block hashes, and the balance discrepancies per
+    /// wallet.
+    pub fn compare(&self, other: &Chain) -> ChainComparison {
+        let hash = |chain: &Chain, height: usize| chain.chain.get(height).map(Block::hash);
+        let length = self.chain.len().max(other.chain.len());
+
+        let first_divergent_height =
+            (0..length).find(|height| hash(self, *height) != hash(other, *height));
+        let blocks = first_divergent_height.map_or_else(Vec::new, |first| {
+            (first..length)
+                .map(|height| BlockDifference {
+                    height,
+                    local: hash(self, height),
+                    other: hash(other, height),
+                })
+                .collect()
+        });
+
+        let addresses: BTreeSet<&String> =
+            self.wallets.keys().chain(other.wallets.keys()).collect();
+        let balances = addresses
+            .into_iter()
+            .filter_map(|address| {
+                let local = self.wallets.get(address).map(|wallet| wallet.balance);
+                let remote = other.wallets.get(address).map(|wallet| wallet.balance);
+
+                let agree = match (local, remote) {
+                    (Some(a), Some(b)) => (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0),
+                    _ => false,
+                };
+
+                (!agree).then(|| BalanceDifference {
+                    address: address.to_owned(),
+                    local,
+                    other: remote,
+                })
+            })
+            .collect();
+
+        ChainComparison {
+            local_height: self.chain.len().checked_sub(1),
+            other_height: other.chain.len().checked_sub(1),
+            first_divergent_height,
+            blocks,
+            balances,
+        }
+    }
+}
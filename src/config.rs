@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ConsensusRules, MemoryLimits, PruningPolicy, StakingConfig, TimestampPrecision};
+
+/// Strategy used to assign addresses to new wallets.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum AddressMode {
+    /// Random alphanumeric addresses.
+    #[default]
+    Random,
+
+    /// Addresses derived from a salted hash of the wallet email and the chain identifier.
+    Deterministic {
+        /// Salt mixed into the address derivation.
+        salt: String,
+    },
+}
+
+/// Treatment of the wallet emails, which are personal data kept in every snapshot and export of
+/// the blockchain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailPrivacy {
+    /// The wallets store their email as given.
+    #[default]
+    Plain,
+
+    /// The wallets store a hash of their email salted with the chain identifier, still matched
+    /// by the lookups by email.
+    Hashed,
+
+    /// The wallets store no email, so the lookups by email find no wallet and the emails are not
+    /// checked for uniqueness.
+    Omitted,
+}
+
+/// Optional blockchain settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Identifier of the blockchain.
+    pub chain_id: String,
+
+    /// Strategy used to assign addresses to new wallets.
+    pub address_mode: AddressMode,
+
+    /// Retention policy for block bodies.
+    #[serde(default)]
+    pub pruning: PruningPolicy,
+
+    /// Caps on the approximate memory used by the mempool and the wallet histories.
+    #[serde(default)]
+    pub memory: MemoryLimits,
+
+    /// Precision of the transaction timestamps.
+    #[serde(default)]
+    pub timestamps: TimestampPrecision,
+
+    /// Consensus-critical parameters, hashed into the genesis block.
+    #[serde(default)]
+    pub consensus: ConsensusRules,
+
+    /// Minimum fee of the transactions submitted to the mempool, a local policy that does not
+    /// apply to the transactions of imported blocks.
+    #[serde(default)]
+    pub min_relay_fee: f64,
+
+    /// Treatment of the wallet emails.
+    #[serde(default)]
+    pub email_privacy: EmailPrivacy,
+
+    /// Epoch rewards of the stakers.
+    #[serde(default)]
+    pub staking: StakingConfig,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        ChainConfig {
+            chain_id: String::from("default"),
+            address_mode: AddressMode::Random,
+            pruning: PruningPolicy::KeepAll,
+            memory: MemoryLimits::default(),
+            timestamps: TimestampPrecision::Seconds,
+            consensus: ConsensusRules::default(),
+            min_relay_fee: 0.0,
+            email_privacy: EmailPrivacy::Plain,
+            staking: StakingConfig::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = ChainConfig::default();
+
+        assert_eq!(config.chain_id, "default");
+        assert_eq!(config.address_mode, AddressMode::Random);
+        assert_eq!(config.pruning, PruningPolicy::KeepAll);
+        assert_eq!(config.memory, MemoryLimits::default());
+        assert_eq!(config.timestamps, TimestampPrecision::Seconds);
+        assert_eq!(config.consensus, ConsensusRules::default());
+        assert_eq!(config.min_relay_fee, 0.0);
+        assert_eq!(config.email_privacy, EmailPrivacy::Plain);
+        assert_eq!(config.staking, StakingConfig::default());
+    }
+}
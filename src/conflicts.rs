@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, Chain, OutPoint, Transaction, TransactionLocation};
+
+/// Tolerance of the balance comparisons, absorbing the rounding of floating point sums.
+const EPSILON: f64 = 1e-9;
+
+/// Transactions of the blockchain spending the same funds twice.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Conflict {
+    /// The same transaction is stored more than once in the blocks and the mempool.
+    DuplicateTransaction {
+        /// The hash of the transaction.
+        hash: String,
+
+        /// Where each copy of the transaction is stored, in chain order.
+        locations: Vec<TransactionLocation>,
+    },
+
+    /// Different transactions of a sender use the same nonce.
+    NonceReuse {
+        /// The address of the sender.
+        address: Address,
+
+        /// The reused nonce.
+        nonce: u64,
+
+        /// The hashes of the transactions, in chain order.
+        transactions: Vec<String>,
+    },
+
+    /// Different transactions spend the same unspent output.
+    DoubleSpend {
+        /// The spent output.
+        input: OutPoint,
+
+        /// The hashes of the transactions, in chain order.
+        transactions: Vec<String>,
+    },
+
+    /// The pending transactions of a wallet spend more than its balance.
+    Overspent {
+        /// The address of the wallet.
+        address: Address,
+
+        /// The amounts and fees of the pending transactions sent or sponsored by the wallet.
+        pending_outgoing: f64,
+
+        /// The balance of the wallet after the pending transactions, negative.
+        balance: f64,
+    },
+}
+
+impl Chain {
+    /// Get the funds a wallet commits to its pending transactions.
+    ///
+    /// The balance of the wallet is debited when a transaction is submitted, so two pending
+    /// transactions cannot spend the same funds.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The amounts of the pending transactions sent by the wallet and the fees it pays.
+    pub fn pending_outgoing(&self, address: &str) -> f64 {
+        self.current_transactions
+            .iter()
+            .map(|transaction| {
+                let amount = match transaction.from == address {
+                    true => transaction.native_amount(),
+                    false => 0.0,
+                };
+                let fee = match transaction.payer() == address {
+                    true => transaction.charged_fee(),
+                    false => 0.0,
+                };
+
+                amount + fee
+            })
+            .sum()
+    }
+
+    /// Find the transactions of the blocks and the mempool spending the same funds, to diagnose
+    /// a blockchain built from untrusted blocks or modified by hand.
+    ///
+    /// The transactions of pruned blocks and the funds issued by the root address are not
+    /// checked.
+    ///
+    /// # Returns
+    /// The duplicated transactions, the nonces reused by a sender, the outputs spent twice, and
+    /// the wallets overspent by their pending transactions, in this order.
+    pub fn detect_conflicts(&self) -> Vec<Conflict> {
+        let mut locations: BTreeMap<&str, Vec<TransactionLocation>> = BTreeMap::new();
+        let mut nonces: BTreeMap<(&str, u64), Vec<&str>> = BTreeMap::new();
+        let mut inputs: BTreeMap<&OutPoint, Vec<&str>> = BTreeMap::new();
+
+        for (transaction, location) in self.located_transactions() {
+            // The funds issued by the root address are not spent from a wallet, and the
+            // coinbases of a miner within the same second share their hash
+            if transaction.from == "Root" {
+                continue;
+            }
+
+            let hash = transaction.hash.as_str();
+            let copies = locations.entry(hash).or_default();
+
+            copies.push(location);
+
+            // The copies of a duplicated transaction do not reuse its nonce or its inputs
+            if copies.len() > 1 {
+                continue;
+            }
+
+            nonces
+                .entry((transaction.from.as_str(), transaction.nonce))
+                .or_default()
+                .push(hash);
+
+            for input in &transaction.inputs {
+                inputs.entry(input).or_default().push(hash);
+            }
+        }
+
+        let duplicates = locations
+            .into_iter()
+            .filter(|(_, locations)| locations.len() > 1)
+            .map(|(hash, locations)| Conflict::DuplicateTransaction {
+                hash: hash.to_string(),
+                locations,
+            });
+        let reused = nonces
+            .into_iter()
+            .filter(|(_, transactions)| transactions.len() > 1)
+            .map(|((address, nonce), transactions)| Conflict::NonceReuse {
+                address: address.to_string(),
+                nonce,
+                transactions: transactions.into_iter().map(str::to_string).collect(),
+            });
+        let spent = inputs
+            .into_iter()
+            .filter(|(_, transactions)| transactions.len() > 1)
+            .map(|(input, transactions)| Conflict::DoubleSpend {
+                input: input.to_owned(),
+                transactions: transactions.into_iter().map(str::to_string).collect(),
+            });
+
+        let senders: BTreeSet<&str> = self
+            .current_transactions
+            .iter()
+            .flat_map(|transaction| [transaction.from.as_str(), transaction.payer()])
+            .collect();
+        let overspent = senders.into_iter().filter_map(|address| {
+            let balance = self.wallets.get(address)?.balance;
+
+            (balance < -EPSILON).then(|| Conflict::Overspent {
+                address: address.to_string(),
+                pending_outgoing: self.pending_outgoing(address),
+                balance,
+            })
+        });
+
+        duplicates
+            .chain(reused)
+            .chain(spent)
+            .chain(overspent)
+            .collect()
+    }
+
+    /// Iterate over the transactions of the blocks, then of the mempool, with their location.
+    ///
+    /// # Returns
+    /// The transactions in chain order.
+    fn located_transactions(&self) -> impl Iterator<Item = (&Transaction, TransactionLocation)> {
+        let mined = self.chain.iter().enumerate().flat_map(|(height, block)| {
+            block
+                .transactions
+                .iter()
+                .map(move |transaction| (transaction, TransactionLocation::Block(height)))
+        });
+        let pending = self
+            .current_transactions
+            .iter()
+            .map(|transaction| (transaction, TransactionLocation::Pending));
+
+        mined.chain(pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    #[test]
+    fn test_detect_conflicts() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let from = chain
+            .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&from, 100.0);
+        chain
+            .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert!(chain.detect_conflicts().is_empty());
+
+        // A mined transaction copied to the mempool, and another transaction with its nonce
+        let mined = chain.chain[1]
+            .transactions
+            .iter()
+            .find(|transaction| transaction.from == from)
+            .unwrap()
+            .to_owned();
+        let mut replay = mined.to_owned();
+
+        replay.amount = 20.0;
+        replay.hash = "replay".to_string();
+        chain.current_transactions.push(mined.to_owned());
+        chain.current_transactions.push(replay);
+        chain.wallets.get_mut(&from).unwrap().balance = -1.0;
+
+        assert_eq!(
+            chain.detect_conflicts(),
+            vec![
+                Conflict::DuplicateTransaction {
+                    hash: mined.hash.to_owned(),
+                    locations: vec![TransactionLocation::Block(1), TransactionLocation::Pending],
+                },
+                Conflict::NonceReuse {
+                    address: from.to_owned(),
+                    nonce: 0,
+                    transactions: vec![mined.hash.to_owned(), "replay".to_string()],
+                },
+                Conflict::Overspent {
+                    address: from.to_owned(),
+                    pending_outgoing: 32.0,
+                    balance: -1.0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encoding::{self, CONSENSUS_RULES_DOMAIN},
+    BlockHeader, LedgerModel,
+};
+
+/// Maximum factor by which a retarget changes the expected amount of hashes per block.
+const MAX_RETARGET_FACTOR: f64 = 4.0;
+
+/// Algorithm updating the mining difficulty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum DifficultyAlgorithm {
+    /// The difficulty only changes through `update_difficulty`.
+    #[default]
+    Fixed,
+
+    /// The difficulty is adjusted every `retarget_interval` blocks, so blocks are mined every
+    /// `target_block_time` seconds on average.
+    ///
+    /// The expected amount of hashes per block is scaled by the ratio between the target and the
+    /// actual time taken by the last interval, by a factor of 4 at most in either direction.
+    Retarget {
+        /// The target time between two blocks, in seconds.
+        target_block_time: i64,
+
+        /// The amount of blocks between two adjustments.
+        retarget_interval: usize,
+    },
+}
+
+impl DifficultyAlgorithm {
+    /// Compute the difficulty of the block following the given blocks.
+    ///
+    /// # Arguments
+    /// - `blocks`: The blocks or the block headers of the blockchain, starting with the genesis
+    ///   block.
+    ///
+    /// # Returns
+    /// The difficulty of the next block, or `None` if the difficulty is fixed or the blockchain
+    /// is empty.
+    pub fn next_difficulty<B: AsRef<BlockHeader>>(&self, blocks: &[B]) -> Option<f64> {
+        self.next_difficulty_from(0, blocks)
+    }
+
+    /// Compute the difficulty of the block following the latest blocks of a blockchain.
+    ///
+    /// # Arguments
+    /// - `first_height`: The height of the first given block.
+    /// - `blocks`: The latest blocks or block headers of the blockchain.
+    ///
+    /// # Returns
+    /// The difficulty of the next block, or `None` if the difficulty is fixed, no block is given,
+    /// or the first block of the retarget window is not given.
+    pub(crate) fn next_difficulty_from<B: AsRef<BlockHeader>>(
+        &self,
+        first_height: usize,
+        blocks: &[B],
+    ) -> Option<f64> {
+        let DifficultyAlgorithm::Retarget {
+            target_block_time,
+            retarget_interval,
+        } = *self
+        else {
+            return None;
+        };
+
+        let last = blocks.last()?.as_ref();
+        let height = first_height + blocks.len() - 1;
+
+        if retarget_interval == 0 || height == 0 || !height.is_multiple_of(retarget_interval) {
+            return Some(last.difficulty);
+        }
+
+        let first = blocks
+            .get((height - retarget_interval).checked_sub(first_height)?)?
+            .as_ref();
+        let expected = (target_block_time * retarget_interval as i64).max(1) as f64;
+        let actual = (last.timestamp - first.timestamp).max(1) as f64;
+        let factor = (expected / actual).clamp(1.0 / MAX_RETARGET_FACTOR, MAX_RETARGET_FACTOR);
+
+        // Every unit of difficulty is one more leading hexadecimal zero, 16 times more hashes
+        Some((last.difficulty + factor.log(16.0)).clamp(1.0, 64.0))
+    }
+}
+
+/// Consensus-critical parameters shared by every node of a network.
+///
+/// The rules are hashed into the genesis block and announced in the handshake, so nodes
+/// following different rules refuse to exchange transactions and blocks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusRules {
+    /// Maximum approximate size in bytes of the transactions of a block, unlimited if `None`.
+    #[serde(default)]
+    pub max_block_weight: Option<usize>,
+
+    /// Maximum amount of transactions of a block besides the coinbase, unlimited if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transactions_per_block: Option<usize>,
+
+    /// Part of the maximum block weight only usable by system transactions, so they are not
+    /// crowded out by transfers during congestion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserved_system_weight: Option<usize>,
+
+    /// Part of the maximum transaction count only usable by system transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserved_system_transactions: Option<usize>,
+
+    /// Amount of blocks mined on top of a coinbase before its reward matures.
+    #[serde(default)]
+    pub coinbase_maturity: usize,
+
+    /// Maximum amount of seconds a block timestamp may be ahead of the local clock.
+    #[serde(default = "default_max_timestamp_drift")]
+    pub max_timestamp_drift: i64,
+
+    /// Algorithm updating the mining difficulty.
+    #[serde(default)]
+    pub difficulty_algorithm: DifficultyAlgorithm,
+
+    /// Heights from which named rule changes apply.
+    #[serde(default)]
+    pub activation_heights: BTreeMap<String, usize>,
+
+    /// Accounting model of the wallet balances, only serialized when it is not the account model.
+    #[serde(default, skip_serializing_if = "LedgerModel::is_account")]
+    pub ledger: LedgerModel,
+}
+
+impl Default for ConsensusRules {
+    fn default() -> Self {
+        ConsensusRules {
+            max_block_weight: None,
+            max_transactions_per_block: None,
+            reserved_system_weight: None,
+            reserved_system_transactions: None,
+            coinbase_maturity: 0,
+            max_timestamp_drift: default_max_timestamp_drift(),
+            difficulty_algorithm: DifficultyAlgorithm::Fixed,
+            activation_heights: BTreeMap::new(),
+            ledger: LedgerModel::Account,
+        }
+    }
+}
+
+/// Default maximum drift of block timestamps, two hours.
+fn default_max_timestamp_drift() -> i64 {
+    2 * 60 * 60
+}
+
+impl ConsensusRules {
+    /// Get the hash identifying the rules.
+    ///
+    /// # Returns
+    /// The SHA-256 hash of the canonical encoding of the rules.
+    pub fn hash(&self) -> String {
+        encoding::hash(CONSENSUS_RULES_DOMAIN, &[self])
+    }
+
+    /// Get the block space usable by transfers, the reserved part of the block limits being left
+    /// to system transactions.
+    ///
+    /// # Returns
+    /// The maximum weight of the coinbase and the transfers of a block, and the maximum amount of
+    /// transfers, unlimited if `usize::MAX`.
+    pub(crate) fn transfer_limits(&self) -> (usize, usize) {
+        let weight = self.max_block_weight.map_or(usize::MAX, |max| {
+            max.saturating_sub(self.reserved_system_weight.unwrap_or_default())
+        });
+        let transactions = self.max_transactions_per_block.map_or(usize::MAX, |max| {
+            max.saturating_sub(self.reserved_system_transactions.unwrap_or_default())
+        });
+
+        (weight, transactions)
+    }
+
+    /// Check whether a named rule change applies at a height.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the rule change.
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// `true` if the rule change has an activation height at or below the height.
+    pub fn is_active(&self, name: &str, height: usize) -> bool {
+        self.activation_heights
+            .get(name)
+            .is_some_and(|activation| *activation <= height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Block;
+
+    #[test]
+    fn test_rules_hash() {
+        let rules = ConsensusRules::default();
+        let other = ConsensusRules {
+            max_block_weight: Some(1_000),
+            ..ConsensusRules::default()
+        };
+
+        assert_eq!(rules.hash(), ConsensusRules::default().hash());
+        assert_ne!(rules.hash(), other.hash());
+        assert_ne!(
+            rules.hash(),
+            ConsensusRules {
+                ledger: LedgerModel::Utxo,
+                ..ConsensusRules::default()
+            }
+            .hash()
+        );
+    }
+
+    #[test]
+    fn test_next_difficulty() {
+        let algorithm = DifficultyAlgorithm::Retarget {
+            target_block_time: 10,
+            retarget_interval: 2,
+        };
+        let blocks = |spacing: i64| -> Vec<Block> {
+            (0..3)
+                .map(|height| {
+                    let mut block = Block::new(String::new(), 2.0);
+                    block.header.timestamp = height * spacing;
+                    block
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            DifficultyAlgorithm::Fixed.next_difficulty(&blocks(10)),
+            None
+        );
+        assert_eq!(algorithm.next_difficulty(&blocks(10)[..2]), Some(2.0));
+
+        // Blocks on target keep the difficulty
+        assert_eq!(algorithm.next_difficulty(&blocks(10)), Some(2.0));
+
+        // Blocks mined 16 times too fast are limited to a factor of 4, half a hex digit
+        assert_eq!(algorithm.next_difficulty(&blocks(0)), Some(2.5));
+        assert_eq!(algorithm.next_difficulty(&blocks(160)), Some(1.5));
+    }
+
+    #[test]
+    fn test_is_active() {
+        let rules = ConsensusRules {
+            activation_heights: BTreeMap::from([("fork".to_string(), 10)]),
+            ..ConsensusRules::default()
+        };
+
+        assert!(!rules.is_active("fork", 9));
+        assert!(rules.is_active("fork", 10));
+        assert!(!rules.is_active("other", 100));
+    }
+}
@@ -0,0 +1,840 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasmi::{
+    core::TrapCode, Caller, Config, Engine, Error, Extern, Linker, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+use crate::{encoding, keys, Address, Chain, ChainError, Keypair, Wallet};
+
+/// Gas available to a call of `call_contract`.
+pub const DEFAULT_GAS_LIMIT: u64 = 1_000_000;
+
+/// Largest linear memory of a contract, in bytes.
+pub const MAX_CONTRACT_MEMORY: usize = 1 << 20;
+
+/// Host functions importable by the contracts from the `env` module.
+const HOST_FUNCTIONS: [&str; 6] = [
+    "storage_read",
+    "storage_write",
+    "transfer",
+    "caller",
+    "input",
+    "output",
+];
+
+/// Gas charged for every call of a host function, on top of the executed instructions.
+const HOST_CALL_GAS: u64 = 100;
+
+/// Gas charged for every byte copied between the contract memory and the host.
+const BYTE_GAS: u64 = 1;
+
+/// Gas charged for every byte written to the contract storage.
+const STORAGE_BYTE_GAS: u64 = 10;
+
+/// Gas charged for every transfer of the contract funds.
+const TRANSFER_GAS: u64 = 1_000;
+
+/// Length of the contract addresses, as long as the wallet addresses.
+const CONTRACT_ADDRESS_LENGTH: usize = 42;
+
+/// A WASM contract deployed on the blockchain, holding funds in the wallet at its address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contract {
+    /// The address of the contract and of its wallet.
+    pub address: Address,
+
+    /// The address of the wallet deploying the contract.
+    pub owner: Address,
+
+    /// The hex encoded SHA-256 digest of the code.
+    pub code_hash: String,
+
+    /// The WASM module.
+    pub code: Vec<u8>,
+
+    /// The hex encoded values written by the contract, by hex encoded key.
+    pub storage: BTreeMap<String, String>,
+}
+
+/// Result of a successful contract call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCall {
+    /// The bytes returned by the contract with the `output` host function.
+    pub output: Vec<u8>,
+
+    /// The gas consumed by the executed instructions and host functions.
+    pub gas_used: u64,
+
+    /// The hashes of the transactions paying the transfers of the contract funds.
+    pub transfers: Vec<String>,
+}
+
+/// Deployed contracts of the blockchain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ContractState {
+    /// The contracts, by address.
+    pub(crate) contracts: BTreeMap<Address, Contract>,
+
+    /// The contract whose transfers are being submitted, the only contract wallet sending
+    /// transactions.
+    #[serde(skip)]
+    pub(crate) sending: Option<Address>,
+}
+
+/// State of a contract call, exposed to the contract through the host functions.
+struct HostState {
+    /// The address of the calling wallet.
+    caller: Address,
+
+    /// The storage of the contract, written back if the call succeeds.
+    storage: BTreeMap<String, String>,
+
+    /// The arguments of the call.
+    input: Vec<u8>,
+
+    /// The bytes returned by the contract.
+    output: Vec<u8>,
+
+    /// The transfers of the contract funds, paid if the call succeeds.
+    transfers: Vec<(Address, f64)>,
+
+    /// The memory limits of the contract.
+    limits: StoreLimits,
+}
+
+impl Chain {
+    /// Deploy a WASM contract, whose exported functions are called with `call_contract`.
+    ///
+    /// The module exports its memory and imports host functions from the `env` module only:
+    /// `storage_read(key_ptr, key_len, out_ptr, out_cap) -> len` (-1 if the key is not set),
+    /// `storage_write(key_ptr, key_len, value_ptr, value_len)`, `transfer(to_ptr, to_len, amount)`,
+    /// `caller(out_ptr, out_cap) -> len`, `input(out_ptr, out_cap) -> len` and
+    /// `output(ptr, len)`.
+    ///
+    /// # Arguments
+    /// - `owner`: The address of the deploying wallet.
+    /// - `code`: The WASM module.
+    /// - `keypair`: The keypair owning the deploying wallet, required if the wallet has a public
+    ///   key.
+    ///
+    /// # Returns
+    /// The address of the contract and of its wallet, or the reason the code is rejected.
+    pub fn deploy_contract(
+        &mut self,
+        owner: &str,
+        code: Vec<u8>,
+        keypair: Option<&Keypair>,
+    ) -> Result<Address, ChainError> {
+        self.check_owner(owner, keypair)?;
+
+        let module = Module::new(&contract_engine(), &code)
+            .map_err(|err| ChainError::InvalidContract(err.to_string()))?;
+
+        if let Some(import) = module
+            .imports()
+            .find(|import| import.module() != "env" || !HOST_FUNCTIONS.contains(&import.name()))
+        {
+            return Err(ChainError::InvalidContract(format!(
+                "unknown import {}.{}",
+                import.module(),
+                import.name()
+            )));
+        }
+
+        if !module
+            .exports()
+            .any(|export| export.name() == "memory" && export.ty().memory().is_some())
+        {
+            return Err(ChainError::InvalidContract(
+                "the memory is not exported".to_string(),
+            ));
+        }
+
+        let code_hash = encoding::to_hex(&Sha256::digest(&code));
+        let address = Chain::derive_address(
+            "contract",
+            &self.config.chain_id,
+            &format!("{}:{}:{}", owner, code_hash, self.contracts.contracts.len()),
+            CONTRACT_ADDRESS_LENGTH,
+        );
+
+        if self.wallets.contains_key(&address) {
+            return Err(ChainError::InvalidContract(format!(
+                "{} is already a wallet",
+                address
+            )));
+        }
+
+        self.wallets.insert(
+            address.to_owned(),
+            Wallet::new(String::new(), address.to_owned(), 0.0),
+        );
+        self.contracts.contracts.insert(
+            address.to_owned(),
+            Contract {
+                address: address.to_owned(),
+                owner: owner.to_string(),
+                code_hash,
+                code,
+                storage: BTreeMap::new(),
+            },
+        );
+
+        self.assert_invariants();
+
+        Ok(address)
+    }
+
+    /// Call an exported function of a contract, with `DEFAULT_GAS_LIMIT` gas.
+    ///
+    /// # Arguments
+    /// - `caller`: The address of the calling wallet.
+    /// - `address`: The address of the contract.
+    /// - `method`: The name of the exported function, taking and returning no values.
+    /// - `args`: The arguments, read by the contract with the `input` host function.
+    /// - `keypair`: The keypair owning the calling wallet, required if the wallet has a public
+    ///   key.
+    ///
+    /// # Returns
+    /// The output, the gas used and the transfers of the call, or the reason it failed.
+    pub fn call_contract(
+        &mut self,
+        caller: &str,
+        address: &str,
+        method: &str,
+        args: &[u8],
+        keypair: Option<&Keypair>,
+    ) -> Result<ContractCall, ChainError> {
+        self.call_contract_with_gas(caller, address, method, args, DEFAULT_GAS_LIMIT, keypair)
+    }
+
+    /// Call an exported function of a contract, with a gas limit.
+    ///
+    /// Every executed instruction and host function consumes gas, so the same call on the same
+    /// state always uses the same gas and ends the same way. The storage writes and the
+    /// transfers of the contract funds only apply if the call succeeds and every transfer is
+    /// accepted, the transfers being added as transactions from the contract wallet.
+    ///
+    /// # Arguments
+    /// - `caller`: The address of the calling wallet.
+    /// - `address`: The address of the contract.
+    /// - `method`: The name of the exported function, taking and returning no values.
+    /// - `args`: The arguments, read by the contract with the `input` host function.
+    /// - `gas_limit`: The most gas the call can use.
+    /// - `keypair`: The keypair owning the calling wallet, required if the wallet has a public
+    ///   key.
+    ///
+    /// # Returns
+    /// The output, the gas used and the transfers of the call, `OutOfGas` if it exceeds the
+    /// gas limit, or the reason it failed.
+    pub fn call_contract_with_gas(
+        &mut self,
+        caller: &str,
+        address: &str,
+        method: &str,
+        args: &[u8],
+        gas_limit: u64,
+        keypair: Option<&Keypair>,
+    ) -> Result<ContractCall, ChainError> {
+        self.check_owner(caller, keypair)?;
+
+        let contract = self
+            .contracts
+            .contracts
+            .get(address)
+            .ok_or_else(|| ChainError::UnknownContract(address.to_string()))?;
+        let (state, gas_used) = execute(contract, caller, method, args, gas_limit)?;
+
+        // Restore the state if a transfer is rejected
+        let wallets = self.wallets.clone();
+        let pending = self.current_transactions.clone();
+        let (collected_fees, evictions) = (self.collected_fees, self.evictions.clone());
+        let ledger = self.ledger.clone();
+        let mut transfers = Vec::new();
+
+        self.contracts.sending = Some(address.to_string());
+
+        for (to, amount) in state.transfers {
+            match self.add_unsigned_transaction(address.to_string(), to, amount) {
+                Ok(hash) => transfers.push(hash),
+                Err(err) => {
+                    self.contracts.sending = None;
+                    self.wallets = wallets;
+                    self.current_transactions = pending;
+                    self.collected_fees = collected_fees;
+                    self.evictions = evictions;
+                    self.ledger = ledger;
+
+                    return Err(err);
+                }
+            }
+        }
+
+        self.contracts.sending = None;
+
+        if let Some(contract) = self.contracts.contracts.get_mut(address) {
+            contract.storage = state.storage;
+        }
+
+        Ok(ContractCall {
+            output: state.output,
+            gas_used,
+            transfers,
+        })
+    }
+
+    /// Get a deployed contract.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the contract.
+    ///
+    /// # Returns
+    /// The contract, or `None` if no contract is deployed at the address.
+    pub fn get_contract(&self, address: &str) -> Option<&Contract> {
+        self.contracts.contracts.get(address)
+    }
+
+    /// Read a value written by a contract.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the contract.
+    /// - `key`: The key of the value.
+    ///
+    /// # Returns
+    /// The value, or `None` if the contract or the key does not exist.
+    pub fn contract_storage(&self, address: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.contracts
+            .contracts
+            .get(address)?
+            .storage
+            .get(&encoding::to_hex(key))
+            .and_then(|value| keys::decode_hex(value))
+    }
+
+    /// Check that a keypair owns the wallet deploying or calling a contract, as the signature of
+    /// a transaction would.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `keypair`: The keypair given for the wallet, if any.
+    ///
+    /// # Returns
+    /// `UnknownSender` if the address is not a wallet, `SignatureRequired` if the wallet has a
+    /// public key and no keypair is given, or `InvalidSignature` if the keypair does not own it.
+    fn check_owner(&self, address: &str, keypair: Option<&Keypair>) -> Result<(), ChainError> {
+        let Some(wallet) = self.wallets.get(address) else {
+            return Err(ChainError::UnknownSender(address.to_string()));
+        };
+
+        match (&wallet.public_key, keypair) {
+            (None, _) => Ok(()),
+            (Some(_), None) => Err(ChainError::SignatureRequired),
+            (Some(public_key), Some(keypair)) if *public_key != keypair.public_key() => {
+                Err(ChainError::InvalidSignature)
+            }
+            (Some(_), Some(_)) => Ok(()),
+        }
+    }
+
+    /// Check that a transaction sent by a contract wallet pays a transfer of its contract.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    ///
+    /// # Returns
+    /// `InvalidContract` if the sender is a contract outside its calls.
+    pub(crate) fn check_contract_sender(&self, from: &str) -> Result<(), ChainError> {
+        match self.contracts.contracts.contains_key(from)
+            && self.contracts.sending.as_deref() != Some(from)
+        {
+            true => Err(ChainError::InvalidContract(format!(
+                "{} only sends the transfers of its calls",
+                from
+            ))),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Create the engine executing the contracts, metering their instructions.
+///
+/// # Returns
+/// The engine.
+fn contract_engine() -> Engine {
+    let mut config = Config::default();
+
+    config.consume_fuel(true);
+
+    Engine::new(&config)
+}
+
+/// Execute an exported function of a contract.
+///
+/// # Arguments
+/// - `contract`: The contract.
+/// - `caller`: The address of the calling wallet.
+/// - `method`: The name of the exported function.
+/// - `args`: The arguments of the call.
+/// - `gas_limit`: The most gas the call can use.
+///
+/// # Returns
+/// The state left by the call and the gas it used, or the reason it failed.
+fn execute(
+    contract: &Contract,
+    caller: &str,
+    method: &str,
+    args: &[u8],
+    gas_limit: u64,
+) -> Result<(HostState, u64), ChainError> {
+    let engine = contract_engine();
+    let module = Module::new(&engine, &contract.code)
+        .map_err(|err| ChainError::InvalidContract(err.to_string()))?;
+    let mut store = Store::new(
+        &engine,
+        HostState {
+            caller: caller.to_string(),
+            storage: contract.storage.clone(),
+            input: args.to_vec(),
+            output: Vec::new(),
+            transfers: Vec::new(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_CONTRACT_MEMORY)
+                .build(),
+        },
+    );
+
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(gas_limit).expect("fuel metering is enabled");
+
+    let failed = |err: Error| match err.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => ChainError::OutOfGas { limit: gas_limit },
+        _ => ChainError::ContractReverted(err.to_string()),
+    };
+    let instance = host_linker(&engine)
+        .instantiate(&mut store, &module)
+        .and_then(|instance| instance.start(&mut store))
+        .map_err(failed)?;
+    let function = instance
+        .get_typed_func::<(), ()>(&store, method)
+        .map_err(|_| ChainError::ContractReverted(format!("no method {}", method)))?;
+
+    function.call(&mut store, ()).map_err(failed)?;
+
+    let gas_used = gas_limit - store.get_fuel().expect("fuel metering is enabled");
+
+    Ok((store.into_data(), gas_used))
+}
+
+/// Define the host functions importable by the contracts.
+///
+/// # Arguments
+/// - `engine`: The engine executing the contracts.
+///
+/// # Returns
+/// The linker resolving the imports of the contracts.
+fn host_linker(engine: &Engine) -> Linker<HostState> {
+    let mut linker = Linker::new(engine);
+
+    linker
+        .func_wrap(
+            "env",
+            "storage_read",
+            |mut caller: Caller<'_, HostState>,
+             key_ptr: u32,
+             key_len: u32,
+             out_ptr: u32,
+             out_cap: u32|
+             -> Result<i32, Error> {
+                let key = read_memory(&mut caller, key_ptr, key_len)?;
+                let value = caller
+                    .data()
+                    .storage
+                    .get(&encoding::to_hex(&key))
+                    .and_then(|value| keys::decode_hex(value));
+
+                match value {
+                    Some(value) => write_memory(&mut caller, out_ptr, out_cap, &value),
+                    None => Ok(-1),
+                }
+            },
+        )
+        .and_then(|linker| {
+            linker.func_wrap(
+                "env",
+                "storage_write",
+                |mut caller: Caller<'_, HostState>,
+                 key_ptr: u32,
+                 key_len: u32,
+                 value_ptr: u32,
+                 value_len: u32|
+                 -> Result<(), Error> {
+                    let key = read_memory(&mut caller, key_ptr, key_len)?;
+                    let value = read_memory(&mut caller, value_ptr, value_len)?;
+
+                    charge(
+                        &mut caller,
+                        (key.len() + value.len()) as u64 * STORAGE_BYTE_GAS,
+                    )?;
+                    caller
+                        .data_mut()
+                        .storage
+                        .insert(encoding::to_hex(&key), encoding::to_hex(&value));
+
+                    Ok(())
+                },
+            )
+        })
+        .and_then(|linker| {
+            linker.func_wrap(
+                "env",
+                "transfer",
+                |mut caller: Caller<'_, HostState>,
+                 to_ptr: u32,
+                 to_len: u32,
+                 amount: f64|
+                 -> Result<(), Error> {
+                    let to = read_memory(&mut caller, to_ptr, to_len)?;
+                    let to = String::from_utf8(to)
+                        .map_err(|_| Error::new("the receiver is not a UTF-8 address"))?;
+
+                    charge(&mut caller, TRANSFER_GAS)?;
+                    caller.data_mut().transfers.push((to, amount));
+
+                    Ok(())
+                },
+            )
+        })
+        .and_then(|linker| {
+            linker.func_wrap(
+                "env",
+                "caller",
+                |mut caller: Caller<'_, HostState>,
+                 out_ptr: u32,
+                 out_cap: u32|
+                 -> Result<i32, Error> {
+                    let address = caller.data().caller.to_owned();
+
+                    write_memory(&mut caller, out_ptr, out_cap, address.as_bytes())
+                },
+            )
+        })
+        .and_then(|linker| {
+            linker.func_wrap(
+                "env",
+                "input",
+                |mut caller: Caller<'_, HostState>,
+                 out_ptr: u32,
+                 out_cap: u32|
+                 -> Result<i32, Error> {
+                    let input = caller.data().input.to_owned();
+
+                    write_memory(&mut caller, out_ptr, out_cap, &input)
+                },
+            )
+        })
+        .and_then(|linker| {
+            linker.func_wrap(
+                "env",
+                "output",
+                |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> Result<(), Error> {
+                    caller.data_mut().output = read_memory(&mut caller, ptr, len)?;
+
+                    Ok(())
+                },
+            )
+        })
+        .expect("the host functions are defined once");
+
+    linker
+}
+
+/// Consume gas for the work of a host function.
+///
+/// # Arguments
+/// - `caller`: The calling contract.
+/// - `gas`: The gas to consume.
+///
+/// # Returns
+/// An `OutOfFuel` trap if the remaining gas is lower.
+fn charge(caller: &mut Caller<'_, HostState>, gas: u64) -> Result<(), Error> {
+    let fuel = caller.get_fuel().expect("fuel metering is enabled");
+    let remaining = fuel
+        .checked_sub(HOST_CALL_GAS + gas)
+        .ok_or(TrapCode::OutOfFuel)?;
+
+    caller
+        .set_fuel(remaining)
+        .expect("fuel metering is enabled");
+
+    Ok(())
+}
+
+/// Copy bytes out of the memory of a contract, charging the gas of the copy.
+///
+/// # Arguments
+/// - `caller`: The calling contract.
+/// - `ptr`: The offset of the bytes in the memory.
+/// - `len`: The amount of bytes.
+///
+/// # Returns
+/// The bytes, or a trap if they are out of the memory bounds.
+fn read_memory(caller: &mut Caller<'_, HostState>, ptr: u32, len: u32) -> Result<Vec<u8>, Error> {
+    charge(caller, len as u64 * BYTE_GAS)?;
+
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Error::new("the memory is not exported"))?;
+
+    memory
+        .data(&caller)
+        .get(ptr as usize..ptr as usize + len as usize)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| Error::new("out of bounds memory access"))
+}
+
+/// Copy bytes into the memory of a contract, truncated to the capacity of the buffer, charging
+/// the gas of the copy.
+///
+/// # Arguments
+/// - `caller`: The calling contract.
+/// - `ptr`: The offset of the buffer in the memory.
+/// - `cap`: The capacity of the buffer.
+/// - `bytes`: The bytes.
+///
+/// # Returns
+/// The length of the bytes before truncation, or a trap if the buffer is out of the memory
+/// bounds.
+fn write_memory(
+    caller: &mut Caller<'_, HostState>,
+    ptr: u32,
+    cap: u32,
+    bytes: &[u8],
+) -> Result<i32, Error> {
+    let len = bytes.len().min(cap as usize);
+
+    charge(caller, len as u64 * BYTE_GAS)?;
+
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Error::new("the memory is not exported"))?;
+
+    memory
+        .data_mut(caller)
+        .get_mut(ptr as usize..ptr as usize + len)
+        .ok_or_else(|| Error::new("out of bounds memory access"))?
+        .copy_from_slice(&bytes[..len]);
+
+    Ok(bytes.len() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Transaction};
+
+    /// A counter paying a unit back to its callers.
+    const COUNTER: &str = r#"
+        (module
+          (import "env" "storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+          (import "env" "storage_write" (func $write (param i32 i32 i32 i32)))
+          (import "env" "caller" (func $caller (param i32 i32) (result i32)))
+          (import "env" "transfer" (func $transfer (param i32 i32 f64)))
+          (import "env" "input" (func $input (param i32 i32) (result i32)))
+          (import "env" "output" (func $output (param i32 i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "count")
+          (func (export "increment")
+            (if (i32.lt_s (call $read (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 4))
+                          (i32.const 0))
+              (then (i32.store (i32.const 16) (i32.const 0))))
+            (i32.store (i32.const 16) (i32.add (i32.load (i32.const 16)) (i32.const 1)))
+            (call $write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 4))
+            (call $output (i32.const 16) (i32.const 4)))
+          (func (export "refund")
+            (call $write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 4))
+            (call $transfer (i32.const 32) (call $caller (i32.const 32) (i32.const 64))
+                            (f64.const 1)))
+          (func (export "echo")
+            (call $output (i32.const 128) (call $input (i32.const 128) (i32.const 64))))
+          (func (export "spin")
+            (loop $spin (br $spin))))
+    "#;
+
+    #[test]
+    fn test_deploy_contract() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let owner = chain.create_wallet("o@mail.com".to_string()).unwrap();
+        let code = wat::parse_str(COUNTER).unwrap();
+
+        assert_eq!(
+            chain.deploy_contract("unknown", code.to_owned(), None),
+            Err(ChainError::UnknownSender("unknown".to_string()))
+        );
+        assert!(matches!(
+            chain.deploy_contract(&owner, b"not wasm".to_vec(), None),
+            Err(ChainError::InvalidContract(_))
+        ));
+        assert!(matches!(
+            chain.deploy_contract(
+                &owner,
+                wat::parse_str(
+                    r#"(module (import "wasi" "fd_write" (func)) (memory (export "memory") 1))"#
+                )
+                .unwrap(),
+                None
+            ),
+            Err(ChainError::InvalidContract(_))
+        ));
+        assert!(matches!(
+            chain.deploy_contract(&owner, wat::parse_str("(module)").unwrap(), None),
+            Err(ChainError::InvalidContract(_))
+        ));
+
+        // The same code deploys to distinct addresses
+        let first = chain
+            .deploy_contract(&owner, code.to_owned(), None)
+            .unwrap();
+        let second = chain.deploy_contract(&owner, code, None).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(chain.get_contract(&first).unwrap().owner, owner);
+        assert_eq!(chain.get_wallet_balance(first), Some(0.0));
+    }
+
+    #[test]
+    fn test_call_contract() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let caller = chain.create_wallet("c@mail.com".to_string()).unwrap();
+        let address = chain
+            .deploy_contract(&caller, wat::parse_str(COUNTER).unwrap(), None)
+            .unwrap();
+
+        chain.credit_wallet(&caller, 10.0);
+
+        assert_eq!(
+            chain.call_contract(&caller, "unknown", "increment", &[], None),
+            Err(ChainError::UnknownContract("unknown".to_string()))
+        );
+        assert!(matches!(
+            chain.call_contract(&caller, &address, "missing", &[], None),
+            Err(ChainError::ContractReverted(_))
+        ));
+
+        let first = chain
+            .call_contract(&caller, &address, "increment", &[], None)
+            .unwrap();
+        let second = chain
+            .call_contract(&caller, &address, "increment", &[], None)
+            .unwrap();
+
+        assert_eq!(first.output, 1i32.to_le_bytes());
+        assert_eq!(second.output, 2i32.to_le_bytes());
+        assert_eq!(
+            chain.contract_storage(&address, b"count"),
+            Some(2i32.to_le_bytes().to_vec())
+        );
+        assert_eq!(
+            chain
+                .call_contract(&caller, &address, "echo", b"hello", None)
+                .unwrap()
+                .output,
+            b"hello"
+        );
+
+        // Running out of gas leaves the storage as it was
+        assert_eq!(
+            chain.call_contract_with_gas(&caller, &address, "spin", &[], 10_000, None),
+            Err(ChainError::OutOfGas { limit: 10_000 })
+        );
+        assert_eq!(
+            chain.call_contract_with_gas(
+                &caller,
+                &address,
+                "increment",
+                &[],
+                second.gas_used - 1,
+                None
+            ),
+            Err(ChainError::OutOfGas {
+                limit: second.gas_used - 1
+            })
+        );
+        assert_eq!(
+            chain.contract_storage(&address, b"count"),
+            Some(2i32.to_le_bytes().to_vec())
+        );
+
+        // The same call on the same state uses the same gas
+        let third = chain
+            .call_contract_with_gas(&caller, &address, "increment", &[], second.gas_used, None)
+            .unwrap();
+
+        assert_eq!(third.output, 3i32.to_le_bytes());
+        assert_eq!(third.gas_used, second.gas_used);
+    }
+
+    #[test]
+    fn test_contract_transfers() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let caller = chain
+            .create_wallet_with_key("c@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let address = chain
+            .deploy_contract(&caller, wat::parse_str(COUNTER).unwrap(), Some(&keypair))
+            .unwrap();
+
+        chain.credit_wallet(&caller, 10.0);
+
+        // The owner of a wallet with a public key deploys and calls with its keypair
+        assert_eq!(
+            chain.deploy_contract(&caller, wat::parse_str(COUNTER).unwrap(), None),
+            Err(ChainError::SignatureRequired)
+        );
+        assert_eq!(
+            chain.call_contract(&caller, &address, "refund", &[], None),
+            Err(ChainError::SignatureRequired)
+        );
+        assert_eq!(
+            chain.call_contract(&caller, &address, "refund", &[], Some(&Keypair::generate())),
+            Err(ChainError::InvalidSignature)
+        );
+
+        // A rejected transfer reverts the storage writes of the call
+        assert_eq!(
+            chain.call_contract(&caller, &address, "refund", &[], Some(&keypair)),
+            Err(ChainError::InsufficientBalance)
+        );
+        assert_eq!(chain.contract_storage(&address, b"count"), None);
+
+        chain
+            .add_transaction(caller.to_owned(), address.to_owned(), 5.0, &keypair)
+            .unwrap();
+
+        let call = chain
+            .call_contract(&caller, &address, "refund", &[], Some(&keypair))
+            .unwrap();
+
+        assert_eq!(call.transfers.len(), 1);
+        assert_eq!(chain.get_wallet_balance(address.to_owned()), Some(3.9));
+        assert_eq!(chain.get_wallet_balance(caller.to_owned()), Some(5.5));
+
+        // The contract funds only move through its calls
+        let transfer = Transaction::builder()
+            .from(address)
+            .to(caller)
+            .amount(1.0)
+            .fee(0.1)
+            .build();
+
+        assert!(matches!(
+            chain.submit_transaction(transfer),
+            Err(ChainError::InvalidContract(_))
+        ));
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, ErrorKind, Read},
+    path::Path,
+};
+
+#[cfg(any(test, feature = "testing"))]
+use std::{thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(test, feature = "testing"))]
+use crate::Chain;
+
+/// A payment of a transaction dataset, between two identities of the dataset.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DatasetRow {
+    /// The identity of the sender.
+    pub from: String,
+
+    /// The identity of the receiver.
+    pub to: String,
+
+    /// The amount of the payment.
+    pub amount: f64,
+
+    /// The time of the payment, in seconds.
+    pub timestamp: i64,
+}
+
+/// A dataset of payments, replayed through a blockchain for benchmarks and demos.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dataset {
+    /// The payments, ordered by timestamp.
+    pub rows: Vec<DatasetRow>,
+}
+
+impl Dataset {
+    /// Create a dataset from payments, ordering them by timestamp.
+    ///
+    /// # Arguments
+    /// - `rows`: The payments.
+    ///
+    /// # Returns
+    /// A new dataset.
+    pub fn new(mut rows: Vec<DatasetRow>) -> Self {
+        rows.sort_by_key(|row| row.timestamp);
+
+        Dataset { rows }
+    }
+
+    /// Read a CSV dataset with a header naming the `from`, `to`, `amount` and `timestamp` columns.
+    ///
+    /// The columns may come in any order and other columns are ignored. Fields are separated by
+    /// commas and may be surrounded by double quotes.
+    ///
+    /// # Arguments
+    /// - `reader`: The CSV content.
+    ///
+    /// # Returns
+    /// The dataset, or an `InvalidData` error naming the first invalid line.
+    pub fn from_csv<R: Read>(reader: R) -> io::Result<Self> {
+        let mut lines = BufReader::new(reader).lines();
+
+        let header = match lines.next() {
+            Some(line) => split_csv_line(&line?),
+            None => return Ok(Dataset::default()),
+        };
+
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|field| field.eq_ignore_ascii_case(name))
+                .ok_or_else(|| invalid_data(format!("Missing column {}", name)))
+        };
+
+        let (from, to, amount, timestamp) = (
+            column("from")?,
+            column("to")?,
+            column("amount")?,
+            column("timestamp")?,
+        );
+
+        let mut rows = vec![];
+
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+
+            // Skip blank lines, typically at the end of the file
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = split_csv_line(&line);
+            let field = |position: usize| fields.get(position).map(String::as_str).unwrap_or("");
+            let invalid = || invalid_data(format!("Invalid row on line {}", index + 2));
+
+            rows.push(DatasetRow {
+                from: field(from).to_string(),
+                to: field(to).to_string(),
+                amount: field(amount).parse().map_err(|_| invalid())?,
+                timestamp: field(timestamp).parse().map_err(|_| invalid())?,
+            });
+        }
+
+        Ok(Dataset::new(rows))
+    }
+
+    /// Read a JSON dataset: an array of objects with `from`, `to`, `amount` and `timestamp` fields.
+    ///
+    /// # Arguments
+    /// - `reader`: The JSON content.
+    ///
+    /// # Returns
+    /// The dataset, or an `InvalidData` error if the content is not a valid dataset.
+    pub fn from_json<R: Read>(reader: R) -> io::Result<Self> {
+        let rows: Vec<DatasetRow> = serde_json::from_reader(reader).map_err(io::Error::from)?;
+
+        Ok(Dataset::new(rows))
+    }
+
+    /// Read a dataset file, in JSON if its extension is `json` and in CSV otherwise.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file.
+    ///
+    /// # Returns
+    /// The dataset, or an error if the file cannot be read or is not a valid dataset.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(&path)?;
+
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Dataset::from_json(file),
+            _ => Dataset::from_csv(file),
+        }
+    }
+}
+
+/// Settings of a dataset replay.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayOptions {
+    /// How many times faster than the dataset the payments are replayed, without waiting if
+    /// zero or infinite.
+    pub speed: f64,
+
+    /// The balance credited to the wallet of every identity when it is created.
+    pub initial_balance: f64,
+
+    /// The amount of submitted payments after which a block is mined, never if zero.
+    pub block_size: usize,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        ReplayOptions {
+            speed: 0.0,
+            initial_balance: 0.0,
+            block_size: 0,
+        }
+    }
+}
+
+/// Outcome of a dataset replay.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayReport {
+    /// A map to associate the identities of the dataset with their wallet addresses.
+    pub wallets: HashMap<String, String>,
+
+    /// The amount of payments accepted by the blockchain.
+    pub submitted: usize,
+
+    /// The amount of payments rejected by the blockchain.
+    pub rejected: usize,
+
+    /// The amount of mined blocks.
+    pub blocks: usize,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Chain {
+    /// Replay the payments of a dataset through the blockchain.
+    ///
+    /// Every identity of the dataset is mapped to a wallet, using the identity as the wallet
+    /// email, and the wallets created for the replay are credited with the initial balance.
+    /// The pauses between the payments follow their timestamps, divided by the speed.
+    ///
+    /// # Arguments
+    /// - `dataset`: The dataset.
+    /// - `options`: The replay settings.
+    ///
+    /// # Returns
+    /// The wallets of the identities and the amount of accepted and rejected payments.
+    pub fn replay_dataset(&mut self, dataset: &Dataset, options: &ReplayOptions) -> ReplayReport {
+        let mut report = ReplayReport::default();
+        let mut previous: Option<i64> = None;
+
+        for row in &dataset.rows {
+            if let Some(previous) = previous {
+                let seconds = (row.timestamp - previous).max(0) as f64 / options.speed;
+
+                if seconds.is_finite() && seconds > 0.0 {
+                    thread::sleep(Duration::from_secs_f64(seconds));
+                }
+            }
+
+            previous = Some(row.timestamp);
+
+            let from = self.dataset_wallet(&row.from, options, &mut report);
+            let to = self.dataset_wallet(&row.to, options, &mut report);
+
+            let accepted = match (from, to) {
+                (Some(from), Some(to)) => {
+                    self.add_unsigned_transaction(from, to, row.amount).is_ok()
+                }
+                _ => false,
+            };
+
+            if !accepted {
+                report.rejected += 1;
+                continue;
+            }
+
+            report.submitted += 1;
+
+            if options.block_size > 0
+                && report.submitted.is_multiple_of(options.block_size)
+                && self.generate_new_block().is_ok()
+            {
+                report.blocks += 1;
+            }
+        }
+
+        report
+    }
+
+    /// Get the wallet of a dataset identity, creating and funding it if needed.
+    ///
+    /// # Arguments
+    /// - `identity`: The identity of the dataset.
+    /// - `options`: The replay settings.
+    /// - `report`: The report recording the wallets of the identities.
+    ///
+    /// # Returns
+    /// The wallet address, or `None` if the identity cannot be used as a wallet email.
+    fn dataset_wallet(
+        &mut self,
+        identity: &str,
+        options: &ReplayOptions,
+        report: &mut ReplayReport,
+    ) -> Option<String> {
+        if let Some(address) = report.wallets.get(identity) {
+            return Some(address.to_owned());
+        }
+
+        let address = match self.get_wallet_by_email(identity) {
+            Some(wallet) => wallet.address.to_owned(),
+            None => {
+                let address = self.create_wallets(&[identity.to_string()]).pop()?.ok()?;
+
+                if options.initial_balance > 0.0 {
+                    self.credit_wallet(&address, options.initial_balance);
+                }
+
+                address
+            }
+        };
+
+        report
+            .wallets
+            .insert(identity.to_string(), address.to_owned());
+
+        Some(address)
+    }
+}
+
+/// Split a CSV line into its fields, removing the surrounding quotes.
+///
+/// # Arguments
+/// - `line`: The line.
+///
+/// # Returns
+/// The fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    line.split(',')
+        .map(|field| field.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+/// Create an `InvalidData` error.
+///
+/// # Arguments
+/// - `message`: The description of the error.
+///
+/// # Returns
+/// The error.
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_csv_line() {
+        assert_eq!(
+            split_csv_line(" alice ,\"bob\",1.5"),
+            vec!["alice".to_string(), "bob".to_string(), "1.5".to_string()]
+        );
+    }
+}
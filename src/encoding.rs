@@ -0,0 +1,444 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Approval, BlockHeader, ConsensusRules, DifficultyAlgorithm, Gas, KeyRotation, LedgerModel,
+    Memo, OutPoint, Script, StakingOperation, Transaction, Witness,
+};
+
+/// Domain of the block header hashes.
+pub const BLOCK_HEADER_DOMAIN: &str = "blockchain/block-header/v1";
+
+/// Domain of the transaction hashes.
+pub const TRANSACTION_DOMAIN: &str = "blockchain/transaction/v1";
+
+/// Domain of the payloads signed by the senders and the fee payers.
+pub const SIGNING_DOMAIN: &str = "blockchain/signing/v1";
+
+/// Domain of the block headers signed by the validators under proof of authority.
+pub const BLOCK_SEAL_DOMAIN: &str = "blockchain/block-seal/v1";
+
+/// Domain of the arbitrary messages signed by the wallet owners.
+pub const MESSAGE_DOMAIN: &str = "blockchain/message/v1";
+
+/// Domain of the keys encrypting the memos to their receivers.
+pub const MEMO_DOMAIN: &str = "blockchain/memo/v1";
+
+/// Domain of the wallet emails hashed by the email privacy settings.
+pub const EMAIL_DOMAIN: &str = "blockchain/email/v1";
+
+/// Domain of the Merkle tree leaves, one per transaction of a block.
+pub const MERKLE_LEAF_DOMAIN: &str = "blockchain/merkle-leaf/v1";
+
+/// Domain of the Merkle tree nodes combining two hashes.
+pub const MERKLE_NODE_DOMAIN: &str = "blockchain/merkle-node/v1";
+
+/// Domain of the consensus rules hashed into the genesis block and the handshake.
+pub const CONSENSUS_RULES_DOMAIN: &str = "blockchain/consensus-rules/v1";
+
+/// A value with a canonical binary encoding, independent of any serialization format.
+///
+/// Integers are encoded in big-endian with a fixed width, floats as their IEEE 754 bits with a
+/// single zero and a single NaN, strings and sequences prefixed with their length, and optional
+/// values prefixed with a presence byte.
+pub trait CanonicalEncode {
+    /// Append the canonical encoding of the value.
+    ///
+    /// # Arguments
+    /// - `out`: The buffer receiving the encoding.
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+impl<T: CanonicalEncode + ?Sized> CanonicalEncode for &T {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+impl CanonicalEncode for u8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl CanonicalEncode for u32 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl CanonicalEncode for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl CanonicalEncode for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+}
+
+impl CanonicalEncode for i64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl CanonicalEncode for f64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let value = match self {
+            value if value.is_nan() => f64::NAN,
+            value if *value == 0.0 => 0.0,
+            value => *value,
+        };
+
+        value.to_bits().encode(out);
+    }
+}
+
+impl CanonicalEncode for str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl CanonicalEncode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out);
+    }
+}
+
+impl<T: CanonicalEncode> CanonicalEncode for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                out.push(1);
+                value.encode(out);
+            }
+            None => out.push(0),
+        }
+    }
+}
+
+impl<T: CanonicalEncode> CanonicalEncode for [T] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+
+        for item in self {
+            item.encode(out);
+        }
+    }
+}
+
+impl<T: CanonicalEncode> CanonicalEncode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out);
+    }
+}
+
+impl<K: CanonicalEncode, V: CanonicalEncode> CanonicalEncode for BTreeMap<K, V> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+
+        for (key, value) in self {
+            key.encode(out);
+            value.encode(out);
+        }
+    }
+}
+
+impl CanonicalEncode for BlockHeader {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.timestamp.encode(out);
+        self.nonce.encode(out);
+        self.previous_hash.encode(out);
+        self.merkle.encode(out);
+        self.difficulty.encode(out);
+        self.rules.encode(out);
+    }
+}
+
+impl CanonicalEncode for DifficultyAlgorithm {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            DifficultyAlgorithm::Fixed => 0u8.encode(out),
+            DifficultyAlgorithm::Retarget {
+                target_block_time,
+                retarget_interval,
+            } => {
+                1u8.encode(out);
+                target_block_time.encode(out);
+                retarget_interval.encode(out);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for LedgerModel {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let tag: u8 = match self {
+            LedgerModel::Account => 0,
+            LedgerModel::Utxo => 1,
+        };
+
+        tag.encode(out);
+    }
+}
+
+impl CanonicalEncode for ConsensusRules {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.max_block_weight.encode(out);
+        self.max_transactions_per_block.encode(out);
+        self.reserved_system_weight.encode(out);
+        self.reserved_system_transactions.encode(out);
+        self.coinbase_maturity.encode(out);
+        self.max_timestamp_drift.encode(out);
+        self.difficulty_algorithm.encode(out);
+        self.activation_heights.encode(out);
+        self.ledger.encode(out);
+    }
+}
+
+impl CanonicalEncode for KeyRotation {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.previous.encode(out);
+        self.public_key.encode(out);
+        u8::from(self.recovery).encode(out);
+    }
+}
+
+impl CanonicalEncode for Memo {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Memo::Plain { text } => {
+                0u8.encode(out);
+                text.encode(out);
+            }
+            Memo::Encrypted {
+                ephemeral_key,
+                ciphertext,
+            } => {
+                1u8.encode(out);
+                ephemeral_key.encode(out);
+                ciphertext.encode(out);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for StakingOperation {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let tag: u8 = match self {
+            StakingOperation::Stake { .. } => 0,
+            StakingOperation::Unstake { .. } => 1,
+            StakingOperation::Claim { .. } => 2,
+        };
+
+        tag.encode(out);
+        self.amount().encode(out);
+    }
+}
+
+impl CanonicalEncode for OutPoint {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.hash.encode(out);
+        self.index.encode(out);
+    }
+}
+
+impl CanonicalEncode for Script {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Script::HashLock { hash } => {
+                0u8.encode(out);
+                hash.encode(out);
+            }
+            Script::TimeLock { unlock_at } => {
+                1u8.encode(out);
+                unlock_at.encode(out);
+            }
+            Script::MultiSig {
+                threshold,
+                public_keys,
+            } => {
+                2u8.encode(out);
+                threshold.encode(out);
+                public_keys.encode(out);
+            }
+            Script::All(scripts) => {
+                3u8.encode(out);
+                scripts.encode(out);
+            }
+            Script::Any(scripts) => {
+                4u8.encode(out);
+                scripts.encode(out);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for Gas {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.limit.encode(out);
+        self.price.encode(out);
+    }
+}
+
+impl CanonicalEncode for Approval {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.public_key.encode(out);
+        self.signature.encode(out);
+    }
+}
+
+impl CanonicalEncode for Witness {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.preimage.encode(out);
+        self.signatures.encode(out);
+    }
+}
+
+impl CanonicalEncode for Transaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.hash.encode(out);
+        self.from.encode(out);
+        self.to.encode(out);
+        self.fee.encode(out);
+        self.amount.encode(out);
+        self.nonce.encode(out);
+        self.timestamp.encode(out);
+        self.sequence.encode(out);
+        self.signature.encode(out);
+        self.fee_payer.encode(out);
+        self.fee_payer_signature.encode(out);
+        self.key_rotation.encode(out);
+        self.inputs.encode(out);
+        self.memo.encode(out);
+        self.lock.encode(out);
+        self.witness.encode(out);
+        self.gas.encode(out);
+        self.asset_id.encode(out);
+        self.asset_name.encode(out);
+        self.staking.encode(out);
+    }
+}
+
+/// Encode values canonically, prefixed with the domain separating their kind.
+///
+/// # Arguments
+/// - `domain`: The kind of the encoded values.
+/// - `values`: The values, in order.
+///
+/// # Returns
+/// The canonical encoding.
+pub fn encode(domain: &str, values: &[&dyn CanonicalEncode]) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    domain.encode(&mut out);
+
+    for value in values {
+        value.encode(&mut out);
+    }
+
+    out
+}
+
+/// Compute the SHA-256 digest of the canonical encoding of values.
+///
+/// # Arguments
+/// - `domain`: The kind of the encoded values.
+/// - `values`: The values, in order.
+///
+/// # Returns
+/// The digest.
+pub fn digest(domain: &str, values: &[&dyn CanonicalEncode]) -> [u8; 32] {
+    Sha256::digest(encode(domain, values)).into()
+}
+
+/// Compute the hex encoded SHA-256 hash of the canonical encoding of values.
+///
+/// # Arguments
+/// - `domain`: The kind of the encoded values.
+/// - `values`: The values, in order.
+///
+/// # Returns
+/// The hash, as 64 lowercase hexadecimal digits.
+pub fn hash(domain: &str, values: &[&dyn CanonicalEncode]) -> String {
+    to_hex(&digest(domain, values))
+}
+
+/// Encode bytes as lowercase hexadecimal digits, two per byte.
+///
+/// # Arguments
+/// - `bytes`: The bytes.
+///
+/// # Returns
+/// The hex encoded bytes.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_layout() {
+        let encoded = encode("d", &[&1u32, &"ab", &Some(-1i64), &None::<u8>]);
+
+        assert_eq!(
+            encoded,
+            [
+                vec![0, 0, 0, 0, 0, 0, 0, 1, b'd'],
+                vec![0, 0, 0, 1],
+                vec![0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b'],
+                vec![1, 255, 255, 255, 255, 255, 255, 255, 255],
+                vec![0],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_encode_floats() {
+        assert_eq!(encode("", &[&-0.0]), encode("", &[&0.0]));
+        assert_eq!(encode("", &[&f64::NAN]), encode("", &[&-f64::NAN]));
+        assert_ne!(encode("", &[&0.1]), encode("", &[&0.2]));
+    }
+
+    #[test]
+    fn test_domains_separate_hashes() {
+        assert_ne!(
+            hash(TRANSACTION_DOMAIN, &[&"a"]),
+            hash(SIGNING_DOMAIN, &[&"a"])
+        );
+        assert_eq!(hash("", &[]).len(), 64);
+        assert_eq!(to_hex(&[0, 15, 255]), "000fff");
+    }
+
+    #[test]
+    fn test_header_hash_is_pinned() {
+        let header = BlockHeader {
+            timestamp: 1_700_000_000_000,
+            nonce: 42,
+            previous_hash: "0".repeat(64),
+            merkle: "m".to_string(),
+            difficulty: 2.5,
+            rules: None,
+            signature: None,
+        };
+
+        assert_eq!(
+            hash(BLOCK_HEADER_DOMAIN, &[&header]),
+            "e4a1bcd64efef4ee80c6319d6141ce0b64787822734db6d4b36466a0f3ae27f7"
+        );
+    }
+}
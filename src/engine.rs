@@ -0,0 +1,229 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{keys, Address, Block, BlockHeader, Chain, ChainError, Keypair};
+
+/// Signature of a block header by the validator sealing the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSignature {
+    /// The hex encoded public key of the validator.
+    pub public_key: String,
+
+    /// The hex encoded signature of the header hash.
+    pub signature: String,
+}
+
+/// A consensus engine deciding who may append blocks to the blockchain and how they prove it.
+pub trait Consensus: Debug + Send + Sync {
+    /// Prepare the header of a new block before its transactions are selected.
+    ///
+    /// # Arguments
+    /// - `chain`: The blockchain the block extends.
+    /// - `block`: The unsealed block.
+    ///
+    /// # Returns
+    /// An error if the node cannot produce the block.
+    fn prepare_block(&self, chain: &Chain, block: &mut Block) -> Result<(), ChainError>;
+
+    /// Seal a block holding its final transactions, so other nodes accept it.
+    ///
+    /// # Arguments
+    /// - `block`: The block.
+    ///
+    /// # Returns
+    /// An error if the node cannot seal the block.
+    fn seal_block(&self, block: &mut Block) -> Result<(), ChainError>;
+
+    /// Verify the seal of a block header.
+    ///
+    /// # Arguments
+    /// - `header`: The block header.
+    ///
+    /// # Returns
+    /// `InvalidBlock` if the header is not sealed according to the engine.
+    fn verify_seal(&self, header: &BlockHeader) -> Result<(), ChainError>;
+}
+
+/// Proof of work: a block is sealed by finding a nonce whose header hash satisfies the difficulty.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProofOfWork;
+
+impl Consensus for ProofOfWork {
+    fn prepare_block(&self, chain: &Chain, block: &mut Block) -> Result<(), ChainError> {
+        // Validate that the proof of work can find a hash with enough leading zeros
+        if !Chain::is_valid_difficulty(chain.difficulty) {
+            return Err(ChainError::InvalidDifficulty);
+        }
+
+        block.header.difficulty = chain.difficulty;
+
+        Ok(())
+    }
+
+    fn seal_block(&self, block: &mut Block) -> Result<(), ChainError> {
+        Block::proof_of_work(&mut block.header);
+
+        Ok(())
+    }
+
+    fn verify_seal(&self, header: &BlockHeader) -> Result<(), ChainError> {
+        match Chain::is_valid_difficulty(header.difficulty) && header.meets_difficulty() {
+            true => Ok(()),
+            false => Err(ChainError::InvalidBlock(
+                "the hash does not satisfy the difficulty".to_string(),
+            )),
+        }
+    }
+}
+
+/// Proof of authority: a block is sealed by the signature of one of the designated validators.
+#[derive(Clone, Debug)]
+pub struct ProofOfAuthority {
+    /// The addresses of the validators allowed to seal blocks.
+    validators: Vec<Address>,
+
+    /// The keypair sealing the blocks of this node, `None` to only verify blocks.
+    signer: Option<Keypair>,
+}
+
+impl ProofOfAuthority {
+    /// Create a new proof-of-authority engine verifying the blocks of a set of validators.
+    ///
+    /// # Arguments
+    /// - `validators`: The addresses of the validators, derived from their public keys.
+    ///
+    /// # Returns
+    /// A new `ProofOfAuthority` instance, not sealing blocks.
+    pub fn new(validators: Vec<Address>) -> Self {
+        ProofOfAuthority {
+            validators,
+            signer: None,
+        }
+    }
+
+    /// Seal the blocks of this node with the keypair of a validator.
+    ///
+    /// # Arguments
+    /// - `keypair`: The keypair of the validator.
+    ///
+    /// # Returns
+    /// The engine sealing blocks with the keypair.
+    pub fn with_signer(mut self, keypair: Keypair) -> Self {
+        self.signer = Some(keypair);
+        self
+    }
+
+    /// Get the validators allowed to seal blocks.
+    ///
+    /// # Returns
+    /// The addresses of the validators.
+    pub fn validators(&self) -> &[Address] {
+        &self.validators
+    }
+
+    /// Get the keypair sealing the blocks of this node.
+    ///
+    /// # Returns
+    /// The keypair, or `NotValidator` if the node holds no keypair of a validator.
+    fn signer(&self) -> Result<&Keypair, ChainError> {
+        self.signer
+            .as_ref()
+            .filter(|keypair| self.validators.contains(&keypair.address()))
+            .ok_or(ChainError::NotValidator)
+    }
+}
+
+impl Consensus for ProofOfAuthority {
+    fn prepare_block(&self, chain: &Chain, block: &mut Block) -> Result<(), ChainError> {
+        self.signer()?;
+
+        // The difficulty is kept for the fork choice, no work is performed
+        block.header.difficulty = chain.difficulty;
+
+        Ok(())
+    }
+
+    fn seal_block(&self, block: &mut Block) -> Result<(), ChainError> {
+        block.header.signature = Some(self.signer()?.sign_header(&block.header));
+
+        Ok(())
+    }
+
+    fn verify_seal(&self, header: &BlockHeader) -> Result<(), ChainError> {
+        let invalid = |reason: &str| Err(ChainError::InvalidBlock(reason.to_string()));
+
+        let Some(signature) = &header.signature else {
+            return invalid("the block is not signed by a validator");
+        };
+
+        let validator = keys::address_from_public_key(&signature.public_key);
+
+        if !self.validators.contains(&validator) {
+            return invalid("the block is signed by an unknown validator");
+        }
+
+        if !keys::verify_header_signature(header) {
+            return invalid("the signature of the validator is invalid");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_of_authority() {
+        let validator = Keypair::generate();
+        let outsider = Keypair::generate();
+        let engine = ProofOfAuthority::new(vec![validator.address()]);
+        let chain = Chain::new(1.0, 100.0, 0.1);
+        let mut block = Block::new(chain.get_last_hash(), chain.difficulty);
+
+        // Only a validator can seal blocks
+        assert_eq!(
+            engine.prepare_block(&chain, &mut block),
+            Err(ChainError::NotValidator)
+        );
+        assert_eq!(
+            engine
+                .clone()
+                .with_signer(outsider.to_owned())
+                .seal_block(&mut block),
+            Err(ChainError::NotValidator)
+        );
+
+        let engine = engine.with_signer(validator);
+
+        assert!(engine.verify_seal(&block.header).is_err());
+        assert_eq!(engine.prepare_block(&chain, &mut block), Ok(()));
+        assert_eq!(engine.seal_block(&mut block), Ok(()));
+        assert_eq!(engine.verify_seal(&block.header), Ok(()));
+
+        // The signature covers the header
+        let mut tampered = block.header.clone();
+        tampered.nonce += 1;
+
+        assert!(engine.verify_seal(&tampered).is_err());
+
+        // A block signed by another key is rejected
+        let mut forged = block.header.clone();
+        forged.signature = Some(outsider.sign_header(&forged));
+
+        assert!(engine.verify_seal(&forged).is_err());
+    }
+
+    #[test]
+    fn test_proof_of_work() {
+        let chain = Chain::new(1.0, 100.0, 0.1);
+        let mut block = Block::new(chain.get_last_hash(), 0.0);
+
+        assert_eq!(ProofOfWork.prepare_block(&chain, &mut block), Ok(()));
+        assert_eq!(block.header.difficulty, 1.0);
+        assert_eq!(ProofOfWork.seal_block(&mut block), Ok(()));
+        assert_eq!(ProofOfWork.verify_seal(&block.header), Ok(()));
+    }
+}
@@ -0,0 +1,319 @@
+use std::{error::Error, fmt, time::Duration};
+
+/// Errors returned by blockchain operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainError {
+    /// The wallet email is empty.
+    InvalidEmail,
+
+    /// A wallet with the same email already exists.
+    DuplicateEmail(String),
+
+    /// The generated wallet address already belongs to another wallet.
+    AddressTaken(String),
+
+    /// The public key is not a hex encoded ed25519 public key.
+    InvalidPublicKey,
+
+    /// The mnemonic seed phrase is not a valid BIP-39 mnemonic.
+    InvalidMnemonic(String),
+
+    /// The key rotation does not apply to the current key of the wallet or moves funds.
+    InvalidKeyRotation(String),
+
+    /// The owners or the threshold of the multi-signature wallet are invalid, or the wallet
+    /// cannot propose the transaction.
+    InvalidMultiSig(String),
+
+    /// The sender address is not a wallet.
+    UnknownSender(String),
+
+    /// The receiver address is not a wallet.
+    UnknownReceiver(String),
+
+    /// The fee payer address is not a wallet.
+    UnknownFeePayer(String),
+
+    /// The sender and the receiver are the same wallet.
+    SelfTransfer,
+
+    /// The transaction amount is not positive.
+    InvalidAmount,
+
+    /// The sender cannot pay the amount, or the fee payer the fee, of the transaction.
+    InsufficientBalance,
+
+    /// The transaction fee is below the fee of the blockchain.
+    FeeTooLow,
+
+    /// The transaction fee is below the minimum relay fee of the mempool policy.
+    RelayFeeTooLow,
+
+    /// The transaction hash does not match its content.
+    InvalidHash,
+
+    /// The sender wallet has a public key, so the transaction must be signed.
+    SignatureRequired,
+
+    /// The transaction is not signed by the owner of the sender wallet.
+    InvalidSignature,
+
+    /// The sender wallet has no public key to verify the signature of its transactions.
+    UnregisteredKey(String),
+
+    /// The transaction is approved by fewer owners of the multi-signature sender wallet than
+    /// its threshold.
+    InsufficientApprovals {
+        /// The threshold of the sender wallet.
+        required: usize,
+
+        /// The amount of distinct owners approving the transaction.
+        actual: usize,
+    },
+
+    /// No transaction with the hash is proposed to the owners of a multi-signature wallet.
+    UnknownProposal(String),
+
+    /// The transaction was already submitted.
+    DuplicateTransaction,
+
+    /// The text of the memo of the transaction is longer than the maximum length.
+    MemoTooLong {
+        /// The maximum length in bytes.
+        max: usize,
+
+        /// The length in bytes of the text of the memo.
+        actual: usize,
+    },
+
+    /// The nonce of the transaction is not the nonce expected from the sender wallet, being
+    /// already used or skipping a transaction.
+    InvalidNonce {
+        /// The nonce expected from the sender wallet.
+        expected: u64,
+
+        /// The nonce of the transaction.
+        actual: u64,
+    },
+
+    /// The transaction breaks the spending policy of the sender wallet.
+    PolicyViolation(String),
+
+    /// The transaction is rejected by a validator registered on the blockchain.
+    TransactionRejected(String),
+
+    /// An input of the transaction refers to an output that is spent or does not exist.
+    DoubleSpend(String),
+
+    /// The inputs of the transaction are not owned by the sender or the fee payer, or do not
+    /// cover the amount and the fee.
+    InvalidInputs(String),
+
+    /// The transaction intake is paused by an operator, with the reason given.
+    ChainPaused(String),
+
+    /// A locking script is malformed or not satisfied by the spending transaction, with the
+    /// reason given.
+    InvalidScript(String),
+
+    /// The funds cannot be staked, unstaked or delegated, with the reason given.
+    InvalidStake(String),
+
+    /// No contract is deployed at the address.
+    UnknownContract(String),
+
+    /// The contract code is rejected, or its wallet sends funds outside its calls, with the
+    /// reason given.
+    InvalidContract(String),
+
+    /// The contract call trapped, with the reason given.
+    ContractReverted(String),
+
+    /// The contract call or the metered transaction needs more gas than its limit.
+    OutOfGas {
+        /// The gas limit of the call or the transaction.
+        limit: u64,
+    },
+
+    /// The gas price or the fee of a metered transaction is invalid, with the reason given.
+    InvalidGas(String),
+
+    /// No asset has the identifier.
+    UnknownAsset(String),
+
+    /// The asset cannot be issued or transferred, with the reason given.
+    InvalidAsset(String),
+
+    /// The mempool is full of transactions paying a higher fee.
+    MempoolFull {
+        /// The advised delay before retrying, until the next block is expected to drain the
+        /// mempool.
+        retry_after_hint: Duration,
+    },
+
+    /// The difficulty is not between 1 and 64.
+    InvalidDifficulty,
+
+    /// The block reward is negative or not a number.
+    InvalidReward,
+
+    /// The transaction fee is negative or not a number.
+    InvalidFee,
+
+    /// The milestone name is empty or surrounded by whitespace.
+    InvalidMilestone(String),
+
+    /// The block does not extend the last block of the blockchain.
+    UnknownParent,
+
+    /// The mining of the block was cancelled before its header satisfied the difficulty.
+    MiningCancelled,
+
+    /// The node cannot seal blocks, not holding the keypair of a validator.
+    NotValidator,
+
+    /// The block is not valid.
+    InvalidBlock(String),
+
+    /// The candidate chain does not carry more cumulative work than the blockchain.
+    InsufficientWork,
+
+    /// The state of the blockchain is inconsistent.
+    InvariantViolation(String),
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::InvalidEmail => write!(f, "Wallet email is empty"),
+            ChainError::DuplicateEmail(email) => {
+                write!(f, "Wallet with email {} already exists", email)
+            }
+            ChainError::AddressTaken(address) => {
+                write!(f, "Wallet with address {} already exists", address)
+            }
+            ChainError::InvalidPublicKey => write!(f, "Wallet public key is invalid"),
+            ChainError::InvalidMnemonic(reason) => write!(f, "Mnemonic is invalid: {}", reason),
+            ChainError::InvalidKeyRotation(reason) => {
+                write!(f, "Key rotation is invalid: {}", reason)
+            }
+            ChainError::InvalidMultiSig(reason) => {
+                write!(f, "Multi-signature wallet is invalid: {}", reason)
+            }
+            ChainError::UnknownSender(address) => write!(f, "Sender {} is not a wallet", address),
+            ChainError::UnknownReceiver(address) => {
+                write!(f, "Receiver {} is not a wallet", address)
+            }
+            ChainError::UnknownFeePayer(address) => {
+                write!(f, "Fee payer {} is not a wallet", address)
+            }
+            ChainError::SelfTransfer => write!(f, "Sender and receiver are the same wallet"),
+            ChainError::InvalidAmount => write!(f, "Transaction amount must be positive"),
+            ChainError::InsufficientBalance => {
+                write!(f, "Sender cannot pay the transaction amount and fee")
+            }
+            ChainError::FeeTooLow => write!(f, "Transaction fee is below the blockchain fee"),
+            ChainError::RelayFeeTooLow => {
+                write!(f, "Transaction fee is below the minimum relay fee")
+            }
+            ChainError::InvalidHash => write!(f, "Transaction hash does not match its content"),
+            ChainError::SignatureRequired => write!(f, "Transaction must be signed by the sender"),
+            ChainError::InvalidSignature => write!(f, "Transaction signature is invalid"),
+            ChainError::UnregisteredKey(address) => {
+                write!(f, "Wallet {} has no registered public key", address)
+            }
+            ChainError::InsufficientApprovals { required, actual } => write!(
+                f,
+                "Transaction is approved by {} owners, fewer than the threshold of {}",
+                actual, required
+            ),
+            ChainError::UnknownProposal(hash) => {
+                write!(f, "Transaction {} is not proposed", hash)
+            }
+            ChainError::DuplicateTransaction => write!(f, "Transaction was already submitted"),
+            ChainError::MemoTooLong { max, actual } => write!(
+                f,
+                "Transaction memo is {} bytes long, longer than {} bytes",
+                actual, max
+            ),
+            ChainError::InvalidNonce { expected, actual } => {
+                write!(
+                    f,
+                    "Transaction nonce {} is invalid, expected {}",
+                    actual, expected
+                )
+            }
+            ChainError::PolicyViolation(reason) => {
+                write!(f, "Transaction breaks the spending policy: {}", reason)
+            }
+            ChainError::TransactionRejected(reason) => {
+                write!(f, "Transaction is rejected: {}", reason)
+            }
+            ChainError::DoubleSpend(input) => {
+                write!(f, "Output {} is spent or does not exist", input)
+            }
+            ChainError::InvalidInputs(reason) => {
+                write!(f, "Transaction inputs are invalid: {}", reason)
+            }
+            ChainError::ChainPaused(reason) => {
+                write!(f, "Transaction intake is paused: {}", reason)
+            }
+            ChainError::InvalidScript(reason) => write!(f, "Invalid script: {}", reason),
+            ChainError::InvalidStake(reason) => write!(f, "Invalid stake: {}", reason),
+            ChainError::UnknownContract(address) => {
+                write!(f, "No contract is deployed at {}", address)
+            }
+            ChainError::InvalidContract(reason) => write!(f, "Invalid contract: {}", reason),
+            ChainError::ContractReverted(reason) => {
+                write!(f, "Contract call reverted: {}", reason)
+            }
+            ChainError::OutOfGas { limit } => write!(f, "Out of gas, the limit is {}", limit),
+            ChainError::InvalidGas(reason) => write!(f, "Invalid gas: {}", reason),
+            ChainError::UnknownAsset(asset_id) => write!(f, "Unknown asset {}", asset_id),
+            ChainError::InvalidAsset(reason) => write!(f, "Invalid asset: {}", reason),
+            ChainError::MempoolFull { retry_after_hint } => write!(
+                f,
+                "Mempool is full, retry in {} seconds",
+                retry_after_hint.as_secs()
+            ),
+            ChainError::InvalidDifficulty => write!(f, "Difficulty must be between 1 and 64"),
+            ChainError::InvalidReward => write!(f, "Block reward must be a non-negative number"),
+            ChainError::InvalidFee => write!(f, "Transaction fee must be a non-negative number"),
+            ChainError::InvalidMilestone(name) => write!(f, "Milestone name '{}' is invalid", name),
+            ChainError::UnknownParent => write!(f, "Block does not extend the last block"),
+            ChainError::MiningCancelled => write!(f, "Mining of the block was cancelled"),
+            ChainError::NotValidator => write!(f, "Node is not a validator of the blockchain"),
+            ChainError::InvalidBlock(reason) => write!(f, "Block is invalid: {}", reason),
+            ChainError::InsufficientWork => {
+                write!(
+                    f,
+                    "Candidate chain does not carry more work than the blockchain"
+                )
+            }
+            ChainError::InvariantViolation(reason) => {
+                write!(f, "Chain invariant violated: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for ChainError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = ChainError::DuplicateEmail("s@mail.com".to_string());
+
+        assert_eq!(
+            error.to_string(),
+            "Wallet with email s@mail.com already exists"
+        );
+        assert_eq!(
+            ChainError::UnknownSender("0x 1234".to_string()).to_string(),
+            "Sender 0x 1234 is not a wallet"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::mpsc::{self, Receiver};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, Transaction};
+
+/// A change of the blockchain sent to the subscribers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainEvent {
+    /// A transaction was added to the pending transactions.
+    TransactionAdded {
+        /// The transaction.
+        transaction: Box<Transaction>,
+    },
+
+    /// A block was mined or imported on top of the blockchain.
+    BlockMined {
+        /// The height of the block.
+        height: usize,
+
+        /// The block.
+        block: Block,
+    },
+
+    /// The reward of a block was credited to the wallet of its miner.
+    RewardPaid {
+        /// The address of the miner.
+        miner: String,
+
+        /// The amount of the reward.
+        amount: f64,
+
+        /// The height of the block.
+        height: usize,
+    },
+
+    /// The mining difficulty of the next block changed.
+    DifficultyChanged {
+        /// The difficulty before the change.
+        previous: f64,
+
+        /// The difficulty after the change.
+        current: f64,
+    },
+
+    /// The blockchain switched to a branch carrying more work, after the blocks of the branch
+    /// were sent.
+    Reorganized {
+        /// The height of the first block that differs.
+        fork_height: usize,
+
+        /// The amount of blocks removed from the blockchain.
+        reverted: usize,
+
+        /// The amount of blocks applied from the branch.
+        applied: usize,
+
+        /// The amount of transactions of the removed blocks and of the pending transactions
+        /// that are valid on the branch, and pending again.
+        requeued: usize,
+
+        /// The amount of transactions of the removed blocks and of the pending transactions
+        /// that are invalid on the branch, and dropped.
+        dropped: usize,
+    },
+}
+
+impl Chain {
+    /// Subscribe to the changes of the blockchain.
+    ///
+    /// The events are sent in the order of the changes, from the time of the subscription. The
+    /// subscription ends when the receiver is dropped.
+    ///
+    /// # Returns
+    /// The receiver of the events.
+    pub fn subscribe(&mut self) -> Receiver<ChainEvent> {
+        let (sender, receiver) = mpsc::channel();
+
+        self.subscribers.push(sender);
+
+        receiver
+    }
+
+    /// Send an event to the subscribers, dropping the ones whose receiver was dropped, and
+    /// deliver it to the webhooks.
+    ///
+    /// The event is only built if there are subscribers or webhooks.
+    ///
+    /// # Arguments
+    /// - `event`: The function building the event from the blockchain.
+    pub(crate) fn emit(&mut self, event: impl FnOnce(&Chain) -> ChainEvent) {
+        if self.subscribers.is_empty() && self.webhooks.is_empty() {
+            return;
+        }
+
+        let event = event(self);
+
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.to_owned()).is_ok());
+        self.deliver_to_webhooks(&event);
+    }
+
+    /// Set the mining difficulty of the next block and notify the subscribers if it changed.
+    ///
+    /// # Arguments
+    /// - `difficulty`: The new mining difficulty level.
+    pub(crate) fn set_difficulty(&mut self, difficulty: f64) {
+        let previous = self.difficulty;
+
+        self.difficulty = difficulty;
+
+        if previous != difficulty {
+            self.emit(|_| ChainEvent::DifficultyChanged {
+                previous,
+                current: difficulty,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_drops_closed_subscribers() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let receiver = chain.subscribe();
+
+        drop(chain.subscribe());
+        chain.set_difficulty(2.0);
+
+        assert_eq!(chain.subscribers.len(), 1);
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(ChainEvent::DifficultyChanged { previous, current })
+                if previous == 1.0 && current == 2.0
+        ));
+    }
+}
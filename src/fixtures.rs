@@ -0,0 +1,119 @@
+use std::{sync::Arc, time::Duration};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{Chain, ChainConfig, DeterministicAddressGenerator, ManualClock};
+
+/// The time of the genesis block of the fixtures, 2024-01-01 at midnight UTC, in milliseconds.
+const GENESIS_TIME: i64 = 1_704_067_200_000;
+
+/// The time between two blocks of the fixtures.
+const BLOCK_TIME: Duration = Duration::from_secs(600);
+
+/// The time between two transactions of the fixtures.
+const TRANSACTION_TIME: Duration = Duration::from_secs(1);
+
+/// The balance credited to every wallet of the fixtures.
+const INITIAL_BALANCE: f64 = 1_000.0;
+
+/// Build a populated blockchain that is identical for the same arguments, for benchmarks,
+/// examples and tests needing a realistic chain quickly.
+///
+/// The blockchain has a difficulty of 1.0, a reward of 100.0 and a fee of 0.1. Its timestamps
+/// come from a manual clock starting on 2024-01-01, and its addresses are derived from the
+/// seed. Every wallet is credited with 1000.0 before the first block, then each block includes
+/// payments between wallets picked by a random generator seeded with the seed. Payments the
+/// sender cannot afford are skipped.
+///
+/// # Arguments
+/// - `seed`: The seed of the addresses and of the random payments.
+/// - `blocks`: The amount of blocks mined after the genesis block.
+/// - `wallets`: The amount of wallets.
+/// - `tx_per_block`: The amount of payments attempted before every block.
+///
+/// # Returns
+/// The blockchain, with its clock left at the time of the last block.
+pub fn build_chain(seed: u64, blocks: usize, wallets: usize, tx_per_block: usize) -> Chain {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let salt = format!("fixtures-{}", seed);
+    let clock = Arc::new(ManualClock::new(GENESIS_TIME));
+
+    let mut chain = Chain::empty(1.0, 100.0, 0.1, ChainConfig::default());
+    chain.address = Chain::derive_address(&salt, &chain.config.chain_id, "miner", 42);
+    chain.set_address_generator(DeterministicAddressGenerator::new(salt));
+    chain.set_clock(clock.clone());
+
+    // The difficulty is valid, so the genesis block is always mined
+    let _ = chain.generate_new_block();
+
+    // Distinct emails derive distinct addresses, so every wallet is created
+    let addresses: Vec<String> = (0..wallets)
+        .filter_map(|index| {
+            chain
+                .create_wallet(format!("wallet{}@mail.com", index))
+                .ok()
+        })
+        .collect();
+
+    for address in &addresses {
+        chain.credit_wallet(address, INITIAL_BALANCE);
+    }
+
+    for _ in 0..blocks {
+        if addresses.len() > 1 {
+            for _ in 0..tx_per_block {
+                let from = rng.gen_range(0..addresses.len());
+                let to = (from + rng.gen_range(1..addresses.len())) % addresses.len();
+                let amount = rng.gen_range(1..=1_000) as f64 / 10.0;
+
+                clock.advance(TRANSACTION_TIME);
+
+                let _ = chain.add_unsigned_transaction(
+                    addresses[from].to_owned(),
+                    addresses[to].to_owned(),
+                    amount,
+                );
+            }
+        }
+
+        clock.advance(BLOCK_TIME);
+
+        let _ = chain.generate_new_block();
+    }
+
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_chain() {
+        let chain = build_chain(42, 5, 4, 10);
+
+        assert_eq!(chain.get_all_blocks().len(), 6);
+        assert_eq!(chain.get_wallets_count(), 4);
+        assert!(chain.get_pending_transactions().is_empty());
+        assert_eq!(chain.validate_range(0, 5), Ok(6));
+        assert!(chain.get_all_blocks()[2].transactions.len() > 1);
+
+        // The same seed builds the same blockchain, another seed a different one
+        assert_eq!(
+            chain.get_last_hash(),
+            build_chain(42, 5, 4, 10).get_last_hash()
+        );
+        assert_ne!(
+            chain.get_last_hash(),
+            build_chain(7, 5, 4, 10).get_last_hash()
+        );
+    }
+
+    #[test]
+    fn test_build_chain_without_payments() {
+        let chain = build_chain(42, 2, 1, 10);
+
+        assert_eq!(chain.get_all_blocks().len(), 3);
+        assert_eq!(chain.get_all_blocks()[2].transactions.len(), 1);
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    mem, slice,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, Block, Chain, ChainError, ChainEvent, StakingOperation, Transaction, Wallet};
+
+/// Changes applied by a block to the wallets and the supply, reverted when the block is removed.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockUndo {
+    /// The change of the balance of every wallet involved in the block, by wallet address.
+    pub balances: BTreeMap<Address, f64>,
+
+    /// The amount of funds issued by the block.
+    pub issued: f64,
+
+    /// The amount of fees paid by the transactions of the block.
+    pub fees: f64,
+
+    /// The change of the asset balances of every wallet involved in the block, by wallet address
+    /// and asset identifier.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assets: BTreeMap<Address, BTreeMap<String, f64>>,
+}
+
+impl BlockUndo {
+    /// Record the changes applied by transactions to the wallets and the supply.
+    ///
+    /// # Arguments
+    /// - `transactions`: The transactions, starting with the coinbase for a block.
+    /// - `wallets`: The wallets of the blockchain, the other addresses being left unchanged.
+    ///
+    /// # Returns
+    /// The changes of the transactions.
+    pub(crate) fn new(transactions: &[Transaction], wallets: &HashMap<String, Wallet>) -> Self {
+        let mut undo = BlockUndo::default();
+
+        for transaction in transactions {
+            let mut change = |address: &str, amount: f64| {
+                if !wallets.contains_key(address) {
+                    return false;
+                }
+
+                *undo.balances.entry(address.to_string()).or_default() += amount;
+
+                true
+            };
+
+            let amount = transaction.native_amount();
+
+            change(&transaction.from, -amount);
+
+            let fee = transaction.charged_fee();
+            let paid = change(transaction.payer(), -fee);
+            let received = change(&transaction.to, amount);
+
+            // Staking moves funds between the wallet and the staking module, claims issuing them
+            if let Some(operation) = &transaction.staking {
+                let staked = change(&transaction.from, operation.balance_change());
+
+                if staked && matches!(operation, StakingOperation::Claim { .. }) {
+                    undo.issued += operation.amount();
+                }
+            }
+
+            if paid {
+                undo.fees += fee;
+            }
+
+            // Funds issued by the root address
+            if received && transaction.from == "Root" {
+                undo.issued += amount;
+            }
+
+            // Assets move between the wallets, their supply being credited to the issuer once
+            if let Some(asset_id) = &transaction.asset_id {
+                let sent = match transaction.asset_name {
+                    Some(_) => 0.0,
+                    None => -transaction.amount,
+                };
+
+                for (address, amount) in [
+                    (&transaction.from, sent),
+                    (&transaction.to, transaction.amount),
+                ] {
+                    if wallets.contains_key(address) {
+                        *undo
+                            .assets
+                            .entry(address.to_owned())
+                            .or_default()
+                            .entry(asset_id.to_owned())
+                            .or_default() += amount;
+                    }
+                }
+            }
+        }
+
+        undo
+    }
+}
+
+impl Chain {
+    /// Get the cumulative work of the blockchain.
+    ///
+    /// # Returns
+    /// The expected amount of hashes computed to mine every block.
+    pub fn cumulative_work(&self) -> f64 {
+        self.chain.iter().map(Block::work).sum()
+    }
+
+    /// Replace the blockchain with a candidate chain carrying more work, following the longest
+    /// valid chain rule.
+    ///
+    /// The candidate must start from the same genesis block. The blocks above the last common
+    /// block are undone, then the blocks of the candidate are validated and applied like
+    /// imported blocks. The transactions of the replaced blocks and the pending transactions
+    /// that are not part of the candidate are submitted again, and dropped if they are no
+    /// longer valid. The subscribers are then sent a `Reorganized` event counting them. The
+    /// blockchain is left unchanged if the candidate is rejected.
+    ///
+    /// # Arguments
+    /// - `candidate`: The blocks of the candidate chain, starting with the genesis block.
+    ///
+    /// # Returns
+    /// The amount of blocks applied from the candidate, `InsufficientWork` if the candidate does
+    /// not carry more work, or an error describing why one of its blocks is invalid.
+    pub fn replace_chain(&mut self, candidate: Vec<Block>) -> Result<usize, ChainError> {
+        // The cached hashes of the candidate are not trusted
+        let genesis = |blocks: &[Block]| blocks.first().map(Block::compute_hash);
+
+        if genesis(&candidate) != genesis(&self.chain) {
+            return Err(ChainError::InvalidBlock(
+                "the genesis block differs".to_string(),
+            ));
+        }
+
+        // Height of the first block that differs
+        let fork = self
+            .chain
+            .iter()
+            .zip(&candidate)
+            .take_while(|(block, other)| block.hash() == other.compute_hash())
+            .count();
+
+        let work = |blocks: &[Block]| blocks.iter().map(Block::work).sum::<f64>();
+
+        if work(&candidate[fork..]) <= work(&self.chain[fork..]) {
+            return Err(ChainError::InsufficientWork);
+        }
+
+        if self.chain[fork..].iter().any(Block::is_pruned) {
+            return Err(ChainError::InvalidBlock(
+                "the replaced blocks were pruned".to_string(),
+            ));
+        }
+
+        // Apply the candidate to a copy, so the payment watches and the plugins only see the
+        // final blockchain
+        let watches = mem::take(&mut self.watches);
+        let plugins = mem::take(&mut self.plugins);
+        let storage = self.storage.take();
+        let mut replaced = self.clone();
+
+        self.watches = watches;
+        self.plugins = plugins;
+        self.storage = storage;
+
+        let orphaned = replaced.rewind(fork);
+
+        for block in candidate.into_iter().skip(fork) {
+            replaced.import_block(block)?;
+        }
+
+        let included: HashSet<String> = replaced.chain[fork..]
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .map(|transaction| transaction.hash.to_owned())
+            .collect();
+
+        // Submit the orphaned transactions again, against the state of the branch
+        let (requeued, dropped) = replaced.requeue(orphaned, &included);
+
+        replaced.watches = mem::take(&mut self.watches);
+        replaced.plugins = mem::take(&mut self.plugins);
+        replaced.storage = self.storage.take();
+
+        let previous = mem::replace(self, replaced);
+
+        // Replace the reverted blocks in the backend, a failed write is retried with the next block
+        let _ = self.store_blocks();
+
+        self.notify_watches();
+        self.notify_reorg(fork, &previous.chain[fork..]);
+        self.assert_invariants();
+
+        let applied = self.chain.len() - fork;
+
+        self.emit(|_| ChainEvent::Reorganized {
+            fork_height: fork,
+            reverted: previous.chain.len() - fork,
+            applied,
+            requeued,
+            dropped,
+        });
+
+        Ok(applied)
+    }
+
+    /// Remove the blocks above a height, e.g. to recover from blocks applied by mistake.
+    ///
+    /// The removed blocks are undone like the blocks replaced by a reorganization: the balance
+    /// changes recorded for each block are reverted, and their transactions are submitted
+    /// again, dropped if they are no longer valid. The subscribers are then sent a
+    /// `Reorganized` event without applied blocks.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the last kept block.
+    ///
+    /// # Returns
+    /// The amount of removed blocks, or `InvalidBlock` if the height is above the last block or
+    /// a removed block was pruned.
+    pub fn rollback_to(&mut self, height: usize) -> Result<usize, ChainError> {
+        let fork = height + 1;
+
+        if fork > self.chain.len() {
+            return Err(ChainError::InvalidBlock(
+                "the height is above the last block".to_string(),
+            ));
+        }
+
+        if self.chain[fork..].iter().any(Block::is_pruned) {
+            return Err(ChainError::InvalidBlock(
+                "the removed blocks were pruned".to_string(),
+            ));
+        }
+
+        if fork == self.chain.len() {
+            return Ok(0);
+        }
+
+        let reverted = self.chain[fork..].to_vec();
+        let orphaned = self.rewind(fork);
+        let (requeued, dropped) = self.requeue(orphaned, &HashSet::new());
+
+        // Remove the reverted blocks from the backend, a failed write is retried with the next
+        // block
+        let _ = self.store_blocks();
+
+        self.notify_watches();
+        self.notify_reorg(fork, &reverted);
+        self.assert_invariants();
+
+        self.emit(|_| ChainEvent::Reorganized {
+            fork_height: fork,
+            reverted: reverted.len(),
+            applied: 0,
+            requeued,
+            dropped,
+        });
+
+        Ok(reverted.len())
+    }
+
+    /// Get the changes applied by a block to the wallets and the supply.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The changes reverted if the block is removed, or `None` if the block is unknown, pruned,
+    /// or was appended before the changes were recorded.
+    pub fn get_block_undo(&self, height: usize) -> Option<&BlockUndo> {
+        self.undo_log.get(&height)
+    }
+
+    /// Undo the pending transactions and the blocks from a height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the first removed block.
+    ///
+    /// # Returns
+    /// The transactions of the removed blocks without their coinbase, followed by the pending
+    /// transactions, in submission order.
+    fn rewind(&mut self, height: usize) -> Vec<Transaction> {
+        self.unindex_blocks(height);
+
+        let blocks: Vec<Block> = self.chain.drain(height..).collect();
+        let mut pending = mem::take(&mut self.current_transactions);
+
+        for transaction in pending.iter().rev() {
+            self.undo_transaction(transaction);
+        }
+
+        // The blocks are undone from the last one, taking the rewards back from the miners
+        for (offset, block) in blocks.iter().enumerate().rev() {
+            for transaction in block.transactions.iter().rev() {
+                self.revert_transaction(transaction);
+            }
+
+            // The blocks appended before their changes were recorded are undone from their
+            // transactions
+            let undo = self
+                .undo_log
+                .remove(&(height + offset))
+                .unwrap_or_else(|| BlockUndo::new(&block.transactions, &self.wallets));
+
+            self.revert_changes(&undo);
+        }
+
+        let mut orphaned: Vec<Transaction> = blocks
+            .into_iter()
+            .flat_map(|block| block.transactions.into_iter().skip(1))
+            .collect();
+
+        orphaned.append(&mut pending);
+
+        self.undo_log.retain(|undone, _| *undone < height);
+        self.snapshots.retain(|snapshot| snapshot.height < height);
+        self.stored_blocks = self.stored_blocks.map(|stored| stored.min(height));
+        self.apply_difficulty_algorithm();
+
+        orphaned
+    }
+
+    /// Submit the transactions of removed blocks again, against the current state.
+    ///
+    /// # Arguments
+    /// - `orphaned`: The transactions, in submission order.
+    /// - `included`: The hashes of the transactions already included in the applied blocks.
+    ///
+    /// # Returns
+    /// The amounts of submitted and dropped transactions.
+    fn requeue(
+        &mut self,
+        orphaned: Vec<Transaction>,
+        included: &HashSet<String>,
+    ) -> (usize, usize) {
+        let (mut requeued, mut dropped) = (0, 0);
+
+        for transaction in orphaned {
+            if included.contains(&transaction.hash) {
+                continue;
+            }
+
+            match self.accept_transaction(transaction, false, false) {
+                Ok(()) => requeued += 1,
+                Err(_) => dropped += 1,
+            }
+        }
+
+        (requeued, dropped)
+    }
+
+    /// Undo the effects of a pending transaction on the wallets and the supply.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    fn undo_transaction(&mut self, transaction: &Transaction) {
+        let undo = BlockUndo::new(slice::from_ref(transaction), &self.wallets);
+
+        self.revert_transaction(transaction);
+        self.revert_changes(&undo);
+    }
+
+    /// Undo the effects of a transaction besides the balances: the ledger, the key rotation, the
+    /// issued asset, the staking operation, the nonce of the sender, and the histories of the
+    /// wallets.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    fn revert_transaction(&mut self, transaction: &Transaction) {
+        self.ledger_mut().undo(transaction);
+
+        if let Some(wallet) = self.wallets.get_mut(&transaction.from) {
+            if let Some(rotation) = &transaction.key_rotation {
+                wallet.public_key = rotation.previous.to_owned();
+            }
+
+            wallet.nonce = wallet.nonce.saturating_sub(1);
+        }
+
+        if let (Some(asset_id), Some(_)) = (&transaction.asset_id, &transaction.asset_name) {
+            self.assets.remove(asset_id);
+        }
+
+        if let Some(operation) = &transaction.staking {
+            self.revert_staking(&transaction.from, operation);
+        }
+
+        for address in [&transaction.from, transaction.payer(), &transaction.to] {
+            if let Some(wallet) = self.wallets.get_mut(address) {
+                wallet.transactions.retain(|hash| *hash != transaction.hash);
+            }
+        }
+    }
+
+    /// Revert the changes applied to the wallets and the supply.
+    ///
+    /// # Arguments
+    /// - `undo`: The changes.
+    fn revert_changes(&mut self, undo: &BlockUndo) {
+        for (address, change) in &undo.balances {
+            if let Some(wallet) = self.wallets.get_mut(address) {
+                wallet.balance -= change;
+            }
+        }
+
+        for (address, changes) in &undo.assets {
+            if let Some(wallet) = self.wallets.get_mut(address) {
+                for (asset_id, change) in changes {
+                    // The balances of the assets whose issuance is undone are removed
+                    if !self.assets.contains_key(asset_id) {
+                        wallet.assets.remove(asset_id);
+                        continue;
+                    }
+
+                    *wallet.assets.entry(asset_id.to_owned()).or_default() -= change;
+                }
+            }
+        }
+
+        self.issued_supply -= undo.issued;
+        self.collected_fees -= undo.fees;
+    }
+}
@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, ChainError, Keypair, Script, Transaction};
+
+/// Gas consumed by every transaction, covering the checks of its hash, its nonce and the
+/// balances of its wallets.
+pub const TRANSACTION_GAS: u64 = 21_000;
+
+/// Gas consumed per verified signature: the signatures of the sender and the fee payer, the
+/// approvals of the multi-signature owners and the signatures of the witness.
+pub const SIGNATURE_GAS: u64 = 3_000;
+
+/// Gas consumed per input looked up in the unspent outputs.
+pub const INPUT_GAS: u64 = 2_000;
+
+/// Gas consumed per condition of the script locking the paid output.
+pub const SCRIPT_GAS: u64 = 500;
+
+/// Gas consumed per byte of data carried by the transaction, its memo and its preimage.
+pub const DATA_BYTE_GAS: u64 = 16;
+
+/// Gas a transaction may consume and the price it pays for each unit.
+///
+/// The payer of a metered transaction must hold `limit * price` on top of the amount, is charged
+/// for the gas the transaction consumes, and keeps the rest. The gas fees are paid to the miner
+/// including the transaction instead of being burned.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Gas {
+    /// The maximum amount of gas consumed by the transaction.
+    pub limit: u64,
+
+    /// The fee paid per unit of gas.
+    pub price: f64,
+}
+
+impl Gas {
+    /// Get the fee paid if the transaction consumes all its gas.
+    ///
+    /// # Returns
+    /// The gas limit times the gas price.
+    pub fn max_fee(&self) -> f64 {
+        self.limit as f64 * self.price
+    }
+}
+
+impl Transaction {
+    /// Compute the gas consumed by validating and applying the transaction.
+    ///
+    /// The signatures carried by the transaction are counted, so the gas of a transaction is
+    /// estimated once it is signed, or with a margin for its signatures.
+    ///
+    /// # Returns
+    /// The gas consumed by the transaction.
+    pub fn gas_used(&self) -> u64 {
+        let witness = self.witness.as_ref();
+        let signatures = usize::from(self.signature.is_some())
+            + usize::from(self.fee_payer_signature.is_some())
+            + self.approvals.len()
+            + witness.map_or(0, |witness| witness.signatures.len());
+        let data = self.memo.as_ref().map_or(0, |memo| memo.size())
+            + witness
+                .and_then(|witness| witness.preimage.as_ref())
+                .map_or(0, String::len);
+
+        TRANSACTION_GAS
+            + SIGNATURE_GAS * signatures as u64
+            + INPUT_GAS * self.inputs.len() as u64
+            + SCRIPT_GAS * self.lock.as_ref().map_or(0, conditions)
+            + DATA_BYTE_GAS * data as u64
+    }
+
+    /// Get the fee charged to the fee payer once the transaction is applied.
+    ///
+    /// # Returns
+    /// The gas consumed times the gas price for a metered transaction, the fee otherwise.
+    pub fn charged_fee(&self) -> f64 {
+        match &self.gas {
+            Some(gas) => self.gas_used().min(gas.limit) as f64 * gas.price,
+            None => self.fee,
+        }
+    }
+
+    /// Validate the gas of a metered transaction.
+    ///
+    /// # Returns
+    /// `InvalidGas` if the fee is not the maximum fee of the gas or the price is not a
+    /// non-negative number, `OutOfGas` if the transaction consumes more than its limit.
+    pub(crate) fn check_gas(&self) -> Result<(), ChainError> {
+        let Some(gas) = &self.gas else {
+            return Ok(());
+        };
+
+        if !gas.price.is_finite() || gas.price < 0.0 {
+            return Err(ChainError::InvalidGas(
+                "the gas price must be a non-negative number".to_string(),
+            ));
+        }
+
+        if self.fee != gas.max_fee() {
+            return Err(ChainError::InvalidGas(
+                "the fee must be the gas limit times the gas price".to_string(),
+            ));
+        }
+
+        match self.gas_used() <= gas.limit {
+            true => Ok(()),
+            false => Err(ChainError::OutOfGas { limit: gas.limit }),
+        }
+    }
+}
+
+impl Block {
+    /// Sum the gas fees of the metered transactions, paid to the miner with the coinbase.
+    ///
+    /// # Returns
+    /// The total fee charged for the gas consumed by the transactions after the coinbase.
+    pub fn gas_fees(&self) -> f64 {
+        self.transactions
+            .iter()
+            .skip(1)
+            .filter(|transaction| transaction.gas.is_some())
+            .map(Transaction::charged_fee)
+            .sum()
+    }
+}
+
+impl Chain {
+    /// Add a new metered transaction, paying for the gas it consumes instead of a share of its
+    /// amount.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `gas`: The gas limit, at least `TRANSACTION_GAS`, and the gas price.
+    /// - `keypair`: The keypair owning the sender wallet.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, `UnregisteredKey` if the
+    /// sender wallet has no public key, or the reason it is invalid.
+    pub fn add_transaction_with_gas(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        gas: Gas,
+        keypair: &Keypair,
+    ) -> Result<String, ChainError> {
+        let transaction = self
+            .metered_transaction(from, to, amount, Some(gas), true)?
+            .build()
+            .sign(keypair);
+        let hash = transaction.hash.to_owned();
+
+        self.submit_transaction(transaction)?;
+
+        Ok(hash)
+    }
+}
+
+/// Count the conditions of a script, the combinations included.
+///
+/// # Arguments
+/// - `script`: The script.
+///
+/// # Returns
+/// The amount of nodes of the script.
+fn conditions(script: &Script) -> u64 {
+    match script {
+        Script::All(scripts) | Script::Any(scripts) => {
+            1 + scripts.iter().map(conditions).sum::<u64>()
+        }
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Memo;
+
+    #[test]
+    fn test_gas_used() {
+        let build = |limit: u64| {
+            Transaction::builder()
+                .from("0x 1234")
+                .to("0x 5678")
+                .amount(1.0)
+                .gas(Gas {
+                    limit,
+                    price: 0.001,
+                })
+                .memo(Memo::plain("invoice"))
+                .lock(Script::Any(vec![
+                    Script::hash_lock("secret"),
+                    Script::TimeLock { unlock_at: 100 },
+                ]))
+                .build()
+        };
+        let transaction = build(30_000);
+
+        assert_eq!(
+            transaction.gas_used(),
+            TRANSACTION_GAS + 3 * SCRIPT_GAS + 7 * DATA_BYTE_GAS
+        );
+        assert_eq!(transaction.fee, 30.0);
+        assert_eq!(
+            transaction.charged_fee(),
+            transaction.gas_used() as f64 * 0.001
+        );
+        assert_eq!(transaction.check_gas(), Ok(()));
+        assert_eq!(
+            build(TRANSACTION_GAS).check_gas(),
+            Err(ChainError::OutOfGas {
+                limit: TRANSACTION_GAS
+            })
+        );
+
+        // The fee covers the whole gas limit
+        let mut underpaid = transaction;
+        underpaid.fee = 1.0;
+
+        assert!(matches!(
+            underpaid.check_gas(),
+            Err(ChainError::InvalidGas(_))
+        ));
+    }
+}
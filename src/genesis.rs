@@ -0,0 +1,220 @@
+use std::{collections::HashSet, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{keys, Chain, ChainConfig, ChainError, ManualClock, Transaction, Wallet};
+
+/// Length of the genesis address receiving the reward of the genesis block.
+const GENESIS_ADDRESS_LENGTH: usize = 42;
+
+/// Funds credited to a wallet in the genesis block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenesisAllocation {
+    /// The wallet address.
+    pub address: String,
+
+    /// The unique user email.
+    pub email: String,
+
+    /// The amount credited to the wallet.
+    pub amount: f64,
+
+    /// The hex encoded ed25519 public key owning the wallet, if its transactions must be signed.
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
+/// Parameters of the genesis block, shared by every node of a network.
+///
+/// Nodes building their blockchain from the same parameters, reward, fee and consensus rules
+/// construct the identical genesis block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    /// Identifier of the blockchain, replacing the one of the blockchain settings.
+    pub chain_id: String,
+
+    /// The time of the genesis block, in seconds since the Unix epoch.
+    pub timestamp: i64,
+
+    /// The initial mining difficulty level of the network.
+    pub difficulty: f64,
+
+    /// The funds credited to wallets in the genesis block.
+    #[serde(default)]
+    pub allocations: Vec<GenesisAllocation>,
+}
+
+impl GenesisConfig {
+    /// Validate the allocations.
+    ///
+    /// # Returns
+    /// An error describing the first invalid allocation, if any.
+    fn validate(&self) -> Result<(), ChainError> {
+        let mut addresses = HashSet::new();
+        let mut emails = HashSet::new();
+
+        for allocation in &self.allocations {
+            if allocation.email.trim().is_empty() {
+                return Err(ChainError::InvalidEmail);
+            }
+
+            if !emails.insert(&allocation.email) {
+                return Err(ChainError::DuplicateEmail(allocation.email.to_owned()));
+            }
+
+            if !addresses.insert(&allocation.address) {
+                return Err(ChainError::InvalidBlock(format!(
+                    "the address {} is allocated twice",
+                    allocation.address
+                )));
+            }
+
+            if allocation.amount.is_nan() || allocation.amount <= 0.0 {
+                return Err(ChainError::InvalidAmount);
+            }
+
+            if allocation
+                .public_key
+                .as_deref()
+                .is_some_and(|public_key| !keys::is_valid_public_key(public_key))
+            {
+                return Err(ChainError::InvalidPublicKey);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Chain {
+    /// Initialize a new blockchain from the parameters of its genesis block.
+    ///
+    /// Unlike `Chain::with_config`, the genesis block does not depend on the local clock or
+    /// a random address: it is mined at the genesis timestamp, rewards an address derived from
+    /// the chain identifier, and credits the allocated wallets, so every node constructs the
+    /// identical block.
+    ///
+    /// # Arguments
+    /// - `genesis`: The parameters of the genesis block.
+    /// - `reward`: The initial block reward for miners.
+    /// - `fee`: The transaction fee.
+    /// - `config`: The optional blockchain settings.
+    ///
+    /// # Returns
+    /// A new `Chain` instance with the genesis block, or an error describing why the parameters
+    /// are invalid.
+    pub fn from_genesis(
+        genesis: GenesisConfig,
+        reward: f64,
+        fee: f64,
+        config: ChainConfig,
+    ) -> Result<Self, ChainError> {
+        if !Chain::is_valid_difficulty(genesis.difficulty) {
+            return Err(ChainError::InvalidDifficulty);
+        }
+
+        genesis.validate()?;
+
+        let config = ChainConfig {
+            chain_id: genesis.chain_id.to_owned(),
+            ..config
+        };
+        let clock = Arc::new(ManualClock::new(genesis.timestamp * 1000));
+        let mut chain = Chain::empty(genesis.difficulty, reward, fee, config);
+
+        chain.address =
+            Chain::derive_address("genesis", &genesis.chain_id, "", GENESIS_ADDRESS_LENGTH);
+        chain.clock = Some(clock.clone());
+
+        // Credit the allocations with pending transactions mined into the genesis block
+        for allocation in genesis.allocations {
+            let transaction = Transaction::builder()
+                .from("Root")
+                .to(allocation.address.to_owned())
+                .amount(allocation.amount)
+                .fee(0.0)
+                .precision(chain.config.timestamps)
+                .clock(clock.clone())
+                .build();
+
+            let mut wallet = Wallet::new(
+                chain.stored_email(&allocation.email).unwrap_or_default(),
+                allocation.address.to_owned(),
+                allocation.amount,
+            );
+            wallet.public_key = allocation.public_key;
+            wallet.transactions.push(transaction.hash.to_owned());
+
+            chain.wallets.insert(allocation.address, wallet);
+            chain.ledger_mut().apply(&transaction);
+            chain.current_transactions.push(transaction);
+            chain.issued_supply += allocation.amount;
+        }
+
+        chain.mine_block(chain.address.to_owned())?;
+        chain.clock = None;
+
+        // Allocations left out of the genesis block would be mined at a local time
+        if !chain.current_transactions.is_empty() {
+            return Err(ChainError::InvalidBlock(
+                "the allocations exceed the maximum block weight".to_string(),
+            ));
+        }
+
+        Ok(chain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_allocations() {
+        let allocation = GenesisAllocation {
+            address: "a".to_string(),
+            email: "a@mail.com".to_string(),
+            amount: 10.0,
+            public_key: None,
+        };
+        let genesis = |allocations: Vec<GenesisAllocation>| GenesisConfig {
+            chain_id: "test".to_string(),
+            timestamp: 0,
+            difficulty: 1.0,
+            allocations,
+        };
+
+        assert_eq!(genesis(vec![allocation.clone()]).validate(), Ok(()));
+        assert_eq!(
+            genesis(vec![allocation.clone(), allocation.clone()]).validate(),
+            Err(ChainError::DuplicateEmail("a@mail.com".to_string()))
+        );
+        assert!(matches!(
+            genesis(vec![
+                allocation.clone(),
+                GenesisAllocation {
+                    email: "b@mail.com".to_string(),
+                    ..allocation.clone()
+                }
+            ])
+            .validate(),
+            Err(ChainError::InvalidBlock(_))
+        ));
+        assert_eq!(
+            genesis(vec![GenesisAllocation {
+                amount: 0.0,
+                ..allocation.clone()
+            }])
+            .validate(),
+            Err(ChainError::InvalidAmount)
+        );
+        assert_eq!(
+            genesis(vec![GenesisAllocation {
+                public_key: Some("key".to_string()),
+                ..allocation
+            }])
+            .validate(),
+            Err(ChainError::InvalidPublicKey)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+/// Default amount of hashes remembered by an inventory cache.
+pub const DEFAULT_INVENTORY_CAPACITY: usize = 10_000;
+
+/// The most recently seen transaction or block hashes, forgetting the oldest ones first.
+#[derive(Clone, Debug)]
+pub struct InventoryCache {
+    /// Maximum amount of remembered hashes.
+    capacity: usize,
+
+    /// The remembered hashes, from the oldest.
+    order: VecDeque<String>,
+
+    /// The remembered hashes, for lookups.
+    hashes: HashSet<String>,
+}
+
+impl Default for InventoryCache {
+    fn default() -> Self {
+        InventoryCache::new(DEFAULT_INVENTORY_CAPACITY)
+    }
+}
+
+impl InventoryCache {
+    /// Create a new empty cache.
+    ///
+    /// # Arguments
+    /// - `capacity`: The maximum amount of remembered hashes.
+    ///
+    /// # Returns
+    /// A new inventory cache.
+    pub fn new(capacity: usize) -> Self {
+        InventoryCache {
+            capacity,
+            order: VecDeque::new(),
+            hashes: HashSet::new(),
+        }
+    }
+
+    /// Check whether a hash was seen recently.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash.
+    ///
+    /// # Returns
+    /// `true` if the hash is remembered.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    /// Remember a hash, forgetting the oldest one if the cache is full.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash.
+    ///
+    /// # Returns
+    /// `true` if the hash was not remembered yet.
+    pub fn insert(&mut self, hash: &str) -> bool {
+        if self.capacity == 0 || self.hashes.contains(hash) {
+            return false;
+        }
+
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+
+        self.order.push_back(hash.to_owned());
+        self.hashes.insert(hash.to_owned())
+    }
+
+    /// Get the amount of remembered hashes.
+    ///
+    /// # Returns
+    /// The amount of hashes.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Check whether the cache is empty.
+    ///
+    /// # Returns
+    /// `true` if no hash is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Counters of the duplicates avoided by the gossip layer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GossipStats {
+    /// Transactions or blocks received again from a peer and dropped without being processed.
+    pub duplicates_received: usize,
+
+    /// Transactions not requested from a peer because they were already processed.
+    pub requests_suppressed: usize,
+
+    /// Transactions or blocks not sent to a peer because it already has them or was already sent them.
+    pub sends_suppressed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inventory_cache() {
+        let mut cache = InventoryCache::new(2);
+
+        assert!(cache.insert("a"));
+        assert!(!cache.insert("a"));
+        assert!(cache.insert("b"));
+        assert!(cache.insert("c"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains("a"));
+        assert!(cache.contains("b"));
+        assert!(cache.contains("c"));
+    }
+
+    #[test]
+    fn test_inventory_cache_disabled() {
+        let mut cache = InventoryCache::new(0);
+
+        assert!(!cache.insert("a"));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use crate::{Chain, StateSnapshot, Transaction};
+
+impl Chain {
+    /// Get the wallet balances after the block at a given height.
+    ///
+    /// The balances are replayed from the latest state snapshot at or below the height, or
+    /// rewound from the current state by undoing the pending transactions and the later blocks.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The balances of every wallet, or `None` if the height is unknown or the block bodies
+    /// needed to compute the state were pruned.
+    pub fn state_at(&self, height: usize) -> Option<StateSnapshot> {
+        let block = self.chain.get(height)?;
+        let mut balances = self.replay_to(height).or_else(|| self.rewind_to(height))?;
+
+        // Wallets without any transaction at that height
+        for address in self.wallets.keys() {
+            balances.entry(address.to_owned()).or_insert(0.0);
+        }
+
+        Some(StateSnapshot {
+            height,
+            block_hash: block.hash(),
+            balances,
+        })
+    }
+
+    /// Get the balance of a wallet after the block at a given height.
+    ///
+    /// # Arguments
+    /// - `address`: The unique wallet address.
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The balance of the wallet, or `None` if the wallet or the height is unknown, or the block
+    /// bodies needed to compute the balance were pruned.
+    pub fn balance_at(&self, address: &str, height: usize) -> Option<f64> {
+        if !self.wallets.contains_key(address) {
+            return None;
+        }
+
+        self.state_at(height)?.balances.get(address).copied()
+    }
+
+    /// Replay the blocks from the latest state snapshot at or below a height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The balances after the block, or `None` if a replayed block was pruned.
+    fn replay_to(&self, height: usize) -> Option<HashMap<String, f64>> {
+        let (mut balances, start) = match self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.height <= height)
+        {
+            Some(snapshot) => (snapshot.balances.clone(), snapshot.height + 1),
+            None => (HashMap::new(), 0),
+        };
+
+        for block in &self.chain[start..=height] {
+            if block.is_pruned() {
+                return None;
+            }
+
+            for transaction in &block.transactions {
+                self.apply_to_balances(&mut balances, transaction, 1.0);
+            }
+        }
+
+        Some(balances)
+    }
+
+    /// Undo the pending transactions and the blocks above a height from the current state.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The balances after the block, or `None` if an undone block was pruned.
+    pub(crate) fn rewind_to(&self, height: usize) -> Option<HashMap<String, f64>> {
+        let mut balances: HashMap<String, f64> = self
+            .wallets
+            .iter()
+            .map(|(address, wallet)| (address.to_owned(), wallet.balance))
+            .collect();
+
+        for transaction in self.current_transactions.iter().rev() {
+            self.apply_to_balances(&mut balances, transaction, -1.0);
+        }
+
+        for block in self.chain[height + 1..].iter().rev() {
+            if block.is_pruned() {
+                return None;
+            }
+
+            for transaction in block.transactions.iter().rev() {
+                self.apply_to_balances(&mut balances, transaction, -1.0);
+            }
+        }
+
+        Some(balances)
+    }
+
+    /// Apply or undo the effects of a transaction on wallet balances.
+    ///
+    /// # Arguments
+    /// - `balances`: The balances to update.
+    /// - `transaction`: The transaction.
+    /// - `sign`: `1.0` to apply the transaction, `-1.0` to undo it.
+    fn apply_to_balances(
+        &self,
+        balances: &mut HashMap<String, f64>,
+        transaction: &Transaction,
+        sign: f64,
+    ) {
+        // Funds issued by the root address and block rewards are not taken from a wallet
+        if self.wallets.contains_key(&transaction.from) {
+            *balances.entry(transaction.from.to_owned()).or_insert(0.0) -=
+                sign * transaction.native_amount();
+        }
+
+        if self.wallets.contains_key(transaction.payer()) {
+            *balances
+                .entry(transaction.payer().to_owned())
+                .or_insert(0.0) -= sign * transaction.charged_fee();
+        }
+
+        if self.wallets.contains_key(&transaction.to) {
+            *balances.entry(transaction.to.to_owned()).or_insert(0.0) +=
+                sign * transaction.native_amount();
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::{collections::HashMap, mem};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, Transaction};
+
+/// Height of the block including every indexed mined transaction, by transaction hash.
+///
+/// The hashes are stored as strings and the heights as `usize` by default. With the
+/// `compact-index` feature, the hex encoded SHA-256 hashes are stored as 32 bytes and the heights
+/// as `u32`, saving about two thirds of the memory of each entry at the cost of decoding the
+/// hash on every lookup. Other hashes and heights are kept in their original form.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TransactionIndex {
+    /// The entries of the hex encoded SHA-256 hashes.
+    #[cfg(feature = "compact-index")]
+    compact: HashMap<[u8; 32], u32>,
+
+    /// The entries, or with the `compact-index` feature the entries not fitting the compact form.
+    wide: HashMap<String, usize>,
+}
+
+impl TransactionIndex {
+    /// Get the height of the block including a transaction.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The height of the block, or `None` if the transaction is not indexed.
+    pub(crate) fn get(&self, hash: &str) -> Option<usize> {
+        #[cfg(feature = "compact-index")]
+        if let Some(height) = compact_key(hash).and_then(|key| self.compact.get(&key)) {
+            return Some(*height as usize);
+        }
+
+        self.wide.get(hash).copied()
+    }
+
+    /// Index a transaction.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    /// - `height`: The height of the block including the transaction.
+    pub(crate) fn insert(&mut self, hash: &str, height: usize) {
+        #[cfg(feature = "compact-index")]
+        if let (Some(key), Ok(height)) = (compact_key(hash), u32::try_from(height)) {
+            self.compact.insert(key, height);
+            return;
+        }
+
+        self.wide.insert(hash.to_owned(), height);
+    }
+
+    /// Remove a transaction from the index.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    pub(crate) fn remove(&mut self, hash: &str) {
+        #[cfg(feature = "compact-index")]
+        if let Some(key) = compact_key(hash) {
+            self.compact.remove(&key);
+        }
+
+        self.wide.remove(hash);
+    }
+
+    /// Remove the transactions of the blocks from a height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the first removed block.
+    pub(crate) fn truncate(&mut self, height: usize) {
+        #[cfg(feature = "compact-index")]
+        self.compact
+            .retain(|_, indexed| (*indexed as usize) < height);
+
+        self.wide.retain(|_, indexed| *indexed < height);
+    }
+
+    /// Get the approximate amount of bytes used by the index entries.
+    ///
+    /// # Returns
+    /// The size of the keys, the heights, and the hash strings.
+    pub(crate) fn approximate_size(&self) -> usize {
+        let wide: usize = self
+            .wide
+            .keys()
+            .map(|hash| mem::size_of::<String>() + mem::size_of::<usize>() + hash.capacity())
+            .sum();
+
+        #[cfg(feature = "compact-index")]
+        return wide + self.compact.len() * mem::size_of::<([u8; 32], u32)>();
+
+        #[cfg(not(feature = "compact-index"))]
+        wide
+    }
+}
+
+/// Decode a hex encoded SHA-256 hash into the key of the compact index.
+///
+/// # Arguments
+/// - `hash`: The hash of a transaction.
+///
+/// # Returns
+/// The 32 bytes of the hash, or `None` if it is not 64 lowercase hex digits, so it encodes back
+/// to the same string.
+#[cfg(feature = "compact-index")]
+fn compact_key(hash: &str) -> Option<[u8; 32]> {
+    let digits = hash.as_bytes();
+
+    if digits.len() != 64 {
+        return None;
+    }
+
+    let nibble = |digit: u8| match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        _ => None,
+    };
+    let mut key = [0; 32];
+
+    for (byte, pair) in key.iter_mut().zip(digits.chunks_exact(2)) {
+        *byte = nibble(pair[0])? << 4 | nibble(pair[1])?;
+    }
+
+    Some(key)
+}
+
+/// Where a transaction of the blockchain is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionLocation {
+    /// The transaction is waiting to be mined.
+    Pending,
+
+    /// The transaction was mined in the block at a height.
+    Block(usize),
+}
+
+impl Chain {
+    /// Locate a transaction by its hash, in the pending transactions or the mined blocks.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The location of the transaction, or `None` if it is unknown or its block was pruned.
+    pub fn locate_transaction(&self, hash: &str) -> Option<TransactionLocation> {
+        if self.get_transaction(hash.to_string()).is_some() {
+            return Some(TransactionLocation::Pending);
+        }
+
+        self.find_transaction_in_chain(hash)
+            .map(|(height, _)| TransactionLocation::Block(height))
+    }
+
+    /// Get a pending or mined transaction by its hash.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The transaction, or `None` if it is unknown or its block was pruned.
+    pub(crate) fn lookup_transaction(&self, hash: &str) -> Option<&Transaction> {
+        self.current_transactions
+            .iter()
+            .find(|transaction| transaction.hash == hash)
+            .or_else(|| {
+                self.find_transaction_in_chain(hash)
+                    .map(|(_, transaction)| transaction)
+            })
+    }
+
+    /// Get the height of the block including a mined transaction from the transaction index.
+    ///
+    /// The blocks added since the index was last updated, typically after the blockchain was
+    /// deserialized, are searched one after another.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The height of the block, or `None` if no block includes the transaction.
+    pub(crate) fn indexed_height(&self, hash: &str) -> Option<usize> {
+        if let Some(height) = self.transaction_index.get(hash) {
+            return Some(height);
+        }
+
+        let start = self.indexed_blocks.min(self.chain.len());
+
+        self.chain[start..]
+            .iter()
+            .position(|block| {
+                block
+                    .transactions
+                    .iter()
+                    .any(|transaction| transaction.hash == hash)
+            })
+            .map(|position| start + position)
+    }
+
+    /// Add the transactions of the blocks missing from the transaction index.
+    pub(crate) fn update_transaction_index(&mut self) {
+        for (height, block) in self.chain.iter().enumerate().skip(self.indexed_blocks) {
+            for transaction in &block.transactions {
+                self.transaction_index.insert(&transaction.hash, height);
+            }
+        }
+
+        self.indexed_blocks = self.chain.len();
+    }
+
+    /// Remove the transactions of the blocks from a height from the transaction index.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the first removed block.
+    pub(crate) fn unindex_blocks(&mut self, height: usize) {
+        self.transaction_index.truncate(height);
+        self.indexed_blocks = self.indexed_blocks.min(height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_index() {
+        let digest = "ab".repeat(32);
+        let mut index = TransactionIndex::default();
+
+        index.insert(&digest, 3);
+        index.insert("replay", 5);
+        index.insert(&digest.to_uppercase(), 7);
+
+        assert_eq!(index.get(&digest), Some(3));
+        assert_eq!(index.get("replay"), Some(5));
+        assert_eq!(index.get(&digest.to_uppercase()), Some(7));
+        assert!(index.approximate_size() > 0);
+
+        index.truncate(5);
+
+        assert_eq!(index.get(&digest), Some(3));
+        assert_eq!(index.get("replay"), None);
+
+        index.remove(&digest);
+
+        assert_eq!(index.get(&digest), None);
+        assert_eq!(index.approximate_size(), 0);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{Block, Chain, ChainError, LedgerModel, Transaction};
+
+/// Tolerance of floating point comparisons between balances.
+const EPSILON: f64 = 1e-6;
+
+impl Chain {
+    /// Check the consistency of the blockchain state.
+    ///
+    /// The following invariants are verified:
+    /// - wallet balances are non-negative;
+    /// - the sum of wallet balances equals the issued funds minus the paid fees;
+    /// - every pending transaction is recorded in the history of its wallets;
+    /// - with the UTXO model, the unspent outputs of every wallet sum up to its balance;
+    /// - the asset balances are non-negative and sum up to the supply of their asset;
+    /// - every block matches its cached hash, links to the hash of the previous block and
+    ///   matches its Merkle root;
+    /// - the transactions of every block follow the canonical order.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error describing the first inconsistency found.
+    pub fn check_invariants(&self) -> Result<(), ChainError> {
+        self.check_state_invariants()?;
+
+        if let Some(genesis) = self.chain.first() {
+            Chain::check_block_hash(genesis, 0)?;
+        }
+
+        for height in 1..self.chain.len() {
+            self.check_block_link(height)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check the invariants after a mutation, when debug assertions or the `strict` feature are enabled.
+    ///
+    /// Only the last pending transaction and the last block link are verified to keep the check
+    /// cheap on long chains.
+    ///
+    /// # Panics
+    /// If an invariant is violated.
+    pub(crate) fn assert_invariants(&self) {
+        #[cfg(any(debug_assertions, feature = "strict"))]
+        {
+            let result = self
+                .check_balances()
+                .and_then(|_| match self.current_transactions.last() {
+                    Some(transaction) => self.check_transaction_indexed(transaction),
+                    None => Ok(()),
+                })
+                .and_then(|_| match self.chain.len() {
+                    0 | 1 => Ok(()),
+                    len => self.check_block_link(len - 1),
+                });
+
+            if let Err(err) = result {
+                panic!("{}", err);
+            }
+        }
+    }
+
+    /// Check the invariants of wallet balances and the pending transactions index.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error describing the first inconsistency found.
+    fn check_state_invariants(&self) -> Result<(), ChainError> {
+        self.check_balances()?;
+
+        for transaction in &self.current_transactions {
+            self.check_transaction_indexed(transaction)?;
+        }
+
+        if self.ledger().model() == LedgerModel::Utxo {
+            self.check_unspent_outputs()?;
+        }
+
+        self.check_asset_supplies()
+    }
+
+    /// Check that the asset balances are non-negative and sum up to the supply of their asset.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error describing the first asset whose balances differ.
+    fn check_asset_supplies(&self) -> Result<(), ChainError> {
+        for asset in self.assets.values() {
+            let mut total = 0.0;
+
+            for wallet in self.wallets.values() {
+                let balance = wallet.assets.get(&asset.id).copied().unwrap_or_default();
+
+                if balance < -EPSILON {
+                    return Err(ChainError::InvariantViolation(format!(
+                        "wallet {} holds {} of asset {}",
+                        wallet.address, balance, asset.id
+                    )));
+                }
+
+                total += balance;
+            }
+
+            if (total - asset.supply).abs() > EPSILON * asset.supply.max(1.0) {
+                return Err(ChainError::InvariantViolation(format!(
+                    "wallets hold {} of asset {} for a supply of {}",
+                    total, asset.id, asset.supply
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the unspent outputs of every wallet sum up to its balance.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error describing the first wallet whose outputs differ.
+    fn check_unspent_outputs(&self) -> Result<(), ChainError> {
+        for wallet in self.wallets.values() {
+            let unspent: f64 = self
+                .get_unspent_outputs(&wallet.address)
+                .iter()
+                .map(|(_, output)| output.amount)
+                .sum();
+
+            if (unspent - wallet.balance).abs() > EPSILON * wallet.balance.abs().max(1.0) {
+                return Err(ChainError::InvariantViolation(format!(
+                    "wallet {} has unspent outputs of {} for a balance of {}",
+                    wallet.address, unspent, wallet.balance
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that wallet balances are non-negative and that funds are conserved, the staked funds
+    /// counting with the wallet balances.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error describing the first inconsistency found.
+    fn check_balances(&self) -> Result<(), ChainError> {
+        let mut total = 0.0;
+
+        for wallet in self.wallets.values() {
+            // Validate if the balance is non-negative
+            if wallet.balance < -EPSILON {
+                return Err(ChainError::InvariantViolation(format!(
+                    "wallet {} has a negative balance of {}",
+                    wallet.address, wallet.balance
+                )));
+            }
+
+            total += wallet.balance;
+        }
+
+        // Bonded funds left the wallet balances
+        total += self.staking.total();
+
+        // Validate if the funds are conserved
+        let expected = self.issued_supply - self.collected_fees;
+
+        if (total - expected).abs() > EPSILON * expected.abs().max(1.0) {
+            return Err(ChainError::InvariantViolation(format!(
+                "wallets hold {} while {} was issued and {} was paid in fees",
+                total, self.issued_supply, self.collected_fees
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check that a transaction is recorded in the history of its wallets.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction to look up.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error if a wallet history misses the transaction.
+    fn check_transaction_indexed(&self, transaction: &Transaction) -> Result<(), ChainError> {
+        for address in [
+            transaction.from.as_str(),
+            transaction.to.as_str(),
+            transaction.payer(),
+        ] {
+            let indexed = match self.wallets.get(address) {
+                Some(wallet) => wallet.transactions.contains(&transaction.hash),
+                None => address == "Root",
+            };
+
+            if !indexed {
+                return Err(ChainError::InvariantViolation(format!(
+                    "transaction {} is missing from the history of {}",
+                    transaction.hash, address
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the cached hash of a block matches its header.
+    ///
+    /// # Arguments
+    /// - `block`: The block.
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error if the header changed since the block was sealed.
+    pub(crate) fn check_block_hash(block: &Block, height: usize) -> Result<(), ChainError> {
+        match block.has_valid_hash() {
+            true => Ok(()),
+            false => Err(ChainError::InvariantViolation(format!(
+                "block {} does not match its cached hash",
+                height
+            ))),
+        }
+    }
+
+    /// Check that a block links to the previous block and matches its Merkle root.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block, greater than zero.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error if the block is inconsistent.
+    pub(crate) fn check_block_link(&self, height: usize) -> Result<(), ChainError> {
+        let previous = &self.chain[height - 1];
+        let block = &self.chain[height];
+
+        Chain::check_block_hash(block, height)?;
+
+        if block.header.previous_hash != previous.hash() {
+            return Err(ChainError::InvariantViolation(format!(
+                "block {} does not link to block {}",
+                height,
+                height - 1
+            )));
+        }
+
+        // Pruned blocks only keep their header
+        if block.is_pruned() {
+            return Ok(());
+        }
+
+        if block.count != block.transactions.len()
+            || block.header.merkle != Chain::get_merkle(block.transactions.clone())
+        {
+            return Err(ChainError::InvariantViolation(format!(
+                "block {} does not match its transactions",
+                height
+            )));
+        }
+
+        if !block.is_ordered() {
+            return Err(ChainError::InvariantViolation(format!(
+                "block {} transactions are not in the canonical order",
+                height
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    #[test]
+    fn test_check_invariants_negative_balance() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+        chain.wallets.get_mut(&address).unwrap().balance = -1.0;
+
+        assert!(matches!(
+            chain.check_invariants(),
+            Err(ChainError::InvariantViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_invariants_supply() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+        chain.wallets.get_mut(&address).unwrap().balance += 20.0;
+
+        assert!(chain.check_invariants().is_err());
+    }
+
+    #[test]
+    fn test_check_invariants_block_link() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        chain.generate_new_block().unwrap();
+
+        chain.chain[1].header.previous_hash = "0".to_string();
+
+        assert!(chain.check_invariants().is_err());
+    }
+
+    #[test]
+    fn test_check_invariants_block_order() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypairs = [Keypair::generate(), Keypair::generate()];
+        let [a, b] =
+            [("a@mail.com", &keypairs[0]), ("b@mail.com", &keypairs[1])].map(|(email, keypair)| {
+                chain
+                    .create_wallet_with_key(email.to_string(), &keypair.public_key())
+                    .unwrap()
+            });
+
+        chain.credit_wallet(&a, 20.0);
+        chain.credit_wallet(&b, 20.0);
+        chain
+            .add_transaction(a.clone(), b.clone(), 1.0, &keypairs[0])
+            .unwrap();
+        chain.add_transaction(b, a, 5.0, &keypairs[1]).unwrap();
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.check_invariants(), Ok(()));
+
+        let block = &mut chain.chain[1];
+        block.transactions.swap(1, 2);
+        block.header.merkle = Chain::get_merkle(block.transactions.clone());
+
+        assert!(chain.check_invariants().is_err());
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "strict"))]
+    #[should_panic(expected = "Chain invariant violated")]
+    fn test_assert_invariants() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+        chain.wallets.get_mut(&address).unwrap().balance += 20.0;
+
+        chain.generate_new_block().unwrap();
+    }
+}
@@ -0,0 +1,475 @@
+use std::{fmt, fmt::Write, fs, io, path::Path};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encoding::{self, BLOCK_SEAL_DOMAIN, MESSAGE_DOMAIN},
+    Address, Approval, BlockHeader, BlockSignature, Chain, Transaction,
+};
+
+/// Length of the addresses derived from public keys.
+const ADDRESS_LENGTH: usize = 42;
+
+/// An ed25519 keypair owning a wallet.
+#[derive(Clone)]
+pub struct Keypair {
+    /// The secret signing key.
+    signing_key: SigningKey,
+}
+
+/// Replacement of the public key owning a wallet, recorded on-chain by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// The hex encoded public key revoked by the rotation, `None` if the wallet had none.
+    pub previous: Option<String>,
+
+    /// The hex encoded public key owning the wallet after the rotation.
+    pub public_key: String,
+
+    /// Whether the previous key was restored from a mnemonic backup to sign the rotation.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recovery: bool,
+}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keypair")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Keypair {
+    /// Generate a new random keypair.
+    ///
+    /// # Returns
+    /// A new keypair.
+    pub fn generate() -> Self {
+        Keypair {
+            signing_key: SigningKey::generate(&mut rand::rngs::OsRng),
+        }
+    }
+
+    /// Restore a keypair from its secret key.
+    ///
+    /// # Arguments
+    /// - `secret`: The hex encoded secret key.
+    ///
+    /// # Returns
+    /// The keypair, or `None` if the secret key is not 32 hex encoded bytes.
+    pub fn from_secret(secret: &str) -> Option<Self> {
+        let bytes: [u8; 32] = decode_hex(secret)?.try_into().ok()?;
+
+        Some(Keypair::from_bytes(&bytes))
+    }
+
+    /// Create a keypair from the bytes of its secret key.
+    ///
+    /// # Arguments
+    /// - `bytes`: The secret key.
+    ///
+    /// # Returns
+    /// The keypair.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Keypair {
+            signing_key: SigningKey::from_bytes(bytes),
+        }
+    }
+
+    /// Load a keypair persisted in a file, or generate and persist a new one if the file is missing.
+    ///
+    /// Nodes use it to keep the same identity across restarts.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file holding the hex encoded secret key.
+    ///
+    /// # Returns
+    /// The keypair, or an error if the file is invalid or cannot be written.
+    pub fn load_or_generate(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(secret) => Keypair::from_secret(secret.trim())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid secret key")),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let keypair = Keypair::generate();
+
+                fs::write(path, keypair.secret())?;
+
+                Ok(keypair)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the secret key, to be stored by the wallet owner only.
+    ///
+    /// # Returns
+    /// The hex encoded secret key.
+    pub fn secret(&self) -> String {
+        encode_hex(self.signing_key.as_bytes())
+    }
+
+    /// Get the public key.
+    ///
+    /// # Returns
+    /// The hex encoded public key.
+    pub fn public_key(&self) -> String {
+        encode_hex(self.signing_key.verifying_key().as_bytes())
+    }
+
+    /// Get the address of the wallet owned by the keypair.
+    ///
+    /// # Returns
+    /// The address derived from the public key.
+    pub fn address(&self) -> Address {
+        address_from_public_key(&self.public_key())
+    }
+
+    /// Sign a transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// The hex encoded signature of the transaction payload.
+    pub fn sign(&self, transaction: &Transaction) -> String {
+        let signature = self
+            .signing_key
+            .sign(transaction.signing_payload().as_bytes());
+
+        encode_hex(&signature.to_bytes())
+    }
+
+    /// Sign an arbitrary message, e.g. a challenge proving the ownership of the wallet.
+    ///
+    /// The message is signed within its own domain, so the signature is never valid for a
+    /// transaction, and the other way around.
+    ///
+    /// # Arguments
+    /// - `message`: The message.
+    ///
+    /// # Returns
+    /// The hex encoded signature of the message payload.
+    pub fn sign_message(&self, message: &[u8]) -> String {
+        let signature = self.signing_key.sign(message_payload(message).as_bytes());
+
+        encode_hex(&signature.to_bytes())
+    }
+
+    /// Sign a block header as a validator, under proof of authority.
+    ///
+    /// # Arguments
+    /// - `header`: The block header.
+    ///
+    /// # Returns
+    /// The public key of the keypair and the hex encoded signature of the header hash.
+    pub fn sign_header(&self, header: &BlockHeader) -> BlockSignature {
+        let signature = self.signing_key.sign(seal_payload(header).as_bytes());
+
+        BlockSignature {
+            public_key: self.public_key(),
+            signature: encode_hex(&signature.to_bytes()),
+        }
+    }
+
+    /// Get the X25519 private key of the keypair, to authenticate encrypted connections and
+    /// decrypt memos.
+    ///
+    /// # Returns
+    /// The private key bytes.
+    pub(crate) fn exchange_private_key(&self) -> [u8; 32] {
+        self.signing_key.to_scalar_bytes()
+    }
+}
+
+impl Chain {
+    /// Verify that a message is signed by the owner of a wallet, e.g. to log a user in with a
+    /// challenge signed by `Keypair::sign_message`.
+    ///
+    /// The signature is checked against the current public key of the wallet, so signatures of
+    /// a revoked key are rejected.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `message`: The message.
+    /// - `signature`: The hex encoded signature.
+    ///
+    /// # Returns
+    /// `true` if the wallet has a public key and the signature is a valid signature of the
+    /// message by it.
+    pub fn verify_message(&self, address: &str, message: &[u8], signature: &str) -> bool {
+        self.get_wallet(address)
+            .and_then(|wallet| wallet.public_key.as_deref())
+            .is_some_and(|public_key| verify_message(public_key, message, signature))
+    }
+}
+
+/// Derive the address of a wallet from its public key.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key.
+///
+/// # Returns
+/// The address of the wallet.
+pub fn address_from_public_key(public_key: &str) -> Address {
+    Chain::hash(&public_key)
+        .chars()
+        .take(ADDRESS_LENGTH)
+        .collect()
+}
+
+/// Check whether a public key is a valid ed25519 public key.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key.
+///
+/// # Returns
+/// `true` if the public key is valid.
+pub(crate) fn is_valid_public_key(public_key: &str) -> bool {
+    parse_public_key(public_key).is_some()
+}
+
+/// Verify the signature of a transaction.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key of the sender.
+/// - `transaction`: The signed transaction.
+///
+/// # Returns
+/// `true` if the transaction carries a valid signature of the sender.
+pub(crate) fn verify_signature(public_key: &str, transaction: &Transaction) -> bool {
+    verify_payload(
+        public_key,
+        &transaction.signing_payload(),
+        transaction.signature.as_deref(),
+    )
+}
+
+/// Verify the signature of the fee payer of a sponsored transaction.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key of the fee payer.
+/// - `transaction`: The sponsored transaction.
+///
+/// # Returns
+/// `true` if the transaction carries a valid signature of the fee payer.
+pub(crate) fn verify_fee_payer_signature(public_key: &str, transaction: &Transaction) -> bool {
+    verify_payload(
+        public_key,
+        &transaction.signing_payload(),
+        transaction.fee_payer_signature.as_deref(),
+    )
+}
+
+/// Verify the approval of a transaction by an owner of a multi-signature wallet.
+///
+/// # Arguments
+/// - `approval`: The approval.
+/// - `transaction`: The approved transaction.
+///
+/// # Returns
+/// `true` if the approval carries a valid signature of the transaction by its public key.
+pub(crate) fn verify_approval(approval: &Approval, transaction: &Transaction) -> bool {
+    verify_payload(
+        &approval.public_key,
+        &transaction.signing_payload(),
+        Some(&approval.signature),
+    )
+}
+
+/// Verify the signature of an arbitrary message.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key of the signer.
+/// - `message`: The message.
+/// - `signature`: The hex encoded signature.
+///
+/// # Returns
+/// `true` if the signature is a valid signature of the message by the key.
+pub(crate) fn verify_message(public_key: &str, message: &[u8], signature: &str) -> bool {
+    verify_payload(public_key, &message_payload(message), Some(signature))
+}
+
+/// Verify the signature of a block header by a validator.
+///
+/// # Arguments
+/// - `header`: The block header, carrying the signature.
+///
+/// # Returns
+/// `true` if the header carries a valid signature of its hash by the public key of the signature.
+pub(crate) fn verify_header_signature(header: &BlockHeader) -> bool {
+    header.signature.as_ref().is_some_and(|signature| {
+        verify_payload(
+            &signature.public_key,
+            &seal_payload(header),
+            Some(&signature.signature),
+        )
+    })
+}
+
+/// Get the payload covered by the signature of a block header.
+///
+/// # Arguments
+/// - `header`: The block header.
+///
+/// # Returns
+/// The hash of the header hash within the block seal domain.
+fn seal_payload(header: &BlockHeader) -> String {
+    encoding::hash(BLOCK_SEAL_DOMAIN, &[&header.hash()])
+}
+
+/// Get the payload covered by the signature of an arbitrary message.
+///
+/// # Arguments
+/// - `message`: The message.
+///
+/// # Returns
+/// The hash of the message within the message domain.
+fn message_payload(message: &[u8]) -> String {
+    encoding::hash(MESSAGE_DOMAIN, &[&message])
+}
+
+/// Verify a signature of a payload.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key of the signer.
+/// - `payload`: The signed payload.
+/// - `signature`: The hex encoded signature.
+///
+/// # Returns
+/// `true` if the signature is a valid signature of the payload by the key.
+fn verify_payload(public_key: &str, payload: &str, signature: Option<&str>) -> bool {
+    let Some(key) = parse_public_key(public_key) else {
+        return false;
+    };
+
+    let Some(bytes) = signature
+        .and_then(decode_hex)
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+    else {
+        return false;
+    };
+
+    key.verify(payload.as_bytes(), &Signature::from_bytes(&bytes))
+        .is_ok()
+}
+
+/// Convert an ed25519 public key to the X25519 public key used by encrypted connections and
+/// memos.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key.
+///
+/// # Returns
+/// The X25519 public key bytes, or `None` if the public key is invalid.
+pub(crate) fn exchange_public_key(public_key: &str) -> Option<[u8; 32]> {
+    parse_public_key(public_key).map(|key| key.to_montgomery().to_bytes())
+}
+
+/// Parse a hex encoded ed25519 public key.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key.
+///
+/// # Returns
+/// The public key, or `None` if it is invalid.
+fn parse_public_key(public_key: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = decode_hex(public_key)?.try_into().ok()?;
+
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+/// Encode bytes as a lowercase hex string.
+///
+/// # Arguments
+/// - `bytes`: The bytes.
+///
+/// # Returns
+/// The hex string.
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Decode a hex string.
+///
+/// # Arguments
+/// - `hex`: The hex string.
+///
+/// # Returns
+/// The bytes, or `None` if the string is not valid hex.
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypair_from_secret() {
+        let keypair = Keypair::generate();
+        let restored = Keypair::from_secret(&keypair.secret()).unwrap();
+
+        assert_eq!(restored.public_key(), keypair.public_key());
+        assert_eq!(restored.address(), keypair.address());
+        assert_eq!(keypair.address().len(), ADDRESS_LENGTH);
+        assert!(Keypair::from_secret("1234").is_none());
+        assert!(Keypair::from_secret(&"zz".repeat(32)).is_none());
+    }
+
+    #[test]
+    fn test_keypair_load_or_generate() {
+        let path = std::env::temp_dir().join(format!("identity-{}.key", std::process::id()));
+        let keypair = Keypair::load_or_generate(&path).unwrap();
+        let restored = Keypair::load_or_generate(&path).unwrap();
+
+        assert_eq!(restored.public_key(), keypair.public_key());
+
+        fs::write(&path, "invalid").unwrap();
+
+        assert!(Keypair::load_or_generate(&path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let keypair = Keypair::generate();
+        let other = Keypair::generate();
+        let transaction = Transaction::builder()
+            .from(keypair.address())
+            .to("to")
+            .amount(1.0)
+            .fee(0.1)
+            .build()
+            .sign(&keypair);
+
+        assert!(verify_signature(&keypair.public_key(), &transaction));
+        assert!(!verify_signature(&other.public_key(), &transaction));
+
+        // The fee is part of the signed payload
+        let mut tampered = transaction.clone();
+        tampered.fee = 0.0;
+
+        assert!(!verify_signature(&keypair.public_key(), &tampered));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode_hex(&[0, 15, 255]), "000fff");
+        assert_eq!(decode_hex("000fff"), Some(vec![0, 15, 255]));
+        assert_eq!(decode_hex("0"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("+f"), None);
+    }
+}
@@ -0,0 +1,585 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Chain, ChainError, Script, Transaction};
+
+/// Tolerance of floating point comparisons between output amounts.
+const EPSILON: f64 = 1e-9;
+
+/// Accounting model of the wallet balances.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerModel {
+    /// Balances are debited and credited in place.
+    #[default]
+    Account,
+
+    /// Transactions spend unspent outputs of previous transactions and create new ones, so a
+    /// double spend references an output that no longer exists.
+    Utxo,
+}
+
+impl LedgerModel {
+    /// Check whether the model is the account model.
+    ///
+    /// # Returns
+    /// `true` for `LedgerModel::Account`.
+    pub fn is_account(&self) -> bool {
+        *self == LedgerModel::Account
+    }
+}
+
+/// Reference to an output of a transaction, formatted as `hash:index`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OutPoint {
+    /// The hash of the transaction creating the output.
+    pub hash: String,
+
+    /// The position of the output among the outputs of the transaction.
+    pub index: usize,
+}
+
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.hash, self.index)
+    }
+}
+
+impl FromStr for OutPoint {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (hash, index) = value
+            .rsplit_once(':')
+            .ok_or_else(|| format!("{} is not formatted as hash:index", value))?;
+        let index = index
+            .parse()
+            .map_err(|_| format!("{} is not an index", index))?;
+
+        Ok(OutPoint {
+            hash: hash.to_string(),
+            index,
+        })
+    }
+}
+
+impl Serialize for OutPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for OutPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// An amount owned by an address, created by a transaction.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TxOutput {
+    /// The address owning the output.
+    pub address: String,
+
+    /// The amount of the output.
+    pub amount: f64,
+
+    /// The script the transaction spending the output must satisfy, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Script>,
+}
+
+/// Accounting of the funds moved by transactions, shared by the ledger models.
+///
+/// The wallet balances are kept by the blockchain in both models, a ledger only validates and
+/// records the funds spent by each transaction.
+pub trait Ledger {
+    /// Get the accounting model of the ledger.
+    ///
+    /// # Returns
+    /// The ledger model.
+    fn model(&self) -> LedgerModel;
+
+    /// Validate the inputs of a transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// An error describing why the inputs are invalid, if they are.
+    fn check(&self, transaction: &Transaction) -> Result<(), ChainError>;
+
+    /// Record a validated transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    fn apply(&mut self, transaction: &Transaction);
+
+    /// Check whether a recorded transaction can be undone, its outputs being unspent.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// `true` if the transaction can be undone.
+    fn can_undo(&self, transaction: &Transaction) -> bool;
+
+    /// Undo a recorded transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    fn undo(&mut self, transaction: &Transaction);
+
+    /// Pick inputs of an address covering a value.
+    ///
+    /// # Arguments
+    /// - `address`: The address owning the inputs.
+    /// - `value`: The value to cover.
+    ///
+    /// # Returns
+    /// The inputs, or `None` if the unspent outputs of the address do not cover the value.
+    fn select_inputs(&self, address: &str, value: f64) -> Option<Vec<OutPoint>>;
+
+    /// Get the unspent outputs of an address.
+    ///
+    /// # Arguments
+    /// - `address`: The address.
+    ///
+    /// # Returns
+    /// The unspent outputs, ordered by reference.
+    fn unspent_outputs(&self, address: &str) -> Vec<(OutPoint, TxOutput)>;
+}
+
+/// Ledger of the account model, where the wallet balances are the only record.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountLedger;
+
+impl Ledger for AccountLedger {
+    fn model(&self) -> LedgerModel {
+        LedgerModel::Account
+    }
+
+    fn check(&self, transaction: &Transaction) -> Result<(), ChainError> {
+        if transaction.lock.is_some() || transaction.witness.is_some() {
+            return Err(ChainError::InvalidScript(
+                "scripts require the UTXO ledger model".to_string(),
+            ));
+        }
+
+        match transaction.inputs.is_empty() {
+            true => Ok(()),
+            false => Err(ChainError::InvalidInputs(
+                "the account ledger takes no inputs".to_string(),
+            )),
+        }
+    }
+
+    fn apply(&mut self, _transaction: &Transaction) {}
+
+    fn can_undo(&self, _transaction: &Transaction) -> bool {
+        true
+    }
+
+    fn undo(&mut self, _transaction: &Transaction) {}
+
+    fn select_inputs(&self, _address: &str, _value: f64) -> Option<Vec<OutPoint>> {
+        Some(Vec::new())
+    }
+
+    fn unspent_outputs(&self, _address: &str) -> Vec<(OutPoint, TxOutput)> {
+        Vec::new()
+    }
+}
+
+/// Ledger of the UTXO model, tracking the unspent outputs of every transaction.
+///
+/// A transaction pays its amount to the receiver in its first output, and returns the change of
+/// the inputs of the sender and the fee payer in the following outputs. Outputs of transactions
+/// sharing a hash are merged.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UtxoSet {
+    /// The unspent outputs.
+    unspent: BTreeMap<OutPoint, TxOutput>,
+
+    /// The outputs spent by recorded transactions, restored when they are undone.
+    spent: HashMap<OutPoint, TxOutput>,
+}
+
+impl UtxoSet {
+    /// Compute the outputs of a transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    /// - `spent`: The outputs spent by its inputs.
+    ///
+    /// # Returns
+    /// The payment to the receiver, followed by the change of the sender and the fee payer.
+    fn outputs(transaction: &Transaction, spent: &[TxOutput]) -> Vec<TxOutput> {
+        let mut outputs = Vec::new();
+
+        if transaction.amount > 0.0 {
+            outputs.push(TxOutput {
+                address: transaction.to.to_owned(),
+                amount: transaction.amount,
+                lock: transaction.lock.to_owned(),
+            });
+        }
+
+        for (owner, owed) in Self::debts(transaction) {
+            let change = Self::owned(spent.iter(), owner) - owed;
+
+            if change > EPSILON {
+                outputs.push(TxOutput {
+                    address: owner.to_string(),
+                    amount: change,
+                    lock: None,
+                });
+            }
+        }
+
+        outputs
+    }
+
+    /// Get the amounts owed by the sender and the fee payer of a transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// The owed amount by address, the sender first.
+    fn debts(transaction: &Transaction) -> Vec<(&str, f64)> {
+        match transaction.payer() == transaction.from {
+            true => vec![(
+                transaction.from.as_str(),
+                transaction.amount + transaction.charged_fee(),
+            )],
+            false => vec![
+                (transaction.from.as_str(), transaction.amount),
+                (transaction.payer(), transaction.charged_fee()),
+            ],
+        }
+    }
+
+    /// Sum the outputs owned by an address.
+    ///
+    /// # Arguments
+    /// - `outputs`: The outputs.
+    /// - `address`: The address.
+    ///
+    /// # Returns
+    /// The total amount of the outputs of the address.
+    fn owned<'a>(outputs: impl Iterator<Item = &'a TxOutput>, address: &str) -> f64 {
+        outputs
+            .filter(|output| output.address == address)
+            .map(|output| output.amount)
+            .sum()
+    }
+}
+
+impl Ledger for UtxoSet {
+    fn model(&self) -> LedgerModel {
+        LedgerModel::Utxo
+    }
+
+    fn check(&self, transaction: &Transaction) -> Result<(), ChainError> {
+        if transaction.asset_id.is_some() {
+            return Err(ChainError::InvalidAsset(
+                "assets require the account ledger model".to_string(),
+            ));
+        }
+
+        if let Some(lock) = &transaction.lock {
+            lock.validate().map_err(ChainError::InvalidScript)?;
+        }
+
+        // Funds issued by the root address are not taken from outputs
+        if transaction.from == "Root" {
+            return match transaction.inputs.is_empty() {
+                true => Ok(()),
+                false => Err(ChainError::InvalidInputs(
+                    "funds issued by the root address take no inputs".to_string(),
+                )),
+            };
+        }
+
+        let mut seen = HashSet::new();
+        let mut spent = Vec::new();
+
+        for input in &transaction.inputs {
+            let output = match self.unspent.get(input) {
+                Some(output) if seen.insert(input) => output,
+                _ => return Err(ChainError::DoubleSpend(input.to_string())),
+            };
+
+            if output.address != transaction.from && output.address != transaction.payer() {
+                return Err(ChainError::InvalidInputs(format!(
+                    "{} is not owned by the sender or the fee payer",
+                    input
+                )));
+            }
+
+            // The spending transaction satisfies the script locking the output
+            if let Some(lock) = &output.lock {
+                lock.evaluate(transaction).map_err(|reason| {
+                    ChainError::InvalidScript(format!("{}: {}", input, reason))
+                })?;
+            }
+
+            spent.push(output);
+        }
+
+        for (owner, owed) in Self::debts(transaction) {
+            if Self::owned(spent.iter().copied(), owner) < owed - EPSILON {
+                return Err(ChainError::InvalidInputs(format!(
+                    "the inputs of {} do not cover {}",
+                    owner, owed
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, transaction: &Transaction) {
+        let mut spent = Vec::new();
+
+        for input in &transaction.inputs {
+            if let Some(output) = self.unspent.remove(input) {
+                self.spent.insert(input.to_owned(), output.to_owned());
+                spent.push(output);
+            }
+        }
+
+        for (index, output) in Self::outputs(transaction, &spent).into_iter().enumerate() {
+            let out_point = OutPoint {
+                hash: transaction.hash.to_owned(),
+                index,
+            };
+
+            self.unspent
+                .entry(out_point)
+                .and_modify(|unspent| unspent.amount += output.amount)
+                .or_insert(output);
+        }
+    }
+
+    fn can_undo(&self, transaction: &Transaction) -> bool {
+        let spent: Vec<TxOutput> = transaction
+            .inputs
+            .iter()
+            .filter_map(|input| self.spent.get(input).cloned())
+            .collect();
+
+        (0..Self::outputs(transaction, &spent).len()).all(|index| {
+            self.unspent.contains_key(&OutPoint {
+                hash: transaction.hash.to_owned(),
+                index,
+            })
+        })
+    }
+
+    fn undo(&mut self, transaction: &Transaction) {
+        let spent: Vec<TxOutput> = transaction
+            .inputs
+            .iter()
+            .filter_map(|input| self.spent.get(input).cloned())
+            .collect();
+
+        for (index, output) in Self::outputs(transaction, &spent).into_iter().enumerate() {
+            let out_point = OutPoint {
+                hash: transaction.hash.to_owned(),
+                index,
+            };
+
+            if let Some(unspent) = self.unspent.get_mut(&out_point) {
+                unspent.amount -= output.amount;
+
+                if unspent.amount <= EPSILON {
+                    self.unspent.remove(&out_point);
+                }
+            }
+        }
+
+        for input in &transaction.inputs {
+            if let Some(output) = self.spent.remove(input) {
+                self.unspent.insert(input.to_owned(), output);
+            }
+        }
+    }
+
+    fn select_inputs(&self, address: &str, value: f64) -> Option<Vec<OutPoint>> {
+        let mut inputs = Vec::new();
+        let mut total = 0.0;
+
+        for (out_point, output) in &self.unspent {
+            if total >= value - EPSILON {
+                break;
+            }
+
+            // Outputs locked by a script are only spent by transactions satisfying it
+            if output.address == address && output.lock.is_none() {
+                inputs.push(out_point.to_owned());
+                total += output.amount;
+            }
+        }
+
+        (total >= value - EPSILON).then_some(inputs)
+    }
+
+    fn unspent_outputs(&self, address: &str) -> Vec<(OutPoint, TxOutput)> {
+        self.unspent
+            .iter()
+            .filter(|(_, output)| output.address == address)
+            .map(|(out_point, output)| (out_point.to_owned(), output.to_owned()))
+            .collect()
+    }
+}
+
+/// The ledger of a blockchain, following the ledger model of its consensus rules.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum LedgerState {
+    /// The ledger of the account model.
+    Account(AccountLedger),
+
+    /// The ledger of the UTXO model.
+    Utxo(UtxoSet),
+}
+
+impl Default for LedgerState {
+    fn default() -> Self {
+        LedgerState::Account(AccountLedger)
+    }
+}
+
+impl LedgerState {
+    /// Create an empty ledger.
+    ///
+    /// # Arguments
+    /// - `model`: The ledger model.
+    ///
+    /// # Returns
+    /// The ledger of the model.
+    pub(crate) fn new(model: LedgerModel) -> Self {
+        match model {
+            LedgerModel::Account => LedgerState::Account(AccountLedger),
+            LedgerModel::Utxo => LedgerState::Utxo(UtxoSet::default()),
+        }
+    }
+}
+
+impl Chain {
+    /// Get the ledger recording the funds moved by the transactions.
+    ///
+    /// # Returns
+    /// The ledger of the ledger model of the consensus rules.
+    pub fn ledger(&self) -> &dyn Ledger {
+        match &self.ledger {
+            LedgerState::Account(ledger) => ledger,
+            LedgerState::Utxo(ledger) => ledger,
+        }
+    }
+
+    /// Get the ledger recording the funds moved by the transactions, to update it.
+    ///
+    /// # Returns
+    /// The mutable ledger.
+    pub(crate) fn ledger_mut(&mut self) -> &mut dyn Ledger {
+        match &mut self.ledger {
+            LedgerState::Account(ledger) => ledger,
+            LedgerState::Utxo(ledger) => ledger,
+        }
+    }
+
+    /// Pick the unspent outputs of a wallet spent by a new transaction.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `value`: The value to cover, the amount and the fee paid by the wallet.
+    ///
+    /// # Returns
+    /// The inputs, always empty with the account model, or `InsufficientBalance` if the unspent
+    /// outputs of the wallet do not cover the value.
+    pub fn select_inputs(&self, address: &str, value: f64) -> Result<Vec<OutPoint>, ChainError> {
+        self.ledger()
+            .select_inputs(address, value)
+            .ok_or(ChainError::InsufficientBalance)
+    }
+
+    /// Get the unspent outputs of a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The unspent outputs, always empty with the account model.
+    pub fn get_unspent_outputs(&self, address: &str) -> Vec<(OutPoint, TxOutput)> {
+        self.ledger().unspent_outputs(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_point_format() {
+        let out_point = OutPoint {
+            hash: "abc".to_string(),
+            index: 2,
+        };
+
+        assert_eq!(out_point.to_string(), "abc:2");
+        assert_eq!("abc:2".parse::<OutPoint>(), Ok(out_point.clone()));
+        assert!("abc".parse::<OutPoint>().is_err());
+        assert_eq!(
+            serde_json::to_string(&out_point).unwrap(),
+            "\"abc:2\"".to_string()
+        );
+    }
+
+    #[test]
+    fn test_utxo_set() {
+        let mut ledger = UtxoSet::default();
+        let credit = Transaction::builder()
+            .from("Root")
+            .to("alice")
+            .amount(10.0)
+            .fee(0.0)
+            .build();
+
+        ledger.apply(&credit);
+
+        let inputs = ledger.select_inputs("alice", 4.4).unwrap();
+        let payment = Transaction::builder()
+            .from("alice")
+            .to("bob")
+            .amount(4.0)
+            .fee(0.4)
+            .inputs(inputs.clone())
+            .build();
+
+        assert_eq!(ledger.check(&payment), Ok(()));
+
+        ledger.apply(&payment);
+
+        assert_eq!(ledger.unspent_outputs("bob")[0].1.amount, 4.0);
+        assert_eq!(ledger.unspent_outputs("alice")[0].1.amount, 5.6);
+        assert_eq!(
+            ledger.check(&payment),
+            Err(ChainError::DoubleSpend(inputs[0].to_string()))
+        );
+        assert!(ledger.can_undo(&payment));
+
+        ledger.undo(&payment);
+
+        assert!(ledger.unspent_outputs("bob").is_empty());
+        assert_eq!(ledger.unspent_outputs("alice")[0].1.amount, 10.0);
+    }
+}
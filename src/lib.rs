@@ -1,11 +1,136 @@
 #![forbid(unsafe_code)]
 
+pub mod address;
+#[cfg(feature = "analytics")]
+pub mod analytics;
+pub mod assets;
+pub mod backend;
+pub mod backpressure;
 pub mod block;
+pub mod bootstrap;
 pub mod chain;
+pub mod clock;
+pub mod compare;
+pub mod config;
+pub mod conflicts;
+pub mod consensus;
+#[cfg(feature = "contracts")]
+pub mod contracts;
+pub mod dataset;
+pub mod encoding;
+pub mod engine;
+pub mod error;
+pub mod events;
+#[cfg(any(test, feature = "testing"))]
+pub mod fixtures;
+pub mod fork;
+pub mod gas;
+pub mod genesis;
+pub mod gossip;
+pub mod history;
+pub mod index;
+pub mod invariants;
+pub mod keys;
+pub mod ledger;
+pub mod light;
+pub mod memo;
+pub mod memory;
+pub mod milestone;
+pub mod miner;
+pub mod mmr;
+pub mod multisig;
+#[cfg(feature = "net")]
+pub mod network;
+pub mod node;
+pub mod overview;
+pub mod pipeline;
+pub mod plugin;
+pub mod policy;
+pub mod proof;
+pub mod pruning;
+pub mod registry;
+pub mod replay;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb_storage;
+pub mod script;
+pub mod search;
+pub mod security;
+#[cfg(any(test, feature = "testing"))]
+pub mod simulation;
+#[cfg(feature = "sled")]
+pub mod sled_storage;
+pub mod staking;
+pub mod storage;
 pub mod transaction;
+#[cfg(feature = "net")]
+pub mod transport;
+pub mod validator;
+pub mod verify;
 pub mod wallet;
+pub mod watch;
+pub mod webhook;
 
+pub use address::*;
+#[cfg(feature = "analytics")]
+pub use analytics::*;
+pub use assets::*;
+pub use backend::*;
 pub use block::*;
+pub use bootstrap::*;
 pub use chain::*;
+pub use clock::*;
+pub use compare::*;
+pub use config::*;
+pub use conflicts::*;
+pub use consensus::*;
+#[cfg(feature = "contracts")]
+pub use contracts::*;
+pub use dataset::*;
+pub use encoding::*;
+pub use engine::*;
+pub use error::*;
+pub use events::*;
+pub use fork::*;
+pub use gas::*;
+pub use genesis::*;
+pub use gossip::*;
+pub use index::*;
+pub use keys::*;
+pub use ledger::*;
+pub use light::*;
+pub use memo::*;
+pub use memory::*;
+pub use milestone::*;
+pub use miner::*;
+pub use mmr::*;
+pub use multisig::*;
+#[cfg(feature = "net")]
+pub use network::*;
+pub use node::*;
+pub use overview::*;
+pub use pipeline::*;
+pub use plugin::*;
+pub use policy::*;
+pub use proof::*;
+pub use pruning::*;
+pub use registry::*;
+pub use replay::*;
+#[cfg(feature = "rocksdb")]
+pub use rocksdb_storage::*;
+pub use script::*;
+pub use search::*;
+pub use security::*;
+#[cfg(any(test, feature = "testing"))]
+pub use simulation::*;
+#[cfg(feature = "sled")]
+pub use sled_storage::*;
+pub use staking::*;
+pub use storage::*;
 pub use transaction::*;
+#[cfg(feature = "net")]
+pub use transport::*;
+pub use validator::*;
+pub use verify::*;
 pub use wallet::*;
+pub use watch::*;
+pub use webhook::*;
@@ -0,0 +1,484 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BlockHeader, Chain, ChainError, Clock, Consensus, ConsensusRules, DifficultyAlgorithm,
+    MerkleMountainRange, MerkleProof, MountainRangeProof, ProofOfWork, StateSnapshot, SystemClock,
+};
+
+/// A trusted starting point of a light client, verified and loaded by
+/// `LightChain::from_bootstrap` instead of every header since the genesis block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightBootstrap {
+    /// The header of the genesis block.
+    pub genesis: BlockHeader,
+
+    /// The consensus rules of the network, committed to by the genesis block.
+    pub rules: ConsensusRules,
+
+    /// The Merkle mountain range of the hashes of the headers before the bundled headers.
+    pub history: MerkleMountainRange,
+
+    /// Proof that the genesis block is the first leaf of the history.
+    pub genesis_proof: MountainRangeProof,
+
+    /// Proof that the parent of the first bundled header is the last leaf of the history.
+    pub parent_proof: MountainRangeProof,
+
+    /// The latest headers, up to the last block.
+    pub headers: Vec<BlockHeader>,
+
+    /// The wallet balances after one of the bundled headers, if the state is known.
+    pub snapshot: Option<StateSnapshot>,
+}
+
+/// A light client following a blockchain through its block headers only.
+///
+/// The headers are validated like the blocks of a full node, except for their transactions:
+/// each header must extend the last one and be sealed by the consensus engine, and the difficulty must
+/// follow the retarget of the consensus rules. Transactions are then verified with Merkle proofs
+/// fetched from a full node, against the headers of the light client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightChain {
+    /// The validated headers, starting with the genesis block unless bootstrapped.
+    headers: Vec<BlockHeader>,
+
+    /// The height of the first header.
+    #[serde(default)]
+    first_height: usize,
+
+    /// The Merkle mountain range of the hashes of the headers before the first header.
+    #[serde(default)]
+    history: MerkleMountainRange,
+
+    /// The wallet balances loaded from a bootstrap bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snapshot: Option<StateSnapshot>,
+
+    /// The consensus rules of the network.
+    rules: ConsensusRules,
+
+    /// Consensus engine verifying the headers, proof of work if `None`.
+    #[serde(skip)]
+    consensus: Option<Arc<dyn Consensus>>,
+}
+
+impl LightChain {
+    /// Create a new light client from a trusted genesis block header.
+    ///
+    /// # Arguments
+    /// - `genesis`: The header of the genesis block.
+    /// - `rules`: The consensus rules of the network.
+    ///
+    /// # Returns
+    /// A new `LightChain` instance, or `InvalidBlock` if the genesis block does not commit to the
+    /// consensus rules.
+    pub fn new(genesis: BlockHeader, rules: ConsensusRules) -> Result<Self, ChainError> {
+        if genesis.rules.as_deref() != Some(rules.hash().as_str()) {
+            return Err(ChainError::InvalidBlock(
+                "the genesis block does not commit to the consensus rules".to_string(),
+            ));
+        }
+
+        Ok(LightChain {
+            headers: vec![genesis],
+            first_height: 0,
+            history: MerkleMountainRange::default(),
+            snapshot: None,
+            rules,
+            consensus: None,
+        })
+    }
+
+    /// Create a new light client from a bootstrap bundle, keeping the bundled headers only.
+    ///
+    /// The genesis block must commit to the consensus rules and be the first leaf of the
+    /// history, whose last leaf must be the parent of the first bundled header. The bundled
+    /// headers are validated like new headers, their difficulty being checked once the retarget
+    /// window is bundled, and the snapshot must follow one of them. The balances of the snapshot
+    /// are trusted, the headers not committing to the state.
+    ///
+    /// # Arguments
+    /// - `bundle`: The bundle built by `Chain::light_bootstrap`.
+    ///
+    /// # Returns
+    /// A new `LightChain` instance following the last bundled header, or `InvalidBlock` if the
+    /// bundle is inconsistent.
+    pub fn from_bootstrap(bundle: LightBootstrap) -> Result<Self, ChainError> {
+        let invalid = |reason: &str| Err(ChainError::InvalidBlock(reason.to_string()));
+
+        let mut light = LightChain::new(bundle.genesis, bundle.rules)?;
+        let genesis = light.headers.remove(0);
+        let mut headers = bundle.headers.into_iter();
+
+        let Some(first) = headers.next() else {
+            return invalid("the bundle has no headers");
+        };
+
+        let history = &bundle.history;
+        let linked = match history.leaves() {
+            0 => first.hash() == genesis.hash(),
+            leaves => {
+                history.verify(0, &genesis.hash(), &bundle.genesis_proof)
+                    && history.verify(leaves - 1, &first.previous_hash, &bundle.parent_proof)
+            }
+        };
+
+        if !linked {
+            return invalid("the headers do not descend from the genesis block");
+        }
+
+        if history.leaves() > 0 {
+            match &light.consensus {
+                Some(consensus) => consensus.verify_seal(&first)?,
+                None => ProofOfWork.verify_seal(&first)?,
+            }
+        }
+
+        light.first_height = history.leaves();
+        light.history = bundle.history;
+        light.headers.push(first);
+        light.add_headers(headers)?;
+
+        if let Some(snapshot) = &bundle.snapshot {
+            if light
+                .get_header(snapshot.height)
+                .is_none_or(|header| header.hash() != snapshot.block_hash)
+            {
+                return invalid("the snapshot does not follow a bundled header");
+            }
+        }
+
+        light.snapshot = bundle.snapshot;
+
+        Ok(light)
+    }
+
+    /// Set the consensus engine verifying the headers, proof of work by default.
+    ///
+    /// # Arguments
+    /// - `consensus`: The consensus engine.
+    pub fn set_consensus(&mut self, consensus: Arc<dyn Consensus>) {
+        self.consensus = Some(consensus);
+    }
+
+    /// Get the height of the last header.
+    ///
+    /// # Returns
+    /// The height of the last header, the genesis block being at height 0.
+    pub fn height(&self) -> usize {
+        self.first_height + self.headers.len() - 1
+    }
+
+    /// Get the height of the first header kept by the light client.
+    ///
+    /// # Returns
+    /// 0, or the height of the first bundled header if bootstrapped.
+    pub fn first_height(&self) -> usize {
+        self.first_height
+    }
+
+    /// Get the wallet balances loaded from a bootstrap bundle.
+    ///
+    /// # Returns
+    /// The state snapshot, or `None` if the bundle had none or the light client was not
+    /// bootstrapped.
+    pub fn get_snapshot(&self) -> Option<&StateSnapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// Get a header by its height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the header.
+    ///
+    /// # Returns
+    /// The header, or `None` if the light client is not that high.
+    pub fn get_header(&self, height: usize) -> Option<&BlockHeader> {
+        self.headers.get(height.checked_sub(self.first_height)?)
+    }
+
+    /// Get the hash of the last header.
+    ///
+    /// # Returns
+    /// The hash of the last header.
+    pub fn get_last_hash(&self) -> String {
+        self.headers[self.headers.len() - 1].hash()
+    }
+
+    /// Verify that a block hash is the hash of the header at a height, including the headers
+    /// before a bootstrap bundle.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the header.
+    /// - `hash`: The hash of the header.
+    /// - `proof`: The proof built by `Chain::header_proof` for the headers before the first
+    ///   header of the light client, ignored for the later headers.
+    ///
+    /// # Returns
+    /// `true` if the header at the height has the hash.
+    pub fn verify_header(&self, height: usize, hash: &str, proof: &MountainRangeProof) -> bool {
+        match self.get_header(height) {
+            Some(header) => header.hash() == hash,
+            None if height < self.first_height => self.history.verify(height, hash, proof),
+            None => false,
+        }
+    }
+
+    /// Get the cumulative work of the headers.
+    ///
+    /// # Returns
+    /// The expected amount of hashes computed to mine every block since the first header.
+    pub fn cumulative_work(&self) -> f64 {
+        self.headers.iter().map(BlockHeader::work).sum()
+    }
+
+    /// Validate a header and append it.
+    ///
+    /// # Arguments
+    /// - `header`: The header of the next block.
+    ///
+    /// # Returns
+    /// `UnknownParent` if the header does not extend the last header, or `InvalidBlock` if it
+    /// breaks the consensus rules.
+    pub fn add_header(&mut self, header: BlockHeader) -> Result<(), ChainError> {
+        if header.previous_hash != self.get_last_hash() {
+            return Err(ChainError::UnknownParent);
+        }
+
+        let invalid = |reason: &str| Err(ChainError::InvalidBlock(reason.to_string()));
+
+        match &self.consensus {
+            Some(consensus) => consensus.verify_seal(&header)?,
+            None => ProofOfWork.verify_seal(&header)?,
+        }
+
+        if let Some(difficulty) = self
+            .rules
+            .difficulty_algorithm
+            .next_difficulty_from(self.first_height, &self.headers)
+        {
+            if header.difficulty != difficulty {
+                return invalid("the difficulty does not match the retarget");
+            }
+        }
+
+        let now = SystemClock.now_millis().div_euclid(1000);
+
+        if header.timestamp > now + self.rules.max_timestamp_drift {
+            return invalid("the timestamp is too far in the future");
+        }
+
+        self.headers.push(header);
+
+        Ok(())
+    }
+
+    /// Validate and append headers, in height order.
+    ///
+    /// The headers before an invalid one are kept.
+    ///
+    /// # Arguments
+    /// - `headers`: The headers following the last header.
+    ///
+    /// # Returns
+    /// The amount of headers appended, or the error of the first invalid header.
+    pub fn add_headers(
+        &mut self,
+        headers: impl IntoIterator<Item = BlockHeader>,
+    ) -> Result<usize, ChainError> {
+        let height = self.height();
+
+        for header in headers {
+            self.add_header(header)?;
+        }
+
+        Ok(self.height() - height)
+    }
+
+    /// Replace the headers with a candidate header chain carrying more work, following the
+    /// longest valid chain rule.
+    ///
+    /// # Arguments
+    /// - `candidate`: The headers of the candidate chain, starting with the first header, the
+    ///   genesis block unless bootstrapped.
+    ///
+    /// # Returns
+    /// The height of the first header that differs, `InsufficientWork` if the candidate does not
+    /// carry more work, or an error describing why one of its headers is invalid. The headers
+    /// are left unchanged if the candidate is rejected.
+    pub fn replace_headers(&mut self, candidate: Vec<BlockHeader>) -> Result<usize, ChainError> {
+        let mut candidate = candidate.into_iter();
+
+        match candidate.next() {
+            Some(first) if first.hash() == self.headers[0].hash() => {}
+            _ => return Err(ChainError::UnknownParent),
+        }
+
+        let mut replacement = LightChain {
+            headers: vec![self.headers[0].clone()],
+            first_height: self.first_height,
+            history: self.history.clone(),
+            snapshot: self.snapshot.clone(),
+            rules: self.rules.clone(),
+            consensus: self.consensus.clone(),
+        };
+
+        replacement.add_headers(candidate)?;
+
+        if replacement.cumulative_work() <= self.cumulative_work() {
+            return Err(ChainError::InsufficientWork);
+        }
+
+        let fork_height = self.first_height
+            + self
+                .headers
+                .iter()
+                .zip(&replacement.headers)
+                .position(|(header, other)| header.hash() != other.hash())
+                .unwrap_or(self.headers.len());
+
+        self.headers = replacement.headers;
+
+        // The snapshot follows a replaced header
+        if self
+            .snapshot
+            .as_ref()
+            .is_some_and(|snapshot| snapshot.height >= fork_height)
+        {
+            self.snapshot = None;
+        }
+
+        Ok(fork_height)
+    }
+
+    /// Verify that a transaction is included in the block at a height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block including the transaction.
+    /// - `tx_hash`: The hash of the transaction.
+    /// - `proof`: The Merkle proof fetched from a full node.
+    ///
+    /// # Returns
+    /// `true` if the proof leads to the Merkle root of the header at the height.
+    pub fn verify_transaction(&self, height: usize, tx_hash: &str, proof: &MerkleProof) -> bool {
+        self.get_header(height)
+            .is_some_and(|header| proof.verify(&header.merkle, tx_hash))
+    }
+
+    /// Get the amount of blocks confirming a block at a height, itself included.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The amount of confirmations, or 0 if the light client is not that high.
+    pub fn confirmations(&self, height: usize) -> usize {
+        (self.height() + 1).saturating_sub(height)
+    }
+}
+
+impl Chain {
+    /// Get the headers of a range of blocks, to serve light clients.
+    ///
+    /// # Arguments
+    /// - `start`: The height of the first header.
+    /// - `count`: The maximum amount of headers.
+    ///
+    /// # Returns
+    /// The headers ordered by height, fewer than `count` if the blockchain is not that high.
+    pub fn get_headers(&self, start: usize, count: usize) -> Vec<BlockHeader> {
+        self.chain
+            .iter()
+            .skip(start)
+            .take(count)
+            .map(|block| block.header.clone())
+            .collect()
+    }
+
+    /// Create a light client from the genesis block of the blockchain.
+    ///
+    /// # Returns
+    /// The light client, following the consensus rules of the blockchain, or `None` if the
+    /// blockchain has no genesis block.
+    pub fn light_client(&self) -> Option<LightChain> {
+        let genesis = self.chain.first()?.header.clone();
+
+        LightChain::new(genesis, self.config.consensus.clone()).ok()
+    }
+
+    /// Build a bootstrap bundle starting a light client from the latest headers.
+    ///
+    /// The bundle holds the headers of the retarget window, the latest state snapshot, and the
+    /// Merkle mountain range of the hashes of the earlier headers. The headers start at the
+    /// snapshot if it is older than the window.
+    ///
+    /// # Returns
+    /// The bundle, to be loaded with `LightChain::from_bootstrap`, or `None` if the blockchain
+    /// has no genesis block.
+    pub fn light_bootstrap(&self) -> Option<LightBootstrap> {
+        let genesis = self.chain.first()?.header.clone();
+        let tip = self.chain.len() - 1;
+
+        // The state at the last block, or the latest snapshot once the bodies are pruned
+        let snapshot = match self.state_at(tip) {
+            Some(mut snapshot) => {
+                snapshot.balances.retain(|_, balance| *balance != 0.0);
+                Some(snapshot)
+            }
+            None => self.snapshots.last().cloned(),
+        };
+
+        let window = match self.config.consensus.difficulty_algorithm {
+            DifficultyAlgorithm::Fixed => 0,
+            DifficultyAlgorithm::Retarget {
+                retarget_interval, ..
+            } => retarget_interval,
+        };
+        let mut first = match tip {
+            0 => 0,
+            tip => tip.saturating_sub(window).max(1),
+        };
+
+        if let Some(snapshot) = &snapshot {
+            first = first.min(snapshot.height);
+        }
+
+        let hashes: Vec<String> = self.chain[..first]
+            .iter()
+            .map(|block| block.hash())
+            .collect();
+        let proof = |index: usize| MerkleMountainRange::prove(&hashes, index).unwrap_or_default();
+
+        Some(LightBootstrap {
+            genesis,
+            rules: self.config.consensus.clone(),
+            history: MerkleMountainRange::from_leaves(hashes.iter().map(String::as_str)),
+            genesis_proof: proof(0),
+            parent_proof: proof(first.saturating_sub(1)),
+            headers: self.get_headers(first, self.chain.len() - first),
+            snapshot,
+        })
+    }
+
+    /// Build the proof of a header for a light client bootstrapped without it.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the header.
+    /// - `first_height`: The height of the first header of the light client.
+    ///
+    /// # Returns
+    /// The proof to check with `LightChain::verify_header`, or `None` if the header is not
+    /// before the first header of the light client.
+    pub fn header_proof(&self, height: usize, first_height: usize) -> Option<MountainRangeProof> {
+        let hashes: Vec<String> = self
+            .chain
+            .get(..first_height)?
+            .iter()
+            .map(|block| block.hash())
+            .collect();
+
+        MerkleMountainRange::prove(&hashes, height)
+    }
+}
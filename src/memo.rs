@@ -0,0 +1,220 @@
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encoding::{self, MEMO_DOMAIN},
+    keys::{self, decode_hex, encode_hex},
+    Chain, ChainError, Keypair, Transaction,
+};
+
+/// Maximum length in bytes of the text of a memo.
+pub const MAX_MEMO_LENGTH: usize = 256;
+
+/// Length in bytes of the authentication tag following the ciphertext of an encrypted memo.
+const TAG_LENGTH: usize = 16;
+
+/// A note attached to a transaction, such as a payment reference.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Memo {
+    /// A memo readable by anyone.
+    Plain {
+        /// The text of the memo.
+        text: String,
+    },
+
+    /// A memo only readable by the receiver, encrypted to the public key of its wallet.
+    Encrypted {
+        /// The hex encoded X25519 public key generated for the memo.
+        ephemeral_key: String,
+
+        /// The hex encoded ciphertext of the text, followed by its authentication tag.
+        ciphertext: String,
+    },
+}
+
+impl Memo {
+    /// Create a memo readable by anyone.
+    ///
+    /// # Arguments
+    /// - `text`: The text of the memo.
+    ///
+    /// # Returns
+    /// The plain memo.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Memo::Plain { text: text.into() }
+    }
+
+    /// Get the length of the text of the memo, counted against `MAX_MEMO_LENGTH`.
+    ///
+    /// # Returns
+    /// The length in bytes of the text, or of the encrypted text without its authentication tag.
+    pub fn size(&self) -> usize {
+        match self {
+            Memo::Plain { text } => text.len(),
+            Memo::Encrypted { ciphertext, .. } => (ciphertext.len() / 2).saturating_sub(TAG_LENGTH),
+        }
+    }
+
+    /// Encrypt a memo to the public key of the receiver.
+    ///
+    /// A key pair is generated for every memo, and the text is encrypted with ChaCha20-Poly1305
+    /// under a key derived from its Diffie-Hellman exchange with the X25519 form of the public
+    /// key, so only the owner of the receiver wallet can read it.
+    ///
+    /// # Arguments
+    /// - `text`: The text of the memo.
+    /// - `public_key`: The hex encoded ed25519 public key of the receiver.
+    ///
+    /// # Returns
+    /// The encrypted memo, or `None` if the public key is invalid.
+    pub fn encrypt(text: &str, public_key: &str) -> Option<Self> {
+        let receiver = keys::exchange_public_key(public_key)?;
+
+        let mut secret = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+
+        let ephemeral = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+        let shared = MontgomeryPoint(receiver).mul_clamped(secret);
+        let ciphertext = cipher(shared, &ephemeral, &receiver)
+            .encrypt(&Nonce::default(), text.as_bytes())
+            .ok()?;
+
+        Some(Memo::Encrypted {
+            ephemeral_key: encode_hex(&ephemeral),
+            ciphertext: encode_hex(&ciphertext),
+        })
+    }
+}
+
+/// Derive the cipher of an encrypted memo from the Diffie-Hellman exchange.
+///
+/// Every memo has its own ephemeral key, so its key is only used once and the nonce is fixed.
+///
+/// # Arguments
+/// - `shared`: The shared secret.
+/// - `ephemeral`: The X25519 public key generated for the memo.
+/// - `receiver`: The X25519 public key of the receiver.
+///
+/// # Returns
+/// The cipher of the memo.
+fn cipher(shared: MontgomeryPoint, ephemeral: &[u8; 32], receiver: &[u8; 32]) -> ChaCha20Poly1305 {
+    let shared = shared.to_bytes();
+    let key = encoding::digest(
+        MEMO_DOMAIN,
+        &[
+            &shared.as_slice(),
+            &ephemeral.as_slice(),
+            &receiver.as_slice(),
+        ],
+    );
+
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+impl Keypair {
+    /// Read the memo of a transaction received by the wallet of the keypair.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// The text of the memo, or `None` if the transaction has no memo or its memo is not
+    /// encrypted to the keypair.
+    pub fn decrypt_memo(&self, transaction: &Transaction) -> Option<String> {
+        let (ephemeral_key, ciphertext) = match transaction.memo.as_ref()? {
+            Memo::Plain { text } => return Some(text.to_owned()),
+            Memo::Encrypted {
+                ephemeral_key,
+                ciphertext,
+            } => (ephemeral_key, ciphertext),
+        };
+
+        let ephemeral: [u8; 32] = decode_hex(ephemeral_key)?.try_into().ok()?;
+        let receiver = keys::exchange_public_key(&self.public_key())?;
+        let shared = MontgomeryPoint(ephemeral).mul_clamped(self.exchange_private_key());
+        let text = cipher(shared, &ephemeral, &receiver)
+            .decrypt(&Nonce::default(), decode_hex(ciphertext)?.as_slice())
+            .ok()?;
+
+        String::from_utf8(text).ok()
+    }
+}
+
+impl Chain {
+    /// Encrypt a memo to the public key of a receiver wallet.
+    ///
+    /// # Arguments
+    /// - `to`: The receiver's address.
+    /// - `text`: The text of the memo.
+    ///
+    /// # Returns
+    /// The encrypted memo, to attach with `TransactionBuilder::memo`, `UnknownReceiver` if the
+    /// receiver is not a wallet, or `InvalidPublicKey` if the wallet has no valid public key.
+    pub fn encrypt_memo(&self, to: &str, text: &str) -> Result<Memo, ChainError> {
+        let wallet = self
+            .get_wallet(to)
+            .ok_or_else(|| ChainError::UnknownReceiver(to.to_string()))?;
+
+        wallet
+            .public_key
+            .as_deref()
+            .and_then(|public_key| Memo::encrypt(text, public_key))
+            .ok_or(ChainError::InvalidPublicKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(memo: Memo) -> Transaction {
+        Transaction::builder()
+            .from("a")
+            .to("b")
+            .amount(1.0)
+            .fee(0.1)
+            .memo(memo)
+            .build()
+    }
+
+    #[test]
+    fn test_decrypt_memo() {
+        let receiver = Keypair::generate();
+        let memo = Memo::encrypt("invoice 42", &receiver.public_key()).unwrap();
+        let encrypted = transaction(memo.clone());
+
+        assert_eq!(
+            receiver.decrypt_memo(&encrypted),
+            Some("invoice 42".to_string())
+        );
+        assert_eq!(Keypair::generate().decrypt_memo(&encrypted), None);
+        assert_ne!(
+            Memo::encrypt("invoice 42", &receiver.public_key()),
+            Some(memo)
+        );
+        assert_eq!(
+            receiver.decrypt_memo(&transaction(Memo::plain("public"))),
+            Some("public".to_string())
+        );
+        assert!(Memo::encrypt("invoice 42", "1234").is_none());
+    }
+
+    #[test]
+    fn test_memo_size() {
+        let receiver = Keypair::generate();
+
+        assert_eq!(Memo::plain("invoice 42").size(), 10);
+        assert_eq!(
+            Memo::encrypt("invoice 42", &receiver.public_key())
+                .unwrap()
+                .size(),
+            10
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::{cmp::Reverse, collections::HashSet, mem};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, Transaction, Wallet};
+
+/// Caps on the approximate memory used by a blockchain, `None` for no cap.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryLimits {
+    /// Maximum amount of bytes used by the pending transactions.
+    ///
+    /// When a new transaction does not fit, pending transactions paying a lower fee are evicted
+    /// and reverted; the new transaction is rejected if not enough of them can be evicted.
+    pub mempool_bytes: Option<usize>,
+
+    /// Maximum amount of bytes used by the wallet transaction histories.
+    ///
+    /// When exceeded after a new block, the oldest mined entries of the longest histories are dropped.
+    pub index_bytes: Option<usize>,
+}
+
+/// Approximate memory used by a blockchain.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// Bytes used by the pending transactions.
+    pub mempool_bytes: usize,
+
+    /// Bytes used by the wallet transaction histories.
+    pub index_bytes: usize,
+
+    /// Bytes used by the index locating the mined transactions in the blocks.
+    #[serde(default)]
+    pub transaction_index_bytes: usize,
+
+    /// Bytes used by the wallets, excluding their transaction histories.
+    pub wallet_bytes: usize,
+
+    /// Bytes used by the transactions stored in block bodies.
+    pub block_bytes: usize,
+
+    /// Amount of pending transactions evicted to respect the mempool cap.
+    pub evicted_transactions: usize,
+
+    /// Amount of history entries dropped to respect the index cap.
+    pub evicted_index_entries: usize,
+}
+
+/// Amount of entries evicted to respect the memory caps.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Evictions {
+    /// Amount of evicted pending transactions.
+    pub(crate) transactions: usize,
+
+    /// Amount of dropped history entries.
+    pub(crate) index_entries: usize,
+}
+
+impl Transaction {
+    /// Get the approximate amount of bytes used by the transaction.
+    ///
+    /// # Returns
+    ///
+    /// The size of the transaction and of its strings.
+    pub fn approximate_size(&self) -> usize {
+        mem::size_of::<Transaction>()
+            + self.hash.capacity()
+            + self.from.capacity()
+            + self.to.capacity()
+            + self.signature.as_ref().map_or(0, String::capacity)
+    }
+}
+
+impl Wallet {
+    /// Get the approximate amount of bytes used by the transaction history of the wallet.
+    ///
+    /// # Returns
+    ///
+    /// The size of the history entries.
+    pub fn approximate_index_size(&self) -> usize {
+        self.transactions
+            .iter()
+            .map(|hash| mem::size_of::<String>() + hash.capacity())
+            .sum()
+    }
+}
+
+impl Chain {
+    /// Get the approximate memory used by the blockchain.
+    ///
+    /// # Returns
+    /// The bytes used by the pending transactions, indices, wallets and blocks, and the amount of evictions.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            mempool_bytes: self.mempool_bytes(),
+            index_bytes: self.index_bytes(),
+            transaction_index_bytes: self.transaction_index.approximate_size(),
+            wallet_bytes: self
+                .wallets
+                .iter()
+                .map(|(address, wallet)| {
+                    address.capacity()
+                        + mem::size_of::<Wallet>()
+                        + wallet.email.capacity()
+                        + wallet.address.capacity()
+                })
+                .sum(),
+            block_bytes: self
+                .chain
+                .iter()
+                .flat_map(|block| &block.transactions)
+                .map(Transaction::approximate_size)
+                .sum(),
+            evicted_transactions: self.evictions.transactions,
+            evicted_index_entries: self.evictions.index_entries,
+        }
+    }
+
+    /// Get the bytes used by the pending transactions.
+    fn mempool_bytes(&self) -> usize {
+        self.current_transactions
+            .iter()
+            .map(Transaction::approximate_size)
+            .sum()
+    }
+
+    /// Get the bytes used by the wallet transaction histories.
+    fn index_bytes(&self) -> usize {
+        self.wallets
+            .values()
+            .map(Wallet::approximate_index_size)
+            .sum()
+    }
+
+    /// Check whether a new pending transaction fits within the mempool cap without evicting any
+    /// pending transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The new pending transaction.
+    ///
+    /// # Returns
+    /// `true` if the mempool has room for the transaction.
+    pub(crate) fn fits_mempool(&self, transaction: &Transaction) -> bool {
+        self.config
+            .memory
+            .mempool_bytes
+            .is_none_or(|limit| self.mempool_bytes() + transaction.approximate_size() <= limit)
+    }
+
+    /// Make room for a new pending transaction within the mempool cap.
+    ///
+    /// Pending transactions paying a lower fee are evicted, lowest fee first. A transaction is only
+    /// evicted if it is the last transaction of its sender, so the nonces stay contiguous, and if
+    /// reverting it keeps the receiver balance non-negative and does not lower the balance of the
+    /// new sender.
+    ///
+    /// # Arguments
+    /// - `transaction`: The new pending transaction.
+    ///
+    /// # Returns
+    /// `true` if the transaction fits in the mempool.
+    pub(crate) fn reserve_mempool(&mut self, transaction: &Transaction) -> bool {
+        let limit = match self.config.memory.mempool_bytes {
+            Some(limit) => limit,
+            None => return true,
+        };
+
+        let size = transaction.approximate_size();
+        let mut used = self.mempool_bytes();
+
+        while used + size > limit {
+            let candidate = self
+                .current_transactions
+                .iter()
+                .enumerate()
+                .filter(|(_, pending)| {
+                    pending.from != "Root"
+                        && pending.key_rotation.is_none()
+                        && pending.asset_id.is_none()
+                        && pending.staking.is_none()
+                        && self.ledger().can_undo(pending)
+                        && self.get_nonce(&pending.from) == Some(pending.nonce + 1)
+                        && pending.fee < transaction.fee
+                        && pending.to != transaction.from
+                        && self
+                            .wallets
+                            .get(&pending.to)
+                            .is_some_and(|wallet| wallet.balance >= pending.amount)
+                })
+                .min_by(|(_, a), (_, b)| a.fee.total_cmp(&b.fee))
+                .map(|(index, _)| index);
+
+            match candidate {
+                Some(index) => used -= self.evict_transaction(index).approximate_size(),
+                None => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Remove a pending transaction and revert its effects on the wallets.
+    ///
+    /// # Arguments
+    /// - `index`: The position of the transaction in the pending transactions.
+    ///
+    /// # Returns
+    /// The evicted transaction.
+    fn evict_transaction(&mut self, index: usize) -> Transaction {
+        let transaction = self.current_transactions.remove(index);
+
+        self.ledger_mut().undo(&transaction);
+
+        if let Some(wallet) = self.wallets.get_mut(&transaction.from) {
+            wallet.balance += transaction.amount;
+            wallet.nonce -= 1;
+            wallet.transactions.retain(|hash| hash != &transaction.hash);
+        }
+
+        if let Some(wallet) = self.wallets.get_mut(transaction.payer()) {
+            wallet.balance += transaction.charged_fee();
+            wallet.transactions.retain(|hash| hash != &transaction.hash);
+        }
+
+        if let Some(wallet) = self.wallets.get_mut(&transaction.to) {
+            wallet.balance -= transaction.amount;
+            wallet.transactions.retain(|hash| hash != &transaction.hash);
+        }
+
+        self.collected_fees -= transaction.charged_fee();
+        self.evictions.transactions += 1;
+
+        transaction
+    }
+
+    /// Drop the oldest mined entries of the longest wallet histories until the index cap is respected.
+    ///
+    /// Entries of pending transactions are always kept.
+    pub(crate) fn apply_index_limit(&mut self) {
+        let limit = match self.config.memory.index_bytes {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        let mut used = self.index_bytes();
+
+        if used <= limit {
+            return;
+        }
+
+        let pending: HashSet<&str> = self
+            .current_transactions
+            .iter()
+            .map(|transaction| transaction.hash.as_str())
+            .collect();
+
+        let mut wallets: Vec<&mut Wallet> = self.wallets.values_mut().collect();
+        wallets.sort_by_key(|wallet| Reverse(wallet.transactions.len()));
+
+        for wallet in wallets {
+            if used <= limit {
+                break;
+            }
+
+            let mut dropped = 0;
+
+            wallet.transactions.retain(|hash| {
+                if used <= limit || pending.contains(hash.as_str()) {
+                    return true;
+                }
+
+                used -= mem::size_of::<String>() + hash.capacity();
+                dropped += 1;
+
+                false
+            });
+
+            self.evictions.index_entries += dropped;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_approximate_size() {
+        let transaction = Transaction::builder()
+            .from("a")
+            .to("b")
+            .amount(1.0)
+            .fee(0.0)
+            .build();
+
+        assert_eq!(
+            transaction.approximate_size(),
+            mem::size_of::<Transaction>() + transaction.hash.capacity() + 2
+        );
+    }
+
+    #[test]
+    fn test_wallet_approximate_index_size() {
+        let mut wallet = Wallet::new("s@mail.com".to_string(), "address".to_string(), 0.0);
+
+        assert_eq!(wallet.approximate_index_size(), 0);
+
+        wallet.transactions.push("hash".to_string());
+
+        assert_eq!(
+            wallet.approximate_index_size(),
+            mem::size_of::<String>() + 4
+        );
+    }
+}
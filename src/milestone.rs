@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, ChainError};
+
+/// A height of the blockchain tagged with a name, e.g. a launch or an upgrade.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Milestone {
+    /// The name of the milestone.
+    pub name: String,
+
+    /// The height of the milestone.
+    pub height: usize,
+
+    /// The hash of the block at the height, `None` until the height is reached.
+    pub hash: Option<String>,
+}
+
+impl Chain {
+    /// Tag a height of the blockchain with a name, so it can be referenced without hardcoding it.
+    ///
+    /// The height may not be reached yet, e.g. to announce an upgrade. A milestone names a height
+    /// rather than a block, so it still applies once a reorganization replaces the block.
+    ///
+    /// # Arguments
+    /// - `name`: The unique name of the milestone, e.g. `launch` or `halving-1`.
+    /// - `height`: The height of the milestone.
+    ///
+    /// # Returns
+    /// The previous height of the milestone if the name was already used, or `InvalidMilestone`
+    /// if the name is empty or surrounded by whitespace.
+    pub fn set_milestone(
+        &mut self,
+        name: &str,
+        height: usize,
+    ) -> Result<Option<usize>, ChainError> {
+        if name.is_empty() || name.trim() != name {
+            return Err(ChainError::InvalidMilestone(name.to_string()));
+        }
+
+        Ok(self.milestones.insert(name.to_string(), height))
+    }
+
+    /// Remove a milestone.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the milestone.
+    ///
+    /// # Returns
+    /// The height of the removed milestone, or `None` if no milestone has the name.
+    pub fn remove_milestone(&mut self, name: &str) -> Option<usize> {
+        self.milestones.remove(name)
+    }
+
+    /// Get a milestone by name.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the milestone.
+    ///
+    /// # Returns
+    /// The milestone, or `None` if no milestone has the name.
+    pub fn get_milestone(&self, name: &str) -> Option<Milestone> {
+        self.milestones
+            .get_key_value(name)
+            .map(|(name, height)| self.milestone(name, *height))
+    }
+
+    /// Get the milestones of the blockchain.
+    ///
+    /// # Returns
+    /// The milestones sorted by height, then by name.
+    pub fn milestones(&self) -> Vec<Milestone> {
+        let mut milestones: Vec<Milestone> = self
+            .milestones
+            .iter()
+            .map(|(name, height)| self.milestone(name, *height))
+            .collect();
+
+        milestones.sort_by(|a, b| a.height.cmp(&b.height).then_with(|| a.name.cmp(&b.name)));
+
+        milestones
+    }
+
+    /// Describe a milestone with the hash of the block at its height.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the milestone.
+    /// - `height`: The height of the milestone.
+    ///
+    /// # Returns
+    /// The milestone.
+    fn milestone(&self, name: &str, height: usize) -> Milestone {
+        Milestone {
+            name: name.to_string(),
+            height,
+            hash: self.get_block_hash(height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_milestone() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+
+        assert_eq!(chain.set_milestone("launch", 0), Ok(None));
+        assert_eq!(chain.set_milestone("launch", 1), Ok(Some(0)));
+        assert_eq!(
+            chain.set_milestone("", 1),
+            Err(ChainError::InvalidMilestone(String::new()))
+        );
+        assert_eq!(
+            chain.set_milestone(" launch", 1),
+            Err(ChainError::InvalidMilestone(" launch".to_string()))
+        );
+        assert_eq!(chain.remove_milestone("launch"), Some(1));
+        assert_eq!(chain.remove_milestone("launch"), None);
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    num::NonZeroUsize,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+#[cfg(feature = "tokio")]
+pub use tokio_util::sync::CancellationToken;
+
+use crate::{BlockHeader, Chain, ChainError};
+
+/// The amount of nonces of a header.
+const NONCE_SPACE: u64 = u32::MAX as u64 + 1;
+
+/// A proof-of-work miner splitting the nonce space across worker threads.
+///
+/// Clones share the cancellation flag, so a clone moved to another thread can cancel the work.
+#[derive(Clone, Debug)]
+pub struct Miner {
+    /// The amount of worker threads.
+    threads: usize,
+
+    /// The flag stopping the workers.
+    cancel: Arc<AtomicBool>,
+}
+
+impl Miner {
+    /// Create a new miner.
+    ///
+    /// # Arguments
+    /// - `threads`: The amount of worker threads, or 0 for one per available CPU.
+    ///
+    /// # Returns
+    /// A new `Miner` instance.
+    pub fn new(threads: usize) -> Self {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            threads => threads,
+        };
+
+        Miner {
+            threads,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Get the amount of worker threads.
+    ///
+    /// # Returns
+    /// The amount of worker threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Stop the work in progress, and any later work until the miner is reset.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Clear the cancellation, so the miner can work again.
+    pub fn reset(&self) {
+        self.cancel.store(false, Ordering::Relaxed);
+    }
+
+    /// Check whether the miner is cancelled.
+    ///
+    /// # Returns
+    /// `true` if the miner was cancelled and not reset since.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Perform the proof-of-work process on a header across the worker threads.
+    ///
+    /// Every worker tries its own range of nonces, ignoring the nonce of the header, and moves
+    /// to the next second once its range is exhausted. The first header satisfying the
+    /// difficulty stops the other workers.
+    ///
+    /// # Arguments
+    /// - `header`: The header to mine.
+    ///
+    /// # Returns
+    /// The header satisfying its difficulty, or `None` if the miner was cancelled first.
+    pub fn mine(&self, header: &BlockHeader) -> Option<BlockHeader> {
+        let found = AtomicBool::new(false);
+        let winner = Mutex::new(None);
+
+        thread::scope(|scope| {
+            for nonces in partition(self.threads) {
+                let (found, winner) = (&found, &winner);
+
+                scope.spawn(move || {
+                    let mut candidate = header.clone();
+
+                    loop {
+                        for nonce in nonces.clone() {
+                            if found.load(Ordering::Relaxed) || self.is_cancelled() {
+                                return;
+                            }
+
+                            candidate.nonce = nonce as u32;
+
+                            if candidate.meets_difficulty() {
+                                found.store(true, Ordering::Relaxed);
+                                winner
+                                    .lock()
+                                    .unwrap_or_else(|e| e.into_inner())
+                                    .get_or_insert(candidate);
+
+                                return;
+                            }
+                        }
+
+                        candidate.timestamp += 1;
+                    }
+                });
+            }
+        });
+
+        winner.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Miner {
+    /// Perform the proof-of-work process on the blocking thread pool of the tokio runtime.
+    ///
+    /// The work is stopped when the token is cancelled or the future is dropped, not by
+    /// `cancel()`.
+    ///
+    /// # Arguments
+    /// - `header`: The header to mine.
+    /// - `token`: The token cancelling the work, e.g. once a competing block arrives.
+    ///
+    /// # Returns
+    /// The header satisfying its difficulty, or `None` if the work was cancelled first.
+    pub async fn mine_async(
+        &self,
+        header: BlockHeader,
+        token: CancellationToken,
+    ) -> Option<BlockHeader> {
+        let miner = Miner::new(self.threads);
+        let workers = miner.clone();
+
+        // Stop the workers once the token is cancelled, or the future dropped
+        let token = token.child_token();
+        let _guard = token.clone().drop_guard();
+        let watcher = tokio::spawn(async move {
+            token.cancelled().await;
+            workers.cancel();
+        });
+
+        let mined = tokio::task::spawn_blocking(move || miner.mine(&header)).await;
+
+        watcher.abort();
+
+        mined.ok().flatten()
+    }
+}
+
+/// Split the nonce space into contiguous ranges of about the same size.
+///
+/// # Arguments
+/// - `threads`: The amount of ranges.
+///
+/// # Returns
+/// The ranges, covering every nonce once.
+fn partition(threads: usize) -> Vec<Range<u64>> {
+    let threads = threads as u64;
+    let span = NONCE_SPACE / threads;
+
+    (0..threads)
+        .map(|worker| match worker == threads - 1 {
+            true => worker * span..NONCE_SPACE,
+            false => worker * span..(worker + 1) * span,
+        })
+        .collect()
+}
+
+impl Chain {
+    /// Generate a new block with a multi-threaded proof of work and append it to the blockchain.
+    ///
+    /// The block reward goes to the address of the blockchain.
+    ///
+    /// # Arguments
+    /// - `threads`: The amount of worker threads, or 0 for one per available CPU.
+    ///
+    /// # Returns
+    /// `InvalidDifficulty` if the difficulty of the blockchain cannot be mined.
+    pub fn mine_block_parallel(&mut self, threads: usize) -> Result<(), ChainError> {
+        let miner = Miner::new(threads);
+
+        self.mine_block_with(self.address.to_owned(), |block| {
+            // The miner is not shared, so it cannot be cancelled
+            if let Some(mined) = miner.mine(&block.header) {
+                block.header = mined;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Chain {
+    /// Generate a new block without blocking the async executor and append it to the blockchain.
+    ///
+    /// The proof of work runs on the blocking thread pool with one worker per available CPU,
+    /// and the block reward goes to the address of the blockchain. Callers sharing the
+    /// blockchain behind a lock can build the block with `block_template`, mine it with
+    /// `Miner::mine_async` without holding the lock, and append it with `import_block`.
+    ///
+    /// # Arguments
+    /// - `token`: The token cancelling the work, e.g. once a competing block arrives.
+    ///
+    /// # Returns
+    /// `MiningCancelled` if the token is cancelled first, or `InvalidDifficulty` if the
+    /// difficulty of the blockchain cannot be mined.
+    pub async fn mine_block_async(&mut self, token: CancellationToken) -> Result<(), ChainError> {
+        let mut block = self.block_template(&self.address)?;
+
+        block.header = Miner::new(0)
+            .mine_async(block.header.clone(), token)
+            .await
+            .ok_or(ChainError::MiningCancelled)?;
+
+        self.import_block(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Block;
+
+    #[test]
+    fn test_partition() {
+        for threads in [1, 3, 8] {
+            let ranges = partition(threads);
+
+            assert_eq!(ranges.len(), threads);
+            assert_eq!(ranges[0].start, 0);
+            assert_eq!(ranges[threads - 1].end, NONCE_SPACE);
+            assert!(ranges.windows(2).all(|pair| pair[0].end == pair[1].start));
+        }
+    }
+
+    #[test]
+    fn test_mine() {
+        let block = Block::new("0".to_string(), 2.0);
+        let miner = Miner::new(4);
+        let mined = miner.mine(&block.header).unwrap();
+
+        assert_eq!(miner.threads(), 4);
+        assert!(mined.meets_difficulty());
+        assert_eq!(mined.previous_hash, block.header.previous_hash);
+        assert_eq!(mined.merkle, block.header.merkle);
+    }
+
+    #[test]
+    fn test_mine_cancelled() {
+        // No hash can satisfy the difficulty in time
+        let block = Block::new("0".to_string(), 64.0);
+        let miner = Miner::new(2);
+        let handle = miner.clone();
+
+        thread::spawn(move || handle.cancel());
+
+        assert!(miner.mine(&block.header).is_none());
+        assert!(miner.is_cancelled());
+
+        miner.reset();
+
+        assert!(!miner.is_cancelled());
+    }
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{encoding, ProofSide, ProofStep};
+
+/// Domain of the Merkle mountain range nodes combining two hashes.
+const MMR_NODE_DOMAIN: &str = "blockchain/mmr-node/v1";
+
+/// An append-only accumulator of hashes, committing to all of them with a few peaks.
+///
+/// The leaves form perfect Merkle trees of decreasing sizes, one per bit set in the amount of
+/// leaves, whose roots are the peaks. Appending a leaf only merges the trees of the same size,
+/// and a leaf is proven with the path to the root of its tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleMountainRange {
+    /// The amount of leaves.
+    leaves: usize,
+
+    /// The roots of the trees, from the largest and oldest to the smallest and newest.
+    peaks: Vec<String>,
+}
+
+/// Proof that a hash is a leaf of a Merkle mountain range.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MountainRangeProof {
+    /// Sibling hashes from the leaf to the peak of its tree.
+    pub path: Vec<ProofStep>,
+}
+
+impl MerkleMountainRange {
+    /// Accumulate hashes, in order.
+    ///
+    /// # Arguments
+    /// - `leaves`: The hashes.
+    ///
+    /// # Returns
+    /// The Merkle mountain range of the hashes.
+    pub fn from_leaves<'a>(leaves: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut range = MerkleMountainRange::default();
+
+        for leaf in leaves {
+            range.push(leaf);
+        }
+
+        range
+    }
+
+    /// Append a hash.
+    ///
+    /// # Arguments
+    /// - `leaf`: The hash.
+    pub fn push(&mut self, leaf: &str) {
+        let mut node = leaf.to_string();
+        let mut height = 0;
+
+        // Merge the trees of the same size, one per trailing bit set in the amount of leaves
+        while self.leaves >> height & 1 == 1 {
+            let left = self.peaks.pop().expect("a tree of every set bit");
+
+            node = mmr_node(&left, &node);
+            height += 1;
+        }
+
+        self.peaks.push(node);
+        self.leaves += 1;
+    }
+
+    /// Get the amount of leaves.
+    ///
+    /// # Returns
+    /// The amount of accumulated hashes.
+    pub fn leaves(&self) -> usize {
+        self.leaves
+    }
+
+    /// Get the peaks.
+    ///
+    /// # Returns
+    /// The roots of the trees, from the largest to the smallest.
+    pub fn peaks(&self) -> &[String] {
+        &self.peaks
+    }
+
+    /// Verify that a hash is the leaf at a position.
+    ///
+    /// # Arguments
+    /// - `index`: The position of the leaf, from 0.
+    /// - `leaf`: The hash.
+    /// - `proof`: The path to the peak of the tree of the leaf.
+    ///
+    /// # Returns
+    /// `true` if the path leads from the hash to the peak of the tree holding the position.
+    pub fn verify(&self, index: usize, leaf: &str, proof: &MountainRangeProof) -> bool {
+        if self.peaks.len() != self.leaves.count_ones() as usize {
+            return false;
+        }
+
+        let Some((peak, height, start)) = locate(self.leaves, index) else {
+            return false;
+        };
+
+        // The sides of the siblings are the bits of the position of the leaf in its tree
+        let position = index - start;
+        let sides = proof.path.iter().enumerate().all(|(level, step)| {
+            step.side
+                == match position >> level & 1 {
+                    0 => ProofSide::Right,
+                    _ => ProofSide::Left,
+                }
+        });
+
+        proof.path.len() == height
+            && sides
+            && proof
+                .path
+                .iter()
+                .fold(leaf.to_string(), |hash, step| match step.side {
+                    ProofSide::Left => mmr_node(&step.hash, &hash),
+                    ProofSide::Right => mmr_node(&hash, &step.hash),
+                })
+                == self.peaks[peak]
+    }
+
+    /// Build the proof of a leaf of the Merkle mountain range of hashes.
+    ///
+    /// # Arguments
+    /// - `leaves`: The accumulated hashes, in order.
+    /// - `index`: The position of the proven leaf.
+    ///
+    /// # Returns
+    /// The path to the peak of the tree of the leaf, or `None` if the position is out of range.
+    pub fn prove(leaves: &[String], index: usize) -> Option<MountainRangeProof> {
+        let (_, height, start) = locate(leaves.len(), index)?;
+        let mut level: Vec<String> = leaves[start..start + (1 << height)].to_vec();
+        let mut position = index - start;
+        let mut path = Vec::with_capacity(height);
+
+        while level.len() > 1 {
+            path.push(match position % 2 {
+                0 => ProofStep {
+                    hash: level[position + 1].to_owned(),
+                    side: ProofSide::Right,
+                },
+                _ => ProofStep {
+                    hash: level[position - 1].to_owned(),
+                    side: ProofSide::Left,
+                },
+            });
+
+            level = level
+                .chunks(2)
+                .map(|pair| mmr_node(&pair[0], &pair[1]))
+                .collect();
+            position /= 2;
+        }
+
+        Some(MountainRangeProof { path })
+    }
+}
+
+/// Find the tree holding a leaf.
+///
+/// # Arguments
+/// - `leaves`: The amount of leaves.
+/// - `index`: The position of the leaf.
+///
+/// # Returns
+/// The index of the peak of the tree, its height and the position of its first leaf, or `None`
+/// if the position is out of range.
+fn locate(leaves: usize, index: usize) -> Option<(usize, usize, usize)> {
+    let mut start = 0;
+
+    for (peak, height) in (0..usize::BITS as usize)
+        .rev()
+        .filter(|height| leaves >> height & 1 == 1)
+        .enumerate()
+    {
+        if index < start + (1 << height) {
+            return Some((peak, height, start));
+        }
+
+        start += 1 << height;
+    }
+
+    None
+}
+
+/// Combine two hashes into a node of a Merkle mountain range.
+///
+/// # Arguments
+/// - `left`: The left hash.
+/// - `right`: The right hash.
+///
+/// # Returns
+/// The hash of the node.
+fn mmr_node(left: &str, right: &str) -> String {
+    encoding::hash(MMR_NODE_DOMAIN, &[&left, &right])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_mountain_range() {
+        let leaves: Vec<String> = (0..11).map(|leaf| format!("leaf {}", leaf)).collect();
+        let range = MerkleMountainRange::from_leaves(leaves.iter().map(String::as_str));
+
+        // 11 leaves form trees of 8, 2 and 1 leaves
+        assert_eq!(range.leaves(), 11);
+        assert_eq!(range.peaks().len(), 3);
+        assert_eq!(range.peaks()[2], leaves[10]);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = MerkleMountainRange::prove(&leaves, index).unwrap();
+
+            assert!(range.verify(index, leaf, &proof));
+            assert!(!range.verify(index, "forged", &proof));
+            assert!(!range.verify((index + 1) % 11, leaf, &proof));
+        }
+
+        assert_eq!(MerkleMountainRange::prove(&leaves, 11), None);
+        assert!(!range.verify(11, "leaf 11", &MountainRangeProof::default()));
+    }
+}
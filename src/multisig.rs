@@ -0,0 +1,410 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{keys, Address, Chain, ChainError, Keypair, Transaction, Wallet};
+
+/// A wallet owned by several keys, whose transactions must be approved by at least a threshold
+/// of its owners.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigWallet {
+    /// The address of the wallet.
+    pub address: Address,
+
+    /// The hex encoded public keys of the owners, sorted.
+    pub owners: Vec<String>,
+
+    /// The amount of distinct owners approving a transaction to authorize it.
+    pub threshold: usize,
+}
+
+/// Approval of a transaction by an owner of a multi-signature wallet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approval {
+    /// The hex encoded public key of the owner.
+    pub public_key: String,
+
+    /// The hex encoded signature of the transaction by the owner.
+    pub signature: String,
+}
+
+impl MultiSigWallet {
+    /// Check that a transaction is approved by enough owners of the wallet.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// `InvalidSignature` if an approval is not a valid signature of an owner, or
+    /// `InsufficientApprovals` if fewer distinct owners than the threshold approve it.
+    pub fn check_approvals(&self, transaction: &Transaction) -> Result<(), ChainError> {
+        let mut approvers = HashSet::new();
+
+        for approval in &transaction.approvals {
+            if !self.owners.contains(&approval.public_key)
+                || !keys::verify_approval(approval, transaction)
+            {
+                return Err(ChainError::InvalidSignature);
+            }
+
+            approvers.insert(approval.public_key.as_str());
+        }
+
+        if approvers.len() < self.threshold {
+            return Err(ChainError::InsufficientApprovals {
+                required: self.threshold,
+                actual: approvers.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a keypair is an owner of the wallet.
+    ///
+    /// # Arguments
+    /// - `keypair`: The keypair.
+    ///
+    /// # Returns
+    /// `true` if the public key of the keypair is an owner.
+    fn is_owner(&self, keypair: &Keypair) -> bool {
+        self.owners.contains(&keypair.public_key())
+    }
+}
+
+impl Transaction {
+    /// Approve the transaction as an owner of its multi-signature sender wallet.
+    ///
+    /// Approving twice with the same keypair keeps the first approval.
+    ///
+    /// # Arguments
+    /// - `keypair`: The keypair of the owner.
+    ///
+    /// # Returns
+    /// The approved transaction.
+    pub fn approve(mut self, keypair: &Keypair) -> Self {
+        let public_key = keypair.public_key();
+
+        if !self
+            .approvals
+            .iter()
+            .any(|approval| approval.public_key == public_key)
+        {
+            let signature = keypair.sign(&self);
+
+            self.approvals.push(Approval {
+                public_key,
+                signature,
+            });
+        }
+
+        self
+    }
+}
+
+impl Chain {
+    /// Create a new wallet whose transactions must be approved by `threshold` of its owners.
+    ///
+    /// The address is derived from the owners and the threshold, so the same set of owners
+    /// always gets the same wallet.
+    ///
+    /// # Arguments
+    /// - `email`: The unique user email.
+    /// - `owners`: The hex encoded ed25519 public keys of the owners.
+    /// - `threshold`: The amount of owners approving a transaction, between 1 and the amount
+    ///   of owners.
+    ///
+    /// # Returns
+    /// The address of the wallet, `InvalidPublicKey` if a public key is invalid, or
+    /// `InvalidMultiSig` if the owners are duplicated or the threshold is out of range.
+    pub fn create_multisig_wallet(
+        &mut self,
+        email: String,
+        mut owners: Vec<String>,
+        threshold: usize,
+    ) -> Result<Address, ChainError> {
+        let invalid = |reason: &str| Err(ChainError::InvalidMultiSig(reason.to_string()));
+
+        if !owners.iter().all(|owner| keys::is_valid_public_key(owner)) {
+            return Err(ChainError::InvalidPublicKey);
+        }
+
+        let count = owners.len();
+
+        owners.sort();
+        owners.dedup();
+
+        if owners.len() != count {
+            return invalid("an owner is listed twice");
+        }
+
+        if owners.len() < 2 {
+            return invalid("the wallet must have at least two owners");
+        }
+
+        if threshold == 0 || threshold > owners.len() {
+            return invalid("the threshold must be between 1 and the amount of owners");
+        }
+
+        let address = keys::address_from_public_key(&Chain::hash(&(&owners, threshold)));
+
+        // Keep the existing wallet if the owners are already registered
+        if self.wallets.contains_key(&address) {
+            return Ok(address);
+        }
+
+        self.wallets.insert(
+            address.to_owned(),
+            Wallet::new(
+                self.stored_email(&email).unwrap_or_default(),
+                address.to_owned(),
+                0.0,
+            ),
+        );
+        self.multisig_wallets.insert(
+            address.to_owned(),
+            MultiSigWallet {
+                address: address.to_owned(),
+                owners,
+                threshold,
+            },
+        );
+
+        self.assert_invariants();
+
+        Ok(address)
+    }
+
+    /// Get the owners and the threshold of a multi-signature wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The multi-signature wallet, or `None` if the wallet is not a multi-signature wallet.
+    pub fn get_multisig_wallet(&self, address: &str) -> Option<&MultiSigWallet> {
+        self.multisig_wallets.get(address)
+    }
+
+    /// Propose a transaction from a multi-signature wallet to its owners, approved by the
+    /// proposer.
+    ///
+    /// The transaction is submitted once approved by the threshold of owners, right away if the
+    /// threshold is 1. A wallet has at most one proposed transaction at a time.
+    ///
+    /// # Arguments
+    /// - `from`: The address of the multi-signature wallet.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `proposer`: The keypair of an owner of the wallet.
+    ///
+    /// # Returns
+    /// The hash of the proposed transaction, or the reason it is invalid.
+    pub fn propose_transaction(
+        &mut self,
+        from: &str,
+        to: String,
+        amount: f64,
+        proposer: &Keypair,
+    ) -> Result<String, ChainError> {
+        let wallet = match self.multisig_wallets.get(from) {
+            Some(wallet) => wallet,
+            None => {
+                return Err(ChainError::InvalidMultiSig(
+                    "the sender is not a multi-signature wallet".to_string(),
+                ))
+            }
+        };
+
+        if !wallet.is_owner(proposer) {
+            return Err(ChainError::InvalidSignature);
+        }
+
+        if self.proposals.contains_key(from) {
+            return Err(ChainError::InvalidMultiSig(
+                "a transaction of the wallet is already proposed".to_string(),
+            ));
+        }
+
+        // Validate the transaction before hashing it
+        self.check_transaction(from, &to, amount)?;
+
+        let inputs = self.select_inputs(from, amount + amount * self.fee)?;
+        let nonce = self.get_nonce(from).unwrap_or_default();
+        let transaction = Transaction::builder()
+            .from(from)
+            .to(to)
+            .amount(amount)
+            .fee_rate(self.fee)
+            .nonce(nonce)
+            .inputs(inputs)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build()
+            .approve(proposer);
+        let hash = transaction.hash.to_owned();
+
+        self.proposals.insert(from.to_string(), transaction);
+        self.execute_proposal(from)?;
+
+        Ok(hash)
+    }
+
+    /// Approve a proposed transaction as an owner of its multi-signature sender wallet.
+    ///
+    /// The approval completing the threshold submits the transaction. A transaction rejected on
+    /// submission is discarded, so the owners can propose it again.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the proposed transaction.
+    /// - `keypair`: The keypair of an owner of the wallet.
+    ///
+    /// # Returns
+    /// `true` if the transaction was submitted, `false` if it awaits more approvals, or the
+    /// reason the approval or the transaction is invalid.
+    pub fn approve_transaction(
+        &mut self,
+        hash: &str,
+        keypair: &Keypair,
+    ) -> Result<bool, ChainError> {
+        let address = self.proposal_sender(hash, keypair)?;
+
+        if let Some(transaction) = self.proposals.remove(&address) {
+            self.proposals
+                .insert(address.to_owned(), transaction.approve(keypair));
+        }
+
+        self.execute_proposal(&address)
+    }
+
+    /// Withdraw a proposed transaction as an owner of its multi-signature sender wallet.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the proposed transaction.
+    /// - `keypair`: The keypair of an owner of the wallet.
+    ///
+    /// # Returns
+    /// The withdrawn transaction, or the reason it cannot be withdrawn.
+    pub fn cancel_proposal(
+        &mut self,
+        hash: &str,
+        keypair: &Keypair,
+    ) -> Result<Transaction, ChainError> {
+        let address = self.proposal_sender(hash, keypair)?;
+
+        self.proposals
+            .remove(&address)
+            .ok_or_else(|| ChainError::UnknownProposal(hash.to_string()))
+    }
+
+    /// Get the transaction proposed to the owners of a multi-signature wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The proposed transaction with its approvals, or `None` if no transaction is proposed.
+    pub fn get_proposal(&self, address: &str) -> Option<&Transaction> {
+        self.proposals.get(address)
+    }
+
+    /// Find the sender of a proposed transaction, checking that a keypair owns it.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the proposed transaction.
+    /// - `keypair`: The keypair of an owner of the wallet.
+    ///
+    /// # Returns
+    /// The address of the multi-signature wallet, `UnknownProposal` if no transaction with the
+    /// hash is proposed, or `InvalidSignature` if the keypair is not an owner.
+    fn proposal_sender(&self, hash: &str, keypair: &Keypair) -> Result<Address, ChainError> {
+        let Some(address) = self
+            .proposals
+            .iter()
+            .find(|(_, transaction)| transaction.hash == hash)
+            .map(|(address, _)| address)
+        else {
+            return Err(ChainError::UnknownProposal(hash.to_string()));
+        };
+
+        match self.multisig_wallets.get(address) {
+            Some(wallet) if wallet.is_owner(keypair) => Ok(address.to_owned()),
+            _ => Err(ChainError::InvalidSignature),
+        }
+    }
+
+    /// Submit the transaction proposed by a multi-signature wallet once enough owners approve it.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// `true` if the transaction was submitted, `false` if it awaits more approvals, or the
+    /// reason the submitted transaction is invalid.
+    fn execute_proposal(&mut self, address: &str) -> Result<bool, ChainError> {
+        let (Some(wallet), Some(transaction)) = (
+            self.multisig_wallets.get(address),
+            self.proposals.get(address),
+        ) else {
+            return Ok(false);
+        };
+
+        match wallet.check_approvals(transaction) {
+            Ok(()) => {}
+            Err(ChainError::InsufficientApprovals { .. }) => return Ok(false),
+            Err(err) => return Err(err),
+        }
+
+        if let Some(transaction) = self.proposals.remove(address) {
+            self.submit_transaction(transaction)?;
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_approvals() {
+        let owners = [
+            Keypair::generate(),
+            Keypair::generate(),
+            Keypair::generate(),
+        ];
+        let wallet = MultiSigWallet {
+            address: "multisig".to_string(),
+            owners: owners.iter().map(Keypair::public_key).collect(),
+            threshold: 2,
+        };
+        let transaction = Transaction::builder()
+            .from("multisig")
+            .to("receiver")
+            .amount(1.0)
+            .fee(0.1)
+            .build();
+
+        let approved = transaction
+            .to_owned()
+            .approve(&owners[0])
+            .approve(&owners[0]);
+
+        assert_eq!(approved.approvals.len(), 1);
+        assert_eq!(
+            wallet.check_approvals(&approved),
+            Err(ChainError::InsufficientApprovals {
+                required: 2,
+                actual: 1
+            })
+        );
+        assert!(wallet
+            .check_approvals(&approved.to_owned().approve(&owners[2]))
+            .is_ok());
+        assert_eq!(
+            wallet.check_approvals(&approved.approve(&Keypair::generate())),
+            Err(ChainError::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    node::NodeAccess, Block, ChainError, MempoolMessage, Node, Peer, SecureChannel, Transaction,
+};
+
+/// Timeout of the connections to peers and of every read and write on them.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+impl NodeAccess for Arc<Mutex<Node>> {
+    fn with<R>(&mut self, f: impl FnOnce(&mut Node) -> R) -> R {
+        f(&mut self.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// A connection to a peer, shared with the threads relaying blocks and transactions.
+#[derive(Clone)]
+struct SharedChannel {
+    /// The hex encoded public key of the peer identity.
+    id: String,
+
+    /// The connection, locked for the lifetime of a message and its answer.
+    channel: Arc<Mutex<SecureChannel>>,
+}
+
+impl SharedChannel {
+    /// Share a connection.
+    ///
+    /// # Arguments
+    /// - `channel`: The connection.
+    ///
+    /// # Returns
+    /// The shared connection.
+    fn new(channel: SecureChannel) -> Self {
+        SharedChannel {
+            id: channel.remote_id().to_owned(),
+            channel: Arc::new(Mutex::new(channel)),
+        }
+    }
+}
+
+impl Peer for SharedChannel {
+    fn send(&mut self, message: MempoolMessage) -> Option<MempoolMessage> {
+        self.channel
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .send(message)
+    }
+
+    fn remote_id(&self) -> Option<String> {
+        Some(self.id.to_owned())
+    }
+}
+
+/// The connections of a network node, shared with the threads serving its peers.
+#[derive(Clone, Default)]
+struct Connections {
+    /// The outgoing connections to peers.
+    outbound: Arc<Mutex<Vec<SharedChannel>>>,
+
+    /// The connections on which the peers that connected to this node receive its blocks and
+    /// transactions.
+    subscribers: Arc<Mutex<Vec<SharedChannel>>>,
+}
+
+impl Connections {
+    /// Get the outgoing connections to peers.
+    ///
+    /// # Returns
+    /// The connections, in connection order.
+    fn outbound(&self) -> Vec<SharedChannel> {
+        self.outbound
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the connections to every peer, outgoing or incoming.
+    ///
+    /// # Returns
+    /// The outgoing connections, then the connections of the subscribed peers.
+    fn all(&self) -> Vec<SharedChannel> {
+        let mut peers = self.outbound();
+
+        peers.extend(
+            self.subscribers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .cloned(),
+        );
+
+        peers
+    }
+
+    /// Answer the messages of a peer, relaying its blocks and transactions to the other peers.
+    ///
+    /// # Arguments
+    /// - `node`: The node.
+    /// - `channel`: The connection to the peer.
+    ///
+    /// # Returns
+    /// `true` if the peer subscribed to the blocks and transactions of the node.
+    fn serve(&self, node: &mut Arc<Mutex<Node>>, channel: &mut SecureChannel) -> io::Result<bool> {
+        Node::serve_with(node, channel, |node, from, message| {
+            Node::relay_with(node, &mut self.all(), from, message)
+        })
+    }
+}
+
+/// A node taking part in a network of nodes over encrypted TCP connections.
+///
+/// The node answers the peers connecting to it from background threads, and gossips its new
+/// transactions and blocks to every connected peer. The blocks and transactions received from
+/// a peer are relayed to the other peers, so they reach every node of a connected network.
+///
+/// A node connecting to a peer opens a second connection on which the peer sends its blocks and
+/// transactions, so nodes behind a NAT skip `listen` and still receive them. They catch up with
+/// what they missed by calling `sync` periodically.
+///
+/// Every node of a network must start from the same genesis block, for example from a copy of
+/// the same persisted blockchain.
+pub struct Network {
+    /// The node, shared with the threads serving incoming connections.
+    node: Arc<Mutex<Node>>,
+
+    /// The connections to peers.
+    connections: Connections,
+}
+
+impl Network {
+    /// Create a new network node without any connection.
+    ///
+    /// # Arguments
+    /// - `node`: The node.
+    ///
+    /// # Returns
+    /// A new network node.
+    pub fn new(node: Node) -> Self {
+        Network {
+            node: Arc::new(Mutex::new(node)),
+            connections: Connections::default(),
+        }
+    }
+
+    /// Get the node, locked for the lifetime of the guard.
+    ///
+    /// # Returns
+    /// A guard giving access to the node.
+    pub fn node(&self) -> MutexGuard<'_, Node> {
+        self.node.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the identities of the peers this node connected to.
+    ///
+    /// # Returns
+    /// The hex encoded public keys of the peer identities.
+    pub fn peers(&self) -> Vec<String> {
+        self.connections
+            .outbound()
+            .into_iter()
+            .map(|peer| peer.id)
+            .collect()
+    }
+
+    /// Accept connections from peers in the background.
+    ///
+    /// # Arguments
+    /// - `address`: The address to listen on, as `host:port`.
+    ///
+    /// # Returns
+    /// The address listened on, or an error if it cannot be bound.
+    pub fn listen(&self, address: &str) -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address)?;
+        let local = listener.local_addr()?;
+        let (node, connections) = (self.node.clone(), self.connections.clone());
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (mut node, connections) = (node.clone(), connections.clone());
+
+                thread::spawn(move || {
+                    let identity = node.with(|node| node.identity().clone());
+
+                    // A failing peer only loses its own connection
+                    let Ok(mut channel) = SecureChannel::accept(stream, &identity) else {
+                        return;
+                    };
+
+                    if let Ok(true) = connections.serve(&mut node, &mut channel) {
+                        if channel.set_timeout(Some(TIMEOUT)).is_ok() {
+                            connections
+                                .subscribers
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .push(SharedChannel::new(channel));
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(local)
+    }
+
+    /// Connect to a peer, then fetch its blocks and exchange the pending transactions with it.
+    ///
+    /// The peer then sends its new blocks and transactions on a second connection, answered in
+    /// the background.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the peer, as `host:port`.
+    ///
+    /// # Returns
+    /// An error if the peer is unreachable or the handshake fails.
+    pub fn connect(&mut self, address: &str) -> io::Result<()> {
+        let identity = self.node.with(|node| node.identity().clone());
+        let mut channel = SecureChannel::connect(address, &identity, TIMEOUT)?;
+
+        Node::sync_blocks_with(&mut self.node, &mut channel);
+        Node::sync_mempool_with(&mut self.node, &mut channel);
+
+        let mut subscription = SecureChannel::connect(address, &identity, TIMEOUT)?;
+
+        subscription.write_message(&MempoolMessage::Subscribe)?;
+        subscription.set_timeout(None)?;
+
+        let (mut node, connections) = (self.node.clone(), self.connections.clone());
+
+        thread::spawn(move || {
+            let _ = connections.serve(&mut node, &mut subscription);
+        });
+
+        self.connections
+            .outbound
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(SharedChannel::new(channel));
+
+        Ok(())
+    }
+
+    /// Fetch the missing blocks and exchange the pending transactions with every peer.
+    ///
+    /// # Returns
+    /// The amount of blocks and transactions added locally.
+    pub fn sync(&mut self) -> usize {
+        self.connections
+            .outbound()
+            .iter_mut()
+            .map(|peer| {
+                Node::sync_blocks_with(&mut self.node, peer)
+                    + Node::sync_mempool_with(&mut self.node, peer)
+            })
+            .sum()
+    }
+
+    /// Submit a transaction to the local mempool and send it to every connected peer.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// An error describing why the transaction is invalid, if it is.
+    pub fn broadcast_transaction(&mut self, transaction: Transaction) -> Result<(), ChainError> {
+        Node::broadcast_transaction_with(&mut self.node, &mut self.connections.all(), transaction)
+    }
+
+    /// Mine a new block and send it to every connected peer.
+    ///
+    /// # Returns
+    /// An error if the block cannot be mined.
+    pub fn mine_block(&mut self) -> Result<(), ChainError> {
+        Node::mine_block_with(&mut self.node, &mut self.connections.all())
+    }
+
+    /// Append a block mined outside of the node and send it to every connected peer.
+    ///
+    /// The node is only locked to append the block, so it keeps serving its peers while the
+    /// block is mined from a template of `Chain::block_template`.
+    ///
+    /// # Arguments
+    /// - `block`: The mined block.
+    ///
+    /// # Returns
+    /// `UnknownParent` if another block was appended meanwhile, or an error describing why the
+    /// block is invalid.
+    pub fn submit_block(&mut self, block: Block) -> Result<(), ChainError> {
+        Node::submit_block_with(&mut self.node, &mut self.connections.all(), block)
+    }
+}
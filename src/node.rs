@@ -0,0 +1,1122 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, ChainError, GossipStats, InventoryCache, Keypair, Transaction};
+
+/// Agent string announced to peers.
+pub const AGENT: &str = concat!("blockchain-cli/", env!("CARGO_PKG_VERSION"));
+
+/// Maximum amount of blocks sent in answer to a single request.
+pub const MAX_BLOCKS_PER_MESSAGE: usize = 500;
+
+/// Messages exchanged between nodes to synchronize their mempools and blocks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MempoolMessage {
+    /// Summary of a mempool, answered with the summary of the receiver.
+    Inventory {
+        /// Hashes of the pending transactions.
+        hashes: Vec<String>,
+    },
+
+    /// Request for pending transactions, answered with the transactions found.
+    GetTransactions {
+        /// Hashes of the requested transactions.
+        hashes: Vec<String>,
+    },
+
+    /// Pending transactions, submitted to the mempool of the receiver.
+    Transactions {
+        /// The pending transactions.
+        transactions: Vec<Transaction>,
+    },
+
+    /// A newly mined block, appended to the blockchain of the receiver.
+    ///
+    /// Answered with a `GetBlocks` request if the receiver misses the blocks preceding it.
+    Block {
+        /// The mined block.
+        block: Block,
+    },
+
+    /// Request for the blocks from a height, answered with the blocks found.
+    GetBlocks {
+        /// The height of the first requested block.
+        from: usize,
+    },
+
+    /// Consecutive blocks, appended to the blockchain of the receiver.
+    Blocks {
+        /// The blocks, from the lowest height.
+        blocks: Vec<Block>,
+    },
+
+    /// Introduction of a node, answered with the introduction of the receiver.
+    Hello {
+        /// The hex encoded public key of the node identity.
+        id: String,
+
+        /// The software of the node.
+        agent: String,
+
+        /// The height of the last block of the node.
+        height: usize,
+
+        /// The hash of the consensus rules followed by the node.
+        #[serde(default)]
+        rules: String,
+    },
+
+    /// Request to receive the new blocks and transactions of the receiver on the connection,
+    /// the sender answering the messages of the receiver from then on.
+    Subscribe,
+}
+
+/// Information about a peer, collected when synchronizing with it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// The hex encoded public key of the peer identity.
+    pub id: String,
+
+    /// The software of the peer.
+    pub agent: String,
+
+    /// The height of the last block of the peer.
+    pub height: usize,
+
+    /// The round-trip time of the introduction, or `None` if the peer connected to this node.
+    pub latency: Option<Duration>,
+}
+
+/// Local rules deciding which valid transactions a node relays to and accepts from its peers.
+///
+/// The policy is independent from the consensus rules: transactions submitted locally are
+/// validated by the blockchain only, and a transaction refused by the policy stays valid.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelayPolicy {
+    /// Minimum fee of a relayed transaction.
+    #[serde(default)]
+    pub min_fee: f64,
+
+    /// Maximum approximate size in bytes of a relayed transaction, unlimited if `None`.
+    #[serde(default)]
+    pub max_size: Option<usize>,
+
+    /// Addresses whose transactions are never relayed, as sender or receiver.
+    #[serde(default)]
+    pub banned: HashSet<String>,
+
+    /// Senders whose transactions are relayed regardless of the fee floor and the size limit.
+    #[serde(default)]
+    pub allowed: HashSet<String>,
+}
+
+impl RelayPolicy {
+    /// Check whether a transaction may be relayed.
+    ///
+    /// The ban-list takes precedence over the allow-list.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// `true` if the transaction complies with the policy.
+    pub fn allows(&self, transaction: &Transaction) -> bool {
+        if [
+            transaction.from.as_str(),
+            transaction.to.as_str(),
+            transaction.payer(),
+        ]
+        .iter()
+        .any(|address| self.banned.contains(*address))
+        {
+            return false;
+        }
+
+        if self.allowed.contains(&transaction.from) {
+            return true;
+        }
+
+        transaction.fee >= self.min_fee
+            && self
+                .max_size
+                .is_none_or(|max_size| transaction.approximate_size() <= max_size)
+    }
+}
+
+/// A connection to another node.
+pub trait Peer {
+    /// Send a message to the node.
+    ///
+    /// # Arguments
+    /// - `message`: The message.
+    ///
+    /// # Returns
+    /// The answer of the node, or `None` if the message expects no answer or the node is unreachable.
+    fn send(&mut self, message: MempoolMessage) -> Option<MempoolMessage>;
+
+    /// Get the identity of the node proven by the connection.
+    ///
+    /// # Returns
+    /// The hex encoded public key of the node identity, or `None` if the connection does not
+    /// authenticate the node, in which case the identity it announces is trusted.
+    fn remote_id(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A blockchain exchanging its pending transactions with peers.
+#[derive(Debug)]
+pub struct Node {
+    /// The local blockchain.
+    chain: Chain,
+
+    /// The rules deciding which transactions are relayed.
+    policy: RelayPolicy,
+
+    /// The identity key announced to peers.
+    identity: Keypair,
+
+    /// Identities of the peers allowed to synchronize, any peer if `None`.
+    allowed_peers: Option<HashSet<String>>,
+
+    /// The peers introduced to this node, by identity.
+    peers: BTreeMap<String, PeerInfo>,
+
+    /// Hashes of the transactions received from peers and already processed.
+    seen: InventoryCache,
+
+    /// Hashes of the transactions known to every peer, by identity.
+    inventories: HashMap<String, InventoryCache>,
+
+    /// Counters of the avoided duplicates.
+    gossip: GossipStats,
+}
+
+impl Node {
+    /// Create a new node.
+    ///
+    /// # Arguments
+    /// - `chain`: The local blockchain.
+    ///
+    /// # Returns
+    /// A new node serving the blockchain.
+    pub fn new(chain: Chain) -> Self {
+        Node::with_policy(chain, RelayPolicy::default())
+    }
+
+    /// Create a new node with a relay policy.
+    ///
+    /// # Arguments
+    /// - `chain`: The local blockchain.
+    /// - `policy`: The rules deciding which transactions are relayed.
+    ///
+    /// # Returns
+    /// A new node serving the blockchain, with a random identity.
+    pub fn with_policy(chain: Chain, policy: RelayPolicy) -> Self {
+        Node {
+            chain,
+            policy,
+            identity: Keypair::generate(),
+            allowed_peers: None,
+            peers: BTreeMap::new(),
+            seen: InventoryCache::default(),
+            inventories: HashMap::new(),
+            gossip: GossipStats::default(),
+        }
+    }
+
+    /// Get the identity key of the node.
+    ///
+    /// # Returns
+    /// A reference to the identity keypair.
+    pub fn identity(&self) -> &Keypair {
+        &self.identity
+    }
+
+    /// Update the identity key of the node, typically loaded with `Keypair::load_or_generate`.
+    ///
+    /// # Arguments
+    /// - `identity`: The new identity keypair.
+    pub fn set_identity(&mut self, identity: Keypair) {
+        self.identity = identity;
+    }
+
+    /// Restrict the peers allowed to synchronize with the node.
+    ///
+    /// # Arguments
+    /// - `allowed`: The hex encoded public keys of the allowed peer identities, or `None` to allow any peer.
+    pub fn set_allowed_peers(&mut self, allowed: Option<HashSet<String>>) {
+        self.allowed_peers = allowed;
+    }
+
+    /// Check whether a peer is allowed to synchronize with the node.
+    ///
+    /// # Arguments
+    /// - `id`: The hex encoded public key of the peer identity.
+    ///
+    /// # Returns
+    /// `true` if the peer is allowed.
+    pub fn is_allowed_peer(&self, id: &str) -> bool {
+        self.allowed_peers
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(id))
+    }
+
+    /// Get the information about the peers introduced to this node.
+    ///
+    /// # Returns
+    /// The peers, ordered by identity.
+    pub fn peer_info(&self) -> Vec<PeerInfo> {
+        self.peers.values().cloned().collect()
+    }
+
+    /// Get the counters of the duplicate transactions suppressed by the gossip layer.
+    ///
+    /// # Returns
+    /// A reference to the gossip counters.
+    pub fn gossip_stats(&self) -> &GossipStats {
+        &self.gossip
+    }
+
+    /// Get the relay policy.
+    ///
+    /// # Returns
+    /// A reference to the relay policy.
+    pub fn relay_policy(&self) -> &RelayPolicy {
+        &self.policy
+    }
+
+    /// Update the relay policy, without affecting the transactions already pending.
+    ///
+    /// # Arguments
+    /// - `policy`: The new relay policy.
+    pub fn set_relay_policy(&mut self, policy: RelayPolicy) {
+        self.policy = policy;
+    }
+
+    /// Get the local blockchain.
+    ///
+    /// # Returns
+    /// A reference to the blockchain.
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Get the local blockchain for updates.
+    ///
+    /// # Returns
+    /// A mutable reference to the blockchain.
+    pub fn chain_mut(&mut self) -> &mut Chain {
+        &mut self.chain
+    }
+
+    /// Handle a message received from a peer.
+    ///
+    /// # Arguments
+    /// - `message`: The message.
+    ///
+    /// # Returns
+    /// The answer to send back, or `None` if the message expects no answer.
+    pub fn handle(&mut self, message: MempoolMessage) -> Option<MempoolMessage> {
+        match message {
+            MempoolMessage::Inventory { .. } => Some(MempoolMessage::Inventory {
+                hashes: self.mempool_hashes(),
+            }),
+            MempoolMessage::GetTransactions { hashes } => {
+                let hashes: HashSet<String> = hashes.into_iter().collect();
+
+                Some(MempoolMessage::Transactions {
+                    transactions: self
+                        .relayable_transactions()
+                        .filter(|transaction| hashes.contains(&transaction.hash))
+                        .cloned()
+                        .collect(),
+                })
+            }
+            MempoolMessage::Transactions { transactions } => {
+                self.accept_transactions(transactions);
+
+                None
+            }
+            MempoolMessage::Block { block } => {
+                if !self.seen.insert(&block.compute_hash()) {
+                    self.gossip.duplicates_received += 1;
+                    return None;
+                }
+
+                match self.chain.import_block(block) {
+                    Err(ChainError::UnknownParent) => Some(MempoolMessage::GetBlocks {
+                        from: self.chain.chain.len(),
+                    }),
+                    _ => None,
+                }
+            }
+            MempoolMessage::GetBlocks { from } => Some(MempoolMessage::Blocks {
+                blocks: self.blocks_from(from),
+            }),
+            MempoolMessage::Blocks { blocks } => {
+                self.import_blocks(blocks);
+
+                None
+            }
+            MempoolMessage::Hello {
+                id,
+                agent,
+                height,
+                rules,
+            } => {
+                let info = PeerInfo {
+                    id,
+                    agent,
+                    height,
+                    latency: None,
+                };
+
+                self.record_peer(info, &rules).then(|| self.hello())
+            }
+            MempoolMessage::Subscribe => None,
+        }
+    }
+
+    /// Exchange the pending transactions with a peer, typically right after connecting to it.
+    ///
+    /// The nodes introduce themselves first, and peers that are not allowed, do not answer,
+    /// or announce an identity other than the one proven by the connection are skipped.
+    /// The mempool summaries are exchanged next, then the missing transactions are fetched from
+    /// the peer and the transactions it misses are pushed to it. Transactions that are already
+    /// known, invalid, not payable from the local balances, or refused by the relay policy are skipped.
+    ///
+    /// The hashes received from or sent to every peer are cached, so transactions already
+    /// processed are not requested again and transactions already sent are not sent again.
+    ///
+    /// # Arguments
+    /// - `peer`: The connection to the peer.
+    ///
+    /// # Returns
+    /// The amount of transactions added to the local mempool.
+    pub fn sync_mempool<P: Peer>(&mut self, peer: &mut P) -> usize {
+        Node::sync_mempool_with(self, peer)
+    }
+
+    /// Submit a transaction to the local mempool and send it to peers.
+    ///
+    /// Peers that already have the transaction are skipped.
+    ///
+    /// # Arguments
+    /// - `peers`: The connections to the peers.
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// An error describing why the transaction is invalid, if it is.
+    pub fn broadcast_transaction<P: Peer>(
+        &mut self,
+        peers: &mut [P],
+        transaction: Transaction,
+    ) -> Result<(), ChainError> {
+        Node::broadcast_transaction_with(self, peers, transaction)
+    }
+
+    /// Mine a new block and send it to peers.
+    ///
+    /// Peers missing the blocks preceding it are sent those first.
+    ///
+    /// # Arguments
+    /// - `peers`: The connections to the peers.
+    ///
+    /// # Returns
+    /// An error if the block cannot be mined.
+    pub fn mine_block<P: Peer>(&mut self, peers: &mut [P]) -> Result<(), ChainError> {
+        Node::mine_block_with(self, peers)
+    }
+
+    /// Append a block mined outside of the node, for example from `Chain::block_template`, and
+    /// send it to peers.
+    ///
+    /// # Arguments
+    /// - `peers`: The connections to the peers.
+    /// - `block`: The mined block.
+    ///
+    /// # Returns
+    /// `UnknownParent` if another block was appended meanwhile, or an error describing why the
+    /// block is invalid.
+    pub fn submit_block<P: Peer>(
+        &mut self,
+        peers: &mut [P],
+        block: Block,
+    ) -> Result<(), ChainError> {
+        Node::submit_block_with(self, peers, block)
+    }
+
+    /// Relay the blocks and transactions received from a peer to the other peers, once handled
+    /// and accepted by the node.
+    ///
+    /// # Arguments
+    /// - `peers`: The connections to the peers, the sender included or not.
+    /// - `from`: The identity of the peer the message was received from, which is skipped.
+    /// - `message`: The message received from the peer.
+    pub fn relay<P: Peer>(&mut self, peers: &mut [P], from: &str, message: MempoolMessage) {
+        Node::relay_with(self, peers, from, message)
+    }
+
+    /// Request the blocks missing locally from a peer and append them to the blockchain.
+    ///
+    /// If the blocks of the peer do not extend the local blockchain, the whole blockchain of
+    /// the peer is fetched and replaces the local one if it carries more work.
+    ///
+    /// # Arguments
+    /// - `peer`: The connection to the peer.
+    ///
+    /// # Returns
+    /// The amount of blocks appended to the blockchain.
+    pub fn sync_blocks<P: Peer>(&mut self, peer: &mut P) -> usize {
+        Node::sync_blocks_with(self, peer)
+    }
+
+    /// Exchange the pending transactions with a peer, accessing the node between messages only.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peer`: The connection to the peer.
+    ///
+    /// # Returns
+    /// The amount of transactions added to the local mempool.
+    pub(crate) fn sync_mempool_with<A: NodeAccess, P: Peer>(node: &mut A, peer: &mut P) -> usize {
+        let Some(id) = Node::introduce_with(node, peer) else {
+            return 0;
+        };
+
+        let hashes = node.with(|node| node.mempool_hashes());
+
+        let Some(MempoolMessage::Inventory { hashes: remote }) =
+            peer.send(MempoolMessage::Inventory { hashes })
+        else {
+            return 0;
+        };
+
+        // Fetch the transactions missing locally
+        let missing = node.with(|node| node.missing_transactions(&id, &remote));
+
+        let accepted = match missing.is_empty() {
+            true => 0,
+            false => match peer.send(MempoolMessage::GetTransactions { hashes: missing }) {
+                Some(MempoolMessage::Transactions { transactions }) => {
+                    node.with(|node| node.accept_transactions(transactions))
+                }
+                _ => 0,
+            },
+        };
+
+        // Push the transactions missing on the peer
+        let transactions = node.with(|node| node.unsent_transactions(&id));
+
+        if !transactions.is_empty() {
+            peer.send(MempoolMessage::Transactions { transactions });
+        }
+
+        accepted
+    }
+
+    /// Submit a transaction and send it to peers, accessing the node between messages only.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peers`: The connections to the peers.
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// An error describing why the transaction is invalid, if it is.
+    pub(crate) fn broadcast_transaction_with<A: NodeAccess, P: Peer>(
+        node: &mut A,
+        peers: &mut [P],
+        transaction: Transaction,
+    ) -> Result<(), ChainError> {
+        let relayed = node.with(|node| {
+            node.chain.submit_transaction(transaction.clone())?;
+            node.seen.insert(&transaction.hash);
+
+            Ok(node.policy.allows(&transaction))
+        })?;
+
+        if relayed {
+            Node::relay_transactions_with(node, peers, None, vec![transaction]);
+        }
+
+        Ok(())
+    }
+
+    /// Mine a new block and send it to peers, accessing the node between messages only.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peers`: The connections to the peers.
+    ///
+    /// # Returns
+    /// An error if the block cannot be mined.
+    pub(crate) fn mine_block_with<A: NodeAccess, P: Peer>(
+        node: &mut A,
+        peers: &mut [P],
+    ) -> Result<(), ChainError> {
+        let Some(block) = node.with(|node| {
+            node.chain.generate_new_block()?;
+
+            let block = node.chain.chain.last().cloned();
+
+            if let Some(block) = &block {
+                node.seen.insert(&block.hash());
+            }
+
+            Ok::<_, ChainError>(block)
+        })?
+        else {
+            return Ok(());
+        };
+
+        Node::relay_block_with(node, peers, None, block);
+
+        Ok(())
+    }
+
+    /// Append a block mined outside of the node and send it to peers, accessing the node
+    /// between messages only.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peers`: The connections to the peers.
+    /// - `block`: The mined block.
+    ///
+    /// # Returns
+    /// `UnknownParent` if another block was appended meanwhile, or an error describing why the
+    /// block is invalid.
+    pub(crate) fn submit_block_with<A: NodeAccess, P: Peer>(
+        node: &mut A,
+        peers: &mut [P],
+        block: Block,
+    ) -> Result<(), ChainError> {
+        let block = node.with(|node| {
+            node.chain.import_block(block)?;
+
+            let block = node.chain.chain.last().cloned();
+
+            if let Some(block) = &block {
+                node.seen.insert(&block.hash());
+            }
+
+            Ok::<_, ChainError>(block)
+        })?;
+
+        if let Some(block) = block {
+            Node::relay_block_with(node, peers, None, block);
+        }
+
+        Ok(())
+    }
+
+    /// Relay the blocks and transactions received from a peer to the other peers, once accepted
+    /// by the node.
+    ///
+    /// Peers that already have them are skipped, so the relay stops once every node of the
+    /// network has them.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peers`: The connections to the peers.
+    /// - `from`: The identity of the peer the message was received from.
+    /// - `message`: The message received from the peer.
+    pub(crate) fn relay_with<A: NodeAccess, P: Peer>(
+        node: &mut A,
+        peers: &mut [P],
+        from: &str,
+        message: MempoolMessage,
+    ) {
+        match message {
+            MempoolMessage::Block { block } => {
+                let hash = block.compute_hash();
+                let accepted = node.with(|node| {
+                    node.chain
+                        .chain
+                        .iter()
+                        .rev()
+                        .find(|block| block.hash() == hash)
+                        .cloned()
+                });
+
+                if let Some(block) = accepted {
+                    Node::relay_block_with(node, peers, Some(from), block);
+                }
+            }
+            MempoolMessage::Transactions { transactions } => {
+                let accepted = node.with(|node| {
+                    transactions
+                        .into_iter()
+                        .filter(|transaction| {
+                            node.policy.allows(transaction)
+                                && node
+                                    .chain
+                                    .current_transactions
+                                    .iter()
+                                    .any(|pending| pending.hash == transaction.hash)
+                        })
+                        .collect::<Vec<_>>()
+                });
+
+                if !accepted.is_empty() {
+                    Node::relay_transactions_with(node, peers, Some(from), accepted);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Send transactions of the node to the peers they were not sent to yet.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peers`: The connections to the peers.
+    /// - `from`: The identity of the peer the transactions were received from, which is skipped.
+    /// - `transactions`: The transactions.
+    fn relay_transactions_with<A: NodeAccess, P: Peer>(
+        node: &mut A,
+        peers: &mut [P],
+        from: Option<&str>,
+        transactions: Vec<Transaction>,
+    ) {
+        for peer in peers.iter_mut() {
+            let id = peer.remote_id();
+
+            if from.is_some() && id.as_deref() == from {
+                continue;
+            }
+
+            let unsent: Vec<Transaction> = node.with(|node| {
+                transactions
+                    .iter()
+                    .filter(|transaction| node.mark_sent(id.clone(), &transaction.hash))
+                    .cloned()
+                    .collect()
+            });
+
+            if !unsent.is_empty() {
+                peer.send(MempoolMessage::Transactions {
+                    transactions: unsent,
+                });
+            }
+        }
+    }
+
+    /// Send a block of the node to the peers it was not sent to yet.
+    ///
+    /// Peers missing the blocks preceding it are sent those first.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peers`: The connections to the peers.
+    /// - `from`: The identity of the peer the block was received from, which is skipped.
+    /// - `block`: The block.
+    fn relay_block_with<A: NodeAccess, P: Peer>(
+        node: &mut A,
+        peers: &mut [P],
+        from: Option<&str>,
+        block: Block,
+    ) {
+        let hash = block.hash();
+
+        for peer in peers.iter_mut() {
+            let id = peer.remote_id();
+
+            if from.is_some() && id.as_deref() == from {
+                continue;
+            }
+
+            if !node.with(|node| node.mark_sent(id, &hash)) {
+                continue;
+            }
+
+            if let Some(MempoolMessage::GetBlocks { from }) = peer.send(MempoolMessage::Block {
+                block: block.clone(),
+            }) {
+                Node::send_blocks_with(node, peer, from);
+            }
+        }
+    }
+
+    /// Request the blocks missing locally from a peer, accessing the node between messages only.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peer`: The connection to the peer.
+    ///
+    /// # Returns
+    /// The amount of blocks appended to the blockchain.
+    pub(crate) fn sync_blocks_with<A: NodeAccess, P: Peer>(node: &mut A, peer: &mut P) -> usize {
+        let mut imported = 0;
+
+        loop {
+            let from = node.with(|node| node.chain.chain.len());
+
+            let Some(MempoolMessage::Blocks { blocks }) =
+                peer.send(MempoolMessage::GetBlocks { from })
+            else {
+                return imported;
+            };
+
+            let count = blocks.len();
+            let appended = node.with(|node| node.import_blocks(blocks));
+
+            imported += appended;
+
+            // The peer is on another branch, which replaces the local one if it has more work
+            if appended == 0 && count > 0 {
+                return imported + Node::resolve_fork_with(node, peer);
+            }
+
+            if appended == 0 || appended < count {
+                return imported;
+            }
+        }
+    }
+
+    /// Fetch the whole blockchain of a peer and switch to it if it carries more work.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peer`: The connection to the peer.
+    ///
+    /// # Returns
+    /// The amount of blocks applied from the blockchain of the peer.
+    fn resolve_fork_with<A: NodeAccess, P: Peer>(node: &mut A, peer: &mut P) -> usize {
+        let mut candidate = vec![];
+
+        loop {
+            let Some(MempoolMessage::Blocks { blocks }) = peer.send(MempoolMessage::GetBlocks {
+                from: candidate.len(),
+            }) else {
+                return 0;
+            };
+
+            let count = blocks.len();
+
+            candidate.extend(blocks);
+
+            if count < MAX_BLOCKS_PER_MESSAGE {
+                break;
+            }
+        }
+
+        node.with(|node| node.chain.replace_chain(candidate).unwrap_or(0))
+    }
+
+    /// Send the blocks from a height to a peer, until it has every local block.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peer`: The connection to the peer.
+    /// - `from`: The height of the first block missing on the peer.
+    fn send_blocks_with<A: NodeAccess, P: Peer>(node: &mut A, peer: &mut P, from: usize) {
+        let mut from = from;
+
+        loop {
+            let blocks = node.with(|node| node.blocks_from(from));
+
+            if blocks.is_empty() {
+                return;
+            }
+
+            from += blocks.len();
+            peer.send(MempoolMessage::Blocks { blocks });
+        }
+    }
+
+    /// Exchange introductions with a peer and record its information.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `peer`: The connection to the peer.
+    ///
+    /// # Returns
+    /// The identity of the peer, or `None` if it did not introduce itself with its proven
+    /// identity, follows other consensus rules, or is not allowed.
+    fn introduce_with<A: NodeAccess, P: Peer>(node: &mut A, peer: &mut P) -> Option<String> {
+        let hello = node.with(|node| node.hello());
+        let started = Instant::now();
+
+        let Some(MempoolMessage::Hello {
+            id,
+            agent,
+            height,
+            rules,
+        }) = peer.send(hello)
+        else {
+            return None;
+        };
+
+        let latency = started.elapsed();
+
+        if peer.remote_id().is_some_and(|remote| remote != id) {
+            return None;
+        }
+
+        let info = PeerInfo {
+            id: id.to_owned(),
+            agent,
+            height,
+            latency: Some(latency),
+        };
+
+        node.with(|node| node.record_peer(info, &rules))
+            .then_some(id)
+    }
+
+    /// Get the blocks from a height, up to the maximum amount per message.
+    ///
+    /// # Arguments
+    /// - `from`: The height of the first block.
+    ///
+    /// # Returns
+    /// The blocks, from the lowest height.
+    fn blocks_from(&self, from: usize) -> Vec<Block> {
+        self.chain
+            .chain
+            .iter()
+            .skip(from)
+            .take(MAX_BLOCKS_PER_MESSAGE)
+            .cloned()
+            .collect()
+    }
+
+    /// Append consecutive blocks received from a peer, stopping at the first rejected block.
+    ///
+    /// # Arguments
+    /// - `blocks`: The blocks, from the lowest height.
+    ///
+    /// # Returns
+    /// The amount of blocks appended.
+    fn import_blocks(&mut self, blocks: Vec<Block>) -> usize {
+        let mut imported = 0;
+
+        for block in blocks {
+            let hash = block.hash();
+
+            if self.chain.import_block(block).is_err() {
+                break;
+            }
+
+            self.seen.insert(&hash);
+            imported += 1;
+        }
+
+        imported
+    }
+
+    /// Record the inventory of a peer and get the transactions to request from it.
+    ///
+    /// # Arguments
+    /// - `id`: The identity of the peer.
+    /// - `remote`: The hashes of the pending transactions of the peer.
+    ///
+    /// # Returns
+    /// The hashes of the transactions neither known nor already processed.
+    fn missing_transactions(&mut self, id: &str, remote: &[String]) -> Vec<String> {
+        let inventory = self.inventories.entry(id.to_owned()).or_default();
+
+        for hash in remote {
+            inventory.insert(hash);
+        }
+
+        remote
+            .iter()
+            .filter(|hash| match self.seen.contains(hash) {
+                true => {
+                    self.gossip.requests_suppressed += 1;
+                    false
+                }
+                false => !self.knows_transaction(hash),
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Get the relayable transactions a peer does not have and record them as sent.
+    ///
+    /// # Arguments
+    /// - `id`: The identity of the peer.
+    ///
+    /// # Returns
+    /// The transactions to send, in submission order.
+    fn unsent_transactions(&mut self, id: &str) -> Vec<Transaction> {
+        let inventory = self.inventories.entry(id.to_owned()).or_default();
+        let mut transactions = Vec::new();
+
+        for transaction in &self.chain.current_transactions {
+            if !self.policy.allows(transaction) {
+                continue;
+            }
+
+            match inventory.insert(&transaction.hash) {
+                true => transactions.push(transaction.clone()),
+                false => self.gossip.sends_suppressed += 1,
+            }
+        }
+
+        transactions
+    }
+
+    /// Record that a hash is sent to a peer.
+    ///
+    /// # Arguments
+    /// - `id`: The proven identity of the peer, if any.
+    /// - `hash`: The hash of the transaction or block.
+    ///
+    /// # Returns
+    /// `false` if the peer already has the hash, which is then not sent again.
+    fn mark_sent(&mut self, id: Option<String>, hash: &str) -> bool {
+        let Some(id) = id else {
+            return true;
+        };
+
+        match self.inventories.entry(id).or_default().insert(hash) {
+            true => true,
+            false => {
+                self.gossip.sends_suppressed += 1;
+                false
+            }
+        }
+    }
+
+    /// Record the information of a peer introducing itself.
+    ///
+    /// # Arguments
+    /// - `info`: The information of the peer.
+    /// - `rules`: The hash of the consensus rules followed by the peer.
+    ///
+    /// # Returns
+    /// `false` if the peer is not allowed or follows other consensus rules, in which case it is
+    /// not recorded.
+    fn record_peer(&mut self, info: PeerInfo, rules: &str) -> bool {
+        if !self.is_allowed_peer(&info.id) || rules != self.chain.get_consensus_rules().hash() {
+            return false;
+        }
+
+        self.peers.insert(info.id.to_owned(), info);
+
+        true
+    }
+
+    /// Get the introduction of the node.
+    ///
+    /// # Returns
+    /// The introduction message.
+    fn hello(&self) -> MempoolMessage {
+        MempoolMessage::Hello {
+            id: self.identity.public_key(),
+            agent: AGENT.to_string(),
+            height: self.chain.chain.len().saturating_sub(1),
+            rules: self.chain.get_consensus_rules().hash(),
+        }
+    }
+
+    /// Get the hashes of the pending transactions allowed by the relay policy.
+    ///
+    /// # Returns
+    /// The hashes, in submission order.
+    fn mempool_hashes(&self) -> Vec<String> {
+        self.relayable_transactions()
+            .map(|transaction| transaction.hash.to_owned())
+            .collect()
+    }
+
+    /// Get the pending transactions allowed by the relay policy.
+    ///
+    /// # Returns
+    /// An iterator over the transactions, in submission order.
+    fn relayable_transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.chain
+            .current_transactions
+            .iter()
+            .filter(|transaction| self.policy.allows(transaction))
+    }
+
+    /// Check whether a transaction is pending or mined locally.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// `true` if the transaction is known.
+    fn knows_transaction(&self, hash: &str) -> bool {
+        self.chain
+            .current_transactions
+            .iter()
+            .chain(
+                self.chain
+                    .chain
+                    .iter()
+                    .flat_map(|block| &block.transactions),
+            )
+            .any(|transaction| transaction.hash == hash)
+    }
+
+    /// Submit transactions received from a peer and allowed by the relay policy to the local mempool.
+    ///
+    /// Transactions already processed recently are dropped without being validated again.
+    ///
+    /// # Arguments
+    /// - `transactions`: The transactions.
+    ///
+    /// # Returns
+    /// The amount of transactions added.
+    fn accept_transactions(&mut self, transactions: Vec<Transaction>) -> usize {
+        let mut accepted = 0;
+
+        for transaction in transactions {
+            if !self.seen.insert(&transaction.hash) {
+                self.gossip.duplicates_received += 1;
+                continue;
+            }
+
+            if self.policy.allows(&transaction)
+                && !self.knows_transaction(&transaction.hash)
+                && self.chain.submit_transaction(transaction).is_ok()
+            {
+                accepted += 1;
+            }
+        }
+
+        accepted
+    }
+}
+
+/// Access to a node, either exclusive or shared with the threads serving its peers.
+///
+/// Operations talking to peers only access the node between two messages, so a node shared
+/// behind a lock keeps answering its peers while waiting for their answers.
+pub(crate) trait NodeAccess {
+    /// Run a closure with exclusive access to the node.
+    ///
+    /// # Arguments
+    /// - `f`: The closure.
+    ///
+    /// # Returns
+    /// The result of the closure.
+    fn with<R>(&mut self, f: impl FnOnce(&mut Node) -> R) -> R;
+}
+
+impl NodeAccess for Node {
+    fn with<R>(&mut self, f: impl FnOnce(&mut Node) -> R) -> R {
+        f(self)
+    }
+}
+
+impl Peer for Node {
+    fn send(&mut self, message: MempoolMessage) -> Option<MempoolMessage> {
+        self.handle(message)
+    }
+
+    fn remote_id(&self) -> Option<String> {
+        Some(self.identity.public_key())
+    }
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, Transaction};
+
+/// Summary of a blockchain for the home page of an explorer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChainOverview {
+    /// Height of the last block.
+    pub height: usize,
+
+    /// The latest blocks, newest first.
+    pub latest_blocks: Vec<Block>,
+
+    /// The latest mined transactions, newest first.
+    pub latest_transactions: Vec<Transaction>,
+
+    /// Amount of transactions waiting to be mined.
+    pub mempool_size: usize,
+
+    /// Current difficulty level of the network.
+    pub difficulty: f64,
+
+    /// Estimated amount of hashes per second, `None` if the blocks were mined within the same second.
+    pub hashrate: Option<f64>,
+
+    /// Total amount of funds issued to wallets.
+    pub supply: f64,
+}
+
+impl Chain {
+    /// Get a summary of the blockchain in a single read.
+    ///
+    /// # Arguments
+    /// - `count`: The amount of latest blocks and transactions to include.
+    ///
+    /// # Returns
+    /// The latest blocks and transactions, the mempool size, the difficulty, the hashrate estimate, and the supply.
+    pub fn overview(&self, count: usize) -> ChainOverview {
+        ChainOverview {
+            height: self.chain.len().saturating_sub(1),
+            latest_blocks: self.chain.iter().rev().take(count).cloned().collect(),
+            latest_transactions: self
+                .chain
+                .iter()
+                .rev()
+                .flat_map(|block| block.transactions.iter().rev())
+                .take(count)
+                .cloned()
+                .collect(),
+            mempool_size: self.current_transactions.len(),
+            difficulty: self.difficulty,
+            hashrate: self.estimate_hashrate(count),
+            supply: self.issued_supply,
+        }
+    }
+
+    /// Estimate the hashrate from the latest blocks.
+    ///
+    /// A block at difficulty `d` needs a hash below a target of `2^(256 - 4d)`, which takes
+    /// `16^d` attempts on average.
+    ///
+    /// # Arguments
+    /// - `count`: The amount of latest blocks to consider.
+    ///
+    /// # Returns
+    /// The estimated amount of hashes per second, or `None` if there are not enough blocks or no time elapsed.
+    fn estimate_hashrate(&self, count: usize) -> Option<f64> {
+        let start = self.chain.len().saturating_sub(count.max(2));
+        let blocks = &self.chain[start..];
+
+        let first = blocks.first()?;
+        let last = blocks.last()?;
+        let elapsed = last.header.timestamp - first.header.timestamp;
+
+        if elapsed <= 0 {
+            return None;
+        }
+
+        let hashes: f64 = blocks[1..].iter().map(Block::work).sum();
+
+        Some(hashes / elapsed as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_hashrate() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chain[0].header.timestamp = 0;
+        chain.chain[1].header.timestamp = 2;
+        chain.chain[2].header.timestamp = 4;
+
+        assert_eq!(chain.estimate_hashrate(3), Some(8.0));
+        assert_eq!(chain.estimate_hashrate(1), Some(8.0));
+    }
+
+    #[test]
+    fn test_estimate_hashrate_same_second() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        chain.generate_new_block().unwrap();
+
+        chain.chain[1].header.timestamp = chain.chain[0].header.timestamp;
+
+        assert_eq!(chain.estimate_hashrate(2), None);
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, ChainError};
+
+/// Default amount of blocks queued between two stages of the sync pipeline.
+const DEFAULT_CAPACITY: usize = 64;
+
+/// Settings of the pipeline applying the blocks downloaded during the initial sync.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    /// Amount of blocks queued between two stages, bounding the memory of the pipeline.
+    pub capacity: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        PipelineConfig {
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+}
+
+/// Time spent by a stage of the sync pipeline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageStats {
+    /// Amount of blocks processed by the stage.
+    pub blocks: usize,
+
+    /// Time spent processing the blocks.
+    pub busy: Duration,
+
+    /// Time spent waiting for the next stage to accept a block, its queue being full.
+    pub stalled: Duration,
+}
+
+/// Statistics of a run of the sync pipeline, to tune its capacity and spot the slowest stage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStats {
+    /// Amount of blocks appended to the blockchain.
+    pub blocks: usize,
+
+    /// Time spent by the whole run.
+    pub elapsed: Duration,
+
+    /// Decoding of the blocks from JSON.
+    pub decode: StageStats,
+
+    /// Verification of the Merkle roots and the transaction signatures.
+    pub signatures: StageStats,
+
+    /// Verification of the seals by the consensus engine, e.g. the proof of work.
+    pub seal: StageStats,
+
+    /// Application of the blocks to the blockchain state.
+    pub apply: StageStats,
+}
+
+impl PipelineStats {
+    /// Get the throughput of the run.
+    ///
+    /// # Returns
+    /// The amount of blocks appended per second.
+    pub fn blocks_per_second(&self) -> f64 {
+        match self.elapsed.is_zero() {
+            true => 0.0,
+            false => self.blocks as f64 / self.elapsed.as_secs_f64(),
+        }
+    }
+}
+
+impl Chain {
+    /// Append consecutive encoded blocks downloaded during the initial sync.
+    ///
+    /// The blocks go through a pipeline whose stages run on their own thread, linked by bounded
+    /// queues: decoding, verification of the transaction signatures, verification of the seal,
+    /// then application to the blockchain state on the calling thread. A block is appended
+    /// under the same rules as `import_block`, while the next blocks are already being verified.
+    ///
+    /// # Arguments
+    /// - `blocks`: The JSON encoded blocks, from the lowest height.
+    /// - `config`: The settings of the pipeline.
+    ///
+    /// # Returns
+    /// The statistics of the run, or the error of the first rejected block. The blocks preceding
+    /// it stay appended.
+    pub fn sync_blocks<I>(
+        &mut self,
+        blocks: I,
+        config: PipelineConfig,
+    ) -> Result<PipelineStats, ChainError>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+        I::IntoIter: Send,
+    {
+        let started = Instant::now();
+        let capacity = config.capacity.max(1);
+        let consensus = self.consensus();
+        let mut keys = self.signing_keys();
+        let blocks = blocks.into_iter();
+
+        thread::scope(|scope| {
+            let (decoded, decoded_rx) = mpsc::sync_channel(capacity);
+            let (signed, signed_rx) = mpsc::sync_channel(capacity);
+            let (sealed, sealed_rx) = mpsc::sync_channel(capacity);
+
+            let decode = scope.spawn(move || {
+                stage(blocks.map(Ok), &decoded, |bytes: Vec<u8>| {
+                    serde_json::from_slice::<Block>(&bytes).map_err(|_| {
+                        ChainError::InvalidBlock("the block cannot be decoded".to_string())
+                    })
+                })
+            });
+            let signatures = scope.spawn(move || {
+                // The keys follow the rotations of the blocks ahead of their application
+                stage(decoded_rx, &signed, |block: Block| {
+                    if !block.has_valid_transactions() {
+                        return Err(ChainError::InvalidBlock(
+                            "the transactions do not match the header".to_string(),
+                        ));
+                    }
+
+                    block.verify_signatures_parallel(&mut keys).map(|_| block)
+                })
+            });
+            let seal = scope.spawn(move || {
+                stage(signed_rx, &sealed, |block: Block| {
+                    consensus.verify_seal(&block.header).map(|_| block)
+                })
+            });
+
+            let (apply, result) = self.apply_synced_blocks(sealed_rx);
+
+            // The stages stop once the application stage drops its queue
+            let join = |handle: thread::ScopedJoinHandle<StageStats>| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            };
+            let stats = PipelineStats {
+                blocks: apply.blocks,
+                elapsed: started.elapsed(),
+                decode: join(decode),
+                signatures: join(signatures),
+                seal: join(seal),
+                apply,
+            };
+
+            result.map(|_| stats)
+        })
+    }
+
+    /// Append the verified blocks leaving the sync pipeline.
+    ///
+    /// # Arguments
+    /// - `blocks`: The queue of the verified blocks, or of the error of the first rejected block.
+    ///
+    /// # Returns
+    /// The statistics of the stage, and the error of the first rejected block.
+    fn apply_synced_blocks(
+        &mut self,
+        blocks: Receiver<Result<Block, ChainError>>,
+    ) -> (StageStats, Result<(), ChainError>) {
+        let mut stats = StageStats::default();
+
+        for block in blocks {
+            let started = Instant::now();
+            let result = block.and_then(|block| self.import_block_with(block, true));
+
+            stats.busy += started.elapsed();
+
+            if result.is_err() {
+                return (stats, result);
+            }
+
+            stats.blocks += 1;
+        }
+
+        (stats, Ok(()))
+    }
+}
+
+/// Run a stage of the sync pipeline until its input is exhausted or an error occurs.
+///
+/// # Arguments
+/// - `input`: The items to process, or the error of a previous stage.
+/// - `output`: The queue of the next stage.
+/// - `process`: The work of the stage on an item.
+///
+/// # Returns
+/// The statistics of the stage.
+fn stage<T, U>(
+    input: impl IntoIterator<Item = Result<T, ChainError>>,
+    output: &SyncSender<Result<U, ChainError>>,
+    mut process: impl FnMut(T) -> Result<U, ChainError>,
+) -> StageStats {
+    let mut stats = StageStats::default();
+
+    for item in input {
+        let started = Instant::now();
+        let result = item.and_then(&mut process);
+        let failed = result.is_err();
+
+        stats.busy += started.elapsed();
+
+        // The next stage stopped, or is handed the error to report it in order
+        let started = Instant::now();
+
+        if output.send(result).is_err() || failed {
+            break;
+        }
+
+        stats.stalled += started.elapsed();
+        stats.blocks += 1;
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, Transaction};
+
+    #[test]
+    fn test_sync_blocks() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let from = chain
+            .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&from, 100.0);
+
+        let mut replica = chain.clone();
+
+        for _ in 0..5 {
+            chain
+                .add_transaction(from.clone(), to.clone(), 1.0, &keypair)
+                .unwrap();
+            chain.generate_new_block().unwrap();
+        }
+
+        let encoded: Vec<Vec<u8>> = chain.chain[1..]
+            .iter()
+            .map(|block| serde_json::to_vec(block).unwrap())
+            .collect();
+
+        // A corrupted block is rejected after the blocks preceding it
+        let mut corrupted = encoded.clone();
+        corrupted[3] = b"{}".to_vec();
+
+        let mut partial = replica.clone();
+
+        assert_eq!(
+            partial.sync_blocks(corrupted, PipelineConfig { capacity: 1 }),
+            Err(ChainError::InvalidBlock(
+                "the block cannot be decoded".to_string()
+            ))
+        );
+        assert_eq!(partial.get_last_hash(), chain.chain[3].hash());
+
+        // The valid blocks are all appended
+        let stats = replica
+            .sync_blocks(encoded, PipelineConfig::default())
+            .unwrap();
+
+        assert_eq!(stats.blocks, 5);
+        assert_eq!(stats.decode.blocks, 5);
+        assert_eq!(stats.seal.blocks, 5);
+        assert_eq!(replica.get_last_hash(), chain.get_last_hash());
+        assert_eq!(
+            replica.get_wallet_balance(to.clone()),
+            chain.get_wallet_balance(to)
+        );
+    }
+
+    #[test]
+    fn test_sync_blocks_signatures() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let from = chain
+            .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&from, 100.0);
+
+        let mut replica = chain.clone();
+        let transfer = Transaction::builder()
+            .from(from)
+            .to(to)
+            .amount(10.0)
+            .fee(1.0)
+            .build();
+
+        chain
+            .submit_transaction(transfer.clone().sign(&keypair))
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        // The transfer is signed by another key
+        let mut forged = chain.chain[1].clone();
+        forged.transactions[1] = transfer.sign(&Keypair::generate());
+        forged.header.merkle = Chain::get_merkle(forged.transactions.clone());
+        Block::proof_of_work(&mut forged.header);
+
+        let encode = |block: &Block| vec![serde_json::to_vec(block).unwrap()];
+        let mut partial = replica.clone();
+
+        assert_eq!(
+            partial.sync_blocks(encode(&forged), PipelineConfig::default()),
+            Err(ChainError::InvalidSignature)
+        );
+        assert_eq!(partial.get_last_hash(), replica.get_last_hash());
+
+        assert!(replica
+            .sync_blocks(encode(&chain.chain[1]), PipelineConfig::default())
+            .is_ok());
+        assert_eq!(replica.get_last_hash(), chain.get_last_hash());
+    }
+}
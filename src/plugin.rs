@@ -0,0 +1,136 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{Block, Chain, Transaction};
+
+/// Identifier of a registered plugin.
+pub type PluginId = usize;
+
+/// An extension following the blocks applied to the blockchain, such as an indexer, a
+/// notification system or a custom state machine.
+///
+/// The hooks run synchronously while the blockchain is borrowed, so they take `&self` and keep
+/// their state behind interior mutability. Every hook does nothing by default.
+pub trait ChainPlugin: Debug + Send + Sync {
+    /// Called for every transaction of a block appended to the blockchain, the coinbase first,
+    /// before `on_block_applied`.
+    ///
+    /// # Arguments
+    /// - `chain`: The blockchain, with the block.
+    /// - `height`: The height of the block.
+    /// - `transaction`: The transaction.
+    fn on_transaction_applied(&self, _chain: &Chain, _height: usize, _transaction: &Transaction) {}
+
+    /// Called for every block appended to the blockchain, mined locally or imported.
+    ///
+    /// # Arguments
+    /// - `chain`: The blockchain, with the block.
+    /// - `height`: The height of the block.
+    /// - `block`: The block.
+    fn on_block_applied(&self, _chain: &Chain, _height: usize, _block: &Block) {}
+
+    /// Called when the blockchain switches to a branch carrying more work, before the hooks of
+    /// the blocks applied from the branch.
+    ///
+    /// # Arguments
+    /// - `chain`: The blockchain, on the new branch.
+    /// - `fork_height`: The height of the first block that differs.
+    /// - `reverted`: The blocks removed from the blockchain, from the fork height on.
+    fn on_reorg(&self, _chain: &Chain, _fork_height: usize, _reverted: &[Block]) {}
+}
+
+/// A shared plugin, so the embedder keeps access to its state once registered.
+impl<P: ChainPlugin + ?Sized> ChainPlugin for Arc<P> {
+    fn on_transaction_applied(&self, chain: &Chain, height: usize, transaction: &Transaction) {
+        (**self).on_transaction_applied(chain, height, transaction);
+    }
+
+    fn on_block_applied(&self, chain: &Chain, height: usize, block: &Block) {
+        (**self).on_block_applied(chain, height, block);
+    }
+
+    fn on_reorg(&self, chain: &Chain, fork_height: usize, reverted: &[Block]) {
+        (**self).on_reorg(chain, fork_height, reverted);
+    }
+}
+
+/// A registered plugin.
+#[derive(Clone, Debug)]
+pub(crate) struct RegisteredPlugin {
+    /// The identifier of the plugin.
+    id: PluginId,
+
+    /// The plugin.
+    plugin: Arc<dyn ChainPlugin>,
+}
+
+impl Chain {
+    /// Register a plugin notified of the blocks applied to the blockchain.
+    ///
+    /// The plugins are notified in registration order, from the next block on. They are shared
+    /// by the clones of the blockchain and are not persisted with it.
+    ///
+    /// # Arguments
+    /// - `plugin`: The plugin.
+    ///
+    /// # Returns
+    /// The identifier of the plugin.
+    pub fn add_plugin<P: ChainPlugin + 'static>(&mut self, plugin: P) -> PluginId {
+        let id = self.next_plugin_id;
+        self.next_plugin_id += 1;
+
+        self.plugins.push(RegisteredPlugin {
+            id,
+            plugin: Arc::new(plugin),
+        });
+
+        id
+    }
+
+    /// Unregister a plugin.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the plugin.
+    ///
+    /// # Returns
+    /// `true` if the plugin is found and removed.
+    pub fn remove_plugin(&mut self, id: PluginId) -> bool {
+        let count = self.plugins.len();
+
+        self.plugins.retain(|registered| registered.id != id);
+
+        self.plugins.len() < count
+    }
+
+    /// Notify the plugins of the block at a height and of its transactions.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    pub(crate) fn notify_block_applied(&self, height: usize) {
+        let block = &self.chain[height];
+
+        for registered in &self.plugins {
+            for transaction in &block.transactions {
+                registered
+                    .plugin
+                    .on_transaction_applied(self, height, transaction);
+            }
+
+            registered.plugin.on_block_applied(self, height, block);
+        }
+    }
+
+    /// Notify the plugins of a switch to another branch, then of the blocks applied from it.
+    ///
+    /// # Arguments
+    /// - `fork_height`: The height of the first block that differs.
+    /// - `reverted`: The blocks removed from the blockchain.
+    pub(crate) fn notify_reorg(&self, fork_height: usize, reverted: &[Block]) {
+        for registered in &self.plugins {
+            registered.plugin.on_reorg(self, fork_height, reverted);
+        }
+
+        for height in fork_height..self.chain.len() {
+            self.notify_block_applied(height);
+        }
+    }
+}
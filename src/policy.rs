@@ -0,0 +1,259 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, ChainError};
+
+/// Amount of seconds in a day.
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A daily window of time, in minutes since midnight UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// The first minute of the window.
+    pub start: u32,
+
+    /// The minute following the window, before the start if the window spans midnight.
+    pub end: u32,
+}
+
+impl TimeWindow {
+    /// Check whether the window contains a time.
+    ///
+    /// # Arguments
+    /// - `timestamp`: The time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// `true` if the minute of the day of the time is within the window.
+    pub fn contains(&self, timestamp: i64) -> bool {
+        let minute = (timestamp.rem_euclid(SECONDS_PER_DAY) / 60) as u32;
+
+        match self.start <= self.end {
+            true => (self.start..self.end).contains(&minute),
+            false => minute >= self.start || minute < self.end,
+        }
+    }
+}
+
+/// Declarative rules restricting the outgoing transactions of a wallet.
+///
+/// Every rule left empty allows any transaction.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendingPolicy {
+    /// The addresses the wallet may pay.
+    #[serde(default)]
+    pub allowed_counterparties: BTreeSet<String>,
+
+    /// The daily windows of time during which the wallet may spend.
+    #[serde(default)]
+    pub time_windows: Vec<TimeWindow>,
+
+    /// The maximum amount of a transaction.
+    #[serde(default)]
+    pub max_amount: Option<f64>,
+}
+
+impl SpendingPolicy {
+    /// Evaluate the policy against a transaction.
+    ///
+    /// # Arguments
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `timestamp`: The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// A `PolicyViolation` error naming the first rule the transaction breaks, if any.
+    pub fn evaluate(&self, to: &str, amount: f64, timestamp: i64) -> Result<(), ChainError> {
+        if !self.allowed_counterparties.is_empty() && !self.allowed_counterparties.contains(to) {
+            return Err(ChainError::PolicyViolation(format!(
+                "{} is not an allowed counterparty",
+                to
+            )));
+        }
+
+        if !self.time_windows.is_empty()
+            && !self
+                .time_windows
+                .iter()
+                .any(|window| window.contains(timestamp))
+        {
+            return Err(ChainError::PolicyViolation(
+                "spending is not allowed at this time of day".to_string(),
+            ));
+        }
+
+        if let Some(max_amount) = self.max_amount.filter(|max_amount| amount > *max_amount) {
+            return Err(ChainError::PolicyViolation(format!(
+                "the amount exceeds the ceiling of {}",
+                max_amount
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A change to the spending policy of a wallet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PolicyAuditEntry {
+    /// The wallet address.
+    pub address: String,
+
+    /// The time of the change, in seconds since the Unix epoch.
+    pub timestamp: i64,
+
+    /// The policy before the change, `None` if the wallet had none.
+    pub previous: Option<SpendingPolicy>,
+
+    /// The policy after the change, `None` if it was removed.
+    pub current: Option<SpendingPolicy>,
+}
+
+impl Chain {
+    /// Attach a spending policy to a wallet, replacing its current policy.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `policy`: The spending policy.
+    ///
+    /// # Returns
+    /// `UnknownSender` if the wallet is not found.
+    pub fn set_spending_policy(
+        &mut self,
+        address: &str,
+        policy: SpendingPolicy,
+    ) -> Result<(), ChainError> {
+        if !self.wallets.contains_key(address) {
+            return Err(ChainError::UnknownSender(address.to_string()));
+        }
+
+        let previous = self.policies.insert(address.to_string(), policy.to_owned());
+
+        self.audit_policy(address, previous, Some(policy));
+
+        Ok(())
+    }
+
+    /// Get the spending policy of a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The spending policy, or `None` if the wallet has none.
+    pub fn get_spending_policy(&self, address: &str) -> Option<&SpendingPolicy> {
+        self.policies.get(address)
+    }
+
+    /// Remove the spending policy of a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The removed policy, or `None` if the wallet had none.
+    pub fn remove_spending_policy(&mut self, address: &str) -> Option<SpendingPolicy> {
+        let previous = self.policies.remove(address)?;
+
+        self.audit_policy(address, Some(previous.to_owned()), None);
+
+        Some(previous)
+    }
+
+    /// Get the changes to the spending policies, oldest first.
+    ///
+    /// # Returns
+    /// The audit log of the spending policies.
+    pub fn get_policy_audit_log(&self) -> &[PolicyAuditEntry] {
+        &self.policy_audit_log
+    }
+
+    /// Evaluate the spending policy of the sender of a transaction at the current time.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    ///
+    /// # Returns
+    /// A `PolicyViolation` error if the transaction breaks the policy of the sender.
+    pub(crate) fn check_spending_policy(
+        &self,
+        from: &str,
+        to: &str,
+        amount: f64,
+    ) -> Result<(), ChainError> {
+        match self.policies.get(from) {
+            Some(policy) => {
+                let now = self.clock().now_millis().div_euclid(1000);
+
+                policy.evaluate(to, amount, now)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record a change to the spending policy of a wallet in the audit log.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `previous`: The policy before the change.
+    /// - `current`: The policy after the change.
+    fn audit_policy(
+        &mut self,
+        address: &str,
+        previous: Option<SpendingPolicy>,
+        current: Option<SpendingPolicy>,
+    ) {
+        self.policy_audit_log.push(PolicyAuditEntry {
+            address: address.to_string(),
+            timestamp: self.clock().now_millis().div_euclid(1000),
+            previous,
+            current,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_window() {
+        let office = TimeWindow {
+            start: 9 * 60,
+            end: 17 * 60,
+        };
+        let night = TimeWindow {
+            start: 22 * 60,
+            end: 6 * 60,
+        };
+        let at = |hour: i64| SECONDS_PER_DAY * 100 + hour * 60 * 60;
+
+        assert!(office.contains(at(9)));
+        assert!(!office.contains(at(17)));
+        assert!(night.contains(at(23)));
+        assert!(night.contains(at(5)));
+        assert!(!night.contains(at(12)));
+    }
+
+    #[test]
+    fn test_evaluate_policy() {
+        let policy = SpendingPolicy {
+            allowed_counterparties: BTreeSet::from(["shop".to_string()]),
+            time_windows: vec![],
+            max_amount: Some(50.0),
+        };
+
+        assert_eq!(policy.evaluate("shop", 50.0, 0), Ok(()));
+        assert!(matches!(
+            policy.evaluate("other", 1.0, 0),
+            Err(ChainError::PolicyViolation(_))
+        ));
+        assert!(matches!(
+            policy.evaluate("shop", 51.0, 0),
+            Err(ChainError::PolicyViolation(_))
+        ));
+        assert_eq!(SpendingPolicy::default().evaluate("other", 1e9, 0), Ok(()));
+    }
+}
@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, BlockHeader, Chain, Transaction};
+
+/// Side of a sibling hash in a Merkle proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSide {
+    /// The sibling hash is concatenated before the current hash.
+    Left,
+
+    /// The sibling hash is concatenated after the current hash.
+    Right,
+}
+
+/// A sibling hash on the path from a transaction to the Merkle root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    /// Hash of the sibling node.
+    pub hash: String,
+
+    /// Side of the sibling node.
+    pub side: ProofSide,
+}
+
+/// Proof that a transaction is included in a block with a given Merkle root.
+///
+/// Light clients holding the headers only can check it against the `merkle` field of a header,
+/// without downloading the block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// The proven transaction.
+    pub transaction: Transaction,
+
+    /// Sibling hashes from the transaction to the Merkle root.
+    pub path: Vec<ProofStep>,
+}
+
+impl MerkleProof {
+    /// Verify that the proof is for an intact transaction and that its path leads to a root.
+    ///
+    /// # Arguments
+    /// - `root`: The Merkle root of the block, from a header the caller trusts.
+    /// - `tx_hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// `true` if the proof is valid.
+    pub fn verify(&self, root: &str, tx_hash: &str) -> bool {
+        self.transaction.hash == tx_hash
+            && self.transaction.verify()
+            && merkle_root(&self.transaction, &self.path) == root
+    }
+}
+
+/// Proof that a transaction is included in a block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransactionProof {
+    /// Height of the block including the transaction.
+    pub height: usize,
+
+    /// Header of the block including the transaction.
+    pub header: BlockHeader,
+
+    /// The proven transaction.
+    pub transaction: Transaction,
+
+    /// Sibling hashes from the transaction to the Merkle root.
+    pub path: Vec<ProofStep>,
+}
+
+impl TransactionProof {
+    /// Verify that the transaction is intact and that its path leads to the Merkle root of the header.
+    ///
+    /// The header itself is not verified: the caller must check that it belongs to the chain,
+    /// e.g. by comparing its hash with a block it trusts.
+    ///
+    /// # Returns
+    /// `true` if the proof is valid.
+    pub fn verify(&self) -> bool {
+        self.transaction.verify()
+            && merkle_root(&self.transaction, &self.path) == self.header.merkle
+    }
+}
+
+/// Compute the Merkle root a path leads to from a transaction.
+///
+/// # Arguments
+/// - `transaction`: The transaction.
+/// - `path`: The sibling hashes from the transaction to the root.
+///
+/// # Returns
+/// The Merkle root.
+fn merkle_root(transaction: &Transaction, path: &[ProofStep]) -> String {
+    path.iter()
+        .fold(Chain::merkle_leaf(transaction), |hash, step| {
+            match step.side {
+                ProofSide::Left => Chain::merkle_node(&step.hash, &hash),
+                ProofSide::Right => Chain::merkle_node(&hash, &step.hash),
+            }
+        })
+}
+
+impl Block {
+    /// Build the path from a transaction of the block to its Merkle root.
+    ///
+    /// Follows the pairing order of `Chain::get_merkle`, which combines the first two hashes of
+    /// a queue and appends the result.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The sibling hashes, or `None` if the transaction is not stored in the block.
+    pub fn merkle_path(&self, hash: &str) -> Option<Vec<ProofStep>> {
+        let mut position = self
+            .transactions
+            .iter()
+            .position(|transaction| transaction.hash == hash)?;
+
+        let mut queue: Vec<String> = self.transactions.iter().map(Chain::merkle_leaf).collect();
+
+        if queue.len() % 2 == 1 {
+            let last = queue.last().cloned().unwrap();
+            queue.push(last);
+        }
+
+        let mut path = Vec::new();
+
+        while queue.len() > 1 {
+            let h1 = queue.remove(0);
+            let h2 = queue.remove(0);
+
+            match position {
+                0 => path.push(ProofStep {
+                    hash: h2.to_owned(),
+                    side: ProofSide::Right,
+                }),
+                1 => path.push(ProofStep {
+                    hash: h1.to_owned(),
+                    side: ProofSide::Left,
+                }),
+                _ => {}
+            }
+
+            // Track the node of the transaction as the queue rotates
+            position = match position {
+                0 | 1 => queue.len(),
+                _ => position - 2,
+            };
+
+            queue.push(Chain::merkle_node(&h1, &h2));
+        }
+
+        Some(path)
+    }
+
+    /// Build a proof that a transaction is included in the block.
+    ///
+    /// # Arguments
+    /// - `tx_hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The proof, to verify against the Merkle root of the block, or `None` if the transaction
+    /// is not stored in the block.
+    pub fn merkle_proof(&self, tx_hash: &str) -> Option<MerkleProof> {
+        let transaction = self
+            .transactions
+            .iter()
+            .find(|transaction| transaction.hash == tx_hash)?;
+
+        Some(MerkleProof {
+            transaction: transaction.clone(),
+            path: self.merkle_path(tx_hash)?,
+        })
+    }
+}
+
+impl Chain {
+    /// Build a proof that a mined transaction is included in the blockchain.
+    ///
+    /// # Arguments
+    /// - `hash`: The hash of the transaction.
+    ///
+    /// # Returns
+    /// The inclusion proof, or `None` if the transaction is not stored in any block body.
+    pub fn get_transaction_proof(&self, hash: &str) -> Option<TransactionProof> {
+        self.chain.iter().enumerate().find_map(|(height, block)| {
+            let proof = block.merkle_proof(hash)?;
+
+            Some(TransactionProof {
+                height,
+                header: block.header.clone(),
+                transaction: proof.transaction,
+                path: proof.path,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_path() {
+        for count in 1..8 {
+            let mut block = Block::new("0".to_string(), 1.0);
+
+            for i in 0..count {
+                block.transactions.push(
+                    Transaction::builder()
+                        .from(format!("sender-{}", i))
+                        .to(format!("receiver-{}", i))
+                        .amount(i as f64 + 1.0)
+                        .fee(0.0)
+                        .build(),
+                );
+            }
+
+            block.header.merkle = Chain::get_merkle(block.transactions.clone());
+
+            for transaction in &block.transactions {
+                let proof = TransactionProof {
+                    height: 0,
+                    header: block.header.clone(),
+                    transaction: transaction.clone(),
+                    path: block.merkle_path(&transaction.hash).unwrap(),
+                };
+
+                assert!(proof.verify());
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_path_not_found() {
+        let block = Block::new("0".to_string(), 1.0);
+
+        assert!(block.merkle_path("hash").is_none());
+        assert!(block.merkle_proof("hash").is_none());
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        let mut block = Block::new("0".to_string(), 1.0);
+
+        for i in 0..3 {
+            block.transactions.push(
+                Transaction::builder()
+                    .from(format!("sender-{}", i))
+                    .to(format!("receiver-{}", i))
+                    .amount(i as f64 + 1.0)
+                    .fee(0.0)
+                    .build(),
+            );
+        }
+
+        block.header.merkle = Chain::get_merkle(block.transactions.clone());
+
+        let hash = block.transactions[1].hash.clone();
+        let other = block.transactions[2].hash.clone();
+        let proof = block.merkle_proof(&hash).unwrap();
+
+        assert!(proof.verify(&block.header.merkle, &hash));
+        assert!(!proof.verify(&block.header.merkle, &other));
+        assert!(!proof.verify(&block.transactions[0].hash, &hash));
+
+        let mut tampered = proof.clone();
+        tampered.transaction.amount = 100.0;
+
+        assert!(!tampered.verify(&block.header.merkle, &hash));
+    }
+}
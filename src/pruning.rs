@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, MemoryUsage};
+
+/// Retention policy for block bodies, applied after every new block.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PruningPolicy {
+    /// Keep every block body.
+    #[default]
+    KeepAll,
+
+    /// Keep the bodies of the last `blocks` blocks only.
+    KeepLast {
+        /// Number of most recent blocks whose bodies are kept.
+        blocks: usize,
+    },
+
+    /// Keep every header and a state snapshot every `interval` blocks, dropping the bodies
+    /// of the blocks covered by the latest snapshot.
+    Snapshots {
+        /// Number of blocks between two snapshots.
+        interval: usize,
+    },
+}
+
+/// Wallet balances at a given block height.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// Height of the block the snapshot was taken at.
+    pub height: usize,
+
+    /// Hash of the block the snapshot was taken at.
+    pub block_hash: String,
+
+    /// A map to associate wallet addresses with their balances.
+    pub balances: HashMap<String, f64>,
+}
+
+/// Storage usage of a blockchain.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageStats {
+    /// Total amount of blocks.
+    pub blocks: usize,
+
+    /// Amount of blocks whose bodies were pruned.
+    pub pruned_blocks: usize,
+
+    /// Amount of transactions stored in block bodies.
+    pub stored_transactions: usize,
+
+    /// Amount of transactions waiting to be mined.
+    pub pending_transactions: usize,
+
+    /// Amount of state snapshots.
+    pub snapshots: usize,
+
+    /// Amount of wallets.
+    pub wallets: usize,
+
+    /// Approximate memory used by the blockchain.
+    pub memory: MemoryUsage,
+}
+
+impl Chain {
+    /// Get the storage usage of the blockchain.
+    ///
+    /// # Returns
+    /// The amount of stored blocks, transactions, and snapshots, and the approximate memory usage.
+    pub fn storage_stats(&self) -> StorageStats {
+        StorageStats {
+            blocks: self.chain.len(),
+            pruned_blocks: self.chain.iter().filter(|block| block.is_pruned()).count(),
+            stored_transactions: self
+                .chain
+                .iter()
+                .map(|block| block.transactions.len())
+                .sum(),
+            pending_transactions: self.current_transactions.len(),
+            snapshots: self.snapshots.len(),
+            wallets: self.wallets.len(),
+            memory: self.memory_usage(),
+        }
+    }
+
+    /// Get the state snapshots taken by the pruning policy.
+    ///
+    /// # Returns
+    /// The state snapshots ordered by height.
+    pub fn get_snapshots(&self) -> &[StateSnapshot] {
+        &self.snapshots
+    }
+
+    /// Take a snapshot of the wallet balances after the block at a given height, and keep it
+    /// with the snapshots of the pruning policy.
+    ///
+    /// The snapshot only holds the wallets with a balance, and lets the state after the height
+    /// be computed once the bodies of the blocks up to the height are pruned. It replaces the
+    /// snapshot previously taken at the same height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// The snapshot, or `None` if the height is unknown or the block bodies needed to compute
+    /// the state were pruned.
+    pub fn snapshot_state(&mut self, height: usize) -> Option<StateSnapshot> {
+        let mut snapshot = self.state_at(height)?;
+        snapshot.balances.retain(|_, balance| *balance != 0.0);
+
+        // Keep the snapshots ordered by height
+        match self
+            .snapshots
+            .binary_search_by_key(&height, |snapshot| snapshot.height)
+        {
+            Ok(index) => self.snapshots[index] = snapshot.clone(),
+            Err(index) => self.snapshots.insert(index, snapshot.clone()),
+        }
+
+        Some(snapshot)
+    }
+
+    /// Drop the bodies of the blocks below a height, keeping their headers.
+    ///
+    /// The state before the height can no longer be computed without a snapshot at or above
+    /// the last pruned block, see `snapshot_state`. The blocks not written to the storage
+    /// backend yet are kept.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the first kept block body.
+    ///
+    /// # Returns
+    /// The amount of block bodies dropped by this call.
+    pub fn prune_blocks_before(&mut self, height: usize) -> usize {
+        self.prune_bodies(height)
+    }
+
+    /// Apply the configured pruning policy to the blockchain.
+    pub(crate) fn apply_pruning(&mut self) {
+        let height = self.chain.len().saturating_sub(1);
+
+        let prune_before = match self.config.pruning {
+            PruningPolicy::KeepAll => return,
+            PruningPolicy::KeepLast { blocks } => self.chain.len().saturating_sub(blocks),
+            PruningPolicy::Snapshots { interval } => {
+                // Take a snapshot every `interval` blocks
+                if interval > 0 && height.is_multiple_of(interval) {
+                    // Leave out the pending transactions, the snapshot only holds the mined state
+                    if let Some(balances) = self.rewind_to(height) {
+                        self.snapshots.push(StateSnapshot {
+                            height,
+                            block_hash: self.get_last_hash(),
+                            balances,
+                        });
+                    }
+                }
+
+                // Drop the bodies of the blocks covered by the latest snapshot
+                match self.snapshots.last() {
+                    Some(snapshot) => snapshot.height + 1,
+                    None => 0,
+                }
+            }
+        };
+
+        self.prune_bodies(prune_before);
+    }
+
+    /// Drop the bodies of the blocks below a height.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the first kept block body.
+    ///
+    /// # Returns
+    /// The amount of block bodies dropped.
+    fn prune_bodies(&mut self, height: usize) -> usize {
+        // Keep the blocks not written to the backend yet
+        let height = match self.stored_blocks {
+            Some(stored) => height.min(stored),
+            None => height,
+        };
+
+        let mut pruned = 0;
+
+        for block in self.chain.iter_mut().take(height) {
+            if block.is_pruned() {
+                continue;
+            }
+
+            // The transactions of pruned blocks can no longer be looked up
+            for transaction in &block.transactions {
+                self.transaction_index.remove(&transaction.hash);
+            }
+
+            block.prune();
+            pruned += 1;
+        }
+
+        // The pruned blocks can no longer be undone
+        self.undo_log = self.undo_log.split_off(&height);
+
+        pruned
+    }
+}
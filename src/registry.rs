@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, ChainConfig};
+
+/// A registry of independent blockchains within one process.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChainRegistry {
+    /// A map to associate blockchains with their chain identifiers.
+    chains: HashMap<String, Chain>,
+}
+
+impl ChainRegistry {
+    /// Create a new empty registry.
+    ///
+    /// # Returns
+    /// A new `ChainRegistry` instance without any blockchains.
+    pub fn new() -> Self {
+        ChainRegistry {
+            chains: HashMap::new(),
+        }
+    }
+
+    /// Create a new blockchain with the specified parameters and register it.
+    ///
+    /// # Arguments
+    /// - `id`: The unique chain identifier.
+    /// - `difficulty`: The initial mining difficulty level of the network.
+    /// - `reward`: The initial block reward for miners.
+    /// - `fee`: The transaction fee.
+    ///
+    /// # Returns
+    /// `true` if the blockchain is successfully created, `false` if the identifier is invalid or already taken.
+    pub fn create_chain(&mut self, id: String, difficulty: f64, reward: f64, fee: f64) -> bool {
+        self.create_chain_with_config(id, difficulty, reward, fee, ChainConfig::default())
+    }
+
+    /// Create a new blockchain with the specified parameters and settings and register it.
+    ///
+    /// The chain identifier of the settings is replaced with `id`.
+    ///
+    /// # Arguments
+    /// - `id`: The unique chain identifier.
+    /// - `difficulty`: The initial mining difficulty level of the network.
+    /// - `reward`: The initial block reward for miners.
+    /// - `fee`: The transaction fee.
+    /// - `config`: The optional blockchain settings.
+    ///
+    /// # Returns
+    /// `true` if the blockchain is successfully created, `false` if the identifier is invalid or already taken.
+    pub fn create_chain_with_config(
+        &mut self,
+        id: String,
+        difficulty: f64,
+        reward: f64,
+        fee: f64,
+        config: ChainConfig,
+    ) -> bool {
+        // Validate the chain identifier
+        if !ChainRegistry::is_valid_id(&id) || self.chains.contains_key(&id) {
+            return false;
+        }
+
+        let config = ChainConfig {
+            chain_id: id.to_owned(),
+            ..config
+        };
+
+        self.chains
+            .insert(id, Chain::with_config(difficulty, reward, fee, config));
+
+        true
+    }
+
+    /// Get a blockchain by its identifier.
+    ///
+    /// # Arguments
+    /// - `id`: The chain identifier.
+    ///
+    /// # Returns
+    /// An option containing a reference to the blockchain if found, or `None` if not found.
+    pub fn get_chain(&self, id: &str) -> Option<&Chain> {
+        self.chains.get(id)
+    }
+
+    /// Get a mutable blockchain by its identifier.
+    ///
+    /// # Arguments
+    /// - `id`: The chain identifier.
+    ///
+    /// # Returns
+    /// An option containing a mutable reference to the blockchain if found, or `None` if not found.
+    pub fn get_chain_mut(&mut self, id: &str) -> Option<&mut Chain> {
+        self.chains.get_mut(id)
+    }
+
+    /// Remove a blockchain from the registry.
+    ///
+    /// # Arguments
+    /// - `id`: The chain identifier.
+    ///
+    /// # Returns
+    /// The removed blockchain, or `None` if it is not registered.
+    pub fn remove_chain(&mut self, id: &str) -> Option<Chain> {
+        self.chains.remove(id)
+    }
+
+    /// Get the identifiers of all registered blockchains.
+    ///
+    /// # Returns
+    /// A sorted list of chain identifiers.
+    pub fn get_chain_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.chains.keys().cloned().collect();
+        ids.sort();
+
+        ids
+    }
+
+    /// Persist every registered blockchain as a JSON file named after its identifier.
+    ///
+    /// The files of the blockchains removed from the registry are deleted, so `load` does not
+    /// restore them.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory to write the blockchains to.
+    ///
+    /// # Returns
+    /// An error if the directory or any of the files cannot be written or deleted.
+    pub fn persist<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+
+        for (id, chain) in &self.chains {
+            let path = dir.as_ref().join(format!("{}.json", id));
+            let content = serde_json::to_string(chain)?;
+
+            fs::write(path, content)?;
+        }
+
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+
+            // Only delete the files `load` would read as a blockchain
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(id) if ChainRegistry::is_valid_id(id) && !self.chains.contains_key(id) => {
+                    fs::remove_file(&path)?
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load a registry from the JSON files previously written by `persist`.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory to read the blockchains from.
+    ///
+    /// # Returns
+    /// A registry with every blockchain found in the directory.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let mut registry = ChainRegistry::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            // Skip anything that is not a persisted blockchain
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let id = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(id) if ChainRegistry::is_valid_id(id) => id.to_string(),
+                _ => continue,
+            };
+
+            let content = fs::read_to_string(&path)?;
+            let mut chain: Chain = serde_json::from_str(&content)
+                .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+            chain.update_transaction_index();
+
+            registry.chains.insert(id, chain);
+        }
+
+        Ok(registry)
+    }
+
+    /// Check whether a chain identifier is non-empty and safe to use as a file name.
+    ///
+    /// # Arguments
+    /// - `id`: The chain identifier.
+    ///
+    /// # Returns
+    /// `true` if the identifier only contains alphanumeric characters, `-` or `_`.
+    fn is_valid_id(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_id() {
+        assert!(ChainRegistry::is_valid_id("classroom-1_a"));
+        assert!(!ChainRegistry::is_valid_id(""));
+        assert!(!ChainRegistry::is_valid_id("../chain"));
+    }
+}
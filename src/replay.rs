@@ -0,0 +1,92 @@
+use std::{sync::Arc, thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, ManualClock, ReplayReport};
+
+/// An action replayed through a blockchain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayEvent {
+    /// A transaction between two existing wallets.
+    Transaction {
+        /// The address of the sender.
+        from: String,
+
+        /// The address of the receiver.
+        to: String,
+
+        /// The amount of the transaction.
+        amount: f64,
+    },
+
+    /// The mining of a block with the pending transactions.
+    MineBlock,
+}
+
+/// An action and the time at which it happened.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimedEvent {
+    /// The time of the action, in milliseconds since the Unix epoch.
+    pub timestamp: i64,
+
+    /// The action.
+    pub event: ReplayEvent,
+}
+
+impl Chain {
+    /// Replay timed actions faster than real time.
+    ///
+    /// The blockchain runs on a manual clock set to the time of every action, so the
+    /// transactions and blocks keep the original timestamps whatever the speed. The pauses
+    /// between the actions are the original ones divided by the speed factor, without waiting
+    /// if it is zero or infinite. The previous clock is restored afterwards.
+    ///
+    /// # Arguments
+    /// - `events`: The actions, ordered by time.
+    /// - `speed_factor`: How many times faster than the original the actions are replayed.
+    ///
+    /// # Returns
+    /// The amount of accepted and rejected transactions and of mined blocks, without wallets.
+    pub fn replay(&mut self, events: &[TimedEvent], speed_factor: f64) -> ReplayReport {
+        let mut report = ReplayReport::default();
+        let Some(first) = events.first() else {
+            return report;
+        };
+
+        let previous_clock = self.clock.take();
+        let clock = Arc::new(ManualClock::new(first.timestamp));
+        let mut previous = first.timestamp;
+
+        self.set_clock(clock.clone());
+
+        for timed in events {
+            let seconds = (timed.timestamp - previous).max(0) as f64 / 1000.0 / speed_factor;
+
+            if seconds.is_finite() && seconds > 0.0 {
+                thread::sleep(Duration::from_secs_f64(seconds));
+            }
+
+            previous = timed.timestamp;
+            clock.set(timed.timestamp);
+
+            match &timed.event {
+                ReplayEvent::Transaction { from, to, amount } => {
+                    match self.add_unsigned_transaction(from.to_owned(), to.to_owned(), *amount) {
+                        Ok(_) => report.submitted += 1,
+                        Err(_) => report.rejected += 1,
+                    }
+                }
+                ReplayEvent::MineBlock => {
+                    if self.generate_new_block().is_ok() {
+                        report.blocks += 1;
+                    }
+                }
+            }
+        }
+
+        self.clock = previous_clock;
+
+        report
+    }
+}
@@ -0,0 +1,125 @@
+use std::{io, path::Path};
+
+use crate::{
+    backend::{
+        kv::{
+            block_key, decode_block_count, wallet_key, BLOCK_COUNT_KEY, STATE_KEY, WALLET_PREFIX,
+        },
+        missing_parent,
+    },
+    Block, Storage, Wallet,
+};
+
+/// Storage writing the blocks and the wallets to a RocksDB database.
+#[derive(Debug)]
+pub struct RocksDbStorage {
+    /// The database.
+    db: rocksdb::DB,
+}
+
+impl RocksDbStorage {
+    /// Open a RocksDB database, creating it if it does not exist.
+    ///
+    /// # Arguments
+    /// - `path`: The directory of the database.
+    ///
+    /// # Returns
+    /// A new `RocksDbStorage` instance, or an error if the database cannot be opened.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(RocksDbStorage {
+            db: rocksdb::DB::open_default(path).map_err(io::Error::other)?,
+        })
+    }
+}
+
+impl Storage for RocksDbStorage {
+    fn put_block(&self, height: usize, block: &Block) -> io::Result<()> {
+        let count = self.block_count()?;
+
+        if height > count {
+            return Err(missing_parent(height));
+        }
+
+        self.db
+            .put(block_key(height), serde_json::to_vec(block)?)
+            .map_err(io::Error::other)?;
+
+        if height == count {
+            self.db
+                .put(BLOCK_COUNT_KEY, (count as u64 + 1).to_be_bytes())
+                .map_err(io::Error::other)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_block(&self, height: usize) -> io::Result<Option<Block>> {
+        match self.db.get(block_key(height)).map_err(io::Error::other)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn block_count(&self) -> io::Result<usize> {
+        let count = self.db.get(BLOCK_COUNT_KEY).map_err(io::Error::other)?;
+
+        decode_block_count(count.as_deref())
+    }
+
+    fn truncate_blocks(&self, height: usize) -> io::Result<()> {
+        let count = self.block_count()?;
+
+        if height >= count {
+            return Ok(());
+        }
+
+        for removed in height..count {
+            self.db
+                .delete(block_key(removed))
+                .map_err(io::Error::other)?;
+        }
+
+        self.db
+            .put(BLOCK_COUNT_KEY, (height as u64).to_be_bytes())
+            .map_err(io::Error::other)
+    }
+
+    fn put_wallet(&self, wallet: &Wallet) -> io::Result<()> {
+        self.db
+            .put(wallet_key(&wallet.address), serde_json::to_vec(wallet)?)
+            .map_err(io::Error::other)
+    }
+
+    fn get_wallet(&self, address: &str) -> io::Result<Option<Wallet>> {
+        match self.db.get(wallet_key(address)).map_err(io::Error::other)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_wallets(&self) -> io::Result<Vec<Wallet>> {
+        self.db
+            .prefix_iterator(WALLET_PREFIX)
+            .map(|entry| entry.map_err(io::Error::other))
+            .take_while(|entry| {
+                // The iterator continues past the prefix once its keys are exhausted
+                entry
+                    .as_ref()
+                    .map_or(true, |(key, _)| key.starts_with(WALLET_PREFIX))
+            })
+            .map(|entry| Ok(serde_json::from_slice(&entry?.1)?))
+            .collect()
+    }
+
+    fn put_state(&self, state: &[u8]) -> io::Result<()> {
+        self.db.put(STATE_KEY, state).map_err(io::Error::other)
+    }
+
+    fn get_state(&self) -> io::Result<Option<Vec<u8>>> {
+        self.db.get(STATE_KEY).map_err(io::Error::other)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.db.flush().map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{encoding, keys, Approval, Chain, ChainError, Keypair, Transaction};
+
+/// Maximum nesting depth of the scripts combined with `All` and `Any`, bounding their evaluation.
+pub const MAX_SCRIPT_DEPTH: usize = 8;
+
+/// Condition locking an output, to be satisfied by the transaction spending it, on top of the
+/// output being owned by its sender or fee payer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Script {
+    /// The spending transaction reveals a preimage whose SHA-256 digest is the hash.
+    HashLock {
+        /// The hex encoded SHA-256 digest of the preimage.
+        hash: String,
+    },
+
+    /// The spending transaction cannot be included in a block before a time, its locktime
+    /// being at least the unlock time.
+    TimeLock {
+        /// The earliest block timestamp spending the output, in seconds since the Unix epoch.
+        unlock_at: i64,
+    },
+
+    /// The spending transaction is signed by at least `threshold` of the keys.
+    MultiSig {
+        /// The amount of distinct keys signing the spending transaction to satisfy the script.
+        threshold: usize,
+
+        /// The hex encoded public keys.
+        public_keys: Vec<String>,
+    },
+
+    /// Every script is satisfied.
+    All(Vec<Script>),
+
+    /// At least one script is satisfied.
+    Any(Vec<Script>),
+}
+
+/// Data of a spending transaction satisfying the scripts locking its inputs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Witness {
+    /// The preimage revealed to satisfy hash locks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preimage: Option<String>,
+
+    /// The signatures of the transaction satisfying multi-signature scripts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<Approval>,
+}
+
+impl Script {
+    /// Create a hash lock satisfied by revealing a preimage.
+    ///
+    /// # Arguments
+    /// - `preimage`: The secret preimage.
+    ///
+    /// # Returns
+    /// The script locking an output until the preimage is revealed.
+    pub fn hash_lock(preimage: &str) -> Self {
+        Script::HashLock {
+            hash: encoding::to_hex(&Sha256::digest(preimage.as_bytes())),
+        }
+    }
+
+    /// Check that the script can be satisfied.
+    ///
+    /// # Returns
+    /// The reason the script is malformed, if it is.
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_at(1)
+    }
+
+    /// Evaluate the script against a spending transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction spending the locked output.
+    ///
+    /// # Returns
+    /// The reason the script is not satisfied, if it is not.
+    pub fn evaluate(&self, transaction: &Transaction) -> Result<(), String> {
+        let witness = transaction.witness.as_ref();
+
+        match self {
+            Script::HashLock { hash } => {
+                let preimage = witness
+                    .and_then(|witness| witness.preimage.as_deref())
+                    .ok_or("the preimage of the hash lock is not revealed")?;
+
+                match encoding::to_hex(&Sha256::digest(preimage.as_bytes())) == *hash {
+                    true => Ok(()),
+                    false => Err("the preimage does not match the hash lock".to_string()),
+                }
+            }
+            Script::TimeLock { unlock_at } => {
+                match transaction
+                    .locktime
+                    .is_some_and(|locktime| locktime >= *unlock_at)
+                {
+                    true => Ok(()),
+                    false => Err(format!("the output is locked until {}", unlock_at)),
+                }
+            }
+            Script::MultiSig {
+                threshold,
+                public_keys,
+            } => {
+                let signers: HashSet<&str> = witness
+                    .map_or(&[][..], |witness| &witness.signatures)
+                    .iter()
+                    .filter(|approval| {
+                        public_keys.contains(&approval.public_key)
+                            && keys::verify_approval(approval, transaction)
+                    })
+                    .map(|approval| approval.public_key.as_str())
+                    .collect();
+
+                match signers.len() >= *threshold {
+                    true => Ok(()),
+                    false => Err(format!(
+                        "{} of {} required signatures",
+                        signers.len(),
+                        threshold
+                    )),
+                }
+            }
+            Script::All(scripts) => scripts
+                .iter()
+                .try_for_each(|script| script.evaluate(transaction)),
+            Script::Any(scripts) => {
+                let mut reasons = Vec::new();
+
+                for script in scripts {
+                    match script.evaluate(transaction) {
+                        Ok(()) => return Ok(()),
+                        Err(reason) => reasons.push(reason),
+                    }
+                }
+
+                Err(format!(
+                    "no alternative is satisfied: {}",
+                    reasons.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Check that the script can be satisfied, at a nesting depth.
+    ///
+    /// # Arguments
+    /// - `depth`: The nesting depth of the script, 1 at the top.
+    ///
+    /// # Returns
+    /// The reason the script is malformed, if it is.
+    fn validate_at(&self, depth: usize) -> Result<(), String> {
+        if depth > MAX_SCRIPT_DEPTH {
+            return Err(format!(
+                "scripts are nested deeper than {}",
+                MAX_SCRIPT_DEPTH
+            ));
+        }
+
+        match self {
+            Script::HashLock { hash } => {
+                match hash.len() == 64 && hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                    true => Ok(()),
+                    false => Err("the hash lock is not a SHA-256 digest".to_string()),
+                }
+            }
+            Script::TimeLock { .. } => Ok(()),
+            Script::MultiSig {
+                threshold,
+                public_keys,
+            } => {
+                if !public_keys.iter().all(|key| keys::is_valid_public_key(key)) {
+                    return Err("a key of the multi-signature script is invalid".to_string());
+                }
+
+                let distinct: HashSet<&String> = public_keys.iter().collect();
+
+                match *threshold > 0 && *threshold <= distinct.len() {
+                    true => Ok(()),
+                    false => Err(format!(
+                        "the threshold must be between 1 and the {} distinct keys",
+                        distinct.len()
+                    )),
+                }
+            }
+            Script::All(scripts) | Script::Any(scripts) => {
+                if scripts.is_empty() {
+                    return Err("a combination of scripts is empty".to_string());
+                }
+
+                scripts
+                    .iter()
+                    .try_for_each(|script| script.validate_at(depth + 1))
+            }
+        }
+    }
+}
+
+impl Transaction {
+    /// Reveal the preimage of the hash locks of the spent outputs.
+    ///
+    /// # Arguments
+    /// - `preimage`: The preimage.
+    ///
+    /// # Returns
+    /// The transaction with the preimage in its witness.
+    pub fn reveal_preimage(mut self, preimage: impl Into<String>) -> Self {
+        self.witness.get_or_insert_with(Witness::default).preimage = Some(preimage.into());
+        self
+    }
+
+    /// Sign the transaction with a key of the multi-signature scripts of the spent outputs.
+    ///
+    /// Signing twice with the same keypair keeps the first signature.
+    ///
+    /// # Arguments
+    /// - `keypair`: The keypair.
+    ///
+    /// # Returns
+    /// The transaction with the signature in its witness.
+    pub fn sign_witness(mut self, keypair: &Keypair) -> Self {
+        let public_key = keypair.public_key();
+        let signature = keypair.sign(&self);
+        let witness = self.witness.get_or_insert_with(Witness::default);
+
+        if !witness
+            .signatures
+            .iter()
+            .any(|approval| approval.public_key == public_key)
+        {
+            witness.signatures.push(Approval {
+                public_key,
+                signature,
+            });
+        }
+
+        self
+    }
+}
+
+impl Chain {
+    /// Add a new transaction paying an output locked by a script, which the receiver can only
+    /// spend with a transaction satisfying the script.
+    ///
+    /// # Arguments
+    /// - `from`: The sender's address.
+    /// - `to`: The receiver's address.
+    /// - `amount`: The amount of the transaction.
+    /// - `lock`: The script locking the paid output.
+    ///
+    /// # Returns
+    /// The hash of the transaction added to the current transactions, or the reason it is invalid.
+    pub fn add_locked_transaction(
+        &mut self,
+        from: String,
+        to: String,
+        amount: f64,
+        lock: Script,
+    ) -> Result<String, ChainError> {
+        let transaction = self
+            .unsigned_transaction(from, to, amount)?
+            .lock(lock)
+            .build();
+        let hash = transaction.hash.to_owned();
+
+        self.submit_transaction(transaction)?;
+
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_script() {
+        let keypairs = [
+            Keypair::generate(),
+            Keypair::generate(),
+            Keypair::generate(),
+        ];
+        let multisig = Script::MultiSig {
+            threshold: 2,
+            public_keys: keypairs[..2].iter().map(Keypair::public_key).collect(),
+        };
+        let script = Script::Any(vec![
+            Script::All(vec![
+                Script::hash_lock("secret"),
+                Script::TimeLock { unlock_at: 100 },
+            ]),
+            multisig,
+        ]);
+        let spend = |locktime: i64| {
+            Transaction::builder()
+                .from("a")
+                .to("b")
+                .amount(1.0)
+                .fee(0.1)
+                .locktime(locktime)
+                .build()
+        };
+
+        assert_eq!(script.validate(), Ok(()));
+        assert!(script.evaluate(&spend(100)).is_err());
+        assert!(script
+            .evaluate(&spend(99).reveal_preimage("secret"))
+            .is_err());
+        assert!(script
+            .evaluate(&spend(100).reveal_preimage("wrong"))
+            .is_err());
+        assert_eq!(
+            script.evaluate(&spend(100).reveal_preimage("secret")),
+            Ok(())
+        );
+
+        // The signatures of keys outside the script and repeated signatures do not count
+        let signed = spend(0)
+            .sign_witness(&keypairs[0])
+            .sign_witness(&keypairs[0])
+            .sign_witness(&keypairs[2]);
+
+        assert!(script.evaluate(&signed).is_err());
+        assert_eq!(script.evaluate(&signed.sign_witness(&keypairs[1])), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_script() {
+        let key = Keypair::generate().public_key();
+        let mut nested = Script::TimeLock { unlock_at: 0 };
+
+        for _ in 0..MAX_SCRIPT_DEPTH {
+            nested = Script::All(vec![nested]);
+        }
+
+        assert!(nested.validate().is_err());
+        assert!(Script::Any(Vec::new()).validate().is_err());
+        assert!(Script::HashLock {
+            hash: "abc".to_string()
+        }
+        .validate()
+        .is_err());
+        assert!(Script::MultiSig {
+            threshold: 2,
+            public_keys: vec![key.to_owned(), key],
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, Transaction, Wallet};
+
+/// The length of the hex encoded block and transaction hashes.
+const HASH_LENGTH: usize = 64;
+
+/// The entity matching a search query.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchResult {
+    /// A block, found by its height or the hash of its header.
+    Block {
+        /// The height of the block.
+        height: usize,
+
+        /// The block.
+        block: Block,
+    },
+
+    /// A transaction, found by its hash.
+    Transaction {
+        /// The height of the block including the transaction, `None` while it is pending.
+        height: Option<usize>,
+
+        /// The transaction.
+        transaction: Box<Transaction>,
+    },
+
+    /// A wallet, found by its address or its email.
+    Wallet {
+        /// The wallet.
+        wallet: Wallet,
+    },
+}
+
+impl Chain {
+    /// Find the block, transaction or wallet matching a query, for the search box of an explorer.
+    ///
+    /// The shape of the query picks the lookup: 64 hex characters are a block or transaction
+    /// hash, other digits a block height, and a query with an `@` a wallet email. Any other
+    /// query, or one the lookup of its shape does not find, is a wallet address.
+    ///
+    /// # Arguments
+    /// - `query`: The query, surrounding whitespace being ignored.
+    ///
+    /// # Returns
+    /// The matching entity, or `None` if nothing matches.
+    pub fn search(&self, query: &str) -> Option<SearchResult> {
+        let query = query.trim();
+
+        if query.is_empty() {
+            return None;
+        }
+
+        let is_hash = query.len() == HASH_LENGTH && query.bytes().all(|b| b.is_ascii_hexdigit());
+        let found = if is_hash {
+            self.search_hash(query)
+        } else if query.bytes().all(|byte| byte.is_ascii_digit()) {
+            self.search_height(query)
+        } else if query.contains('@') {
+            self.get_wallet_by_email(query)
+                .map(|wallet| SearchResult::Wallet {
+                    wallet: wallet.clone(),
+                })
+        } else {
+            None
+        };
+
+        found.or_else(|| {
+            self.get_wallet(query).map(|wallet| SearchResult::Wallet {
+                wallet: wallet.clone(),
+            })
+        })
+    }
+
+    /// Find a block by its height.
+    ///
+    /// # Arguments
+    /// - `query`: The height, in decimal digits.
+    ///
+    /// # Returns
+    /// The block, or `None` if the height is out of range.
+    fn search_height(&self, query: &str) -> Option<SearchResult> {
+        let height = query.parse().ok()?;
+
+        self.get_block(height).map(|block| SearchResult::Block {
+            height,
+            block: block.clone(),
+        })
+    }
+
+    /// Find a block by the hash of its header, or a mined or pending transaction by its hash.
+    ///
+    /// # Arguments
+    /// - `query`: The hash, in lowercase or uppercase hex.
+    ///
+    /// # Returns
+    /// The block or the transaction, or `None` if nothing has the hash.
+    fn search_hash(&self, query: &str) -> Option<SearchResult> {
+        let hash = query.to_ascii_lowercase();
+
+        if let Some(height) = self.chain.iter().position(|block| block.hash() == hash) {
+            return Some(SearchResult::Block {
+                height,
+                block: self.chain[height].clone(),
+            });
+        }
+
+        if let Some((height, transaction)) = self.find_transaction_in_chain(&hash) {
+            return Some(SearchResult::Transaction {
+                height: Some(height),
+                transaction: Box::new(transaction.clone()),
+            });
+        }
+
+        self.get_transaction(hash)
+            .map(|transaction| SearchResult::Transaction {
+                height: None,
+                transaction: Box::new(transaction.clone()),
+            })
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{encoding, Address, Chain, ChainError, HdWallet, Keypair, Transaction};
+
+/// Domain separating the hashes of the public key fingerprints.
+const FINGERPRINT_DOMAIN: &str = "key-fingerprint";
+
+/// Length of the public key fingerprints, in hexadecimal digits.
+const FINGERPRINT_LENGTH: usize = 16;
+
+/// Kind of a change to the key owning a wallet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEventKind {
+    /// The key was replaced by a new random key.
+    KeyRotation,
+
+    /// The key was restored from the mnemonic backup and replaced by the next key it derives.
+    MnemonicRecovery,
+}
+
+/// A change to the key owning a wallet, recorded for the operators to detect takeovers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityEvent {
+    /// The wallet address.
+    pub address: Address,
+
+    /// The kind of the change.
+    pub kind: SecurityEventKind,
+
+    /// The time the change was accepted, in seconds since the Unix epoch.
+    pub timestamp: i64,
+
+    /// The hex encoded public key signing the change, `None` if the wallet had no key.
+    pub initiating_key: Option<String>,
+
+    /// The fingerprint of the public key owning the wallet after the change.
+    pub new_key_fingerprint: String,
+
+    /// The hash of the transaction recording the change.
+    pub transaction: String,
+}
+
+/// Compute the short fingerprint identifying a public key in the security events.
+///
+/// # Arguments
+/// - `public_key`: The hex encoded public key.
+///
+/// # Returns
+/// The first 16 hexadecimal digits of the hash of the key.
+pub fn key_fingerprint(public_key: &str) -> String {
+    encoding::hash(FINGERPRINT_DOMAIN, &[&public_key])
+        .chars()
+        .take(FINGERPRINT_LENGTH)
+        .collect()
+}
+
+impl Chain {
+    /// Recover a wallet owned by a key of an HD wallet restored from its mnemonic.
+    ///
+    /// The wallet is rotated to the next key derived by the HD wallet, so a leaked copy of the
+    /// recovered key is revoked while the mnemonic still backs the wallet up. The rotation is
+    /// recorded on-chain as a recovery.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `hd_wallet`: The HD wallet restored with `HdWallet::from_mnemonic`.
+    /// - `index`: The index of the child key owning the wallet.
+    ///
+    /// # Returns
+    /// The keypair owning the wallet after the recovery, the child at `index + 1`, or an error
+    /// describing why the recovery is invalid.
+    pub fn recover_wallet(
+        &mut self,
+        address: &str,
+        hd_wallet: &HdWallet,
+        index: u32,
+    ) -> Result<Keypair, ChainError> {
+        let keypair = hd_wallet.derive_child(index + 1);
+
+        self.submit_key_rotation(
+            address,
+            Some(&hd_wallet.derive_child(index)),
+            &keypair,
+            true,
+        )?;
+
+        Ok(keypair)
+    }
+
+    /// Get the changes to the key owning a wallet.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    ///
+    /// # Returns
+    /// The key rotations and recoveries of the wallet, oldest first, or `None` if the wallet is
+    /// not found.
+    pub fn wallet_security_events(&self, address: &str) -> Option<&[SecurityEvent]> {
+        self.wallets
+            .get(address)
+            .map(|wallet| wallet.security_events.as_slice())
+    }
+
+    /// Get the changes to the keys owning the wallets.
+    ///
+    /// # Returns
+    /// The key rotations and recoveries of every wallet, oldest first.
+    pub fn get_security_audit_log(&self) -> &[SecurityEvent] {
+        &self.security_audit_log
+    }
+
+    /// Record a key rotation in the wallet and in the audit log, once per transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction recording the key rotation.
+    pub(crate) fn record_security_event(&mut self, transaction: &Transaction) {
+        let Some(rotation) = &transaction.key_rotation else {
+            return;
+        };
+        let Some(wallet) = self.wallets.get(&transaction.from) else {
+            return;
+        };
+
+        // Transactions submitted again by a rollback are already recorded
+        if wallet
+            .security_events
+            .iter()
+            .any(|event| event.transaction == transaction.hash)
+        {
+            return;
+        }
+
+        let event = SecurityEvent {
+            address: transaction.from.to_owned(),
+            kind: match rotation.recovery {
+                true => SecurityEventKind::MnemonicRecovery,
+                false => SecurityEventKind::KeyRotation,
+            },
+            timestamp: self.clock().now_millis().div_euclid(1000),
+            initiating_key: rotation.previous.to_owned(),
+            new_key_fingerprint: key_fingerprint(&rotation.public_key),
+            transaction: transaction.hash.to_owned(),
+        };
+
+        if let Some(wallet) = self.wallets.get_mut(&transaction.from) {
+            wallet.security_events.push(event.clone());
+        }
+
+        self.security_audit_log.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_wallet() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let (address, hd_wallet) = chain
+            .create_wallet_with_mnemonic("s@mail.com".to_string())
+            .unwrap();
+        let restored = HdWallet::from_mnemonic(&hd_wallet.mnemonic(), "").unwrap();
+
+        assert_eq!(chain.wallet_security_events("unknown"), None);
+        assert_eq!(chain.wallet_security_events(&address), Some(&[][..]));
+
+        // Only the key owning the wallet recovers it
+        assert_eq!(
+            chain.recover_wallet(&address, &restored, 1).unwrap_err(),
+            ChainError::InvalidSignature
+        );
+
+        let keypair = chain.recover_wallet(&address, &restored, 0).unwrap();
+        let events = chain.wallet_security_events(&address).unwrap();
+
+        assert_eq!(keypair.public_key(), hd_wallet.derive_child(1).public_key());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, SecurityEventKind::MnemonicRecovery);
+        assert_eq!(
+            events[0].initiating_key,
+            Some(hd_wallet.derive_child(0).public_key())
+        );
+        assert_eq!(
+            events[0].new_key_fingerprint,
+            key_fingerprint(&keypair.public_key())
+        );
+        assert_eq!(chain.get_security_audit_log(), events);
+    }
+}
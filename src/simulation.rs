@@ -0,0 +1,757 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{Block, Chain, ChainConfig, Clock, ManualClock};
+
+/// The time at which the simulations start, 2024-01-01 at midnight UTC, in milliseconds.
+const START_TIME: i64 = 1_704_067_200_000;
+
+/// Settings of a network simulation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationConfig {
+    /// The amount of mining nodes, sharing the hash power equally.
+    pub nodes: usize,
+
+    /// The amount of peers of every node, at least 2 so the nodes form a ring.
+    pub peers: usize,
+
+    /// The amount of blocks mined in every run.
+    pub blocks: usize,
+
+    /// The average time between two blocks across the network.
+    pub block_interval: Duration,
+
+    /// The minimum delay of a block between two peers.
+    pub min_latency: Duration,
+
+    /// The maximum delay of a block between two peers.
+    pub max_latency: Duration,
+
+    /// The amount of runs.
+    pub runs: usize,
+
+    /// The seed of the first run, incremented for every next run.
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            nodes: 8,
+            peers: 3,
+            blocks: 100,
+            block_interval: Duration::from_secs(10),
+            min_latency: Duration::from_millis(100),
+            max_latency: Duration::from_secs(2),
+            runs: 1,
+            seed: 42,
+        }
+    }
+}
+
+/// Distribution of the propagation delays of the blocks, in milliseconds.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DelayStats {
+    /// The amount of measured delays.
+    pub samples: usize,
+
+    /// The average delay.
+    pub mean: f64,
+
+    /// The median delay.
+    pub p50: u64,
+
+    /// The 90th percentile of the delays.
+    pub p90: u64,
+
+    /// The 99th percentile of the delays.
+    pub p99: u64,
+
+    /// The longest delay.
+    pub max: u64,
+}
+
+impl DelayStats {
+    /// Summarize delays.
+    ///
+    /// # Arguments
+    /// - `delays`: The delays, in milliseconds.
+    ///
+    /// # Returns
+    /// The distribution of the delays, all zeros if there is none.
+    fn new(mut delays: Vec<u64>) -> Self {
+        if delays.is_empty() {
+            return DelayStats::default();
+        }
+
+        delays.sort_unstable();
+
+        let percentile = |p: usize| delays[(delays.len() - 1) * p / 100];
+
+        DelayStats {
+            samples: delays.len(),
+            mean: delays.iter().sum::<u64>() as f64 / delays.len() as f64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: delays[delays.len() - 1],
+        }
+    }
+}
+
+/// Statistics of the runs of a network simulation, serializable for comparing consensus settings.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    /// The amount of runs.
+    pub runs: usize,
+
+    /// The amount of blocks mined across the runs.
+    pub mined_blocks: usize,
+
+    /// The amount of mined blocks left out of the final blockchain of their run.
+    pub stale_blocks: usize,
+
+    /// The share of mined blocks left out of the final blockchain of their run.
+    pub stale_rate: f64,
+
+    /// The amount of heights at which competing blocks were mined.
+    pub forks: usize,
+
+    /// The amount of heights at which competing blocks were mined, per mined block.
+    pub fork_rate: f64,
+
+    /// The delays between the mining of a block and its reception by every other node.
+    pub propagation: DelayStats,
+
+    /// The amount of reorganizations of the nodes, per amount of reverted blocks.
+    pub reorg_depths: BTreeMap<usize, usize>,
+}
+
+/// A step of a scenario run by `run_scenario`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioStep {
+    /// Split the nodes into groups by their indexes, dropping the blocks relayed from a group to
+    /// another while the blocks already in flight still arrive. The nodes left out of every group
+    /// form one more group.
+    Partition(Vec<Vec<usize>>),
+
+    /// Mine blocks at the block interval, then deliver the relayed blocks.
+    Mine(usize),
+
+    /// Reconnect every node, each announcing its tip to its peers, then deliver the relayed
+    /// blocks.
+    Heal,
+
+    /// Check that the nodes converged to a single tip, mining up to `within` more blocks until
+    /// they do, since branches carrying the same work are only settled by the next block.
+    AssertConvergence {
+        /// The maximum amount of blocks mined before the nodes share a single tip.
+        within: usize,
+    },
+}
+
+/// Outcome of a scenario run by `run_scenario`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    /// The amount of blocks mined during the scenario.
+    pub mined_blocks: usize,
+
+    /// The amount of blocks mined by every convergence assertion before the nodes shared a
+    /// single tip, in step order.
+    pub convergence: Vec<usize>,
+
+    /// The height of the best tip at the end of the scenario.
+    pub height: usize,
+
+    /// The amount of reorganizations of the nodes, per amount of reverted blocks.
+    pub reorg_depths: BTreeMap<usize, usize>,
+}
+
+/// Failure of a scenario run by `run_scenario`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScenarioError {
+    /// A partition refers to a node index beyond the amount of nodes.
+    UnknownNode(usize),
+
+    /// The nodes still followed several tips once a convergence assertion mined its blocks.
+    Diverged {
+        /// The index of the convergence assertion in the steps.
+        step: usize,
+
+        /// The amount of distinct tips of the nodes.
+        tips: usize,
+    },
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::UnknownNode(node) => write!(f, "Node {} is not simulated", node),
+            ScenarioError::Diverged { step, tips } => {
+                write!(f, "Nodes follow {} tips after step {}", tips, step)
+            }
+        }
+    }
+}
+
+impl Error for ScenarioError {}
+
+/// An event of a simulation.
+#[derive(Clone, Debug)]
+enum Event {
+    /// A node finds the next block.
+    Mine,
+
+    /// A block reaches a node.
+    Deliver {
+        /// The index of the node.
+        node: usize,
+
+        /// The hash of the block.
+        hash: String,
+    },
+}
+
+/// A mining node of a simulation.
+#[derive(Debug)]
+struct SimulatedNode {
+    /// The blockchain of the node.
+    chain: Chain,
+
+    /// The indexes of the peers of the node.
+    peers: Vec<usize>,
+
+    /// The hashes of the blocks received by the node.
+    seen: HashSet<String>,
+}
+
+/// A block mined during a simulation.
+#[derive(Debug)]
+struct MinedBlock {
+    /// The block.
+    block: Block,
+
+    /// The height of the block.
+    height: usize,
+
+    /// The time at which the block was mined, in milliseconds.
+    mined_at: i64,
+}
+
+/// A run of a network simulation, with its own nodes and random generator.
+#[derive(Debug)]
+struct Simulation<'a> {
+    /// The settings of the simulation.
+    config: &'a SimulationConfig,
+
+    /// The random generator of the run.
+    rng: StdRng,
+
+    /// The clock shared by the nodes.
+    clock: Arc<ManualClock>,
+
+    /// The nodes.
+    nodes: Vec<SimulatedNode>,
+
+    /// The blocks mined during the run, by hash.
+    blocks: HashMap<String, MinedBlock>,
+
+    /// The pending events, by time and order of scheduling.
+    events: BTreeMap<(i64, u64), Event>,
+
+    /// The amount of scheduled events.
+    scheduled: u64,
+
+    /// The propagation delays measured during the run.
+    delays: Vec<u64>,
+
+    /// The amount of reorganizations of the nodes, per amount of reverted blocks.
+    reorg_depths: BTreeMap<usize, usize>,
+
+    /// The group of every node while the network is partitioned.
+    groups: Option<Vec<usize>>,
+}
+
+impl<'a> Simulation<'a> {
+    /// Create the nodes of a run, sharing the same genesis block.
+    ///
+    /// # Arguments
+    /// - `config`: The settings of the simulation.
+    /// - `seed`: The seed of the run.
+    ///
+    /// # Returns
+    /// The run, before its first block.
+    fn new(config: &'a SimulationConfig, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let clock = Arc::new(ManualClock::new(START_TIME));
+
+        let mut genesis = Chain::empty(1.0, 100.0, 0.1, ChainConfig::default());
+        genesis.address = Chain::derive_address("simulation", &seed.to_string(), "genesis", 42);
+        genesis.set_clock(clock.clone());
+
+        // The difficulty is valid, so the genesis block is always mined
+        let _ = genesis.generate_new_block();
+
+        let peers = topology(config.nodes, config.peers, &mut rng);
+        let nodes = peers
+            .into_iter()
+            .enumerate()
+            .map(|(index, peers)| {
+                let mut chain = genesis.clone();
+                chain.address =
+                    Chain::derive_address("simulation", &seed.to_string(), &index.to_string(), 42);
+
+                SimulatedNode {
+                    chain,
+                    peers,
+                    seen: HashSet::new(),
+                }
+            })
+            .collect();
+
+        Simulation {
+            config,
+            rng,
+            clock,
+            nodes,
+            blocks: HashMap::new(),
+            events: BTreeMap::new(),
+            scheduled: 0,
+            delays: Vec::new(),
+            reorg_depths: BTreeMap::new(),
+            groups: None,
+        }
+    }
+
+    /// Schedule an event.
+    ///
+    /// # Arguments
+    /// - `time`: The time of the event, in milliseconds.
+    /// - `event`: The event.
+    fn schedule(&mut self, time: i64, event: Event) {
+        self.events.insert((time, self.scheduled), event);
+        self.scheduled += 1;
+    }
+
+    /// Schedule the next block, after an exponentially distributed delay.
+    ///
+    /// # Arguments
+    /// - `now`: The current time, in milliseconds.
+    fn schedule_mining(&mut self, now: i64) {
+        let mean = self.config.block_interval.as_millis() as f64;
+        let delay = -(1.0 - self.rng.gen::<f64>()).ln() * mean;
+
+        // Blocks are at least a millisecond apart, the resolution of the clock
+        self.schedule(now + (delay as i64).max(1), Event::Mine);
+    }
+
+    /// Send a block from a node to its peers.
+    ///
+    /// # Arguments
+    /// - `now`: The current time, in milliseconds.
+    /// - `node`: The index of the node.
+    /// - `hash`: The hash of the block.
+    fn relay(&mut self, now: i64, node: usize, hash: &str) {
+        let (min, max) = (
+            self.config.min_latency.as_millis() as i64,
+            self.config.max_latency.as_millis() as i64,
+        );
+
+        for peer in self.nodes[node].peers.clone() {
+            // Blocks relayed across a partition are dropped
+            if self
+                .groups
+                .as_ref()
+                .is_some_and(|groups| groups[node] != groups[peer])
+            {
+                continue;
+            }
+
+            let latency = self.rng.gen_range(min..=max.max(min));
+
+            self.schedule(
+                now + latency,
+                Event::Deliver {
+                    node: peer,
+                    hash: hash.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Mine a block on the blockchain of a random node.
+    ///
+    /// # Arguments
+    /// - `now`: The current time, in milliseconds.
+    fn mine(&mut self, now: i64) {
+        let node = self.rng.gen_range(0..self.nodes.len());
+        let chain = &mut self.nodes[node].chain;
+
+        if chain.generate_new_block().is_err() {
+            return;
+        }
+
+        let height = chain.get_all_blocks().len() - 1;
+        let block = chain.get_all_blocks()[height].clone();
+        let hash = block.hash();
+
+        self.nodes[node].seen.insert(hash.to_owned());
+        self.blocks.insert(
+            hash.to_owned(),
+            MinedBlock {
+                block,
+                height,
+                mined_at: now,
+            },
+        );
+
+        self.relay(now, node, &hash);
+    }
+
+    /// Receive a block on a node, switching to its branch if it carries more work, and relay it.
+    ///
+    /// # Arguments
+    /// - `now`: The current time, in milliseconds.
+    /// - `node`: The index of the node.
+    /// - `hash`: The hash of the block.
+    fn deliver(&mut self, now: i64, node: usize, hash: &str) {
+        if !self.nodes[node].seen.insert(hash.to_string()) {
+            return;
+        }
+
+        self.delays.push((now - self.blocks[hash].mined_at) as u64);
+
+        let chain = &mut self.nodes[node].chain;
+        let block = &self.blocks[hash].block;
+
+        if block.header.previous_hash == chain.get_last_hash() {
+            let _ = chain.import_block(block.clone());
+        } else {
+            // The node fetches the ancestors of the block from the peer, down to the genesis block
+            let mut branch = vec![block.clone()];
+
+            while let Some(parent) = self
+                .blocks
+                .get(&branch[branch.len() - 1].header.previous_hash)
+            {
+                branch.push(parent.block.clone());
+            }
+
+            let mut candidate = chain.get_all_blocks()[..1].to_vec();
+            candidate.extend(branch.into_iter().rev());
+
+            let height = chain.get_all_blocks().len();
+
+            if let Ok(applied) = chain.replace_chain(candidate) {
+                let reverted = height - (chain.get_all_blocks().len() - applied);
+
+                if reverted > 0 {
+                    *self.reorg_depths.entry(reverted).or_insert(0) += 1;
+                }
+            }
+        }
+
+        self.relay(now, node, hash);
+    }
+
+    /// Mine the blocks of the run and deliver them until every node received every block.
+    fn run(&mut self) {
+        self.mine_blocks(self.config.blocks);
+    }
+
+    /// Mine blocks and deliver them until no block is in flight.
+    ///
+    /// # Arguments
+    /// - `blocks`: The amount of blocks.
+    fn mine_blocks(&mut self, blocks: usize) {
+        let mut mined = 0;
+
+        if blocks > 0 {
+            self.schedule_mining(self.clock.now_millis());
+        }
+
+        while let Some(((now, _), event)) = self.events.pop_first() {
+            self.clock.set(now);
+
+            match event {
+                Event::Mine => {
+                    self.mine(now);
+                    mined += 1;
+
+                    if mined < blocks {
+                        self.schedule_mining(now);
+                    }
+                }
+                Event::Deliver { node, hash } => self.deliver(now, node, &hash),
+            }
+        }
+    }
+
+    /// Split the nodes into groups exchanging no block.
+    ///
+    /// # Arguments
+    /// - `groups`: The indexes of the nodes of every group.
+    ///
+    /// # Returns
+    /// `UnknownNode` if a group refers to a node beyond the amount of nodes.
+    fn partition(&mut self, groups: &[Vec<usize>]) -> Result<(), ScenarioError> {
+        let mut assigned = vec![groups.len(); self.nodes.len()];
+
+        for (group, nodes) in groups.iter().enumerate() {
+            for node in nodes {
+                match assigned.get_mut(*node) {
+                    Some(assigned) => *assigned = group,
+                    None => return Err(ScenarioError::UnknownNode(*node)),
+                }
+            }
+        }
+
+        self.groups = Some(assigned);
+
+        Ok(())
+    }
+
+    /// Reconnect every node and announce the tips, then deliver them until no block is in flight.
+    fn heal(&mut self) {
+        let now = self.clock.now_millis();
+
+        self.groups = None;
+
+        for node in 0..self.nodes.len() {
+            let tip = self.nodes[node].chain.get_last_hash();
+
+            // The genesis block is shared by every node
+            if self.blocks.contains_key(&tip) {
+                self.relay(now, node, &tip);
+            }
+        }
+
+        self.mine_blocks(0);
+    }
+
+    /// Count the distinct tips of the nodes.
+    ///
+    /// # Returns
+    /// The amount of distinct hashes of the last blocks of the nodes.
+    fn tips(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| node.chain.get_last_hash())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Add the statistics of the run to a report.
+    ///
+    /// # Arguments
+    /// - `report`: The report of the simulation.
+    /// - `delays`: The propagation delays of the simulation.
+    fn record(self, report: &mut SimulationReport, delays: &mut Vec<u64>) {
+        let best = self
+            .nodes
+            .iter()
+            .map(|node| &node.chain)
+            .max_by(|a, b| a.cumulative_work().total_cmp(&b.cumulative_work()));
+        let included = best.map_or(0, |chain| chain.get_all_blocks().len() - 1);
+
+        let mut heights: HashMap<usize, usize> = HashMap::new();
+
+        for mined in self.blocks.values() {
+            *heights.entry(mined.height).or_insert(0) += 1;
+        }
+
+        report.runs += 1;
+        report.mined_blocks += self.blocks.len();
+        report.stale_blocks += self.blocks.len().saturating_sub(included);
+        report.forks += heights.values().filter(|count| **count > 1).count();
+
+        for (depth, count) in self.reorg_depths {
+            *report.reorg_depths.entry(depth).or_insert(0) += count;
+        }
+
+        delays.extend(self.delays);
+    }
+}
+
+/// Run a scenario of partitions and heals on a simulated network, turning the fork and
+/// reorganization rules into assertions a test can check continuously.
+///
+/// The nodes start from the same genesis block on a manual clock, as with `simulate`, and run
+/// the steps in order: `Partition` cuts the links between groups of nodes, `Mine` lets every
+/// group extend its own branch, `Heal` reconnects the network, and `AssertConvergence` fails
+/// unless the nodes settle on a single tip within a bound. Only the first run of the settings
+/// is simulated, and the amount of blocks of the settings is ignored.
+///
+/// # Arguments
+/// - `config`: The settings of the simulated network.
+/// - `steps`: The steps of the scenario.
+///
+/// # Returns
+/// The blocks mined, the blocks needed by every convergence and the reorganization depths, or
+/// the first step that failed.
+pub fn run_scenario(
+    config: &SimulationConfig,
+    steps: &[ScenarioStep],
+) -> Result<ScenarioReport, ScenarioError> {
+    let mut report = ScenarioReport::default();
+
+    if config.nodes == 0 {
+        return Ok(report);
+    }
+
+    let mut simulation = Simulation::new(config, config.seed);
+
+    for (step, action) in steps.iter().enumerate() {
+        match action {
+            ScenarioStep::Partition(groups) => simulation.partition(groups)?,
+            ScenarioStep::Mine(blocks) => simulation.mine_blocks(*blocks),
+            ScenarioStep::Heal => simulation.heal(),
+            ScenarioStep::AssertConvergence { within } => {
+                let mut mined = 0;
+
+                while simulation.tips() > 1 {
+                    if mined == *within {
+                        return Err(ScenarioError::Diverged {
+                            step,
+                            tips: simulation.tips(),
+                        });
+                    }
+
+                    simulation.mine_blocks(1);
+                    mined += 1;
+                }
+
+                report.convergence.push(mined);
+            }
+        }
+    }
+
+    report.mined_blocks = simulation.blocks.len();
+    report.height = simulation
+        .nodes
+        .iter()
+        .map(|node| node.chain.get_all_blocks().len() - 1)
+        .max()
+        .unwrap_or_default();
+    report.reorg_depths = simulation.reorg_depths;
+
+    Ok(report)
+}
+
+/// Connect the nodes in a ring, then add random links until every node has enough peers.
+///
+/// # Arguments
+/// - `nodes`: The amount of nodes.
+/// - `peers`: The amount of peers of every node.
+/// - `rng`: The random generator.
+///
+/// # Returns
+/// The indexes of the peers of every node.
+fn topology(nodes: usize, peers: usize, rng: &mut StdRng) -> Vec<Vec<usize>> {
+    let mut links: Vec<Vec<usize>> = vec![Vec::new(); nodes];
+    let connect = |links: &mut Vec<Vec<usize>>, a: usize, b: usize| {
+        if a != b && !links[a].contains(&b) {
+            links[a].push(b);
+            links[b].push(a);
+        }
+    };
+
+    for node in 0..nodes {
+        connect(&mut links, node, (node + 1) % nodes);
+    }
+
+    for node in 0..nodes {
+        let mut others: Vec<usize> = (0..nodes).filter(|other| *other != node).collect();
+        others.shuffle(rng);
+
+        for other in others {
+            if links[node].len() >= peers.min(nodes - 1) {
+                break;
+            }
+
+            connect(&mut links, node, other);
+        }
+    }
+
+    links
+}
+
+/// Simulate a network of miners gossiping blocks, to evaluate consensus settings.
+///
+/// Every run starts the nodes from the same genesis block on a manual clock. Blocks are found
+/// by random nodes at exponentially distributed intervals and relayed by every node to its peers
+/// with a random latency. Nodes receiving a block that does not extend their blockchain fetch
+/// its ancestors and switch to its branch if it carries more work. The same settings always
+/// produce the same report.
+///
+/// # Arguments
+/// - `config`: The settings of the simulation.
+///
+/// # Returns
+/// The block propagation delays, the stale and fork rates, and the reorganization depths
+/// across the runs.
+pub fn simulate(config: &SimulationConfig) -> SimulationReport {
+    let mut report = SimulationReport::default();
+    let mut delays = Vec::new();
+
+    if config.nodes == 0 {
+        return report;
+    }
+
+    for run in 0..config.runs {
+        let mut simulation = Simulation::new(config, config.seed.wrapping_add(run as u64));
+
+        simulation.run();
+        simulation.record(&mut report, &mut delays);
+    }
+
+    if report.mined_blocks > 0 {
+        report.stale_rate = report.stale_blocks as f64 / report.mined_blocks as f64;
+        report.fork_rate = report.forks as f64 / report.mined_blocks as f64;
+    }
+
+    report.propagation = DelayStats::new(delays);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_stats() {
+        let stats = DelayStats::new((1..=100).rev().collect());
+
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.mean, 50.5);
+        assert_eq!(
+            (stats.p50, stats.p90, stats.p99, stats.max),
+            (50, 90, 99, 100)
+        );
+        assert_eq!(DelayStats::new(Vec::new()), DelayStats::default());
+    }
+
+    #[test]
+    fn test_topology() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let links = topology(10, 4, &mut rng);
+
+        for (node, peers) in links.iter().enumerate() {
+            assert!(peers.len() >= 4);
+            assert!(peers.contains(&((node + 1) % 10)));
+            assert!(peers.iter().all(|peer| links[*peer].contains(&node)));
+        }
+
+        assert_eq!(topology(1, 3, &mut rng), vec![Vec::<usize>::new()]);
+    }
+}
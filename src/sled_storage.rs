@@ -0,0 +1,152 @@
+use std::{io, path::Path};
+
+use crate::{
+    backend::{
+        kv::{
+            block_key, decode_block_count, wallet_key, BLOCK_COUNT_KEY, STATE_KEY, WALLET_PREFIX,
+        },
+        missing_parent,
+    },
+    Block, Storage, Wallet,
+};
+
+/// Storage writing the blocks and the wallets to a sled database.
+#[derive(Clone, Debug)]
+pub struct SledStorage {
+    /// The database.
+    db: sled::Db,
+}
+
+impl SledStorage {
+    /// Open a sled database, creating it if it does not exist.
+    ///
+    /// # Arguments
+    /// - `path`: The directory of the database.
+    ///
+    /// # Returns
+    /// A new `SledStorage` instance, or an error if the database cannot be opened.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(SledStorage {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+impl Storage for SledStorage {
+    fn put_block(&self, height: usize, block: &Block) -> io::Result<()> {
+        let count = self.block_count()?;
+
+        if height > count {
+            return Err(missing_parent(height));
+        }
+
+        self.db
+            .insert(block_key(height), serde_json::to_vec(block)?)?;
+
+        if height == count {
+            self.db
+                .insert(BLOCK_COUNT_KEY, &(count as u64 + 1).to_be_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn get_block(&self, height: usize) -> io::Result<Option<Block>> {
+        match self.db.get(block_key(height))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn block_count(&self) -> io::Result<usize> {
+        decode_block_count(self.db.get(BLOCK_COUNT_KEY)?.as_deref())
+    }
+
+    fn truncate_blocks(&self, height: usize) -> io::Result<()> {
+        let count = self.block_count()?;
+
+        if height >= count {
+            return Ok(());
+        }
+
+        for removed in height..count {
+            self.db.remove(block_key(removed))?;
+        }
+
+        self.db
+            .insert(BLOCK_COUNT_KEY, &(height as u64).to_be_bytes())?;
+
+        Ok(())
+    }
+
+    fn put_wallet(&self, wallet: &Wallet) -> io::Result<()> {
+        self.db
+            .insert(wallet_key(&wallet.address), serde_json::to_vec(wallet)?)?;
+
+        Ok(())
+    }
+
+    fn get_wallet(&self, address: &str) -> io::Result<Option<Wallet>> {
+        match self.db.get(wallet_key(address))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_wallets(&self) -> io::Result<Vec<Wallet>> {
+        self.db
+            .scan_prefix(WALLET_PREFIX)
+            .map(|entry| Ok(serde_json::from_slice(&entry?.1)?))
+            .collect()
+    }
+
+    fn put_state(&self, state: &[u8]) -> io::Result<()> {
+        self.db.insert(STATE_KEY, state)?;
+
+        Ok(())
+    }
+
+    fn get_state(&self) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.db.get(STATE_KEY)?.map(|bytes| bytes.to_vec()))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.db.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, sync::Arc};
+
+    use super::*;
+    use crate::Chain;
+
+    #[test]
+    fn test_sled_storage() {
+        let dir = env::temp_dir().join(format!("blockchain-sled-{}", std::process::id()));
+        let storage = Arc::new(SledStorage::open(&dir).unwrap());
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+
+        chain.create_wallet("s@mail.com".to_string()).unwrap();
+        chain.set_storage(storage.clone()).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.persist().unwrap();
+
+        assert_eq!(storage.block_count().unwrap(), 2);
+        assert!(storage.put_block(3, &chain.chain[0]).is_err());
+        assert_eq!(storage.get_wallets().unwrap().len(), 1);
+
+        storage.truncate_blocks(1).unwrap();
+
+        assert_eq!(storage.block_count().unwrap(), 1);
+        assert!(storage.get_block(1).unwrap().is_none());
+
+        // The state refers to the truncated block
+        assert!(Chain::restore(storage).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
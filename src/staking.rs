@@ -0,0 +1,661 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, Chain, ChainError, Keypair, Transaction};
+
+/// Settings of the rewards paid to the stakers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StakingConfig {
+    /// Amount of blocks of an epoch, the rewards being distributed at the end of every epoch.
+    /// No rewards are distributed if 0.
+    pub epoch_length: usize,
+
+    /// Amount distributed to the stakers at the end of every epoch.
+    pub epoch_reward: f64,
+
+    /// Share of the rewards of the delegators kept by their validator, between 0 and 1.
+    pub commission: f64,
+}
+
+impl Default for StakingConfig {
+    fn default() -> Self {
+        StakingConfig {
+            epoch_length: 100,
+            epoch_reward: 0.0,
+            commission: 0.0,
+        }
+    }
+}
+
+/// An operation of the staking module on the funds of a wallet, recorded by a transaction from
+/// the wallet to itself.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StakingOperation {
+    /// Bond an amount of the balance of the wallet.
+    Stake {
+        /// The bonded amount.
+        amount: f64,
+    },
+
+    /// Release an amount of the stake of the wallet back to its balance.
+    Unstake {
+        /// The released amount.
+        amount: f64,
+    },
+
+    /// Issue an amount of the pending rewards of the wallet to its balance.
+    Claim {
+        /// The claimed amount.
+        amount: f64,
+    },
+}
+
+impl StakingOperation {
+    /// Get the amount of the operation.
+    ///
+    /// # Returns
+    /// The bonded, released or claimed amount.
+    pub fn amount(&self) -> f64 {
+        match *self {
+            StakingOperation::Stake { amount }
+            | StakingOperation::Unstake { amount }
+            | StakingOperation::Claim { amount } => amount,
+        }
+    }
+
+    /// Get the change of the balance of the wallet applied by the operation.
+    ///
+    /// # Returns
+    /// The amount debited by a bond as a negative change, or the amount credited otherwise.
+    pub fn balance_change(&self) -> f64 {
+        match *self {
+            StakingOperation::Stake { amount } => -amount,
+            StakingOperation::Unstake { amount } | StakingOperation::Claim { amount } => amount,
+        }
+    }
+}
+
+/// A validator of the staking module, with the stake it accounts for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StakingValidator {
+    /// The address of the validator.
+    pub address: Address,
+
+    /// The amount staked by the validator itself.
+    pub own_stake: f64,
+
+    /// The amount staked by the addresses delegating to the validator.
+    pub delegated_stake: f64,
+
+    /// The amount of addresses delegating to the validator.
+    pub delegators: usize,
+}
+
+impl StakingValidator {
+    /// Get the total stake accounted for by the validator.
+    ///
+    /// # Returns
+    /// The own and delegated stakes.
+    pub fn total_stake(&self) -> f64 {
+        self.own_stake + self.delegated_stake
+    }
+}
+
+/// Stakes, delegations and pending rewards of the wallets.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StakingState {
+    /// The amount bonded by every staker, by address.
+    stakes: BTreeMap<Address, f64>,
+
+    /// The validator every delegator stakes for, by delegator address.
+    delegations: BTreeMap<Address, Address>,
+
+    /// The rewards not claimed yet, by address.
+    rewards: BTreeMap<Address, f64>,
+
+    /// The amount of epochs whose rewards were distributed.
+    epochs: usize,
+}
+
+impl StakingState {
+    /// Get the total amount bonded by the stakers.
+    ///
+    /// # Returns
+    /// The sum of the stakes.
+    pub(crate) fn total(&self) -> f64 {
+        self.stakes.values().sum()
+    }
+
+    /// Check whether an address stakes for itself.
+    ///
+    /// # Arguments
+    /// - `address`: The address.
+    ///
+    /// # Returns
+    /// `true` if the address has a stake and delegates it to no validator.
+    fn is_validator(&self, address: &str) -> bool {
+        self.stakes.contains_key(address) && !self.delegations.contains_key(address)
+    }
+
+    /// Release an amount of the stake of an address, the address no longer delegating if it
+    /// releases everything.
+    ///
+    /// # Arguments
+    /// - `address`: The address of a staker.
+    /// - `amount`: The released amount.
+    fn release(&mut self, address: &str, amount: f64) {
+        let remaining = self.stakes.get(address).copied().unwrap_or_default() - amount;
+
+        match remaining > 0.0 {
+            true => {
+                self.stakes.insert(address.to_string(), remaining);
+            }
+            false => {
+                self.stakes.remove(address);
+                self.delegations.remove(address);
+            }
+        }
+    }
+
+    /// Get the validator the stake of an address counts for.
+    ///
+    /// # Arguments
+    /// - `address`: The address of a staker.
+    ///
+    /// # Returns
+    /// The validator it delegates to, or the address itself.
+    fn validator_of<'a>(&'a self, address: &'a str) -> &'a str {
+        self.delegations
+            .get(address)
+            .map_or(address, String::as_str)
+    }
+}
+
+impl Chain {
+    /// Bond funds of a wallet, so they count for the validator set and earn epoch rewards.
+    ///
+    /// The amount is debited from the balance of the wallet and held by the staking module until
+    /// it is unstaked. A wallet staking for the first time is a validator, unless it delegates.
+    /// The bond is recorded by a transaction from the wallet to itself with no fee, mined like
+    /// the other transactions.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `amount`: The amount to bond.
+    /// - `keypair`: The keypair owning the wallet, required if the wallet has a public key.
+    ///
+    /// # Returns
+    /// The total stake of the wallet, or an error if the wallet cannot bond the amount.
+    pub fn stake(
+        &mut self,
+        address: &str,
+        amount: f64,
+        keypair: Option<&Keypair>,
+    ) -> Result<f64, ChainError> {
+        self.submit_staking(address, StakingOperation::Stake { amount }, keypair)?;
+
+        Ok(self.get_stake(address))
+    }
+
+    /// Release bonded funds back to the balance of a wallet.
+    ///
+    /// A wallet unstaking everything stops delegating. The addresses delegating to a validator
+    /// without stake earn no rewards until it stakes again or they delegate to another validator.
+    /// The release is recorded by a transaction like the bond.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `amount`: The amount to release.
+    /// - `keypair`: The keypair owning the wallet, required if the wallet has a public key.
+    ///
+    /// # Returns
+    /// The remaining stake of the wallet, or an error if it did not bond the amount.
+    pub fn unstake(
+        &mut self,
+        address: &str,
+        amount: f64,
+        keypair: Option<&Keypair>,
+    ) -> Result<f64, ChainError> {
+        self.submit_staking(address, StakingOperation::Unstake { amount }, keypair)?;
+
+        Ok(self.get_stake(address))
+    }
+
+    /// Count the stake of an address for a validator, instead of the address validating itself.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the delegator, which must have a stake.
+    /// - `validator`: The address of the validator, or the delegator itself to stop delegating.
+    ///
+    /// # Returns
+    /// An error if the address has no stake, the validator is not a validator, or other
+    /// addresses delegate to the address.
+    pub fn delegate(&mut self, address: &str, validator: &str) -> Result<(), ChainError> {
+        if !self.staking.stakes.contains_key(address) {
+            return Err(ChainError::InvalidStake(format!(
+                "{} has no stake to delegate",
+                address
+            )));
+        }
+
+        if validator == address {
+            self.staking.delegations.remove(address);
+
+            return Ok(());
+        }
+
+        if !self.staking.is_validator(validator) {
+            return Err(ChainError::InvalidStake(format!(
+                "{} is not a validator",
+                validator
+            )));
+        }
+
+        // Delegations are one level deep, so a validator cannot delegate its delegators
+        if self.staking.delegations.values().any(|to| to == address) {
+            return Err(ChainError::InvalidStake(format!(
+                "{} has delegators",
+                address
+            )));
+        }
+
+        self.staking
+            .delegations
+            .insert(address.to_string(), validator.to_string());
+
+        Ok(())
+    }
+
+    /// Credit the pending rewards of a wallet to its balance.
+    ///
+    /// The rewards are issued by a transaction like the bonds, unless none are pending.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `keypair`: The keypair owning the wallet, required if the wallet has a public key.
+    ///
+    /// # Returns
+    /// The claimed amount, 0 if no rewards are pending, or `UnknownSender` if the address is not
+    /// a wallet.
+    pub fn claim_rewards(
+        &mut self,
+        address: &str,
+        keypair: Option<&Keypair>,
+    ) -> Result<f64, ChainError> {
+        if !self.wallets.contains_key(address) {
+            return Err(ChainError::UnknownSender(address.to_string()));
+        }
+
+        let amount = self.pending_rewards(address);
+
+        if amount > 0.0 {
+            self.submit_staking(address, StakingOperation::Claim { amount }, keypair)?;
+        }
+
+        Ok(amount)
+    }
+
+    /// Submit a transaction recording an operation of the staking module.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `operation`: The staking operation.
+    /// - `keypair`: The keypair owning the wallet, `None` for a wallet without public key.
+    ///
+    /// # Returns
+    /// An error describing why the operation is invalid, if it is.
+    fn submit_staking(
+        &mut self,
+        address: &str,
+        operation: StakingOperation,
+        keypair: Option<&Keypair>,
+    ) -> Result<(), ChainError> {
+        let Some(nonce) = self.get_nonce(address) else {
+            return Err(ChainError::UnknownSender(address.to_string()));
+        };
+
+        let mut transaction = Transaction::builder()
+            .from(address)
+            .to(address)
+            .amount(0.0)
+            .fee(0.0)
+            .staking(operation)
+            .nonce(nonce)
+            .precision(self.config.timestamps)
+            .clock(self.clock())
+            .build();
+
+        if let Some(keypair) = keypair {
+            transaction = transaction.sign(keypair);
+        }
+
+        self.submit_transaction(transaction)
+    }
+
+    /// Apply a staking operation whose signature was verified and add it to the current
+    /// transactions.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction recording the staking operation.
+    ///
+    /// # Returns
+    /// An error describing why the staking operation is invalid, if it is.
+    pub(crate) fn accept_staking(&mut self, transaction: Transaction) -> Result<(), ChainError> {
+        let invalid = |reason: String| Err(ChainError::InvalidStake(reason));
+
+        let Some(operation) = &transaction.staking else {
+            return invalid("the transaction records no staking operation".to_string());
+        };
+
+        if transaction.from != transaction.to
+            || transaction.amount != 0.0
+            || transaction.fee != 0.0
+            || transaction.gas.is_some()
+            || transaction.asset_id.is_some()
+            || transaction.fee_payer.is_some()
+            || !transaction.inputs.is_empty()
+        {
+            return invalid("the transaction moves funds".to_string());
+        }
+
+        // Bonded funds leave the wallet without a transaction spending its outputs
+        if !self.ledger().model().is_account() {
+            return invalid("staking requires the account ledger model".to_string());
+        }
+
+        let address = &transaction.from;
+        let amount = operation.amount();
+
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(ChainError::InvalidAmount);
+        }
+
+        let Some(wallet) = self.wallets.get(address) else {
+            return Err(ChainError::UnknownSender(address.to_owned()));
+        };
+
+        match operation {
+            StakingOperation::Stake { .. } if wallet.balance < amount => {
+                return Err(ChainError::InsufficientBalance);
+            }
+            StakingOperation::Unstake { .. } if self.get_stake(address) < amount => {
+                return invalid(format!(
+                    "{} has a stake of {} only",
+                    address,
+                    self.get_stake(address)
+                ));
+            }
+            StakingOperation::Claim { .. } if self.pending_rewards(address) < amount => {
+                return invalid(format!(
+                    "{} has {} of pending rewards only",
+                    address,
+                    self.pending_rewards(address)
+                ));
+            }
+            _ => {}
+        }
+
+        if !self.reserve_mempool(&transaction) {
+            return Err(self.mempool_full());
+        }
+
+        if let Some(wallet) = self.wallets.get_mut(address) {
+            wallet.balance += operation.balance_change();
+            wallet.nonce += 1;
+            wallet.transactions.push(transaction.hash.to_owned());
+        }
+
+        match operation {
+            StakingOperation::Stake { .. } => {
+                *self.staking.stakes.entry(address.to_owned()).or_default() += amount;
+            }
+            StakingOperation::Unstake { .. } => {
+                self.staking.release(address, amount);
+            }
+            StakingOperation::Claim { .. } => {
+                let remaining = self.pending_rewards(address) - amount;
+
+                match remaining > 0.0 {
+                    true => self.staking.rewards.insert(address.to_owned(), remaining),
+                    false => self.staking.rewards.remove(address),
+                };
+
+                // The rewards are issued when they are claimed
+                self.issued_supply += amount;
+            }
+        }
+
+        self.current_transactions.push(transaction);
+
+        self.assert_invariants();
+
+        Ok(())
+    }
+
+    /// Undo the effects of a staking operation on the staking module, the balance of the wallet
+    /// and the supply being reverted with the other balances.
+    ///
+    /// A delegation dropped by unstaking everything is not restored.
+    ///
+    /// # Arguments
+    /// - `address`: The wallet address.
+    /// - `operation`: The staking operation.
+    pub(crate) fn revert_staking(&mut self, address: &str, operation: &StakingOperation) {
+        match *operation {
+            StakingOperation::Stake { amount } => self.staking.release(address, amount),
+            StakingOperation::Unstake { amount } => {
+                *self.staking.stakes.entry(address.to_string()).or_default() += amount;
+            }
+            StakingOperation::Claim { amount } => {
+                *self.staking.rewards.entry(address.to_string()).or_default() += amount;
+            }
+        }
+    }
+
+    /// Get the amount bonded by an address.
+    ///
+    /// # Arguments
+    /// - `address`: The address.
+    ///
+    /// # Returns
+    /// The stake, 0 if the address does not stake.
+    pub fn get_stake(&self, address: &str) -> f64 {
+        self.staking
+            .stakes
+            .get(address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Get the validator an address delegates its stake to.
+    ///
+    /// # Arguments
+    /// - `address`: The address.
+    ///
+    /// # Returns
+    /// The address of the validator, or `None` if the address does not delegate.
+    pub fn get_delegation(&self, address: &str) -> Option<&str> {
+        self.staking.delegations.get(address).map(String::as_str)
+    }
+
+    /// Get the rewards of an address not claimed yet.
+    ///
+    /// # Arguments
+    /// - `address`: The address.
+    ///
+    /// # Returns
+    /// The pending rewards, 0 if none.
+    pub fn pending_rewards(&self, address: &str) -> f64 {
+        self.staking
+            .rewards
+            .get(address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Get the total amount bonded by the stakers.
+    ///
+    /// # Returns
+    /// The sum of the stakes.
+    pub fn total_staked(&self) -> f64 {
+        self.staking.total()
+    }
+
+    /// Get the validator set.
+    ///
+    /// # Returns
+    /// The addresses staking for themselves, with their own and delegated stakes, from the
+    /// highest total stake, then by address.
+    pub fn staking_validators(&self) -> Vec<StakingValidator> {
+        let mut validators: BTreeMap<&str, StakingValidator> = self
+            .staking
+            .stakes
+            .iter()
+            .filter(|(address, _)| self.staking.is_validator(address))
+            .map(|(address, stake)| {
+                let validator = StakingValidator {
+                    address: address.to_owned(),
+                    own_stake: *stake,
+                    delegated_stake: 0.0,
+                    delegators: 0,
+                };
+
+                (address.as_str(), validator)
+            })
+            .collect();
+
+        for (delegator, validator) in &self.staking.delegations {
+            if let Some(validator) = validators.get_mut(validator.as_str()) {
+                validator.delegated_stake += self.get_stake(delegator);
+                validator.delegators += 1;
+            }
+        }
+
+        let mut validators: Vec<StakingValidator> = validators.into_values().collect();
+
+        validators.sort_by(|a, b| b.total_stake().total_cmp(&a.total_stake()));
+
+        validators
+    }
+
+    /// Distribute the rewards of the epochs completed by the last block.
+    ///
+    /// The reward of an epoch is shared between the validators in proportion to their total
+    /// stake. The share of a validator is split between itself and its delegators in proportion
+    /// to their stakes, the validator keeping the commission on the part of its delegators. The
+    /// stakes of the addresses delegating to an address that no longer validates earn nothing.
+    pub(crate) fn distribute_staking_rewards(&mut self) {
+        let config = &self.config.staking;
+
+        if config.epoch_length == 0 {
+            return;
+        }
+
+        // The genesis block opens the first epoch
+        let epochs = self.chain.len().saturating_sub(1) / config.epoch_length;
+        let pending = epochs.saturating_sub(self.staking.epochs);
+
+        self.staking.epochs = self.staking.epochs.max(epochs);
+
+        let validators = self.staking_validators();
+        let total: f64 = validators.iter().map(StakingValidator::total_stake).sum();
+
+        if pending == 0 || total <= 0.0 || config.epoch_reward <= 0.0 {
+            return;
+        }
+
+        let reward = config.epoch_reward * pending as f64;
+        let commission = config.commission.clamp(0.0, 1.0);
+        let mut rewards: BTreeMap<Address, f64> = BTreeMap::new();
+
+        for (staker, stake) in &self.staking.stakes {
+            let validator = self.staking.validator_of(staker);
+
+            if !self.staking.is_validator(validator) {
+                continue;
+            }
+
+            let share = reward * stake / total;
+
+            match validator == staker {
+                true => *rewards.entry(staker.to_owned()).or_default() += share,
+                false => {
+                    *rewards.entry(staker.to_owned()).or_default() += share * (1.0 - commission);
+                    *rewards.entry(validator.to_string()).or_default() += share * commission;
+                }
+            }
+        }
+
+        for (address, amount) in rewards {
+            *self.staking.rewards.entry(address).or_default() += amount;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChainConfig;
+
+    #[test]
+    fn test_staking_rewards() {
+        let config = ChainConfig {
+            staking: StakingConfig {
+                epoch_length: 2,
+                epoch_reward: 40.0,
+                commission: 0.5,
+            },
+            ..ChainConfig::default()
+        };
+        let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+        let validator = chain.create_wallet("v@mail.com".to_string()).unwrap();
+        let delegator = chain.create_wallet("d@mail.com".to_string()).unwrap();
+        let solo = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+        for address in [&validator, &delegator, &solo] {
+            chain.credit_wallet(address, 100.0);
+        }
+
+        assert_eq!(chain.stake(&validator, 30.0, None), Ok(30.0));
+        assert_eq!(chain.stake(&delegator, 20.0, None), Ok(20.0));
+        assert_eq!(chain.stake(&solo, 50.0, None), Ok(50.0));
+        assert_eq!(chain.delegate(&delegator, &validator), Ok(()));
+        assert!(chain.delegate(&solo, &delegator).is_err());
+        assert!(chain.delegate(&validator, &solo).is_err());
+        assert_eq!(chain.get_wallet_balance(validator.clone()), Some(70.0));
+
+        let validators = chain.staking_validators();
+
+        assert_eq!(validators.len(), 2);
+        assert!(validators
+            .iter()
+            .all(|validator| validator.total_stake() == 50.0));
+        assert_eq!(
+            validators
+                .iter()
+                .find(|candidate| candidate.address == validator)
+                .map(|candidate| candidate.delegators),
+            Some(1)
+        );
+
+        // The second block closes the first epoch
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.pending_rewards(&solo), 0.0);
+
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.pending_rewards(&solo), 20.0);
+        assert_eq!(chain.pending_rewards(&validator), 16.0);
+        assert_eq!(chain.pending_rewards(&delegator), 4.0);
+
+        assert_eq!(chain.claim_rewards(&validator, None), Ok(16.0));
+        assert_eq!(chain.claim_rewards(&validator, None), Ok(0.0));
+        assert_eq!(chain.unstake(&delegator, 20.0, None), Ok(0.0));
+        assert_eq!(chain.get_delegation(&delegator), None);
+        assert_eq!(chain.total_staked(), 80.0);
+        assert_eq!(chain.check_invariants(), Ok(()));
+    }
+}
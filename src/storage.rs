@@ -0,0 +1,281 @@
+use std::{
+    error::Error,
+    fmt,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    mem,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Block, Chain};
+
+/// Name of the file holding the blockchain state without its blocks.
+const STATE_FILE: &str = "state.json";
+
+/// Name of the file holding the blocks, one serialized block per line.
+const BLOCKS_FILE: &str = "blocks.jsonl";
+
+/// Name of the file holding the checksums of the other files.
+const CHECKSUMS_FILE: &str = "checksums.json";
+
+/// Inconsistency between the persisted files of a blockchain and their checksums.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The state file does not match its checksum.
+    CorruptState,
+
+    /// The block at a height does not match its checksum.
+    CorruptBlock(usize),
+
+    /// The blocks file holds another amount of blocks than recorded, typically after a truncation.
+    LengthMismatch {
+        /// The amount of blocks recorded in the checksums.
+        expected: usize,
+
+        /// The amount of blocks found in the blocks file.
+        found: usize,
+    },
+
+    /// The rolling checksum of the blockchain does not match the checksums of its files.
+    ChecksumMismatch,
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::CorruptState => write!(f, "Persisted state is corrupted"),
+            IntegrityError::CorruptBlock(height) => {
+                write!(f, "Persisted block {} is corrupted", height)
+            }
+            IntegrityError::LengthMismatch { expected, found } => {
+                write!(f, "Expected {} persisted blocks, found {}", expected, found)
+            }
+            IntegrityError::ChecksumMismatch => write!(f, "Persisted chain checksum is invalid"),
+        }
+    }
+}
+
+impl Error for IntegrityError {}
+
+impl From<IntegrityError> for io::Error {
+    fn from(err: IntegrityError) -> Self {
+        io::Error::new(ErrorKind::InvalidData, err)
+    }
+}
+
+/// Checksums of the persisted files of a blockchain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Checksums {
+    /// SHA-256 checksum of the state file.
+    state: String,
+
+    /// SHA-256 checksum of every serialized block, by height.
+    blocks: Vec<String>,
+
+    /// Rolling checksum chaining the state checksum with every block checksum.
+    chain: String,
+}
+
+/// File storage of a blockchain, with checksums detecting corrupted or truncated files.
+///
+/// The directory holds the state of the blockchain, its blocks with one block per line, and
+/// the checksum of the state and of every block, chained into a rolling checksum of the whole
+/// blockchain.
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    /// The directory holding the files.
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    /// Open a storage directory, creating it if it does not exist.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory holding the files.
+    ///
+    /// # Returns
+    /// The storage, or an error if the directory cannot be created.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        Ok(FileStorage {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Persist a blockchain, replacing the previously persisted one.
+    ///
+    /// The checksums are written last, so an interrupted save is detected by `verify_integrity`.
+    ///
+    /// # Arguments
+    /// - `chain`: The blockchain.
+    ///
+    /// # Returns
+    /// An error if a file cannot be written.
+    pub fn save(&self, chain: &Chain) -> io::Result<()> {
+        let mut state = chain.clone();
+        let blocks = mem::take(&mut state.chain);
+
+        let content = serde_json::to_vec(&state)?;
+        let state_checksum = checksum(&content);
+
+        fs::write(self.dir.join(STATE_FILE), content)?;
+
+        let mut file = io::BufWriter::new(File::create(self.dir.join(BLOCKS_FILE))?);
+        let mut block_checksums = Vec::with_capacity(blocks.len());
+
+        for block in &blocks {
+            let line = serde_json::to_vec(block)?;
+
+            block_checksums.push(checksum(&line));
+            file.write_all(&line)?;
+            file.write_all(b"\n")?;
+        }
+
+        file.flush()?;
+
+        let checksums = Checksums {
+            chain: rolling_checksum(&state_checksum, &block_checksums),
+            state: state_checksum,
+            blocks: block_checksums,
+        };
+
+        fs::write(
+            self.dir.join(CHECKSUMS_FILE),
+            serde_json::to_vec(&checksums)?,
+        )
+    }
+
+    /// Load the persisted blockchain after verifying the integrity of its files.
+    ///
+    /// # Returns
+    /// The blockchain, or an error if a file cannot be read or is corrupted.
+    pub fn load(&self) -> io::Result<Chain> {
+        self.verify_integrity()?;
+
+        let content = fs::read(self.dir.join(STATE_FILE))?;
+        let mut chain: Chain = serde_json::from_slice(&content).map_err(io::Error::from)?;
+
+        for line in BufReader::new(File::open(self.dir.join(BLOCKS_FILE))?).lines() {
+            let block: Block = serde_json::from_str(&line?).map_err(io::Error::from)?;
+
+            chain.chain.push(block);
+        }
+
+        chain.update_transaction_index();
+
+        Ok(chain)
+    }
+
+    /// Verify the persisted files against their checksums, without deserializing the blockchain.
+    ///
+    /// The blocks are read one line at a time, so the verification does not hold the blockchain
+    /// in memory.
+    ///
+    /// # Returns
+    /// An error if a file cannot be read, or an `InvalidData` error wrapping the `IntegrityError`
+    /// found first.
+    pub fn verify_integrity(&self) -> io::Result<()> {
+        let checksums: Checksums =
+            serde_json::from_slice(&fs::read(self.dir.join(CHECKSUMS_FILE))?)
+                .map_err(io::Error::from)?;
+
+        if rolling_checksum(&checksums.state, &checksums.blocks) != checksums.chain {
+            return Err(IntegrityError::ChecksumMismatch.into());
+        }
+
+        if checksum(&fs::read(self.dir.join(STATE_FILE))?) != checksums.state {
+            return Err(IntegrityError::CorruptState.into());
+        }
+
+        let mut reader = BufReader::new(File::open(self.dir.join(BLOCKS_FILE))?);
+        let mut line = Vec::new();
+        let mut found = 0;
+
+        loop {
+            line.clear();
+
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+
+            // A truncated last line lacks its line break
+            let Some(content) = line.strip_suffix(b"\n") else {
+                return Err(IntegrityError::CorruptBlock(found).into());
+            };
+
+            match checksums.blocks.get(found) {
+                Some(expected) if checksum(content) == *expected => found += 1,
+                Some(_) => return Err(IntegrityError::CorruptBlock(found).into()),
+                None => {
+                    return Err(IntegrityError::LengthMismatch {
+                        expected: checksums.blocks.len(),
+                        found: found + 1,
+                    }
+                    .into())
+                }
+            }
+        }
+
+        if found != checksums.blocks.len() {
+            return Err(IntegrityError::LengthMismatch {
+                expected: checksums.blocks.len(),
+                found,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the hex encoded SHA-256 checksum of some bytes.
+///
+/// # Arguments
+/// - `bytes`: The bytes.
+///
+/// # Returns
+/// The checksum.
+fn checksum(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+/// Chain the checksum of the state with the checksum of every block.
+///
+/// # Arguments
+/// - `state`: The checksum of the state.
+/// - `blocks`: The checksums of the blocks, by height.
+///
+/// # Returns
+/// The rolling checksum of the blockchain.
+fn rolling_checksum(state: &str, blocks: &[String]) -> String {
+    blocks.iter().fold(state.to_owned(), |rolling, block| {
+        checksum(format!("{}{}", rolling, block).as_bytes())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_checksum() {
+        let blocks = vec![checksum(b"a"), checksum(b"b")];
+        let swapped = vec![checksum(b"b"), checksum(b"a")];
+
+        assert_eq!(rolling_checksum("state", &[]), "state");
+        assert_ne!(
+            rolling_checksum("state", &blocks),
+            rolling_checksum("state", &swapped)
+        );
+    }
+}
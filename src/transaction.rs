@@ -1,7 +1,12 @@
-use chrono::Utc;
+use std::{marker::PhantomData, sync::Arc};
+
 use serde::{Deserialize, Serialize};
 
-use crate::Chain;
+use crate::{
+    encoding::{self, SIGNING_DOMAIN, TRANSACTION_DOMAIN},
+    Approval, Clock, Gas, KeyRotation, Keypair, Memo, OutPoint, Script, StakingOperation,
+    TimestampPrecision, Witness,
+};
 
 /// Exchange of assets between two parties.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,14 +20,86 @@ pub struct Transaction {
     /// Transaction receiver address.
     pub to: String,
 
-    /// Transaction fee.
+    /// Transaction fee paid by the sender on top of the amount, or by the fee payer.
     pub fee: f64,
 
     /// Transaction amount.
     pub amount: f64,
 
-    /// Transaction timestamp.
+    /// Position of the transaction among the transactions sent by the sender wallet, starting
+    /// from 0, so that a transaction cannot be replayed.
+    #[serde(default)]
+    pub nonce: u64,
+
+    /// Transaction timestamp, in seconds or milliseconds depending on the blockchain settings.
     pub timestamp: i64,
+
+    /// Counter breaking the ties between transactions of the same millisecond, `None` for
+    /// timestamps in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+
+    /// Signature of the sender, required when the sender wallet has a public key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+
+    /// Address of the wallet paying the fee instead of the sender, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+
+    /// Signature of the fee payer, required when the fee payer wallet has a public key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer_signature: Option<String>,
+
+    /// Signatures of the owners of a multi-signature sender wallet, at least its threshold.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<Approval>,
+
+    /// Key rotation of the sender wallet recorded by the transaction, which moves no funds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_rotation: Option<KeyRotation>,
+
+    /// Unspent outputs of the sender and the fee payer spent by the transaction, only used by
+    /// the UTXO ledger model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<OutPoint>,
+
+    /// Note attached by the sender, possibly encrypted to the receiver.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<Memo>,
+
+    /// Time before which the transaction cannot be included in a block, in seconds since the
+    /// Unix epoch like the block timestamps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locktime: Option<i64>,
+
+    /// Script locking the amount paid to the receiver, to be satisfied by the transaction
+    /// spending it, only used by the UTXO ledger model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Script>,
+
+    /// Preimage and signatures satisfying the scripts locking the spent outputs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness: Option<Witness>,
+
+    /// Gas limit and gas price of a metered transaction, whose fee is the maximum it pays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<Gas>,
+
+    /// Identifier of the asset transferred by the transaction, whose amount is counted in the
+    /// asset instead of the coins of the blockchain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_id: Option<String>,
+
+    /// Name of the asset issued by the transaction, whose amount is the supply credited to the
+    /// sender.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_name: Option<String>,
+
+    /// Operation of the staking module recorded by the transaction, which moves no funds to
+    /// other wallets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staking: Option<StakingOperation>,
 }
 
 impl Transaction {
@@ -38,29 +115,622 @@ impl Transaction {
     /// # Returns
     ///
     /// A new transaction with the given hash, sender, receiver, fee, amount, and timestamp.
+    #[deprecated(
+        note = "use `Transaction::builder()`, the positional fee and amount are easy to flip"
+    )]
     pub fn new(from: String, to: String, fee: f64, amount: f64) -> Self {
-        let timestamp = Utc::now().timestamp();
+        Transaction::builder()
+            .from(from)
+            .to(to)
+            .amount(amount)
+            .fee(fee)
+            .build()
+    }
 
-        // Create a hash of the transaction
-        let hash = Chain::hash(&(&from, &to, amount, timestamp));
+    /// Start building a new transaction.
+    ///
+    /// The sender, the receiver, the amount, and the fee must be set in this order before the
+    /// transaction can be built.
+    ///
+    /// # Returns
+    ///
+    /// A new transaction builder expecting the sender.
+    pub fn builder() -> TransactionBuilder<NoSender> {
+        TransactionBuilder {
+            from: String::new(),
+            to: String::new(),
+            amount: 0.0,
+            fee: 0.0,
+            nonce: 0,
+            fee_payer: None,
+            key_rotation: None,
+            inputs: Vec::new(),
+            memo: None,
+            locktime: None,
+            lock: None,
+            gas: None,
+            asset_id: None,
+            asset_name: None,
+            staking: None,
+            precision: TimestampPrecision::default(),
+            clock: None,
+            state: PhantomData,
+        }
+    }
 
-        // Create a new transaction
-        Transaction {
-            hash,
-            from,
-            to,
-            fee,
+    /// Verify that the transaction hash matches the transaction content.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the transaction is intact, `false` otherwise.
+    pub fn verify(&self) -> bool {
+        self.hash == self.compute_hash()
+    }
+
+    /// Get the weight of the transaction, counted against the maximum block weight.
+    ///
+    /// # Returns
+    ///
+    /// The amount of bytes of the serialized transaction.
+    pub fn weight(&self) -> usize {
+        serde_json::to_vec(self).map_or(0, |bytes| bytes.len())
+    }
+
+    /// Check whether the transaction is a system transaction, such as a key rotation, which may
+    /// use the block space reserved by the consensus rules.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the transaction carries a key rotation.
+    pub fn is_system(&self) -> bool {
+        self.key_rotation.is_some()
+    }
+
+    /// Sign the transaction with the keypair of the sender.
+    ///
+    /// # Arguments
+    ///
+    /// - `keypair` - The keypair owning the sender wallet.
+    ///
+    /// # Returns
+    ///
+    /// The signed transaction.
+    pub fn sign(mut self, keypair: &Keypair) -> Self {
+        self.signature = Some(keypair.sign(&self));
+        self
+    }
+
+    /// Sign the transaction with the keypair of the fee payer, agreeing to pay its fee.
+    ///
+    /// # Arguments
+    ///
+    /// - `keypair` - The keypair owning the fee payer wallet.
+    ///
+    /// # Returns
+    ///
+    /// The sponsored transaction.
+    pub fn sponsor(mut self, keypair: &Keypair) -> Self {
+        self.fee_payer_signature = Some(keypair.sign(&self));
+        self
+    }
+
+    /// Get the address of the wallet paying the fee.
+    ///
+    /// # Returns
+    ///
+    /// The fee payer if the transaction is sponsored, the sender otherwise.
+    pub fn payer(&self) -> &str {
+        self.fee_payer.as_deref().unwrap_or(&self.from)
+    }
+
+    /// Get the payload covered by the signatures: the transaction hash, the fee, the fee payer
+    /// of sponsored transactions, and the gas of metered transactions.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the payload.
+    pub fn signing_payload(&self) -> String {
+        encoding::hash(
+            SIGNING_DOMAIN,
+            &[&self.hash, &self.fee, &self.fee_payer, &self.gas],
+        )
+    }
+
+    /// Check whether the transaction can be included in a block.
+    ///
+    /// # Arguments
+    ///
+    /// - `timestamp` - The timestamp of the block, in seconds.
+    ///
+    /// # Returns
+    ///
+    /// `true` unless the locktime of the transaction is later than the timestamp.
+    pub fn is_unlocked(&self, timestamp: i64) -> bool {
+        self.locktime.is_none_or(|locktime| locktime <= timestamp)
+    }
+
+    /// Compute the hash of the canonical encoding of the transaction content: the sender, the
+    /// receiver, the amount, the nonce, the timestamp and its tie-breaker counter, the key
+    /// rotation, the inputs, the memo, the locktime, the locking script, the asset with the name
+    /// it is issued under, and the staking operation.
+    ///
+    /// # Returns
+    ///
+    /// The hash of the transaction.
+    fn compute_hash(&self) -> String {
+        encoding::hash(
+            TRANSACTION_DOMAIN,
+            &[
+                &self.from,
+                &self.to,
+                &self.amount,
+                &self.nonce,
+                &self.timestamp,
+                &self.sequence,
+                &self.key_rotation,
+                &self.inputs,
+                &self.memo,
+                &self.locktime,
+                &self.lock,
+                &self.asset_id,
+                &self.asset_name,
+                &self.staking,
+            ],
+        )
+    }
+}
+
+/// State of a transaction builder expecting the sender.
+#[derive(Debug)]
+pub struct NoSender;
+
+/// State of a transaction builder expecting the receiver.
+#[derive(Debug)]
+pub struct NoReceiver;
+
+/// State of a transaction builder expecting the amount.
+#[derive(Debug)]
+pub struct NoAmount;
+
+/// State of a transaction builder expecting the fee.
+#[derive(Debug)]
+pub struct NoFee;
+
+/// State of a transaction builder ready to build the transaction.
+#[derive(Debug)]
+pub struct Ready;
+
+/// Builder of a transaction, enforcing at compile time that every field is set.
+#[derive(Debug)]
+pub struct TransactionBuilder<S> {
+    /// Transaction sender address.
+    from: String,
+
+    /// Transaction receiver address.
+    to: String,
+
+    /// Transaction amount.
+    amount: f64,
+
+    /// Transaction fee paid by the sender on top of the amount, or by the fee payer.
+    fee: f64,
+
+    /// Position of the transaction among the transactions of the sender.
+    nonce: u64,
+
+    /// Address of the wallet paying the fee instead of the sender, if any.
+    fee_payer: Option<String>,
+
+    /// Key rotation of the sender wallet recorded by the transaction, if any.
+    key_rotation: Option<KeyRotation>,
+
+    /// Unspent outputs spent by the transaction.
+    inputs: Vec<OutPoint>,
+
+    /// Note attached by the sender, if any.
+    memo: Option<Memo>,
+
+    /// Time before which the transaction cannot be included in a block, if any.
+    locktime: Option<i64>,
+
+    /// Script locking the amount paid to the receiver, if any.
+    lock: Option<Script>,
+
+    /// Gas limit and gas price of a metered transaction, if any.
+    gas: Option<Gas>,
+
+    /// Identifier of the transferred asset, if any.
+    asset_id: Option<String>,
+
+    /// Name of the issued asset, if any.
+    asset_name: Option<String>,
+
+    /// Operation of the staking module recorded by the transaction, if any.
+    staking: Option<StakingOperation>,
+
+    /// Precision of the transaction timestamp.
+    precision: TimestampPrecision,
+
+    /// Clock of the transaction timestamp, the system clock if `None`.
+    clock: Option<Arc<dyn Clock>>,
+
+    /// The fields set so far.
+    state: PhantomData<S>,
+}
+
+impl<S> TransactionBuilder<S> {
+    /// Move the builder to the next state.
+    fn into_state<T>(self) -> TransactionBuilder<T> {
+        TransactionBuilder {
+            from: self.from,
+            to: self.to,
+            amount: self.amount,
+            fee: self.fee,
+            nonce: self.nonce,
+            fee_payer: self.fee_payer,
+            key_rotation: self.key_rotation,
+            inputs: self.inputs,
+            memo: self.memo,
+            locktime: self.locktime,
+            lock: self.lock,
+            gas: self.gas,
+            asset_id: self.asset_id,
+            asset_name: self.asset_name,
+            staking: self.staking,
+            precision: self.precision,
+            clock: self.clock,
+            state: PhantomData,
+        }
+    }
+}
+
+impl TransactionBuilder<NoSender> {
+    /// Set the sender.
+    ///
+    /// # Arguments
+    ///
+    /// - `from` - The transaction sender address.
+    ///
+    /// # Returns
+    ///
+    /// The builder expecting the receiver.
+    pub fn from(self, from: impl Into<String>) -> TransactionBuilder<NoReceiver> {
+        TransactionBuilder {
+            from: from.into(),
+            ..self.into_state()
+        }
+    }
+}
+
+impl TransactionBuilder<NoReceiver> {
+    /// Set the receiver.
+    ///
+    /// # Arguments
+    ///
+    /// - `to` - The transaction receiver address.
+    ///
+    /// # Returns
+    ///
+    /// The builder expecting the amount.
+    pub fn to(self, to: impl Into<String>) -> TransactionBuilder<NoAmount> {
+        TransactionBuilder {
+            to: to.into(),
+            ..self.into_state()
+        }
+    }
+}
+
+impl TransactionBuilder<NoAmount> {
+    /// Set the amount.
+    ///
+    /// # Arguments
+    ///
+    /// - `amount` - The transaction amount.
+    ///
+    /// # Returns
+    ///
+    /// The builder expecting the fee.
+    pub fn amount(self, amount: f64) -> TransactionBuilder<NoFee> {
+        TransactionBuilder {
             amount,
-            timestamp,
+            ..self.into_state()
         }
     }
 }
 
+impl TransactionBuilder<NoFee> {
+    /// Set the fee paid on top of the amount.
+    ///
+    /// # Arguments
+    ///
+    /// - `fee` - The transaction fee.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn fee(self, fee: f64) -> TransactionBuilder<Ready> {
+        TransactionBuilder {
+            fee,
+            ..self.into_state()
+        }
+    }
+
+    /// Set the fee as a share of the amount, like `Chain::add_transaction` does.
+    ///
+    /// # Arguments
+    ///
+    /// - `rate` - The share of the amount paid as a fee.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn fee_rate(self, rate: f64) -> TransactionBuilder<Ready> {
+        let fee = self.amount * rate;
+
+        self.fee(fee)
+    }
+
+    /// Meter the transaction, paying for the gas it consumes instead of a fixed fee.
+    ///
+    /// # Arguments
+    ///
+    /// - `gas` - The gas limit and the gas price, the fee being set to their product.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn gas(self, gas: Gas) -> TransactionBuilder<Ready> {
+        TransactionBuilder {
+            gas: Some(gas),
+            ..self.fee(gas.max_fee())
+        }
+    }
+}
+
+impl TransactionBuilder<Ready> {
+    /// Set the precision of the transaction timestamp, in whole seconds by default.
+    ///
+    /// # Arguments
+    ///
+    /// - `precision` - The precision of the timestamp.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn precision(self, precision: TimestampPrecision) -> Self {
+        TransactionBuilder { precision, ..self }
+    }
+
+    /// Set the nonce of the transaction, 0 by default.
+    ///
+    /// # Arguments
+    ///
+    /// - `nonce` - The nonce expected from the sender wallet, see `Chain::get_nonce`.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn nonce(self, nonce: u64) -> Self {
+        TransactionBuilder { nonce, ..self }
+    }
+
+    /// Let another wallet pay the fee, the sender paying the amount only.
+    ///
+    /// # Arguments
+    ///
+    /// - `fee_payer` - The address of the wallet paying the fee.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn fee_payer(self, fee_payer: impl Into<String>) -> Self {
+        TransactionBuilder {
+            fee_payer: Some(fee_payer.into()),
+            ..self
+        }
+    }
+
+    /// Record a key rotation of the sender wallet, built from the sender to itself with no
+    /// amount and no fee.
+    ///
+    /// # Arguments
+    ///
+    /// - `key_rotation` - The previous and the new public keys of the sender wallet.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn key_rotation(self, key_rotation: KeyRotation) -> Self {
+        TransactionBuilder {
+            key_rotation: Some(key_rotation),
+            ..self
+        }
+    }
+
+    /// Set the unspent outputs spent by the transaction, required by the UTXO ledger model.
+    ///
+    /// # Arguments
+    ///
+    /// - `inputs` - The outputs of the sender and the fee payer, usually picked with
+    ///   `Chain::select_inputs`.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn inputs(self, inputs: Vec<OutPoint>) -> Self {
+        TransactionBuilder { inputs, ..self }
+    }
+
+    /// Attach a memo, such as a payment reference.
+    ///
+    /// # Arguments
+    ///
+    /// - `memo` - The memo, plain or encrypted to the receiver with `Memo::encrypt`.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn memo(self, memo: Memo) -> Self {
+        TransactionBuilder {
+            memo: Some(memo),
+            ..self
+        }
+    }
+
+    /// Lock the transaction until a time, before which it stays pending.
+    ///
+    /// # Arguments
+    ///
+    /// - `unlock_at` - The earliest block timestamp including the transaction, in seconds since
+    ///   the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn locktime(self, unlock_at: i64) -> Self {
+        TransactionBuilder {
+            locktime: Some(unlock_at),
+            ..self
+        }
+    }
+
+    /// Lock the amount paid to the receiver with a script, which the transaction spending it
+    /// must satisfy.
+    ///
+    /// # Arguments
+    ///
+    /// - `script` - The locking script.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn lock(self, script: Script) -> Self {
+        TransactionBuilder {
+            lock: Some(script),
+            ..self
+        }
+    }
+
+    /// Transfer an asset instead of the coins of the blockchain, the amount being counted in the
+    /// asset and the fee still paid in coins.
+    ///
+    /// # Arguments
+    ///
+    /// - `asset_id` - The identifier of the asset, see `Chain::issue_asset`.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn asset(self, asset_id: impl Into<String>) -> Self {
+        TransactionBuilder {
+            asset_id: Some(asset_id.into()),
+            ..self
+        }
+    }
+
+    /// Issue a named asset whose supply is the amount, built from the sender to itself with no
+    /// fee, see `Chain::issue_asset`.
+    ///
+    /// # Arguments
+    ///
+    /// - `asset_id` - The identifier of the asset, derived from the issuer and the name.
+    /// - `name` - The name of the asset.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn asset_issuance(self, asset_id: impl Into<String>, name: impl Into<String>) -> Self {
+        TransactionBuilder {
+            asset_id: Some(asset_id.into()),
+            asset_name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Record an operation of the staking module on the funds of the sender wallet, built from
+    /// the sender to itself with no amount and no fee, see `Chain::stake`.
+    ///
+    /// # Arguments
+    ///
+    /// - `operation` - The staking operation.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn staking(self, operation: StakingOperation) -> Self {
+        TransactionBuilder {
+            staking: Some(operation),
+            ..self
+        }
+    }
+
+    /// Set the clock of the transaction timestamp, the system clock by default.
+    ///
+    /// # Arguments
+    ///
+    /// - `clock` - The clock.
+    ///
+    /// # Returns
+    ///
+    /// The builder ready to build the transaction.
+    pub fn clock(self, clock: Arc<dyn Clock>) -> Self {
+        TransactionBuilder {
+            clock: Some(clock),
+            ..self
+        }
+    }
+
+    /// Build the transaction.
+    ///
+    /// # Returns
+    ///
+    /// A new unsigned transaction with its hash and timestamp. Transactions from wallets with a
+    /// public key must be signed with `Transaction::sign` before being submitted with
+    /// `Chain::submit_transaction`.
+    pub fn build(self) -> Transaction {
+        let (timestamp, sequence) = match &self.clock {
+            Some(clock) => self.precision.now_on(clock.as_ref()),
+            None => self.precision.now(),
+        };
+
+        let mut transaction = Transaction {
+            hash: String::new(),
+            from: self.from,
+            to: self.to,
+            fee: self.fee,
+            amount: self.amount,
+            nonce: self.nonce,
+            timestamp,
+            sequence,
+            signature: None,
+            fee_payer: self.fee_payer,
+            fee_payer_signature: None,
+            approvals: Vec::new(),
+            key_rotation: self.key_rotation,
+            inputs: self.inputs,
+            memo: self.memo,
+            locktime: self.locktime,
+            lock: self.lock,
+            witness: None,
+            gas: self.gas,
+            asset_id: self.asset_id,
+            asset_name: self.asset_name,
+            staking: self.staking,
+        };
+
+        // Create a hash of the transaction
+        transaction.hash = transaction.compute_hash();
+
+        transaction
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_new_transaction() {
         let from = "0x 1234".to_string();
         let to = "0x 5678".to_string();
@@ -73,4 +743,137 @@ mod tests {
         assert_eq!(transaction.fee, fee);
         assert_eq!(transaction.amount, amount);
     }
+
+    #[test]
+    fn test_verify_transaction() {
+        let mut transaction = Transaction::builder()
+            .from("0x 1234")
+            .to("0x 5678")
+            .amount(1.0)
+            .fee(0.1)
+            .build();
+
+        assert!(transaction.verify());
+
+        transaction.amount = 1000.0;
+
+        assert!(!transaction.verify());
+    }
+
+    #[test]
+    fn test_builder_nonce() {
+        let transaction = Transaction::builder()
+            .from("0x 1234")
+            .to("0x 5678")
+            .amount(1.0)
+            .fee(0.1)
+            .nonce(3)
+            .build();
+
+        assert_eq!(transaction.nonce, 3);
+        assert!(transaction.verify());
+
+        // The hash, and so the signatures, cover the nonce
+        let mut replayed = transaction.clone();
+        replayed.nonce = 4;
+
+        assert!(!replayed.verify());
+    }
+
+    #[test]
+    fn test_builder() {
+        let transaction = Transaction::builder()
+            .from("0x 1234")
+            .to("0x 5678")
+            .amount(100.0)
+            .fee(0.1)
+            .build();
+
+        assert_eq!(transaction.from, "0x 1234");
+        assert_eq!(transaction.to, "0x 5678");
+        assert_eq!(transaction.fee, 0.1);
+        assert_eq!(transaction.amount, 100.0);
+        assert!(transaction.verify());
+    }
+
+    #[test]
+    fn test_builder_fee_payer() {
+        let build = |fee_payer: Option<&str>| {
+            let builder = Transaction::builder()
+                .from("0x 1234")
+                .to("0x 5678")
+                .amount(100.0)
+                .fee(0.1);
+
+            match fee_payer {
+                Some(fee_payer) => builder.fee_payer(fee_payer),
+                None => builder,
+            }
+            .build()
+        };
+
+        let sponsored = build(Some("0x 9abc"));
+        let unsponsored = build(None);
+
+        assert_eq!(sponsored.payer(), "0x 9abc");
+        assert_eq!(unsponsored.payer(), "0x 1234");
+        assert!(sponsored.verify());
+
+        // The signatures cover the fee payer
+        let mut redirected = sponsored.clone();
+        redirected.fee_payer = Some("0x def0".to_string());
+
+        assert_ne!(sponsored.signing_payload(), redirected.signing_payload());
+    }
+
+    #[test]
+    fn test_builder_fee_rate() {
+        let transaction = Transaction::builder()
+            .from("0x 1234")
+            .to("0x 5678")
+            .amount(100.0)
+            .fee_rate(0.01)
+            .build();
+
+        assert_eq!(transaction.fee, 1.0);
+    }
+
+    #[test]
+    fn test_builder_precision_millis() {
+        let build = || {
+            Transaction::builder()
+                .from("0x 1234")
+                .to("0x 5678")
+                .amount(100.0)
+                .fee(0.1)
+                .precision(TimestampPrecision::Millis)
+                .build()
+        };
+
+        let first = build();
+        let second = build();
+
+        assert!(first.sequence.is_some());
+        assert!((first.timestamp, first.sequence) < (second.timestamp, second.sequence));
+        assert_ne!(first.hash, second.hash);
+        assert!(first.verify());
+        assert!(second.verify());
+    }
+
+    #[test]
+    fn test_verify_transaction_in_seconds() {
+        let json = |hash: &str| {
+            format!(
+                r#"{{"hash":"{}","from":"0x 1234","to":"0x 5678","fee":0.1,"amount":1.0,"nonce":0,"timestamp":1}}"#,
+                hash
+            )
+        };
+        let mut transaction: Transaction = serde_json::from_str(&json("")).unwrap();
+        transaction.hash = transaction.compute_hash();
+        let json = json(&transaction.hash);
+
+        assert_eq!(transaction.sequence, None);
+        assert!(transaction.verify());
+        assert_eq!(serde_json::to_string(&transaction).unwrap(), json);
+    }
 }
@@ -0,0 +1,424 @@
+use std::{
+    collections::HashSet,
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use snow::{Builder, HandshakeState, TransportState};
+
+use crate::{
+    keys::exchange_public_key, node::NodeAccess, Dialer, Keypair, MempoolMessage, Node, Peer,
+};
+
+/// Noise handshake authenticating both nodes by their static keys.
+const NOISE_PATTERN: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// Maximum length of a Noise message.
+const MAX_FRAME_LENGTH: usize = 65_535;
+
+/// Length of the authentication tag appended to every encrypted frame.
+const TAG_LENGTH: usize = 16;
+
+/// Maximum length of a message accepted from a peer.
+const MAX_MESSAGE_LENGTH: usize = 16 * 1024 * 1024;
+
+/// An encrypted and authenticated connection to another node.
+///
+/// Both ends prove the ownership of their node identity key during a Noise XX handshake, and
+/// every message is then encrypted with ChaCha20-Poly1305. The node dialing the connection
+/// does not need to accept incoming connections, so nodes behind a NAT take part in the network
+/// through their outbound connections only.
+pub struct SecureChannel {
+    /// The underlying TCP connection.
+    stream: TcpStream,
+
+    /// The Noise session keys.
+    transport: TransportState,
+
+    /// The hex encoded public key of the remote node identity.
+    remote_id: String,
+}
+
+impl SecureChannel {
+    /// Dial a node and perform the handshake as the initiator.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the node, as `host:port`.
+    /// - `identity`: The identity key of the local node.
+    /// - `timeout`: The timeout of the connection and of every read and write.
+    ///
+    /// # Returns
+    /// The channel, or an error if the node is unreachable or the handshake fails.
+    pub fn connect(address: &str, identity: &Keypair, timeout: Duration) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Unresolved address"))?;
+        let stream = TcpStream::connect_timeout(&address, timeout)?;
+
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        SecureChannel::handshake(stream, identity, true)
+    }
+
+    /// Perform the handshake as the responder on an accepted connection.
+    ///
+    /// # Arguments
+    /// - `stream`: The accepted connection.
+    /// - `identity`: The identity key of the local node.
+    ///
+    /// # Returns
+    /// The channel, or an error if the handshake fails.
+    pub fn accept(stream: TcpStream, identity: &Keypair) -> io::Result<Self> {
+        SecureChannel::handshake(stream, identity, false)
+    }
+
+    /// Update the timeout of every read and write on the connection.
+    ///
+    /// # Arguments
+    /// - `timeout`: The timeout, or `None` to wait indefinitely.
+    ///
+    /// # Returns
+    /// An error if the timeout cannot be set.
+    pub(crate) fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.set_write_timeout(timeout)
+    }
+
+    /// Get the identity of the remote node.
+    ///
+    /// # Returns
+    /// The hex encoded public key the remote node proved the ownership of during the handshake.
+    pub fn remote_id(&self) -> &str {
+        &self.remote_id
+    }
+
+    /// Send a message to the remote node.
+    ///
+    /// The message is split into encrypted frames, preceded by a frame holding its length.
+    ///
+    /// # Arguments
+    /// - `message`: The message.
+    ///
+    /// # Returns
+    /// An error if the message cannot be encrypted or written.
+    pub fn write_message<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let payload = serde_json::to_vec(message)?;
+
+        self.write_frame(&(payload.len() as u32).to_be_bytes())?;
+
+        for chunk in payload.chunks(MAX_FRAME_LENGTH - TAG_LENGTH) {
+            self.write_frame(chunk)?;
+        }
+
+        self.stream.flush()
+    }
+
+    /// Receive a message from the remote node.
+    ///
+    /// # Returns
+    /// The message, or an error if the connection is closed or a frame cannot be decrypted.
+    pub fn read_message<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        let length: [u8; 4] = self
+            .read_frame()?
+            .try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid message length"))?;
+        let length = u32::from_be_bytes(length) as usize;
+
+        if length > MAX_MESSAGE_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Message is too large",
+            ));
+        }
+
+        let mut payload = Vec::with_capacity(length);
+
+        while payload.len() < length {
+            payload.extend(self.read_frame()?);
+        }
+
+        serde_json::from_slice(&payload).map_err(io::Error::from)
+    }
+
+    /// Exchange the handshake messages and switch to the session keys.
+    ///
+    /// Both nodes send the public key of their identity in their encrypted handshake message,
+    /// which must match the static key they authenticated with.
+    ///
+    /// # Arguments
+    /// - `stream`: The connection.
+    /// - `identity`: The identity key of the local node.
+    /// - `initiator`: Whether the local node dialed the connection.
+    ///
+    /// # Returns
+    /// The channel, or an error if the handshake fails.
+    fn handshake(mut stream: TcpStream, identity: &Keypair, initiator: bool) -> io::Result<Self> {
+        let mut handshake = handshake_state(identity, initiator).map_err(io::Error::other)?;
+        let id = identity.public_key();
+        let mut buffer = vec![0u8; MAX_FRAME_LENGTH];
+        let mut remote_id = String::new();
+        let mut writing = initiator;
+        let mut first = true;
+
+        while !handshake.is_handshake_finished() {
+            if writing {
+                // The first message is not encrypted, so the identity waits for the next one
+                let payload = match first {
+                    true => &[][..],
+                    false => id.as_bytes(),
+                };
+                let length = handshake
+                    .write_message(payload, &mut buffer)
+                    .map_err(io::Error::other)?;
+
+                send_frame(&mut stream, &buffer[..length])?;
+            } else {
+                let frame = receive_frame(&mut stream)?;
+                let length = handshake
+                    .read_message(&frame, &mut buffer)
+                    .map_err(io::Error::other)?;
+
+                if length > 0 {
+                    remote_id = String::from_utf8_lossy(&buffer[..length]).into_owned();
+                }
+            }
+
+            writing = !writing;
+            first = false;
+        }
+
+        let authenticated = match (
+            handshake.get_remote_static(),
+            exchange_public_key(&remote_id),
+        ) {
+            (Some(remote), Some(expected)) => remote == expected,
+            _ => false,
+        };
+
+        if !authenticated {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Remote identity does not match its handshake key",
+            ));
+        }
+
+        Ok(SecureChannel {
+            stream,
+            transport: handshake.into_transport_mode().map_err(io::Error::other)?,
+            remote_id,
+        })
+    }
+
+    /// Encrypt and write a single frame.
+    ///
+    /// # Arguments
+    /// - `plaintext`: The content of the frame.
+    ///
+    /// # Returns
+    /// An error if the frame cannot be encrypted or written.
+    fn write_frame(&mut self, plaintext: &[u8]) -> io::Result<()> {
+        let mut buffer = vec![0u8; plaintext.len() + TAG_LENGTH];
+        let length = self
+            .transport
+            .write_message(plaintext, &mut buffer)
+            .map_err(io::Error::other)?;
+
+        send_frame(&mut self.stream, &buffer[..length])
+    }
+
+    /// Read and decrypt a single frame.
+    ///
+    /// # Returns
+    /// The content of the frame, or an error if it cannot be read or decrypted.
+    fn read_frame(&mut self) -> io::Result<Vec<u8>> {
+        let frame = receive_frame(&mut self.stream)?;
+        let mut buffer = vec![0u8; frame.len()];
+        let length = self
+            .transport
+            .read_message(&frame, &mut buffer)
+            .map_err(io::Error::other)?;
+
+        buffer.truncate(length);
+
+        Ok(buffer)
+    }
+}
+
+impl Peer for SecureChannel {
+    fn send(&mut self, message: MempoolMessage) -> Option<MempoolMessage> {
+        self.write_message(&message).ok()?;
+        self.read_message().ok()?
+    }
+
+    fn remote_id(&self) -> Option<String> {
+        Some(self.remote_id.to_owned())
+    }
+}
+
+/// Dials nodes over encrypted connections.
+#[derive(Clone, Debug)]
+pub struct TcpDialer {
+    /// The identity key of the local node.
+    pub identity: Keypair,
+
+    /// Public keys of the node identities accepted as peers, any node if `None`.
+    pub trusted: Option<HashSet<String>>,
+
+    /// The timeout of the connection and of every read and write.
+    pub timeout: Duration,
+}
+
+impl TcpDialer {
+    /// Create a new dialer accepting any node.
+    ///
+    /// # Arguments
+    /// - `identity`: The identity key of the local node.
+    ///
+    /// # Returns
+    /// A new dialer with a timeout of 10 seconds.
+    pub fn new(identity: Keypair) -> Self {
+        TcpDialer {
+            identity,
+            trusted: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Dialer for TcpDialer {
+    type Peer = SecureChannel;
+
+    fn dial(&mut self, address: &str) -> Option<SecureChannel> {
+        let channel = SecureChannel::connect(address, &self.identity, self.timeout).ok()?;
+
+        match &self.trusted {
+            Some(trusted) if !trusted.contains(channel.remote_id()) => None,
+            _ => Some(channel),
+        }
+    }
+}
+
+impl Node {
+    /// Answer the messages of a connected node until it disconnects or subscribes.
+    ///
+    /// # Arguments
+    /// - `channel`: The connection to the node.
+    ///
+    /// # Returns
+    /// An error if the node is not allowed, announces another identity than the one it proved,
+    /// or the connection fails before the node disconnects.
+    pub fn serve(&mut self, channel: &mut SecureChannel) -> io::Result<()> {
+        Node::serve_with(self, channel, |_, _, _| {}).map(|_| ())
+    }
+
+    /// Answer the messages of a connected node, accessing the node between messages only.
+    ///
+    /// # Arguments
+    /// - `node`: The access to the node.
+    /// - `channel`: The connection to the node.
+    /// - `relay`: Relays the blocks and transactions received from the node, once answered.
+    ///
+    /// # Returns
+    /// `true` if the node subscribed to the blocks and transactions of this node, which are
+    /// then sent on the connection, or an error if the node is not allowed, announces another
+    /// identity than the one it proved, or the connection fails before the node disconnects.
+    pub(crate) fn serve_with<A: NodeAccess>(
+        node: &mut A,
+        channel: &mut SecureChannel,
+        mut relay: impl FnMut(&mut A, &str, MempoolMessage),
+    ) -> io::Result<bool> {
+        if !node.with(|node| node.is_allowed_peer(channel.remote_id())) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Peer is not allowed",
+            ));
+        }
+
+        loop {
+            let message: MempoolMessage = match channel.read_message() {
+                Ok(message) => message,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+                Err(err) => return Err(err),
+            };
+
+            if matches!(&message, MempoolMessage::Hello { id, .. } if id != channel.remote_id()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Peer announced another identity",
+                ));
+            }
+
+            if matches!(message, MempoolMessage::Subscribe) {
+                return Ok(true);
+            }
+
+            let relayed = matches!(
+                message,
+                MempoolMessage::Block { .. } | MempoolMessage::Transactions { .. }
+            )
+            .then(|| message.clone());
+            let answer = node.with(|node| node.handle(message));
+
+            channel.write_message(&answer)?;
+
+            if let Some(message) = relayed {
+                relay(node, channel.remote_id(), message);
+            }
+        }
+    }
+}
+
+/// Create a Noise handshake keyed by a node identity.
+///
+/// # Arguments
+/// - `identity`: The identity key of the local node.
+/// - `initiator`: Whether the local node dialed the connection.
+///
+/// # Returns
+/// The handshake state.
+fn handshake_state(identity: &Keypair, initiator: bool) -> Result<HandshakeState, snow::Error> {
+    let private_key = identity.exchange_private_key();
+    let builder = Builder::new(NOISE_PATTERN.parse()?).local_private_key(&private_key);
+
+    if initiator {
+        builder.build_initiator()
+    } else {
+        builder.build_responder()
+    }
+}
+
+/// Write a length-prefixed frame.
+///
+/// # Arguments
+/// - `stream`: The connection.
+/// - `frame`: The frame.
+///
+/// # Returns
+/// An error if the frame cannot be written.
+fn send_frame(stream: &mut TcpStream, frame: &[u8]) -> io::Result<()> {
+    stream.write_all(&(frame.len() as u16).to_be_bytes())?;
+    stream.write_all(frame)
+}
+
+/// Read a length-prefixed frame.
+///
+/// # Arguments
+/// - `stream`: The connection.
+///
+/// # Returns
+/// The frame, or an error if the connection is closed.
+fn receive_frame(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut length = [0u8; 2];
+
+    stream.read_exact(&mut length)?;
+
+    let mut frame = vec![0u8; u16::from_be_bytes(length) as usize];
+
+    stream.read_exact(&mut frame)?;
+
+    Ok(frame)
+}
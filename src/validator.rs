@@ -0,0 +1,145 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{Chain, ChainError, Transaction};
+
+/// Identifier of a registered transaction validator.
+pub type ValidatorId = usize;
+
+/// The outcome of a transaction validator.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    /// The transaction passes this validator and goes on to the next one.
+    Continue,
+
+    /// The transaction is accepted without running the remaining validators.
+    Accept,
+
+    /// The transaction is rejected without running the remaining validators.
+    Reject(String),
+}
+
+/// A rule of the embedder applied to the submitted transactions, such as a KYC check or a
+/// business rule, on top of the consensus rules.
+pub trait TransactionValidator: Debug + Send + Sync {
+    /// Validate a transaction that satisfies the consensus rules, before it is applied.
+    ///
+    /// # Arguments
+    /// - `chain`: The blockchain, without the transaction.
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// Whether the transaction goes on to the next validator, is accepted, or is rejected.
+    fn validate(&self, chain: &Chain, transaction: &Transaction) -> Verdict;
+}
+
+/// A registered transaction validator.
+#[derive(Clone, Debug)]
+pub(crate) struct RegisteredValidator {
+    /// The identifier of the validator.
+    id: ValidatorId,
+
+    /// The validator.
+    validator: Arc<dyn TransactionValidator>,
+}
+
+impl Chain {
+    /// Register a validator run on the transactions submitted to the blockchain.
+    ///
+    /// The validators run in registration order, after the transaction passed the consensus
+    /// validation and before it is applied, until one of them accepts or rejects it. They are
+    /// local rules like the spending policies: they are not run on the transactions of imported
+    /// blocks nor on key rotations, and are not persisted with the blockchain.
+    ///
+    /// # Arguments
+    /// - `validator`: The validator.
+    ///
+    /// # Returns
+    /// The identifier of the validator.
+    pub fn add_validator<V: TransactionValidator + 'static>(
+        &mut self,
+        validator: V,
+    ) -> ValidatorId {
+        let id = self.next_validator_id;
+        self.next_validator_id += 1;
+
+        self.validators.push(RegisteredValidator {
+            id,
+            validator: Arc::new(validator),
+        });
+
+        id
+    }
+
+    /// Unregister a transaction validator.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the validator.
+    ///
+    /// # Returns
+    /// `true` if the validator is found and removed.
+    pub fn remove_validator(&mut self, id: ValidatorId) -> bool {
+        let count = self.validators.len();
+
+        self.validators.retain(|registered| registered.id != id);
+
+        self.validators.len() < count
+    }
+
+    /// Run the registered validators on a transaction.
+    ///
+    /// # Arguments
+    /// - `transaction`: The transaction.
+    ///
+    /// # Returns
+    /// `TransactionRejected` with the reason of the first validator rejecting the transaction.
+    pub(crate) fn run_validators(&self, transaction: &Transaction) -> Result<(), ChainError> {
+        for registered in &self.validators {
+            match registered.validator.validate(self, transaction) {
+                Verdict::Continue => continue,
+                Verdict::Accept => break,
+                Verdict::Reject(reason) => return Err(ChainError::TransactionRejected(reason)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Fixed(Verdict);
+
+    impl TransactionValidator for Fixed {
+        fn validate(&self, _chain: &Chain, _transaction: &Transaction) -> Verdict {
+            self.0.to_owned()
+        }
+    }
+
+    #[test]
+    fn test_run_validators_short_circuits() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let transaction = Transaction::builder()
+            .from("a")
+            .to("b")
+            .amount(1.0)
+            .fee(0.1)
+            .build();
+
+        assert_eq!(chain.run_validators(&transaction), Ok(()));
+
+        chain.add_validator(Fixed(Verdict::Continue));
+        let accept = chain.add_validator(Fixed(Verdict::Accept));
+        chain.add_validator(Fixed(Verdict::Reject("blocked".to_string())));
+
+        assert_eq!(chain.run_validators(&transaction), Ok(()));
+        assert!(chain.remove_validator(accept));
+        assert!(!chain.remove_validator(accept));
+        assert_eq!(
+            chain.run_validators(&transaction),
+            Err(ChainError::TransactionRejected("blocked".to_string()))
+        );
+    }
+}
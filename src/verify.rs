@@ -0,0 +1,193 @@
+use crate::{Chain, ChainError, Consensus, ProofOfWork};
+
+/// Progress of the verification of a range of blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationProgress {
+    /// The height of the first block of the range.
+    pub from_height: usize,
+
+    /// The height of the last block of the range.
+    pub to_height: usize,
+
+    /// The height of the last verified block.
+    pub height: usize,
+}
+
+impl ValidationProgress {
+    /// Get the number of verified blocks.
+    ///
+    /// # Returns
+    /// The number of blocks verified so far, including the current one.
+    pub fn validated(&self) -> usize {
+        self.height - self.from_height + 1
+    }
+
+    /// Get the number of blocks left to verify.
+    ///
+    /// # Returns
+    /// The number of blocks of the range following the current one.
+    pub fn remaining(&self) -> usize {
+        self.to_height - self.height
+    }
+}
+
+impl Chain {
+    /// Verify a range of blocks without re-verifying the rest of the blockchain.
+    ///
+    /// # Arguments
+    /// - `from_height`: The height of the first block to verify.
+    /// - `to_height`: The height of the last block to verify, included.
+    ///
+    /// # Returns
+    /// The number of verified blocks, or an `InvariantViolation` error describing the first
+    /// invalid block.
+    pub fn validate_range(
+        &self,
+        from_height: usize,
+        to_height: usize,
+    ) -> Result<usize, ChainError> {
+        self.validate_range_with_progress(from_height, to_height, |_| {})
+    }
+
+    /// Verify a range of blocks, reporting the progress after every block.
+    ///
+    /// Each block must match its cached hash, link to the previous block, satisfy its difficulty,
+    /// start with the coinbase and hold valid transactions matching its Merkle root in the
+    /// canonical order. Only the header of pruned blocks is verified. The range is capped at the
+    /// last block, and an empty range verifies no block.
+    ///
+    /// # Arguments
+    /// - `from_height`: The height of the first block to verify.
+    /// - `to_height`: The height of the last block to verify, included.
+    /// - `progress`: The callback receiving the progress after every verified block.
+    ///
+    /// # Returns
+    /// The number of verified blocks, or an `InvariantViolation` error describing the first
+    /// invalid block.
+    pub fn validate_range_with_progress(
+        &self,
+        from_height: usize,
+        to_height: usize,
+        mut progress: impl FnMut(&ValidationProgress),
+    ) -> Result<usize, ChainError> {
+        let to_height = to_height.min(self.chain.len().saturating_sub(1));
+
+        if self.chain.is_empty() || from_height > to_height {
+            return Ok(0);
+        }
+
+        for height in from_height..=to_height {
+            self.validate_block(height)?;
+
+            progress(&ValidationProgress {
+                from_height,
+                to_height,
+                height,
+            });
+        }
+
+        Ok(to_height - from_height + 1)
+    }
+
+    /// Verify a stored block against the previous block and the consensus rules.
+    ///
+    /// # Arguments
+    /// - `height`: The height of the block.
+    ///
+    /// # Returns
+    /// An `InvariantViolation` error describing why the block is invalid.
+    fn validate_block(&self, height: usize) -> Result<(), ChainError> {
+        let block = &self.chain[height];
+        let invalid = |reason: &str| {
+            Err(ChainError::InvariantViolation(format!(
+                "block {} {}",
+                height, reason
+            )))
+        };
+
+        // The genesis block has no previous block to link to
+        match height {
+            0 => Chain::check_block_hash(block, height)?,
+            _ => self.check_block_link(height)?,
+        }
+
+        // The genesis block is mined before a consensus engine can be set
+        let sealed = match height {
+            0 => ProofOfWork.verify_seal(&block.header),
+            _ => self.consensus().verify_seal(&block.header),
+        };
+
+        if sealed.is_err() {
+            return invalid("is not sealed by the consensus engine");
+        }
+
+        // Pruned blocks only keep their header
+        if block.is_pruned() {
+            return Ok(());
+        }
+
+        if height == 0
+            && (block.count != block.transactions.len()
+                || block.header.merkle != Chain::get_merkle(block.transactions.clone()))
+        {
+            return invalid("does not match its transactions");
+        }
+
+        if block
+            .transactions
+            .first()
+            .is_none_or(|coinbase| coinbase.from != "Root")
+        {
+            return invalid("does not start with the coinbase");
+        }
+
+        if !block.verify_transactions() {
+            return invalid("holds an invalid transaction");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+
+    #[test]
+    fn test_validation_progress() {
+        let progress = ValidationProgress {
+            from_height: 10,
+            to_height: 19,
+            height: 12,
+        };
+
+        assert_eq!(progress.validated(), 3);
+        assert_eq!(progress.remaining(), 7);
+    }
+
+    #[test]
+    fn test_validate_range_tampered_block() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let keypair = Keypair::generate();
+        let a = chain
+            .create_wallet_with_key("a@mail.com".to_string(), &keypair.public_key())
+            .unwrap();
+        let b = chain.create_wallet("b@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&a, 20.0);
+        chain.generate_new_block().unwrap();
+        chain.add_transaction(a, b, 1.0, &keypair).unwrap();
+        chain.generate_new_block().unwrap();
+        chain.generate_new_block().unwrap();
+
+        chain.chain[2].transactions[1].amount = 10.0;
+
+        assert!(matches!(
+            chain.validate_range(0, 3),
+            Err(ChainError::InvariantViolation(_))
+        ));
+        assert_eq!(chain.validate_range(0, 1), Ok(2));
+        assert_eq!(chain.validate_range(3, 3), Ok(1));
+    }
+}
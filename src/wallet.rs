@@ -1,9 +1,52 @@
+use std::{collections::BTreeMap, fmt};
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+
+use crate::{ChainError, Keypair, SecurityEvent};
+
+/// Amount of words of the generated mnemonics, 256 bits of entropy.
+const MNEMONIC_WORDS: usize = 24;
+
+/// Key of the HMAC deriving the master key from the seed, as specified by SLIP-0010 for ed25519.
+const MASTER_KEY: &[u8] = b"ed25519 seed";
+
+/// Path of the account whose children own the wallets: purpose 44, coin type 1 (shared by test
+/// networks), and account 0, all hardened.
+const ACCOUNT_PATH: [u32; 3] = [44, 1, 0];
+
+/// Offset of the hardened child indexes, the only ones ed25519 keys support.
+const HARDENED: u32 = 1 << 31;
+
+/// Address uniquely identifying a wallet.
+pub type Address = String;
+
+/// Order of wallets in a listing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletSort {
+    /// Ascending by address.
+    #[default]
+    Address,
+
+    /// Ascending by email.
+    Email,
+
+    /// Ascending by balance.
+    BalanceAsc,
+
+    /// Descending by balance.
+    BalanceDesc,
+}
 
 /// A wallet that holds a balance of a cryptocurrency.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Wallet {
-    /// Unique email address associated with the wallet.
+    /// Unique email address associated with the wallet, or its hash or nothing depending on the
+    /// email privacy of the blockchain settings.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub email: String,
 
     /// Address uniquely identifying the wallet.
@@ -14,6 +57,22 @@ pub struct Wallet {
 
     /// A history of transactions associated with the wallet.
     pub transactions: Vec<String>,
+
+    /// The nonce expected on the next transaction sent from the wallet.
+    #[serde(default)]
+    pub nonce: u64,
+
+    /// Hex encoded ed25519 public key of the owner, required to sign the outgoing transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+
+    /// The key rotations and recoveries of the wallet, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_events: Vec<SecurityEvent>,
+
+    /// The balances of the assets held by the wallet, by asset identifier.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assets: BTreeMap<String, f64>,
 }
 
 impl Wallet {
@@ -34,10 +93,141 @@ impl Wallet {
             address,
             balance,
             transactions: vec![],
+            nonce: 0,
+            public_key: None,
+            security_events: Vec::new(),
+            assets: BTreeMap::new(),
         }
     }
 }
 
+/// A hierarchical deterministic wallet, deriving the keypairs of many wallets from a single
+/// mnemonic seed phrase.
+///
+/// The seed follows BIP-39, and the keys are derived with SLIP-0010, the BIP-32 scheme for
+/// ed25519, at the hardened path `m/44'/1'/0'/index'`. Backing the mnemonic up is enough to
+/// restore every derived keypair.
+#[derive(Clone)]
+pub struct HdWallet {
+    /// The mnemonic seed phrase.
+    mnemonic: Mnemonic,
+
+    /// The seed derived from the mnemonic and the passphrase.
+    seed: [u8; 64],
+}
+
+impl fmt::Debug for HdWallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HdWallet").finish_non_exhaustive()
+    }
+}
+
+impl HdWallet {
+    /// Generate a new random mnemonic of 24 words.
+    ///
+    /// # Returns
+    /// A new HD wallet without passphrase.
+    pub fn generate() -> Self {
+        let mnemonic = Mnemonic::generate(MNEMONIC_WORDS).expect("the word count is supported");
+
+        HdWallet::new(mnemonic, "")
+    }
+
+    /// Restore an HD wallet from its mnemonic.
+    ///
+    /// # Arguments
+    /// - `phrase`: The mnemonic seed phrase, of 12 to 24 English words.
+    /// - `passphrase`: The optional passphrase protecting the seed, empty if none.
+    ///
+    /// # Returns
+    /// The HD wallet, or `InvalidMnemonic` if a word is unknown or the checksum does not match.
+    pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<Self, ChainError> {
+        let mnemonic =
+            Mnemonic::parse(phrase).map_err(|err| ChainError::InvalidMnemonic(err.to_string()))?;
+
+        Ok(HdWallet::new(mnemonic, passphrase))
+    }
+
+    /// Create an HD wallet from a parsed mnemonic.
+    ///
+    /// # Arguments
+    /// - `mnemonic`: The mnemonic.
+    /// - `passphrase`: The passphrase protecting the seed.
+    ///
+    /// # Returns
+    /// The HD wallet.
+    fn new(mnemonic: Mnemonic, passphrase: &str) -> Self {
+        let seed = mnemonic.to_seed(passphrase);
+
+        HdWallet { mnemonic, seed }
+    }
+
+    /// Get the mnemonic seed phrase, to be backed up by the owner only.
+    ///
+    /// # Returns
+    /// The words of the mnemonic, separated by spaces.
+    pub fn mnemonic(&self) -> String {
+        self.mnemonic.to_string()
+    }
+
+    /// Derive the keypair of a child wallet.
+    ///
+    /// # Arguments
+    /// - `index`: The index of the child, below 2^31.
+    ///
+    /// # Returns
+    /// The keypair at the path `m/44'/1'/0'/index'`.
+    pub fn derive_child(&self, index: u32) -> Keypair {
+        let path: Vec<u32> = ACCOUNT_PATH.iter().copied().chain([index]).collect();
+
+        Keypair::from_bytes(&derive_path(&self.seed, &path))
+    }
+}
+
+/// Derive an ed25519 secret key from a seed along a hardened path, following SLIP-0010.
+///
+/// # Arguments
+/// - `seed`: The seed.
+/// - `path`: The child indexes from the master key, hardened whatever their value.
+///
+/// # Returns
+/// The secret key at the end of the path.
+fn derive_path(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    let (mut key, mut chain_code) = hmac_sha512(MASTER_KEY, &[seed]);
+
+    for index in path {
+        let hardened = (index | HARDENED).to_be_bytes();
+
+        (key, chain_code) = hmac_sha512(&chain_code, &[&[0], &key, &hardened]);
+    }
+
+    key
+}
+
+/// Compute an HMAC-SHA512 and split it into a key and a chain code.
+///
+/// # Arguments
+/// - `key`: The key of the HMAC.
+/// - `data`: The parts of the authenticated data.
+///
+/// # Returns
+/// The left half of the HMAC, and its right half.
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any size");
+
+    for part in data {
+        mac.update(part);
+    }
+
+    let output = mac.finalize().into_bytes();
+    let (mut left, mut right) = ([0u8; 32], [0u8; 32]);
+
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+
+    (left, right)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,5 +243,47 @@ mod tests {
         assert_eq!(wallet.address, address);
         assert_eq!(wallet.balance, balance);
         assert!(wallet.transactions.is_empty());
+        assert!(wallet.public_key.is_none());
+    }
+
+    #[test]
+    fn test_derive_path() {
+        // Test vector 1 of SLIP-0010 for ed25519
+        let seed = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let master = Keypair::from_bytes(&derive_path(&seed, &[]));
+        let child = Keypair::from_bytes(&derive_path(&seed, &[0]));
+
+        assert_eq!(
+            master.secret(),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            child.secret(),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert_eq!(
+            child.public_key(),
+            "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c"
+        );
+    }
+
+    #[test]
+    fn test_hd_wallet_from_mnemonic() {
+        let phrase = [["abandon"; 11].join(" ").as_str(), "about"].join(" ");
+        let wallet = HdWallet::from_mnemonic(&phrase, "TREZOR").unwrap();
+
+        // Test vector of BIP-39
+        assert_eq!(
+            wallet.seed[..8],
+            [0xc5, 0x52, 0x57, 0xc3, 0x60, 0xc0, 0x7c, 0x72]
+        );
+        assert_eq!(wallet.mnemonic(), phrase);
+        assert!(matches!(
+            HdWallet::from_mnemonic(&["abandon"; 12].join(" "), ""),
+            Err(ChainError::InvalidMnemonic(_))
+        ));
     }
 }
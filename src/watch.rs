@@ -0,0 +1,251 @@
+use std::{fmt, sync::Arc};
+
+use crate::{Chain, Transaction};
+
+/// Identifier of a payment watch.
+pub type WatchId = usize;
+
+/// Notification sent to the callback of a payment watch.
+#[derive(Clone, Debug)]
+pub enum PaymentEvent {
+    /// A matching payment reached the confirmation threshold.
+    Confirmed {
+        /// The identifier of the watch.
+        watch: WatchId,
+
+        /// The matching payment.
+        transaction: Transaction,
+
+        /// Height of the block including the payment.
+        height: usize,
+
+        /// Amount of blocks on top of the payment, including its own block.
+        confirmations: usize,
+    },
+
+    /// A confirmed payment is no longer part of the blockchain.
+    Reversed {
+        /// The identifier of the watch.
+        watch: WatchId,
+
+        /// The reversed payment.
+        transaction: Transaction,
+    },
+}
+
+/// Callback notified of the events of a payment watch.
+pub type PaymentCallback = Arc<dyn Fn(&PaymentEvent) + Send + Sync>;
+
+/// A confirmed payment, remembered to detect its reversal.
+#[derive(Clone, Debug)]
+struct ConfirmedPayment {
+    /// The confirmed payment.
+    transaction: Transaction,
+
+    /// Height of the block including the payment.
+    height: usize,
+
+    /// Hash of the header of the block including the payment.
+    block_hash: String,
+}
+
+/// A watch for an incoming payment.
+#[derive(Clone)]
+pub(crate) struct PaymentWatch {
+    /// The identifier of the watch.
+    id: WatchId,
+
+    /// The receiver address.
+    address: String,
+
+    /// The minimum amount of the payment.
+    amount: f64,
+
+    /// The amount of confirmations required.
+    min_confirmations: usize,
+
+    /// The height of the first block searched for the payment.
+    from_height: usize,
+
+    /// The payment once confirmed.
+    confirmed: Option<ConfirmedPayment>,
+
+    /// The callback notified of the events.
+    callback: PaymentCallback,
+}
+
+impl fmt::Debug for PaymentWatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PaymentWatch")
+            .field("id", &self.id)
+            .field("address", &self.address)
+            .field("amount", &self.amount)
+            .field("min_confirmations", &self.min_confirmations)
+            .field("from_height", &self.from_height)
+            .field("confirmed", &self.confirmed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Chain {
+    /// Watch for an incoming payment and notify a callback once it is confirmed.
+    ///
+    /// Payments mined from the next block on are searched. The callback receives a
+    /// `PaymentEvent::Confirmed` event once a payment of at least `amount` to `address` has
+    /// `min_confirmations` confirmations, and a `PaymentEvent::Reversed` event if the block including
+    /// it is later replaced, after which the watch waits for a new payment.
+    ///
+    /// # Arguments
+    /// - `address`: The receiver address.
+    /// - `amount`: The minimum amount of the payment.
+    /// - `min_confirmations`: The amount of confirmations required, at least one.
+    /// - `callback`: The callback notified of the events.
+    ///
+    /// # Returns
+    /// The identifier of the watch.
+    pub fn watch_payment<F>(
+        &mut self,
+        address: &str,
+        amount: f64,
+        min_confirmations: usize,
+        callback: F,
+    ) -> WatchId
+    where
+        F: Fn(&PaymentEvent) + Send + Sync + 'static,
+    {
+        let id = self.next_watch_id;
+        self.next_watch_id += 1;
+
+        self.watches.push(PaymentWatch {
+            id,
+            address: address.to_string(),
+            amount,
+            min_confirmations: min_confirmations.max(1),
+            from_height: self.chain.len(),
+            confirmed: None,
+            callback: Arc::new(callback),
+        });
+
+        id
+    }
+
+    /// Stop watching for a payment.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the watch.
+    ///
+    /// # Returns
+    /// `true` if the watch is found and removed.
+    pub fn unwatch_payment(&mut self, id: WatchId) -> bool {
+        let count = self.watches.len();
+
+        self.watches.retain(|watch| watch.id != id);
+
+        self.watches.len() < count
+    }
+
+    /// Notify the payment watches of the confirmations and reversals after a new block.
+    pub(crate) fn notify_watches(&mut self) {
+        let blocks = &self.chain;
+
+        for watch in self.watches.iter_mut() {
+            // Validate if the confirmed payment is still part of the blockchain
+            if let Some(confirmed) = &watch.confirmed {
+                let reverted = match blocks.get(confirmed.height) {
+                    Some(block) => block.hash() != confirmed.block_hash,
+                    None => true,
+                };
+
+                if !reverted {
+                    continue;
+                }
+
+                (watch.callback)(&PaymentEvent::Reversed {
+                    watch: watch.id,
+                    transaction: confirmed.transaction.clone(),
+                });
+
+                watch.from_height = confirmed.height;
+                watch.confirmed = None;
+            }
+
+            // Search the blocks with enough confirmations for a matching payment
+            let last = (blocks.len() + 1).saturating_sub(watch.min_confirmations);
+
+            let payment = blocks
+                .iter()
+                .enumerate()
+                .take(last)
+                .skip(watch.from_height)
+                .find_map(|(height, block)| {
+                    block
+                        .transactions
+                        .iter()
+                        .find(|transaction| {
+                            transaction.to == watch.address
+                                && transaction.asset_id.is_none()
+                                && transaction.amount >= watch.amount
+                        })
+                        .map(|transaction| (height, block, transaction))
+                });
+
+            match payment {
+                Some((height, block, transaction)) => {
+                    (watch.callback)(&PaymentEvent::Confirmed {
+                        watch: watch.id,
+                        transaction: transaction.clone(),
+                        height,
+                        confirmations: blocks.len() - height,
+                    });
+
+                    watch.confirmed = Some(ConfirmedPayment {
+                        transaction: transaction.clone(),
+                        height,
+                        block_hash: block.hash(),
+                    });
+                }
+                None => watch.from_height = watch.from_height.max(last),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_notify_watches_reversal() {
+        let mut chain = Chain::new(1.0, 100.0, 0.1);
+        let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+
+        chain.watch_payment(&address, 10.0, 1, move |event| {
+            sink.lock().unwrap().push(event.clone())
+        });
+
+        chain.credit_wallet(&address, 20.0);
+        chain.generate_new_block().unwrap();
+
+        // Simulate a reorganization replacing the block of the payment
+        chain.chain[1].header.nonce += 1;
+        chain.chain[1].seal();
+        chain.notify_watches();
+
+        let events = events.lock().unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0],
+            PaymentEvent::Confirmed { height: 1, .. }
+        ));
+        assert!(matches!(events[1], PaymentEvent::Reversed { .. }));
+        assert!(matches!(
+            events[2],
+            PaymentEvent::Confirmed { height: 1, .. }
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+use std::{fmt::Debug, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, ChainEvent};
+
+/// Identifier of a failed notification.
+pub type NotificationId = usize;
+
+/// Maximum amount of failed notifications kept, the oldest ones being dropped first.
+const MAX_FAILED_NOTIFICATIONS: usize = 1_000;
+
+/// An integration target the events of the blockchain are delivered to, such as an HTTP
+/// endpoint.
+///
+/// The deliveries run synchronously while the blockchain is borrowed, so implementations
+/// should bound the time of a delivery, e.g. with a request timeout.
+pub trait Webhook: Debug + Send + Sync {
+    /// Deliver an event.
+    ///
+    /// # Arguments
+    /// - `event`: The event.
+    ///
+    /// # Returns
+    /// An error describing why the event was not delivered.
+    fn deliver(&self, event: &ChainEvent) -> Result<(), String>;
+}
+
+/// A shared webhook, so the embedder keeps access to its state once registered.
+impl<W: Webhook + ?Sized> Webhook for Arc<W> {
+    fn deliver(&self, event: &ChainEvent) -> Result<(), String> {
+        (**self).deliver(event)
+    }
+}
+
+/// An event whose delivery to a webhook exhausted its attempts, kept to be re-driven.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FailedNotification {
+    /// The identifier of the failed notification.
+    pub id: NotificationId,
+
+    /// The name of the webhook.
+    pub webhook: String,
+
+    /// The undelivered event.
+    pub event: ChainEvent,
+
+    /// The amount of delivery attempts, re-drives included, 0 if the event was queued behind the
+    /// earlier failed notifications of the webhook.
+    pub attempts: usize,
+
+    /// The error of the last attempt, or the reason the event was queued.
+    pub error: String,
+
+    /// The time of the last attempt or of the queuing, in milliseconds since the Unix epoch.
+    pub failed_at: i64,
+}
+
+/// A registered webhook.
+#[derive(Clone, Debug)]
+pub(crate) struct RegisteredWebhook {
+    /// The name of the webhook.
+    name: String,
+
+    /// The webhook.
+    webhook: Arc<dyn Webhook>,
+
+    /// The amount of attempts of a delivery.
+    max_attempts: usize,
+}
+
+impl RegisteredWebhook {
+    /// Deliver an event, attempting again after every failure.
+    ///
+    /// # Arguments
+    /// - `event`: The event.
+    ///
+    /// # Returns
+    /// The amount of attempts and the error of the last one if every attempt failed.
+    fn deliver(&self, event: &ChainEvent) -> Result<(), (usize, String)> {
+        let mut error = String::new();
+
+        for _ in 0..self.max_attempts {
+            match self.webhook.deliver(event) {
+                Ok(()) => return Ok(()),
+                Err(err) => error = err,
+            }
+        }
+
+        Err((self.max_attempts, error))
+    }
+}
+
+impl Chain {
+    /// Register a webhook receiving the events of the blockchain.
+    ///
+    /// Each event is delivered in the order of the changes, from the time of the registration,
+    /// and attempted again right away after a failure. Once the attempts are exhausted, the
+    /// event is kept in `failed_notifications` until it is re-driven, and the later events are
+    /// queued behind it so they are delivered in order. Webhooks are shared by the clones of the
+    /// blockchain and are not persisted with it, unlike the failed notifications: registering
+    /// the webhooks again under the same names after loading the blockchain lets their
+    /// notifications be re-driven.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the webhook, replacing the webhook registered under it, if any.
+    /// - `webhook`: The webhook.
+    /// - `max_attempts`: The amount of attempts of a delivery, at least 1.
+    pub fn add_webhook<W: Webhook + 'static>(
+        &mut self,
+        name: &str,
+        webhook: W,
+        max_attempts: usize,
+    ) {
+        self.remove_webhook(name);
+
+        self.webhooks.push(RegisteredWebhook {
+            name: name.to_string(),
+            webhook: Arc::new(webhook),
+            max_attempts: max_attempts.max(1),
+        });
+    }
+
+    /// Unregister a webhook. Its failed notifications are kept until they are discarded.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the webhook.
+    ///
+    /// # Returns
+    /// `true` if the webhook is found and removed.
+    pub fn remove_webhook(&mut self, name: &str) -> bool {
+        let count = self.webhooks.len();
+
+        self.webhooks.retain(|registered| registered.name != name);
+
+        self.webhooks.len() < count
+    }
+
+    /// Get the events whose delivery to a webhook exhausted its attempts.
+    ///
+    /// At most the last 1000 failed notifications are kept.
+    ///
+    /// # Returns
+    /// The failed notifications, oldest first.
+    pub fn failed_notifications(&self) -> &[FailedNotification] {
+        &self.failed_notifications
+    }
+
+    /// Deliver the failed notifications of a webhook again, e.g. once its target is back up.
+    ///
+    /// The notifications are re-driven oldest first and stop at the first one failing again,
+    /// which stays with the later ones, so the events keep their order and a target still down
+    /// is not flooded.
+    ///
+    /// # Arguments
+    /// - `webhook`: The name of the webhook.
+    ///
+    /// # Returns
+    /// The amount of notifications delivered, or `None` if the webhook is not registered.
+    pub fn redrive_notifications(&mut self, webhook: &str) -> Option<usize> {
+        let registered = self
+            .webhooks
+            .iter()
+            .find(|registered| registered.name == webhook)?
+            .clone();
+        let now = self.clock().now_millis();
+        let mut delivered = Vec::new();
+
+        for notification in &mut self.failed_notifications {
+            if notification.webhook != webhook {
+                continue;
+            }
+
+            match registered.deliver(&notification.event) {
+                Ok(()) => delivered.push(notification.id),
+                Err((attempts, error)) => {
+                    notification.attempts += attempts;
+                    notification.error = error;
+                    notification.failed_at = now;
+
+                    break;
+                }
+            }
+        }
+
+        self.failed_notifications
+            .retain(|notification| !delivered.contains(&notification.id));
+
+        Some(delivered.len())
+    }
+
+    /// Discard the failed notifications of a webhook, e.g. once it is removed.
+    ///
+    /// # Arguments
+    /// - `webhook`: The name of the webhook.
+    ///
+    /// # Returns
+    /// The amount of notifications discarded.
+    pub fn discard_failed_notifications(&mut self, webhook: &str) -> usize {
+        let count = self.failed_notifications.len();
+
+        self.failed_notifications
+            .retain(|notification| notification.webhook != webhook);
+
+        count - self.failed_notifications.len()
+    }
+
+    /// Deliver an event to the webhooks, keeping the failed deliveries.
+    ///
+    /// The event is queued without being delivered to a webhook with failed notifications, so
+    /// it is re-driven after them.
+    ///
+    /// # Arguments
+    /// - `event`: The event.
+    pub(crate) fn deliver_to_webhooks(&mut self, event: &ChainEvent) {
+        if self.webhooks.is_empty() {
+            return;
+        }
+
+        let now = self.clock().now_millis();
+
+        for registered in &self.webhooks {
+            let backlog = self
+                .failed_notifications
+                .iter()
+                .any(|notification| notification.webhook == registered.name);
+
+            let (attempts, error) = match backlog {
+                true => (0, "queued behind an undelivered notification".to_string()),
+                false => match registered.deliver(event) {
+                    Ok(()) => continue,
+                    Err(failure) => failure,
+                },
+            };
+
+            if self.failed_notifications.len() >= MAX_FAILED_NOTIFICATIONS {
+                self.failed_notifications.remove(0);
+            }
+
+            self.failed_notifications.push(FailedNotification {
+                id: self.next_notification_id,
+                webhook: registered.name.to_owned(),
+                event: event.to_owned(),
+                attempts,
+                error,
+                failed_at: now,
+            });
+            self.next_notification_id += 1;
+        }
+    }
+}
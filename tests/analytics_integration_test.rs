@@ -0,0 +1,99 @@
+#![cfg(feature = "analytics")]
+
+mod common;
+
+use std::{env, fs, fs::File};
+
+use arrow_array::{Array, StringArray, UInt64Array};
+use blockchain::{Chain, BLOCKS_FILE, TRANSACTIONS_FILE};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+/// Setup a blockchain with a mined and a pending transaction.
+///
+/// # Returns
+///
+/// The blockchain and the hash of the pending transaction.
+fn setup_chain() -> (Chain, String) {
+    let mut chain = Chain::new(1.0, 100.0, 0.1);
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let pending = chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+
+    (chain, pending)
+}
+
+#[test]
+fn test_blocks_record_batch() {
+    let (chain, _) = setup_chain();
+    let batch = chain.blocks_record_batch().unwrap();
+
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.num_columns(), 9);
+
+    let hashes = batch
+        .column_by_name("hash")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    assert_eq!(hashes.value(1), chain.get_last_hash());
+}
+
+#[test]
+fn test_transactions_record_batch() {
+    let (chain, pending) = setup_chain();
+    let batch = chain.transactions_record_batch().unwrap();
+
+    // Two coinbases, the credit, and the pending transaction
+    assert_eq!(batch.num_rows(), 4);
+
+    let heights = batch
+        .column_by_name("block_height")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    let hashes = batch
+        .column_by_name("hash")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    assert_eq!(heights.value(0), 0);
+    assert_eq!(heights.value(2), 1);
+    assert!(heights.is_null(3));
+    assert_eq!(hashes.value(3), pending);
+}
+
+#[test]
+fn test_export_parquet() {
+    let dir = env::temp_dir().join(format!("blockchain-analytics-{}", std::process::id()));
+    let (chain, _) = setup_chain();
+
+    fs::create_dir_all(&dir).unwrap();
+    chain.export_parquet(&dir).unwrap();
+
+    let rows = |name: &str| -> usize {
+        let file = File::open(dir.join(name)).unwrap();
+
+        ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum()
+    };
+
+    let (blocks, transactions) = (rows(BLOCKS_FILE), rows(TRANSACTIONS_FILE));
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(blocks, 2);
+    assert_eq!(transactions, 4);
+}
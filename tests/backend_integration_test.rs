@@ -0,0 +1,121 @@
+use std::{io::ErrorKind, sync::Arc};
+
+use blockchain::{Chain, ChainConfig, InMemoryStorage, Keypair, PruningPolicy, Storage};
+
+/// Setup a blockchain with two wallets.
+///
+/// # Arguments
+///
+/// - `pruning` - The pruning policy of the blockchain.
+///
+/// # Returns
+///
+/// The blockchain, the addresses of its wallets, and the keypair of the sender.
+fn setup(pruning: PruningPolicy) -> (Chain, String, String, Keypair) {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            pruning,
+            ..ChainConfig::default()
+        },
+    );
+    let keypair = Keypair::generate();
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+
+    (chain, from, to, keypair)
+}
+
+#[test]
+fn test_persist_and_restore() {
+    let (mut chain, from, to, keypair) = setup(PruningPolicy::KeepLast { blocks: 1 });
+    let storage = Arc::new(InMemoryStorage::new());
+
+    assert_eq!(chain.persist().unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(
+        Chain::restore(storage.clone()).unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+
+    chain.set_storage(storage.clone()).unwrap();
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    let transactions = chain.get_block(1).unwrap().transactions.len();
+
+    chain.generate_new_block().unwrap();
+
+    // The pruned block is still read from the backend with its transactions
+    assert!(chain.get_block(1).unwrap().is_pruned());
+    assert_eq!(
+        chain
+            .get_stored_block(1)
+            .unwrap()
+            .unwrap()
+            .transactions
+            .len(),
+        transactions
+    );
+    assert!(chain.get_stored_block(3).unwrap().is_none());
+
+    chain.persist().unwrap();
+
+    // The blocks appended after the persisted state are imported again
+    chain.generate_new_block().unwrap();
+
+    let restored = Chain::restore(storage.clone()).unwrap();
+
+    assert_eq!(storage.block_count().unwrap(), 4);
+    assert_eq!(restored.get_last_hash(), chain.get_last_hash());
+    assert_eq!(restored.get_wallet_balance(to), Some(10.0));
+    assert!(restored.get_block(1).unwrap().is_pruned());
+    assert_eq!(
+        restored
+            .get_stored_block(1)
+            .unwrap()
+            .unwrap()
+            .transactions
+            .len(),
+        transactions
+    );
+}
+
+#[test]
+fn test_storage_reorg() {
+    let (mut chain, from, to, keypair) = setup(PruningPolicy::KeepAll);
+    let storage = Arc::new(InMemoryStorage::new());
+
+    chain.generate_new_block().unwrap();
+
+    let mut fork = chain.clone();
+
+    chain.set_storage(storage.clone()).unwrap();
+    chain.generate_new_block().unwrap();
+
+    fork.add_transaction(from, to, 10.0, &keypair).unwrap();
+    fork.generate_new_block().unwrap();
+    fork.generate_new_block().unwrap();
+
+    // The reverted block is replaced in the backend
+    assert_eq!(chain.replace_chain(fork.get_all_blocks().to_vec()), Ok(2));
+    assert_eq!(storage.block_count().unwrap(), 4);
+    assert_eq!(
+        storage.get_block(2).unwrap().unwrap().hash(),
+        fork.get_block(2).unwrap().hash()
+    );
+
+    // A new backend only receives the blocks it misses
+    let mut other = fork.clone();
+
+    other.set_storage(storage.clone()).unwrap();
+
+    assert_eq!(storage.block_count().unwrap(), 4);
+}
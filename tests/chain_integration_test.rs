@@ -1,46 +1,102 @@
 mod common;
 
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use blockchain::{
+    AddressMode, Block, CancellationToken, Chain, ChainConfig, ChainError, ChainEvent, ChainPlugin,
+    Clock, ConsensusRules, DifficultyAlgorithm, EmailPrivacy, Gas, GenesisAllocation,
+    GenesisConfig, Keypair, LedgerModel, ManualClock, Memo, MemoryLimits, PipelineConfig,
+    ProofOfAuthority, ReplayEvent, Script, SearchResult, SequentialAddressGenerator,
+    SpendingPolicy, StakingConfig, SystemClock, TimeWindow, TimedEvent, TimestampPrecision,
+    Transaction, TransactionLocation, TransactionValidator, Verdict, WalletSort, MAX_MEMO_LENGTH,
+    SIGNATURE_GAS, TRANSACTION_GAS,
+};
+
 use crate::common::setup;
 
 #[test]
 fn test_add_transaction() {
     let mut chain = setup();
 
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
 
-    let result = chain.add_transaction(from, to, 10.0);
+    let result = chain.add_transaction(from, to, 10.0, &keypair);
 
-    assert!(result);
-    assert_eq!(chain.current_transactions.len(), 1);
+    assert_eq!(
+        result.as_deref().ok(),
+        Some(chain.get_pending_transactions()[0].hash.as_str())
+    );
+    assert_eq!(chain.get_pending_transactions().len(), 1);
+}
+
+#[test]
+fn test_add_transaction_with_memo() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let hash = chain
+        .add_transaction_with_memo(
+            from.clone(),
+            to.clone(),
+            10.0,
+            Some("invoice 42".to_string()),
+            &keypair,
+        )
+        .unwrap();
+
+    assert_eq!(
+        chain.get_transaction(hash).unwrap().memo,
+        Some(Memo::plain("invoice 42"))
+    );
+    assert_eq!(
+        chain.add_transaction_with_memo(
+            from,
+            to,
+            1.0,
+            Some("x".repeat(MAX_MEMO_LENGTH + 1)),
+            &keypair
+        ),
+        Err(ChainError::MemoTooLong {
+            max: MAX_MEMO_LENGTH,
+            actual: MAX_MEMO_LENGTH + 1,
+        })
+    );
+    assert_eq!(chain.get_pending_transactions().len(), 2);
 }
 
 #[test]
 fn test_add_transaction_validation_failed() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
 
-    let result = chain.add_transaction(from, to, 0.0);
+    let result = chain.add_transaction(from, to, 0.0, &keypair);
 
-    assert!(!result);
-    assert!(chain.current_transactions.is_empty());
+    assert_eq!(result, Err(ChainError::InvalidAmount));
+    assert!(chain.get_pending_transactions().is_empty());
 }
 
 #[test]
 fn test_validate_transaction() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
 
     let result = chain.validate_transaction(&from, &to, 10.0);
 
@@ -50,11 +106,10 @@ fn test_validate_transaction() {
 #[test]
 fn test_validate_transaction_failed_by_invalid_amount() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
 
     let result = chain.validate_transaction(&from, &to, -1.0);
 
@@ -64,8 +119,8 @@ fn test_validate_transaction_failed_by_invalid_amount() {
 #[test]
 fn test_validate_transaction_failed_by_invalid_sender() {
     let mut chain = setup();
-    let _ = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let _ = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
     let result = chain.validate_transaction("invalid", &to, 1.0);
 
@@ -75,11 +130,10 @@ fn test_validate_transaction_failed_by_invalid_sender() {
 #[test]
 fn test_validate_transaction_failed_by_invalid_receiver() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let _ = chain.create_wallet("r@mail.com".to_string());
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let _ = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
 
     let result = chain.validate_transaction(&from, "invalid", 1.0);
 
@@ -98,8 +152,8 @@ fn test_validate_transaction_failed_by_same_addresses() {
 #[test]
 fn test_validate_transaction_failed_by_invalid_sender_balance() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
     let result = chain.validate_transaction(&from, &to, 1.0);
 
@@ -118,15 +172,17 @@ fn test_validate_transaction_failed_by_root() {
 #[test]
 fn test_get_transaction() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
 
-    chain.add_transaction(from.clone(), to.clone(), 10.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
 
-    let transaction = chain.get_transaction(chain.current_transactions[0].hash.clone());
+    let transaction = chain.get_transaction(chain.get_pending_transactions()[0].hash.clone());
 
     assert!(transaction.is_some());
     assert_eq!(transaction.unwrap().from, from);
@@ -142,17 +198,247 @@ fn test_get_transaction_not_found() {
     assert!(transaction.is_none());
 }
 
+#[test]
+fn test_add_sponsored_transaction() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+    let (fee_payer, fee_payer_keypair) = common::create_wallet(&mut chain, "p@mail.com");
+
+    chain.credit_wallet(&from, 10.0);
+
+    // The sender alone cannot pay the fee on top of its whole balance
+    assert_eq!(
+        chain.add_transaction(from.clone(), to.clone(), 10.0, &keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+
+    let sponsor = |chain: &mut Chain, fee_payer: &str, fee_payer_keypair: &Keypair| {
+        chain.add_sponsored_transaction(
+            from.clone(),
+            to.clone(),
+            fee_payer.to_string(),
+            10.0,
+            &keypair,
+            fee_payer_keypair,
+        )
+    };
+
+    assert_eq!(
+        sponsor(&mut chain, &fee_payer, &fee_payer_keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+    assert_eq!(
+        sponsor(&mut chain, "unknown", &fee_payer_keypair),
+        Err(ChainError::UnknownFeePayer("unknown".to_string()))
+    );
+
+    chain.credit_wallet(&fee_payer, 2.0);
+    chain.credit_wallet(&to, 2.0);
+
+    // Both wallets sign with their own keypair
+    assert_eq!(
+        sponsor(&mut chain, &to, &fee_payer_keypair),
+        Err(ChainError::UnregisteredKey(to.clone()))
+    );
+    assert_eq!(
+        sponsor(&mut chain, &fee_payer, &keypair),
+        Err(ChainError::InvalidSignature)
+    );
+
+    let hash = sponsor(&mut chain, &fee_payer, &fee_payer_keypair).unwrap();
+
+    assert_eq!(chain.get_wallet_balance(from.clone()), Some(0.0));
+    assert_eq!(chain.get_wallet_balance(fee_payer.clone()), Some(1.0));
+    assert_eq!(chain.get_wallet_balance(to.clone()), Some(12.0));
+    assert_eq!(
+        chain.get_transaction(hash.clone()).unwrap().payer(),
+        fee_payer
+    );
+    assert!(chain
+        .get_wallet(&fee_payer)
+        .unwrap()
+        .transactions
+        .contains(&hash));
+
+    // The history replays the fee on the fee payer
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.balance_at(&fee_payer, 0), Some(0.0));
+    assert_eq!(chain.balance_at(&fee_payer, 1), Some(1.0));
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_spending_policy() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let shop = chain.create_wallet("shop@mail.com".to_string()).unwrap();
+    let other = chain.create_wallet("o@mail.com".to_string()).unwrap();
+    // 10:00 UTC
+    let clock = Arc::new(ManualClock::new((100 * 86_400 + 10 * 3_600) * 1000));
+
+    chain.set_clock(clock.clone());
+    chain.credit_wallet(&from, 100.0);
+
+    assert_eq!(
+        chain.set_spending_policy("unknown", SpendingPolicy::default()),
+        Err(ChainError::UnknownSender("unknown".to_string()))
+    );
+
+    let policy = SpendingPolicy {
+        allowed_counterparties: BTreeSet::from([shop.clone()]),
+        time_windows: vec![TimeWindow {
+            start: 9 * 60,
+            end: 17 * 60,
+        }],
+        max_amount: Some(20.0),
+    };
+
+    chain.set_spending_policy(&from, policy.clone()).unwrap();
+
+    assert_eq!(chain.get_spending_policy(&from), Some(&policy));
+    assert!(!chain.validate_transaction(&from, &other, 5.0));
+    assert!(matches!(
+        chain.add_transaction(from.clone(), shop.clone(), 25.0, &keypair),
+        Err(ChainError::PolicyViolation(_))
+    ));
+    assert!(chain
+        .add_transaction(from.clone(), shop.clone(), 5.0, &keypair)
+        .is_ok());
+
+    // 18:00 UTC, outside the window
+    clock.advance(Duration::from_secs(8 * 3_600));
+
+    assert!(matches!(
+        chain.check_transaction(&from, &shop, 6.0),
+        Err(ChainError::PolicyViolation(_))
+    ));
+
+    // The policy is evaluated before the signature of a submitted transaction
+    let unsigned = Transaction::builder()
+        .from(from.clone())
+        .to(other.clone())
+        .amount(1.0)
+        .fee_rate(0.1)
+        .build();
+
+    assert!(matches!(
+        chain.submit_transaction(unsigned),
+        Err(ChainError::PolicyViolation(_))
+    ));
+
+    // Imported blocks are not subject to the local policies
+    let mut peer = chain.clone();
+
+    assert_eq!(peer.remove_spending_policy(&from), Some(policy.clone()));
+    assert_eq!(peer.remove_spending_policy(&from), None);
+
+    peer.add_transaction(from.clone(), other.clone(), 30.0, &keypair)
+        .unwrap();
+    peer.generate_new_block().unwrap();
+
+    let block = peer.get_all_blocks().last().unwrap().clone();
+
+    assert_eq!(chain.import_block(block), Ok(()));
+    assert_eq!(chain.get_wallet_balance(other), Some(30.0));
+
+    // Every change is audited
+    let audit = peer.get_policy_audit_log();
+
+    assert_eq!(audit.len(), 2);
+    assert_eq!(audit[0].address, from);
+    assert_eq!(audit[0].previous, None);
+    assert_eq!(audit[0].current, Some(policy.clone()));
+    assert_eq!(audit[1].previous, Some(policy));
+    assert_eq!(audit[1].current, None);
+    assert_eq!(audit[1].timestamp, clock.now_millis() / 1000);
+}
+
+#[test]
+fn test_find_transaction_in_chain() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let hash = chain
+        .add_transaction(from.clone(), to, 10.0, &keypair)
+        .unwrap();
+
+    assert!(chain.find_transaction_in_chain(&hash).is_none());
+
+    chain.generate_new_block().unwrap();
+
+    // Mined transactions are no longer pending but remain reachable
+    let (height, transaction) = chain.find_transaction_in_chain(&hash).unwrap();
+
+    assert!(chain.get_transaction(hash.clone()).is_none());
+    assert_eq!(height, 1);
+    assert_eq!(transaction.from, from);
+    assert!(chain.find_transaction_in_chain("NonExistentHash").is_none());
+}
+
+#[test]
+fn test_get_blocks() {
+    let mut chain = setup();
+
+    for _ in 0..4 {
+        chain.generate_new_block().unwrap();
+    }
+
+    let last_hash = chain.get_last_hash();
+
+    assert_eq!(
+        chain.get_block(0).unwrap().header.previous_hash,
+        "0".repeat(64)
+    );
+    assert!(chain.get_block(5).is_none());
+    assert_eq!(
+        chain
+            .get_block_by_hash(&last_hash)
+            .map(|block| block.header.nonce),
+        chain.get_block(4).map(|block| block.header.nonce)
+    );
+    assert!(chain.get_block_by_hash("NonExistentHash").is_none());
+
+    let pages: Vec<usize> = (1..=3)
+        .map(|page| chain.get_blocks(page, 2).len())
+        .collect();
+
+    assert_eq!(pages, vec![2, 2, 1]);
+    assert!(chain.get_blocks(4, 2).is_empty());
+    assert!(chain.get_blocks(usize::MAX, 2).is_empty());
+    assert_eq!(chain.get_blocks(1, usize::MAX).len(), 5);
+    assert!(chain.get_blocks(1, 0).is_empty());
+    assert_eq!(
+        chain.get_blocks(3, 2)[0].header.previous_hash,
+        chain.get_block(3).unwrap().hash()
+    );
+    assert_eq!(chain.get_block_hash(4), Some(last_hash));
+    assert_eq!(
+        chain.get_block_hash(3),
+        chain.get_block(3).map(Block::compute_hash)
+    );
+    assert!(chain.get_block_hash(5).is_none());
+}
+
 #[test]
 fn test_get_transactions() {
     let mut chain = setup();
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
 
-    chain.add_transaction(from.clone(), to.clone(), 10.0);
-    chain.add_transaction(to.clone(), from.clone(), 20.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain
+        .add_transaction(to.clone(), from.clone(), 5.0, &to_keypair)
+        .unwrap();
 
     let transactions = chain.get_transactions(0, 10);
 
@@ -183,7 +469,7 @@ fn test_get_transactions_empty_page() {
 fn test_create_wallet() {
     let mut chain = setup();
 
-    let result = chain.create_wallet("s@mail.com".to_string());
+    let result = chain.create_wallet("s@mail.com".to_string()).unwrap();
 
     assert_eq!(result.len(), 42);
 }
@@ -191,7 +477,7 @@ fn test_create_wallet() {
 #[test]
 fn test_get_wallet_balance() {
     let mut chain = setup();
-    let address = chain.create_wallet("s@mail.com".to_string());
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
 
     let result = chain.get_wallet_balance(address);
 
@@ -211,24 +497,84 @@ fn test_get_wallet_balance_not_found() {
 fn test_get_wallet_transactions() {
     let mut chain = setup();
 
-    let from = chain.create_wallet("s@mail.com".to_string());
-    let to = chain.create_wallet("r@mail.com".to_string());
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
 
-    let sender = chain.wallets.get_mut(&from).unwrap();
-    sender.balance += 20.0;
+    chain.credit_wallet(&from, 20.0);
 
-    chain.add_transaction(from.clone(), to.clone(), 10.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
 
     let transactions = chain.get_wallet_transactions(from, 0, 10).unwrap();
 
     assert!(!transactions.is_empty());
 }
 
+#[test]
+fn test_get_wallet_transactions_after_mining() {
+    let mut chain = setup();
+
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let mined = chain
+        .add_transaction(from.clone(), to.clone(), 5.0, &keypair)
+        .unwrap();
+
+    chain.generate_new_block().unwrap();
+
+    let pending = chain
+        .add_transaction(from.clone(), to.clone(), 4.0, &keypair)
+        .unwrap();
+
+    // The history spans the mined blocks and the pending transactions
+    let history = chain.get_wallet_transactions(from.clone(), 1, 10).unwrap();
+    let hashes: Vec<&str> = history.iter().map(|t| t.hash.as_str()).collect();
+
+    assert_eq!(hashes.len(), 3);
+    assert_eq!(hashes[1..], [mined.as_str(), pending.as_str()]);
+    assert_eq!(
+        chain.locate_transaction(&mined),
+        Some(TransactionLocation::Block(1))
+    );
+    assert_eq!(
+        chain.locate_transaction(&pending),
+        Some(TransactionLocation::Pending)
+    );
+    assert_eq!(chain.locate_transaction("NonExistentHash"), None);
+
+    // Pages of the history
+    let page = |page: usize| {
+        chain
+            .get_wallet_transactions(from.clone(), page, 2)
+            .unwrap()
+            .len()
+    };
+
+    assert_eq!((page(1), page(2), page(3)), (2, 1, 0));
+    assert_eq!(page(usize::MAX), 0);
+
+    // A deserialized blockchain finds the mined transactions before its index is rebuilt
+    let restored: Chain = serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+    assert_eq!(
+        restored.locate_transaction(&mined),
+        Some(TransactionLocation::Block(1))
+    );
+    assert_eq!(
+        restored.get_wallet_transactions(to, 1, 10).unwrap().len(),
+        2
+    );
+}
+
 #[test]
 fn test_get_new_wallet_transactions() {
     let mut chain = setup();
 
-    let from = chain.create_wallet("s@mail.com".to_string());
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
 
     let transactions = chain.get_wallet_transactions(from, 0, 10).unwrap();
 
@@ -267,8 +613,27 @@ fn test_update_difficulty() {
 
     let result = chain.update_difficulty(4.0);
 
-    assert!(result);
-    assert_eq!(chain.difficulty, 4.0);
+    assert_eq!(result, Ok(()));
+    assert_eq!(chain.get_difficulty(), 4.0);
+}
+
+#[test]
+fn test_update_difficulty_invalid() {
+    let mut chain = setup();
+
+    assert_eq!(
+        chain.update_difficulty(0.0),
+        Err(ChainError::InvalidDifficulty)
+    );
+    assert_eq!(
+        chain.update_difficulty(65.0),
+        Err(ChainError::InvalidDifficulty)
+    );
+    assert_eq!(
+        chain.update_difficulty(f64::NAN),
+        Err(ChainError::InvalidDifficulty)
+    );
+    assert_eq!(chain.get_difficulty(), 1.0);
 }
 
 #[test]
@@ -277,8 +642,10 @@ fn test_update_reward() {
 
     let result = chain.update_reward(50.0);
 
-    assert!(result);
-    assert_eq!(chain.reward, 50.0);
+    assert_eq!(result, Ok(()));
+    assert_eq!(chain.get_reward(), 50.0);
+    assert_eq!(chain.update_reward(-1.0), Err(ChainError::InvalidReward));
+    assert_eq!(chain.get_reward(), 50.0);
 }
 
 #[test]
@@ -287,8 +654,10 @@ fn test_update_fee() {
 
     let result = chain.update_fee(0.02);
 
-    assert!(result);
-    assert_eq!(chain.fee, 0.02);
+    assert_eq!(result, Ok(()));
+    assert_eq!(chain.get_fee(), 0.02);
+    assert_eq!(chain.update_fee(f64::INFINITY), Err(ChainError::InvalidFee));
+    assert_eq!(chain.get_fee(), 0.02);
 }
 
 #[test]
@@ -297,6 +666,2886 @@ fn test_generate_new_block() {
 
     let result = chain.generate_new_block();
 
-    assert!(result);
-    assert_eq!(chain.chain.len(), 2);
+    assert_eq!(result, Ok(()));
+    assert_eq!(chain.get_all_blocks().len(), 2);
+}
+
+#[test]
+fn test_mine_block_parallel() {
+    let mut chain = Chain::new(2.0, 100.0, 0.1);
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    let hash = chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+
+    assert_eq!(chain.mine_block_parallel(4), Ok(()));
+    assert_eq!(chain.get_all_blocks().len(), 2);
+    assert!(chain.get_block(1).unwrap().meets_difficulty());
+    assert!(chain.find_transaction_in_chain(&hash).is_some());
+    assert_eq!(chain.validate_range(0, 1), Ok(2));
+}
+
+#[tokio::test]
+async fn test_mine_block_async() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    let hash = chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+
+    assert_eq!(
+        chain.mine_block_async(CancellationToken::new()).await,
+        Ok(())
+    );
+    assert_eq!(chain.get_all_blocks().len(), 2);
+    assert!(chain.find_transaction_in_chain(&hash).is_some());
+    assert!(chain.get_pending_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_mine_block_async_cancelled() {
+    let mut chain = setup();
+    let token = CancellationToken::new();
+
+    // No hash can satisfy the difficulty before the token is cancelled
+    chain.update_difficulty(64.0).unwrap();
+
+    let cancel = token.clone();
+    tokio::spawn(async move { cancel.cancel() });
+
+    assert_eq!(
+        chain.mine_block_async(token).await,
+        Err(ChainError::MiningCancelled)
+    );
+    assert_eq!(chain.get_all_blocks().len(), 1);
+}
+
+#[tokio::test]
+async fn test_add_transaction_when_ready_async() {
+    let payment = |chain: &mut Chain| {
+        let (from, keypair) = common::create_wallet(chain, "s@mail.com");
+        let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&from, 20.0);
+        chain.generate_new_block().unwrap();
+        chain
+            .try_add_transaction(from.clone(), to.clone(), 1.0, &keypair)
+            .unwrap();
+
+        (from, to, keypair)
+    };
+
+    // A mempool holding a single payment is full until the next block
+    let mut probe = setup();
+
+    payment(&mut probe);
+
+    let config = ChainConfig {
+        memory: MemoryLimits {
+            mempool_bytes: Some(probe.memory_usage().mempool_bytes),
+            index_bytes: None,
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let (from, to, keypair) = payment(&mut chain);
+    let error = chain
+        .try_add_transaction(from.clone(), to.clone(), 1.0, &keypair)
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        ChainError::MempoolFull {
+            retry_after_hint: Duration::from_secs(1)
+        }
+    );
+    assert_eq!(error.to_string(), "Mempool is full, retry in 1 seconds");
+
+    let chain = Arc::new(tokio::sync::Mutex::new(chain));
+    let miner = chain.clone();
+
+    tokio::spawn(async move { miner.lock().await.generate_new_block().unwrap() });
+
+    assert!(Chain::add_transaction_when_ready_async(
+        &chain,
+        &from,
+        &to,
+        1.0,
+        &keypair,
+        Duration::from_secs(30)
+    )
+    .await
+    .is_ok());
+    assert_eq!(chain.lock().await.get_pending_transactions().len(), 1);
+}
+
+#[test]
+fn test_generate_new_block_invalid_difficulty() {
+    let mut chain = Chain::new(0.0, 100.0, 0.1);
+
+    assert!(chain.get_all_blocks().is_empty());
+    assert_eq!(
+        chain.generate_new_block(),
+        Err(ChainError::InvalidDifficulty)
+    );
+
+    chain.update_difficulty(1.0).unwrap();
+
+    assert_eq!(chain.generate_new_block(), Ok(()));
+    assert_eq!(chain.get_all_blocks().len(), 1);
+}
+
+#[test]
+fn test_add_transaction_errors() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    assert_eq!(
+        chain.add_transaction("Root".to_string(), to.clone(), 1.0, &keypair),
+        Err(ChainError::UnknownSender("Root".to_string()))
+    );
+    assert_eq!(
+        chain.add_transaction("unknown".to_string(), to.clone(), 1.0, &keypair),
+        Err(ChainError::UnknownSender("unknown".to_string()))
+    );
+    assert_eq!(
+        chain.add_transaction(from.clone(), "unknown".to_string(), 1.0, &keypair),
+        Err(ChainError::UnknownReceiver("unknown".to_string()))
+    );
+    assert_eq!(
+        chain.add_transaction(from.clone(), from.clone(), 1.0, &keypair),
+        Err(ChainError::SelfTransfer)
+    );
+    assert_eq!(
+        chain.add_transaction(from.clone(), to.clone(), f64::NAN, &keypair),
+        Err(ChainError::InvalidAmount)
+    );
+    assert_eq!(
+        chain.add_transaction(from, to.clone(), 100.0, &keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+}
+
+#[test]
+fn test_add_transaction_signature() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let keyless = chain.create_wallet("k@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    chain.credit_wallet(&keyless, 20.0);
+
+    // Wallets without public key cannot prove who spends them
+    assert_eq!(
+        chain.add_transaction(keyless.clone(), to.clone(), 1.0, &keypair),
+        Err(ChainError::UnregisteredKey(keyless.clone()))
+    );
+    assert_eq!(
+        chain.add_transaction(from.clone(), to.clone(), 1.0, &Keypair::generate()),
+        Err(ChainError::InvalidSignature)
+    );
+    assert!(chain.add_transaction(from, to, 1.0, &keypair).is_ok());
+    assert_eq!(chain.get_pending_transactions().len(), 3);
+    assert_eq!(chain.get_wallet_balance(keyless), Some(20.0));
+}
+
+#[test]
+fn test_create_wallet_deterministic_address() {
+    let config = ChainConfig {
+        chain_id: "classroom".to_string(),
+        address_mode: AddressMode::Deterministic {
+            salt: "salt".to_string(),
+        },
+        ..ChainConfig::default()
+    };
+
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config.clone());
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    let mut reset = Chain::with_config(1.0, 100.0, 0.1, config);
+    let result = reset.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert_eq!(address.len(), 42);
+    assert_eq!(result, address);
+}
+
+#[test]
+fn test_create_wallet_deterministic_address_existing_email() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            chain_id: "classroom".to_string(),
+            address_mode: AddressMode::Deterministic {
+                salt: "salt".to_string(),
+            },
+            ..ChainConfig::default()
+        },
+    );
+
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&address, 20.0);
+
+    let result = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert_eq!(result, address);
+    assert_eq!(chain.get_wallets_count(), 1);
+    assert_eq!(chain.get_wallet_balance(address), Some(20.0));
+}
+
+#[test]
+fn test_create_wallet_deterministic_address_per_chain() {
+    let config = ChainConfig {
+        chain_id: "a".to_string(),
+        address_mode: AddressMode::Deterministic {
+            salt: "salt".to_string(),
+        },
+        ..ChainConfig::default()
+    };
+
+    let mut a = Chain::with_config(1.0, 100.0, 0.1, config.clone());
+    let mut b = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            chain_id: "b".to_string(),
+            ..config
+        },
+    );
+
+    let address = a.create_wallet("s@mail.com".to_string()).unwrap();
+    let result = b.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert_ne!(result, address);
+}
+
+#[test]
+fn test_create_wallet_custom_address_generator() {
+    let mut chain = setup();
+    chain.set_address_generator(SequentialAddressGenerator::new("wallet-".to_string()));
+
+    let first = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let second = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    assert_eq!(first, "wallet-0");
+    assert_eq!(second, "wallet-1");
+    assert!(chain.get_wallet_balance(second).is_some());
+}
+
+#[test]
+fn test_create_wallet_address_taken() {
+    let mut chain = setup();
+    chain.set_address_generator(SequentialAddressGenerator::new("wallet-".to_string()));
+
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&address, 20.0);
+
+    // A new generator starts over and yields the address of the existing wallet
+    chain.set_address_generator(SequentialAddressGenerator::new("wallet-".to_string()));
+
+    let result = chain.create_wallet("r@mail.com".to_string());
+
+    assert_eq!(result, Err(ChainError::AddressTaken(address.to_owned())));
+    assert_eq!(chain.get_wallets_count(), 1);
+    assert_eq!(
+        chain.get_wallet(&address).unwrap().email,
+        "s@mail.com".to_string()
+    );
+    assert_eq!(chain.get_wallet_balance(address), Some(20.0));
+}
+
+#[test]
+fn test_create_wallets() {
+    let mut chain = setup();
+
+    let emails: Vec<String> = (0..100).map(|i| format!("{}@mail.com", i)).collect();
+    let result = chain.create_wallets(&emails);
+
+    assert_eq!(result.len(), 100);
+    assert!(result.iter().all(|address| address.is_ok()));
+    assert_eq!(chain.get_wallets_count(), 100);
+}
+
+#[test]
+fn test_create_wallets_duplicate_emails() {
+    let mut chain = setup();
+    chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    let result = chain.create_wallets(&[
+        "s@mail.com".to_string(),
+        "r@mail.com".to_string(),
+        "r@mail.com".to_string(),
+        " ".to_string(),
+    ]);
+
+    assert_eq!(
+        result[0],
+        Err(ChainError::DuplicateEmail("s@mail.com".to_string()))
+    );
+    assert!(result[1].is_ok());
+    assert_eq!(
+        result[2],
+        Err(ChainError::DuplicateEmail("r@mail.com".to_string()))
+    );
+    assert_eq!(result[3], Err(ChainError::InvalidEmail));
+    assert_eq!(chain.get_wallets_count(), 2);
+}
+
+#[test]
+fn test_list_wallets() {
+    let mut chain = setup();
+    chain.set_address_generator(SequentialAddressGenerator::new("wallet-".to_string()));
+
+    let first = chain.create_wallet("b@mail.com".to_string()).unwrap();
+    let second = chain.create_wallet("a@mail.com".to_string()).unwrap();
+    let third = chain.create_wallet("c@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&third, 20.0);
+
+    let by_address = chain.list_wallets(1, 2, WalletSort::Address);
+    let by_email = chain.list_wallets(1, 10, WalletSort::Email);
+    let by_balance = chain.list_wallets(1, 1, WalletSort::BalanceDesc);
+    let last_page = chain.list_wallets(2, 2, WalletSort::Address);
+
+    assert_eq!(by_address.len(), 2);
+    assert_eq!(by_address[0].address, first);
+    assert_eq!(by_email[0].address, second);
+    assert_eq!(by_balance[0].address, third);
+    assert_eq!(last_page.len(), 1);
+    assert_eq!(last_page[0].address, third);
+}
+
+#[test]
+fn test_list_wallets_empty_page() {
+    let mut chain = setup();
+    chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert!(chain.list_wallets(10, 10, WalletSort::Address).is_empty());
+    assert!(chain
+        .list_wallets(usize::MAX, 10, WalletSort::Address)
+        .is_empty());
+    assert!(chain.list_wallets(1, 0, WalletSort::Address).is_empty());
+}
+
+#[test]
+fn test_search_wallets() {
+    let mut chain = setup();
+    chain.set_address_generator(SequentialAddressGenerator::new("wallet-".to_string()));
+
+    let address = chain.create_wallet("sender@mail.com".to_string()).unwrap();
+    chain
+        .create_wallet("receiver@mail.com".to_string())
+        .unwrap();
+
+    let by_email = chain.search_wallets("send");
+    let by_address = chain.search_wallets("wallet-");
+
+    assert_eq!(by_email.len(), 1);
+    assert_eq!(by_email[0].address, address);
+    assert_eq!(by_address.len(), 2);
+    assert!(chain.search_wallets("").is_empty());
+    assert!(chain.search_wallets("unknown").is_empty());
+}
+
+#[test]
+fn test_get_wallet() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    let wallet = chain.get_wallet(&address);
+
+    assert!(wallet.is_some());
+    assert_eq!(wallet.unwrap().email, "s@mail.com");
+    assert!(chain.get_wallet("address").is_none());
+}
+
+#[test]
+fn test_credit_wallet() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert!(chain.credit_wallet(&address, 20.0));
+    assert!(!chain.credit_wallet(&address, -20.0));
+    assert!(!chain.credit_wallet("address", 20.0));
+
+    let credit = &chain.get_pending_transactions()[0];
+
+    assert_eq!(chain.get_pending_transactions().len(), 1);
+    assert_eq!(credit.from, "Root");
+    assert_eq!(credit.amount, 20.0);
+    assert_eq!(
+        chain.get_wallet(&address).unwrap().transactions,
+        vec![credit.hash.clone()]
+    );
+    assert_eq!(chain.get_wallet_balance(address), Some(20.0));
+}
+
+#[test]
+fn test_get_all_blocks() {
+    let chain = setup();
+
+    let blocks = chain.get_all_blocks();
+
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].header.previous_hash, "0".repeat(64));
+}
+
+#[test]
+fn test_add_transaction_fee() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
+
+    let transaction = chain.get_pending_transactions().last().unwrap();
+
+    assert_eq!(transaction.amount, 10.0);
+    assert_eq!(transaction.fee, 1.0);
+    assert_eq!(chain.get_wallet_balance(from), Some(9.0));
+    assert_eq!(chain.get_wallet_balance(to), Some(10.0));
+}
+
+#[test]
+fn test_add_transaction_insufficient_balance_for_fee() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 10.0);
+
+    let result = chain.add_transaction(from, to, 10.0, &keypair);
+
+    assert_eq!(result, Err(ChainError::InsufficientBalance));
+}
+
+#[test]
+fn test_check_invariants() {
+    let mut chain = setup();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 20.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain.add_transaction(to, from, 5.0, &to_keypair).unwrap();
+
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_validate_range() {
+    let mut chain = setup();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain.add_transaction(to, from, 5.0, &to_keypair).unwrap();
+    chain.generate_new_block().unwrap();
+
+    let mut heights = vec![];
+    let result = chain.validate_range_with_progress(1, 100, |progress| {
+        heights.push((progress.height, progress.remaining()));
+    });
+
+    assert_eq!(result, Ok(3));
+    assert_eq!(heights, vec![(1, 2), (2, 1), (3, 0)]);
+    assert_eq!(chain.validate_range(0, 0), Ok(1));
+    assert_eq!(chain.validate_range(3, 1), Ok(0));
+    assert_eq!(chain.validate_range(10, 20), Ok(0));
+}
+
+#[test]
+fn test_subscribe() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let events = chain.subscribe();
+    let hash = chain
+        .add_transaction(from.clone(), to, 10.0, &keypair)
+        .unwrap();
+
+    chain.generate_new_block_for(&from).unwrap();
+    chain.update_difficulty(2.0).unwrap();
+
+    let events: Vec<ChainEvent> = events.try_iter().collect();
+
+    assert_eq!(events.len(), 4);
+    assert!(matches!(
+        &events[0],
+        ChainEvent::TransactionAdded { transaction } if transaction.hash == hash
+    ));
+    assert!(matches!(
+        &events[1],
+        ChainEvent::RewardPaid { miner, amount, height: 1 } if *miner == from && *amount == 100.0
+    ));
+    assert!(matches!(
+        &events[2],
+        ChainEvent::BlockMined { height: 1, block } if block.transactions.len() == 3
+    ));
+    assert!(matches!(
+        &events[3],
+        ChainEvent::DifficultyChanged { previous, current } if *previous == 1.0 && *current == 2.0
+    ));
+}
+
+#[test]
+fn test_from_genesis() {
+    let keypair = Keypair::generate();
+    let genesis = GenesisConfig {
+        chain_id: "testnet".to_string(),
+        timestamp: 1_700_000_000,
+        difficulty: 1.0,
+        allocations: vec![
+            GenesisAllocation {
+                address: "alice".to_string(),
+                email: "a@mail.com".to_string(),
+                amount: 500.0,
+                public_key: Some(keypair.public_key()),
+            },
+            GenesisAllocation {
+                address: "bob".to_string(),
+                email: "b@mail.com".to_string(),
+                amount: 250.0,
+                public_key: None,
+            },
+        ],
+    };
+
+    let mut a = Chain::from_genesis(genesis.clone(), 100.0, 0.1, ChainConfig::default()).unwrap();
+    let mut b = Chain::from_genesis(genesis.clone(), 100.0, 0.1, ChainConfig::default()).unwrap();
+
+    // Both nodes construct the identical genesis block
+    assert_eq!(a.get_all_blocks().len(), 1);
+    assert_eq!(a.get_last_hash(), b.get_last_hash());
+    assert_eq!(a.get_all_blocks()[0].header.timestamp, 1_700_000_000);
+    assert_eq!(a.get_all_blocks()[0].transactions.len(), 3);
+    assert_eq!(a.get_wallet_balance("alice".to_string()), Some(500.0));
+    assert_eq!(a.get_wallet_balance("bob".to_string()), Some(250.0));
+    assert_eq!(a.check_invariants(), Ok(()));
+
+    // Blocks of one node extend the blockchain of the other
+    a.add_transaction("alice".to_string(), "bob".to_string(), 10.0, &keypair)
+        .unwrap();
+    a.generate_new_block().unwrap();
+
+    assert_eq!(b.import_block(a.get_all_blocks()[1].clone()), Ok(()));
+    assert_eq!(b.get_wallet_balance("bob".to_string()), Some(260.0));
+
+    // Another timestamp gives another genesis block
+    let other = Chain::from_genesis(
+        GenesisConfig {
+            timestamp: 1_700_000_001,
+            ..genesis.clone()
+        },
+        100.0,
+        0.1,
+        ChainConfig::default(),
+    )
+    .unwrap();
+
+    assert_ne!(other.get_block_hash(0), a.get_block_hash(0));
+    assert_eq!(
+        Chain::from_genesis(
+            GenesisConfig {
+                difficulty: 0.0,
+                ..genesis
+            },
+            100.0,
+            0.1,
+            ChainConfig::default()
+        )
+        .err(),
+        Some(ChainError::InvalidDifficulty)
+    );
+}
+
+#[derive(Debug)]
+struct Kyc {
+    verified: BTreeSet<String>,
+}
+
+impl TransactionValidator for Kyc {
+    fn validate(&self, _chain: &Chain, transaction: &Transaction) -> Verdict {
+        match self.verified.contains(&transaction.from) {
+            true => Verdict::Continue,
+            false => Verdict::Reject(format!("{} is not verified", transaction.from)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Trusted(String);
+
+impl TransactionValidator for Trusted {
+    fn validate(&self, _chain: &Chain, transaction: &Transaction) -> Verdict {
+        match transaction.from == self.0 {
+            true => Verdict::Accept,
+            false => Verdict::Continue,
+        }
+    }
+}
+
+#[test]
+fn test_transaction_validators() {
+    let mut chain = setup();
+    let (verified, verified_keypair) = common::create_wallet(&mut chain, "v@mail.com");
+    let (unverified, unverified_keypair) = common::create_wallet(&mut chain, "u@mail.com");
+    let (treasury, treasury_keypair) = common::create_wallet(&mut chain, "t@mail.com");
+
+    for address in [&verified, &unverified, &treasury] {
+        chain.credit_wallet(address, 100.0);
+    }
+
+    chain.add_validator(Trusted(treasury.clone()));
+    let kyc = chain.add_validator(Kyc {
+        verified: BTreeSet::from([verified.clone()]),
+    });
+
+    assert!(chain
+        .add_transaction(
+            verified.clone(),
+            unverified.clone(),
+            10.0,
+            &verified_keypair
+        )
+        .is_ok());
+    assert_eq!(
+        chain.add_transaction(
+            unverified.clone(),
+            verified.clone(),
+            10.0,
+            &unverified_keypair
+        ),
+        Err(ChainError::TransactionRejected(format!(
+            "{} is not verified",
+            unverified
+        )))
+    );
+    assert_eq!(chain.get_wallet_balance(unverified.clone()), Some(110.0));
+
+    // The trusted sender is accepted before the KYC check runs
+    assert!(chain
+        .add_transaction(
+            treasury.clone(),
+            unverified.clone(),
+            10.0,
+            &treasury_keypair
+        )
+        .is_ok());
+
+    // Consensus validation runs first
+    assert_eq!(
+        chain.add_transaction(
+            unverified.clone(),
+            verified.clone(),
+            1000.0,
+            &unverified_keypair
+        ),
+        Err(ChainError::InsufficientBalance)
+    );
+
+    assert!(chain.remove_validator(kyc));
+    assert!(chain
+        .add_transaction(unverified, verified, 10.0, &unverified_keypair)
+        .is_ok());
+}
+
+#[test]
+fn test_min_relay_fee() {
+    let alice = Keypair::generate();
+    let bob = Keypair::generate();
+    let allocation = |address: &str, keypair: &Keypair| GenesisAllocation {
+        address: address.to_string(),
+        email: format!("{}@mail.com", address),
+        amount: 100.0,
+        public_key: Some(keypair.public_key()),
+    };
+    let genesis = GenesisConfig {
+        chain_id: "testnet".to_string(),
+        timestamp: 1_700_000_000,
+        difficulty: 1.0,
+        allocations: vec![allocation("alice", &alice), allocation("bob", &bob)],
+    };
+    let config = ChainConfig {
+        min_relay_fee: 0.5,
+        ..ChainConfig::default()
+    };
+
+    let mut chain = Chain::from_genesis(genesis.clone(), 100.0, 0.1, config.clone()).unwrap();
+    let mut follower = chain.clone();
+    let mut peer = Chain::from_genesis(genesis, 100.0, 0.1, ChainConfig::default()).unwrap();
+
+    // The fee of the blockchain is satisfied, but not the floor of the mempool
+    assert_eq!(
+        chain.add_transaction("alice".to_string(), "bob".to_string(), 1.0, &alice),
+        Err(ChainError::RelayFeeTooLow)
+    );
+    assert_eq!(chain.get_wallet_balance("alice".to_string()), Some(100.0));
+
+    // The estimate never suggests a fee below the floor
+    assert_eq!(chain.estimate_fee(1.0), 0.5);
+    assert_eq!(chain.estimate_fee(10.0), 1.0);
+
+    let transaction = Transaction::builder()
+        .from("alice")
+        .to("bob")
+        .amount(1.0)
+        .fee(chain.estimate_fee(1.0))
+        .build()
+        .sign(&alice);
+
+    assert_eq!(chain.submit_transaction(transaction), Ok(()));
+
+    // The estimate follows the fee rates paid recently
+    assert_eq!(chain.estimate_fee(10.0), 5.0);
+
+    // The floor does not apply to the transactions of imported blocks
+    peer.add_transaction("bob".to_string(), "alice".to_string(), 1.0, &bob)
+        .unwrap();
+    peer.generate_new_block().unwrap();
+
+    assert_eq!(
+        follower.import_block(peer.get_all_blocks()[1].clone()),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_get_transactions_zero_size() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&address, 20.0);
+
+    assert!(chain.get_transactions(1, 0).is_empty());
+    assert!(chain
+        .get_wallet_transactions(address, 1, 0)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_get_transaction_proof() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    chain
+        .add_transaction(from.clone(), to, 10.0, &keypair)
+        .unwrap();
+
+    let hash = chain.get_pending_transactions()[1].hash.to_owned();
+
+    assert!(chain.get_transaction_proof(&hash).is_none());
+
+    chain.generate_new_block().unwrap();
+
+    let proof = chain.get_transaction_proof(&hash).unwrap();
+
+    assert_eq!(proof.height, 1);
+    assert_eq!(proof.transaction.from, from);
+    assert!(proof.verify());
+}
+
+#[test]
+fn test_get_transaction_proof_tampered() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&address, 20.0);
+
+    let hash = chain.get_pending_transactions()[0].hash.to_owned();
+
+    chain.generate_new_block().unwrap();
+
+    let mut proof = chain.get_transaction_proof(&hash).unwrap();
+    proof.transaction.amount = 40.0;
+
+    assert!(!proof.verify());
+}
+
+#[test]
+fn test_overview() {
+    let mut chain = setup();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain.add_transaction(to, from, 1.0, &to_keypair).unwrap();
+
+    let overview = chain.overview(2);
+
+    assert_eq!(overview.height, 2);
+    assert_eq!(overview.latest_blocks.len(), 2);
+    assert_eq!(
+        overview.latest_blocks[0].header.merkle,
+        chain.get_all_blocks()[2].header.merkle
+    );
+    assert_eq!(overview.latest_transactions.len(), 2);
+    assert_eq!(overview.latest_transactions[0].amount, 10.0);
+    assert_eq!(overview.mempool_size, 1);
+    assert_eq!(overview.difficulty, 1.0);
+    assert_eq!(overview.supply, 20.0);
+}
+
+#[test]
+fn test_search() {
+    let mut chain = setup();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 20.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain
+        .add_transaction(to.clone(), from.clone(), 1.0, &to_keypair)
+        .unwrap();
+
+    let mined = chain.get_block(1).unwrap().transactions[1].hash.clone();
+    let pending = chain.get_pending_transactions()[0].hash.clone();
+    let block_hash = chain.get_block(1).unwrap().hash();
+
+    assert!(matches!(
+        chain.search("1"),
+        Some(SearchResult::Block { height: 1, .. })
+    ));
+    assert!(matches!(
+        chain.search(&block_hash.to_uppercase()),
+        Some(SearchResult::Block { height: 1, .. })
+    ));
+    assert!(matches!(
+        chain.search(&mined),
+        Some(SearchResult::Transaction { height: Some(1), transaction }) if transaction.hash == mined
+    ));
+    assert!(matches!(
+        chain.search(&pending),
+        Some(SearchResult::Transaction { height: None, .. })
+    ));
+    assert!(matches!(
+        chain.search(&format!(" {} ", from)),
+        Some(SearchResult::Wallet { wallet }) if wallet.address == from
+    ));
+    assert!(matches!(
+        chain.search("r@mail.com"),
+        Some(SearchResult::Wallet { wallet }) if wallet.address == to
+    ));
+    assert!(chain.search("7").is_none());
+    assert!(chain.search(&"0".repeat(64)).is_none());
+    assert!(chain.search("unknown").is_none());
+    assert!(chain.search("").is_none());
+}
+
+#[test]
+fn test_create_wallet_idempotent() {
+    let mut chain = setup();
+
+    let address = chain
+        .create_wallet_idempotent("s@mail.com".to_string())
+        .unwrap();
+    let result = chain
+        .create_wallet_idempotent("s@mail.com".to_string())
+        .unwrap();
+
+    assert_eq!(result, address);
+    assert_eq!(chain.get_wallets_count(), 1);
+    assert_eq!(
+        chain.get_wallet_by_email("s@mail.com").unwrap().address,
+        address
+    );
+}
+
+#[test]
+fn test_create_wallet_not_idempotent() {
+    let mut chain = setup();
+
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let result = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert_ne!(result, address);
+    assert_eq!(chain.get_wallets_count(), 2);
+    assert!(chain.get_wallet_by_email("r@mail.com").is_none());
+}
+
+#[test]
+fn test_submit_transaction() {
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let transaction = Transaction::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .amount(10.0)
+        .fee(2.0)
+        .build();
+
+    assert!(chain.submit_transaction(transaction).is_ok());
+    assert_eq!(chain.get_wallet_balance(from), Some(8.0));
+    assert_eq!(chain.get_wallet_balance(to), Some(10.0));
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_submit_transaction_fee_too_low() {
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let transaction = Transaction::builder()
+        .from(from.clone())
+        .to(to)
+        .amount(10.0)
+        .fee(0.5)
+        .build();
+
+    assert_eq!(
+        chain.submit_transaction(transaction),
+        Err(ChainError::FeeTooLow)
+    );
+    assert_eq!(chain.get_wallet_balance(from), Some(20.0));
+}
+
+#[test]
+fn test_submit_transaction_tampered() {
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let mut transaction = Transaction::builder()
+        .from(from)
+        .to(to)
+        .amount(1.0)
+        .fee_rate(0.1)
+        .build();
+    transaction.amount = 10.0;
+
+    assert_eq!(
+        chain.submit_transaction(transaction),
+        Err(ChainError::InvalidHash)
+    );
+}
+
+#[test]
+fn test_submit_transaction_nonce() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let transaction = |nonce, amount| {
+        Transaction::builder()
+            .from(from.clone())
+            .to(to.clone())
+            .amount(amount)
+            .fee_rate(0.1)
+            .nonce(nonce)
+            .build()
+            .sign(&keypair)
+    };
+
+    // A nonce skipping a transaction is rejected
+    assert_eq!(chain.get_nonce(&from), Some(0));
+    assert_eq!(
+        chain.submit_transaction(transaction(1, 1.0)),
+        Err(ChainError::InvalidNonce {
+            expected: 0,
+            actual: 1
+        })
+    );
+
+    assert_eq!(chain.submit_transaction(transaction(0, 1.0)), Ok(()));
+    assert_eq!(chain.get_nonce(&from), Some(1));
+    assert_eq!(chain.get_nonce(&to), Some(0));
+
+    // A used nonce is rejected, even once mined
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(
+        chain.submit_transaction(transaction(0, 2.0)),
+        Err(ChainError::InvalidNonce {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(chain.check_nonce(&from, 1), Ok(()));
+    assert_eq!(
+        chain.check_nonce("unknown", 0),
+        Err(ChainError::UnknownSender("unknown".to_string()))
+    );
+
+    chain
+        .add_transaction(from.clone(), to.clone(), 1.0, &keypair)
+        .unwrap();
+
+    assert_eq!(chain.get_nonce(&from), Some(2));
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_submit_transaction_insufficient_balance_for_fee() {
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let transaction = Transaction::builder()
+        .from(from)
+        .to(to)
+        .amount(10.0)
+        .fee(15.0)
+        .build();
+
+    assert_eq!(
+        chain.submit_transaction(transaction),
+        Err(ChainError::InsufficientBalance)
+    );
+}
+
+#[test]
+fn test_timestamps_in_millis() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            timestamps: TimestampPrecision::Millis,
+            ..ChainConfig::default()
+        },
+    );
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    assert!(chain
+        .add_transaction(from.clone(), to.clone(), 1.0, &keypair)
+        .is_ok());
+    assert!(chain.add_transaction(from, to, 1.0, &keypair).is_ok());
+
+    let transactions = chain.get_transactions(1, 10);
+
+    assert_eq!(transactions.len(), 3);
+    assert!(transactions.iter().all(|t| t.sequence.is_some()));
+    assert_ne!(transactions[1].hash, transactions[2].hash);
+    assert!(
+        (transactions[1].timestamp, transactions[1].sequence)
+            < (transactions[2].timestamp, transactions[2].sequence)
+    );
+}
+
+#[test]
+fn test_import_block() {
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let (to, keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut replica = chain.clone();
+
+    // A transaction pending on both chains, and one pending on the miner only
+    let pending = Transaction::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .amount(10.0)
+        .fee(1.0)
+        .build();
+
+    chain.submit_transaction(pending.clone()).unwrap();
+    replica.submit_transaction(pending).unwrap();
+    chain
+        .add_transaction(to.clone(), from.clone(), 5.0, &keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert_eq!(replica.import_block(block.clone()), Ok(()));
+    assert_eq!(replica.get_last_hash(), chain.get_last_hash());
+    assert!(replica.get_pending_transactions().is_empty());
+    assert_eq!(
+        replica.get_wallet_balance(from.clone()),
+        chain.get_wallet_balance(from)
+    );
+    assert_eq!(
+        replica.get_wallet_balance(to.clone()),
+        chain.get_wallet_balance(to)
+    );
+    assert_eq!(replica.check_invariants(), Ok(()));
+
+    // The block no longer extends the last block
+    assert_eq!(replica.import_block(block), Err(ChainError::UnknownParent));
+}
+
+#[test]
+fn test_import_invalid_block() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut replica = chain.clone();
+
+    chain
+        .add_transaction(from.clone(), to, 10.0, &keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    // The hash does not satisfy the difficulty anymore
+    let mut tampered = block.clone();
+    tampered.header.difficulty = 64.0;
+
+    assert!(matches!(
+        replica.import_block(tampered),
+        Err(ChainError::InvalidBlock(_))
+    ));
+
+    // The cached hash does not match the header
+    let mut tampered = block.clone();
+    tampered.header.nonce += 1;
+
+    assert!(!tampered.has_valid_hash());
+
+    while !tampered.meets_difficulty() {
+        tampered.header.nonce += 1;
+    }
+
+    assert_eq!(
+        replica.import_block(tampered),
+        Err(ChainError::InvalidBlock(
+            "the hash does not match the header".to_string()
+        ))
+    );
+
+    // The transactions do not match the Merkle root
+    let mut tampered = block.clone();
+    tampered.transactions[1].amount = 50.0;
+
+    assert!(matches!(
+        replica.import_block(tampered),
+        Err(ChainError::InvalidBlock(_))
+    ));
+
+    // The transaction conflicts with a pending transaction of the sender, so the state is left
+    // unchanged
+    let receiver = replica.create_wallet("x@mail.com".to_string()).unwrap();
+
+    replica
+        .add_transaction(from.clone(), receiver, 90.0, &keypair)
+        .unwrap();
+
+    let balance = replica.get_wallet_balance(from.clone());
+
+    assert_eq!(
+        replica.import_block(block),
+        Err(ChainError::InvalidNonce {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(replica.get_pending_transactions().len(), 1);
+    assert_eq!(replica.get_wallet_balance(from), balance);
+    assert_eq!(replica.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_genesis_commits_to_consensus_rules() {
+    let chain = setup();
+    let rules = chain.get_consensus_rules().hash();
+    let blocks = chain.get_all_blocks();
+
+    assert_eq!(blocks[0].header.rules, Some(rules));
+
+    let mut chain = setup();
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.get_all_blocks()[1].header.rules, None);
+}
+
+#[test]
+fn test_max_block_weight() {
+    let transaction = Transaction::builder()
+        .from("Root")
+        .to("address")
+        .amount(100.0)
+        .fee(0.1)
+        .build();
+    let weight = transaction.weight();
+
+    // The coinbase and about two transactions fit in a block
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            consensus: ConsensusRules {
+                max_block_weight: Some(3 * weight),
+                ..ConsensusRules::default()
+            },
+            ..ChainConfig::default()
+        },
+    );
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+
+    for _ in 0..4 {
+        chain
+            .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+            .unwrap();
+    }
+
+    let submitted = chain.get_pending_transactions().len();
+    let mut replica = chain.clone();
+
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+    let pending = chain.get_pending_transactions().to_vec();
+
+    assert!(block.weight() <= 3 * weight);
+    assert!(!pending.is_empty());
+    assert_eq!(block.count + pending.len(), submitted + 1);
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // A block holding every transaction is too heavy
+    let mut heavy = block;
+    heavy.transactions.extend(pending);
+    heavy.order_transactions();
+    heavy.count = heavy.transactions.len();
+    heavy.header.merkle = Chain::get_merkle(heavy.transactions.clone());
+    heavy.header.nonce = 0;
+    Block::proof_of_work(&mut heavy.header);
+
+    assert_eq!(
+        replica.import_block(heavy),
+        Err(ChainError::InvalidBlock(
+            "the block exceeds the maximum weight".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_max_transactions_per_block() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            consensus: ConsensusRules {
+                max_transactions_per_block: Some(2),
+                ..ConsensusRules::default()
+            },
+            ..ChainConfig::default()
+        },
+    );
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+
+    for _ in 0..4 {
+        chain
+            .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+            .unwrap();
+    }
+
+    let mut replica = chain.clone();
+
+    // The coinbase and the two oldest transactions, the rest stays pending
+    let submitted = chain.get_pending_transactions().to_vec();
+
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert_eq!(block.count, 3);
+    assert_eq!(chain.get_pending_transactions().len(), 3);
+    assert_eq!(chain.get_pending_transactions()[0].hash, submitted[2].hash);
+
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    assert!(chain.get_pending_transactions().is_empty());
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // A block holding more transactions is rejected
+    let mut full = block;
+    full.transactions.extend(submitted[2..].iter().cloned());
+    full.order_transactions();
+    full.count = full.transactions.len();
+    full.header.merkle = Chain::get_merkle(full.transactions.clone());
+    full.header.nonce = 0;
+    Block::proof_of_work(&mut full.header);
+
+    assert_eq!(
+        replica.import_block(full),
+        Err(ChainError::InvalidBlock(
+            "the block exceeds the maximum transaction count".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_reserved_system_transactions() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            consensus: ConsensusRules {
+                max_transactions_per_block: Some(2),
+                reserved_system_transactions: Some(1),
+                ..ConsensusRules::default()
+            },
+            ..ChainConfig::default()
+        },
+    );
+    let keypair = Keypair::generate();
+    let owner = chain
+        .create_wallet_with_key("o@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+
+    for _ in 0..3 {
+        chain
+            .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+            .unwrap();
+    }
+
+    // The key rotation is submitted last, during congestion
+    chain.rotate_keys(&owner, Some(&keypair)).unwrap();
+
+    let mut replica = chain.clone();
+    let submitted = chain.get_pending_transactions().to_vec();
+
+    chain.generate_new_block().unwrap();
+
+    // The transfers only fill their part of the block, the rotation takes the reserved part
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert_eq!(block.count, 3);
+    assert!(block.transactions.iter().any(Transaction::is_system));
+    assert_eq!(chain.get_pending_transactions().len(), 3);
+    assert_eq!(chain.get_pending_transactions()[0].hash, submitted[1].hash);
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // A block whose transfers take the reserved part is rejected
+    let mut crowded = block.clone();
+    crowded
+        .transactions
+        .retain(|transaction| !transaction.is_system());
+    crowded.transactions.push(submitted[1].clone());
+    crowded.order_transactions();
+    crowded.count = crowded.transactions.len();
+    crowded.header.merkle = Chain::get_merkle(crowded.transactions.clone());
+    crowded.header.nonce = 0;
+    Block::proof_of_work(&mut crowded.header);
+
+    assert_eq!(
+        replica.import_block(crowded),
+        Err(ChainError::InvalidBlock(
+            "the transfers exceed the block space outside the system lane".to_string()
+        ))
+    );
+    assert_eq!(replica.import_block(block), Ok(()));
+}
+
+#[test]
+fn test_import_block_signatures() {
+    let mut chain = setup();
+    let keypair = Keypair::generate();
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut replica = chain.clone();
+    let transfer = |keypair: &Keypair| {
+        Transaction::builder()
+            .from(from.clone())
+            .to(to.clone())
+            .amount(10.0)
+            .fee(1.0)
+            .nonce(1)
+            .build()
+            .sign(keypair)
+    };
+
+    // The transfer is signed by the key rotated earlier in the block
+    let rotated = chain.rotate_keys(&from, Some(&keypair)).unwrap();
+
+    chain.submit_transaction(transfer(&rotated)).unwrap();
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    // The same block with the transfer signed by the revoked key
+    let mut tampered = block.clone();
+    tampered.transactions.pop();
+    tampered.transactions.push(transfer(&keypair));
+    tampered.order_transactions();
+    tampered.header.merkle = Chain::get_merkle(tampered.transactions.clone());
+    tampered.header.nonce = 0;
+    Block::proof_of_work(&mut tampered.header);
+
+    assert_eq!(
+        replica.import_block(tampered),
+        Err(ChainError::InvalidSignature)
+    );
+    assert_eq!(replica.import_block(block), Ok(()));
+    assert_eq!(replica.get_last_hash(), chain.get_last_hash());
+    assert_eq!(replica.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_import_block_from_the_future() {
+    let mut chain = setup();
+    let mut replica = chain.clone();
+
+    chain.generate_new_block().unwrap();
+
+    let mut block = chain.get_all_blocks().last().unwrap().clone();
+    let drift = chain.get_consensus_rules().max_timestamp_drift;
+
+    block.header.timestamp += drift + 60;
+    block.header.nonce = 0;
+    Block::proof_of_work(&mut block.header);
+
+    assert_eq!(
+        replica.import_block(block),
+        Err(ChainError::InvalidBlock(
+            "the timestamp is too far in the future".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_email_privacy() {
+    let config = ChainConfig {
+        email_privacy: EmailPrivacy::Hashed,
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let address = chain.create_wallet("alice@mail.com".to_string()).unwrap();
+
+    // The email is matched by the lookups without being stored
+    assert_ne!(chain.get_wallet(&address).unwrap().email, "alice@mail.com");
+    assert_eq!(
+        chain.get_wallet_by_email("alice@mail.com").unwrap().address,
+        address
+    );
+    assert_eq!(
+        chain
+            .create_wallet_idempotent("alice@mail.com".to_string())
+            .unwrap(),
+        address
+    );
+    assert_eq!(
+        chain.create_wallets(&["alice@mail.com".to_string()]),
+        vec![Err(ChainError::DuplicateEmail(
+            "alice@mail.com".to_string()
+        ))]
+    );
+    assert!(chain.search_wallets("alice").is_empty());
+    assert!(!serde_json::to_string(&chain).unwrap().contains("alice"));
+
+    let config = ChainConfig {
+        email_privacy: EmailPrivacy::Omitted,
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let address = chain.create_wallet("bob@mail.com".to_string()).unwrap();
+    let wallet = serde_json::to_value(chain.get_wallet(&address).unwrap()).unwrap();
+
+    assert!(wallet.get("email").is_none());
+    assert!(chain.get_wallet_by_email("bob@mail.com").is_none());
+}
+
+#[test]
+fn test_timelocked_transaction() {
+    let mut chain = setup();
+    let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+
+    chain.set_clock(clock.clone());
+
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+    let (other, other_keypair) = common::create_wallet(&mut chain, "o@mail.com");
+
+    chain.credit_wallet(&from, 100.0);
+    chain.credit_wallet(&other, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let hash = chain
+        .add_timelocked_transaction(
+            from.to_owned(),
+            to.to_owned(),
+            10.0,
+            1_700_000_060,
+            &from_keypair,
+        )
+        .unwrap();
+    chain
+        .add_transaction(other.to_owned(), to.to_owned(), 5.0, &other_keypair)
+        .unwrap();
+
+    assert_eq!(
+        chain.get_transaction(hash.to_owned()).unwrap().locktime,
+        Some(1_700_000_060)
+    );
+
+    // The locked transaction stays pending without holding back the unrelated one
+    let mut replica = chain.clone();
+
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.get_all_blocks().last().unwrap().count, 2);
+    assert_eq!(chain.get_pending_transactions().len(), 1);
+    assert_eq!(chain.get_pending_transactions()[0].hash, hash);
+
+    replica
+        .import_block(chain.get_all_blocks().last().unwrap().clone())
+        .unwrap();
+
+    clock.advance(Duration::from_secs(60));
+
+    let mut block = chain.block_template(chain.get_address()).unwrap();
+
+    assert!(block
+        .transactions
+        .iter()
+        .any(|transaction| transaction.hash == hash));
+
+    // A block including the transaction before its locktime is rejected
+    block.header.timestamp -= 1;
+    Block::proof_of_work(&mut block.header);
+
+    assert_eq!(
+        replica.import_block(block),
+        Err(ChainError::InvalidBlock(
+            "a transaction is locked after the block timestamp".to_string()
+        ))
+    );
+
+    chain.generate_new_block().unwrap();
+
+    assert!(chain.get_pending_transactions().is_empty());
+    assert!(chain.find_transaction_in_chain(&hash).is_some());
+}
+
+#[test]
+fn test_compare() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&address, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut replica = chain.clone();
+
+    assert!(chain.compare(&replica).is_identical());
+
+    // The replica mines another transaction, and misses a wallet of the blockchain
+    let other = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    replica.credit_wallet(&address, 50.0);
+    replica.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    let comparison = chain.compare(&replica);
+
+    assert!(!comparison.is_identical());
+    assert_eq!(comparison.local_height, Some(3));
+    assert_eq!(comparison.other_height, Some(2));
+    assert_eq!(comparison.first_divergent_height, Some(2));
+    assert_eq!(comparison.blocks.len(), 2);
+    assert_eq!(comparison.blocks[1].local, Some(chain.get_last_hash()));
+    assert_eq!(comparison.blocks[1].other, None);
+    assert!(comparison
+        .balances
+        .iter()
+        .any(|difference| difference.address == address
+            && difference.local == Some(100.0)
+            && difference.other == Some(150.0)));
+    assert!(comparison
+        .balances
+        .iter()
+        .any(|difference| difference.address == other && difference.other.is_none()));
+}
+
+#[test]
+fn test_replace_chain() {
+    let mut chain = setup();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 100.0);
+    chain.credit_wallet(&to, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut fork = chain.clone();
+
+    // The blockchain mines two blocks while the fork mines one
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    let orphaned = fork
+        .add_transaction(to.clone(), from.clone(), 5.0, &to_keypair)
+        .unwrap();
+    fork.generate_new_block().unwrap();
+
+    assert!(chain.cumulative_work() > fork.cumulative_work());
+    assert_eq!(
+        chain.replace_chain(fork.get_all_blocks().to_vec()),
+        Err(ChainError::InsufficientWork)
+    );
+
+    // The fork switches to the blockchain and submits its orphaned transaction again
+    assert_eq!(fork.replace_chain(chain.get_all_blocks().to_vec()), Ok(2));
+    assert_eq!(fork.get_last_hash(), chain.get_last_hash());
+    assert_eq!(fork.get_pending_transactions().len(), 1);
+    assert_eq!(fork.get_pending_transactions()[0].hash, orphaned);
+    assert_eq!(
+        fork.locate_transaction(&orphaned),
+        Some(TransactionLocation::Pending)
+    );
+    assert_eq!(
+        fork.locate_transaction(&chain.get_all_blocks()[1].transactions.last().unwrap().hash),
+        Some(TransactionLocation::Block(1))
+    );
+    assert_eq!(
+        fork.get_wallet_balance(from.clone()),
+        chain.get_wallet_balance(from).map(|balance| balance + 5.0)
+    );
+    assert_eq!(fork.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_replace_chain_requeue() {
+    let mut chain = setup();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 100.0);
+    chain.credit_wallet(&to, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut fork = chain.clone();
+
+    chain
+        .add_transaction(from.clone(), to.clone(), 20.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    // The first payment is still valid on the blockchain, the second one overspends there
+    let requeued = fork
+        .add_transaction(to.clone(), from.clone(), 5.0, &to_keypair)
+        .unwrap();
+    let dropped = fork
+        .add_transaction(from.clone(), to.clone(), 90.0, &from_keypair)
+        .unwrap();
+    fork.generate_new_block().unwrap();
+
+    let pending = fork
+        .add_transaction(to.clone(), from.clone(), 1.0, &to_keypair)
+        .unwrap();
+    let receiver = fork.subscribe();
+
+    assert_eq!(fork.replace_chain(chain.get_all_blocks().to_vec()), Ok(2));
+
+    let hashes: Vec<String> = fork
+        .get_pending_transactions()
+        .iter()
+        .map(|transaction| transaction.hash.to_owned())
+        .collect();
+
+    assert_eq!(hashes, vec![requeued, pending]);
+    assert_eq!(fork.locate_transaction(&dropped), None);
+    assert!(receiver.try_iter().any(|event| matches!(
+        event,
+        ChainEvent::Reorganized {
+            fork_height: 2,
+            reverted: 1,
+            applied: 2,
+            requeued: 2,
+            dropped: 1,
+        }
+    )));
+    assert_eq!(fork.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_generate_new_block_for() {
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            coinbase_maturity: 2,
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let (miner, keypair) = common::create_wallet(&mut chain, "m@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    assert_eq!(
+        chain.generate_new_block_for("unknown"),
+        Err(ChainError::UnknownReceiver("unknown".to_string()))
+    );
+
+    let mut fork = chain.clone();
+
+    chain.generate_new_block_for(&miner).unwrap();
+
+    // The reward is credited but cannot be spent before two blocks are mined on top of it
+    assert_eq!(chain.get_wallet_balance(miner.clone()), Some(100.0));
+    assert_eq!(chain.get_spendable_balance(&miner), Some(0.0));
+    assert_eq!(
+        chain.add_transaction(miner.clone(), to.clone(), 10.0, &keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.get_spendable_balance(&miner), Some(0.0));
+
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.get_spendable_balance(&miner), Some(100.0));
+    assert!(chain
+        .add_transaction(miner.clone(), to, 10.0, &keypair)
+        .is_ok());
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // A node importing the block credits the miner too
+    let mut base = fork.clone();
+
+    fork.import_block(chain.get_all_blocks()[1].clone())
+        .unwrap();
+
+    assert_eq!(fork.get_wallet_balance(miner.clone()), Some(100.0));
+    assert_eq!(fork.check_invariants(), Ok(()));
+
+    // Switching to a branch with more work without the block takes the reward back
+    base.generate_new_block().unwrap();
+    base.generate_new_block().unwrap();
+
+    assert_eq!(fork.replace_chain(base.get_all_blocks().to_vec()), Ok(2));
+    assert_eq!(fork.get_wallet_balance(miner), Some(0.0));
+    assert_eq!(fork.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_locked_transaction() {
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            ledger: LedgerModel::Utxo,
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let (to, keypair) = common::create_wallet(&mut chain, "r@mail.com");
+    let lock = Script::All(vec![
+        Script::hash_lock("secret"),
+        Script::TimeLock { unlock_at: 1 },
+    ]);
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    assert!(matches!(
+        chain.add_locked_transaction(from.clone(), to.clone(), 10.0, Script::Any(vec![])),
+        Err(ChainError::InvalidScript(_))
+    ));
+
+    chain
+        .add_locked_transaction(from.clone(), to.clone(), 10.0, lock.clone())
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    // The locked output is not picked to pay the transactions of the receiver
+    let (locked, output) = chain.get_unspent_outputs(&to).remove(0);
+
+    assert_eq!(output.lock, Some(lock));
+    assert_eq!(chain.get_wallet_balance(to.clone()), Some(10.0));
+    assert_eq!(
+        chain.add_transaction(to.clone(), from.clone(), 1.0, &keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+
+    // The spending transaction must satisfy the script
+    let spend = |locktime: Option<i64>| {
+        let builder = Transaction::builder()
+            .from(to.clone())
+            .to(from.clone())
+            .amount(9.0)
+            .fee_rate(0.1)
+            .nonce(chain.get_nonce(&to).unwrap())
+            .inputs(vec![locked.clone()]);
+
+        match locktime {
+            Some(locktime) => builder.locktime(locktime).build(),
+            None => builder.build(),
+        }
+        .sign(&keypair)
+    };
+
+    for transaction in [
+        spend(Some(1)),
+        spend(Some(1)).reveal_preimage("guess"),
+        spend(None).reveal_preimage("secret"),
+    ] {
+        assert!(matches!(
+            chain.clone().submit_transaction(transaction),
+            Err(ChainError::InvalidScript(_))
+        ));
+    }
+
+    chain
+        .submit_transaction(spend(Some(1)).reveal_preimage("secret"))
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    assert!(chain.get_wallet_balance(to.clone()).unwrap() < 0.2);
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // Scripts require the UTXO ledger
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+
+    assert!(matches!(
+        chain.add_locked_transaction(from, to, 10.0, Script::hash_lock("secret")),
+        Err(ChainError::InvalidScript(_))
+    ));
+}
+
+#[test]
+fn test_utxo_ledger() {
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            ledger: LedgerModel::Utxo,
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.credit_wallet(&to, 5.0);
+    chain.generate_new_block().unwrap();
+
+    let mut fork = chain.clone();
+    let credit = chain.get_unspent_outputs(&from)[0].0.clone();
+
+    assert_eq!(chain.ledger().model(), LedgerModel::Utxo);
+    assert_eq!(chain.select_inputs(&from, 100.0), Ok(vec![credit.clone()]));
+    assert_eq!(
+        chain.select_inputs(&from, 101.0),
+        Err(ChainError::InsufficientBalance)
+    );
+
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
+
+    let amounts = |chain: &Chain, address: &str| -> Vec<f64> {
+        chain
+            .get_unspent_outputs(address)
+            .into_iter()
+            .map(|(_, output)| output.amount)
+            .collect()
+    };
+
+    assert_eq!(amounts(&chain, &from), vec![89.0]);
+    assert_eq!(amounts(&chain, &to).iter().sum::<f64>(), 15.0);
+
+    // The spent output cannot be spent again
+    let nonce = chain.get_nonce(&from).unwrap();
+    let transaction = |inputs| {
+        Transaction::builder()
+            .from(from.clone())
+            .to(to.clone())
+            .amount(1.0)
+            .fee_rate(0.1)
+            .nonce(nonce)
+            .inputs(inputs)
+            .build()
+            .sign(&keypair)
+    };
+    let owned_by_receiver = chain.get_unspent_outputs(&to)[0].0.clone();
+
+    assert_eq!(
+        chain.submit_transaction(transaction(vec![credit.clone()])),
+        Err(ChainError::DoubleSpend(credit.to_string()))
+    );
+    assert!(matches!(
+        chain.submit_transaction(transaction(vec![])),
+        Err(ChainError::InvalidInputs(_))
+    ));
+    assert!(matches!(
+        chain.submit_transaction(transaction(vec![owned_by_receiver])),
+        Err(ChainError::InvalidInputs(_))
+    ));
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // A node importing the block records the same outputs
+    let mut peer = fork.clone();
+
+    chain.generate_new_block().unwrap();
+    peer.import_block(chain.get_all_blocks()[2].clone())
+        .unwrap();
+
+    assert_eq!(amounts(&peer, &from), vec![89.0]);
+    assert_eq!(peer.check_invariants(), Ok(()));
+
+    // Switching to a branch without the transaction restores the spent output
+    fork.generate_new_block().unwrap();
+    fork.generate_new_block().unwrap();
+
+    assert_eq!(peer.replace_chain(fork.get_all_blocks().to_vec()), Ok(2));
+    assert_eq!(peer.get_pending_transactions().len(), 1);
+    assert_eq!(peer.get_wallet_balance(from.clone()), Some(89.0));
+    assert_eq!(peer.check_invariants(), Ok(()));
+}
+
+#[derive(Debug, Default)]
+struct Indexer {
+    log: Mutex<Vec<String>>,
+}
+
+impl ChainPlugin for Indexer {
+    fn on_transaction_applied(&self, _chain: &Chain, height: usize, transaction: &Transaction) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("transaction {} {}", height, transaction.hash));
+    }
+
+    fn on_block_applied(&self, chain: &Chain, height: usize, block: &Block) {
+        assert_eq!(chain.get_block_hash(height), Some(block.hash()));
+
+        self.log.lock().unwrap().push(format!("block {}", height));
+    }
+
+    fn on_reorg(&self, _chain: &Chain, fork_height: usize, reverted: &[Block]) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("reorg {} {}", fork_height, reverted.len()));
+    }
+}
+
+#[test]
+fn test_plugins() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    let mut fork = chain.clone();
+    let indexer = Arc::new(Indexer::default());
+    let id = chain.add_plugin(indexer.clone());
+
+    let hash = chain
+        .add_transaction(from.clone(), to, 10.0, &keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    let log = indexer.log.lock().unwrap().clone();
+
+    // The coinbase first, then the transfer and the block
+    assert_eq!(log.len(), 3);
+    assert!(log[0].starts_with("transaction 2 "));
+    assert_eq!(log[1], format!("transaction 2 {}", hash));
+    assert_eq!(log[2], "block 2");
+
+    // The fork mines more work, so the blockchain switches to it
+    fork.generate_new_block().unwrap();
+    fork.generate_new_block().unwrap();
+    indexer.log.lock().unwrap().clear();
+
+    assert_eq!(chain.replace_chain(fork.get_all_blocks().to_vec()), Ok(2));
+
+    let log = indexer.log.lock().unwrap().clone();
+
+    assert_eq!(log[0], "reorg 2 1");
+    assert_eq!(
+        log.iter()
+            .filter(|entry| entry.starts_with("block "))
+            .collect::<Vec<_>>(),
+        ["block 2", "block 3"]
+    );
+
+    assert!(chain.remove_plugin(id));
+    indexer.log.lock().unwrap().clear();
+    chain.generate_new_block().unwrap();
+
+    assert!(indexer.log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_replace_chain_invalid() {
+    let mut chain = setup();
+    let mut fork = chain.clone();
+
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    // Another genesis block
+    assert!(matches!(
+        fork.replace_chain(setup().get_all_blocks().to_vec()),
+        Err(ChainError::InvalidBlock(_))
+    ));
+
+    // A tampered block leaves the blockchain unchanged
+    let last_hash = fork.get_last_hash();
+    let mut candidate = chain.get_all_blocks().to_vec();
+    candidate[2].header.previous_hash = last_hash.to_owned();
+
+    assert!(fork.replace_chain(candidate).is_err());
+    assert_eq!(fork.get_last_hash(), last_hash);
+    assert_eq!(fork.get_all_blocks().len(), 1);
+}
+
+#[test]
+fn test_rollback_to() {
+    let mut chain = setup();
+    let miner = chain.create_wallet("m@mail.com".to_string()).unwrap();
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block_for(&miner).unwrap();
+
+    let undo = chain.get_block_undo(1).unwrap();
+
+    assert_eq!(undo.issued, 200.0);
+    assert_eq!(undo.balances[&from], 100.0);
+
+    let reward = chain.get_wallet_balance(miner.clone()).unwrap();
+    let supply = chain.overview(0).supply;
+
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block_for(&miner).unwrap();
+    chain
+        .add_transaction(to.clone(), from.clone(), 1.0, &to_keypair)
+        .unwrap();
+    chain.generate_new_block_for(&miner).unwrap();
+
+    let balances = |chain: &Chain| {
+        (
+            chain.get_wallet_balance(from.clone()).unwrap(),
+            chain.get_wallet_balance(to.clone()).unwrap(),
+        )
+    };
+    let before = balances(&chain);
+
+    // The rewards of the removed blocks are taken back, their payments are pending again
+    assert_eq!(chain.rollback_to(1), Ok(2));
+    assert_eq!(chain.get_all_blocks().len(), 2);
+    assert_eq!(chain.get_pending_transactions().len(), 2);
+    assert_eq!(chain.get_wallet_balance(miner.clone()), Some(reward));
+    assert_eq!(chain.overview(0).supply, supply);
+    assert_eq!(balances(&chain), before);
+    assert!(chain.get_block_undo(2).is_none());
+
+    // Removing the pending payments restores the balances of the first block
+    assert_eq!(chain.rollback_to(1), Ok(0));
+    assert_eq!(chain.rollback_to(0), Ok(1));
+    assert_eq!(chain.get_pending_transactions().len(), 0);
+    assert_eq!(chain.get_wallet_balance(from.clone()), Some(0.0));
+    assert_eq!(chain.get_wallet_balance(to.clone()), Some(0.0));
+    assert_eq!(chain.overview(0).supply, supply - 200.0);
+
+    assert!(matches!(
+        chain.rollback_to(1),
+        Err(ChainError::InvalidBlock(_))
+    ));
+}
+
+#[test]
+fn test_difficulty_retarget() {
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            difficulty_algorithm: DifficultyAlgorithm::Retarget {
+                target_block_time: 600,
+                retarget_interval: 2,
+            },
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let mut replica = chain.clone();
+
+    // Blocks mined much faster than the target raise the difficulty every two blocks
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.get_difficulty(), 1.0);
+
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.get_difficulty(), 1.5);
+
+    for block in chain.get_all_blocks()[1..].iter().cloned() {
+        assert_eq!(replica.import_block(block), Ok(()));
+    }
+
+    assert_eq!(replica.get_difficulty(), chain.get_difficulty());
+
+    // A block mined at another difficulty is rejected
+    let mut block = Block::new(replica.get_last_hash(), 2.0);
+    block.transactions.push(
+        Transaction::builder()
+            .from("Root")
+            .to(replica.get_address())
+            .amount(100.0)
+            .fee(0.1)
+            .build(),
+    );
+    block.count = 1;
+    block.header.merkle = Chain::get_merkle(block.transactions.clone());
+    Block::proof_of_work(&mut block.header);
+
+    assert_eq!(
+        replica.import_block(block),
+        Err(ChainError::InvalidBlock(
+            "the difficulty does not match the retarget".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_replay() {
+    let mut chain = setup();
+
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&from, 100.0);
+
+    let start = 1_600_000_000_000;
+    let transaction = |from: &str, to: &str, amount: f64| ReplayEvent::Transaction {
+        from: from.to_string(),
+        to: to.to_string(),
+        amount,
+    };
+    let events = vec![
+        TimedEvent {
+            timestamp: start,
+            event: transaction(&from, &to, 10.0),
+        },
+        TimedEvent {
+            timestamp: start + 500,
+            event: transaction(&to, &from, 1_000.0),
+        },
+        TimedEvent {
+            timestamp: start + 60_000,
+            event: ReplayEvent::MineBlock,
+        },
+    ];
+
+    // A minute of activity replayed without waiting
+    let started = Instant::now();
+    let report = chain.replay(&events, f64::INFINITY);
+
+    assert!(started.elapsed() < Duration::from_secs(30));
+    assert_eq!(report.submitted, 1);
+    assert_eq!(report.rejected, 1);
+    assert_eq!(report.blocks, 1);
+
+    // The transactions and the block keep the timestamps of the events
+    let block = chain.get_all_blocks().last().unwrap();
+
+    assert_eq!(block.header.timestamp, 1_600_000_060);
+    assert_eq!(block.transactions[0].timestamp, 1_600_000_060);
+    assert!(block
+        .transactions
+        .iter()
+        .any(|transaction| transaction.amount == 10.0 && transaction.timestamp == 1_600_000_000));
+
+    // The system clock is restored
+    assert!((chain.clock().now_millis() - SystemClock.now_millis()).abs() < 60_000);
+
+    // Half a second of activity replayed ten times faster
+    let events = vec![
+        TimedEvent {
+            timestamp: start + 120_000,
+            event: transaction(&from, &to, 1.0),
+        },
+        TimedEvent {
+            timestamp: start + 120_500,
+            event: ReplayEvent::MineBlock,
+        },
+    ];
+    let started = Instant::now();
+
+    assert_eq!(chain.replay(&events, 10.0).blocks, 1);
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_block_payout() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+    chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+    chain.generate_new_block().unwrap();
+
+    let payout = chain.get_all_blocks().last().unwrap().payout().unwrap();
+
+    // The miner receives the subsidy, the fees of the senders are burned
+    assert_eq!(payout.subsidy, 100.0);
+    assert!((payout.fees - 1.0).abs() < 1e-9);
+    assert_eq!(payout.miner_payout, payout.subsidy);
+    assert_eq!(payout.burned, payout.fees);
+}
+
+#[test]
+fn test_add_transaction_with_gas() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+    let miner = chain.create_wallet("m@mail.com".to_string()).unwrap();
+    let gas = Gas {
+        limit: 50_000,
+        price: 0.0001,
+    };
+
+    chain.credit_wallet(&from, 10.0);
+
+    // The gas limit must cover the gas used, and the payer hold the maximum fee
+    assert_eq!(
+        chain.add_transaction_with_gas(
+            from.to_owned(),
+            to.to_owned(),
+            1.0,
+            Gas {
+                limit: TRANSACTION_GAS - 1,
+                price: 0.0001,
+            },
+            &keypair,
+        ),
+        Err(ChainError::OutOfGas {
+            limit: TRANSACTION_GAS - 1
+        })
+    );
+    assert_eq!(
+        chain.add_transaction_with_gas(from.to_owned(), to.to_owned(), 6.0, gas, &keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+
+    let hash = chain
+        .add_transaction_with_gas(from.to_owned(), to.to_owned(), 1.0, gas, &keypair)
+        .unwrap();
+    let transaction = chain.get_transaction(hash).unwrap().to_owned();
+    let used = TRANSACTION_GAS + SIGNATURE_GAS;
+    let charged = used as f64 * 0.0001;
+
+    // The unused gas is refunded to the payer
+    assert_eq!(transaction.fee, gas.max_fee());
+    assert_eq!(transaction.gas_used(), used);
+    assert_eq!(transaction.charged_fee(), charged);
+    assert!((chain.get_wallet_balance(from.to_owned()).unwrap() - (9.0 - charged)).abs() < 1e-9);
+
+    // The miner earns the gas fees with the reward, and the peers accept the coinbase
+    let mut peer: Chain = serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+    chain.generate_new_block_for(&miner).unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().to_owned();
+    let payout = block.payout().unwrap();
+
+    assert_eq!(block.gas_fees(), charged);
+    assert_eq!(payout.subsidy, 100.0);
+    assert_eq!(payout.miner_payout, 100.0 + charged);
+    assert_eq!(payout.burned, 0.0);
+    assert_eq!(chain.get_wallet_balance(miner), Some(100.0 + charged));
+    assert_eq!(peer.import_block(block.to_owned()), Ok(()));
+
+    // A coinbase claiming more than the reward and the gas fees is rejected
+    let mut peer: Chain = serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+    let mut block = chain.block_template(&to).unwrap();
+
+    block.transactions[0] = Transaction::builder()
+        .from("Root")
+        .to(to)
+        .amount(100.0 + charged)
+        .fee(0.1)
+        .build();
+    block.header.merkle = Chain::get_merkle(block.transactions.clone());
+    Block::proof_of_work(&mut block.header);
+
+    assert!(matches!(
+        peer.import_block(block),
+        Err(ChainError::InvalidBlock(_))
+    ));
+}
+
+#[test]
+fn test_assets() {
+    let mut chain = setup();
+    let issuer = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let holder = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&issuer, 1.0);
+    chain.generate_new_block().unwrap();
+
+    let gold = chain.issue_asset(&issuer, "Gold", 500.0, None).unwrap();
+
+    // Transfers move the asset, the sender paying the fee in coins
+    assert_eq!(
+        chain.transfer_asset(issuer.to_owned(), holder.to_owned(), &gold, 501.0),
+        Err(ChainError::InsufficientBalance)
+    );
+    assert_eq!(
+        chain.transfer_asset(issuer.to_owned(), holder.to_owned(), "unknown", 1.0),
+        Err(ChainError::UnknownAsset("unknown".to_string()))
+    );
+
+    let hash = chain
+        .transfer_asset(issuer.to_owned(), holder.to_owned(), &gold, 200.0)
+        .unwrap();
+
+    assert_eq!(
+        chain.get_transaction(hash).unwrap().asset_id.as_deref(),
+        Some(gold.as_str())
+    );
+    assert_eq!(chain.get_asset_balance(&issuer, &gold), Some(300.0));
+    assert_eq!(chain.get_asset_balance(&holder, &gold), Some(200.0));
+    assert_eq!(chain.get_wallet_balance(issuer.to_owned()), Some(0.9));
+    assert_eq!(chain.get_wallet_balance(holder.to_owned()), Some(0.0));
+
+    // The transfer is mined and undone like the transfers of coins
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(
+        chain.get_block_undo(2).unwrap().assets[&holder][&gold],
+        200.0
+    );
+
+    chain.rollback_to(1).unwrap();
+
+    assert_eq!(chain.get_asset_balance(&holder, &gold), Some(200.0));
+    assert_eq!(chain.get_pending_transactions().len(), 2);
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // The asset balances are stored with the blockchain
+    let restored: Chain = serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+    assert_eq!(restored.get_asset(&gold).unwrap().supply, 500.0);
+    assert_eq!(restored.get_asset_balance(&holder, &gold), Some(200.0));
+
+    // The UTXO ledger model holds no assets
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            ledger: LedgerModel::Utxo,
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let issuer = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert!(matches!(
+        chain.issue_asset(&issuer, "Gold", 500.0, None),
+        Err(ChainError::InvalidAsset(_))
+    ));
+}
+
+#[test]
+fn test_asset_issuance() {
+    let mut chain = setup();
+    let (issuer, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+
+    chain.generate_new_block().unwrap();
+
+    let mut replica = chain.clone();
+
+    // The issuer of a wallet with a public key signs the issuance
+    assert_eq!(
+        chain.issue_asset(&issuer, "Gold", 500.0, None),
+        Err(ChainError::SignatureRequired)
+    );
+    assert_eq!(
+        chain.issue_asset(&issuer, "Gold", 500.0, Some(&Keypair::generate())),
+        Err(ChainError::InvalidSignature)
+    );
+
+    // No asset is issued while the intake is paused
+    chain.pause_intake("storage migration");
+
+    assert_eq!(
+        chain.issue_asset(&issuer, "Gold", 500.0, Some(&keypair)),
+        Err(ChainError::ChainPaused("storage migration".to_string()))
+    );
+
+    chain.resume_intake();
+
+    let gold = chain
+        .issue_asset(&issuer, "Gold", 500.0, Some(&keypair))
+        .unwrap();
+    let pending = chain.get_pending_transactions();
+
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].asset_name.as_deref(), Some("Gold"));
+    assert_eq!(chain.get_asset_balance(&issuer, &gold), Some(500.0));
+
+    // The issuance is mined and imported by the other nodes
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert_eq!(replica.import_block(block), Ok(()));
+    assert_eq!(replica.get_asset(&gold), chain.get_asset(&gold));
+    assert_eq!(replica.get_asset_balance(&issuer, &gold), Some(500.0));
+    assert_eq!(replica.check_invariants(), Ok(()));
+
+    // The issuance is submitted again if its block is removed
+    replica.rollback_to(1).unwrap();
+
+    assert_eq!(replica.get_pending_transactions().len(), 1);
+    assert_eq!(replica.get_asset_balance(&issuer, &gold), Some(500.0));
+    assert_eq!(replica.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_milestones() {
+    let mut chain = setup();
+
+    chain.generate_new_block().unwrap();
+    chain.set_milestone("launch", 0).unwrap();
+    chain.set_milestone("upgrade-1", 10).unwrap();
+    chain.set_milestone("halving-1", 1).unwrap();
+
+    // The milestones are sorted by height, the unreached heights have no block hash
+    let milestones = chain.milestones();
+    let names: Vec<&str> = milestones
+        .iter()
+        .map(|milestone| milestone.name.as_str())
+        .collect();
+
+    assert_eq!(names, ["launch", "halving-1", "upgrade-1"]);
+    assert_eq!(milestones[1].hash, chain.get_block_hash(1));
+    assert_eq!(milestones[2].hash, None);
+
+    // The milestones are stored with the blockchain
+    let restored: Chain = serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+    assert_eq!(
+        restored.get_milestone("halving-1"),
+        chain.get_milestone("halving-1")
+    );
+    assert_eq!(restored.get_milestone("unknown"), None);
+}
+
+#[test]
+fn test_pending_double_spend() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 15.0);
+    chain.generate_new_block().unwrap();
+
+    // The first payment commits the balance, so the second one cannot spend it again
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
+
+    assert_eq!(
+        chain.add_transaction(from.clone(), to.clone(), 10.0, &keypair),
+        Err(ChainError::InsufficientBalance)
+    );
+    assert_eq!(chain.pending_outgoing(&from), 11.0);
+    assert_eq!(chain.pending_outgoing(&to), 0.0);
+    assert!(chain.detect_conflicts().is_empty());
+}
+
+#[test]
+fn test_proof_of_authority() {
+    let validator = Keypair::generate();
+    let outsider = Keypair::generate();
+    let engine = ProofOfAuthority::new(vec![validator.address()]);
+    let mut chain = setup();
+    let mut replica = chain.clone();
+
+    chain.set_consensus(Arc::new(engine.clone().with_signer(validator.clone())));
+    replica.set_consensus(Arc::new(engine.clone()));
+
+    // The validator signs its blocks and the other nodes accept them
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+    let signature = block.header.signature.clone().unwrap();
+
+    assert_eq!(signature.public_key, validator.public_key());
+    assert_eq!(replica.import_block(block), Ok(()));
+    assert_eq!(replica.check_invariants(), Ok(()));
+
+    // A node without the keypair of a validator cannot produce blocks
+    assert_eq!(replica.generate_new_block(), Err(ChainError::NotValidator));
+
+    let mut outsider_chain = chain.clone();
+
+    outsider_chain.set_consensus(Arc::new(engine.with_signer(outsider.clone())));
+    assert_eq!(
+        outsider_chain.generate_new_block(),
+        Err(ChainError::NotValidator)
+    );
+
+    // A block signed by another key is rejected
+    let mut forged = chain.block_template(chain.get_address()).unwrap();
+
+    forged.header.signature = Some(outsider.sign_header(&forged.header));
+
+    assert!(matches!(
+        replica.import_block(forged),
+        Err(ChainError::InvalidBlock(_))
+    ));
+    assert_eq!(replica.get_last_hash(), chain.get_last_hash());
+}
+
+#[test]
+fn test_sync_blocks() {
+    let mut chain = setup();
+    let mut replica = chain.clone();
+
+    for _ in 0..10 {
+        chain.generate_new_block().unwrap();
+    }
+
+    let blocks = chain.get_all_blocks()[1..]
+        .iter()
+        .map(|block| serde_json::to_vec(block).unwrap())
+        .collect::<Vec<_>>();
+    let stats = replica
+        .sync_blocks(blocks, PipelineConfig { capacity: 2 })
+        .unwrap();
+
+    assert_eq!(stats.blocks, 10);
+    assert_eq!(stats.signatures.blocks, 10);
+    assert_eq!(stats.apply.blocks, 10);
+    assert!(stats.blocks_per_second() > 0.0);
+    assert_eq!(replica.get_last_hash(), chain.get_last_hash());
+    assert_eq!(replica.check_invariants(), Ok(()));
+
+    // The blocks are already known
+    let blocks = vec![serde_json::to_vec(&chain.get_all_blocks()[1]).unwrap()];
+
+    assert_eq!(
+        replica.sync_blocks(blocks, PipelineConfig::default()),
+        Err(ChainError::UnknownParent)
+    );
+}
+
+#[test]
+fn test_pause_intake() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+        .unwrap();
+    chain.pause_intake("storage migration");
+
+    assert_eq!(chain.intake_paused(), Some("storage migration"));
+    assert_eq!(
+        chain.add_transaction(from.clone(), to.clone(), 10.0, &keypair),
+        Err(ChainError::ChainPaused("storage migration".to_string()))
+    );
+    assert_eq!(
+        chain.add_transaction(from.clone(), "unknown".to_string(), 10.0, &keypair),
+        Err(ChainError::ChainPaused("storage migration".to_string()))
+    );
+
+    // The pending transactions are still mined, and the pause is persisted
+    chain.generate_new_block().unwrap();
+
+    assert!(chain.get_pending_transactions().is_empty());
+    assert_eq!(chain.get_wallet_balance(to.clone()), Some(10.0));
+
+    let mut restored: Chain =
+        serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+    assert_eq!(restored.intake_paused(), Some("storage migration"));
+    assert_eq!(
+        restored.resume_intake(),
+        Some("storage migration".to_string())
+    );
+    assert_eq!(restored.resume_intake(), None);
+    assert!(restored.add_transaction(from, to, 10.0, &keypair).is_ok());
+}
+
+#[test]
+fn test_staking() {
+    let config = ChainConfig {
+        staking: StakingConfig {
+            epoch_length: 3,
+            epoch_reward: 30.0,
+            commission: 0.1,
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let validator = chain.create_wallet("v@mail.com".to_string()).unwrap();
+    let (delegator, keypair) = common::create_wallet(&mut chain, "d@mail.com");
+
+    chain.credit_wallet(&validator, 100.0);
+    chain.credit_wallet(&delegator, 100.0);
+
+    let mut replica = chain.clone();
+
+    assert_eq!(
+        chain.stake(&validator, 0.0, None),
+        Err(ChainError::InvalidAmount)
+    );
+    assert_eq!(
+        chain.stake(&validator, 150.0, None),
+        Err(ChainError::InsufficientBalance)
+    );
+    assert_eq!(
+        chain.stake("unknown", 10.0, None),
+        Err(ChainError::UnknownSender("unknown".to_string()))
+    );
+    assert!(chain.delegate(&delegator, &validator).is_err());
+
+    // The owner of a wallet with a public key signs its staking operations
+    assert_eq!(
+        chain.stake(&delegator, 50.0, None),
+        Err(ChainError::SignatureRequired)
+    );
+    assert_eq!(
+        chain.stake(&delegator, 50.0, Some(&Keypair::generate())),
+        Err(ChainError::InvalidSignature)
+    );
+
+    chain.stake(&validator, 50.0, None).unwrap();
+    chain.stake(&delegator, 50.0, Some(&keypair)).unwrap();
+    chain.delegate(&delegator, &validator).unwrap();
+
+    assert_eq!(chain.get_delegation(&delegator), Some(validator.as_str()));
+    assert!(chain
+        .add_transaction(delegator.clone(), validator.clone(), 60.0, &keypair)
+        .is_err());
+
+    let validators = chain.staking_validators();
+
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators[0].address, validator);
+    assert_eq!(validators[0].own_stake, 50.0);
+    assert_eq!(validators[0].delegated_stake, 50.0);
+
+    // The rewards of the first epoch are pending until claimed
+    for _ in 0..3 {
+        chain.generate_new_block().unwrap();
+    }
+
+    assert_eq!(chain.pending_rewards(&validator), 16.5);
+    assert_eq!(chain.pending_rewards(&delegator), 13.5);
+    assert_eq!(
+        chain.claim_rewards(&delegator, None),
+        Err(ChainError::SignatureRequired)
+    );
+    assert_eq!(chain.claim_rewards(&delegator, Some(&keypair)), Ok(13.5));
+    assert_eq!(chain.get_wallet_balance(delegator.clone()), Some(63.5));
+
+    // The bonds are mined and imported by the other nodes
+    assert_eq!(
+        replica.import_block(chain.get_all_blocks()[1].clone()),
+        Ok(())
+    );
+    assert_eq!(replica.get_stake(&delegator), 50.0);
+    assert_eq!(replica.get_wallet_balance(delegator.clone()), Some(50.0));
+
+    // The claim is undone with its block, and submitted again
+    chain.generate_new_block().unwrap();
+    chain.rollback_to(3).unwrap();
+
+    assert_eq!(chain.pending_rewards(&delegator), 0.0);
+    assert_eq!(chain.get_pending_transactions().len(), 1);
+    assert_eq!(chain.check_invariants(), Ok(()));
+
+    // The stakes and rewards are persisted with the chain
+    let mut restored: Chain =
+        serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+    assert_eq!(restored.total_staked(), 100.0);
+    assert_eq!(restored.pending_rewards(&validator), 16.5);
+    assert!(restored.unstake(&validator, 60.0, None).is_err());
+    assert_eq!(restored.unstake(&validator, 50.0, None), Ok(0.0));
+    assert!(restored.staking_validators().is_empty());
+    assert_eq!(restored.check_invariants(), Ok(()));
+
+    // Staking requires the account ledger
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            ledger: LedgerModel::Utxo,
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let wallet = chain.create_wallet("u@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&wallet, 100.0);
+
+    assert!(matches!(
+        chain.stake(&wallet, 10.0, None),
+        Err(ChainError::InvalidStake(_))
+    ));
+}
+
+#[test]
+fn test_contract() {
+    // A vault paying two units to its callers, remembering the last one
+    let code = wat::parse_str(
+        r#"
+        (module
+          (import "env" "caller" (func $caller (param i32 i32) (result i32)))
+          (import "env" "storage_write" (func $write (param i32 i32 i32 i32)))
+          (import "env" "transfer" (func $transfer (param i32 i32 f64)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "last")
+          (func (export "withdraw") (local $len i32)
+            (local.set $len (call $caller (i32.const 16) (i32.const 64)))
+            (call $write (i32.const 0) (i32.const 4) (i32.const 16) (local.get $len))
+            (call $transfer (i32.const 16) (local.get $len) (f64.const 2))))
+        "#,
+    )
+    .unwrap();
+
+    for ledger in [LedgerModel::Account, LedgerModel::Utxo] {
+        let config = ChainConfig {
+            consensus: ConsensusRules {
+                ledger,
+                ..ConsensusRules::default()
+            },
+            ..ChainConfig::default()
+        };
+        let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+        let (owner, keypair) = common::create_wallet(&mut chain, "o@mail.com");
+        let caller = chain.create_wallet("c@mail.com".to_string()).unwrap();
+
+        chain.credit_wallet(&owner, 100.0);
+        chain.generate_new_block().unwrap();
+
+        let vault = chain
+            .deploy_contract(&owner, code.to_owned(), Some(&keypair))
+            .unwrap();
+
+        chain
+            .add_transaction(owner.clone(), vault.clone(), 10.0, &keypair)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+
+        let call = chain
+            .call_contract(&caller, &vault, "withdraw", &[], None)
+            .unwrap();
+
+        assert_eq!(call.transfers.len(), 1);
+        assert!(call.gas_used > 0);
+        assert_eq!(
+            chain.contract_storage(&vault, b"last"),
+            Some(caller.as_bytes().to_vec())
+        );
+
+        chain.generate_new_block().unwrap();
+
+        assert_eq!(chain.get_wallet_balance(caller.clone()), Some(2.0));
+        assert_eq!(chain.get_wallet_balance(vault.clone()), Some(7.8));
+        assert_eq!(chain.check_invariants(), Ok(()));
+
+        // The contracts are persisted with the chain
+        let mut restored: Chain =
+            serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+
+        assert_eq!(restored.get_contract(&vault), chain.get_contract(&vault));
+        assert!(restored
+            .call_contract(&caller, &vault, "withdraw", &[], None)
+            .is_ok());
+        assert!(matches!(
+            restored.submit_transaction(
+                Transaction::builder()
+                    .from(vault)
+                    .to(caller)
+                    .amount(1.0)
+                    .fee_rate(0.1)
+                    .build()
+            ),
+            Err(ChainError::InvalidContract(_))
+        ));
+    }
 }
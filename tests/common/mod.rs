@@ -1,4 +1,7 @@
-use blockchain::Chain;
+// Every test crate uses its own subset of the helpers
+#![allow(dead_code)]
+
+use blockchain::{Chain, Keypair, Node};
 
 /// Setup a new blockchain.
 ///
@@ -8,3 +11,73 @@ use blockchain::Chain;
 pub fn setup() -> Chain {
     Chain::new(1.0, 100.0, 0.1)
 }
+
+/// Create a wallet owned by a new keypair.
+///
+/// # Arguments
+///
+/// - `chain` - The blockchain to create the wallet in.
+/// - `email` - The unique user email.
+///
+/// # Returns
+///
+/// The address of the wallet and the keypair signing its transactions.
+pub fn create_wallet(chain: &mut Chain, email: &str) -> (String, Keypair) {
+    let keypair = Keypair::generate();
+    let address = chain
+        .create_wallet_with_key(email.to_string(), &keypair.public_key())
+        .unwrap();
+
+    (address, keypair)
+}
+
+/// Get the keypair of the sender wallet of the nodes, identical on every node.
+///
+/// # Returns
+///
+/// The keypair of the sender.
+pub fn sender() -> Keypair {
+    Keypair::from_secret(&"01".repeat(32)).unwrap()
+}
+
+/// Get the keypair of the receiver wallet of the nodes, identical on every node.
+///
+/// # Returns
+///
+/// The keypair of the receiver.
+pub fn receiver() -> Keypair {
+    Keypair::from_secret(&"02".repeat(32)).unwrap()
+}
+
+/// Setup a blockchain with two funded wallets, derived identically on every node.
+///
+/// # Returns
+///
+/// The blockchain, the sender address, and the receiver address.
+pub fn setup_chain() -> (Chain, String, String) {
+    let mut chain = Chain::new(1.0, 100.0, 0.1);
+
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &sender().public_key())
+        .unwrap();
+    let to = chain
+        .create_wallet_with_key("r@mail.com".to_string(), &receiver().public_key())
+        .unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.credit_wallet(&to, 100.0);
+    chain.generate_new_block().unwrap();
+
+    (chain, from, to)
+}
+
+/// Setup a node over the blockchain of `setup_chain`.
+///
+/// # Returns
+///
+/// The node, the sender address, and the receiver address.
+pub fn setup_node() -> (Node, String, String) {
+    let (chain, from, to) = setup_chain();
+
+    (Node::new(chain), from, to)
+}
@@ -0,0 +1,88 @@
+use std::{
+    env, fs,
+    time::{Duration, Instant},
+};
+
+use blockchain::{Chain, Dataset, DatasetRow, ReplayOptions};
+
+#[test]
+fn test_dataset_from_csv() {
+    let csv = "timestamp,from,to,amount,memo\n20,bob,alice,2.5,rent\n10,\"alice\",bob,5,\n\n";
+    let dataset = Dataset::from_csv(csv.as_bytes()).unwrap();
+
+    // The rows are ordered by timestamp
+    assert_eq!(
+        dataset.rows,
+        vec![
+            DatasetRow {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 5.0,
+                timestamp: 10,
+            },
+            DatasetRow {
+                from: "bob".to_string(),
+                to: "alice".to_string(),
+                amount: 2.5,
+                timestamp: 20,
+            },
+        ]
+    );
+
+    assert!(Dataset::from_csv("from,to,amount\n".as_bytes()).is_err());
+    assert!(Dataset::from_csv("from,to,amount,timestamp\na,b,x,1\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_dataset_load_json() {
+    let path = env::temp_dir().join(format!("dataset-{}.json", std::process::id()));
+
+    fs::write(
+        &path,
+        r#"[{"from": "alice", "to": "bob", "amount": 5.0, "timestamp": 10}]"#,
+    )
+    .unwrap();
+
+    let dataset = Dataset::load(&path).unwrap();
+
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(dataset.rows.len(), 1);
+    assert_eq!(dataset.rows[0].from, "alice");
+}
+
+#[test]
+fn test_replay_dataset() {
+    let mut chain = Chain::new(1.0, 100.0, 0.1);
+    let dataset = Dataset::from_csv(
+        "from,to,amount,timestamp\nalice,bob,10,0\nbob,carol,5,1\ncarol,alice,500,2\nalice,carol,1,2\n"
+            .as_bytes(),
+    )
+    .unwrap();
+
+    let started = Instant::now();
+    let report = chain.replay_dataset(
+        &dataset,
+        &ReplayOptions {
+            speed: 10.0,
+            initial_balance: 100.0,
+            block_size: 2,
+        },
+    );
+
+    // Two seconds of payments replayed ten times faster
+    assert!(started.elapsed() >= Duration::from_millis(200));
+
+    // Carol cannot pay 500
+    assert_eq!(report.wallets.len(), 3);
+    assert_eq!(report.submitted, 3);
+    assert_eq!(report.rejected, 1);
+    assert_eq!(report.blocks, 1);
+    assert_eq!(
+        chain
+            .get_wallet_by_email("alice")
+            .map(|wallet| wallet.address.to_owned()),
+        report.wallets.get("alice").cloned()
+    );
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use blockchain::{Chain, ChainConfig, Keypair, PruningPolicy};
+
+use crate::common::setup;
+
+/// Setup a new blockchain with a wallet paying another one in every block.
+///
+/// # Returns
+///
+/// The sender address, the sender keypair, and the receiver address.
+fn setup_payments(chain: &mut Chain) -> (String, Keypair, String) {
+    let (from, keypair) = common::create_wallet(chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+
+    for _ in 0..4 {
+        chain
+            .add_transaction(from.clone(), to.clone(), 10.0, &keypair)
+            .unwrap();
+        chain.generate_new_block().unwrap();
+    }
+
+    (from, keypair, to)
+}
+
+#[test]
+fn test_balance_at() {
+    let mut chain = setup();
+    let (from, _, to) = setup_payments(&mut chain);
+
+    assert_eq!(chain.balance_at(&from, 0), Some(0.0));
+    assert_eq!(chain.balance_at(&from, 1), Some(100.0));
+    assert_eq!(chain.balance_at(&from, 2), Some(89.0));
+    assert_eq!(chain.balance_at(&to, 3), Some(20.0));
+    assert_eq!(chain.balance_at(&to, 5), Some(40.0));
+    assert_eq!(chain.balance_at(&to, 6), None);
+    assert_eq!(chain.balance_at("unknown", 1), None);
+}
+
+#[test]
+fn test_balance_at_ignores_pending_transactions() {
+    let mut chain = setup();
+    let (from, keypair, to) = setup_payments(&mut chain);
+
+    chain
+        .add_transaction(from.clone(), to, 10.0, &keypair)
+        .unwrap();
+
+    assert_eq!(chain.balance_at(&from, 5), Some(56.0));
+}
+
+#[test]
+fn test_state_at() {
+    let mut chain = setup();
+    let (from, _, to) = setup_payments(&mut chain);
+
+    let state = chain.state_at(2).unwrap();
+
+    assert_eq!(state.height, 2);
+    assert_eq!(state.block_hash, chain.get_all_blocks()[2].hash());
+    assert_eq!(state.balances.get(&from), Some(&89.0));
+    assert_eq!(state.balances.get(&to), Some(&10.0));
+}
+
+#[test]
+fn test_state_at_pruned() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            pruning: PruningPolicy::KeepLast { blocks: 2 },
+            ..ChainConfig::default()
+        },
+    );
+    let (from, _, _) = setup_payments(&mut chain);
+
+    // Rewound from the current state through the kept blocks
+    assert_eq!(chain.balance_at(&from, 4), Some(67.0));
+    assert_eq!(chain.balance_at(&from, 2), None);
+}
+
+#[test]
+fn test_state_at_snapshots() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            pruning: PruningPolicy::Snapshots { interval: 2 },
+            ..ChainConfig::default()
+        },
+    );
+    let (from, _, _) = setup_payments(&mut chain);
+
+    // Replayed from the snapshot taken at the height
+    assert_eq!(chain.balance_at(&from, 4), Some(67.0));
+    assert_eq!(chain.balance_at(&from, 5), Some(56.0));
+}
@@ -0,0 +1,491 @@
+mod common;
+
+use blockchain::{
+    key_fingerprint, ChainError, HdWallet, Keypair, Memo, SecurityEventKind, Transaction,
+};
+
+use crate::common::setup;
+
+#[test]
+fn test_create_wallet_with_key() {
+    let mut chain = setup();
+    let keypair = Keypair::generate();
+
+    let address = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let wallet = chain.get_wallet(&address).unwrap();
+
+    assert_eq!(address, keypair.address());
+    assert_eq!(wallet.public_key, Some(keypair.public_key()));
+    assert_eq!(
+        chain.create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key()),
+        Ok(address)
+    );
+    assert_eq!(chain.get_wallets_count(), 1);
+}
+
+#[test]
+fn test_create_wallet_with_invalid_key() {
+    let mut chain = setup();
+
+    assert_eq!(
+        chain.create_wallet_with_key("s@mail.com".to_string(), "1234"),
+        Err(ChainError::InvalidPublicKey)
+    );
+    assert_eq!(chain.get_wallets_count(), 0);
+}
+
+#[test]
+fn test_submit_signed_transaction() {
+    let mut chain = setup();
+    let keypair = Keypair::generate();
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    // Only signed transactions spend from a wallet with a public key
+    assert_eq!(
+        chain.add_transaction(from.clone(), to.clone(), 1.0, &Keypair::generate()),
+        Err(ChainError::InvalidSignature)
+    );
+
+    let unsigned = Transaction::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .amount(10.0)
+        .fee_rate(chain.get_fee())
+        .build();
+
+    assert_eq!(
+        chain.submit_transaction(unsigned.clone()),
+        Err(ChainError::SignatureRequired)
+    );
+    assert_eq!(
+        chain.submit_transaction(unsigned.clone().sign(&Keypair::generate())),
+        Err(ChainError::InvalidSignature)
+    );
+
+    let signed = unsigned.sign(&keypair);
+
+    assert!(chain.submit_transaction(signed.clone()).is_ok());
+    assert_eq!(chain.get_wallet_balance(from.clone()), Some(9.0));
+    assert_eq!(chain.get_wallet_balance(to), Some(10.0));
+
+    // Replaying the transaction is rejected, even once mined
+    assert_eq!(
+        chain.submit_transaction(signed.clone()),
+        Err(ChainError::DuplicateTransaction)
+    );
+
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(
+        chain.submit_transaction(signed),
+        Err(ChainError::DuplicateTransaction)
+    );
+    assert_eq!(chain.get_wallet_balance(from), Some(9.0));
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_submit_transaction_idempotent() {
+    let mut chain = setup();
+    let keypair = Keypair::generate();
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let transaction = Transaction::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .amount(10.0)
+        .fee_rate(chain.get_fee())
+        .build();
+    let signed = transaction.clone().sign(&keypair);
+    let hash = chain.submit_transaction_idempotent(signed.clone()).unwrap();
+
+    // A retry returns the hash of the submitted transaction without spending twice, even once
+    // mined
+    assert_eq!(hash, signed.hash);
+    assert_eq!(
+        chain.submit_transaction_idempotent(signed.clone()),
+        Ok(hash.clone())
+    );
+
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.submit_transaction_idempotent(signed), Ok(hash));
+    assert_eq!(chain.get_wallet_balance(from.clone()), Some(9.0));
+
+    // The same transaction with another fee is not a retry
+    let mut overpaid = transaction;
+
+    overpaid.fee = 2.0;
+
+    assert_eq!(
+        chain.submit_transaction_idempotent(overpaid.sign(&keypair)),
+        Err(ChainError::DuplicateTransaction)
+    );
+    assert_eq!(chain.get_wallet_balance(from), Some(9.0));
+}
+
+#[test]
+fn test_submit_sponsored_transaction() {
+    let mut chain = setup();
+    let sender = Keypair::generate();
+    let sponsor = Keypair::generate();
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &sender.public_key())
+        .unwrap();
+    let fee_payer = chain
+        .create_wallet_with_key("p@mail.com".to_string(), &sponsor.public_key())
+        .unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 10.0);
+    chain.credit_wallet(&fee_payer, 5.0);
+
+    let transaction = Transaction::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .amount(10.0)
+        .fee_rate(chain.get_fee())
+        .fee_payer(fee_payer.clone())
+        .build()
+        .sign(&sender);
+
+    // The fee payer must agree to pay the fee
+    assert_eq!(
+        chain.submit_transaction(transaction.clone()),
+        Err(ChainError::SignatureRequired)
+    );
+    assert_eq!(
+        chain.submit_transaction(transaction.clone().sponsor(&sender)),
+        Err(ChainError::InvalidSignature)
+    );
+
+    // The sender spends its whole balance while the fee payer pays the fee
+    assert_eq!(
+        chain.submit_transaction(transaction.sponsor(&sponsor)),
+        Ok(())
+    );
+    assert_eq!(chain.get_wallet_balance(from.clone()), Some(0.0));
+    assert_eq!(chain.get_wallet_balance(fee_payer.clone()), Some(4.0));
+    assert_eq!(chain.get_wallet_balance(to.clone()), Some(10.0));
+    assert_eq!(chain.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_rotate_keys() {
+    let mut chain = setup();
+    let keypair = Keypair::generate();
+    let from = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    let mut peer = chain.clone();
+
+    assert_eq!(
+        chain.rotate_keys("unknown", None).unwrap_err(),
+        ChainError::UnknownSender("unknown".to_string())
+    );
+    assert_eq!(
+        chain.rotate_keys(&from, None).unwrap_err(),
+        ChainError::SignatureRequired
+    );
+    assert_eq!(
+        chain
+            .rotate_keys(&from, Some(&Keypair::generate()))
+            .unwrap_err(),
+        ChainError::InvalidSignature
+    );
+
+    let rotated = chain.rotate_keys(&from, Some(&keypair)).unwrap();
+    let wallet = chain.get_wallet(&from).unwrap();
+
+    assert_eq!(wallet.public_key, Some(rotated.public_key()));
+    assert_eq!(wallet.balance, 20.0);
+
+    // The previous key is revoked, and the rotation took the first nonce of the wallet
+    let fee = chain.get_fee();
+    let nonce = chain.get_nonce(&from).unwrap();
+    let transaction = |amount: f64| {
+        Transaction::builder()
+            .from(from.clone())
+            .to(to.clone())
+            .amount(amount)
+            .fee_rate(fee)
+            .nonce(nonce)
+            .build()
+    };
+
+    assert_eq!(nonce, 1);
+
+    assert_eq!(
+        chain.submit_transaction(transaction(1.0).sign(&keypair)),
+        Err(ChainError::InvalidSignature)
+    );
+    assert!(chain
+        .submit_transaction(transaction(2.0).sign(&rotated))
+        .is_ok());
+
+    // A rotation that does not start from the current key is rejected
+    assert!(matches!(
+        chain.rotate_keys(&from, Some(&keypair)),
+        Err(ChainError::InvalidSignature)
+    ));
+
+    // The rotation is recorded on-chain and applied by the peers importing the block
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert!(block
+        .transactions
+        .iter()
+        .any(|transaction| transaction.key_rotation.is_some()));
+    assert_eq!(peer.import_block(block), Ok(()));
+    assert_eq!(
+        peer.get_wallet(&from).unwrap().public_key,
+        Some(rotated.public_key())
+    );
+    assert_eq!(peer.get_wallet_balance(from), Some(17.8));
+    assert_eq!(peer.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_verify_message() {
+    let mut chain = setup();
+    let keypair = Keypair::generate();
+    let address = chain
+        .create_wallet_with_key("s@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+    let unsigned = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    let challenge = b"login 1700000000";
+    let signature = keypair.sign_message(challenge);
+
+    assert!(chain.verify_message(&address, challenge, &signature));
+    assert!(!chain.verify_message(&address, b"login 1700000001", &signature));
+    assert!(!chain.verify_message(&address, challenge, "1234"));
+    assert!(!chain.verify_message(&unsigned, challenge, &signature));
+    assert!(!chain.verify_message("unknown", challenge, &signature));
+
+    // The signatures of messages and transactions are not interchangeable
+    let transaction = Transaction::builder()
+        .from(address.clone())
+        .to(unsigned)
+        .amount(1.0)
+        .fee_rate(0.1)
+        .build();
+    let signed = transaction.clone().sign(&keypair);
+
+    assert!(!chain.verify_message(
+        &address,
+        transaction.signing_payload().as_bytes(),
+        signed.signature.as_deref().unwrap()
+    ));
+
+    let mut forged = transaction;
+    forged.signature = Some(keypair.sign_message(forged.signing_payload().as_bytes()));
+
+    assert_eq!(
+        chain.submit_transaction(forged),
+        Err(ChainError::InvalidSignature)
+    );
+
+    // A rotation revokes the signatures of the previous key
+    chain.rotate_keys(&address, Some(&keypair)).unwrap();
+
+    assert!(!chain.verify_message(&address, challenge, &signature));
+}
+
+#[test]
+fn test_encrypted_memo() {
+    let mut chain = setup();
+    let from = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    let keypair = Keypair::generate();
+    let to = chain
+        .create_wallet_with_key("r@mail.com".to_string(), &keypair.public_key())
+        .unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+
+    assert_eq!(
+        chain.encrypt_memo(&from, "invoice 42"),
+        Err(ChainError::InvalidPublicKey)
+    );
+    assert_eq!(
+        chain.encrypt_memo("unknown", "invoice 42"),
+        Err(ChainError::UnknownReceiver("unknown".to_string()))
+    );
+
+    let memo = chain.encrypt_memo(&to, "invoice 42").unwrap();
+    let transaction = Transaction::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .amount(1.0)
+        .fee_rate(0.1)
+        .memo(memo)
+        .build();
+    let hash = transaction.hash.to_owned();
+
+    // The memo is covered by the hash
+    let mut tampered = transaction.clone();
+    tampered.memo = Some(Memo::plain("invoice 43"));
+
+    assert_eq!(
+        chain.submit_transaction(tampered),
+        Err(ChainError::InvalidHash)
+    );
+
+    chain.submit_transaction(transaction).unwrap();
+    chain.generate_new_block().unwrap();
+
+    // Only the receiver reads the memo stored on-chain
+    let (_, mined) = chain.find_transaction_in_chain(&hash).unwrap();
+
+    assert!(!serde_json::to_string(mined).unwrap().contains("invoice"));
+    assert_eq!(keypair.decrypt_memo(mined), Some("invoice 42".to_string()));
+    assert_eq!(Keypair::generate().decrypt_memo(mined), None);
+}
+
+#[test]
+fn test_create_wallet_with_mnemonic() {
+    let mut chain = setup();
+    let (address, hd_wallet) = chain
+        .create_wallet_with_mnemonic("s@mail.com".to_string())
+        .unwrap();
+
+    assert_eq!(hd_wallet.mnemonic().split(' ').count(), 24);
+    assert_eq!(address, hd_wallet.derive_child(0).address());
+
+    // The mnemonic restores the keypairs, and derives the keypairs of more wallets
+    let restored = HdWallet::from_mnemonic(&hd_wallet.mnemonic(), "").unwrap();
+    let child = restored.derive_child(1);
+
+    assert_eq!(
+        restored.derive_child(0).public_key(),
+        hd_wallet.derive_child(0).public_key()
+    );
+    assert_ne!(child.public_key(), restored.derive_child(0).public_key());
+
+    // A passphrase derives other keypairs from the same mnemonic
+    assert_ne!(
+        HdWallet::from_mnemonic(&hd_wallet.mnemonic(), "passphrase")
+            .unwrap()
+            .derive_child(0)
+            .public_key(),
+        restored.derive_child(0).public_key()
+    );
+    assert_eq!(
+        chain.create_wallet_with_key("r@mail.com".to_string(), &child.public_key()),
+        Ok(child.address())
+    );
+    assert!(matches!(
+        HdWallet::from_mnemonic("not a mnemonic", ""),
+        Err(ChainError::InvalidMnemonic(_))
+    ));
+}
+
+#[test]
+fn test_wallet_security_events() {
+    let mut chain = setup();
+    let (address, hd_wallet) = chain
+        .create_wallet_with_mnemonic("s@mail.com".to_string())
+        .unwrap();
+    let mut peer = chain.clone();
+
+    // The mnemonic no longer recovers the wallet once its key is rotated
+    let rotated = chain
+        .rotate_keys(&address, Some(&hd_wallet.derive_child(0)))
+        .unwrap();
+
+    assert!(chain.recover_wallet(&address, &hd_wallet, 0).is_err());
+    assert!(chain.rotate_keys(&address, Some(&rotated)).is_ok());
+
+    let events = chain.wallet_security_events(&address).unwrap().to_vec();
+
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|event| event.kind == SecurityEventKind::KeyRotation));
+    assert_eq!(
+        events[0].initiating_key,
+        Some(hd_wallet.derive_child(0).public_key())
+    );
+    assert_eq!(
+        events[0].new_key_fingerprint,
+        key_fingerprint(&rotated.public_key())
+    );
+    assert_eq!(events[1].initiating_key, Some(rotated.public_key()));
+
+    // The peers importing the block record the same events
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert_eq!(peer.import_block(block), Ok(()));
+    assert_eq!(
+        peer.wallet_security_events(&address).map(<[_]>::len),
+        Some(2)
+    );
+
+    // A rollback submitting the rotations again does not record them twice
+    chain.rollback_to(0).unwrap();
+
+    assert_eq!(chain.wallet_security_events(&address).unwrap(), events);
+    assert_eq!(chain.get_security_audit_log(), events);
+}
+
+#[test]
+fn test_recover_wallet() {
+    let mut chain = setup();
+    let (address, hd_wallet) = chain
+        .create_wallet_with_mnemonic("s@mail.com".to_string())
+        .unwrap();
+    let restored = HdWallet::from_mnemonic(&hd_wallet.mnemonic(), "").unwrap();
+    let mut peer = chain.clone();
+
+    let keypair = chain.recover_wallet(&address, &restored, 0).unwrap();
+
+    assert_eq!(
+        chain.get_wallet(&address).unwrap().public_key,
+        Some(hd_wallet.derive_child(1).public_key())
+    );
+
+    // The recovered key is revoked, and the recovery is recorded on-chain
+    assert!(matches!(
+        chain.rotate_keys(&address, Some(&restored.derive_child(0))),
+        Err(ChainError::InvalidSignature)
+    ));
+
+    chain.generate_new_block().unwrap();
+
+    let block = chain.get_all_blocks().last().unwrap().clone();
+
+    assert!(block.transactions.iter().any(|transaction| transaction
+        .key_rotation
+        .as_ref()
+        .is_some_and(|rotation| rotation.recovery)));
+    assert_eq!(peer.import_block(block), Ok(()));
+
+    let events = peer.wallet_security_events(&address).unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, SecurityEventKind::MnemonicRecovery);
+    assert_eq!(
+        events[0].new_key_fingerprint,
+        key_fingerprint(&keypair.public_key())
+    );
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use blockchain::{
+    Block, Chain, ChainConfig, ChainError, ConsensusRules, DifficultyAlgorithm, LightBootstrap,
+    LightChain,
+};
+
+use crate::common::setup;
+
+#[test]
+fn test_light_client_sync() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    let mut light = chain.light_client().unwrap();
+
+    assert_eq!(light.add_headers(chain.get_headers(1, 10)), Ok(2));
+    assert_eq!(light.height(), 2);
+    assert_eq!(light.get_last_hash(), chain.get_last_hash());
+    assert_eq!(light.cumulative_work(), chain.cumulative_work());
+    assert_eq!(light.confirmations(1), 2);
+    assert_eq!(light.confirmations(3), 0);
+
+    // Headers already applied no longer extend the last header
+    assert_eq!(
+        light.add_header(chain.get_headers(1, 1).remove(0)),
+        Err(ChainError::UnknownParent)
+    );
+
+    let block = chain.get_block(1).unwrap();
+    let hash = block.transactions[1].hash.clone();
+    let proof = block.merkle_proof(&hash).unwrap();
+
+    assert!(light.verify_transaction(1, &hash, &proof));
+    assert!(!light.verify_transaction(2, &hash, &proof));
+    assert!(!light.verify_transaction(5, &hash, &proof));
+}
+
+#[test]
+fn test_light_client_rules() {
+    let chain = setup();
+    let genesis = chain.get_headers(0, 1).remove(0);
+    let rules = ConsensusRules {
+        max_block_weight: Some(1_000),
+        ..ConsensusRules::default()
+    };
+
+    assert!(LightChain::new(genesis.clone(), ConsensusRules::default()).is_ok());
+    assert!(matches!(
+        LightChain::new(genesis, rules),
+        Err(ChainError::InvalidBlock(_))
+    ));
+    assert!(Chain::new(100.0, 100.0, 0.1).light_client().is_none());
+}
+
+#[test]
+fn test_light_client_retarget() {
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            difficulty_algorithm: DifficultyAlgorithm::Retarget {
+                target_block_time: 600,
+                retarget_interval: 2,
+            },
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let mut light = chain.light_client().unwrap();
+
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(light.add_headers(chain.get_headers(1, 2)), Ok(2));
+
+    // A header mined at another difficulty is rejected
+    let mut block = Block::new(light.get_last_hash(), 1.0);
+    Block::proof_of_work(&mut block.header);
+
+    assert_eq!(
+        light.add_header(block.header),
+        Err(ChainError::InvalidBlock(
+            "the difficulty does not match the retarget".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_replace_headers() {
+    let mut chain = setup();
+    let mut fork = chain.clone();
+    let (from, keypair) = common::create_wallet(&mut fork, "s@mail.com");
+    let to = fork.create_wallet("r@mail.com".to_string()).unwrap();
+
+    fork.credit_wallet(&from, 20.0);
+    fork.add_transaction(from, to, 10.0, &keypair).unwrap();
+
+    chain.generate_new_block().unwrap();
+    fork.generate_new_block().unwrap();
+    fork.generate_new_block().unwrap();
+
+    let mut light = chain.light_client().unwrap();
+    light.add_headers(chain.get_headers(1, 10)).unwrap();
+
+    assert_eq!(
+        light.replace_headers(chain.get_headers(0, 10)),
+        Err(ChainError::InsufficientWork)
+    );
+    assert_eq!(
+        light.replace_headers(setup().get_headers(0, 10)),
+        Err(ChainError::UnknownParent)
+    );
+    assert_eq!(light.replace_headers(fork.get_headers(0, 10)), Ok(1));
+    assert_eq!(light.get_last_hash(), fork.get_last_hash());
+}
+
+#[test]
+fn test_light_bootstrap() {
+    let config = ChainConfig {
+        consensus: ConsensusRules {
+            difficulty_algorithm: DifficultyAlgorithm::Retarget {
+                target_block_time: 600,
+                retarget_interval: 4,
+            },
+            ..ConsensusRules::default()
+        },
+        ..ChainConfig::default()
+    };
+    let mut chain = Chain::with_config(1.0, 100.0, 0.1, config);
+    let wallet = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&wallet, 20.0);
+
+    for _ in 0..10 {
+        chain.generate_new_block().unwrap();
+    }
+
+    // The bundle holds the retarget window and commits to the earlier headers
+    let bundle = chain.light_bootstrap().unwrap();
+    let bundle: LightBootstrap =
+        serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+    assert_eq!(bundle.headers.len(), 5);
+    assert_eq!(bundle.history.leaves(), 6);
+
+    let mut light = LightChain::from_bootstrap(bundle.clone()).unwrap();
+
+    assert_eq!(light.first_height(), 6);
+    assert_eq!(light.height(), 10);
+    assert_eq!(light.get_last_hash(), chain.get_last_hash());
+    assert!(light.get_header(5).is_none());
+    assert_eq!(light.get_snapshot().unwrap().height, 10);
+    assert_eq!(light.get_snapshot().unwrap().balances[&wallet], 20.0);
+
+    // The headers before the bundle are proven against the history
+    let old = chain.get_block(3).unwrap().hash();
+    let proof = chain.header_proof(3, light.first_height()).unwrap();
+
+    assert!(light.verify_header(3, &old, &proof));
+    assert!(!light.verify_header(4, &old, &proof));
+    assert!(light.verify_header(8, &chain.get_block(8).unwrap().hash(), &proof));
+
+    // The light client follows the next headers, through the next retarget
+    for _ in 0..3 {
+        chain.generate_new_block().unwrap();
+    }
+
+    assert_eq!(light.add_headers(chain.get_headers(11, 10)), Ok(3));
+    assert_eq!(light.confirmations(10), 4);
+
+    // Inconsistent bundles are rejected
+    let mut forged = bundle.clone();
+    forged.headers.remove(2);
+
+    assert!(LightChain::from_bootstrap(forged).is_err());
+
+    let mut forged = bundle.clone();
+    forged.parent_proof = forged.genesis_proof.clone();
+
+    assert!(LightChain::from_bootstrap(forged).is_err());
+
+    let mut forged = bundle;
+    forged.snapshot.as_mut().unwrap().block_hash = old;
+
+    assert!(matches!(
+        LightChain::from_bootstrap(forged),
+        Err(ChainError::InvalidBlock(_))
+    ));
+}
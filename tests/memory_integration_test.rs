@@ -0,0 +1,173 @@
+use std::mem;
+
+use blockchain::{Chain, ChainConfig, ChainError, Keypair, MemoryLimits, Transaction};
+
+/// The largest approximate size of a signed transaction between two wallets.
+fn transaction_size() -> usize {
+    mem::size_of::<Transaction>() + 64 + 2 * 42 + 128
+}
+
+/// Setup a new blockchain with memory caps and three funded wallets.
+fn setup(memory: MemoryLimits) -> (Chain, Vec<String>, Vec<Keypair>) {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            memory,
+            ..ChainConfig::default()
+        },
+    );
+
+    let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::generate()).collect();
+    let addresses: Vec<String> = ["a@mail.com", "b@mail.com", "c@mail.com"]
+        .iter()
+        .zip(&keypairs)
+        .map(|(email, keypair)| {
+            chain
+                .create_wallet_with_key(email.to_string(), &keypair.public_key())
+                .unwrap()
+        })
+        .collect();
+
+    for address in &addresses {
+        chain.credit_wallet(address, 100.0);
+    }
+
+    chain.generate_new_block().unwrap();
+
+    (chain, addresses, keypairs)
+}
+
+#[test]
+fn test_memory_usage() {
+    let (mut chain, addresses, keypairs) = setup(MemoryLimits::default());
+
+    assert_eq!(chain.memory_usage().mempool_bytes, 0);
+
+    chain
+        .add_transaction(
+            addresses[0].to_owned(),
+            addresses[1].to_owned(),
+            10.0,
+            &keypairs[0],
+        )
+        .unwrap();
+
+    let stats = chain.storage_stats();
+
+    assert!(stats.memory.mempool_bytes > 0);
+    assert!(stats.memory.index_bytes > 0);
+    assert!(stats.memory.transaction_index_bytes > 0);
+    assert!(stats.memory.wallet_bytes > 0);
+    assert!(stats.memory.block_bytes > 0);
+    assert_eq!(stats.memory.evicted_transactions, 0);
+}
+
+#[test]
+fn test_mempool_cap_evicts_lower_fee() {
+    let (mut chain, addresses, keypairs) = setup(MemoryLimits {
+        mempool_bytes: Some(2 * transaction_size()),
+        index_bytes: None,
+    });
+
+    // Only the last pending transaction of a sender can be evicted, so its nonces stay
+    // contiguous
+    assert!(chain
+        .add_transaction(
+            addresses[0].to_owned(),
+            addresses[1].to_owned(),
+            2.0,
+            &keypairs[0]
+        )
+        .is_ok());
+    assert!(chain
+        .add_transaction(
+            addresses[0].to_owned(),
+            addresses[1].to_owned(),
+            1.0,
+            &keypairs[0]
+        )
+        .is_ok());
+    assert!(chain
+        .add_transaction(
+            addresses[2].to_owned(),
+            addresses[0].to_owned(),
+            5.0,
+            &keypairs[2]
+        )
+        .is_ok());
+
+    let pending = chain.get_pending_transactions();
+
+    assert_eq!(pending.len(), 2);
+    assert!(pending.iter().all(|transaction| transaction.amount != 1.0));
+    assert_eq!(
+        chain.get_wallet_balance(addresses[1].to_owned()),
+        Some(102.0)
+    );
+    assert_eq!(chain.get_nonce(&addresses[0]), Some(1));
+    assert_eq!(chain.memory_usage().evicted_transactions, 1);
+    assert!(chain.check_invariants().is_ok());
+}
+
+#[test]
+fn test_mempool_cap_rejects_lower_fee() {
+    let (mut chain, addresses, keypairs) = setup(MemoryLimits {
+        mempool_bytes: Some(transaction_size()),
+        index_bytes: None,
+    });
+
+    assert!(chain
+        .add_transaction(
+            addresses[0].to_owned(),
+            addresses[1].to_owned(),
+            2.0,
+            &keypairs[0]
+        )
+        .is_ok());
+    assert!(matches!(
+        chain.add_transaction(
+            addresses[2].to_owned(),
+            addresses[1].to_owned(),
+            1.0,
+            &keypairs[2]
+        ),
+        Err(ChainError::MempoolFull { .. })
+    ));
+    assert_eq!(chain.get_pending_transactions().len(), 1);
+    assert_eq!(
+        chain.get_wallet_balance(addresses[2].to_owned()),
+        Some(100.0)
+    );
+}
+
+#[test]
+fn test_index_cap_drops_mined_entries() {
+    let (mut chain, addresses, keypairs) = setup(MemoryLimits {
+        mempool_bytes: None,
+        index_bytes: Some(0),
+    });
+
+    chain
+        .add_transaction(
+            addresses[0].to_owned(),
+            addresses[1].to_owned(),
+            1.0,
+            &keypairs[0],
+        )
+        .unwrap();
+
+    let history = chain.get_wallet(&addresses[0]).unwrap().transactions.len();
+
+    assert_eq!(history, 1);
+    assert_eq!(chain.memory_usage().evicted_index_entries, 3);
+
+    chain.generate_new_block().unwrap();
+
+    let usage = chain.memory_usage();
+
+    assert_eq!(usage.index_bytes, 0);
+    assert_eq!(usage.evicted_index_entries, 5);
+    assert!(chain.check_invariants().is_ok());
+}
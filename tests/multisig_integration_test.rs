@@ -0,0 +1,139 @@
+mod common;
+
+use blockchain::{ChainError, Keypair};
+
+use crate::common::setup;
+
+#[test]
+fn test_multisig_transaction() {
+    let mut chain = setup();
+    let owners = [
+        Keypair::generate(),
+        Keypair::generate(),
+        Keypair::generate(),
+    ];
+    let wallet = chain
+        .create_multisig_wallet(
+            "treasury@mail.com".to_string(),
+            owners.iter().map(Keypair::public_key).collect(),
+            2,
+        )
+        .unwrap();
+    let receiver = chain
+        .create_wallet("receiver@mail.com".to_string())
+        .unwrap();
+
+    assert!(chain.credit_wallet(&wallet, 100.0));
+    chain.generate_new_block().unwrap();
+
+    // A single owner cannot spend the funds
+    assert_eq!(
+        chain.add_transaction(wallet.to_owned(), receiver.to_owned(), 10.0, &owners[0]),
+        Err(ChainError::InsufficientApprovals {
+            required: 2,
+            actual: 0
+        })
+    );
+
+    let hash = chain
+        .propose_transaction(&wallet, receiver.to_owned(), 10.0, &owners[0])
+        .unwrap();
+
+    assert!(chain.get_proposal(&wallet).is_some());
+    assert!(matches!(
+        chain.propose_transaction(&wallet, receiver.to_owned(), 5.0, &owners[1]),
+        Err(ChainError::InvalidMultiSig(_))
+    ));
+    assert_eq!(
+        chain.approve_transaction(&hash, &Keypair::generate()),
+        Err(ChainError::InvalidSignature)
+    );
+    assert_eq!(chain.approve_transaction(&hash, &owners[0]), Ok(false));
+    assert_eq!(chain.get_wallet_balance(receiver.to_owned()), Some(0.0));
+
+    // The second owner completes the threshold
+    assert_eq!(chain.approve_transaction(&hash, &owners[2]), Ok(true));
+    assert!(chain.get_proposal(&wallet).is_none());
+    assert_eq!(chain.get_wallet_balance(receiver.to_owned()), Some(10.0));
+    assert_eq!(
+        chain.approve_transaction(&hash, &owners[1]),
+        Err(ChainError::UnknownProposal(hash.to_owned()))
+    );
+
+    let transaction = chain.get_transaction(hash).unwrap();
+
+    assert_eq!(transaction.approvals.len(), 2);
+    assert!(chain
+        .get_multisig_wallet(&wallet)
+        .unwrap()
+        .check_approvals(transaction)
+        .is_ok());
+}
+
+#[test]
+fn test_cancel_proposal() {
+    let mut chain = setup();
+    let owners = [Keypair::generate(), Keypair::generate()];
+    let wallet = chain
+        .create_multisig_wallet(
+            "treasury@mail.com".to_string(),
+            owners.iter().map(Keypair::public_key).collect(),
+            2,
+        )
+        .unwrap();
+    let receiver = chain
+        .create_wallet("receiver@mail.com".to_string())
+        .unwrap();
+
+    assert!(chain.credit_wallet(&wallet, 100.0));
+
+    let hash = chain
+        .propose_transaction(&wallet, receiver, 10.0, &owners[0])
+        .unwrap();
+
+    assert_eq!(
+        chain.cancel_proposal(&hash, &Keypair::generate()).err(),
+        Some(ChainError::InvalidSignature)
+    );
+    assert_eq!(chain.cancel_proposal(&hash, &owners[1]).unwrap().hash, hash);
+    assert!(chain.get_proposal(&wallet).is_none());
+}
+
+#[test]
+fn test_create_multisig_wallet_invalid() {
+    let mut chain = setup();
+    let owners = vec![
+        Keypair::generate().public_key(),
+        Keypair::generate().public_key(),
+    ];
+
+    assert!(matches!(
+        chain.create_multisig_wallet("a@mail.com".to_string(), owners.to_owned(), 3),
+        Err(ChainError::InvalidMultiSig(_))
+    ));
+    assert!(matches!(
+        chain.create_multisig_wallet(
+            "a@mail.com".to_string(),
+            vec![owners[0].to_owned(), owners[0].to_owned()],
+            1
+        ),
+        Err(ChainError::InvalidMultiSig(_))
+    ));
+    assert_eq!(
+        chain.create_multisig_wallet("a@mail.com".to_string(), vec!["key".to_string()], 1),
+        Err(ChainError::InvalidPublicKey)
+    );
+
+    let address = chain
+        .create_multisig_wallet("a@mail.com".to_string(), owners.to_owned(), 2)
+        .unwrap();
+
+    assert_eq!(
+        chain.create_multisig_wallet(
+            "b@mail.com".to_string(),
+            owners.into_iter().rev().collect(),
+            2
+        ),
+        Ok(address)
+    );
+}
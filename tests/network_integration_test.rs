@@ -0,0 +1,136 @@
+#![cfg(feature = "net")]
+
+mod common;
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use blockchain::{Network, Node, Transaction};
+
+use crate::common::{sender, setup_chain};
+
+/// Wait until a condition holds, as the relayed messages are handled in the background.
+///
+/// # Arguments
+///
+/// - `condition` - The condition.
+///
+/// # Returns
+///
+/// `true` if the condition holds before the timeout.
+fn eventually(condition: impl Fn() -> bool) -> bool {
+    let started = Instant::now();
+
+    while started.elapsed() < Duration::from_secs(10) {
+        if condition() {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    false
+}
+
+#[test]
+fn test_gossip_over_tcp() {
+    let (chain, from, to) = setup_chain();
+    let listening = Network::new(Node::new(chain.clone()));
+    let mut dialing = Network::new(Node::new(chain.clone()));
+    let mut behind_nat = Network::new(Node::new(chain));
+
+    let address = listening.listen("127.0.0.1:0").unwrap().to_string();
+
+    dialing.connect(&address).unwrap();
+
+    assert_eq!(
+        dialing.peers(),
+        vec![listening.node().identity().public_key()]
+    );
+    assert_eq!(listening.node().peer_info().len(), 1);
+
+    // Transactions and blocks are pushed to the listening node
+    let transaction = Transaction::builder()
+        .from(from.clone())
+        .to(to)
+        .amount(10.0)
+        .fee(1.0)
+        .build()
+        .sign(&sender());
+
+    dialing.broadcast_transaction(transaction).unwrap();
+
+    assert_eq!(listening.node().chain().get_pending_transactions().len(), 1);
+
+    dialing.mine_block().unwrap();
+
+    let last_hash = dialing.node().chain().get_last_hash();
+
+    assert_eq!(listening.node().chain().get_last_hash(), last_hash);
+    assert_eq!(
+        listening.node().chain().get_wallet_balance(from.clone()),
+        Some(89.0)
+    );
+
+    // A node that never listens catches up through its own connection
+    behind_nat.connect(&address).unwrap();
+
+    assert_eq!(behind_nat.node().chain().get_last_hash(), last_hash);
+
+    // The block is relayed by the listening node to the node behind the NAT
+    dialing.mine_block().unwrap();
+
+    let last_hash = dialing.node().chain().get_last_hash();
+
+    assert!(eventually(
+        || behind_nat.node().chain().get_last_hash() == last_hash
+    ));
+    assert_eq!(behind_nat.sync(), 0);
+}
+
+#[test]
+fn test_relay_over_line_topology() {
+    let (chain, from, to) = setup_chain();
+    let mut first = Network::new(Node::new(chain.clone()));
+    let mut middle = Network::new(Node::new(chain.clone()));
+    let mut last = Network::new(Node::new(chain));
+
+    // The middle node connects to the first one, and the last node to the middle one
+    let address = first.listen("127.0.0.1:0").unwrap().to_string();
+
+    middle.connect(&address).unwrap();
+
+    let address = middle.listen("127.0.0.1:0").unwrap().to_string();
+
+    last.connect(&address).unwrap();
+
+    // The transaction of the last node reaches the first node through the middle node
+    let transaction = Transaction::builder()
+        .from(from)
+        .to(to)
+        .amount(10.0)
+        .fee(1.0)
+        .build()
+        .sign(&sender());
+
+    last.broadcast_transaction(transaction).unwrap();
+
+    assert!(eventually(|| {
+        first.node().chain().get_pending_transactions().len() == 1
+    }));
+
+    // The block of the first node reaches the last node, to which it is not connected
+    first.mine_block().unwrap();
+
+    let last_hash = first.node().chain().get_last_hash();
+
+    assert!(eventually(
+        || middle.node().chain().get_last_hash() == last_hash
+    ));
+    assert!(eventually(
+        || last.node().chain().get_last_hash() == last_hash
+    ));
+    assert!(last.node().chain().get_pending_transactions().is_empty());
+}
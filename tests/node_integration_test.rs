@@ -0,0 +1,464 @@
+mod common;
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::atomic::AtomicBool,
+};
+
+use blockchain::{
+    load_known_peers, Block, BootstrapConfig, Chain, ChainConfig, ChainError, ConsensusRules,
+    Dialer, MempoolMessage, Node, Peer, RelayPolicy, Transaction, AGENT,
+};
+
+use crate::common::{receiver, sender, setup_node};
+
+/// A peer that never answers.
+struct Unreachable;
+
+impl Peer for Unreachable {
+    fn send(&mut self, _message: MempoolMessage) -> Option<MempoolMessage> {
+        None
+    }
+}
+
+/// An in-process connection to a shared node.
+struct Shared(Rc<RefCell<Node>>);
+
+impl Peer for Shared {
+    fn send(&mut self, message: MempoolMessage) -> Option<MempoolMessage> {
+        self.0.borrow_mut().handle(message)
+    }
+}
+
+/// A dialer reaching in-process nodes after a number of failed attempts.
+struct FlakyDialer {
+    /// The reachable nodes by address, with the amount of attempts failing before connecting.
+    nodes: HashMap<String, (Rc<RefCell<Node>>, usize)>,
+}
+
+impl Dialer for FlakyDialer {
+    type Peer = Shared;
+
+    fn dial(&mut self, address: &str) -> Option<Shared> {
+        let (node, failures) = self.nodes.get_mut(address)?;
+
+        if *failures > 0 {
+            *failures -= 1;
+            return None;
+        }
+
+        Some(Shared(node.clone()))
+    }
+}
+
+#[test]
+fn test_sync_mempool() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    assert!(a
+        .chain_mut()
+        .add_transaction(from.clone(), to.clone(), 10.0, &sender())
+        .is_ok());
+    assert!(b
+        .chain_mut()
+        .add_transaction(to.clone(), from.clone(), 5.0, &receiver())
+        .is_ok());
+
+    assert_eq!(b.sync_mempool(&mut a), 1);
+
+    for node in [&a, &b] {
+        let pending = node.chain().get_pending_transactions();
+
+        assert_eq!(pending.len(), 2);
+        assert_eq!(node.chain().get_wallet_balance(from.clone()), Some(94.0));
+        assert_eq!(node.chain().get_wallet_balance(to.clone()), Some(104.5));
+        assert_eq!(node.chain().check_invariants(), Ok(()));
+    }
+
+    // Synchronizing again is a no-op
+    assert_eq!(b.sync_mempool(&mut a), 0);
+    assert_eq!(a.chain().get_pending_transactions().len(), 2);
+}
+
+#[test]
+fn test_peer_info() {
+    let (mut a, _, _) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    assert!(a.peer_info().is_empty());
+    assert_eq!(b.sync_mempool(&mut a), 0);
+
+    let info = b.peer_info();
+
+    assert_eq!(info.len(), 1);
+    assert_eq!(info[0].id, a.identity().public_key());
+    assert_eq!(info[0].agent, AGENT);
+    assert_eq!(info[0].height, 1);
+    assert!(info[0].latency.is_some());
+
+    let info = a.peer_info();
+
+    assert_eq!(info.len(), 1);
+    assert_eq!(info[0].id, b.identity().public_key());
+    assert_eq!(info[0].latency, None);
+}
+
+#[test]
+fn test_consensus_rules_mismatch() {
+    let (mut a, from, to) = setup_node();
+    let mut b = Node::new(Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            consensus: ConsensusRules {
+                coinbase_maturity: 100,
+                ..ConsensusRules::default()
+            },
+            ..ChainConfig::default()
+        },
+    ));
+
+    assert!(a
+        .chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .is_ok());
+
+    // Both nodes refuse a peer following other rules
+    assert_eq!(b.sync_mempool(&mut a), 0);
+    assert_eq!(a.sync_mempool(&mut b), 0);
+    assert!(a.peer_info().is_empty());
+    assert!(b.peer_info().is_empty());
+}
+
+#[test]
+fn test_allowed_peers() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    assert!(a
+        .chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .is_ok());
+
+    // The peer refuses unknown nodes
+    a.set_allowed_peers(Some(HashSet::new()));
+
+    assert_eq!(b.sync_mempool(&mut a), 0);
+    assert!(a.peer_info().is_empty());
+
+    // The node skips unknown peers
+    a.set_allowed_peers(None);
+    b.set_allowed_peers(Some(HashSet::from(["other".to_string()])));
+
+    assert_eq!(b.sync_mempool(&mut a), 0);
+
+    b.set_allowed_peers(Some(HashSet::from([a.identity().public_key()])));
+
+    assert_eq!(b.sync_mempool(&mut a), 1);
+}
+
+#[test]
+fn test_gossip_suppresses_duplicates() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    a.chain_mut()
+        .add_transaction(from.clone(), to.clone(), 10.0, &sender())
+        .unwrap();
+
+    // The peer refuses to relay the transaction, so it never announces it back
+    b.set_relay_policy(RelayPolicy {
+        banned: HashSet::from([from]),
+        ..RelayPolicy::default()
+    });
+
+    assert_eq!(a.sync_mempool(&mut b), 0);
+    assert_eq!(a.sync_mempool(&mut b), 0);
+    assert_eq!(a.gossip_stats().sends_suppressed, 1);
+
+    // A transaction received twice is processed once
+    let (mut c, _, _) = setup_node();
+    let transactions = a.chain().get_pending_transactions().to_vec();
+
+    c.handle(MempoolMessage::Transactions {
+        transactions: transactions.clone(),
+    });
+    c.handle(MempoolMessage::Transactions { transactions });
+
+    assert_eq!(c.chain().get_pending_transactions().len(), 1);
+    assert_eq!(c.gossip_stats().duplicates_received, 1);
+    assert_eq!(c.chain().get_wallet_balance(to), Some(110.0));
+
+    // A transaction already processed is not requested again
+    assert_eq!(c.sync_mempool(&mut a), 0);
+    assert_eq!(c.gossip_stats().requests_suppressed, 1);
+}
+
+#[test]
+fn test_broadcast_and_mine_block() {
+    let (mut a, from, to) = setup_node();
+    let b = Rc::new(RefCell::new(Node::new(a.chain().clone())));
+    let c = Rc::new(RefCell::new(Node::new(a.chain().clone())));
+    let mut d = Node::new(a.chain().clone());
+
+    let transaction = Transaction::builder()
+        .from(from)
+        .to(to)
+        .amount(10.0)
+        .fee(1.0)
+        .build()
+        .sign(&sender());
+
+    assert!(a
+        .broadcast_transaction(&mut [Shared(b.clone())], transaction)
+        .is_ok());
+    assert_eq!(b.borrow().chain().get_pending_transactions().len(), 1);
+
+    a.mine_block(&mut [Shared(b.clone())]).unwrap();
+
+    assert_eq!(
+        b.borrow().chain().get_last_hash(),
+        a.chain().get_last_hash()
+    );
+    assert!(b.borrow().chain().get_pending_transactions().is_empty());
+
+    // The peer missing the previous block requests it
+    a.mine_block(&mut [Shared(b.clone()), Shared(c.clone())])
+        .unwrap();
+
+    for node in [&b, &c] {
+        assert_eq!(node.borrow().chain().get_all_blocks().len(), 4);
+        assert_eq!(
+            node.borrow().chain().get_last_hash(),
+            a.chain().get_last_hash()
+        );
+        assert_eq!(node.borrow().chain().check_invariants(), Ok(()));
+    }
+
+    // A node catching up fetches every missing block
+    assert_eq!(d.sync_blocks(&mut a), 2);
+    assert_eq!(d.sync_blocks(&mut a), 0);
+    assert_eq!(d.chain().get_last_hash(), a.chain().get_last_hash());
+}
+
+#[test]
+fn test_submit_block_template() {
+    let (mut a, from, to) = setup_node();
+    let b = Rc::new(RefCell::new(Node::new(a.chain().clone())));
+    let cancel = AtomicBool::new(false);
+
+    a.chain_mut()
+        .add_transaction(from.clone(), to, 10.0, &sender())
+        .unwrap();
+
+    let mut block = a.chain().block_template(&from).unwrap();
+    let stale = block.clone();
+
+    assert_eq!(block.transactions.len(), 2);
+    assert_eq!(a.chain().get_pending_transactions().len(), 1);
+    assert!(Block::proof_of_work_cancellable(&mut block.header, &cancel));
+
+    a.submit_block(&mut [Shared(b.clone())], block).unwrap();
+
+    assert!(a.chain().get_pending_transactions().is_empty());
+    assert_eq!(
+        b.borrow().chain().get_last_hash(),
+        a.chain().get_last_hash()
+    );
+    assert_eq!(b.borrow().chain().check_invariants(), Ok(()));
+
+    // A template built before the last block no longer extends the blockchain
+    assert_eq!(
+        a.submit_block(&mut [Shared(b.clone())], stale),
+        Err(ChainError::UnknownParent)
+    );
+}
+
+#[test]
+fn test_sync_blocks_resolves_fork() {
+    let (mut a, from, to) = setup_node();
+    let mut b = Node::new(a.chain().clone());
+
+    a.chain_mut().generate_new_block().unwrap();
+    a.chain_mut().generate_new_block().unwrap();
+    b.chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .unwrap();
+    b.chain_mut().generate_new_block().unwrap();
+
+    // The node on the branch with less work switches to the other branch
+    assert_eq!(a.sync_blocks(&mut b), 0);
+    assert_eq!(b.sync_blocks(&mut a), 2);
+    assert_eq!(b.chain().get_last_hash(), a.chain().get_last_hash());
+    assert_eq!(b.chain().get_pending_transactions().len(), 1);
+    assert_eq!(b.chain().check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_sync_mempool_skips_mined() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    assert!(a
+        .chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .is_ok());
+    assert_eq!(b.sync_mempool(&mut a), 1);
+
+    b.chain_mut().generate_new_block().unwrap();
+
+    assert_eq!(b.sync_mempool(&mut a), 0);
+    assert!(b.chain().get_pending_transactions().is_empty());
+}
+
+#[test]
+fn test_sync_mempool_unreachable() {
+    let (mut a, from, to) = setup_node();
+
+    assert!(a
+        .chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .is_ok());
+    assert_eq!(a.sync_mempool(&mut Unreachable), 0);
+}
+
+#[test]
+fn test_mempool_message_serialization() {
+    let message = MempoolMessage::GetTransactions {
+        hashes: vec!["hash".to_string()],
+    };
+
+    assert_eq!(
+        serde_json::to_string(&message).unwrap(),
+        r#"{"type":"get_transactions","hashes":["hash"]}"#
+    );
+}
+
+#[test]
+fn test_relay_policy_fee_floor() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    a.set_relay_policy(RelayPolicy {
+        min_fee: 0.5,
+        ..RelayPolicy::default()
+    });
+
+    // Valid for the blockchain but below the fee floor of the relay policy
+    assert!(a
+        .chain_mut()
+        .add_transaction(to.clone(), from.clone(), 1.0, &receiver())
+        .is_ok());
+    assert!(a
+        .chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .is_ok());
+
+    assert_eq!(b.sync_mempool(&mut a), 1);
+    assert_eq!(b.chain().get_pending_transactions()[0].fee, 1.0);
+}
+
+#[test]
+fn test_relay_policy_banned() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    b.set_relay_policy(RelayPolicy {
+        banned: [from.clone()].into(),
+        allowed: [from.clone()].into(),
+        ..RelayPolicy::default()
+    });
+
+    assert!(a
+        .chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .is_ok());
+
+    // Incoming transactions are refused as well
+    assert_eq!(b.sync_mempool(&mut a), 0);
+    assert!(b.chain().get_pending_transactions().is_empty());
+}
+
+#[test]
+fn test_relay_policy_allowed() {
+    let (mut a, from, to) = setup_node();
+    let (mut b, _, _) = setup_node();
+
+    let policy = RelayPolicy {
+        min_fee: 5.0,
+        max_size: Some(1),
+        allowed: [from.clone()].into(),
+        ..RelayPolicy::default()
+    };
+
+    a.set_relay_policy(policy.clone());
+    b.set_relay_policy(policy);
+
+    // Only the allowed sender bypasses the fee floor and the size limit
+    assert!(a
+        .chain_mut()
+        .add_transaction(from.clone(), to.clone(), 10.0, &sender())
+        .is_ok());
+    assert!(a
+        .chain_mut()
+        .add_transaction(to, from.clone(), 10.0, &receiver())
+        .is_ok());
+
+    assert_eq!(b.sync_mempool(&mut a), 1);
+    assert_eq!(b.chain().get_pending_transactions()[0].from, from);
+}
+
+#[test]
+fn test_bootstrap() {
+    let (mut a, from, to) = setup_node();
+    let (b, _, _) = setup_node();
+    let b = Rc::new(RefCell::new(b));
+
+    assert!(b
+        .borrow_mut()
+        .chain_mut()
+        .add_transaction(from.clone(), to, 10.0, &sender())
+        .is_ok());
+
+    let path = std::env::temp_dir().join(format!("peers-{}.json", std::process::id()));
+    let config = BootstrapConfig {
+        peers: vec!["b:8000".to_string(), "down:8000".to_string()],
+        peers_file: Some(path.clone()),
+        initial_backoff_ms: 0,
+        ..BootstrapConfig::default()
+    };
+    let mut dialer = FlakyDialer {
+        nodes: HashMap::from([("b:8000".to_string(), (b.clone(), 2))]),
+    };
+
+    let peers = a.bootstrap(&config, &mut dialer);
+
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].0, "b:8000");
+    assert_eq!(a.chain().get_pending_transactions().len(), 1);
+    assert_eq!(load_known_peers(&path), vec!["b:8000".to_string()]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_bootstrap_gives_up() {
+    let (mut a, _, _) = setup_node();
+    let (b, _, _) = setup_node();
+
+    let config = BootstrapConfig {
+        peers: vec!["b:8000".to_string()],
+        max_attempts: 2,
+        initial_backoff_ms: 0,
+        ..BootstrapConfig::default()
+    };
+    let mut dialer = FlakyDialer {
+        nodes: HashMap::from([("b:8000".to_string(), (Rc::new(RefCell::new(b)), 2))]),
+    };
+
+    assert!(a.bootstrap(&config, &mut dialer).is_empty());
+}
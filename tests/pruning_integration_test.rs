@@ -0,0 +1,164 @@
+mod common;
+
+use blockchain::{Chain, ChainConfig, ConsensusRules, PruningPolicy};
+
+/// Setup a new blockchain with a pruning policy.
+fn setup(pruning: PruningPolicy) -> Chain {
+    Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            pruning,
+            ..ChainConfig::default()
+        },
+    )
+}
+
+#[test]
+fn test_keep_all() {
+    let mut chain = setup(PruningPolicy::KeepAll);
+
+    for _ in 0..3 {
+        chain.generate_new_block().unwrap();
+    }
+
+    let stats = chain.storage_stats();
+
+    assert_eq!(stats.blocks, 4);
+    assert_eq!(stats.pruned_blocks, 0);
+    assert_eq!(stats.stored_transactions, 4);
+}
+
+#[test]
+fn test_keep_last() {
+    let mut chain = setup(PruningPolicy::KeepLast { blocks: 2 });
+
+    for _ in 0..4 {
+        chain.generate_new_block().unwrap();
+    }
+
+    let stats = chain.storage_stats();
+
+    assert_eq!(stats.blocks, 5);
+    assert_eq!(stats.pruned_blocks, 3);
+    assert_eq!(stats.stored_transactions, 2);
+    assert!(chain.get_all_blocks()[2].is_pruned());
+    assert!(!chain.get_all_blocks()[3].is_pruned());
+}
+
+#[test]
+fn test_snapshots() {
+    let mut chain = setup(PruningPolicy::Snapshots { interval: 2 });
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&address, 20.0);
+
+    for _ in 0..5 {
+        chain.generate_new_block().unwrap();
+    }
+
+    let stats = chain.storage_stats();
+    let snapshots = chain.get_snapshots();
+
+    assert_eq!(stats.snapshots, 3);
+    assert_eq!(snapshots[2].height, 4);
+    assert_eq!(snapshots[2].block_hash, chain.get_all_blocks()[4].hash());
+    assert_eq!(snapshots[2].balances.get(&address), Some(&20.0));
+    assert_eq!(stats.pruned_blocks, 5);
+    assert!(!chain.get_all_blocks()[5].is_pruned());
+}
+
+#[test]
+fn test_snapshots_exclude_pending_transactions() {
+    let mut chain = Chain::with_config(
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig {
+            pruning: PruningPolicy::Snapshots { interval: 1 },
+            consensus: ConsensusRules {
+                max_transactions_per_block: Some(2),
+                ..ConsensusRules::default()
+            },
+            ..ChainConfig::default()
+        },
+    );
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&from, 20.0);
+
+    chain
+        .add_transaction(from.to_owned(), to.to_owned(), 5.0, &keypair)
+        .unwrap();
+    chain
+        .add_transaction(from.to_owned(), to.to_owned(), 3.0, &keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    // The second transaction does not fit in the block and stays pending
+    let snapshot = chain.get_snapshots().last().unwrap();
+
+    assert_eq!(chain.get_pending_transactions().len(), 1);
+    assert_eq!(snapshot.height, 1);
+    assert_eq!(snapshot.balances.get(&from), Some(&14.5));
+    assert_eq!(snapshot.balances.get(&to), Some(&5.0));
+}
+
+#[test]
+fn test_storage_stats() {
+    let mut chain = setup(PruningPolicy::KeepAll);
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
+
+    chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+
+    let stats = chain.storage_stats();
+
+    assert_eq!(stats.blocks, 2);
+    assert_eq!(stats.pending_transactions, 1);
+    assert_eq!(stats.wallets, 2);
+}
+
+#[test]
+fn test_snapshot_state_and_prune_blocks() {
+    let mut chain = setup(PruningPolicy::KeepAll);
+    let (from, from_keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let (to, to_keypair) = common::create_wallet(&mut chain, "r@mail.com");
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
+
+    chain
+        .add_transaction(from.clone(), to.clone(), 10.0, &from_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain
+        .add_transaction(to.clone(), from.clone(), 5.0, &to_keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+
+    let snapshot = chain.snapshot_state(1).unwrap();
+
+    // Wallets without funds are left out of the snapshot
+    assert_eq!(snapshot.height, 1);
+    assert_eq!(snapshot.balances.get(&from), Some(&20.0));
+    assert_eq!(snapshot.balances.get(&to), None);
+    assert_eq!(chain.get_snapshots().len(), 1);
+    assert!(chain.snapshot_state(9).is_none());
+
+    assert_eq!(chain.prune_blocks_before(2), 2);
+    assert_eq!(chain.prune_blocks_before(2), 0);
+    assert!(chain.get_all_blocks()[1].is_pruned());
+    assert!(!chain.get_all_blocks()[2].is_pruned());
+    assert_eq!(chain.validate_range(0, 3), Ok(4));
+
+    // The state after the snapshot is still computed, the state before it no longer is
+    assert_eq!(chain.balance_at(&to, 2), Some(10.0));
+    assert_eq!(
+        chain.balance_at(&from, 3),
+        chain.get_wallet_balance(from.clone())
+    );
+    assert!(chain.state_at(0).is_none());
+    assert!(chain.snapshot_state(0).is_none());
+}
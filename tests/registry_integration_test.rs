@@ -0,0 +1,142 @@
+use std::{env, fs};
+
+use blockchain::{ChainConfig, ChainRegistry};
+
+#[test]
+fn test_create_chain() {
+    let mut registry = ChainRegistry::new();
+
+    let result = registry.create_chain("classroom".to_string(), 1.0, 100.0, 0.1);
+
+    assert!(result);
+    assert!(registry.get_chain("classroom").is_some());
+}
+
+#[test]
+fn test_create_chain_duplicate_id() {
+    let mut registry = ChainRegistry::new();
+    registry.create_chain("classroom".to_string(), 1.0, 100.0, 0.1);
+
+    let result = registry.create_chain("classroom".to_string(), 1.0, 100.0, 0.1);
+
+    assert!(!result);
+    assert_eq!(registry.get_chain_ids().len(), 1);
+}
+
+#[test]
+fn test_create_chain_invalid_id() {
+    let mut registry = ChainRegistry::new();
+
+    let result = registry.create_chain("../classroom".to_string(), 1.0, 100.0, 0.1);
+
+    assert!(!result);
+    assert!(registry.get_chain_ids().is_empty());
+}
+
+#[test]
+fn test_chains_are_independent() {
+    let mut registry = ChainRegistry::new();
+    registry.create_chain("a".to_string(), 1.0, 100.0, 0.1);
+    registry.create_chain("b".to_string(), 1.0, 100.0, 0.1);
+
+    let chain = registry.get_chain_mut("a").unwrap();
+    chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    assert_eq!(registry.get_chain("a").unwrap().get_wallets_count(), 1);
+    assert_eq!(registry.get_chain("b").unwrap().get_wallets_count(), 0);
+}
+
+#[test]
+fn test_remove_chain() {
+    let mut registry = ChainRegistry::new();
+    registry.create_chain("classroom".to_string(), 1.0, 100.0, 0.1);
+
+    let result = registry.remove_chain("classroom");
+
+    assert!(result.is_some());
+    assert!(registry.get_chain("classroom").is_none());
+}
+
+#[test]
+fn test_remove_chain_not_found() {
+    let mut registry = ChainRegistry::new();
+
+    let result = registry.remove_chain("classroom");
+
+    assert!(result.is_none());
+}
+
+#[test]
+fn test_persist_and_load() {
+    let dir = env::temp_dir().join(format!("blockchain-registry-{}", std::process::id()));
+
+    let mut registry = ChainRegistry::new();
+    registry.create_chain("a".to_string(), 1.0, 100.0, 0.1);
+    registry.create_chain("b".to_string(), 1.0, 100.0, 0.1);
+
+    let address = registry
+        .get_chain_mut("a")
+        .unwrap()
+        .create_wallet("s@mail.com".to_string())
+        .unwrap();
+
+    registry.persist(&dir).unwrap();
+
+    let loaded = ChainRegistry::load(&dir).unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        loaded.get_chain_ids(),
+        vec!["a".to_string(), "b".to_string()]
+    );
+    assert!(loaded
+        .get_chain("a")
+        .unwrap()
+        .get_wallet_balance(address)
+        .is_some());
+}
+
+#[test]
+fn test_persist_removed_chain() {
+    let dir = env::temp_dir().join(format!(
+        "blockchain-registry-removed-{}",
+        std::process::id()
+    ));
+
+    let mut registry = ChainRegistry::new();
+    registry.create_chain("a".to_string(), 1.0, 100.0, 0.1);
+    registry.create_chain("b".to_string(), 1.0, 100.0, 0.1);
+    registry.persist(&dir).unwrap();
+
+    fs::write(dir.join("notes.txt"), "kept").unwrap();
+
+    // The file of the removed chain is deleted on the next persist
+    registry.remove_chain("b");
+    registry.persist(&dir).unwrap();
+
+    let loaded = ChainRegistry::load(&dir).unwrap();
+    let notes = dir.join("notes.txt").exists();
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded.get_chain_ids(), vec!["a".to_string()]);
+    assert!(notes);
+}
+
+#[test]
+fn test_create_chain_sets_chain_id() {
+    let mut registry = ChainRegistry::new();
+
+    registry.create_chain_with_config(
+        "classroom".to_string(),
+        1.0,
+        100.0,
+        0.1,
+        ChainConfig::default(),
+    );
+
+    let chain = registry.get_chain("classroom").unwrap();
+
+    assert_eq!(chain.get_config().chain_id, "classroom");
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use blockchain::{run_scenario, simulate, ScenarioError, ScenarioStep, SimulationConfig};
+
+#[test]
+fn test_simulate() {
+    let config = SimulationConfig {
+        blocks: 30,
+        runs: 2,
+        ..SimulationConfig::default()
+    };
+    let report = simulate(&config);
+
+    assert_eq!(report.runs, 2);
+    assert_eq!(report.mined_blocks, 60);
+    assert_eq!(report.propagation.samples, 60 * 7);
+    assert!(report.propagation.p50 >= 100);
+    assert!(report.propagation.p99 <= report.propagation.max);
+    assert_eq!(
+        report.stale_rate,
+        report.stale_blocks as f64 / report.mined_blocks as f64
+    );
+
+    // The same settings always produce the same report
+    assert_eq!(simulate(&config), report);
+}
+
+#[test]
+fn test_simulate_latency() {
+    let config = SimulationConfig {
+        blocks: 50,
+        runs: 2,
+        block_interval: Duration::from_secs(2),
+        ..SimulationConfig::default()
+    };
+    let instant = SimulationConfig {
+        min_latency: Duration::ZERO,
+        max_latency: Duration::ZERO,
+        ..config.clone()
+    };
+
+    let report = simulate(&config);
+
+    // Blocks found faster than they propagate cause forks and reorganizations
+    assert!(report.forks > 0);
+    assert!(report.stale_blocks > 0);
+    assert!(!report.reorg_depths.is_empty());
+
+    let report = simulate(&instant);
+
+    assert_eq!(report.stale_blocks, 0);
+    assert_eq!(report.propagation.max, 0);
+    assert!(report.reorg_depths.is_empty());
+}
+
+#[test]
+fn test_simulate_single_node() {
+    let report = simulate(&SimulationConfig {
+        nodes: 1,
+        blocks: 5,
+        ..SimulationConfig::default()
+    });
+
+    assert_eq!(report.mined_blocks, 5);
+    assert_eq!(report.stale_blocks, 0);
+    assert_eq!(report.propagation.samples, 0);
+}
+
+#[test]
+fn test_partition_heals() {
+    let config = SimulationConfig::default();
+    let split = vec![
+        ScenarioStep::Partition(vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]),
+        ScenarioStep::Mine(20),
+    ];
+
+    // The groups extend their own branches while partitioned
+    assert!(matches!(
+        run_scenario(&config, &[split.clone(), vec![ScenarioStep::AssertConvergence { within: 0 }]].concat()),
+        Err(ScenarioError::Diverged { step: 2, tips }) if tips >= 2
+    ));
+
+    let steps = [
+        split,
+        vec![
+            ScenarioStep::Heal,
+            ScenarioStep::AssertConvergence { within: 3 },
+            ScenarioStep::Mine(5),
+            ScenarioStep::AssertConvergence { within: 0 },
+        ],
+    ]
+    .concat();
+    let report = run_scenario(&config, &steps).unwrap();
+
+    // The nodes of the lighter branch reorganize to the heavier one once healed
+    assert_eq!(report.convergence.len(), 2);
+    assert!(report.convergence[0] <= 3);
+    assert!(report.mined_blocks >= 25);
+    assert!(report.height < report.mined_blocks);
+    assert!(!report.reorg_depths.is_empty());
+
+    // The same settings always produce the same report
+    assert_eq!(run_scenario(&config, &steps), Ok(report));
+}
+
+#[test]
+fn test_partition_unknown_node() {
+    assert_eq!(
+        run_scenario(
+            &SimulationConfig::default(),
+            &[ScenarioStep::Partition(vec![vec![0], vec![8]])]
+        ),
+        Err(ScenarioError::UnknownNode(8))
+    );
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use std::{env, fs, io, path::PathBuf};
+
+use blockchain::{Chain, FileStorage, IntegrityError};
+
+/// Setup a persisted blockchain with three blocks.
+///
+/// # Arguments
+///
+/// - `name` - The name of the storage directory.
+///
+/// # Returns
+///
+/// The storage directory, the storage, and the blockchain.
+fn setup_storage(name: &str) -> (PathBuf, FileStorage, Chain) {
+    let dir = env::temp_dir().join(format!("blockchain-{}-{}", name, std::process::id()));
+    let storage = FileStorage::open(&dir).unwrap();
+    let mut chain = Chain::new(1.0, 100.0, 0.1);
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 100.0);
+    chain.generate_new_block().unwrap();
+    chain.add_transaction(from, to, 10.0, &keypair).unwrap();
+    chain.generate_new_block().unwrap();
+
+    storage.save(&chain).unwrap();
+
+    (dir, storage, chain)
+}
+
+/// Get the integrity error wrapped in an I/O error.
+///
+/// # Arguments
+///
+/// - `result` - The result of the verification.
+///
+/// # Returns
+///
+/// The integrity error, if any.
+fn integrity_error(result: io::Result<()>) -> Option<IntegrityError> {
+    result
+        .err()?
+        .get_ref()?
+        .downcast_ref::<IntegrityError>()
+        .cloned()
+}
+
+#[test]
+fn test_save_and_load() {
+    let (dir, storage, chain) = setup_storage("storage-load");
+
+    assert!(storage.verify_integrity().is_ok());
+
+    let loaded = storage.load().unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded.get_all_blocks().len(), 3);
+    assert_eq!(loaded.get_last_hash(), chain.get_last_hash());
+    assert_eq!(loaded.get_wallets_count(), 2);
+    assert_eq!(loaded.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_verify_integrity_detects_bit_rot() {
+    let (dir, storage, _) = setup_storage("storage-rot");
+    let path = dir.join("blocks.jsonl");
+    let mut content = fs::read(&path).unwrap();
+
+    // Flip a bit in the second block
+    let second = content.iter().position(|byte| *byte == b'\n').unwrap() + 10;
+    content[second] ^= 1;
+    fs::write(&path, content).unwrap();
+
+    let block = integrity_error(storage.verify_integrity());
+    let load = storage.load().map(|_| ()).unwrap_err().kind();
+
+    // A corrupted state is detected too
+    let path = dir.join("state.json");
+    let mut content = fs::read(&path).unwrap();
+    content[5] ^= 1;
+    fs::write(&path, content).unwrap();
+
+    let state = integrity_error(storage.verify_integrity());
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(block, Some(IntegrityError::CorruptBlock(1)));
+    assert_eq!(load, io::ErrorKind::InvalidData);
+    assert_eq!(state, Some(IntegrityError::CorruptState));
+}
+
+#[test]
+fn test_verify_integrity_detects_truncation() {
+    let (dir, storage, _) = setup_storage("storage-truncated");
+    let path = dir.join("blocks.jsonl");
+    let content = fs::read(&path).unwrap();
+    let lines: Vec<&[u8]> = content.split_inclusive(|byte| *byte == b'\n').collect();
+
+    // The last block is missing
+    fs::write(&path, lines[..2].concat()).unwrap();
+
+    let missing = integrity_error(storage.verify_integrity());
+
+    // The last block is cut in the middle
+    let mut truncated = lines[..2].concat();
+    truncated.extend_from_slice(&lines[2][..lines[2].len() / 2]);
+    fs::write(&path, truncated).unwrap();
+
+    let cut = integrity_error(storage.verify_integrity());
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        missing,
+        Some(IntegrityError::LengthMismatch {
+            expected: 3,
+            found: 2
+        })
+    );
+    assert_eq!(cut, Some(IntegrityError::CorruptBlock(2)));
+}
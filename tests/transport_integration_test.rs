@@ -0,0 +1,89 @@
+#![cfg(feature = "net")]
+
+mod common;
+
+use std::{collections::HashSet, net::TcpListener, thread};
+
+use blockchain::{Dialer, Keypair, SecureChannel, TcpDialer};
+
+use crate::common::{receiver, sender, setup_node};
+
+#[test]
+fn test_sync_mempool_over_secure_channel() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let client = Keypair::generate();
+    let server = Keypair::generate();
+    let (client_key, server_key) = (client.public_key(), server.public_key());
+
+    let handle = thread::spawn(move || {
+        let (mut node, from, to) = setup_node();
+
+        node.chain_mut()
+            .add_transaction(to, from, 5.0, &receiver())
+            .unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut channel = SecureChannel::accept(stream, &server).unwrap();
+
+        node.set_identity(server);
+
+        assert_eq!(channel.remote_id(), client_key);
+
+        node.serve(&mut channel).unwrap();
+        node.chain().get_pending_transactions().len()
+    });
+
+    let (mut node, from, to) = setup_node();
+
+    node.chain_mut()
+        .add_transaction(from, to, 10.0, &sender())
+        .unwrap();
+
+    let mut dialer = TcpDialer {
+        trusted: Some(HashSet::from([server_key.clone()])),
+        ..TcpDialer::new(client.clone())
+    };
+
+    node.set_identity(client);
+
+    let mut channel = dialer.dial(&address).unwrap();
+
+    assert_eq!(node.sync_mempool(&mut channel), 1);
+    assert_eq!(node.chain().get_pending_transactions().len(), 2);
+    assert_eq!(node.peer_info()[0].id, server_key);
+
+    drop(channel);
+
+    assert_eq!(handle.join().unwrap(), 2);
+}
+
+#[test]
+fn test_dial_untrusted_node() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+
+        SecureChannel::accept(stream, &Keypair::generate()).is_ok()
+    });
+
+    let mut dialer = TcpDialer {
+        trusted: Some(HashSet::from([Keypair::generate().public_key()])),
+        ..TcpDialer::new(Keypair::generate())
+    };
+
+    assert!(dialer.dial(&address).is_none());
+    assert!(handle.join().unwrap());
+}
+
+#[test]
+fn test_dial_unreachable_node() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    drop(listener);
+
+    assert!(TcpDialer::new(Keypair::generate()).dial(&address).is_none());
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use blockchain::{Chain, PaymentEvent};
+
+use crate::common::setup;
+
+/// Watch for a payment and collect the events.
+fn watch(
+    chain: &mut Chain,
+    address: &str,
+    amount: f64,
+    min_confirmations: usize,
+) -> (usize, Arc<Mutex<Vec<PaymentEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+
+    let id = chain.watch_payment(address, amount, min_confirmations, move |event| {
+        sink.lock().unwrap().push(event.clone())
+    });
+
+    (id, events)
+}
+
+#[test]
+fn test_watch_payment() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
+
+    let (id, events) = watch(&mut chain, &to, 10.0, 3);
+
+    chain
+        .add_transaction(from, to.clone(), 10.0, &keypair)
+        .unwrap();
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    assert!(events.lock().unwrap().is_empty());
+
+    chain.generate_new_block().unwrap();
+    chain.generate_new_block().unwrap();
+
+    let events = events.lock().unwrap();
+
+    assert_eq!(events.len(), 1);
+
+    match &events[0] {
+        PaymentEvent::Confirmed {
+            watch,
+            transaction,
+            height,
+            confirmations,
+        } => {
+            assert_eq!(*watch, id);
+            assert_eq!(transaction.to, to);
+            assert_eq!(*height, 2);
+            assert_eq!(*confirmations, 3);
+        }
+        event => panic!("unexpected event {:?}", event),
+    }
+}
+
+#[test]
+fn test_watch_payment_amount_too_low() {
+    let mut chain = setup();
+    let (from, keypair) = common::create_wallet(&mut chain, "s@mail.com");
+    let to = chain.create_wallet("r@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&from, 20.0);
+    chain.generate_new_block().unwrap();
+
+    let (_, events) = watch(&mut chain, &to, 10.0, 1);
+
+    chain.add_transaction(from, to, 5.0, &keypair).unwrap();
+    chain.generate_new_block().unwrap();
+
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_watch_payment_before_watch() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    chain.credit_wallet(&address, 20.0);
+    chain.generate_new_block().unwrap();
+
+    let (_, events) = watch(&mut chain, &address, 10.0, 1);
+
+    chain.generate_new_block().unwrap();
+
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_unwatch_payment() {
+    let mut chain = setup();
+    let address = chain.create_wallet("s@mail.com".to_string()).unwrap();
+
+    let (id, events) = watch(&mut chain, &address, 10.0, 1);
+
+    assert!(chain.unwatch_payment(id));
+    assert!(!chain.unwatch_payment(id));
+
+    chain.credit_wallet(&address, 20.0);
+    chain.generate_new_block().unwrap();
+
+    assert!(events.lock().unwrap().is_empty());
+}
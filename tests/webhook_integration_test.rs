@@ -0,0 +1,154 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+use blockchain::{Chain, ChainEvent, Webhook};
+
+use crate::common::setup;
+
+/// A webhook whose target can be taken down, counting the delivery attempts.
+#[derive(Debug, Default)]
+struct FlakyWebhook {
+    /// Whether the target is down.
+    down: AtomicBool,
+
+    /// The amount of delivery attempts.
+    attempts: AtomicUsize,
+
+    /// The amount of delivered events.
+    delivered: AtomicUsize,
+}
+
+impl Webhook for FlakyWebhook {
+    fn deliver(&self, _event: &ChainEvent) -> Result<(), String> {
+        self.attempts.fetch_add(1, Ordering::SeqCst);
+
+        if self.down.load(Ordering::SeqCst) {
+            return Err("connection refused".to_string());
+        }
+
+        self.delivered.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_webhook_dead_letters() {
+    let mut chain = setup();
+    let webhook = Arc::new(FlakyWebhook::default());
+    chain.add_webhook("audit", webhook.clone(), 3);
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(webhook.delivered.load(Ordering::SeqCst), 1);
+    assert!(chain.failed_notifications().is_empty());
+
+    // The target goes down, every attempt of the next event fails and the later events are
+    // queued behind it
+    webhook.down.store(true, Ordering::SeqCst);
+    chain.generate_new_block().unwrap();
+    chain.update_difficulty(2.0).unwrap();
+
+    let failed = chain.failed_notifications();
+
+    assert_eq!(failed.len(), 2);
+    assert_eq!(webhook.attempts.load(Ordering::SeqCst), 1 + 3);
+    assert_eq!(failed[0].webhook, "audit");
+    assert_eq!(failed[0].attempts, 3);
+    assert_eq!(failed[0].error, "connection refused");
+    assert!(matches!(
+        failed[0].event,
+        ChainEvent::BlockMined { height: 2, .. }
+    ));
+    assert_eq!(failed[1].attempts, 0);
+    assert!(matches!(
+        failed[1].event,
+        ChainEvent::DifficultyChanged { .. }
+    ));
+
+    // Re-driving stops at the first failure while the target is still down
+    assert_eq!(chain.redrive_notifications("audit"), Some(0));
+    assert_eq!(chain.failed_notifications()[0].attempts, 6);
+    assert_eq!(chain.failed_notifications()[1].attempts, 0);
+
+    // The events are queued until the backlog is re-driven, even once the target is back up
+    webhook.down.store(false, Ordering::SeqCst);
+    chain.update_difficulty(3.0).unwrap();
+
+    assert_eq!(chain.failed_notifications().len(), 3);
+    assert_eq!(webhook.delivered.load(Ordering::SeqCst), 1);
+
+    assert_eq!(chain.redrive_notifications("audit"), Some(3));
+    assert!(chain.failed_notifications().is_empty());
+    assert_eq!(chain.redrive_notifications("unknown"), None);
+
+    chain.update_difficulty(4.0).unwrap();
+
+    assert_eq!(webhook.delivered.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn test_failed_notifications_persisted() {
+    let mut chain = setup();
+    let webhook = Arc::new(FlakyWebhook::default());
+
+    webhook.down.store(true, Ordering::SeqCst);
+    chain.add_webhook("audit", webhook.clone(), 1);
+    chain.generate_new_block().unwrap();
+
+    // The webhooks are registered again under their names once the blockchain is loaded, in
+    // any order
+    let mut loaded: Chain = serde_json::from_str(&serde_json::to_string(&chain).unwrap()).unwrap();
+    let restarted = Arc::new(FlakyWebhook::default());
+
+    loaded.add_webhook("metrics", Arc::new(FlakyWebhook::default()), 1);
+    loaded.add_webhook("audit", restarted.clone(), 1);
+
+    assert_eq!(loaded.failed_notifications().len(), 1);
+    assert_eq!(
+        loaded.failed_notifications()[0].id,
+        chain.failed_notifications()[0].id
+    );
+
+    loaded.update_difficulty(2.0).unwrap();
+    loaded.update_difficulty(3.0).unwrap();
+
+    // The events following the persisted notification are re-driven after it
+    assert_eq!(loaded.failed_notifications().len(), 3);
+    assert_eq!(restarted.delivered.load(Ordering::SeqCst), 0);
+    assert_eq!(loaded.redrive_notifications("audit"), Some(3));
+    assert!(loaded.failed_notifications().is_empty());
+    assert_eq!(restarted.delivered.load(Ordering::SeqCst), 3);
+
+    // The identifiers of the new failed notifications follow the persisted ones
+    restarted.down.store(true, Ordering::SeqCst);
+    loaded.update_difficulty(4.0).unwrap();
+
+    assert_eq!(
+        loaded.failed_notifications()[0].id,
+        chain.failed_notifications()[0].id + 3
+    );
+}
+
+#[test]
+fn test_remove_webhook() {
+    let mut chain = setup();
+    let webhook = Arc::new(FlakyWebhook::default());
+    chain.add_webhook("audit", webhook.clone(), 1);
+    webhook.down.store(true, Ordering::SeqCst);
+    chain.generate_new_block().unwrap();
+
+    assert!(chain.remove_webhook("audit"));
+    assert!(!chain.remove_webhook("audit"));
+
+    // The failed notifications outlive the webhook until they are discarded
+    chain.generate_new_block().unwrap();
+
+    assert_eq!(chain.failed_notifications().len(), 1);
+    assert_eq!(chain.redrive_notifications("audit"), None);
+    assert_eq!(chain.discard_failed_notifications("audit"), 1);
+    assert!(chain.failed_notifications().is_empty());
+}